@@ -0,0 +1,399 @@
+//! Journals of the day's sales for accounting software
+//!
+//! The sales of each day are booked as one journal entry: the revenue of
+//! each tax group, the service charge, the tax owed and the tips owed to
+//! staff are credited, and the money taken is debited to a single account
+//! such as "Undeposited Funds", so every entry balances. Refunds simply take
+//! the amounts down.
+//!
+//! The entries are written for import into QuickBooks Desktop (IIF),
+//! QuickBooks Online (CSV) or Xero (manual journal CSV), with the accounts
+//! set up in [`Accounts`].
+use chrono::{Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::archive;
+use crate::data::{csv_escape, Error};
+use crate::query::{self, Range};
+use crate::sale::Sale;
+use crate::store;
+use crate::tax::TaxGroup;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    QuickBooksIif,
+    QuickBooksCsv,
+    XeroCsv,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [
+        Format::QuickBooksIif,
+        Format::QuickBooksCsv,
+        Format::XeroCsv,
+    ];
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::QuickBooksIif => "iif",
+            Format::QuickBooksCsv | Format::XeroCsv => "csv",
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Format::QuickBooksIif => "QuickBooks Desktop (IIF)",
+                Format::QuickBooksCsv => "QuickBooks Online (CSV)",
+                Format::XeroCsv => "Xero (CSV)",
+            }
+        )
+    }
+}
+
+/// An account a journal line is booked to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Account {
+    /// Where the money taken goes until it is deposited.
+    Payments,
+    Revenue(TaxGroup),
+    ServiceCharge,
+    DeliveryFees,
+    /// Sales tax owed.
+    Tax,
+    /// Gratuities owed to staff.
+    Tips,
+}
+
+impl Account {
+    pub const ALL: [Account; 9] = [
+        Account::Payments,
+        Account::Revenue(TaxGroup::Food),
+        Account::Revenue(TaxGroup::Alcohol),
+        Account::Revenue(TaxGroup::NonTaxable),
+        Account::Revenue(TaxGroup::Other),
+        Account::ServiceCharge,
+        Account::DeliveryFees,
+        Account::Tax,
+        Account::Tips,
+    ];
+}
+
+impl fmt::Display for Account {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Account::Payments => write!(f, "Payments"),
+            Account::Revenue(group) => write!(f, "Sales: {group}"),
+            Account::ServiceCharge => write!(f, "Service charges"),
+            Account::DeliveryFees => write!(f, "Delivery fees"),
+            Account::Tax => write!(f, "Tax payable"),
+            Account::Tips => write!(f, "Tips payable"),
+        }
+    }
+}
+
+/// The accounts to book to: names for QuickBooks, or codes for Xero.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Accounts {
+    pub payments: String,
+    pub food: String,
+    pub alcohol: String,
+    pub non_taxable: String,
+    pub other: String,
+    pub service_charge: String,
+    pub delivery_fees: String,
+    pub tax: String,
+    pub tips: String,
+    /// The Xero tax rate of every line, as the tax is booked on a line of
+    /// its own.
+    pub xero_tax_rate: String,
+}
+
+impl Default for Accounts {
+    fn default() -> Self {
+        Self {
+            payments: "Undeposited Funds".to_string(),
+            food: "Food Sales".to_string(),
+            alcohol: "Alcohol Sales".to_string(),
+            non_taxable: "Non-taxable Sales".to_string(),
+            other: "Other Sales".to_string(),
+            service_charge: "Service Charges".to_string(),
+            delivery_fees: "Delivery Fees".to_string(),
+            tax: "Sales Tax Payable".to_string(),
+            tips: "Tips Payable".to_string(),
+            xero_tax_rate: "Tax Exempt".to_string(),
+        }
+    }
+}
+
+impl Accounts {
+    pub fn get(&self, account: Account) -> &str {
+        match account {
+            Account::Payments => &self.payments,
+            Account::Revenue(TaxGroup::Food) => &self.food,
+            Account::Revenue(TaxGroup::Alcohol) => &self.alcohol,
+            Account::Revenue(TaxGroup::NonTaxable) => &self.non_taxable,
+            Account::Revenue(TaxGroup::Other) => &self.other,
+            Account::ServiceCharge => &self.service_charge,
+            Account::DeliveryFees => &self.delivery_fees,
+            Account::Tax => &self.tax,
+            Account::Tips => &self.tips,
+        }
+    }
+
+    pub fn get_mut(&mut self, account: Account) -> &mut String {
+        match account {
+            Account::Payments => &mut self.payments,
+            Account::Revenue(TaxGroup::Food) => &mut self.food,
+            Account::Revenue(TaxGroup::Alcohol) => &mut self.alcohol,
+            Account::Revenue(TaxGroup::NonTaxable) => &mut self.non_taxable,
+            Account::Revenue(TaxGroup::Other) => &mut self.other,
+            Account::ServiceCharge => &mut self.service_charge,
+            Account::DeliveryFees => &mut self.delivery_fees,
+            Account::Tax => &mut self.tax,
+            Account::Tips => &mut self.tips,
+        }
+    }
+}
+
+/// The entry booking the sales of one day.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Journal {
+    pub date: NaiveDate,
+    /// The amount of each account in cents, positive for a debit and
+    /// negative for a credit. They add up to zero.
+    pub lines: BTreeMap<Account, i64>,
+}
+
+impl Journal {
+    fn add(&mut self, sale: &Sale) {
+        let totals = sale.totals();
+        let mut credit = |account, amount: f32| {
+            let cents = (f64::from(amount) * 100.0).round() as i64;
+            *self.lines.entry(account).or_default() -= cents;
+            *self.lines.entry(Account::Payments).or_default() += cents;
+        };
+
+        for item in &sale.items {
+            credit(Account::Revenue(item.tax_group), item.total());
+        }
+        credit(Account::ServiceCharge, totals.service_charge);
+        credit(Account::DeliveryFees, totals.delivery_fee);
+        credit(Account::Tax, totals.tax);
+        credit(Account::Tips, totals.gratuity);
+    }
+
+    /// The lines to book, leaving out accounts nothing was booked to.
+    pub fn booked(&self) -> impl Iterator<Item = (Account, i64)> + '_ {
+        self.lines
+            .iter()
+            .filter(|(_, cents)| **cents != 0)
+            .map(|(account, cents)| (*account, *cents))
+    }
+}
+
+/// The journal of each day in `range` with sales, from the store in
+/// `format` and the archive.
+pub fn journals(
+    format: store::Format,
+    range: Range,
+) -> Result<Vec<Journal>, Error> {
+    let mut days: BTreeMap<NaiveDate, Journal> = BTreeMap::new();
+    let mut visit = |_, sale: Sale| {
+        let date = sale.created_at.with_timezone(&Local).date_naive();
+        days.entry(date)
+            .or_insert_with(|| Journal {
+                date,
+                ..Journal::default()
+            })
+            .add(&sale);
+    };
+    query::scan(format, range, &mut visit)?;
+    archive::scan(range, &mut visit)?;
+
+    Ok(days.into_values().collect())
+}
+
+/// `journals` in `format`, booked to `accounts`.
+pub fn write(
+    journals: &[Journal],
+    format: Format,
+    accounts: &Accounts,
+) -> String {
+    let mut out = String::new();
+    match format {
+        Format::QuickBooksIif => {
+            out.push_str("!TRNS\tTRNSTYPE\tDATE\tACCNT\tAMOUNT\tMEMO\n");
+            out.push_str("!SPL\tTRNSTYPE\tDATE\tACCNT\tAMOUNT\tMEMO\n");
+            out.push_str("!ENDTRNS\n");
+            for journal in journals {
+                let date = journal.date.format("%m/%d/%Y");
+                let memo = memo(journal);
+                for (i, (account, cents)) in journal.booked().enumerate() {
+                    out.push_str(&format!(
+                        "{}\tGENERAL JOURNAL\t{date}\t{}\t{}\t{memo}\n",
+                        if i == 0 { "TRNS" } else { "SPL" },
+                        iif_escape(accounts.get(account)),
+                        amount(cents),
+                    ));
+                }
+                if journal.booked().next().is_some() {
+                    out.push_str("ENDTRNS\n");
+                }
+            }
+        }
+        Format::QuickBooksCsv => {
+            out.push_str(
+                "Journal No,Journal Date,Account Name,Debits,Credits,\
+                 Description\n",
+            );
+            for journal in journals {
+                let number = journal.date.format("POS-%Y%m%d");
+                let date = journal.date.format("%m/%d/%Y");
+                let memo = csv_escape(&memo(journal));
+                for (account, cents) in journal.booked() {
+                    let (debit, credit) = if cents > 0 {
+                        (amount(cents), String::new())
+                    } else {
+                        (String::new(), amount(-cents))
+                    };
+                    out.push_str(&format!(
+                        "{number},{date},{},{debit},{credit},{memo}\n",
+                        csv_escape(accounts.get(account)),
+                    ));
+                }
+            }
+        }
+        Format::XeroCsv => {
+            out.push_str(
+                "*Narration,*Date,Description,*AccountCode,*TaxRate,\
+                 *Amount\n",
+            );
+            let rate = csv_escape(&accounts.xero_tax_rate);
+            for journal in journals {
+                let date = journal.date.format("%Y-%m-%d");
+                let memo = csv_escape(&memo(journal));
+                for (account, cents) in journal.booked() {
+                    out.push_str(&format!(
+                        "{memo},{date},{},{},{rate},{}\n",
+                        csv_escape(&account.to_string()),
+                        csv_escape(accounts.get(account)),
+                        amount(cents),
+                    ));
+                }
+            }
+        }
+    }
+    out
+}
+
+fn memo(journal: &Journal) -> String {
+    format!("Sales {}", journal.date.format("%Y-%m-%d"))
+}
+
+fn amount(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{sign}{}.{:02}", cents.abs() / 100, cents.abs() % 100)
+}
+
+/// IIF fields can't hold tabs or line breaks.
+fn iif_escape(field: &str) -> String {
+    field.replace(['\t', '\n', '\r'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sale::SaleItem;
+
+    fn day(sales: &[Sale]) -> Journal {
+        let mut journal = Journal {
+            date: NaiveDate::from_ymd_opt(2024, 3, 15).unwrap(),
+            ..Journal::default()
+        };
+        for sale in sales {
+            journal.add(sale);
+        }
+        journal
+    }
+
+    fn sale(items: Vec<SaleItem>, gratuity: Option<f32>) -> Sale {
+        Sale {
+            items,
+            gratuity_amount: gratuity,
+            ..Sale::default()
+        }
+    }
+
+    fn item(price: f32, tax_group: TaxGroup) -> SaleItem {
+        SaleItem {
+            tax_group,
+            ..SaleItem::new("Item", price, 1)
+        }
+    }
+
+    #[test]
+    fn a_day_balances() {
+        let journal = day(&[
+            sale(vec![item(10.0, TaxGroup::Food)], Some(2.0)),
+            sale(vec![item(6.5, TaxGroup::Alcohol)], None),
+        ]);
+
+        assert_eq!(journal.lines.values().sum::<i64>(), 0);
+        assert_eq!(journal.lines[&Account::Revenue(TaxGroup::Food)], -1000);
+        assert_eq!(journal.lines[&Account::Revenue(TaxGroup::Alcohol)], -650);
+        assert_eq!(journal.lines[&Account::Tax], -(80 + 65));
+        assert_eq!(journal.lines[&Account::Tips], -200);
+        assert_eq!(journal.lines[&Account::Payments], 1000 + 650 + 145 + 200);
+    }
+
+    #[test]
+    fn refunds_take_the_amounts_down() {
+        let journal = day(&[
+            sale(vec![item(10.0, TaxGroup::Food)], None),
+            sale(vec![item(-10.0, TaxGroup::Food)], None),
+        ]);
+
+        assert_eq!(journal.booked().count(), 0);
+    }
+
+    #[test]
+    fn iif_starts_each_entry_with_the_payments() {
+        let journal = day(&[sale(vec![item(10.0, TaxGroup::Food)], None)]);
+        let iif =
+            write(&[journal], Format::QuickBooksIif, &Accounts::default());
+
+        assert_eq!(
+            iif.lines().skip(3).collect::<Vec<_>>(),
+            [
+                "TRNS\tGENERAL JOURNAL\t03/15/2024\tUndeposited Funds\t10.80\t\
+                 Sales 2024-03-15",
+                "SPL\tGENERAL JOURNAL\t03/15/2024\tFood Sales\t-10.00\t\
+                 Sales 2024-03-15",
+                "SPL\tGENERAL JOURNAL\t03/15/2024\tSales Tax Payable\t-0.80\t\
+                 Sales 2024-03-15",
+                "ENDTRNS",
+            ]
+        );
+    }
+
+    #[test]
+    fn quickbooks_csv_splits_debits_and_credits() {
+        let journal = day(&[sale(vec![item(10.0, TaxGroup::Food)], None)]);
+        let csv =
+            write(&[journal], Format::QuickBooksCsv, &Accounts::default());
+
+        assert_eq!(
+            csv.lines().nth(2),
+            Some("POS-20240315,03/15/2024,Food Sales,,10.00,Sales 2024-03-15")
+        );
+    }
+}
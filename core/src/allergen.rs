@@ -0,0 +1,76 @@
+//! Allergens carried by catalog products and flagged on kitchen tickets
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Allergen {
+    Gluten,
+    Dairy,
+    Eggs,
+    Peanuts,
+    TreeNuts,
+    Soy,
+    Fish,
+    Shellfish,
+    Sesame,
+}
+
+impl Allergen {
+    pub const ALL: [Allergen; 9] = [
+        Allergen::Gluten,
+        Allergen::Dairy,
+        Allergen::Eggs,
+        Allergen::Peanuts,
+        Allergen::TreeNuts,
+        Allergen::Soy,
+        Allergen::Fish,
+        Allergen::Shellfish,
+        Allergen::Sesame,
+    ];
+
+    /// The name printed on kitchen tickets, which stays in English for the
+    /// kitchen whatever the UI language.
+    pub fn ticket_name(&self) -> &'static str {
+        match self {
+            Allergen::Gluten => "GLUTEN",
+            Allergen::Dairy => "DAIRY",
+            Allergen::Eggs => "EGGS",
+            Allergen::Peanuts => "PEANUTS",
+            Allergen::TreeNuts => "TREE NUTS",
+            Allergen::Soy => "SOY",
+            Allergen::Fish => "FISH",
+            Allergen::Shellfish => "SHELLFISH",
+            Allergen::Sesame => "SESAME",
+        }
+    }
+}
+
+impl std::fmt::Display for Allergen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Allergen::Gluten => tr("Gluten"),
+                Allergen::Dairy => tr("Dairy"),
+                Allergen::Eggs => tr("Eggs"),
+                Allergen::Peanuts => tr("Peanuts"),
+                Allergen::TreeNuts => tr("Tree nuts"),
+                Allergen::Soy => tr("Soy"),
+                Allergen::Fish => tr("Fish"),
+                Allergen::Shellfish => tr("Shellfish"),
+                Allergen::Sesame => tr("Sesame"),
+            }
+        )
+    }
+}
+
+/// `allergens` as a comma separated list, e.g. "Gluten, Dairy".
+pub fn list(allergens: &[Allergen]) -> String {
+    allergens
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,119 @@
+//! Old sales moved out of the store into one file per month
+//!
+//! Once sales are older than the age set in the settings and no longer open,
+//! [`store::archive`] moves them to e.g. `archive/2024-03.json`, written in
+//! the same format as a [`data::Export`]. The app then no longer holds their
+//! headers in memory. A month is only read when it is opened on the list's
+//! archive tab, one month at a time, or streamed through by a report.
+//!
+//! [`store::archive`]: crate::store::archive
+use chrono::{Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::data::{self, Error, Export};
+use crate::data_dir;
+use crate::query::{self, Range};
+use crate::sale::Sale;
+use crate::store::Format;
+
+pub fn dir() -> PathBuf {
+    data_dir().join("archive")
+}
+
+fn month_path(month: &str) -> PathBuf {
+    dir().join(format!("{month}.json"))
+}
+
+fn index_path() -> PathBuf {
+    dir().join("index.json")
+}
+
+/// What is in the archive, without reading the month files.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Index {
+    /// The number of sales archived by month, e.g. "2024-03".
+    pub months: BTreeMap<String, usize>,
+    /// The highest id archived, so that new sales never reuse it.
+    pub last_id: Option<usize>,
+}
+
+impl Index {
+    pub fn load() -> Self {
+        std::fs::read_to_string(index_path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<(), Error> {
+        std::fs::create_dir_all(dir())?;
+        data::write_file(&index_path(), &serde_json::to_vec_pretty(self)?)
+    }
+}
+
+/// The month a sale is archived under, in local time.
+fn month(sale: &Sale) -> String {
+    sale.created_at
+        .with_timezone(&Local)
+        .format("%Y-%m")
+        .to_string()
+}
+
+/// Add `sales` to the files of their months.
+///
+/// Only called by [`store::archive`](crate::store::archive), which holds the
+/// store's write lock.
+pub fn add(sales: BTreeMap<usize, Sale>) -> Result<Index, Error> {
+    let mut index = Index::load();
+    index.last_id = index.last_id.max(sales.keys().last().copied());
+
+    let mut by_month: BTreeMap<String, BTreeMap<usize, Sale>> = BTreeMap::new();
+    for (id, sale) in sales {
+        by_month.entry(month(&sale)).or_default().insert(id, sale);
+    }
+
+    std::fs::create_dir_all(dir())?;
+    for (month, sales) in by_month {
+        let path = month_path(&month);
+        let mut export = if path.exists() {
+            data::read(&path)?
+        } else {
+            Export {
+                version: data::VERSION,
+                modified_at: None,
+                sales: BTreeMap::new(),
+            }
+        };
+        export.sales.extend(sales);
+        export.modified_at = Some(Utc::now());
+        data::write(&path, &export)?;
+        index.months.insert(month, export.sales.len());
+    }
+
+    index.save()?;
+    Ok(index)
+}
+
+/// Call `f` with every archived sale in `range`, reading a month at a time.
+pub fn scan(range: Range, mut f: impl FnMut(usize, Sale)) -> Result<(), Error> {
+    let within = |month: &String| {
+        range
+            .from
+            .is_none_or(|from| *month >= from.format("%Y-%m").to_string())
+            && range
+                .to
+                .is_none_or(|to| *month <= to.format("%Y-%m").to_string())
+    };
+
+    for month in Index::load().months.keys().filter(|month| within(month)) {
+        query::scan_file(&month_path(month), Format::Json, range, &mut f)?;
+    }
+    Ok(())
+}
+
+/// The sales archived in `month`, e.g. "2024-03".
+pub fn read(month: &str) -> Result<BTreeMap<usize, Sale>, Error> {
+    data::read(&month_path(month)).map(|export| export.sales)
+}
@@ -0,0 +1,78 @@
+//! Automatic rotating backups of all sales
+//!
+//! Snapshots use the same versioned format as [`data::Export`] and are
+//! written to a `backups` directory as `backup-<timestamp>.json`, so their
+//! file names sort chronologically.
+use chrono::{Local, NaiveDateTime};
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::data::{self, Error, Export};
+use crate::data_dir;
+
+const PREFIX: &str = "backup-";
+const TIMESTAMP: &str = "%Y%m%d-%H%M%S";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub taken_at: NaiveDateTime,
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.taken_at.format("%Y-%m-%d %H:%M:%S"))
+    }
+}
+
+pub fn dir() -> PathBuf {
+    data_dir().join("backups")
+}
+
+/// Write a new snapshot of `export` and delete all but the `keep` most recent
+/// ones.
+pub async fn write(export: Export, keep: usize) -> Result<Snapshot, Error> {
+    std::fs::create_dir_all(dir())?;
+
+    let taken_at = Local::now().naive_local();
+    let path =
+        dir().join(format!("{PREFIX}{}.json", taken_at.format(TIMESTAMP)));
+    data::write(&path, &export)?;
+    rotate(keep)?;
+
+    Ok(Snapshot { path, taken_at })
+}
+
+/// All snapshots, most recent first.
+pub fn list() -> Vec<Snapshot> {
+    let Ok(entries) = std::fs::read_dir(dir()) else {
+        return Vec::new();
+    };
+
+    let mut snapshots: Vec<Snapshot> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let stamp = path
+                .file_stem()?
+                .to_str()?
+                .strip_prefix(PREFIX)?
+                .to_string();
+            let taken_at =
+                NaiveDateTime::parse_from_str(&stamp, TIMESTAMP).ok()?;
+            Some(Snapshot { path, taken_at })
+        })
+        .collect();
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.taken_at));
+    snapshots
+}
+
+pub async fn restore(snapshot: Snapshot) -> Result<Export, Error> {
+    data::read(&snapshot.path)
+}
+
+fn rotate(keep: usize) -> Result<(), Error> {
+    for snapshot in list().into_iter().skip(keep.max(1)) {
+        std::fs::remove_file(snapshot.path)?;
+    }
+    Ok(())
+}
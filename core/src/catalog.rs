@@ -0,0 +1,376 @@
+//! The product catalog, with optional stock counts
+//!
+//! Items added to a sale from the catalog remember the product they came
+//! from. Stock moves when a sale is saved, by how much its quantities changed
+//! since the stored version of it, so removing or voiding an item or lowering
+//! its quantity puts the units back in stock.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+
+use crate::allergen::Allergen;
+use crate::data::{self, Error};
+use crate::data_dir;
+use crate::i18n;
+use crate::sale::Sale;
+use crate::tax::TaxGroup;
+
+pub mod promotion;
+
+pub use promotion::Promotion;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Product {
+    pub id: usize,
+    pub name: String,
+    pub price: Option<f32>,
+    pub tax_group: TaxGroup,
+    /// Units in stock, or `None` if stock isn't tracked.
+    pub stock: Option<i32>,
+    /// Stock at or below this is shown as running low.
+    pub low_stock: u32,
+    /// Whether the price is per kg, with the quantity read off a scale.
+    #[serde(default)]
+    pub by_weight: bool,
+    /// Containers commonly weighed along with the product, the first being
+    /// the default.
+    #[serde(default)]
+    pub tares: Vec<Tare>,
+    #[serde(default)]
+    pub allergens: Vec<Allergen>,
+    /// Lower unit prices from larger quantities on.
+    #[serde(default)]
+    pub tiers: Vec<Tier>,
+    /// Prices for some hours of some weekdays, the first active one
+    /// applying.
+    #[serde(default)]
+    pub promotions: Vec<Promotion>,
+    /// The page of quick buttons the product is on, e.g. Drinks, none if
+    /// empty.
+    #[serde(default)]
+    pub category: String,
+    /// Whether its price is asked for each time it is added to a sale, e.g.
+    /// for miscellaneous items.
+    #[serde(default)]
+    pub open_price: bool,
+}
+
+/// A container whose weight is taken off a scale reading.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tare {
+    pub name: String,
+    /// In kg.
+    pub weight: f32,
+}
+
+impl fmt::Display for Tare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({} kg)", self.name, i18n::number(self.weight, 3))
+    }
+}
+
+/// A unit price from a quantity on, e.g. $4.50 each from 10.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Tier {
+    pub min: u32,
+    pub price: f32,
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}+ @ {}", self.min, i18n::money(self.price))
+    }
+}
+
+impl Product {
+    pub fn display_name(&self) -> &str {
+        if self.name.is_empty() {
+            "Untitled product"
+        } else {
+            &self.name
+        }
+    }
+
+    /// The price tiers of items added from the product: its regular price
+    /// from one on, then its quantity tiers by quantity. Empty when it has
+    /// no quantity tiers.
+    pub fn price_tiers(&self) -> Vec<Tier> {
+        if self.tiers.is_empty() || self.by_weight {
+            return Vec::new();
+        }
+
+        let mut tiers: Vec<Tier> = std::iter::once(Tier {
+            min: 1,
+            price: self.price.unwrap_or(0.0),
+        })
+        .chain(self.tiers.iter().copied().filter(|tier| tier.min > 1))
+        .collect();
+        tiers.sort_by_key(|tier| tier.min);
+        tiers
+    }
+
+    /// The promotion whose price applies to a sale made `at`.
+    pub fn promotion_at(&self, at: DateTime<Utc>) -> Option<&Promotion> {
+        self.promotions
+            .iter()
+            .find(|promotion| promotion.is_active(at))
+    }
+
+    pub fn is_out_of_stock(&self) -> bool {
+        self.stock.is_some_and(|stock| stock <= 0)
+    }
+
+    pub fn is_low_on_stock(&self) -> bool {
+        self.stock
+            .is_some_and(|stock| stock <= self.low_stock as i32)
+    }
+}
+
+impl fmt::Display for Product {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} • ${:.2}",
+            self.display_name(),
+            self.price.unwrap_or(0.0)
+        )?;
+        if self.is_out_of_stock() {
+            write!(f, " (out of stock)")?;
+        }
+        Ok(())
+    }
+}
+
+/// What happens when a sale is saved with more of a product than in stock.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum ZeroStock {
+    #[default]
+    Warn,
+    Block,
+}
+
+impl ZeroStock {
+    pub const ALL: [ZeroStock; 2] = [ZeroStock::Warn, ZeroStock::Block];
+}
+
+impl fmt::Display for ZeroStock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ZeroStock::Warn => "Warn and save",
+                ZeroStock::Block => "Don't save",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Catalog {
+    products: Vec<Product>,
+}
+
+impl Catalog {
+    fn path() -> PathBuf {
+        data_dir().join("catalog.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save in the background, see [`data::save_json`].
+    pub fn save(
+        &self,
+    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        data::save_json(Self::path(), self)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Product> {
+        self.products.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.products.is_empty()
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Product> {
+        self.products.iter().find(|product| product.id == id)
+    }
+
+    /// The product named `name`, or else the only one whose name starts
+    /// with it, ignoring case.
+    pub fn find(&self, name: &str) -> Option<&Product> {
+        let name = name.trim().to_lowercase();
+        if name.is_empty() {
+            return None;
+        }
+        if let Some(product) = self
+            .products
+            .iter()
+            .find(|product| product.name.to_lowercase() == name)
+        {
+            return Some(product);
+        }
+        let mut starting = self
+            .products
+            .iter()
+            .filter(|product| product.name.to_lowercase().starts_with(&name));
+        match (starting.next(), starting.next()) {
+            (Some(product), None) => Some(product),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut Product> {
+        self.products.iter_mut().find(|product| product.id == id)
+    }
+
+    /// Add an empty product, returning its id.
+    pub fn add(&mut self) -> usize {
+        let id = self.products.iter().map(|p| p.id + 1).max().unwrap_or(0);
+        self.products.push(Product {
+            id,
+            name: String::new(),
+            price: None,
+            tax_group: TaxGroup::Food,
+            stock: None,
+            low_stock: 0,
+            by_weight: false,
+            tares: Vec::new(),
+            allergens: Vec::new(),
+            tiers: Vec::new(),
+            promotions: Vec::new(),
+            category: String::new(),
+            open_price: false,
+        });
+        id
+    }
+
+    /// The categories products are in, in the order they first appear.
+    pub fn categories(&self) -> Vec<&str> {
+        let mut categories: Vec<&str> = Vec::new();
+        for product in &self.products {
+            let category = product.category.trim();
+            if !category.is_empty() && !categories.contains(&category) {
+                categories.push(category);
+            }
+        }
+        categories
+    }
+
+    pub fn remove(&mut self, id: usize) {
+        self.products.retain(|product| product.id != id);
+    }
+
+    /// Products which saving `sale` over its `stored` version would take
+    /// below zero stock.
+    pub fn shortfalls(
+        &self,
+        stored: Option<&Sale>,
+        sale: &Sale,
+    ) -> Vec<&Product> {
+        changes(stored, sale)
+            .into_iter()
+            .filter(|(_, sold)| *sold > 0)
+            .filter_map(|(id, sold)| {
+                self.get(id)
+                    .filter(|product| product.stock.is_some_and(|s| s < sold))
+            })
+            .collect()
+    }
+
+    /// Move stock by how many units of each product `sale` sells beyond
+    /// its `stored` version.
+    pub fn settle(&mut self, stored: Option<&Sale>, sale: &Sale) {
+        for (id, sold) in changes(stored, sale) {
+            if let Some(stock) =
+                self.get_mut(id).and_then(|product| product.stock.as_mut())
+            {
+                *stock -= sold;
+            }
+        }
+    }
+}
+
+/// Units of each product `sale` sells beyond its `stored` version.
+fn changes(stored: Option<&Sale>, sale: &Sale) -> BTreeMap<usize, i32> {
+    let before = stored.map(units).unwrap_or_default();
+    let after = units(sale);
+
+    before
+        .keys()
+        .chain(after.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|id| {
+            let sold = after.get(id).copied().unwrap_or(0)
+                - before.get(id).copied().unwrap_or(0);
+            (*id, sold)
+        })
+        .filter(|(_, sold)| *sold != 0)
+        .collect()
+}
+
+/// Units of each product on `sale`, leaving out voided lines. Stock isn't
+/// counted for products sold by weight.
+fn units(sale: &Sale) -> BTreeMap<usize, i32> {
+    // Returned items go back into stock
+    let sign = if sale.is_return() { -1 } else { 1 };
+    sale.items.iter().fold(BTreeMap::new(), |mut units, item| {
+        if item.is_void() {
+            return units;
+        }
+        if let (Some(id), None) = (item.product, item.weighing) {
+            *units.entry(id).or_default() += sign * item.quantity() as i32;
+        }
+        units
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sale::SaleItem;
+
+    #[test]
+    fn voiding_an_item_puts_its_stock_back() {
+        let mut catalog = Catalog::default();
+        let id = catalog.add();
+        let beer = catalog.get_mut(id).unwrap();
+        beer.name = "Beer".to_string();
+        beer.price = Some(5.0);
+        beer.stock = Some(10);
+        let stock = |catalog: &Catalog| catalog.get(id).unwrap().stock;
+
+        let item = SaleItem {
+            product: Some(id),
+            ..SaleItem::new("Beer", 5.0, 3)
+        };
+        let item_id = item.id;
+        let mut sale = Sale {
+            items: vec![item, SaleItem::new("Water", 2.0, 1)],
+            ..Sale::default()
+        };
+        catalog.settle(None, &sale);
+        assert_eq!(stock(&catalog), Some(7));
+
+        let stored = sale.clone();
+        sale.void(item_id, "Spilled", Utc::now());
+        catalog.settle(Some(&stored), &sale);
+        assert_eq!(stock(&catalog), Some(10));
+
+        // Saving the voided sale again takes nothing
+        catalog.settle(Some(&sale.clone()), &sale);
+        assert_eq!(stock(&catalog), Some(10));
+    }
+}
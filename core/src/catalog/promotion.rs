@@ -0,0 +1,83 @@
+//! Prices for some hours of some weekdays, e.g. happy hour
+//!
+//! Times are local to the till. A window ending before it starts runs past
+//! midnight, e.g. 22:00 to 02:00.
+use chrono::{DateTime, Datelike, Local, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Promotion {
+    pub name: String,
+    /// The weekdays it runs on, every day if empty.
+    pub days: Vec<Weekday>,
+    pub from: NaiveTime,
+    pub until: NaiveTime,
+    pub price: f32,
+}
+
+impl Promotion {
+    pub fn new(price: f32) -> Self {
+        Self {
+            name: String::new(),
+            days: Vec::new(),
+            from: NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+            until: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            price,
+        }
+    }
+
+    pub fn display_name(&self) -> &str {
+        if self.name.is_empty() {
+            "Promotion"
+        } else {
+            &self.name
+        }
+    }
+
+    /// Whether the promotion price applies at `at`.
+    pub fn is_active(&self, at: DateTime<Utc>) -> bool {
+        let local = at.with_timezone(&Local);
+        let time = local.time();
+        let in_window = if self.from <= self.until {
+            self.from <= time && time < self.until
+        } else {
+            time >= self.from || time < self.until
+        };
+
+        in_window
+            && (self.days.is_empty() || self.days.contains(&local.weekday()))
+    }
+}
+
+/// A time of day a promotion can start or end at, every half hour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slot(pub NaiveTime);
+
+impl Slot {
+    pub fn all() -> Vec<Slot> {
+        (0..48)
+            .filter_map(|half_hours| {
+                NaiveTime::from_hms_opt(half_hours / 2, half_hours % 2 * 30, 0)
+            })
+            .map(Slot)
+            .collect()
+    }
+}
+
+impl fmt::Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.format("%H:%M"))
+    }
+}
+
+/// The weekdays from Monday, with short names for toggles.
+pub const WEEK: [(Weekday, &str); 7] = [
+    (Weekday::Mon, "Mo"),
+    (Weekday::Tue, "Tu"),
+    (Weekday::Wed, "We"),
+    (Weekday::Thu, "Th"),
+    (Weekday::Fri, "Fr"),
+    (Weekday::Sat, "Sa"),
+    (Weekday::Sun, "Su"),
+];
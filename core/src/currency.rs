@@ -0,0 +1,283 @@
+//! Payments taken in a foreign currency, e.g. euros at a till pricing in
+//! dollars
+//!
+//! Sales are priced in the base currency. A payment in another currency is
+//! converted at the rate set in the settings, typed in or fetched, and any
+//! change is given back in the base currency. The payment keeps both amounts
+//! and the rate, so the receipt shows what was handed over.
+//!
+//! Fetched rates are also cached on disk with every quote the API gave, so
+//! the till keeps its rates when the connection is down.
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+
+use crate::data::{self, Error};
+use crate::data_dir;
+
+/// A free exchange rate API that needs no key.
+pub const DEFAULT_API: &str = "https://open.er-api.com/v6/latest/{base}";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rate {
+    /// The ISO code of the currency, e.g. "EUR".
+    pub code: String,
+    /// What one unit of the currency is worth in the base currency.
+    pub rate: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The ISO code of the currency sales are priced in, e.g. "USD".
+    pub base: String,
+    pub rates: Vec<Rate>,
+    /// When the rates were last fetched, if ever.
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// Where rates are fetched from, with `{base}` standing for the base
+    /// currency.
+    pub api: String,
+    /// How many hours to keep fetched rates before fetching them again, 0
+    /// to only fetch them by hand.
+    pub refresh_hours: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            base: "USD".to_string(),
+            rates: Vec::new(),
+            fetched_at: None,
+            api: DEFAULT_API.to_string(),
+            refresh_hours: 0,
+        }
+    }
+}
+
+impl Config {
+    /// The rate of `code`, if it is set.
+    pub fn rate(&self, code: &str) -> Option<f32> {
+        self.rates
+            .iter()
+            .find(|rate| rate.code.eq_ignore_ascii_case(code))
+            .map(|rate| rate.rate)
+            .filter(|rate| *rate > 0.0)
+    }
+
+    /// The currencies payments can be taken in.
+    pub fn codes(&self) -> Vec<String> {
+        self.rates
+            .iter()
+            .filter(|rate| rate.rate > 0.0 && !rate.code.trim().is_empty())
+            .map(|rate| rate.code.clone())
+            .collect()
+    }
+
+    /// The address to fetch the rates of the base currency from.
+    pub fn url(&self) -> String {
+        let api = match self.api.trim() {
+            "" => DEFAULT_API,
+            api => api,
+        };
+        api.replace("{base}", &self.base.trim().to_uppercase())
+    }
+
+    /// Whether the rates should be fetched again at `now`.
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.refresh_hours > 0
+            && !self.codes().is_empty()
+            && self.fetched_at.is_none_or(|at| {
+                now - at >= Duration::hours(self.refresh_hours.into())
+            })
+    }
+
+    /// Set the rates of the currencies from the quotes of `cache`, if they
+    /// are for the base currency. Returns how many were set.
+    pub fn apply(&mut self, cache: &Cache) -> usize {
+        if !cache.base.eq_ignore_ascii_case(self.base.trim()) {
+            return 0;
+        }
+        let mut set = 0;
+        for rate in &mut self.rates {
+            if let Some(quote) = cache
+                .quotes
+                .get(&rate.code.trim().to_uppercase())
+                .filter(|quote| **quote > 0.0)
+            {
+                rate.rate = 1.0 / quote;
+                set += 1;
+            }
+        }
+        self.fetched_at = Some(cache.fetched_at);
+        set
+    }
+}
+
+/// The rates last fetched, kept for when they can't be fetched.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cache {
+    pub base: String,
+    pub fetched_at: DateTime<Utc>,
+    /// How much one unit of `base` buys of each currency, as exchange rate
+    /// APIs quote them.
+    pub quotes: BTreeMap<String, f32>,
+}
+
+impl Cache {
+    fn path() -> PathBuf {
+        data_dir().join("exchange_rates.json")
+    }
+
+    pub fn load() -> Option<Self> {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+    }
+
+    /// Save in the background, see [`data::save_json`].
+    pub fn save(
+        &self,
+    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        data::save_json(Self::path(), self)
+    }
+}
+
+/// A payment in a foreign currency, being typed on the show screen.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Entry {
+    pub code: Option<String>,
+    pub amount: String,
+}
+
+/// A payment taken in a foreign currency, as recorded on a sale.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Payment {
+    pub currency: String,
+    /// The currency the sale was priced in.
+    pub base: String,
+    /// What one unit of `currency` was worth in `base`.
+    pub rate: f32,
+    /// The amount handed over, in `currency`.
+    pub tendered: f32,
+    /// What the amount handed over is worth in `base`.
+    pub amount: f32,
+    /// The change given back, in `base`.
+    pub change: f32,
+}
+
+impl Payment {
+    /// `tendered` in `currency` paying `due`, or `None` if it isn't enough.
+    pub fn new(
+        config: &Config,
+        currency: &str,
+        tendered: f32,
+        due: f32,
+    ) -> Option<Self> {
+        let rate = config.rate(currency)?;
+        let amount = cents(tendered * rate);
+        if amount < cents(due) {
+            return None;
+        }
+        Some(Self {
+            currency: currency.to_string(),
+            base: config.base.clone(),
+            rate,
+            tendered,
+            amount,
+            change: cents(amount - due),
+        })
+    }
+}
+
+impl fmt::Display for Payment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.2} {}", self.tendered, self.currency)
+    }
+}
+
+fn cents(amount: f32) -> f32 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// The rates in a response of an exchange rate API, e.g.
+/// `{"rates": {"EUR": 0.92}}`, by currency code.
+pub fn parse_rates(json: &str) -> Result<BTreeMap<String, f32>, String> {
+    let body: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let rates = body["rates"]
+        .as_object()
+        .ok_or("The response has no exchange rates")?;
+    Ok(rates
+        .iter()
+        .filter_map(|(code, rate)| Some((code.clone(), rate.as_f64()? as f32)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_the_payment_and_gives_change_in_the_base_currency() {
+        let mut config = Config {
+            rates: vec![
+                Rate {
+                    code: "EUR".to_string(),
+                    rate: 0.0,
+                },
+                Rate {
+                    code: "gbp".to_string(),
+                    rate: 1.25,
+                },
+            ],
+            ..Config::default()
+        };
+        let fetched_at = Utc::now();
+        let mut cache = Cache {
+            base: "EUR".to_string(),
+            fetched_at,
+            quotes: parse_rates(r#"{"result":"success","rates":{"EUR":0.8}}"#)
+                .unwrap(),
+        };
+        assert_eq!(config.apply(&cache), 0);
+        cache.base = "USD".to_string();
+        assert_eq!(config.apply(&cache), 1);
+        assert_eq!(config.rate("eur"), Some(1.25));
+        assert_eq!(config.fetched_at, Some(fetched_at));
+        assert_eq!(config.codes(), ["EUR", "gbp"]);
+
+        assert_eq!(Payment::new(&config, "EUR", 40.0, 51.6), None);
+        let payment = Payment::new(&config, "EUR", 50.0, 51.6).unwrap();
+        assert_eq!(payment.amount, 62.5);
+        assert_eq!(payment.change, 10.9);
+        assert_eq!(payment.base, "USD");
+        assert_eq!(payment.to_string(), "50.00 EUR");
+
+        assert!(parse_rates(r#"{"error":"unknown"}"#).is_err());
+    }
+
+    #[test]
+    fn rates_are_due_again_after_the_refresh_interval() {
+        let now = Utc::now();
+        let mut config = Config {
+            rates: vec![Rate {
+                code: "EUR".to_string(),
+                rate: 1.08,
+            }],
+            base: "cad".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(config.url(), "https://open.er-api.com/v6/latest/CAD");
+        assert!(!config.is_due(now));
+
+        config.refresh_hours = 6;
+        assert!(config.is_due(now));
+        config.fetched_at = Some(now - Duration::hours(5));
+        assert!(!config.is_due(now));
+        config.fetched_at = Some(now - Duration::hours(6));
+        assert!(config.is_due(now));
+    }
+}
@@ -0,0 +1,88 @@
+//! User-defined custom fields on sales, e.g. "PO number" or "Event name"
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Kind {
+    #[default]
+    Text,
+    Number,
+    Choice,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 3] = [Kind::Text, Kind::Number, Kind::Choice];
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Kind::Text => "Text",
+                Kind::Number => "Number",
+                Kind::Choice => "Choice",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Field {
+    /// Stable id under which values are stored on a sale, so fields can be
+    /// renamed without losing data.
+    pub id: usize,
+    pub name: String,
+    pub kind: Kind,
+    /// The options of a [`Kind::Choice`] field.
+    pub options: Vec<String>,
+    /// Whether the field is printed on receipts.
+    pub on_receipt: bool,
+}
+
+impl Field {
+    pub fn new(id: usize) -> Self {
+        Self {
+            id,
+            ..Self::default()
+        }
+    }
+
+    pub fn display_name(&self) -> &str {
+        if self.name.is_empty() {
+            "Untitled field"
+        } else {
+            &self.name
+        }
+    }
+
+    /// The non-empty options of a [`Kind::Choice`] field.
+    pub fn choices(&self) -> Vec<String> {
+        self.options
+            .iter()
+            .filter(|option| !option.is_empty())
+            .cloned()
+            .collect()
+    }
+
+    /// Whether `value` may be stored in this field.
+    pub fn accepts(&self, value: &str) -> bool {
+        match self.kind {
+            Kind::Text => true,
+            Kind::Number => {
+                value.is_empty() || value == "-" || value.parse::<f64>().is_ok()
+            }
+            Kind::Choice => {
+                value.is_empty() || self.options.iter().any(|o| o == value)
+            }
+        }
+    }
+}
+
+/// The next free id for a new field.
+pub fn next_id(fields: &[Field]) -> usize {
+    fields.iter().map(|field| field.id + 1).max().unwrap_or(0)
+}
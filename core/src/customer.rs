@@ -0,0 +1,285 @@
+//! Customers, and the sales made for each of them
+//!
+//! Customers are kept in their own file. A sale is made for a customer by
+//! picking them on the edit screen, which records their id on the sale, so
+//! their history follows them through renames. Only the sales in the store
+//! are counted: archived ones are no longer held in memory.
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+
+use crate::data::{self, csv_escape, slug, Error};
+use crate::data_dir;
+use crate::i18n::Locale;
+use crate::store::Header;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Customer {
+    pub id: usize,
+    pub name: String,
+    #[serde(default)]
+    pub phone: String,
+    #[serde(default)]
+    pub email: String,
+    /// The language their receipts are written in, if not the store's.
+    #[serde(default)]
+    pub locale: Option<Locale>,
+    /// Their tax ID, e.g. a VAT ID, printed on their receipts.
+    #[serde(default)]
+    pub tax_id: String,
+}
+
+impl Customer {
+    pub fn display_name(&self) -> &str {
+        if self.name.is_empty() {
+            "Unnamed customer"
+        } else {
+            &self.name
+        }
+    }
+
+    /// The name of the file their purchase history is exported to, e.g.
+    /// `customer-3-ada-lovelace.csv`.
+    pub fn file_name(&self) -> String {
+        format!("customer-{}-{}.csv", self.id, slug(&self.name))
+    }
+
+    /// The customer to pick on a sale.
+    pub fn choice(&self) -> Choice {
+        Choice {
+            id: self.id,
+            name: self.display_name().to_string(),
+            tax_id: self.tax_id.trim().to_string(),
+        }
+    }
+
+    /// The phone number and email, whichever are known.
+    pub fn contact(&self) -> String {
+        [self.phone.trim(), self.email.trim()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" • ")
+    }
+}
+
+/// A customer to pick on a sale, by name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice {
+    pub id: usize,
+    pub name: String,
+    /// Their tax ID, kept on the sale they are picked on.
+    pub tax_id: String,
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Customers {
+    customers: Vec<Customer>,
+}
+
+impl Customers {
+    fn path() -> PathBuf {
+        data_dir().join("customers.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save in the background, see [`data::save_json`].
+    pub fn save(
+        &self,
+    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        data::save_json(Self::path(), self)
+    }
+
+    pub fn add(&mut self, name: &str, phone: &str, email: &str) -> usize {
+        let id = self.customers.iter().map(|c| c.id + 1).max().unwrap_or(0);
+        self.customers.push(Customer {
+            id,
+            name: name.trim().to_string(),
+            phone: phone.trim().to_string(),
+            email: email.trim().to_string(),
+            locale: None,
+            tax_id: String::new(),
+        });
+        id
+    }
+
+    /// Set the tax ID of customer `id` as typed.
+    pub fn set_tax_id(&mut self, id: usize, tax_id: &str) {
+        if let Some(customer) = self.customers.iter_mut().find(|c| c.id == id) {
+            customer.tax_id = tax_id.to_string();
+        }
+    }
+
+    /// Write the receipts of customer `id` in `locale`, or in the language
+    /// of the store for `None`.
+    pub fn set_locale(&mut self, id: usize, locale: Option<Locale>) {
+        if let Some(customer) = self.customers.iter_mut().find(|c| c.id == id) {
+            customer.locale = locale;
+        }
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Customer> {
+        self.customers.iter().find(|customer| customer.id == id)
+    }
+
+    pub fn remove(&mut self, id: usize) {
+        self.customers.retain(|customer| customer.id != id);
+    }
+
+    /// Customers whose name, phone or email contains `query`, by name.
+    pub fn search(&self, query: &str) -> Vec<&Customer> {
+        let query = query.trim().to_lowercase();
+        let mut found: Vec<&Customer> = self
+            .customers
+            .iter()
+            .filter(|customer| {
+                [&customer.name, &customer.phone, &customer.email]
+                    .iter()
+                    .any(|field| field.to_lowercase().contains(&query))
+            })
+            .collect();
+        found.sort_by_key(|customer| customer.name.to_lowercase());
+        found
+    }
+
+    /// Every customer to pick from, by name.
+    pub fn choices(&self) -> Vec<Choice> {
+        self.search("").into_iter().map(Customer::choice).collect()
+    }
+}
+
+/// The ids of the sales made for each customer.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Index {
+    sales: BTreeMap<usize, BTreeSet<usize>>,
+}
+
+impl Index {
+    pub fn new<'a>(
+        headers: impl IntoIterator<Item = (&'a usize, &'a Header)>,
+    ) -> Self {
+        let mut sales: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+        for (id, header) in headers {
+            if let Some(customer) = header.customer {
+                sales.entry(customer).or_default().insert(*id);
+            }
+        }
+        Self { sales }
+    }
+
+    /// The sales of `customer`, the oldest first.
+    pub fn sales(&self, customer: usize) -> impl Iterator<Item = usize> + '_ {
+        self.sales.get(&customer).into_iter().flatten().copied()
+    }
+}
+
+/// What a customer has spent over all their sales.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct History {
+    pub count: usize,
+    pub lifetime: f32,
+    pub average: f32,
+}
+
+impl History {
+    pub fn new<'a>(headers: impl IntoIterator<Item = &'a Header>) -> Self {
+        let (count, lifetime) =
+            headers.into_iter().fold((0, 0.0), |(count, sum), header| {
+                (count + 1, sum + header.total)
+            });
+        Self {
+            count,
+            lifetime,
+            average: if count == 0 {
+                0.0
+            } else {
+                lifetime / count as f32
+            },
+        }
+    }
+}
+
+/// The purchase history of a customer as CSV, one sale per line, with
+/// their lifetime spend and average ticket on the last lines.
+pub fn export_csv(sales: &[(usize, Header)]) -> String {
+    let mut csv = String::from("sale,date,name,order,total\n");
+    for (id, header) in sales {
+        csv.push_str(&format!(
+            "{id},{},{},{},{:.2}\n",
+            header
+                .created_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M"),
+            csv_escape(&header.name),
+            header.order_type,
+            header.total
+        ));
+    }
+    let history = History::new(sales.iter().map(|(_, header)| header));
+    csv.push_str(&format!(",,Lifetime spend,,{:.2}\n", history.lifetime));
+    csv.push_str(&format!(",,Average ticket,,{:.2}\n", history.average));
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sale::{Sale, SaleItem};
+    use crate::tax::TaxGroup;
+
+    fn header(customer: Option<usize>, price: f32) -> Header {
+        let mut item = SaleItem::new("Coffee beans", price, 1);
+        item.tax_group = TaxGroup::NonTaxable;
+        Header::new(&Sale {
+            items: vec![item],
+            customer,
+            ..Sale::default()
+        })
+    }
+
+    #[test]
+    fn sales_are_indexed_by_customer_with_their_spend() {
+        let mut customers = Customers::default();
+        let ada = customers.add(" Ada ", "555-0101", "");
+        let bo = customers.add("Bo", "", "bo@example.com");
+        assert_eq!(customers.get(ada).unwrap().name, "Ada");
+        assert_eq!(customers.search("EXAMPLE").len(), 1);
+        assert_eq!(customers.get(bo).unwrap().contact(), "bo@example.com");
+
+        let headers = BTreeMap::from([
+            (1, header(Some(ada), 12.0)),
+            (2, header(None, 5.0)),
+            (3, header(Some(bo), 7.0)),
+            (4, header(Some(ada), 30.0)),
+        ]);
+        let index = Index::new(&headers);
+        assert_eq!(index.sales(ada).collect::<Vec<_>>(), [1, 4]);
+        assert_eq!(index.sales(99).count(), 0);
+
+        let history = History::new(index.sales(ada).map(|id| &headers[&id]));
+        assert_eq!(
+            history,
+            History {
+                count: 2,
+                lifetime: 42.0,
+                average: 21.0
+            }
+        );
+        assert_eq!(History::new([]).average, 0.0);
+    }
+}
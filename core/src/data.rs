@@ -0,0 +1,384 @@
+//! Versioned JSON export and import of all app data
+//!
+//! An export is a single JSON document holding every sale keyed by id,
+//! tagged with a format `version` so that older files can still be recognized
+//! as the data model grows. Importing is done in two steps: [`validate`]
+//! performs a dry run of a file [`read`] from disk against the current sales
+//! and reports what would happen, and [`apply`] merges the validated file
+//! into the app state.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::fs::File;
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use crate::custom;
+use crate::migrate;
+use crate::sale::Sale;
+
+/// The current version of the export format. Files from older versions are
+/// upgraded as they are read, see [`migrate`](crate::migrate).
+pub const VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Export {
+    pub version: u32,
+    /// When the exported sales were last changed.
+    #[serde(default)]
+    pub modified_at: Option<DateTime<Utc>>,
+    pub sales: BTreeMap<usize, Sale>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Error {
+    Io(String),
+    Format(String),
+    UnsupportedVersion(u32),
+    /// Sales written by this version could not be upgraded.
+    Migration(u32, String),
+    /// Another instance of the app holds the lock on the store.
+    Locked,
+    /// These sales were saved at a later revision than the copies written,
+    /// which were left out.
+    Stale(Vec<usize>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {e}"),
+            Error::Format(e) => write!(f, "Invalid file: {e}"),
+            Error::UnsupportedVersion(v) => {
+                write!(f, "Unsupported export version {v} (expected {VERSION})")
+            }
+            Error::Migration(v, e) => {
+                write!(f, "Could not upgrade sales from version {v}: {e}")
+            }
+            Error::Locked => write!(
+                f,
+                "The sales file is in use by another instance of the app"
+            ),
+            Error::Stale(ids) => write!(
+                f,
+                "Sale changed elsewhere: {}",
+                ids.iter()
+                    .map(|id| format!("#{id}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Format(e.to_string())
+    }
+}
+
+/// How to treat imported sales whose id is already in use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Conflict {
+    #[default]
+    Skip,
+    Overwrite,
+    KeepBoth,
+}
+
+impl Conflict {
+    pub const ALL: [Conflict; 3] =
+        [Conflict::Skip, Conflict::Overwrite, Conflict::KeepBoth];
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Conflict::Skip => "Keep existing",
+                Conflict::Overwrite => "Overwrite existing",
+                Conflict::KeepBoth => "Import as new sales",
+            }
+        )
+    }
+}
+
+/// The result of a dry run of an import.
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub export: Export,
+    /// Ids of imported sales which don't exist yet.
+    pub new: Vec<usize>,
+    /// Ids of imported sales which are already in use.
+    pub conflicts: Vec<usize>,
+}
+
+/// An export of sale `id` alone, e.g. to attach to a bug report or move it
+/// to another machine. It reads back like any other export.
+pub fn single(id: usize, sale: &Sale) -> Export {
+    Export {
+        version: VERSION,
+        modified_at: Some(Utc::now()),
+        sales: BTreeMap::from([(id, sale.clone())]),
+    }
+}
+
+/// The sale of a [`single`] export as a new draft, with fresh ids and no
+/// links to catalog products, which are only meaningful on the machine it
+/// came from.
+pub fn single_sale(export: Export) -> Result<Sale, Error> {
+    if export.sales.len() != 1 {
+        return Err(Error::Format(format!(
+            "expected a single sale, found {}",
+            export.sales.len()
+        )));
+    }
+
+    let mut sale = export.sales.into_values().next().unwrap().with_fresh_ids();
+    for item in &mut sale.items {
+        item.product = None;
+    }
+    Ok(sale)
+}
+
+pub fn to_json(export: &Export) -> Result<String, Error> {
+    Ok(serde_json::to_string_pretty(export)?)
+}
+
+pub fn write(path: &Path, export: &Export) -> Result<(), Error> {
+    write_file(path, &serde_json::to_vec_pretty(export)?)
+}
+
+/// Write `contents` to `path` so that it holds either the old or the new
+/// contents even if the power fails halfway: they are written to a temporary
+/// file and flushed to disk first, then swapped in by a rename.
+pub fn write_file(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+
+    let mut file = File::create(&temp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    std::fs::rename(&temp, path)?;
+
+    // The rename only survives a power loss once the directory is flushed,
+    // which not every platform allows
+    if let Some(dir) = path.parent().and_then(|dir| File::open(dir).ok()) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// The order saves by [`save_json`] were started in.
+static SAVES: AtomicU64 = AtomicU64::new(0);
+/// The last save written to each file, by the order it was started in.
+static WRITTEN: Mutex<BTreeMap<PathBuf, u64>> = Mutex::new(BTreeMap::new());
+
+/// Save `value` as JSON to `path`, e.g. the catalog or the config, to be
+/// written in the background. It is turned into JSON right away and written
+/// by [`write_file`], creating the directory first. A save started before
+/// the last one written to the same file is skipped, as that one is newer.
+pub fn save_json<T: Serialize>(
+    path: PathBuf,
+    value: &T,
+) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+    let started = SAVES.fetch_add(1, Ordering::SeqCst);
+    let json = serde_json::to_vec_pretty(value);
+
+    async move {
+        let json = json?;
+        let mut written =
+            WRITTEN.lock().unwrap_or_else(PoisonError::into_inner);
+        if written.get(&path).is_some_and(|last| *last > started) {
+            return Ok(());
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        write_file(&path, &json)?;
+        written.insert(path, started);
+        Ok(())
+    }
+}
+
+/// Export a summary of every sale as CSV, one row per sale, with a column
+/// for each custom field.
+pub fn export_csv(
+    path: &Path,
+    sales: &BTreeMap<usize, Sale>,
+    fields: &[custom::Field],
+) -> Result<(), Error> {
+    std::fs::write(path, to_csv(sales, fields))?;
+    Ok(())
+}
+
+/// The CSV written by [`export_csv`].
+pub fn to_csv(
+    sales: &BTreeMap<usize, Sale>,
+    fields: &[custom::Field],
+) -> String {
+    let mut header: Vec<String> = [
+        "id",
+        "name",
+        "order type",
+        "items",
+        "subtotal",
+        "service charge",
+        "tax",
+        "gratuity",
+        "delivery fee",
+        "total",
+    ]
+    .iter()
+    .map(|column| column.to_string())
+    .collect();
+    header.extend(fields.iter().map(|field| field.name.clone()));
+
+    let rows = sales.iter().map(|(id, sale)| {
+        let mut row = vec![
+            id.to_string(),
+            sale.name.clone(),
+            sale.order_type.to_string(),
+            sale.items.len().to_string(),
+            format!("{:.2}", sale.calculate_subtotal()),
+            format!("{:.2}", sale.calculate_service_charge()),
+            format!("{:.2}", sale.calculate_tax()),
+            format!("{:.2}", sale.gratuity_amount.unwrap_or(0.0)),
+            format!("{:.2}", sale.calculate_delivery_fee()),
+            format!("{:.2}", sale.calculate_total()),
+        ];
+        row.extend(
+            fields
+                .iter()
+                .map(|field| sale.custom_field(field.id).to_string()),
+        );
+        row
+    });
+
+    std::iter::once(header)
+        .chain(rows)
+        .map(|row| {
+            let row: Vec<String> =
+                row.iter().map(|cell| csv_escape(cell)).collect();
+            row.join(",") + "\n"
+        })
+        .collect()
+}
+
+/// `cell` as a CSV cell, quoted if it holds a comma, a quote or a line
+/// break.
+pub fn csv_escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+/// `name` in lowercase words joined by dashes, to name a file after, e.g.
+/// `acme-co` for "Acme Co.".
+pub(crate) fn slug(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+pub fn read(path: &Path) -> Result<Export, Error> {
+    parse(&std::fs::read_to_string(path)?)
+}
+
+pub fn parse(json: &str) -> Result<Export, Error> {
+    migrate::export(serde_json::from_str(json)?)
+}
+
+/// Check an export against the current sales without changing anything.
+pub fn validate(export: Export, in_use: &BTreeSet<usize>) -> Import {
+    let (conflicts, new) =
+        export.sales.keys().partition(|id| in_use.contains(id));
+
+    Import {
+        export,
+        new,
+        conflicts,
+    }
+}
+
+/// Merge a validated import into `sales`, where `in_use` holds the ids of
+/// all sales including those not loaded, using `next_id` to allocate ids for
+/// sales imported under a new id. Returns the number of sales imported.
+pub fn apply(
+    import: Import,
+    conflict: Conflict,
+    sales: &mut HashMap<usize, Sale>,
+    in_use: &BTreeSet<usize>,
+    mut next_id: impl FnMut() -> usize,
+) -> usize {
+    let mut imported = 0;
+
+    for (id, sale) in import.export.sales {
+        let sale = sale.with_fresh_ids();
+        let id = if in_use.contains(&id) {
+            match conflict {
+                Conflict::Skip => continue,
+                Conflict::Overwrite => id,
+                Conflict::KeepBoth => next_id(),
+            }
+        } else {
+            id
+        };
+        sales.insert(id, sale);
+        imported += 1;
+    }
+
+    imported
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::{Context, Poll, Waker};
+
+    /// Run a save, which writes without waiting on anything.
+    fn run(save: impl Future<Output = Result<(), Error>>) -> Result<(), Error> {
+        let mut cx = Context::from_waker(Waker::noop());
+        match std::pin::pin!(save).poll(&mut cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => panic!("the save waited"),
+        }
+    }
+
+    #[test]
+    fn cells_with_separators_are_quoted() {
+        assert_eq!(csv_escape("Food"), "Food");
+        assert_eq!(csv_escape("Smith, Jane"), "\"Smith, Jane\"");
+        assert_eq!(csv_escape("12\" pizza"), "\"12\"\" pizza\"");
+    }
+
+    #[test]
+    fn a_save_overtaken_by_a_later_one_is_skipped() {
+        let path = std::env::temp_dir()
+            .join(format!("receipts-save-{}.json", std::process::id()));
+        let older = save_json(path.clone(), &"older");
+        let newer = save_json(path.clone(), &"newer");
+
+        run(newer).unwrap();
+        run(older).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "\"newer\"");
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,82 @@
+//! Delivery orders: where they go and the fee for taking them there
+//!
+//! The fee is set in the settings, either flat or by the distance typed on
+//! the sale. The distance isn't looked up from the address: that is left to
+//! whoever takes the order, until a map service is wired in.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::i18n::tr;
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Pricing {
+    #[default]
+    Flat,
+    /// The base fee plus a price per kilometer.
+    ByDistance,
+}
+
+impl Pricing {
+    pub const ALL: [Pricing; 2] = [Pricing::Flat, Pricing::ByDistance];
+}
+
+impl fmt::Display for Pricing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Pricing::Flat => "Flat fee",
+                Pricing::ByDistance => "By distance",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub pricing: Pricing,
+    /// The flat fee, or the base fee when priced by distance.
+    pub fee: f32,
+    pub per_km: f32,
+}
+
+impl Config {
+    /// The fee for delivering `distance` kilometers away.
+    pub fn fee(&self, distance: Option<f32>) -> f32 {
+        match self.pricing {
+            Pricing::Flat => self.fee,
+            Pricing::ByDistance => {
+                self.fee + self.per_km * distance.unwrap_or(0.0).max(0.0)
+            }
+        }
+    }
+}
+
+/// Where a delivery order goes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Delivery {
+    pub address: String,
+    pub phone: String,
+    /// How far away the address is, in kilometers.
+    pub distance: Option<f32>,
+    /// The fee charged, as set up when the sale was last edited.
+    pub fee: f32,
+}
+
+impl Delivery {
+    /// The address and phone number on one line, e.g. for the sale header.
+    pub fn summary(&self) -> String {
+        let address = self.address.split_whitespace().collect::<Vec<_>>();
+        match (address.is_empty(), self.phone.trim()) {
+            (true, "") => tr("No address").to_string(),
+            (false, "") => address.join(" "),
+            (true, phone) => phone.to_string(),
+            (false, phone) => format!("{} • {phone}", address.join(" ")),
+        }
+    }
+}
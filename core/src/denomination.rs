@@ -0,0 +1,218 @@
+//! The notes and coins of a currency, to count the drawer by and to give
+//! change in
+//!
+//! Denominations are kept in cents, so counts add up exactly. Each currency
+//! has its own set, with the usual ones for common currencies built in and
+//! editable in the settings.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::i18n;
+
+/// The largest amount of change worked out piece by piece, in cents: above
+/// it the largest denomination is given until it is reached.
+const LIMIT: u64 = 1_000_000;
+
+/// The 1-2-5 series most currencies follow, for those not built in.
+const SERIES: [u32; 15] = [
+    50000, 20000, 10000, 5000, 2000, 1000, 500, 200, 100, 50, 20, 10, 5, 2, 1,
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The denominations of each currency in cents, by ISO code.
+    pub sets: BTreeMap<String, Vec<u32>>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let sets = [
+            (
+                "USD",
+                &[10000, 5000, 2000, 1000, 500, 100, 25, 10, 5, 1][..],
+            ),
+            (
+                "EUR",
+                &[
+                    20000, 10000, 5000, 2000, 1000, 500, 200, 100, 50, 20, 10,
+                    5, 2, 1,
+                ],
+            ),
+            (
+                "GBP",
+                &[5000, 2000, 1000, 500, 200, 100, 50, 20, 10, 5, 2, 1],
+            ),
+            ("CAD", &[10000, 5000, 2000, 1000, 500, 200, 100, 25, 10, 5]),
+            (
+                "AUD",
+                &[10000, 5000, 2000, 1000, 500, 200, 100, 50, 20, 10, 5],
+            ),
+        ];
+        Self {
+            sets: sets
+                .into_iter()
+                .map(|(code, set)| (code.to_string(), set.to_vec()))
+                .collect(),
+        }
+    }
+}
+
+impl Config {
+    /// The denominations of `code`, the largest first.
+    pub fn of(&self, code: &str) -> Vec<u32> {
+        let mut set = self
+            .sets
+            .get(&code.trim().to_uppercase())
+            .cloned()
+            .unwrap_or_else(|| SERIES.to_vec());
+        set.retain(|cents| *cents > 0);
+        set.sort_unstable_by(|a, b| b.cmp(a));
+        set.dedup();
+        set
+    }
+
+    pub fn add(&mut self, code: &str, cents: u32) {
+        let mut set = self.of(code);
+        if cents > 0 && !set.contains(&cents) {
+            set.push(cents);
+        }
+        self.sets.insert(code.trim().to_uppercase(), set);
+    }
+
+    pub fn remove(&mut self, code: &str, cents: u32) {
+        let mut set = self.of(code);
+        set.retain(|value| *value != cents);
+        self.sets.insert(code.trim().to_uppercase(), set);
+    }
+}
+
+/// An amount in cents, rounded to the nearest one.
+pub fn cents(amount: f32) -> u32 {
+    (amount.max(0.0) * 100.0).round() as u32
+}
+
+/// A denomination as written on it, e.g. `20` or `0.25`.
+pub fn label(cents: u32) -> String {
+    if cents.is_multiple_of(100) {
+        i18n::number(cents as f32 / 100.0, 0)
+    } else {
+        i18n::number(cents as f32 / 100.0, 2)
+    }
+}
+
+/// What `pieces` of each denomination come to, from a count of the drawer.
+pub fn total(pieces: impl IntoIterator<Item = (u32, u32)>) -> f32 {
+    let cents: u64 = pieces
+        .into_iter()
+        .map(|(cents, count)| u64::from(cents) * u64::from(count))
+        .sum();
+    cents as f32 / 100.0
+}
+
+/// How to give `amount` in the fewest pieces of `denominations`, as the
+/// count of each one, the largest first.
+///
+/// When the smallest pieces can't make the amount exactly, e.g. without
+/// pennies, it is the most that can be given without going over.
+pub fn change(amount: f32, denominations: &[u32]) -> Vec<(u32, u32)> {
+    let mut values: Vec<u32> =
+        denominations.iter().copied().filter(|v| *v > 0).collect();
+    values.sort_unstable_by(|a, b| b.cmp(a));
+    values.dedup();
+    let Some(&largest) = values.first() else {
+        return Vec::new();
+    };
+
+    // The smaller pieces never add up to more than this in the fewest, as
+    // enough of any of them is worth fewer of the largest
+    let bound = values[1..]
+        .iter()
+        .map(|value| lcm(u64::from(*value), u64::from(largest)))
+        .sum::<u64>()
+        .min(LIMIT);
+    let mut pieces: BTreeMap<u32, u32> = BTreeMap::new();
+    let mut left = u64::from(cents(amount));
+    if left > bound {
+        let count = (left - bound).div_ceil(u64::from(largest));
+        pieces.insert(largest, count as u32);
+        left -= count * u64::from(largest);
+    }
+
+    // The fewest pieces making each amount up to what is left, and the
+    // piece given last to make it
+    let left = left as usize;
+    let mut fewest = vec![u32::MAX; left + 1];
+    let mut last = vec![0; left + 1];
+    fewest[0] = 0;
+    for amount in 1..=left {
+        for &value in &values {
+            let Some(rest) = amount.checked_sub(value as usize) else {
+                continue;
+            };
+            if fewest[rest] != u32::MAX && fewest[rest] + 1 < fewest[amount] {
+                fewest[amount] = fewest[rest] + 1;
+                last[amount] = value;
+            }
+        }
+    }
+
+    let mut amount = (0..=left)
+        .rev()
+        .find(|amount| fewest[*amount] != u32::MAX)
+        .unwrap_or(0);
+    while amount > 0 {
+        *pieces.entry(last[amount]).or_default() += 1;
+        amount -= last[amount] as usize;
+    }
+    pieces.into_iter().rev().collect()
+}
+
+fn lcm(a: u64, b: u64) -> u64 {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    a / x * b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn change_is_given_in_the_fewest_pieces() {
+        let config = Config::default();
+        let usd = config.of("usd");
+        assert_eq!(
+            change(18.41, &usd),
+            [
+                (1000, 1),
+                (500, 1),
+                (100, 3),
+                (25, 1),
+                (10, 1),
+                (5, 1),
+                (1, 1)
+            ]
+        );
+        assert_eq!(change(0.0, &usd), []);
+        assert_eq!(
+            change(1234.0, &usd),
+            [(10000, 12), (2000, 1), (1000, 1), (100, 4)]
+        );
+
+        // Greedy would give 4 + 1 + 1
+        assert_eq!(change(0.06, &[4, 3, 1]), [(3, 2)]);
+        // Without pennies, as much as can be given
+        assert_eq!(change(0.43, &config.of("CAD")), [(25, 1), (10, 1), (5, 1)]);
+
+        assert_eq!(total(usd.iter().map(|cents| (*cents, 2))), 372.82);
+        assert_eq!(config.of("XYZ"), SERIES);
+        let mut config = config;
+        config.add("usd", 200);
+        config.remove("USD", 1);
+        assert_eq!(config.of("USD")[..6], [10000, 5000, 2000, 1000, 500, 200]);
+        assert_eq!(config.of("USD").last(), Some(&5));
+    }
+}
@@ -0,0 +1,58 @@
+//! Fiscal numbers and signatures for receipts, where the law requires them
+//!
+//! In fiscalized jurisdictions every paid receipt is numbered and signed by a
+//! certified module, and the signature, often as a QR code, printed on it.
+//! Only what is set up and recorded is here: talking to the module is left
+//! to the frontend.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Provider {
+    /// Receipts aren't signed.
+    #[default]
+    Off,
+    /// A stand-in signing with a fixed key, to try fiscal receipts out.
+    Mock,
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Provider::Off => "Off",
+                Provider::Mock => "Mock (for testing)",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The module receipts are signed by when paid.
+    pub provider: Provider,
+}
+
+impl Config {
+    pub fn is_enabled(&self) -> bool {
+        self.provider != Provider::Off
+    }
+}
+
+/// What the fiscal module gave back for a paid receipt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Signature {
+    /// The fiscal number of the receipt, as printed.
+    pub number: String,
+    pub signature: String,
+    /// What the QR code on the receipt holds, if the module asks for one.
+    #[serde(default)]
+    pub qr: Option<String>,
+    pub signed_at: DateTime<Utc>,
+}
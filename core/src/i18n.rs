@@ -0,0 +1,971 @@
+//! Translations of the UI and locale-specific number and date formats
+//!
+//! Strings are looked up by their English text, so any string without a
+//! translation falls back to English. The locale is process-wide rather than
+//! threaded through every view, and is set from the config at startup and
+//! whenever it is changed in settings. A receipt for a customer reading
+//! another language is written [`with`] theirs instead, for the one thread
+//! writing it.
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+static LOCALE: AtomicU8 = AtomicU8::new(0);
+
+thread_local! {
+    /// The locale set for the current thread by [`with`], if any.
+    static OVERRIDE: Cell<Option<Locale>> = const { Cell::new(None) };
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Locale {
+    #[default]
+    English,
+    German,
+    French,
+    Spanish,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 4] = [
+        Locale::English,
+        Locale::German,
+        Locale::French,
+        Locale::Spanish,
+    ];
+
+    /// The code of the language, e.g. "de" for German.
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::German => "de",
+            Locale::French => "fr",
+            Locale::Spanish => "es",
+        }
+    }
+
+    pub fn decimal_separator(self) -> char {
+        match self {
+            Locale::English => '.',
+            Locale::German | Locale::French | Locale::Spanish => ',',
+        }
+    }
+
+    fn date_format(self) -> &'static str {
+        match self {
+            Locale::English => "%Y-%m-%d",
+            Locale::German => "%d.%m.%Y",
+            Locale::French | Locale::Spanish => "%d/%m/%Y",
+        }
+    }
+
+    fn time_format(self) -> &'static str {
+        match self {
+            Locale::English | Locale::German | Locale::Spanish => "%H:%M",
+            Locale::French => "%Hh%M",
+        }
+    }
+
+    fn translate(self, text: &str) -> Option<&'static str> {
+        match self {
+            Locale::English => None,
+            Locale::German => german(text),
+            Locale::French => french(text),
+            Locale::Spanish => spanish(text),
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Locale::English => "English",
+                Locale::German => "Deutsch",
+                Locale::French => "Français",
+                Locale::Spanish => "Español",
+            }
+        )
+    }
+}
+
+pub fn set(locale: Locale) {
+    LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+pub fn locale() -> Locale {
+    OVERRIDE
+        .get()
+        .unwrap_or_else(|| Locale::ALL[LOCALE.load(Ordering::Relaxed) as usize])
+}
+
+/// Run `f` in `locale` rather than the one set, e.g. to write a receipt in
+/// the language of its customer. Only the current thread is affected.
+pub fn with<T>(locale: Locale, f: impl FnOnce() -> T) -> T {
+    let outer = OVERRIDE.replace(Some(locale));
+    let result = f();
+    OVERRIDE.set(outer);
+    result
+}
+
+/// `text` in the current locale.
+pub fn tr(text: &'static str) -> &'static str {
+    locale().translate(text).unwrap_or(text)
+}
+
+/// Like [`tr`] for text not known until run time, e.g. the text of a
+/// receipt layout, which is kept as is if it has no translation.
+pub fn translate(text: &str) -> &str {
+    locale().translate(text).unwrap_or(text)
+}
+
+/// `value` with `decimals` decimals, e.g. `12,5` in German.
+pub fn number(value: f32, decimals: usize) -> String {
+    let number = format!("{:.*}", decimals, value);
+    match locale().decimal_separator() {
+        '.' => number,
+        separator => number.replace('.', &separator.to_string()),
+    }
+}
+
+/// An amount of money, e.g. `$12.50` or `12,50 $`.
+pub fn money(amount: f32) -> String {
+    let sign = if amount < 0.0 { "-" } else { "" };
+    let amount = number(amount.abs(), 2);
+    match locale() {
+        Locale::English => format!("{sign}${amount}"),
+        Locale::German | Locale::French | Locale::Spanish => {
+            format!("{sign}{amount} $")
+        }
+    }
+}
+
+/// Parse a number typed with either a comma or a dot as the decimal
+/// separator, so `12,5` and `12.5` both work whatever the locale.
+///
+/// A separator used more than once, or the one of two different separators
+/// that comes first, groups thousands, as in `1.234,50` or `1,234,567`. A
+/// lone separator is only taken as grouping when it is the locale's
+/// grouping separator followed by three digits, e.g. `1,234` in English,
+/// and not after a lone zero, so `0.500` is a half in German too.
+pub fn parse_number(input: &str) -> Option<f32> {
+    let input = input.trim();
+    let decimal = match (input.rfind('.'), input.rfind(',')) {
+        (Some(dot), Some(comma)) if dot > comma => Some('.'),
+        (Some(_), Some(_)) => Some(','),
+        (Some(_), None) => Some('.'),
+        (None, Some(_)) => Some(','),
+        (None, None) => None,
+    }
+    .filter(|separator| input.matches(*separator).count() == 1)
+    .filter(|separator| {
+        let mut parts = input.split(*separator);
+        let whole = parts.next().unwrap_or_default();
+        let grouping = *separator != locale().decimal_separator()
+            && !input.contains(locale().decimal_separator())
+            && !whole
+                .trim_start_matches(['-', '+'])
+                .trim_start_matches('0')
+                .is_empty()
+            && parts.next().is_some_and(|decimals| decimals.len() == 3);
+        !grouping
+    });
+
+    let number: String = input
+        .chars()
+        .filter_map(|c| match c {
+            '.' | ',' if Some(c) == decimal => Some('.'),
+            '.' | ',' => None,
+            c => Some(c),
+        })
+        .collect();
+    number.parse().ok()
+}
+
+pub fn date(at: &DateTime<Utc>) -> String {
+    day(at.date_naive())
+}
+
+pub fn day(day: NaiveDate) -> String {
+    day.format(locale().date_format()).to_string()
+}
+
+/// The time of day of `at`, e.g. `18:42`, or `18h42` in French.
+pub fn time(at: &DateTime<Utc>) -> String {
+    at.format(locale().time_format()).to_string()
+}
+
+fn german(text: &str) -> Option<&'static str> {
+    Some(match text {
+        "Loading sales…" => "Verkäufe werden geladen…",
+        "Loading sale…" => "Verkauf wird geladen…",
+        "Create your first sale →" => "Ersten Verkauf anlegen →",
+        "Settings" => "Einstellungen",
+        "Reports" => "Berichte",
+        "Gift cards" => "Gutscheine",
+        "Catalog" => "Katalog",
+        "New Sale" => "Neuer Verkauf",
+        "Sales" => "Verkäufe",
+        "Templates" => "Vorlagen",
+        "Total" => "Gesamt",
+        "item(s)" => "Artikel",
+        "sale(s)" => "Verkäufe",
+        "Jump to date" => "Zum Datum springen",
+        "Load more" => "Mehr laden",
+        "older" => "ältere",
+        "Use" => "Verwenden",
+        "Pager" => "Pager",
+        "Copy ticket" => "Bon kopieren",
+        "Copy receipt" => "Beleg kopieren",
+        "Save as template" => "Als Vorlage speichern",
+        "Copy as JSON" => "Als JSON kopieren",
+        "Save as JSON" => "Als JSON speichern",
+        "Order ready" => "Bestellung fertig",
+        "Picked up" => "Abgeholt",
+        "Edit" => "Bearbeiten",
+        "Item Name" => "Artikel",
+        "Qty" => "Menge",
+        "Price" => "Preis",
+        "Tax Group" => "Steuergruppe",
+        "Subtotal" => "Zwischensumme",
+        "Service Charge" => "Servicegebühr",
+        "Tax" => "Steuer",
+        "Gratuity" => "Trinkgeld",
+        "Gift card" => "Gutschein",
+        "Due" => "Offen",
+        "Apply" => "Anwenden",
+        "Cancel" => "Abbrechen",
+        "Adjust" => "Ändern",
+        "Retry" => "Erneut versuchen",
+        "Sale Name" => "Name des Verkaufs",
+        "Save" => "Speichern",
+        "No section" => "Kein Abschnitt",
+        "Section" => "Abschnitt",
+        "Section name" => "Name des Abschnitts",
+        "Untitled section" => "Unbenannter Abschnitt",
+        "+ Add Item" => "+ Artikel",
+        "+ Add Section" => "+ Abschnitt",
+        "+ Item" => "+ Artikel",
+        "+ Gift Card" => "+ Gutschein",
+        "+ Gift card payment" => "+ Zahlung mit Gutschein",
+        "+ From catalog…" => "+ Aus dem Katalog…",
+        "Choose…" => "Auswählen…",
+        "Card code" => "Gutscheincode",
+        "Item name" => "Artikelname",
+        "Quantity" => "Menge",
+        "selected" => "ausgewählt",
+        "Remove selected" => "Ausgewählte entfernen",
+        "Duplicate line" => "Zeile duplizieren",
+        "Top" => "Beliebt",
+        "No tax" => "Steuerfrei",
+        "Comps and adjustments can't take the total below zero" => "Nachlässe und Korrekturen dürfen die Summe nicht unter null bringen",
+        "waiting to send" => "warten auf Versand",
+        "Pay by card" => "Mit Karte zahlen",
+        "Paid by card" => "Mit Karte bezahlt",
+        "Waiting for card…" => "Warte auf Karte…",
+        "Archive" => "Archiv",
+        "Pick a month to see its sales" => {
+            "Monat wählen, um seine Verkäufe zu sehen"
+        }
+        "Red" => "Rot",
+        "Orange" => "Orange",
+        "Yellow" => "Gelb",
+        "Green" => "Grün",
+        "Blue" => "Blau",
+        "Purple" => "Lila",
+        "Add a tag" => "Tag hinzufügen",
+        "All tags" => "Alle Tags",
+        "Per guest" => "Pro Gast",
+        "Split evenly" => "Gleichmäßig geteilt",
+        "Guests" => "Gäste",
+        "guests" => "Gäste",
+        "Auto:" => "Automatisch:",
+        "Use rule" => "Regel verwenden",
+        "All orders" => "Alle Bestellungen",
+        "Quantity prices" => "Mengenpreise",
+        "Reorder" => "Nachbestellen",
+        "Split from" => "Geteilt aus",
+        "Split into" => "Geteilt in",
+        "Refund of" => "Erstattung von",
+        "Refunded by" => "Erstattet durch",
+        "Reorder of" => "Nachbestellung von",
+        "Reordered as" => "Nachbestellt als",
+        "Return" => "Rückgabe",
+        "Cash drawer" => "Kassenlade",
+        "Shifts" => "Schichten",
+        "Paid cash" => "Bar bezahlt",
+        "Print…" => "Drucken…",
+        "Preview" => "Vorschau",
+        "Done" => "Fertig",
+        "Item" => "Artikel",
+        "Allergy note" => "Allergiehinweis",
+        "Allergy" => "Allergie",
+        "Gluten" => "Gluten",
+        "Dairy" => "Milch",
+        "Eggs" => "Eier",
+        "Peanuts" => "Erdnüsse",
+        "Tree nuts" => "Schalenfrüchte",
+        "Soy" => "Soja",
+        "Fish" => "Fisch",
+        "Shellfish" => "Krebstiere",
+        "Sesame" => "Sesam",
+        "No tare" => "Ohne Tara",
+        "Tare" => "Tara",
+        "Net" => "Netto",
+        "Dine in" => "Vor Ort",
+        "Takeout" => "Zum Mitnehmen",
+        "Delivery" => "Lieferung",
+        "Upcoming" => "Anstehend",
+        "Recurring sales" => "Wiederkehrende Verkäufe",
+        "Invoice" => "Rechnung",
+        "Invoice to" => "Rechnung an",
+        "Customer" => "Kunde",
+        "Customers" => "Kunden",
+        "Bill to" => "Rechnungsempfänger",
+        "Pay in" => "Zahlen in",
+        "Paid in" => "Bezahlt in",
+        "Currency" => "Währung",
+        "Take payment" => "Zahlung annehmen",
+        "Change" => "Wechselgeld",
+        "change" => "Wechselgeld",
+        "Not enough" => "Nicht genug",
+        "Cash tendered" => "Gegeben",
+        "Quick sale" => "Schnellverkauf",
+        "Save and pay" => "Speichern und kassieren",
+        "Read scale" => "Waage ablesen",
+        "Email receipt" => "Beleg per E-Mail senden",
+        "Receipt history" => "Belegverlauf",
+        "Fiscal no." => "Fiskalnr.",
+        "Delete" => "Löschen",
+        "Trash" => "Papierkorb",
+        "Empty trash" => "Papierkorb leeren",
+        "Deleted sales are purged for good after" => "Gelöschte Verkäufe werden endgültig entfernt nach",
+        "days" => "Tagen",
+        "Deleted sales are kept until purged" => "Gelöschte Verkäufe bleiben bis zum Entfernen erhalten",
+        "The trash is empty" => "Der Papierkorb ist leer",
+        "deleted" => "gelöscht",
+        "Restore" => "Wiederherstellen",
+        "Purge" => "Entfernen",
+        "sale(s) for good? This can't be undone." => "Verkäufe endgültig? Das lässt sich nicht rückgängig machen.",
+        "Sale changed elsewhere" => "Verkauf anderswo geändert",
+        "Keep mine" => "Meine behalten",
+        "Keep theirs" => "Andere behalten",
+        "Merge items" => "Positionen zusammenführen",
+        "The sale was changed elsewhere while you edited it, e.g. on the \
+             order board or by a sync. Merging keeps the lines added and \
+             removed on both sides." => {
+            "Der Verkauf wurde während Ihrer Bearbeitung anderswo geändert, \
+             z. B. an der Bestellanzeige oder durch eine Synchronisierung. \
+             Beim Zusammenführen bleiben die auf beiden Seiten \
+             hinzugefügten und entfernten Positionen erhalten."
+        }
+        "Only the lines differ" => "Nur die Positionen unterscheiden sich",
+        "Mine" => "Meine",
+        "Theirs" => "Andere",
+        "No lines changed" => "Keine Positionen geändert",
+        "Keyboard shortcuts" => "Tastenkürzel",
+        "Show or hide these shortcuts" => "Diese Tastenkürzel ein- oder ausblenden",
+        "Open diagnostics" => "Diagnose öffnen",
+        "Close a dialog, or go back" => "Dialog schließen oder zurück",
+        "Next field" => "Nächstes Feld",
+        "Previous field" => "Vorheriges Feld",
+        "Save and close" => "Speichern und schließen",
+        "Save the sale" => "Verkauf speichern",
+        "Back to the sales" => "Zurück zu den Verkäufen",
+        "Back to the sale" => "Zurück zum Verkauf",
+        "Back to settings" => "Zurück zu den Einstellungen",
+        "Back to the customers" => "Zurück zu den Kunden",
+        "Cancel purging, or go back" => "Löschen abbrechen oder zurück",
+        "Close the keyboard, or go back" => "Tastatur schließen oder zurück",
+        "Close" => "Schließen",
+        "Void" => "Storno",
+        "Reason, e.g. sent back" => "Grund, z. B. zurückgegangen",
+        "Manager PIN" => "Manager-PIN",
+        "Catalog price" => "Katalogpreis",
+        "Approve" => "Freigeben",
+        "Approve price overrides" => "Preisänderungen freigeben",
+        "Some prices were changed from the catalog price by more than \
+         allowed without a manager." => {
+            "Einige Preise weichen weiter vom Katalogpreis ab, als ohne \
+             Manager erlaubt ist."
+        }
+        "The line stays on the sale, struck through, and is left out of \
+         the totals." => {
+            "Die Position bleibt durchgestrichen auf dem Verkauf und zählt \
+             nicht mehr zu den Summen."
+        }
+        "Printed" => "Gedruckt",
+        "Emailed to" => "Gemailt an",
+        "No lines yet" => "Noch keine Zeilen",
+        "Add a line to the sale first" => "Zuerst eine Zeile hinzufügen",
+        "Enter adds the line • Ctrl+Enter saves and takes payment • \
+             Products in the catalog need no price" => {
+            "Enter fügt die Zeile hinzu • Strg+Enter speichert und kassiert • \
+             Katalogprodukte brauchen keinen Preis"
+        }
+        "Pick the currency paid in" => "Die Währung der Zahlung auswählen",
+        "No customer" => "Kein Kunde",
+        "issued" => "ausgestellt",
+        "due" => "fällig",
+        "Overdue" => "Überfällig",
+        "Payment" => "Zahlung",
+        "Balance" => "Restbetrag",
+        "Record payment" => "Zahlung erfassen",
+        "Export statement" => "Kontoauszug exportieren",
+        "That is more than the balance" => "Das ist mehr als der Restbetrag",
+        "Due at" => "Fällig um",
+        "Type the due time as e.g. 2024-03-16 11:00" =>
+            "Fälligkeit z. B. als 2024-03-16 11:00 eingeben",
+        "Delivery fee" => "Liefergebühr",
+        "Address" => "Adresse",
+        "Phone" => "Telefon",
+        "No address" => "Keine Adresse",
+        "Food (8%)" => "Speisen (8%)",
+        "Alcohol (10%)" => "Alkohol (10%)",
+        "Non-taxable" => "Steuerfrei",
+        "Other (8%)" => "Sonstiges (8%)",
+        "RECEIPT" => "BELEG",
+        "Receipt" => "Beleg",
+        "New sale" => "Neuer Verkauf",
+        "Service charge" => "Servicegebühr",
+        "TOTAL" => "SUMME",
+        "DUE" => "OFFEN",
+        "BALANCE" => "RESTBETRAG",
+        "VOID" => "STORNO",
+        "Paid" => "Bezahlt",
+        "at" => "zu",
+        "Tax ID" => "USt-IdNr.",
+        "Customer tax ID" => "USt-IdNr. Kunde",
+        _ => return None,
+    })
+}
+
+fn french(text: &str) -> Option<&'static str> {
+    Some(match text {
+        "Loading sales…" => "Chargement des ventes…",
+        "Loading sale…" => "Chargement de la vente…",
+        "Create your first sale →" => "Créer votre première vente →",
+        "Settings" => "Réglages",
+        "Reports" => "Rapports",
+        "Gift cards" => "Cartes cadeaux",
+        "Catalog" => "Catalogue",
+        "New Sale" => "Nouvelle vente",
+        "Sales" => "Ventes",
+        "Templates" => "Modèles",
+        "Total" => "Total",
+        "item(s)" => "article(s)",
+        "sale(s)" => "vente(s)",
+        "Jump to date" => "Aller à la date",
+        "Load more" => "Charger plus",
+        "older" => "plus anciennes",
+        "Use" => "Utiliser",
+        "Pager" => "Bipeur",
+        "Copy ticket" => "Copier le bon",
+        "Copy receipt" => "Copier le reçu",
+        "Save as template" => "Enregistrer comme modèle",
+        "Copy as JSON" => "Copier en JSON",
+        "Save as JSON" => "Enregistrer en JSON",
+        "Order ready" => "Commande prête",
+        "Picked up" => "Récupérée",
+        "Edit" => "Modifier",
+        "Item Name" => "Article",
+        "Qty" => "Qté",
+        "Price" => "Prix",
+        "Tax Group" => "Groupe de taxe",
+        "Subtotal" => "Sous-total",
+        "Service Charge" => "Service",
+        "Tax" => "Taxe",
+        "Gratuity" => "Pourboire",
+        "Gift card" => "Carte cadeau",
+        "Due" => "Reste dû",
+        "Apply" => "Appliquer",
+        "Cancel" => "Annuler",
+        "Adjust" => "Ajuster",
+        "Retry" => "Réessayer",
+        "Sale Name" => "Nom de la vente",
+        "Save" => "Enregistrer",
+        "No section" => "Aucune section",
+        "Section" => "Section",
+        "Section name" => "Nom de la section",
+        "Untitled section" => "Section sans nom",
+        "+ Add Item" => "+ Article",
+        "+ Add Section" => "+ Section",
+        "+ Item" => "+ Article",
+        "+ Gift Card" => "+ Carte cadeau",
+        "+ Gift card payment" => "+ Paiement par carte cadeau",
+        "+ From catalog…" => "+ Depuis le catalogue…",
+        "Choose…" => "Choisir…",
+        "Card code" => "Code de la carte",
+        "Item name" => "Nom de l'article",
+        "Quantity" => "Quantité",
+        "selected" => "sélectionné(s)",
+        "Remove selected" => "Retirer la sélection",
+        "Duplicate line" => "Dupliquer la ligne",
+        "Top" => "Populaires",
+        "No tax" => "Sans taxe",
+        "Comps and adjustments can't take the total below zero" => {
+            "Les remises et ajustements ne peuvent pas rendre le total négatif"
+        }
+        "waiting to send" => "en attente d'envoi",
+        "Pay by card" => "Payer par carte",
+        "Paid by card" => "Payé par carte",
+        "Waiting for card…" => "En attente de la carte…",
+        "Archive" => "Archives",
+        "Pick a month to see its sales" => {
+            "Choisissez un mois pour voir ses ventes"
+        }
+        "Red" => "Rouge",
+        "Orange" => "Orange",
+        "Yellow" => "Jaune",
+        "Green" => "Vert",
+        "Blue" => "Bleu",
+        "Purple" => "Violet",
+        "Add a tag" => "Ajouter une étiquette",
+        "All tags" => "Toutes les étiquettes",
+        "Per guest" => "Par convive",
+        "Split evenly" => "Partage égal",
+        "Guests" => "Convives",
+        "guests" => "convives",
+        "Auto:" => "Auto :",
+        "Use rule" => "Appliquer la règle",
+        "All orders" => "Toutes les commandes",
+        "Quantity prices" => "Prix par quantité",
+        "Reorder" => "Recommander",
+        "Split from" => "Séparé de",
+        "Split into" => "Séparé en",
+        "Refund of" => "Remboursement de",
+        "Refunded by" => "Remboursé par",
+        "Reorder of" => "Nouvelle commande de",
+        "Reordered as" => "Recommandé en",
+        "Return" => "Retour",
+        "Cash drawer" => "Tiroir-caisse",
+        "Shifts" => "Services",
+        "Paid cash" => "Payé en espèces",
+        "Print…" => "Imprimer…",
+        "Preview" => "Aperçu",
+        "Done" => "Terminé",
+        "Item" => "Article",
+        "Allergy note" => "Note d'allergie",
+        "Allergy" => "Allergie",
+        "Gluten" => "Gluten",
+        "Dairy" => "Lait",
+        "Eggs" => "Œufs",
+        "Peanuts" => "Arachides",
+        "Tree nuts" => "Fruits à coque",
+        "Soy" => "Soja",
+        "Fish" => "Poisson",
+        "Shellfish" => "Crustacés",
+        "Sesame" => "Sésame",
+        "No tare" => "Sans tare",
+        "Tare" => "Tare",
+        "Net" => "Net",
+        "Dine in" => "Sur place",
+        "Takeout" => "À emporter",
+        "Delivery" => "Livraison",
+        "Upcoming" => "À venir",
+        "Recurring sales" => "Ventes récurrentes",
+        "Invoice" => "Facture",
+        "Invoice to" => "Facture à",
+        "Customer" => "Client",
+        "Customers" => "Clients",
+        "Bill to" => "Facturer à",
+        "Pay in" => "Payer en",
+        "Paid in" => "Payé en",
+        "Currency" => "Devise",
+        "Take payment" => "Encaisser",
+        "Change" => "Monnaie rendue",
+        "change" => "monnaie",
+        "Not enough" => "Insuffisant",
+        "Cash tendered" => "Espèces reçues",
+        "Quick sale" => "Vente rapide",
+        "Save and pay" => "Enregistrer et encaisser",
+        "Read scale" => "Lire la balance",
+        "Email receipt" => "Envoyer le reçu par e-mail",
+        "Receipt history" => "Historique du reçu",
+        "Fiscal no." => "N° fiscal",
+        "Delete" => "Supprimer",
+        "Trash" => "Corbeille",
+        "Empty trash" => "Vider la corbeille",
+        "Deleted sales are purged for good after" => {
+            "Les ventes supprimées sont effacées définitivement après"
+        }
+        "days" => "jours",
+        "Deleted sales are kept until purged" => {
+            "Les ventes supprimées sont gardées jusqu'à leur effacement"
+        }
+        "The trash is empty" => "La corbeille est vide",
+        "deleted" => "supprimée",
+        "Restore" => "Restaurer",
+        "Purge" => "Effacer",
+        "sale(s) for good? This can't be undone." => {
+            "vente(s) définitivement ? C'est irréversible."
+        }
+        "Sale changed elsewhere" => "Vente modifiée ailleurs",
+        "Keep mine" => "Garder la mienne",
+        "Keep theirs" => "Garder l'autre",
+        "Merge items" => "Fusionner les articles",
+        "The sale was changed elsewhere while you edited it, e.g. on the \
+             order board or by a sync. Merging keeps the lines added and \
+             removed on both sides." => {
+            "La vente a été modifiée ailleurs pendant votre modification, \
+             par ex. sur l'écran des commandes ou par une synchronisation. \
+             La fusion garde les lignes ajoutées et retirées des deux côtés."
+        }
+        "Only the lines differ" => "Seules les lignes diffèrent",
+        "Mine" => "La mienne",
+        "Theirs" => "L'autre",
+        "No lines changed" => "Aucune ligne modifiée",
+        "Keyboard shortcuts" => "Raccourcis clavier",
+        "Show or hide these shortcuts" => "Afficher ou masquer ces raccourcis",
+        "Open diagnostics" => "Ouvrir le diagnostic",
+        "Close a dialog, or go back" => {
+            "Fermer une boîte de dialogue ou revenir"
+        }
+        "Next field" => "Champ suivant",
+        "Previous field" => "Champ précédent",
+        "Save and close" => "Enregistrer et fermer",
+        "Save the sale" => "Enregistrer la vente",
+        "Back to the sales" => "Retour aux ventes",
+        "Back to the sale" => "Retour à la vente",
+        "Back to settings" => "Retour aux paramètres",
+        "Back to the customers" => "Retour aux clients",
+        "Cancel purging, or go back" => "Annuler la purge ou revenir",
+        "Close the keyboard, or go back" => "Fermer le clavier ou revenir",
+        "Close" => "Fermer",
+        "Void" => "Annulation",
+        "Reason, e.g. sent back" => "Motif, p. ex. renvoyé",
+        "Manager PIN" => "PIN du responsable",
+        "Catalog price" => "Prix du catalogue",
+        "Approve" => "Approuver",
+        "Approve price overrides" => "Approuver les changements de prix",
+        "Some prices were changed from the catalog price by more than \
+         allowed without a manager." => {
+            "Certains prix s'écartent du prix du catalogue plus qu'autorisé \
+             sans responsable."
+        }
+        "The line stays on the sale, struck through, and is left out of \
+         the totals." => {
+            "La ligne reste sur la vente, barrée, et n'est plus comptée dans \
+             les totaux."
+        }
+        "Printed" => "Imprimé",
+        "Emailed to" => "Envoyé à",
+        "No lines yet" => "Aucune ligne",
+        "Add a line to the sale first" => "Ajoutez d'abord une ligne",
+        "Enter adds the line • Ctrl+Enter saves and takes payment • \
+             Products in the catalog need no price" => {
+            "Entrée ajoute la ligne • Ctrl+Entrée enregistre et encaisse • \
+             Les produits du catalogue n'ont pas besoin de prix"
+        }
+        "Pick the currency paid in" => "Choisissez la devise du paiement",
+        "No customer" => "Aucun client",
+        "issued" => "émise le",
+        "due" => "échéance",
+        "Overdue" => "En retard",
+        "Payment" => "Paiement",
+        "Balance" => "Solde",
+        "Record payment" => "Enregistrer le paiement",
+        "Export statement" => "Exporter le relevé",
+        "That is more than the balance" => "C'est plus que le solde",
+        "Due at" => "Prévu le",
+        "Type the due time as e.g. 2024-03-16 11:00" => {
+            "Saisissez l'échéance, p. ex. 2024-03-16 11:00"
+        }
+        "Delivery fee" => "Frais de livraison",
+        "Address" => "Adresse",
+        "Phone" => "Téléphone",
+        "No address" => "Pas d'adresse",
+        "Food (8%)" => "Alimentation (8%)",
+        "Alcohol (10%)" => "Alcool (10%)",
+        "Non-taxable" => "Non taxable",
+        "Other (8%)" => "Autre (8%)",
+        "RECEIPT" => "REÇU",
+        "Receipt" => "Reçu",
+        "New sale" => "Nouvelle vente",
+        "Service charge" => "Service",
+        "TOTAL" => "TOTAL",
+        "DUE" => "RESTE DÛ",
+        "BALANCE" => "SOLDE",
+        "VOID" => "ANNULÉ",
+        "Paid" => "Payé",
+        "at" => "à",
+        "Tax ID" => "N° TVA",
+        "Customer tax ID" => "N° TVA client",
+        _ => return None,
+    })
+}
+
+fn spanish(text: &str) -> Option<&'static str> {
+    Some(match text {
+        "Loading sales…" => "Cargando ventas…",
+        "Loading sale…" => "Cargando venta…",
+        "Create your first sale →" => "Crea tu primera venta →",
+        "Settings" => "Ajustes",
+        "Reports" => "Informes",
+        "Gift cards" => "Tarjetas regalo",
+        "Catalog" => "Catálogo",
+        "New Sale" => "Nueva venta",
+        "Sales" => "Ventas",
+        "Templates" => "Plantillas",
+        "Total" => "Total",
+        "item(s)" => "artículo(s)",
+        "sale(s)" => "venta(s)",
+        "Jump to date" => "Ir a la fecha",
+        "Load more" => "Cargar más",
+        "older" => "anteriores",
+        "Use" => "Usar",
+        "Pager" => "Localizador",
+        "Copy ticket" => "Copiar comanda",
+        "Copy receipt" => "Copiar recibo",
+        "Save as template" => "Guardar como plantilla",
+        "Copy as JSON" => "Copiar como JSON",
+        "Save as JSON" => "Guardar como JSON",
+        "Order ready" => "Pedido listo",
+        "Picked up" => "Recogido",
+        "Edit" => "Editar",
+        "Item Name" => "Artículo",
+        "Qty" => "Cant.",
+        "Price" => "Precio",
+        "Tax Group" => "Grupo de impuesto",
+        "Subtotal" => "Subtotal",
+        "Service Charge" => "Servicio",
+        "Tax" => "Impuesto",
+        "Gratuity" => "Propina",
+        "Gift card" => "Tarjeta regalo",
+        "Due" => "Pendiente",
+        "Apply" => "Aplicar",
+        "Cancel" => "Cancelar",
+        "Adjust" => "Ajustar",
+        "Retry" => "Reintentar",
+        "Sale Name" => "Nombre de la venta",
+        "Save" => "Guardar",
+        "No section" => "Sin sección",
+        "Section" => "Sección",
+        "Section name" => "Nombre de la sección",
+        "Untitled section" => "Sección sin nombre",
+        "+ Add Item" => "+ Artículo",
+        "+ Add Section" => "+ Sección",
+        "+ Item" => "+ Artículo",
+        "+ Gift Card" => "+ Tarjeta regalo",
+        "+ Gift card payment" => "+ Pago con tarjeta regalo",
+        "+ From catalog…" => "+ Del catálogo…",
+        "Choose…" => "Elegir…",
+        "Card code" => "Código de tarjeta",
+        "Item name" => "Nombre del artículo",
+        "Quantity" => "Cantidad",
+        "selected" => "seleccionado(s)",
+        "Remove selected" => "Quitar seleccionados",
+        "Duplicate line" => "Duplicar línea",
+        "Top" => "Populares",
+        "No tax" => "Sin impuesto",
+        "Comps and adjustments can't take the total below zero" => "Las invitaciones y ajustes no pueden dejar el total por debajo de cero",
+        "waiting to send" => "pendientes de envío",
+        "Pay by card" => "Pagar con tarjeta",
+        "Paid by card" => "Pagado con tarjeta",
+        "Waiting for card…" => "Esperando la tarjeta…",
+        "Archive" => "Archivo",
+        "Pick a month to see its sales" => "Elija un mes para ver sus ventas",
+        "Red" => "Rojo",
+        "Orange" => "Naranja",
+        "Yellow" => "Amarillo",
+        "Green" => "Verde",
+        "Blue" => "Azul",
+        "Purple" => "Morado",
+        "Add a tag" => "Añadir etiqueta",
+        "All tags" => "Todas las etiquetas",
+        "Per guest" => "Por comensal",
+        "Split evenly" => "A partes iguales",
+        "Guests" => "Comensales",
+        "guests" => "comensales",
+        "Auto:" => "Automático:",
+        "Use rule" => "Usar regla",
+        "All orders" => "Todos los pedidos",
+        "Quantity prices" => "Precios por cantidad",
+        "Reorder" => "Volver a pedir",
+        "Split from" => "Dividida de",
+        "Split into" => "Dividida en",
+        "Refund of" => "Reembolso de",
+        "Refunded by" => "Reembolsada por",
+        "Reorder of" => "Nuevo pedido de",
+        "Reordered as" => "Pedida de nuevo como",
+        "Return" => "Devolución",
+        "Cash drawer" => "Cajón de efectivo",
+        "Shifts" => "Turnos",
+        "Paid cash" => "Pagado en efectivo",
+        "Print…" => "Imprimir…",
+        "Preview" => "Vista previa",
+        "Done" => "Listo",
+        "Item" => "Artículo",
+        "Allergy note" => "Nota de alergia",
+        "Allergy" => "Alergia",
+        "Gluten" => "Gluten",
+        "Dairy" => "Lácteos",
+        "Eggs" => "Huevos",
+        "Peanuts" => "Cacahuetes",
+        "Tree nuts" => "Frutos de cáscara",
+        "Soy" => "Soja",
+        "Fish" => "Pescado",
+        "Shellfish" => "Crustáceos",
+        "Sesame" => "Sésamo",
+        "No tare" => "Sin tara",
+        "Tare" => "Tara",
+        "Net" => "Neto",
+        "Dine in" => "Para comer aquí",
+        "Takeout" => "Para llevar",
+        "Delivery" => "Entrega a domicilio",
+        "Upcoming" => "Próximos",
+        "Recurring sales" => "Ventas recurrentes",
+        "Invoice" => "Factura",
+        "Invoice to" => "Factura a",
+        "Customer" => "Cliente",
+        "Customers" => "Clientes",
+        "Bill to" => "Facturar a",
+        "Pay in" => "Pagar en",
+        "Paid in" => "Pagado en",
+        "Currency" => "Moneda",
+        "Take payment" => "Cobrar",
+        "Change" => "Cambio",
+        "change" => "cambio",
+        "Not enough" => "Insuficiente",
+        "Cash tendered" => "Efectivo entregado",
+        "Quick sale" => "Venta rápida",
+        "Save and pay" => "Guardar y cobrar",
+        "Read scale" => "Leer la báscula",
+        "Email receipt" => "Enviar recibo por correo",
+        "Receipt history" => "Historial del recibo",
+        "Fiscal no." => "N.º fiscal",
+        "Delete" => "Eliminar",
+        "Trash" => "Papelera",
+        "Empty trash" => "Vaciar la papelera",
+        "Deleted sales are purged for good after" => "Las ventas eliminadas se borran definitivamente tras",
+        "days" => "días",
+        "Deleted sales are kept until purged" => "Las ventas eliminadas se guardan hasta borrarlas",
+        "The trash is empty" => "La papelera está vacía",
+        "deleted" => "eliminada",
+        "Restore" => "Restaurar",
+        "Purge" => "Borrar",
+        "sale(s) for good? This can't be undone." => "venta(s) definitivamente? No se puede deshacer.",
+        "Sale changed elsewhere" => "Venta modificada en otro lugar",
+        "Keep mine" => "Conservar la mía",
+        "Keep theirs" => "Conservar la otra",
+        "Merge items" => "Combinar artículos",
+        "The sale was changed elsewhere while you edited it, e.g. on the \
+             order board or by a sync. Merging keeps the lines added and \
+             removed on both sides." => {
+            "La venta se modificó en otro lugar mientras la editaba, p. ej. \
+             en el tablero de pedidos o por una sincronización. Al combinar \
+             se conservan las líneas añadidas y quitadas en ambos lados."
+        }
+        "Only the lines differ" => "Solo difieren las líneas",
+        "Mine" => "La mía",
+        "Theirs" => "La otra",
+        "No lines changed" => "Ninguna línea cambiada",
+        "Keyboard shortcuts" => "Atajos de teclado",
+        "Show or hide these shortcuts" => "Mostrar u ocultar estos atajos",
+        "Open diagnostics" => "Abrir diagnóstico",
+        "Close a dialog, or go back" => "Cerrar un diálogo o volver",
+        "Next field" => "Campo siguiente",
+        "Previous field" => "Campo anterior",
+        "Save and close" => "Guardar y cerrar",
+        "Save the sale" => "Guardar la venta",
+        "Back to the sales" => "Volver a las ventas",
+        "Back to the sale" => "Volver a la venta",
+        "Back to settings" => "Volver a los ajustes",
+        "Back to the customers" => "Volver a los clientes",
+        "Cancel purging, or go back" => "Cancelar la purga o volver",
+        "Close the keyboard, or go back" => "Cerrar el teclado o volver",
+        "Close" => "Cerrar",
+        "Void" => "Anulación",
+        "Reason, e.g. sent back" => "Motivo, p. ej. devuelto",
+        "Manager PIN" => "PIN de encargado",
+        "Catalog price" => "Precio de catálogo",
+        "Approve" => "Aprobar",
+        "Approve price overrides" => "Aprobar cambios de precio",
+        "Some prices were changed from the catalog price by more than \
+         allowed without a manager." => {
+            "Algunos precios se apartan del precio de catálogo más de lo \
+             permitido sin encargado."
+        }
+        "The line stays on the sale, struck through, and is left out of \
+         the totals." => {
+            "La línea queda en la venta, tachada, y deja de contar en los \
+             totales."
+        }
+        "Printed" => "Impreso",
+        "Emailed to" => "Enviado a",
+        "No lines yet" => "Aún no hay líneas",
+        "Add a line to the sale first" => "Añade primero una línea",
+        "Enter adds the line • Ctrl+Enter saves and takes payment • \
+             Products in the catalog need no price" => {
+            "Intro añade la línea • Ctrl+Intro guarda y cobra • \
+             Los productos del catálogo no necesitan precio"
+        }
+        "Pick the currency paid in" => "Elige la moneda del pago",
+        "No customer" => "Sin cliente",
+        "issued" => "emitida el",
+        "due" => "vence",
+        "Overdue" => "Vencida",
+        "Payment" => "Pago",
+        "Balance" => "Saldo",
+        "Record payment" => "Registrar pago",
+        "Export statement" => "Exportar estado de cuenta",
+        "That is more than the balance" => "Es más que el saldo",
+        "Due at" => "Para el",
+        "Type the due time as e.g. 2024-03-16 11:00" =>
+            "Escribe la hora, p. ej. 2024-03-16 11:00",
+        "Delivery fee" => "Gastos de envío",
+        "Address" => "Dirección",
+        "Phone" => "Teléfono",
+        "No address" => "Sin dirección",
+        "Food (8%)" => "Comida (8%)",
+        "Alcohol (10%)" => "Alcohol (10%)",
+        "Non-taxable" => "Exento",
+        "Other (8%)" => "Otros (8%)",
+        "RECEIPT" => "RECIBO",
+        "Receipt" => "Recibo",
+        "New sale" => "Nueva venta",
+        "Service charge" => "Servicio",
+        "TOTAL" => "TOTAL",
+        "DUE" => "PENDIENTE",
+        "BALANCE" => "SALDO",
+        "VOID" => "ANULADO",
+        "Paid" => "Pagado",
+        "at" => "a",
+        "Tax ID" => "NIF",
+        "Customer tax ID" => "NIF cliente",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_parsed_where_unambiguous() {
+        with(Locale::German, || {
+            assert_eq!(parse_number("12,5"), Some(12.5));
+            assert_eq!(parse_number("12.5"), Some(12.5));
+            assert_eq!(parse_number("1.234"), Some(1234.0));
+            assert_eq!(parse_number("1.234,5"), Some(1234.5));
+            // A weight, not five hundred
+            assert_eq!(parse_number("0.500"), Some(0.5));
+            assert_eq!(parse_number(".500"), Some(0.5));
+            assert_eq!(parse_number(",5"), Some(0.5));
+            assert_eq!(parse_number("-0.250"), Some(-0.25));
+        });
+        with(Locale::English, || {
+            assert_eq!(parse_number("1,234"), Some(1234.0));
+            assert_eq!(parse_number("1,234,567.5"), Some(1234567.5));
+            assert_eq!(parse_number("0,500"), Some(0.5));
+            assert_eq!(parse_number("12,5"), Some(12.5));
+            assert_eq!(parse_number("lots"), None);
+        });
+    }
+}
@@ -0,0 +1,236 @@
+//! Invoices: sales paid later, in one go or a bit at a time
+//!
+//! A sale becomes an invoice once it is given a customer and payment terms.
+//! Payments are recorded against it as they come in, and what is left is its
+//! balance, which is overdue once the due date has passed. A statement lists
+//! the invoices of a customer with their payments and the running balance.
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::data::{csv_escape, slug};
+use crate::sale::Sale;
+
+/// Payment terms in days, as offered on the edit screen.
+pub const TERMS: [Terms; 5] =
+    [Terms(0), Terms(7), Terms(14), Terms(30), Terms(60)];
+
+/// How many days after it is issued an invoice is due.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct Terms(pub u32);
+
+impl Default for Terms {
+    fn default() -> Self {
+        Terms(30)
+    }
+}
+
+impl fmt::Display for Terms {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            0 => write!(f, "Due on receipt"),
+            days => write!(f, "Net {days}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Payment {
+    pub date: NaiveDate,
+    pub amount: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Invoice {
+    /// Who the invoice is billed to, which statements are made for.
+    pub customer: String,
+    pub issued: NaiveDate,
+    #[serde(default)]
+    pub terms: Terms,
+    #[serde(default)]
+    pub payments: Vec<Payment>,
+}
+
+impl Invoice {
+    pub fn new(issued: NaiveDate) -> Self {
+        Self {
+            customer: String::new(),
+            issued,
+            terms: Terms::default(),
+            payments: Vec::new(),
+        }
+    }
+
+    pub fn due(&self) -> NaiveDate {
+        self.issued
+            .checked_add_days(Days::new(u64::from(self.terms.0)))
+            .unwrap_or(self.issued)
+    }
+
+    /// The same invoice issued again on `issued`, without its payments.
+    pub fn reissued(&self, issued: NaiveDate) -> Self {
+        Self {
+            issued,
+            payments: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// The amount paid so far.
+    pub fn paid(&self) -> f32 {
+        self.payments.iter().map(|payment| payment.amount).sum()
+    }
+
+    pub fn is_for(&self, customer: &str) -> bool {
+        self.customer.trim().eq_ignore_ascii_case(customer.trim())
+    }
+}
+
+/// What the sales list shows of an invoice.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Summary {
+    pub customer: String,
+    pub due: NaiveDate,
+    pub balance: f32,
+}
+
+impl Summary {
+    pub fn new(sale: &Sale) -> Option<Self> {
+        let invoice = sale.invoice.as_ref()?;
+        Some(Self {
+            customer: invoice.customer.clone(),
+            due: invoice.due(),
+            balance: sale.calculate_balance(),
+        })
+    }
+
+    /// Whether a balance is left past the due date, as of `today`.
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        self.balance >= 0.005 && self.due < today
+    }
+}
+
+/// The name of the statement file of `customer`, e.g.
+/// `statement-acme-co.csv`.
+pub fn file_name(customer: &str) -> String {
+    format!("statement-{}.csv", slug(customer))
+}
+
+/// The statement of `customer` as CSV: every invoice and payment of theirs
+/// in `sales` by date, with the running balance, and what is outstanding
+/// on the last line.
+pub fn statement<'a>(
+    customer: &str,
+    sales: impl IntoIterator<Item = (usize, &'a Sale)>,
+) -> String {
+    let mut lines: Vec<(NaiveDate, usize, String, f32, f32)> = Vec::new();
+    for (id, sale) in sales {
+        let Some(invoice) =
+            sale.invoice.as_ref().filter(|i| i.is_for(customer))
+        else {
+            continue;
+        };
+        lines.push((
+            invoice.issued,
+            id,
+            format!("Invoice due {}", invoice.due()),
+            sale.calculate_due(),
+            0.0,
+        ));
+        for payment in &invoice.payments {
+            lines.push((
+                payment.date,
+                id,
+                "Payment".to_string(),
+                0.0,
+                payment.amount,
+            ));
+        }
+    }
+    // Stable, so each invoice comes before the payments made on its day
+    lines.sort_by_key(|(date, ..)| *date);
+
+    let mut csv =
+        String::from("date,invoice,description,charge,payment,balance\n");
+    let mut balance = 0.0;
+    for (date, id, description, charge, payment) in lines {
+        balance += charge - payment;
+        csv.push_str(&format!(
+            "{date},{id},{},{charge:.2},{payment:.2},{balance:.2}\n",
+            csv_escape(&description)
+        ));
+    }
+    csv.push_str(&format!(
+        ",,{},,,{balance:.2}\n",
+        csv_escape(&format!("Outstanding for {}", customer.trim()))
+    ));
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sale::SaleItem;
+
+    fn day(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    fn invoice(customer: &str, price: f32, issued: NaiveDate) -> Sale {
+        let mut item = SaleItem::new("Catering", price, 1);
+        item.tax_group = crate::tax::TaxGroup::NonTaxable;
+        Sale {
+            items: vec![item],
+            invoice: Some(Invoice {
+                customer: customer.to_string(),
+                ..Invoice::new(issued)
+            }),
+            ..Sale::default()
+        }
+    }
+
+    #[test]
+    fn balance_goes_down_with_payments_and_is_overdue_after_terms() {
+        let mut sale = invoice("Acme", 500.0, day(3, 1));
+        let summary = Summary::new(&sale).unwrap();
+        assert_eq!(summary.due, day(3, 31));
+        assert!(!summary.is_overdue(day(3, 31)));
+        assert!(summary.is_overdue(day(4, 1)));
+
+        let invoice = sale.invoice.as_mut().unwrap();
+        invoice.payments.push(Payment {
+            date: day(3, 10),
+            amount: 200.0,
+        });
+        invoice.payments.push(Payment {
+            date: day(4, 2),
+            amount: 300.0,
+        });
+        assert_eq!(sale.calculate_balance(), 0.0);
+        assert!(!Summary::new(&sale).unwrap().is_overdue(day(5, 1)));
+    }
+
+    #[test]
+    fn statement_runs_the_balance_of_one_customer() {
+        let mut first = invoice("Acme", 500.0, day(3, 1));
+        first.invoice.as_mut().unwrap().payments.push(Payment {
+            date: day(3, 20),
+            amount: 200.0,
+        });
+        let second = invoice("acme ", 120.0, day(3, 15));
+        let other = invoice("Globex", 80.0, day(3, 2));
+
+        let csv = statement("Acme", [(1, &first), (2, &second), (3, &other)]);
+        assert_eq!(file_name(" Acme Co. "), "statement-acme-co.csv");
+        assert_eq!(
+            csv,
+            "date,invoice,description,charge,payment,balance\n\
+             2024-03-01,1,Invoice due 2024-03-31,500.00,0.00,500.00\n\
+             2024-03-15,2,Invoice due 2024-04-14,120.00,0.00,620.00\n\
+             2024-03-20,1,Payment,0.00,200.00,420.00\n\
+             ,,Outstanding for Acme,,,420.00\n"
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Sales, tax and storage of iced_receipts, without any UI
+//!
+//! This is the domain logic shared by every frontend of the app: the desktop
+//! app built on `iced` and the headless commands for exports and reports are
+//! both written on top of it, and so could a web frontend be.
+//!
+//! - [`sale`] holds the [`Sale`](sale::Sale) model, and [`sale::totals`]
+//!   the money math: subtotal, tax, service charge and total.
+//! - [`tax`], [`service`], [`catalog`] and [`allergen`] are what goes into
+//!   a sale, and [`i18n`] how its amounts and dates are written. An
+//!   [`invoice`] is a sale paid later, and [`delivery`] one taken to the
+//!   customer. [`customer`] keeps who the sales were made for, their tax
+//!   IDs checked by [`vat`] as the store's is, and
+//!   [`currency`] converts what they pay in other currencies, with change
+//!   given in the fewest notes and coins of each [`denomination`]. Paid
+//!   receipts can carry a [`fiscal`] number and signature.
+//! - [`store`] reads and writes the sales in the [`data_dir`], with old ones
+//!   moved to the [`archive`], deleted ones to the [`trash`] and snapshots
+//!   kept by [`backup`]. [`query`] streams through them for reports, and
+//!   [`data`] is the versioned format shared with exports, upgraded by
+//!   [`migrate`]. [`accounting`] books them as journals for QuickBooks or
+//!   Xero.
+//! - [`shift`] tracks who is clocked in, and which shift a sale was made on,
+//!   and [`tips`] shares the day's tips out among them.
+//!
+//! The locale and tax rounding are set once for the whole process, with
+//! [`i18n::set`] and [`tax::set`], before any sale is worked on:
+//!
+//! ```
+//! use receipts_core::sale::{Sale, SaleItem};
+//! use receipts_core::tax::{self, Rounding};
+//!
+//! tax::set(Rounding::default());
+//!
+//! let sale = Sale {
+//!     items: vec![SaleItem::new("Lemonade", 3.25, 2)],
+//!     ..Sale::default()
+//! };
+//! let totals = sale.totals();
+//! assert_eq!(totals.subtotal, 6.5);
+//! assert_eq!(totals.total, totals.subtotal + totals.tax);
+//! ```
+use std::path::PathBuf;
+
+pub mod accounting;
+pub mod allergen;
+pub mod archive;
+pub mod backup;
+pub mod catalog;
+pub mod currency;
+pub mod custom;
+pub mod customer;
+pub mod data;
+pub mod delivery;
+pub mod denomination;
+pub mod fiscal;
+pub mod i18n;
+pub mod invoice;
+pub mod migrate;
+pub mod query;
+pub mod sale;
+pub mod service;
+pub mod shift;
+pub mod store;
+pub mod tag;
+pub mod tax;
+pub mod terminal;
+pub mod tips;
+pub mod trash;
+pub mod vat;
+
+/// The directory holding all of the app's files.
+pub fn data_dir() -> PathBuf {
+    #[cfg(test)]
+    if let Some(dir) = TEST_DATA_DIR.with_borrow(Clone::clone) {
+        return dir;
+    }
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("iced_receipts")
+}
+
+#[cfg(test)]
+thread_local! {
+    /// The data directory of the test running on this thread, if it asked
+    /// for one of its own with [`test_data_dir`].
+    static TEST_DATA_DIR: std::cell::RefCell<Option<PathBuf>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Give the test running on this thread an empty data directory, named
+/// after it, rather than the app's.
+#[cfg(test)]
+pub(crate) fn test_data_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+        .join(format!("receipts-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    TEST_DATA_DIR.set(Some(dir.clone()));
+    dir
+}
+
+/// Run `task` to the end in a test, e.g. a load of the store, which reads
+/// and writes without waiting on anything.
+#[cfg(test)]
+pub(crate) fn block_on<T>(task: impl std::future::Future<Output = T>) -> T {
+    use std::task::{Context, Poll, Waker};
+
+    let mut cx = Context::from_waker(Waker::noop());
+    match std::pin::pin!(task).poll(&mut cx) {
+        Poll::Ready(result) => result,
+        Poll::Pending => panic!("the task waited"),
+    }
+}
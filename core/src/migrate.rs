@@ -0,0 +1,287 @@
+//! Upgrades of sales written by older versions of the app
+//!
+//! Every store, archive month and export records the format `version` it
+//! was written in. Each of the [`STEPS`] upgrades a sale by one version, so
+//! a file from any older version is brought up to date by running the steps
+//! after its version in order. Steps work on the sale's JSON form before it
+//! is read as a [`Sale`], so they can rename or reshape fields freely.
+//!
+//! The store is upgraded once, when it is loaded, and the original is kept
+//! beside it as e.g. `sales.json.v1`. If an upgrade fails the original is
+//! moved there instead, so that nothing is ever written over it.
+use chrono::{DateTime, Utc};
+use serde::de::{self, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::data::{Error, Export, VERSION};
+use crate::sale::Sale;
+
+/// Upgrades the fields of a sale from one version to the next.
+type Step = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// The steps from each version to the next, starting at version 1.
+const STEPS: [Step; 1] = [link_returns];
+
+const _: () = assert!(STEPS.len() + 1 == VERSION as usize);
+
+/// Version 2 replaced the `return_of` id of returns with a link.
+fn link_returns(sale: &mut Map<String, Value>) -> Result<(), String> {
+    match sale.remove("return_of") {
+        None | Some(Value::Null) => Ok(()),
+        Some(Value::Number(id)) => {
+            sale.insert(
+                "link".to_string(),
+                json!({ "relation": "RefundOf", "sale": id }),
+            );
+            Ok(())
+        }
+        Some(other) => Err(format!("return_of is not a sale id: {other}")),
+    }
+}
+
+/// A store or export as written by any version, its sales not read yet.
+#[derive(Debug, Deserialize)]
+pub struct Raw {
+    pub version: u32,
+    #[serde(default)]
+    pub modified_at: Option<DateTime<Utc>>,
+    pub sales: BTreeMap<usize, Loose>,
+}
+
+/// Upgrade the sales of `raw` to the current version.
+pub fn export(raw: Raw) -> Result<Export, Error> {
+    let version = raw.version;
+    let sales = raw
+        .sales
+        .into_iter()
+        .map(|(id, Loose(sale))| Ok((id, self::sale(version, sale)?)))
+        .collect::<Result<_, Error>>()?;
+
+    Ok(Export {
+        version: VERSION,
+        modified_at: raw.modified_at,
+        sales,
+    })
+}
+
+/// Read a sale written by `version`, upgrading it first if it is older.
+pub fn sale(version: u32, mut sale: Value) -> Result<Sale, Error> {
+    if version > VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+    if version < VERSION {
+        let Value::Object(fields) = &mut sale else {
+            return Err(Error::Migration(version, "not a sale".to_string()));
+        };
+        for step in &STEPS[version.max(1) as usize - 1..] {
+            step(fields).map_err(|e| Error::Migration(version, e))?;
+        }
+    }
+    Ok(serde_json::from_value(sale)?)
+}
+
+/// Any value read from JSON or MessagePack. Unlike a plain [`Value`] it
+/// takes maps keyed by numbers, as MessagePack writes them, keying them by
+/// the number as text like JSON does.
+#[derive(Debug)]
+pub struct Loose(pub Value);
+
+impl<'de> Deserialize<'de> for Loose {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(LooseVisitor).map(Loose)
+    }
+}
+
+struct LooseVisitor;
+
+impl<'de> Visitor<'de> for LooseVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "any value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Value, D::Error> {
+        Loose::deserialize(deserializer).map(|Loose(value)| value)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> Result<Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(Loose(value)) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> Result<Value, A::Error> {
+        let mut fields = Map::new();
+        while let Some((Loose(key), Loose(value))) = map.next_entry()? {
+            let key = match key {
+                Value::String(key) => key,
+                key => key.to_string(),
+            };
+            fields.insert(key, value);
+        }
+        Ok(Value::Object(fields))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sale::{Link, Relation};
+    use crate::store::{self, Format};
+    use crate::{block_on, data, test_data_dir};
+
+    /// The same two sales, a sale and its refund, as written by each
+    /// version, starting at version 1.
+    const FIXTURES: [&str; VERSION as usize] = [
+        r#"{
+            "version": 1,
+            "modified_at": "2024-03-01T12:00:00Z",
+            "sales": {
+                "6": {
+                    "items": [{ "id": 0, "name": "Soup", "price": 4.5,
+                        "quantity": 2, "tax_group": "Food", "section": null }],
+                    "service_charge_percent": null,
+                    "gratuity_amount": 1.0,
+                    "name": "Table 6",
+                    "return_of": null,
+                    "created_at": "2024-03-01T11:00:00Z"
+                },
+                "7": {
+                    "items": [{ "id": 0, "name": "Soup", "price": -4.5,
+                        "quantity": 1, "tax_group": "Food", "section": null }],
+                    "service_charge_percent": null,
+                    "gratuity_amount": null,
+                    "name": "Refund",
+                    "return_of": 6,
+                    "created_at": "2024-03-01T11:30:00Z"
+                }
+            }
+        }"#,
+        r#"{
+            "version": 2,
+            "modified_at": "2024-03-01T12:00:00Z",
+            "sales": {
+                "6": {
+                    "items": [{ "id": 0, "name": "Soup", "price": 4.5,
+                        "quantity": 2, "tax_group": "Food", "section": null }],
+                    "service_charge_percent": null,
+                    "gratuity_amount": 1.0,
+                    "name": "Table 6",
+                    "created_at": "2024-03-01T11:00:00Z"
+                },
+                "7": {
+                    "items": [{ "id": 0, "name": "Soup", "price": -4.5,
+                        "quantity": 1, "tax_group": "Food", "section": null }],
+                    "service_charge_percent": null,
+                    "gratuity_amount": null,
+                    "name": "Refund",
+                    "link": { "relation": "RefundOf", "sale": 6 },
+                    "created_at": "2024-03-01T11:30:00Z"
+                }
+            }
+        }"#,
+    ];
+
+    #[test]
+    fn every_version_is_upgraded_to_the_current_one() {
+        let current = data::parse(FIXTURES[VERSION as usize - 1]).unwrap();
+        let current = serde_json::to_value(&current).unwrap();
+
+        for (at, fixture) in FIXTURES.iter().enumerate() {
+            let export = data::parse(fixture).unwrap();
+            assert_eq!(export.version, VERSION, "from version {}", at + 1);
+            assert_eq!(export.sales[&6].link, None);
+            assert_eq!(
+                export.sales[&7].link,
+                Some(Link {
+                    relation: Relation::RefundOf,
+                    sale: 6
+                })
+            );
+            assert_eq!(export.sales[&6].gratuity_amount, Some(1.0));
+            assert_eq!(
+                serde_json::to_value(&export).unwrap(),
+                current,
+                "from version {}",
+                at + 1
+            );
+        }
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let newer = FIXTURES[VERSION as usize - 1]
+            .replace(&format!("\"version\": {VERSION}"), "\"version\": 99");
+        assert!(matches!(
+            data::parse(&newer),
+            Err(Error::UnsupportedVersion(99))
+        ));
+    }
+
+    #[test]
+    fn a_store_which_fails_to_upgrade_is_kept_as_it_was() {
+        let dir = test_data_dir("migrate-failed");
+        let broken =
+            FIXTURES[0].replace("\"return_of\": 6", "\"return_of\": \"six\"");
+        std::fs::write(dir.join("sales.json"), &broken).unwrap();
+
+        assert!(matches!(
+            block_on(store::load_index(Format::Json)),
+            Err(Error::Migration(1, _))
+        ));
+        assert!(!dir.join("sales.json").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("sales.json.v1")).unwrap(),
+            broken
+        );
+    }
+}
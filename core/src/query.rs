@@ -0,0 +1,318 @@
+//! Read-side queries over the sales store for reports
+//!
+//! Queries stream the store from disk one sale at a time, folding every sale
+//! in range into its group as it goes, so memory use stays flat no matter
+//! how much history the store holds.
+use chrono::{Local, NaiveDate};
+use flate2::read::GzDecoder;
+use serde::de::{self, DeserializeSeed, MapAccess, Visitor};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::archive;
+use crate::data::{self, Error};
+use crate::migrate::{self, Loose};
+use crate::sale::Sale;
+use crate::shift::{Shift, Shifts};
+use crate::store::{self, Format};
+use crate::tag;
+
+/// An inclusive range of local calendar days. Open ends are unbounded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Range {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl Range {
+    pub fn contains(&self, sale: &Sale) -> bool {
+        let day = sale.created_at.with_timezone(&Local).date_naive();
+        self.from.is_none_or(|from| day >= from)
+            && self.to.is_none_or(|to| day <= to)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GroupBy {
+    #[default]
+    Day,
+    Month,
+    OrderType,
+    TaxGroup,
+    Shift,
+}
+
+impl GroupBy {
+    pub const ALL: [GroupBy; 5] = [
+        GroupBy::Day,
+        GroupBy::Month,
+        GroupBy::OrderType,
+        GroupBy::TaxGroup,
+        GroupBy::Shift,
+    ];
+}
+
+impl fmt::Display for GroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                GroupBy::Day => "Day",
+                GroupBy::Month => "Month",
+                GroupBy::OrderType => "Order type",
+                GroupBy::TaxGroup => "Tax group",
+                GroupBy::Shift => "Shift",
+            }
+        )
+    }
+}
+
+/// The aggregated figures of one group.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Row {
+    pub key: String,
+    /// The number of sales (or, grouped by tax group, of items).
+    pub count: usize,
+    pub subtotal: f32,
+    pub tax: f32,
+    pub total: f32,
+    /// The gratuities, included in the total.
+    pub tips: f32,
+}
+
+impl Row {
+    /// The figures of `sale`, as one row.
+    fn of(sale: &Sale) -> Self {
+        Row {
+            key: String::new(),
+            count: 1,
+            subtotal: sale.calculate_subtotal(),
+            tax: sale.calculate_tax(),
+            total: sale.calculate_total(),
+            tips: sale.totals().gratuity,
+        }
+    }
+
+    pub fn add(&mut self, other: &Row) {
+        self.count += other.count;
+        self.subtotal += other.subtotal;
+        self.tax += other.tax;
+        self.total += other.total;
+        self.tips += other.tips;
+    }
+}
+
+/// Aggregate the sales in `range` of the store in `format` and the archive
+/// matching `filter` into one row per group, ordered by group key.
+pub fn aggregate(
+    format: Format,
+    range: Range,
+    group_by: GroupBy,
+    filter: tag::Filter,
+) -> Result<Vec<Row>, Error> {
+    let mut groups: BTreeMap<String, Row> = BTreeMap::new();
+    let mut add = |key: String, figures: Row| {
+        groups
+            .entry(key.clone())
+            .or_insert_with(|| Row {
+                key,
+                ..Row::default()
+            })
+            .add(&figures)
+    };
+    let shifts = match group_by {
+        GroupBy::Shift => Shifts::load(),
+        _ => Shifts::default(),
+    };
+
+    let mut visit = |_, sale: Sale| {
+        if !filter.matches(&sale.tags, sale.label) {
+            return;
+        }
+        let local = sale.created_at.with_timezone(&Local);
+        match group_by {
+            GroupBy::Day => {
+                add(local.format("%Y-%m-%d").to_string(), Row::of(&sale))
+            }
+            GroupBy::Month => {
+                add(local.format("%Y-%m").to_string(), Row::of(&sale))
+            }
+            GroupBy::OrderType => {
+                add(sale.order_type.to_string(), Row::of(&sale))
+            }
+            GroupBy::TaxGroup => {
+                for item in &sale.items {
+                    let tax = item.tax();
+                    add(
+                        item.tax_group.to_string(),
+                        Row {
+                            count: 1,
+                            subtotal: item.total(),
+                            tax,
+                            total: item.total() + tax,
+                            ..Row::default()
+                        },
+                    );
+                }
+            }
+            GroupBy::Shift => add(
+                sale.shift
+                    .and_then(|id| shifts.get(id))
+                    .map_or_else(|| "No shift".to_string(), Shift::label),
+                Row::of(&sale),
+            ),
+        }
+    };
+    scan(format, range, &mut visit)?;
+    archive::scan(range, &mut visit)?;
+
+    Ok(groups.into_values().collect())
+}
+
+/// The figures of every shift with sales in `range`, by shift id.
+pub fn by_shift(
+    format: Format,
+    range: Range,
+) -> Result<BTreeMap<usize, Row>, Error> {
+    let mut shifts: BTreeMap<usize, Row> = BTreeMap::new();
+    let mut visit = |_, sale: Sale| {
+        if let Some(id) = sale.shift {
+            shifts.entry(id).or_default().add(&Row::of(&sale));
+        }
+    };
+    scan(format, range, &mut visit)?;
+    archive::scan(range, &mut visit)?;
+
+    Ok(shifts)
+}
+
+/// Call `f` with every sale in `range` of the store in `format`, without
+/// loading the whole store into memory.
+pub fn scan(
+    format: Format,
+    range: Range,
+    f: impl FnMut(usize, Sale),
+) -> Result<(), Error> {
+    scan_file(&store::path(format), format, range, f)
+}
+
+/// Call `f` with every sale in `range` of the store-like file at `path`.
+pub fn scan_file(
+    path: &Path,
+    format: Format,
+    range: Range,
+    f: impl FnMut(usize, Sale),
+) -> Result<(), Error> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let reader = BufReader::new(File::open(path)?);
+    let seed = StoreSeed { range, f };
+
+    match format {
+        Format::Json => seed
+            .deserialize(&mut serde_json::Deserializer::from_reader(reader))?,
+        Format::Binary => seed.deserialize(
+            &mut rmp_serde::Deserializer::new(GzDecoder::new(reader)),
+        )?,
+    }
+    Ok(())
+}
+
+/// Visits the top-level store object, streaming its `sales` map.
+struct StoreSeed<F> {
+    range: Range,
+    f: F,
+}
+
+impl<'de, F: FnMut(usize, Sale)> DeserializeSeed<'de> for StoreSeed<F> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F: FnMut(usize, Sale)> Visitor<'de> for StoreSeed<F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a sales store")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        mut self,
+        mut map: A,
+    ) -> Result<(), A::Error> {
+        // Written before the sales, as the first field of the store
+        let mut version = data::VERSION;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "version" {
+                version = map.next_value()?;
+            } else if key == "sales" {
+                map.next_value_seed(SalesSeed {
+                    range: self.range,
+                    version,
+                    f: &mut self.f,
+                })?;
+            } else {
+                map.next_value::<de::IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Visits the `sales` map one entry at a time, upgrading sales written by
+/// an older `version`.
+struct SalesSeed<'a, F> {
+    range: Range,
+    version: u32,
+    f: &'a mut F,
+}
+
+impl<'de, F: FnMut(usize, Sale)> DeserializeSeed<'de> for SalesSeed<'_, F> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F: FnMut(usize, Sale)> Visitor<'de> for SalesSeed<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map of sales by id")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        if self.version == data::VERSION {
+            while let Some((id, sale)) = map.next_entry::<usize, Sale>()? {
+                if self.range.contains(&sale) {
+                    (self.f)(id, sale);
+                }
+            }
+            return Ok(());
+        }
+
+        while let Some((id, Loose(sale))) = map.next_entry::<usize, Loose>()? {
+            let sale =
+                migrate::sale(self.version, sale).map_err(de::Error::custom)?;
+            if self.range.contains(&sale) {
+                (self.f)(id, sale);
+            }
+        }
+        Ok(())
+    }
+}
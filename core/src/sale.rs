@@ -0,0 +1,823 @@
+//! Sales, their items and what is paid towards them
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::allergen::Allergen;
+use crate::catalog::Tier;
+use crate::currency;
+use crate::delivery::{self, Delivery};
+use crate::fiscal;
+use crate::i18n::{self, tr};
+use crate::invoice::Invoice;
+use crate::service::{self, Rule};
+use crate::tag::Label;
+use crate::tax::{self, TaxGroup};
+use crate::terminal;
+use crate::tips;
+
+pub mod conflict;
+pub mod names;
+pub mod shorthand;
+pub mod totals;
+
+pub use totals::Totals;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaleItem {
+    pub id: usize,
+    pub name: String,
+    /// The price as typed, if it has been.
+    pub price: Option<f32>,
+    /// The quantity as typed, if it has been. Weighed items use the
+    /// [`weighing`](Self::weighing) instead.
+    pub quantity: Option<u32>,
+    pub tax_group: TaxGroup,
+    pub section: Option<usize>,
+    /// The code of the gift card sold by this item, if it is one.
+    #[serde(default)]
+    pub gift_card: Option<String>,
+    /// The catalog product the item was added from.
+    #[serde(default)]
+    pub product: Option<usize>,
+    /// Set for items sold by weight, whose quantity is the net weight.
+    #[serde(default)]
+    pub weighing: Option<Weighing>,
+    /// Allergens of the catalog product the item was added from.
+    #[serde(default)]
+    pub allergens: Vec<Allergen>,
+    /// Price tiers of the catalog product, which set the price as the
+    /// quantity changes until a price is typed by hand.
+    #[serde(default)]
+    pub tiers: Vec<Tier>,
+    /// The promotion whose price the item was added at, e.g. "Happy hour".
+    #[serde(default)]
+    pub promotion: Option<String>,
+    /// The regular price of the catalog product the item was added from, to
+    /// tell a price typed over it.
+    #[serde(default)]
+    pub list_price: Option<f32>,
+    /// Left out of tax: a negative line which adjusts the bill, rather than
+    /// a comp which takes the taxed amount down.
+    #[serde(default)]
+    pub adjustment: bool,
+    /// Set once the item is voided on the saved sale, which keeps the line
+    /// but takes it out of the totals.
+    #[serde(default)]
+    pub void: Option<Void>,
+}
+
+impl Default for SaleItem {
+    fn default() -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            name: String::new(),
+            price: None,
+            quantity: None,
+            tax_group: TaxGroup::Food,
+            section: None,
+            gift_card: None,
+            product: None,
+            weighing: None,
+            allergens: Vec::new(),
+            tiers: Vec::new(),
+            promotion: None,
+            list_price: None,
+            adjustment: false,
+            void: None,
+        }
+    }
+}
+
+impl SaleItem {
+    /// An item with a name, price and quantity, e.g. for made-up sales.
+    pub fn new(name: &str, price: f32, quantity: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            price: Some(price),
+            quantity: Some(quantity),
+            ..Self::default()
+        }
+    }
+
+    pub fn price(&self) -> f32 {
+        self.price.unwrap_or(0.0)
+    }
+    pub fn quantity(&self) -> f32 {
+        match self.weighing {
+            Some(weighing) => weighing.net(),
+            None => self.quantity.unwrap_or(0) as f32,
+        }
+    }
+    pub fn price_string(&self) -> String {
+        match self.price {
+            // Only the minus of a negative price typed so far
+            Some(p) if p == 0.0 && p.is_sign_negative() => "-".to_string(),
+            Some(p) => i18n::number(p, 2),
+            None => String::new(),
+        }
+    }
+    /// The tax on the item before rounding, none for adjustments.
+    pub fn tax(&self) -> f32 {
+        totals::line_tax(self, tax::rounding())
+    }
+    pub fn is_void(&self) -> bool {
+        self.void.is_some()
+    }
+    /// Whether the item is a comp or adjustment, taking money off the sale.
+    pub fn is_negative(&self) -> bool {
+        self.price() < 0.0
+    }
+    /// The quantity as typed, which is the scale reading for weighed items.
+    pub fn quantity_string(&self) -> String {
+        match self.weighing {
+            Some(weighing) => {
+                weighing.gross.map_or(String::new(), |g| i18n::number(g, 3))
+            }
+            None => self.quantity.map_or(String::new(), |q| q.to_string()),
+        }
+    }
+    /// The quantity as shown on tickets and receipts, e.g. `2` or `0.350 kg`.
+    pub fn quantity_label(&self) -> String {
+        match self.weighing {
+            Some(weighing) => format!("{} kg", i18n::number(weighing.net(), 3)),
+            None => self.quantity_string(),
+        }
+    }
+    /// The price times the quantity, rounded to the cent, or nothing once
+    /// voided.
+    pub fn total(&self) -> f32 {
+        totals::line(self, tax::rounding())
+    }
+    /// The catalog price and the price typed over it, if the item was added
+    /// from the catalog and its price changed by hand since. Tier and
+    /// promotion prices aren't overrides.
+    pub fn price_override(&self) -> Option<(f32, f32)> {
+        let list = self.list_price?;
+        let price = self.price?;
+        let typed = self.product.is_some()
+            && self.tiers.is_empty()
+            && self.promotion.is_none();
+        (typed && (price - list).abs() >= 0.005).then_some((list, price))
+    }
+    /// Whether the price typed over the catalog price is off it by more
+    /// than `percent`.
+    pub fn overrides_by_more_than(&self, percent: u32) -> bool {
+        self.price_override().is_some_and(|(list, price)| {
+            (price - list).abs() > list.abs() * percent as f32 / 100.0
+        })
+    }
+    /// The price tier for the quantity, if the item has tiers.
+    pub fn tier(&self) -> Option<Tier> {
+        let quantity = self.quantity.unwrap_or(0);
+        self.tiers
+            .iter()
+            .filter(|tier| tier.min <= quantity.max(1))
+            .max_by_key(|tier| tier.min)
+            .copied()
+    }
+}
+
+/// Why and when an item was voided.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Void {
+    pub reason: String,
+    pub at: DateTime<Utc>,
+}
+
+/// A scale reading of an item sold by weight, in kg.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Weighing {
+    /// What the scale read, container included.
+    pub gross: Option<f32>,
+    /// The weight of the container, taken off the reading.
+    pub tare: f32,
+}
+
+impl Weighing {
+    pub fn net(&self) -> f32 {
+        (self.gross.unwrap_or(0.0) - self.tare).max(0.0)
+    }
+}
+
+/// How a sale relates to the one it links to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Relation {
+    SplitFrom,
+    RefundOf,
+    ReorderOf,
+}
+
+impl Relation {
+    /// The relation as seen from the other sale, e.g. "Refunded by".
+    pub fn inverse(self) -> &'static str {
+        match self {
+            Relation::SplitFrom => tr("Split into"),
+            Relation::RefundOf => tr("Refunded by"),
+            Relation::ReorderOf => tr("Reordered as"),
+        }
+    }
+}
+
+impl std::fmt::Display for Relation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Relation::SplitFrom => tr("Split from"),
+                Relation::RefundOf => tr("Refund of"),
+                Relation::ReorderOf => tr("Reorder of"),
+            }
+        )
+    }
+}
+
+/// A reference from one sale to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Link {
+    pub relation: Relation,
+    pub sale: usize,
+}
+
+/// A receipt of the sale handed out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Issue {
+    pub at: DateTime<Utc>,
+    /// The employee on the till, if anyone was clocked in.
+    #[serde(default)]
+    pub by: Option<String>,
+    pub via: Via,
+}
+
+/// How a receipt was handed out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Via {
+    Print,
+    /// Emailed to this address.
+    Email(String),
+}
+
+impl std::fmt::Display for Via {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Via::Print => write!(f, "{}", tr("Printed")),
+            Via::Email(to) => write!(f, "{} {to}", tr("Emailed to")),
+        }
+    }
+}
+
+/// Payment towards a sale from a gift card.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tender {
+    pub id: usize,
+    pub code: String,
+    /// The amount drawn from the card, once applied.
+    pub amount: f32,
+}
+
+impl Default for Tender {
+    fn default() -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            code: String::new(),
+            amount: 0.0,
+        }
+    }
+}
+
+/// A named group of items within a sale, e.g. a course such as "Appetizers"
+/// or "Drinks". Sections are kept in course order in [`Sale::sections`].
+///
+/// Sales written before sections were folded on screen only, not in the
+/// sale, hold a `collapsed` field too, which is ignored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Section {
+    pub id: usize,
+    pub name: String,
+}
+
+impl Default for Section {
+    fn default() -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            name: String::new(),
+        }
+    }
+}
+
+impl Section {
+    pub fn display_name(&self) -> &str {
+        if self.name.is_empty() {
+            tr("Untitled section")
+        } else {
+            &self.name
+        }
+    }
+}
+
+/// Where a sale is in the counter-service pickup flow.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum OrderStatus {
+    #[default]
+    Open,
+    Ready,
+    PickedUp,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
+pub enum OrderType {
+    #[default]
+    DineIn,
+    Takeout,
+    Delivery,
+}
+
+impl OrderType {
+    pub const ALL: [OrderType; 3] =
+        [OrderType::DineIn, OrderType::Takeout, OrderType::Delivery];
+}
+
+impl std::fmt::Display for OrderType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                OrderType::DineIn => tr("Dine in"),
+                OrderType::Takeout => tr("Takeout"),
+                OrderType::Delivery => tr("Delivery"),
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sale {
+    pub items: Vec<SaleItem>,
+    #[serde(default)]
+    pub sections: Vec<Section>,
+    /// The service charge typed on the sale, overriding any rule.
+    pub service_charge_percent: Option<f32>,
+    /// The service charge rule matching the sale when it was last edited.
+    #[serde(default)]
+    pub service_rule: Option<Rule>,
+    /// The size of the party.
+    #[serde(default)]
+    pub guests: Option<u32>,
+    pub gratuity_amount: Option<f32>,
+    pub name: String,
+    #[serde(default)]
+    pub order_status: OrderStatus,
+    #[serde(default)]
+    pub order_type: OrderType,
+    /// The pager/buzzer handed to a takeout customer, if any.
+    #[serde(default)]
+    pub pager: Option<u32>,
+    /// Where a delivery order goes, kept if the order type is changed.
+    #[serde(default)]
+    pub delivery: Option<Delivery>,
+    /// Values of the custom fields defined in settings, by field id.
+    #[serde(default)]
+    pub custom_fields: BTreeMap<usize, String>,
+    /// Allergies of the guest, printed at the top of the kitchen ticket.
+    #[serde(default)]
+    pub allergy_note: String,
+    /// Free-form tags, e.g. "catering", stored lowercase.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub label: Option<Label>,
+    #[serde(default)]
+    pub tenders: Vec<Tender>,
+    /// Cash taken for the sale, as recorded in the drawer.
+    #[serde(default)]
+    pub cash: Option<f32>,
+    /// A payment taken on the card terminal.
+    #[serde(default)]
+    pub card: Option<terminal::Card>,
+    /// A payment taken in a foreign currency.
+    #[serde(default)]
+    pub foreign: Option<currency::Payment>,
+    /// The sale this one was made from, e.g. the one it refunds.
+    #[serde(default)]
+    pub link: Option<Link>,
+    /// The shift on the till when the sale was made.
+    #[serde(default)]
+    pub shift: Option<usize>,
+    /// When a scheduled order is due, e.g. a catering pickup tomorrow.
+    #[serde(default)]
+    pub due_at: Option<DateTime<Utc>>,
+    /// Set for sales billed to a customer and paid later.
+    #[serde(default)]
+    pub invoice: Option<Invoice>,
+    /// The id of the customer the sale was made for.
+    #[serde(default)]
+    pub customer: Option<usize>,
+    /// The tax ID the customer had when picked, printed on the receipt.
+    #[serde(default)]
+    pub customer_tax_id: String,
+    /// Every time the receipt was printed or emailed, the first one first.
+    #[serde(default)]
+    pub issued: Vec<Issue>,
+    /// The fiscal number and signature the receipt got when paid.
+    #[serde(default)]
+    pub fiscal: Option<fiscal::Signature>,
+    #[serde(default = "Utc::now")]
+    pub created_at: DateTime<Utc>,
+    /// How many times the sale was saved, to tell a stale copy from the
+    /// latest one.
+    #[serde(default)]
+    pub revision: u64,
+}
+
+impl Default for Sale {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            sections: Vec::new(),
+            service_charge_percent: None,
+            service_rule: None,
+            guests: None,
+            gratuity_amount: None,
+            name: String::new(),
+            order_status: OrderStatus::default(),
+            order_type: OrderType::default(),
+            pager: None,
+            delivery: None,
+            custom_fields: BTreeMap::new(),
+            allergy_note: String::new(),
+            tags: Vec::new(),
+            label: None,
+            tenders: Vec::new(),
+            cash: None,
+            card: None,
+            foreign: None,
+            link: None,
+            shift: None,
+            due_at: None,
+            invoice: None,
+            customer: None,
+            customer_tax_id: String::new(),
+            issued: Vec::new(),
+            fiscal: None,
+            created_at: Utc::now(),
+            revision: 0,
+        }
+    }
+}
+
+impl Sale {
+    /// A made-up sale, e.g. to preview receipt layouts with.
+    pub fn sample() -> Self {
+        Self {
+            items: vec![
+                SaleItem::new("Margherita pizza", 11.5, 2),
+                SaleItem::new("House salad", 7.0, 1),
+                SaleItem::new("Lemonade", 3.25, 3),
+            ],
+            name: "Table 4".to_string(),
+            guests: Some(3),
+            ..Self::default()
+        }
+    }
+
+    /// Give every item and section a fresh id, e.g. after the sale was read
+    /// from a file, so they can't collide with the ids of other sales.
+    pub fn with_fresh_ids(mut self) -> Self {
+        let mut renamed = Vec::with_capacity(self.sections.len());
+        for section in &mut self.sections {
+            let id = Section::default().id;
+            renamed.push((section.id, id));
+            section.id = id;
+        }
+        for item in &mut self.items {
+            item.id = SaleItem::default().id;
+            item.section = item.section.and_then(|old| {
+                renamed
+                    .iter()
+                    .find(|(from, _)| *from == old)
+                    .map(|(_, to)| *to)
+            });
+        }
+        for tender in &mut self.tenders {
+            tender.id = Tender::default().id;
+        }
+        self
+    }
+
+    /// What of the sale is stored, leaving out anything only being typed on
+    /// screen, to tell whether a change to it needs saving.
+    pub fn stored(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    /// Whether a receipt was handed out before, so another is a copy.
+    pub fn is_reprint(&self) -> bool {
+        !self.issued.is_empty()
+    }
+
+    /// Whether the sale refunds items of another.
+    pub fn is_return(&self) -> bool {
+        self.link
+            .is_some_and(|link| link.relation == Relation::RefundOf)
+    }
+
+    /// The same order again as a new draft, e.g. for a regular's usual,
+    /// leaving out gift cards sold and anything about paying or pickup.
+    pub fn reorder(&self, id: usize) -> Sale {
+        Sale {
+            items: self
+                .items
+                .iter()
+                .filter(|item| item.gift_card.is_none())
+                .cloned()
+                .collect(),
+            sections: self.sections.clone(),
+            service_charge_percent: self.service_charge_percent,
+            guests: self.guests,
+            name: self.name.clone(),
+            order_type: self.order_type,
+            delivery: self.delivery.clone(),
+            custom_fields: self.custom_fields.clone(),
+            allergy_note: self.allergy_note.clone(),
+            tags: self.tags.clone(),
+            label: self.label,
+            customer: self.customer,
+            customer_tax_id: self.customer_tax_id.clone(),
+            invoice: self
+                .invoice
+                .as_ref()
+                .map(|invoice| invoice.reissued(Utc::now().date_naive())),
+            link: Some(Link {
+                relation: Relation::ReorderOf,
+                sale: id,
+            }),
+            ..Sale::default()
+        }
+        .with_fresh_ids()
+    }
+
+    /// A return of `quantities` of the items of this sale, by item id, as a
+    /// new sale with negative prices. Tax and the service charge follow the
+    /// refunded items, while the gratuity and gift cards sold are kept.
+    pub fn returned(
+        &self,
+        id: usize,
+        quantities: &BTreeMap<usize, u32>,
+    ) -> Sale {
+        let items = self
+            .items
+            .iter()
+            .filter(|item| item.gift_card.is_none())
+            .filter_map(|item| {
+                let quantity = *quantities.get(&item.id)?;
+                if quantity == 0 {
+                    return None;
+                }
+                Some(SaleItem {
+                    id: SaleItem::default().id,
+                    price: Some(-item.price()),
+                    tiers: Vec::new(),
+                    promotion: None,
+                    // A weighed item can only be returned whole
+                    quantity: match item.weighing {
+                        Some(_) => None,
+                        None => Some(quantity.min(item.quantity() as u32)),
+                    },
+                    ..item.clone()
+                })
+            })
+            .collect();
+
+        Sale {
+            items,
+            sections: self.sections.clone(),
+            service_charge_percent: self.service_charge_rate(),
+            name: format!("Return of #{id}"),
+            order_status: OrderStatus::PickedUp,
+            order_type: self.order_type,
+            link: Some(Link {
+                relation: Relation::RefundOf,
+                sale: id,
+            }),
+            ..Sale::default()
+        }
+    }
+
+    /// Items grouped by section in course order. Items which do not belong to
+    /// any section come first, under `None`.
+    pub fn courses(&self) -> Vec<(Option<&Section>, Vec<&SaleItem>)> {
+        std::iter::once((None, self.items_in_section(None)))
+            .chain(self.sections.iter().map(|section| {
+                (Some(section), self.items_in_section(Some(section.id)))
+            }))
+            .collect()
+    }
+
+    pub fn items_in_section(&self, section: Option<usize>) -> Vec<&SaleItem> {
+        self.items
+            .iter()
+            .filter(|item| item.section == section)
+            .collect()
+    }
+
+    pub fn calculate_section_subtotal(&self, section: Option<usize>) -> f32 {
+        self.items_in_section(section)
+            .iter()
+            .map(|item| item.total())
+            .sum()
+    }
+
+    pub fn custom_field(&self, id: usize) -> &str {
+        self.custom_fields.get(&id).map_or("", String::as_str)
+    }
+
+    /// The items whose price was typed over the catalog price since
+    /// `stored`, the sale as last saved.
+    pub fn overrides_since(&self, stored: Option<&Sale>) -> Vec<&SaleItem> {
+        self.items
+            .iter()
+            .filter(|item| !item.is_void() && item.price_override().is_some())
+            .filter(|item| {
+                stored
+                    .and_then(|stored| {
+                        stored.items.iter().find(|before| before.id == item.id)
+                    })
+                    .is_none_or(|before| before.price != item.price)
+            })
+            .collect()
+    }
+
+    /// Void item `id` for `reason`, unless it is voided already, giving
+    /// back the item as it was.
+    pub fn void(
+        &mut self,
+        id: usize,
+        reason: &str,
+        at: DateTime<Utc>,
+    ) -> Option<SaleItem> {
+        let item = self
+            .items
+            .iter_mut()
+            .find(|item| item.id == id && !item.is_void())?;
+        let before = item.clone();
+        item.void = Some(Void {
+            reason: reason.trim().to_string(),
+            at,
+        });
+        Some(before)
+    }
+
+    /// Hand the order over to the customer, freeing up their pager.
+    pub fn mark_picked_up(&mut self) {
+        self.order_status = OrderStatus::PickedUp;
+        self.pager = None;
+    }
+
+    /// The money figures of the sale, rounded by the rule set up in the
+    /// settings.
+    pub fn totals(&self) -> Totals {
+        Totals::new(self, tax::rounding())
+    }
+
+    pub fn calculate_subtotal(&self) -> f32 {
+        self.totals().subtotal
+    }
+
+    /// The tax, always the sum of the [`tax_breakdown`](Self::tax_breakdown).
+    pub fn calculate_tax(&self) -> f32 {
+        self.totals().tax
+    }
+
+    /// The tax of each taxed group the sale has items in, in the order of
+    /// [`TaxGroup::ALL`].
+    pub fn tax_breakdown(&self) -> Vec<(TaxGroup, f32)> {
+        self.totals().taxes
+    }
+
+    /// The service charge in percent: as typed, or else by the rule.
+    pub fn service_charge_rate(&self) -> Option<f32> {
+        self.service_charge_percent
+            .or(self.service_rule.map(|rule| rule.percent))
+    }
+
+    /// Look up the service charge rule matching the sale as it is now.
+    pub fn apply_service_rules(&mut self, rules: &[Rule]) {
+        self.service_rule = service::rule_for(rules, self);
+    }
+
+    /// Where the order goes, for delivery orders.
+    pub fn delivery(&self) -> Option<&Delivery> {
+        self.delivery
+            .as_ref()
+            .filter(|_| self.order_type == OrderType::Delivery)
+    }
+
+    /// Work out the delivery fee of a delivery order as `config` says.
+    pub fn apply_delivery(&mut self, config: &delivery::Config) {
+        if self.order_type == OrderType::Delivery {
+            let delivery = self.delivery.get_or_insert_with(Delivery::default);
+            delivery.fee = config.fee(delivery.distance);
+        }
+    }
+
+    pub fn calculate_delivery_fee(&self) -> f32 {
+        self.totals().delivery_fee
+    }
+
+    pub fn calculate_service_charge(&self) -> f32 {
+        self.totals().service_charge
+    }
+
+    pub fn calculate_total(&self) -> f32 {
+        self.totals().total
+    }
+
+    /// A gratuity of `percent` of the sale, figured on `base`.
+    pub fn suggested_gratuity(&self, percent: f32, base: tips::Base) -> f32 {
+        let rounding = tax::rounding();
+        rounding.round(base.of(&Totals::new(self, rounding)) * percent / 100.0)
+    }
+
+    /// The amount paid with gift cards.
+    pub fn calculate_tendered(&self) -> f32 {
+        self.tenders.iter().map(|tender| tender.amount).sum()
+    }
+
+    /// The amount left to pay after gift cards.
+    pub fn calculate_due(&self) -> f32 {
+        self.calculate_total() - self.calculate_tendered()
+    }
+
+    /// What is left to pay on an invoice after the payments recorded, or
+    /// the amount due of other sales.
+    pub fn calculate_balance(&self) -> f32 {
+        let paid = self.invoice.as_ref().map_or(0.0, Invoice::paid);
+        tax::rounding().round(self.calculate_due() - paid)
+    }
+
+    /// The total per guest, for a party of more than one.
+    pub fn calculate_per_guest(&self) -> Option<f32> {
+        self.guests
+            .filter(|guests| *guests > 1)
+            .map(|guests| self.calculate_total() / guests as f32)
+    }
+
+    /// The amount due split evenly between the guests, to the cent, as how
+    /// many guests pay what, e.g. 2 × $12.34 and 1 × $12.35.
+    pub fn even_split(&self) -> Vec<(u32, f32)> {
+        let Some(guests) = self.guests.filter(|guests| *guests > 1) else {
+            return Vec::new();
+        };
+        let cents = (self.calculate_due() * 100.0).round() as i64;
+        if cents <= 0 {
+            return Vec::new();
+        }
+
+        let share = cents / i64::from(guests);
+        let extra = (cents % i64::from(guests)) as u32;
+        [(guests - extra, share), (extra, share + 1)]
+            .into_iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, cents)| (count, cents as f32 / 100.0))
+            .collect()
+    }
+
+    /// Drop tenders which were never applied and reduce the rest, last
+    /// first, so they don't pay more than the total.
+    pub fn settle_tenders(&mut self) {
+        self.tenders.retain(|tender| tender.amount > 0.0);
+
+        let mut excess = -self.calculate_due();
+        for tender in self.tenders.iter_mut().rev() {
+            if excess <= 0.0 {
+                break;
+            }
+            let reduced = tender.amount.min(excess);
+            tender.amount -= reduced;
+            excess -= reduced;
+        }
+        self.tenders.retain(|tender| tender.amount > 0.0);
+    }
+}
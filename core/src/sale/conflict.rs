@@ -0,0 +1,376 @@
+//! Two edits of the same sale, made apart and saved one after the other
+//!
+//! A sale is edited on a copy, the draft. If the stored sale changed in the
+//! meantime, e.g. it was marked picked up on the order board or replaced by a
+//! sync, saving the draft would silently undo that change. The draft is held
+//! back as a [`Conflict`] instead, to keep either side or merge them.
+//!
+//! Sales are compared by what they hold: the ids of items, sections and
+//! tenders are given afresh whenever a sale is read in, so they don't count.
+//! Copies kept apart, like the local and the remote one when syncing, are
+//! compared by [`revision`](Sale::revision) first, see [`overwritten`].
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use super::{Sale, SaleItem};
+
+/// The field whose changes are merged line by line, not kept whole.
+const ITEMS: &str = "items";
+
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    /// The id of the sale both edits are of.
+    pub id: usize,
+    /// The stored sale the draft was taken from.
+    pub base: Sale,
+    /// The draft, as edited here.
+    pub mine: Sale,
+    /// The stored sale, as changed elsewhere since.
+    pub theirs: Sale,
+}
+
+/// Which edit of the sale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Mine,
+    Theirs,
+}
+
+/// A field of the sale the two edits left different.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    /// The name of the field, e.g. "Order status".
+    pub name: String,
+    pub mine: String,
+    pub theirs: String,
+}
+
+/// The lines one edit added to the sale or removed from it.
+#[derive(Debug, Clone, Default)]
+pub struct Lines<'a> {
+    pub added: Vec<&'a SaleItem>,
+    pub removed: Vec<&'a SaleItem>,
+}
+
+impl Conflict {
+    /// The conflict of saving `mine` over `theirs`, unless the stored sale is
+    /// still the `base` the draft was taken from.
+    pub fn new(
+        id: usize,
+        base: Sale,
+        mine: Sale,
+        theirs: Sale,
+    ) -> Option<Self> {
+        if normalized(&base) == normalized(&theirs) {
+            return None;
+        }
+        Some(Self {
+            id,
+            base,
+            mine,
+            theirs,
+        })
+    }
+
+    /// The conflict of two copies of a sale changed apart, e.g. a local and
+    /// a remote one, as changes made to the older of the two.
+    pub fn between(id: usize, mine: Sale, theirs: Sale) -> Self {
+        let base = if mine.revision <= theirs.revision {
+            mine.clone()
+        } else {
+            theirs.clone()
+        };
+        Self {
+            id,
+            base,
+            mine,
+            theirs,
+        }
+    }
+
+    /// The fields, other than the lines, which each side left different.
+    pub fn fields(&self) -> Vec<Field> {
+        let [base, mine, theirs] =
+            [&self.base, &self.mine, &self.theirs].map(normalized);
+
+        mine.iter()
+            .filter(|(key, _)| key.as_str() != ITEMS)
+            .filter_map(|(key, value)| {
+                let theirs = theirs.get(key).unwrap_or(&Value::Null);
+                let base = base.get(key).unwrap_or(&Value::Null);
+                (value != theirs && (value != base || theirs != base)).then(
+                    || Field {
+                        name: name(key),
+                        mine: display(value),
+                        theirs: display(theirs),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// The lines `side` added and removed since the draft was taken.
+    pub fn lines(&self, side: Side) -> Lines<'_> {
+        let sale = match side {
+            Side::Mine => &self.mine,
+            Side::Theirs => &self.theirs,
+        };
+        Lines {
+            added: unmatched(sale, &self.base),
+            removed: unmatched(&self.base, sale),
+        }
+    }
+
+    /// The stored sale with the changes made here: the lines added and
+    /// removed, and the fields changed only here.
+    pub fn merge(&self) -> Sale {
+        let [base, mine, theirs] =
+            [&self.base, &self.mine, &self.theirs].map(normalized);
+        let mut merged = object(&self.theirs);
+        let raw = object(&self.mine);
+        for (key, value) in &mine {
+            if key == ITEMS || base.get(key) != theirs.get(key) {
+                continue;
+            }
+            if base.get(key) != Some(value) {
+                if let Some(value) = raw.get(key) {
+                    merged.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        let mut sale: Sale = serde_json::from_value(Value::Object(merged))
+            .expect("a sale merged from two sales is a sale");
+
+        // Lines removed here go, unless they are gone already
+        sale.items = self.theirs.items.clone();
+        for removed in unmatched(&self.base, &self.mine) {
+            let removed = key(removed, &self.base);
+            if let Some(at) = sale
+                .items
+                .iter()
+                .position(|item| key(item, &self.theirs) == removed)
+            {
+                sale.items.remove(at);
+            }
+        }
+        for added in unmatched(&self.mine, &self.base) {
+            let mut item = added.clone();
+            item.section = section_name(&self.mine, added).and_then(|name| {
+                sale.sections
+                    .iter()
+                    .find(|section| section.name == name)
+                    .map(|section| section.id)
+            });
+            sale.items.push(item);
+        }
+        sale
+    }
+}
+
+/// The ids of the sales in `over` which writing `written` in their place
+/// would lose changes to: those at a later revision, or at the same one but
+/// changed apart.
+pub fn overwritten(
+    written: &BTreeMap<usize, Sale>,
+    over: &BTreeMap<usize, Sale>,
+) -> Vec<usize> {
+    over.iter()
+        .filter(|(id, sale)| {
+            written.get(id).is_some_and(|written| {
+                sale.revision > written.revision
+                    || sale.revision == written.revision
+                        && normalized(sale) != normalized(written)
+            })
+        })
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+/// The items of `sale` with no like item in `other`, counting repeats.
+fn unmatched<'a>(sale: &'a Sale, other: &Sale) -> Vec<&'a SaleItem> {
+    let mut others: Vec<Value> =
+        other.items.iter().map(|item| key(item, other)).collect();
+    sale.items
+        .iter()
+        .filter(|item| {
+            let key = key(item, sale);
+            match others.iter().position(|other| *other == key) {
+                Some(at) => {
+                    others.remove(at);
+                    false
+                }
+                None => true,
+            }
+        })
+        .collect()
+}
+
+/// What `item` of `sale` holds, with its section by name rather than id.
+fn key(item: &SaleItem, sale: &Sale) -> Value {
+    let mut value = serde_json::to_value(item).unwrap_or_default();
+    if let Value::Object(fields) = &mut value {
+        fields.remove("id");
+        fields.insert(
+            "section".to_string(),
+            section_name(sale, item).map_or(Value::Null, Value::from),
+        );
+    }
+    value
+}
+
+fn section_name<'a>(sale: &'a Sale, item: &SaleItem) -> Option<&'a str> {
+    let id = item.section?;
+    sale.sections
+        .iter()
+        .find(|section| section.id == id)
+        .map(|section| section.name.as_str())
+}
+
+fn object(sale: &Sale) -> Map<String, Value> {
+    match serde_json::to_value(sale) {
+        Ok(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    }
+}
+
+/// The fields of `sale` without the ids given when it was read in.
+fn normalized(sale: &Sale) -> Map<String, Value> {
+    let mut fields = object(sale);
+    fields.insert(
+        ITEMS.to_string(),
+        sale.items.iter().map(|item| key(item, sale)).collect(),
+    );
+    for list in ["sections", "tenders"] {
+        if let Some(Value::Array(entries)) = fields.get_mut(list) {
+            for entry in entries {
+                if let Value::Object(entry) = entry {
+                    entry.remove("id");
+                }
+            }
+        }
+    }
+    fields
+}
+
+/// `order_status` as "Order status".
+fn name(key: &str) -> String {
+    let name = key.replace('_', " ");
+    let mut chars = name.chars();
+    chars.next().map_or(String::new(), |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null => "—".to_string(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sale::OrderStatus;
+
+    fn sale(items: &[(&str, u32)]) -> Sale {
+        Sale {
+            name: "Table 4".to_string(),
+            items: items
+                .iter()
+                .map(|(name, quantity)| SaleItem::new(name, 3.5, *quantity))
+                .collect(),
+            ..Sale::default()
+        }
+    }
+
+    fn names(items: &[SaleItem]) -> Vec<(&str, u32)> {
+        items
+            .iter()
+            .map(|item| (item.name.as_str(), item.quantity.unwrap_or_default()))
+            .collect()
+    }
+
+    #[test]
+    fn a_draft_conflicts_only_if_the_stored_sale_changed() {
+        let base = sale(&[("Latte", 2)]);
+        let mut mine = base.clone();
+        mine.items[0].quantity = Some(3);
+
+        // Read in again, e.g. by a sync, but the same
+        let theirs = base.clone().with_fresh_ids();
+        assert!(Conflict::new(1, base.clone(), mine.clone(), theirs).is_none());
+
+        let mut theirs = base.clone();
+        theirs.order_status = OrderStatus::PickedUp;
+        let conflict = Conflict::new(1, base, mine, theirs).unwrap();
+        assert_eq!(
+            conflict.fields(),
+            vec![Field {
+                name: "Order status".to_string(),
+                mine: "Open".to_string(),
+                theirs: "PickedUp".to_string(),
+            }]
+        );
+        let mine = conflict.lines(Side::Mine);
+        assert_eq!(mine.added[0].quantity, Some(3));
+        assert_eq!(mine.removed[0].quantity, Some(2));
+        assert!(conflict.lines(Side::Theirs).added.is_empty());
+    }
+
+    #[test]
+    fn copies_at_a_later_revision_are_not_overwritten() {
+        let mut local = sale(&[("Latte", 2)]);
+        local.revision = 3;
+        let mut remote = local.clone().with_fresh_ids();
+
+        let copies = |local: &Sale, remote: &Sale| {
+            (
+                BTreeMap::from([(1, local.clone())]),
+                BTreeMap::from([(1, remote.clone())]),
+            )
+        };
+        let (locals, remotes) = copies(&local, &remote);
+        assert!(overwritten(&remotes, &locals).is_empty());
+
+        // Saved again remotely: pulling is fine, pushing would lose it
+        remote.revision = 4;
+        remote.order_status = OrderStatus::Ready;
+        let (locals, remotes) = copies(&local, &remote);
+        assert!(overwritten(&remotes, &locals).is_empty());
+        assert_eq!(overwritten(&locals, &remotes), [1]);
+
+        // Saved on both sides from the same revision
+        local.revision = 4;
+        local.name = "Table 5".to_string();
+        let (locals, remotes) = copies(&local, &remote);
+        assert_eq!(overwritten(&remotes, &locals), [1]);
+        assert_eq!(overwritten(&locals, &remotes), [1]);
+
+        let conflict = Conflict::between(1, local, remote);
+        assert_eq!(conflict.base.name, "Table 5");
+    }
+
+    #[test]
+    fn merging_keeps_the_changes_of_both_sides() {
+        let base = sale(&[("Latte", 2), ("Bagel", 1), ("Bagel", 1)]);
+
+        let mut mine = base.clone();
+        mine.items.remove(1);
+        mine.items.push(SaleItem::new("Scone", 3.5, 1));
+        mine.allergy_note = "Nuts".to_string();
+
+        let mut theirs = base.clone().with_fresh_ids();
+        theirs.items.push(SaleItem::new("Juice", 3.5, 2));
+        theirs.order_status = OrderStatus::Ready;
+
+        let merged = Conflict::new(1, base, mine, theirs).unwrap().merge();
+        assert_eq!(
+            names(&merged.items),
+            [("Latte", 2), ("Bagel", 1), ("Juice", 2), ("Scone", 1)]
+        );
+        assert_eq!(merged.allergy_note, "Nuts");
+        assert_eq!(merged.order_status, OrderStatus::Ready);
+    }
+}
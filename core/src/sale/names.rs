@@ -0,0 +1,152 @@
+//! The item names used on earlier sales, to suggest while typing a new one
+//!
+//! The stored sales are only read in full when opened, so the names are kept
+//! apart, in an index updated whenever a sale is saved. Each name remembers
+//! the price and tax group it was last sold at, and how often it was used,
+//! the most used being suggested first.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::PathBuf;
+
+use super::Sale;
+use crate::data::{self, Error};
+use crate::data_dir;
+use crate::tax::TaxGroup;
+
+/// A name sold before, as it was last sold.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Name {
+    pub name: String,
+    pub price: Option<f32>,
+    pub tax_group: TaxGroup,
+    /// How many saves of sales had it.
+    pub uses: u32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Names {
+    /// By lowercase name, so names differing in case are the same.
+    names: BTreeMap<String, Name>,
+}
+
+impl Names {
+    fn path() -> PathBuf {
+        data_dir().join("item_names.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save in the background, see [`data::save_json`].
+    pub fn save(
+        &self,
+    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        data::save_json(Self::path(), self)
+    }
+
+    /// Add the names of the items of `sale`, as it is saved. Voided lines
+    /// and gift cards, whose names are one of a kind, are left out.
+    pub fn record(&mut self, sale: &Sale) {
+        for item in &sale.items {
+            let name = item.name.trim();
+            if name.is_empty() || item.is_void() || item.gift_card.is_some() {
+                continue;
+            }
+            let entry =
+                self.names
+                    .entry(name.to_lowercase())
+                    .or_insert_with(|| Name {
+                        name: String::new(),
+                        price: None,
+                        tax_group: item.tax_group,
+                        uses: 0,
+                    });
+            entry.name = name.to_string();
+            entry.price = item.price.or(entry.price);
+            entry.tax_group = item.tax_group;
+            entry.uses += 1;
+        }
+    }
+
+    /// Up to `limit` names containing `typed`, ignoring case: those starting
+    /// with it first, then the most used. The name typed in full is left
+    /// out, there being nothing left to complete.
+    pub fn matching(&self, typed: &str, limit: usize) -> Vec<&Name> {
+        let typed = typed.trim().to_lowercase();
+        if typed.is_empty() {
+            return Vec::new();
+        }
+        let mut found: Vec<(&String, &Name)> = self
+            .names
+            .iter()
+            .filter(|(key, _)| **key != typed && key.contains(&typed))
+            .collect();
+        found.sort_by_key(|(key, name)| {
+            (!key.starts_with(&typed), std::cmp::Reverse(name.uses))
+        });
+        found
+            .into_iter()
+            .take(limit)
+            .map(|(_, name)| name)
+            .collect()
+    }
+
+    /// How many saves of sales had `name`, ignoring case.
+    pub fn uses(&self, name: &str) -> u32 {
+        self.names
+            .get(&name.trim().to_lowercase())
+            .map_or(0, |name| name.uses)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sale::SaleItem;
+
+    #[test]
+    fn names_are_suggested_by_prefix_then_by_use() {
+        let mut names = Names::default();
+        let mut wine = SaleItem::new("House wine", 6.0, 1);
+        wine.tax_group = TaxGroup::Alcohol;
+        let sale = |items: Vec<SaleItem>| Sale {
+            items,
+            ..Sale::default()
+        };
+        names.record(&sale(vec![
+            wine.clone(),
+            SaleItem::new("White wine", 7.0, 1),
+        ]));
+        names.record(&sale(vec![
+            SaleItem::new("white WINE", 7.5, 2),
+            SaleItem::new("  ", 1.0, 1),
+        ]));
+        assert_eq!(names.len(), 2);
+
+        let found = names.matching("WINE", 5);
+        // The most used first, with the latest spelling and price
+        assert_eq!(found[0].name, "white WINE");
+        assert_eq!(found[0].price, Some(7.5));
+        assert_eq!(found[0].uses, 2);
+        assert_eq!(found[1].tax_group, TaxGroup::Alcohol);
+
+        // Starting with what was typed comes before being used more
+        assert_eq!(names.matching("hou", 5)[0].name, "House wine");
+        assert_eq!(names.matching("w", 5)[0].name, "white WINE");
+        assert!(names.matching("house wine", 5).is_empty());
+        assert!(names.matching("", 5).is_empty());
+    }
+}
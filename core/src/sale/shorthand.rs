@@ -0,0 +1,180 @@
+//! Sale lines typed as shorthand, e.g. `3 burger @ 9.99 food`
+//!
+//! A line is an optional quantity, the name, an optional unit price and an
+//! optional tax group, in that order:
+//!
+//! - the quantity is a number, marked or not: `3`, `3x` or `3 x`
+//! - the price follows `@`, or is the last number: `@ 9.99`, `@9.99` or
+//!   `9,99`, with or without a currency sign
+//! - the tax group is its name last: `food`, `alcohol`, `other`, or
+//!   `exempt` for non-taxable
+//!
+//! So `2x latte 4.50`, `3 burger @ 9.99 food` and `wine alcohol` all work.
+//! It is read by the quick sale screen, and by the item name field when
+//! turned on in the settings.
+use crate::i18n;
+use crate::tax::TaxGroup;
+
+/// Marks a quantity, as in `2x` or `2 x`.
+const TIMES: [char; 3] = ['x', '×', '*'];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub quantity: u32,
+    pub name: String,
+    /// The unit price, if it was typed.
+    pub price: Option<f32>,
+    /// The tax group, if it was typed.
+    pub tax_group: Option<TaxGroup>,
+}
+
+impl Line {
+    /// Whether anything but the name was typed, e.g. not for `7up`.
+    pub fn is_shorthand(&self, input: &str) -> bool {
+        self.name != input.trim()
+    }
+}
+
+/// The line typed as `input`, or `None` if it has no name or a price
+/// marked with `@` isn't one.
+pub fn parse(input: &str) -> Option<Line> {
+    let mut words: Vec<&str> = input.split_whitespace().collect();
+
+    let mut quantity = 1;
+    if let [first, _, ..] = words[..] {
+        let digits = first.trim_end_matches(|c: char| {
+            TIMES.contains(&c.to_ascii_lowercase())
+        });
+        if let Ok(count) = digits.parse() {
+            quantity = count;
+            words.remove(0);
+            let marked = digits.len() < first.len();
+            if let [times, _, ..] = words[..] {
+                if !marked && is_times(times) {
+                    words.remove(0);
+                }
+            }
+        }
+    }
+
+    let mut tax_group = None;
+    if let [_, .., last] = words[..] {
+        tax_group = self::tax_group(last);
+        if tax_group.is_some() {
+            words.pop();
+        }
+    }
+
+    let mut price = None;
+    if let Some(at) = words.iter().rposition(|word| word.starts_with('@')) {
+        let amount = match words[at] {
+            "@" => words[at + 1..].join(""),
+            marked => marked[1..].to_string(),
+        };
+        price = Some(self::price(&amount)?);
+        words.truncate(at);
+    } else if let [.., last] = words[..] {
+        price = self::price(last);
+        if price.is_some() {
+            words.pop();
+        }
+    }
+
+    if quantity == 0 || words.is_empty() {
+        return None;
+    }
+    Some(Line {
+        quantity,
+        name: words.join(" "),
+        price,
+        tax_group,
+    })
+}
+
+fn is_times(word: &str) -> bool {
+    let mut chars = word.chars();
+    matches!(
+        (chars.next(), chars.next()),
+        (Some(c), None) if TIMES.contains(&c.to_ascii_lowercase())
+    )
+}
+
+fn price(word: &str) -> Option<f32> {
+    i18n::parse_number(word.trim_start_matches(['$', '€', '£']))
+        .filter(|price| *price >= 0.0)
+}
+
+fn tax_group(word: &str) -> Option<TaxGroup> {
+    match word.to_lowercase().as_str() {
+        "food" => Some(TaxGroup::Food),
+        "alcohol" => Some(TaxGroup::Alcohol),
+        "exempt" | "non-taxable" | "nontaxable" => Some(TaxGroup::NonTaxable),
+        "other" => Some(TaxGroup::Other),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(
+        quantity: u32,
+        name: &str,
+        price: Option<f32>,
+        tax_group: Option<TaxGroup>,
+    ) -> Option<Line> {
+        Some(Line {
+            quantity,
+            name: name.to_string(),
+            price,
+            tax_group,
+        })
+    }
+
+    #[test]
+    fn shorthand_is_read_as_quantity_name_and_price() {
+        assert_eq!(parse("2x latte 4.50"), line(2, "latte", Some(4.5), None));
+        assert_eq!(parse(" 2 X oat latte "), line(2, "oat latte", None, None));
+        assert_eq!(parse("3 bagel $2"), line(3, "bagel", Some(2.0), None));
+        assert_eq!(
+            parse("flat white 4,20"),
+            line(1, "flat white", Some(4.2), None)
+        );
+        assert_eq!(parse("7up"), line(1, "7up", None, None));
+        assert_eq!(parse("2 x"), line(2, "x", None, None));
+        assert_eq!(parse("12"), None);
+        assert_eq!(parse("0x latte"), None);
+        assert_eq!(parse("2x 4.50"), None);
+        assert_eq!(parse("   "), None);
+    }
+
+    #[test]
+    fn a_price_can_follow_an_at_and_a_tax_group_come_last() {
+        let burger = line(3, "burger", Some(9.99), Some(TaxGroup::Food));
+        assert_eq!(parse("3 burger @ 9.99 food"), burger);
+        assert_eq!(parse("3x burger @9.99 FOOD"), burger);
+        assert_eq!(parse("3 burger 9.99 food"), burger);
+        assert_eq!(
+            parse("house red alcohol"),
+            line(1, "house red", None, Some(TaxGroup::Alcohol))
+        );
+        assert_eq!(
+            parse("stamps @ 1.10 exempt"),
+            line(1, "stamps", Some(1.1), Some(TaxGroup::NonTaxable))
+        );
+
+        // A name can be a tax group, and can hold numbers before the price
+        assert_eq!(parse("food"), line(1, "food", None, None));
+        assert_eq!(
+            parse("table 4 special @ 12"),
+            line(1, "table 4 special", Some(12.0), None)
+        );
+        assert_eq!(parse("burger @ lots"), None);
+        assert_eq!(parse("@ 9.99"), None);
+
+        let typed = "2 x latte";
+        assert!(parse(typed).unwrap().is_shorthand(typed));
+        assert!(!parse("oat latte").unwrap().is_shorthand(" oat latte "));
+    }
+}
@@ -0,0 +1,340 @@
+//! The money figures of a sale, worked out apart from the UI
+//!
+//! Every figure is rounded to the cent by the [`Rounding`] passed in, so the
+//! math can be checked against any rule. The `calculate_*` methods of
+//! [`Sale`] use the rule set up in the settings.
+use super::{Sale, SaleItem};
+use crate::tax::{Per, Rounding, TaxGroup};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Totals {
+    pub subtotal: f32,
+    pub service_charge: f32,
+    /// The tax of each taxed group the sale has items in, in the order of
+    /// [`TaxGroup::ALL`], rounded for each item or each group.
+    pub taxes: Vec<(TaxGroup, f32)>,
+    /// The sum of [`taxes`](Self::taxes).
+    pub tax: f32,
+    pub gratuity: f32,
+    /// The fee of a delivery order, untaxed.
+    pub delivery_fee: f32,
+    /// The exact sum of the other figures as each is shown.
+    pub total: f32,
+}
+
+impl Totals {
+    pub fn new(sale: &Sale, rounding: Rounding) -> Self {
+        let subtotal = rounding
+            .round(sale.items.iter().map(|item| line(item, rounding)).sum());
+        let service_charge =
+            sale.service_charge_rate().map_or(0.0, |percent| {
+                rounding.round(subtotal * (percent / 100.0))
+            });
+        let taxes = taxes(sale, rounding);
+        let tax = rounding.round(taxes.iter().map(|(_, tax)| tax).sum());
+        let gratuity = rounding.round(sale.gratuity_amount.unwrap_or(0.0));
+        let delivery_fee = sale
+            .delivery()
+            .map_or(0.0, |delivery| rounding.round(delivery.fee));
+
+        Self {
+            subtotal,
+            service_charge,
+            taxes,
+            tax,
+            gratuity,
+            delivery_fee,
+            total: rounding.round(
+                subtotal + tax + service_charge + gratuity + delivery_fee,
+            ),
+        }
+    }
+}
+
+/// The price times the quantity of `item`, rounded to the cent, nothing
+/// for voided items.
+pub fn line(item: &SaleItem, rounding: Rounding) -> f32 {
+    if item.is_void() {
+        0.0
+    } else {
+        rounding.round(item.price() * item.quantity())
+    }
+}
+
+/// The tax on `item` before rounding, none for adjustments.
+pub fn line_tax(item: &SaleItem, rounding: Rounding) -> f32 {
+    if item.adjustment {
+        0.0
+    } else {
+        line(item, rounding) * item.tax_group.tax_rate()
+    }
+}
+
+fn taxes(sale: &Sale, rounding: Rounding) -> Vec<(TaxGroup, f32)> {
+    let taxed = |item: &&SaleItem, group| {
+        item.tax_group == group && !item.adjustment && !item.is_void()
+    };
+
+    TaxGroup::ALL
+        .into_iter()
+        .filter(|group| group.tax_rate() > 0.0)
+        .filter(|group| sale.items.iter().any(|item| taxed(&item, *group)))
+        .map(|group| {
+            let tax = sale
+                .items
+                .iter()
+                .filter(|item| taxed(item, group))
+                .map(|item| line_tax(item, rounding))
+                .map(|tax| match rounding.per {
+                    Per::Line => rounding.round(tax),
+                    Per::Group => tax,
+                })
+                .sum();
+            (group, rounding.round(tax))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sale::Tender;
+    use crate::tax::Mode;
+
+    const HALF_UP: Rounding = Rounding {
+        mode: Mode::HalfUp,
+        per: Per::Group,
+    };
+    const HALF_EVEN: Rounding = Rounding {
+        mode: Mode::HalfEven,
+        per: Per::Group,
+    };
+    const PER_LINE: Rounding = Rounding {
+        mode: Mode::HalfUp,
+        per: Per::Line,
+    };
+
+    fn item(price: f32, quantity: u32, tax_group: TaxGroup) -> SaleItem {
+        SaleItem {
+            price: Some(price),
+            quantity: Some(quantity),
+            tax_group,
+            ..SaleItem::default()
+        }
+    }
+
+    fn sale(items: Vec<SaleItem>) -> Sale {
+        Sale {
+            items,
+            ..Sale::default()
+        }
+    }
+
+    #[test]
+    fn half_cents_round_by_mode() {
+        let item = item(0.125, 1, TaxGroup::NonTaxable);
+        assert_eq!(line(&item, HALF_UP), 0.13);
+        assert_eq!(line(&item, HALF_EVEN), 0.12);
+    }
+
+    #[test]
+    fn binary_noise_is_ignored() {
+        // 2.675 is a little less than that as an f32
+        let item = item(2.675, 1, TaxGroup::NonTaxable);
+        assert_eq!(line(&item, HALF_UP), 2.68);
+        assert_eq!(line(&item, HALF_EVEN), 2.68);
+    }
+
+    #[test]
+    fn empty_sale_is_zero() {
+        assert_eq!(Totals::new(&Sale::default(), HALF_UP), Totals::default());
+    }
+
+    #[test]
+    fn tax_is_rounded_per_group_or_per_line() {
+        // 8% of $0.65 is 5.2 cents, three times
+        let sale = sale(vec![
+            item(0.65, 1, TaxGroup::Food),
+            item(0.65, 1, TaxGroup::Food),
+            item(0.65, 1, TaxGroup::Food),
+        ]);
+
+        assert_eq!(Totals::new(&sale, HALF_UP).tax, 0.16);
+        assert_eq!(Totals::new(&sale, PER_LINE).tax, 0.15);
+    }
+
+    #[test]
+    fn taxes_are_broken_down_by_taxed_group() {
+        let sale = sale(vec![
+            item(10.0, 2, TaxGroup::Alcohol),
+            item(5.0, 1, TaxGroup::NonTaxable),
+            item(12.5, 1, TaxGroup::Food),
+        ]);
+        let totals = Totals::new(&sale, HALF_UP);
+
+        assert_eq!(
+            totals.taxes,
+            vec![(TaxGroup::Food, 1.0), (TaxGroup::Alcohol, 2.0)]
+        );
+        assert_eq!(totals.tax, 3.0);
+        assert_eq!(totals.subtotal, 37.5);
+        assert_eq!(totals.total, 40.5);
+    }
+
+    #[test]
+    fn voided_items_are_left_out() {
+        let mut sale = sale(vec![
+            item(10.0, 2, TaxGroup::Alcohol),
+            item(12.5, 1, TaxGroup::Food),
+        ]);
+        let id = sale.items[0].id;
+        let voided = sale.void(id, " Sent back ", chrono::Utc::now());
+        assert_eq!(voided.map(|item| item.void), Some(None));
+        assert!(sale.void(id, "Again", chrono::Utc::now()).is_none());
+        let totals = Totals::new(&sale, HALF_UP);
+
+        assert_eq!(sale.items[0].void.as_ref().unwrap().reason, "Sent back");
+        assert_eq!(totals.taxes, vec![(TaxGroup::Food, 1.0)]);
+        assert_eq!(totals.subtotal, 12.5);
+        assert_eq!(totals.total, 13.5);
+    }
+
+    #[test]
+    fn service_charge_is_a_share_of_the_subtotal() {
+        let mut sale = sale(vec![item(45.5, 1, TaxGroup::NonTaxable)]);
+        sale.service_charge_percent = Some(18.0);
+        let totals = Totals::new(&sale, HALF_UP);
+
+        assert_eq!(totals.service_charge, 8.19);
+        assert_eq!(totals.total, 53.69);
+    }
+
+    #[test]
+    fn typed_service_charge_overrides_the_rule() {
+        let mut sale = sale(vec![item(100.0, 1, TaxGroup::NonTaxable)]);
+        sale.service_rule = Some(crate::service::Rule {
+            percent: 20.0,
+            ..crate::service::Rule::default()
+        });
+        assert_eq!(Totals::new(&sale, HALF_UP).service_charge, 20.0);
+
+        sale.service_charge_percent = Some(0.0);
+        assert_eq!(Totals::new(&sale, HALF_UP).service_charge, 0.0);
+    }
+
+    #[test]
+    fn gratuity_is_added_untaxed() {
+        let mut sale = sale(vec![item(20.0, 1, TaxGroup::Food)]);
+        sale.gratuity_amount = Some(4.005);
+        let totals = Totals::new(&sale, HALF_UP);
+
+        assert_eq!(totals.gratuity, 4.01);
+        assert_eq!(totals.tax, 1.6);
+        assert_eq!(totals.total, 25.61);
+    }
+
+    #[test]
+    fn delivery_fee_is_added_untaxed_to_delivery_orders() {
+        use crate::delivery::{Config, Pricing};
+
+        let config = Config {
+            pricing: Pricing::ByDistance,
+            fee: 2.0,
+            per_km: 0.5,
+        };
+        let mut sale = sale(vec![item(20.0, 1, TaxGroup::Food)]);
+        sale.order_type = crate::sale::OrderType::Delivery;
+        sale.apply_delivery(&config);
+        assert_eq!(Totals::new(&sale, HALF_UP).delivery_fee, 2.0);
+
+        sale.delivery.as_mut().unwrap().distance = Some(3.0);
+        sale.apply_delivery(&config);
+        let totals = Totals::new(&sale, HALF_UP);
+        assert_eq!(totals.delivery_fee, 3.5);
+        assert_eq!(totals.tax, 1.6);
+        assert_eq!(totals.total, 25.1);
+
+        // The address is kept, but the fee dropped, for other orders
+        sale.order_type = crate::sale::OrderType::Takeout;
+        assert_eq!(Totals::new(&sale, HALF_UP).delivery_fee, 0.0);
+        assert!(sale.delivery.is_some());
+    }
+
+    #[test]
+    fn total_is_the_sum_of_the_rounded_figures() {
+        let mut sale = sale(vec![
+            item(3.33, 3, TaxGroup::Food),
+            item(7.77, 1, TaxGroup::Alcohol),
+        ]);
+        sale.service_charge_percent = Some(12.5);
+        sale.gratuity_amount = Some(1.0);
+        let totals = Totals::new(&sale, HALF_UP);
+
+        let sum = totals.subtotal
+            + totals.service_charge
+            + totals.tax
+            + totals.gratuity
+            + totals.delivery_fee;
+        assert_eq!(totals.total, HALF_UP.round(sum));
+    }
+
+    #[test]
+    fn comps_take_tax_down() {
+        let sale = sale(vec![
+            item(20.0, 1, TaxGroup::Food),
+            item(-5.0, 1, TaxGroup::Food),
+        ]);
+        let totals = Totals::new(&sale, HALF_UP);
+
+        assert_eq!(totals.subtotal, 15.0);
+        assert_eq!(totals.tax, 1.2);
+        assert_eq!(totals.total, 16.2);
+    }
+
+    #[test]
+    fn adjustments_are_left_out_of_tax() {
+        let mut adjustment = item(-5.0, 1, TaxGroup::Food);
+        adjustment.adjustment = true;
+        let sale = sale(vec![item(20.0, 1, TaxGroup::Food), adjustment]);
+        let totals = Totals::new(&sale, HALF_UP);
+
+        assert_eq!(totals.subtotal, 15.0);
+        assert_eq!(totals.tax, 1.6);
+        assert_eq!(totals.total, 16.6);
+    }
+
+    #[test]
+    fn weighed_items_use_the_net_weight() {
+        let mut item = item(10.0, 0, TaxGroup::NonTaxable);
+        item.weighing = Some(crate::sale::Weighing {
+            gross: Some(0.5),
+            tare: 0.15,
+        });
+        assert_eq!(line(&item, HALF_UP), 3.5);
+    }
+
+    #[test]
+    fn even_split_spreads_the_odd_cents() {
+        let mut sale = sale(vec![item(10.0, 1, TaxGroup::NonTaxable)]);
+        sale.guests = Some(3);
+        assert_eq!(sale.even_split(), vec![(2, 3.33), (1, 3.34)]);
+
+        sale.guests = Some(1);
+        assert!(sale.even_split().is_empty());
+    }
+
+    #[test]
+    fn tenders_are_cut_down_to_the_total() {
+        let mut sale = sale(vec![item(10.0, 1, TaxGroup::NonTaxable)]);
+        let tender = |amount| Tender {
+            amount,
+            ..Tender::default()
+        };
+        sale.tenders = vec![tender(8.0), tender(0.0), tender(5.0)];
+        sale.settle_tenders();
+
+        let amounts: Vec<f32> = sale.tenders.iter().map(|t| t.amount).collect();
+        assert_eq!(amounts, vec![8.0, 2.0]);
+        assert_eq!(sale.calculate_due(), 0.0);
+    }
+}
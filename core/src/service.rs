@@ -0,0 +1,82 @@
+//! Rules setting the service charge of a sale by its party size and order
+//! type, e.g. "18% for parties of 6+" or "none on takeout"
+//!
+//! The first rule matching a sale applies, unless a service charge is typed
+//! on the sale itself.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::i18n::{number, tr};
+use crate::sale::{OrderType, Sale};
+
+/// Which orders a rule applies to.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Orders {
+    #[default]
+    All,
+    Only(OrderType),
+}
+
+impl Orders {
+    pub const ALL: [Orders; 4] = [
+        Orders::All,
+        Orders::Only(OrderType::DineIn),
+        Orders::Only(OrderType::Takeout),
+        Orders::Only(OrderType::Delivery),
+    ];
+}
+
+impl fmt::Display for Orders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Orders::All => write!(f, "{}", tr("All orders")),
+            Orders::Only(order_type) => write!(f, "{order_type}"),
+        }
+    }
+}
+
+/// A service charge rule, shown as e.g. "18% • Dine in • 6+ guests".
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Rule {
+    pub orders: Orders,
+    /// The smallest party the rule applies to; 0 for any.
+    pub min_guests: u32,
+    pub percent: f32,
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self {
+            orders: Orders::All,
+            min_guests: 6,
+            percent: 18.0,
+        }
+    }
+}
+
+impl Rule {
+    pub fn matches(&self, sale: &Sale) -> bool {
+        let orders = match self.orders {
+            Orders::All => true,
+            Orders::Only(order_type) => sale.order_type == order_type,
+        };
+        orders && sale.guests.unwrap_or(0) >= self.min_guests
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}% • {}", number(self.percent, 1), self.orders)?;
+        if self.min_guests > 0 {
+            write!(f, " • {}+ {}", self.min_guests, tr("guests"))?;
+        }
+        Ok(())
+    }
+}
+
+/// The first of `rules` matching `sale`.
+pub fn rule_for(rules: &[Rule], sale: &Sale) -> Option<Rule> {
+    rules.iter().find(|rule| rule.matches(sale)).copied()
+}
@@ -0,0 +1,175 @@
+//! Employee shifts, from clocking in to clocking out
+//!
+//! Sales are tied to the shift on the till when they are made, so reports can
+//! total the sales and tips of each shift, e.g. to pool the tips at its end.
+//! Several employees can be clocked in at once: the till is with whoever
+//! clocked in last, or took it over since.
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::path::PathBuf;
+
+use crate::data::{self, Error};
+use crate::data_dir;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Shift {
+    pub id: usize,
+    pub employee: String,
+    pub clock_in: DateTime<Utc>,
+    pub clock_out: Option<DateTime<Utc>>,
+}
+
+impl Shift {
+    pub fn is_open(&self) -> bool {
+        self.clock_out.is_none()
+    }
+
+    /// The shift as grouped by in reports, e.g. "2024-03-15 09:00 Alice",
+    /// which sorts by when it started.
+    pub fn label(&self) -> String {
+        format!(
+            "{} {}",
+            self.clock_in.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            self.employee
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Shifts {
+    shifts: Vec<Shift>,
+    /// The open shift new sales are made on.
+    till: Option<usize>,
+}
+
+impl Shifts {
+    fn path() -> PathBuf {
+        data_dir().join("shifts.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save in the background, see [`data::save_json`].
+    pub fn save(
+        &self,
+    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        data::save_json(Self::path(), self)
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Shift> {
+        self.shifts.iter().find(|shift| shift.id == id)
+    }
+
+    /// The shift new sales are made on, if anyone is clocked in.
+    pub fn on_till(&self) -> Option<usize> {
+        self.till
+    }
+
+    /// Clock `employee` in and hand them the till, returning their shift.
+    /// Someone clocked in already just takes the till back.
+    pub fn clock_in(&mut self, employee: &str) -> usize {
+        let employee = employee.trim();
+        let open = self
+            .open()
+            .find(|shift| shift.employee.eq_ignore_ascii_case(employee))
+            .map(|shift| shift.id);
+        let id = match open {
+            Some(id) => id,
+            None => {
+                let id = self.shifts.last().map_or(1, |shift| shift.id + 1);
+                self.shifts.push(Shift {
+                    id,
+                    employee: employee.to_string(),
+                    clock_in: Utc::now(),
+                    clock_out: None,
+                });
+                id
+            }
+        };
+        self.till = Some(id);
+        id
+    }
+
+    /// End shift `id`. The till goes to whoever clocked in last of those
+    /// still working, if it was theirs.
+    pub fn clock_out(&mut self, id: usize) {
+        if let Some(shift) = self.shifts.iter_mut().find(|s| s.id == id) {
+            shift.clock_out.get_or_insert_with(Utc::now);
+        }
+        if self.till == Some(id) {
+            self.till = self.open().last().map(|shift| shift.id);
+        }
+    }
+
+    /// Hand the till to open shift `id`.
+    pub fn take_till(&mut self, id: usize) {
+        if self.get(id).is_some_and(Shift::is_open) {
+            self.till = Some(id);
+        }
+    }
+
+    /// Every shift, in the order they started.
+    pub fn iter(&self) -> impl Iterator<Item = &Shift> {
+        self.shifts.iter()
+    }
+
+    /// The shifts in progress, in the order they started.
+    pub fn open(&self) -> impl DoubleEndedIterator<Item = &Shift> {
+        self.shifts.iter().filter(|shift| shift.is_open())
+    }
+
+    /// Ended shifts, the latest first.
+    pub fn history(&self) -> impl Iterator<Item = &Shift> {
+        self.shifts.iter().rev().filter(|shift| !shift.is_open())
+    }
+
+    /// Everyone who ever clocked in, to pick from.
+    pub fn employees(&self) -> Vec<String> {
+        self.shifts
+            .iter()
+            .map(|shift| shift.employee.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn till_follows_clocking_in_and_out() {
+        let mut shifts = Shifts::default();
+        assert_eq!(shifts.on_till(), None);
+
+        let alice = shifts.clock_in("Alice");
+        let bob = shifts.clock_in("Bob ");
+        assert_eq!(shifts.on_till(), Some(bob));
+        assert_eq!(shifts.get(bob).unwrap().employee, "Bob");
+
+        // Clocking in again only takes the till back
+        assert_eq!(shifts.clock_in("alice"), alice);
+        assert_eq!(shifts.open().count(), 2);
+
+        shifts.clock_out(alice);
+        assert_eq!(shifts.on_till(), Some(bob));
+        shifts.take_till(alice);
+        assert_eq!(shifts.on_till(), Some(bob));
+
+        shifts.clock_out(bob);
+        assert_eq!(shifts.on_till(), None);
+        assert_eq!(
+            shifts.history().map(|shift| shift.id).collect::<Vec<_>>(),
+            [bob, alice]
+        );
+        assert_eq!(shifts.employees(), ["Alice", "Bob"]);
+    }
+}
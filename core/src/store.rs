@@ -0,0 +1,720 @@
+//! The on-disk store holding all sales between runs
+//!
+//! The store uses the same versioned format as a [`data::Export`], written to
+//! the app's data directory whenever sales change. It is kept either as JSON
+//! in `sales.json` or, for terminals with slow disks, in a compact binary
+//! form in `sales.bin`: gzip-compressed MessagePack. MessagePack is used
+//! rather than a schema-less format like bincode because its maps keep field
+//! names, so stores written before a field was added to sales still load.
+//!
+//! Switching formats is transparent: when the store is only found in the
+//! other format it is converted on load.
+//!
+//! To keep launch fast with years of history, the app only loads the
+//! [`Header`] of every sale at startup, from an index written next to the
+//! store, and fetches a sale in full when it is opened. The sales held in
+//! memory are therefore [`Partial`]: anything that needs every sale, like
+//! saving or backing up, merges the loaded sales over those on disk.
+//!
+//! Loading and saving block on the disk, so they are run as tasks off the UI
+//! thread. Saves are tagged with a revision so that a slow write can never
+//! overwrite a newer store that finished first.
+//!
+//! A save never leaves a half-written store behind: the store is written to
+//! a temporary file, flushed to disk and swapped in by a rename. Its SHA-256
+//! checksum is kept beside it, e.g. in `sales.json.sha256`, and checked at
+//! startup. A store which doesn't match is moved aside and replaced by the
+//! most recent backup which can be read.
+//!
+//! The checksum of a new store is put down as pending, in e.g.
+//! `sales.json.sha256.new`, before the store is swapped in, and only takes
+//! the place of the old one after. Losing power in between leaves either
+//! the old store with its checksum, or the new one with the pending one,
+//! which is then settled as its checksum.
+//!
+//! Every sale carries its own [`revision`](Sale::revision) too, kept in the
+//! index. A save holding an older revision of a sale than the store keeps
+//! the stored copy of that sale rather than undo the later changes, writes
+//! the other sales and fails with [`Error::Stale`] naming the sales kept.
+//!
+//! Only one instance of the app may write the store, the one holding the
+//! lock on `sales.lock`. Others can still read it, but their saves fail with
+//! [`Error::Locked`] until the instance holding it quits.
+use chrono::NaiveDateTime;
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs::{File, TryLockError};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use crate::archive;
+use crate::backup;
+use crate::data::{self, Error, Export};
+use crate::data_dir;
+use crate::invoice;
+use crate::migrate;
+use crate::query;
+use crate::sale::{Link, OrderStatus, OrderType, Sale};
+use crate::tag::Label;
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Format {
+    #[default]
+    Json,
+    Binary,
+}
+
+impl Format {
+    pub const ALL: [Format; 2] = [Format::Json, Format::Binary];
+
+    fn other(self) -> Self {
+        match self {
+            Format::Json => Format::Binary,
+            Format::Binary => Format::Json,
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Format::Json => "JSON",
+                Format::Binary => "Compact binary",
+            }
+        )
+    }
+}
+
+impl From<rmp_serde::encode::Error> for Error {
+    fn from(e: rmp_serde::encode::Error) -> Self {
+        Error::Format(e.to_string())
+    }
+}
+
+impl From<rmp_serde::decode::Error> for Error {
+    fn from(e: rmp_serde::decode::Error) -> Self {
+        Error::Format(e.to_string())
+    }
+}
+
+/// Whether the latest change to a sale has been written to the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveStatus {
+    /// Waiting for the given revision of the store to be written.
+    Saving(u64),
+    Failed,
+}
+
+impl fmt::Display for SaveStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveStatus::Saving(_) => write!(f, "Saving…"),
+            SaveStatus::Failed => write!(f, "Not saved"),
+        }
+    }
+}
+
+/// What the app needs to know about a sale without loading it in full.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Header {
+    pub name: String,
+    pub total: f32,
+    pub order_status: OrderStatus,
+    pub order_type: OrderType,
+    pub pager: Option<u32>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub link: Option<Link>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub label: Option<Label>,
+    #[serde(default)]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub invoice: Option<invoice::Summary>,
+    #[serde(default)]
+    pub customer: Option<usize>,
+    #[serde(default)]
+    pub revision: u64,
+}
+
+impl Header {
+    pub fn new(sale: &Sale) -> Self {
+        Self {
+            name: sale.name.clone(),
+            total: sale.calculate_total(),
+            order_status: sale.order_status,
+            order_type: sale.order_type,
+            pager: sale.pager,
+            created_at: sale.created_at,
+            link: sale.link,
+            tags: sale.tags.clone(),
+            label: sale.label,
+            due_at: sale.due_at,
+            invoice: invoice::Summary::new(sale),
+            customer: sale.customer,
+            revision: sale.revision,
+        }
+    }
+}
+
+/// The headers of every sale in the store.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Index {
+    /// The version of the store indexed, which is stale once it is older
+    /// than the app.
+    #[serde(default)]
+    pub version: u32,
+    pub modified_at: Option<DateTime<Utc>>,
+    pub headers: BTreeMap<usize, Header>,
+    /// When the backup was taken which the store was restored from, if it
+    /// was found damaged as it was loaded.
+    #[serde(skip)]
+    pub restored_from: Option<NaiveDateTime>,
+}
+
+impl Index {
+    /// The ids of the `sales` the index holds a later revision of.
+    pub fn stale(&self, sales: &BTreeMap<usize, Sale>) -> Vec<usize> {
+        sales
+            .iter()
+            .filter(|(id, sale)| {
+                self.headers
+                    .get(id)
+                    .is_some_and(|header| header.revision > sale.revision)
+            })
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    fn new(export: &Export) -> Self {
+        Self {
+            version: export.version,
+            restored_from: None,
+            modified_at: export.modified_at,
+            headers: export
+                .sales
+                .iter()
+                .map(|(id, sale)| (*id, Header::new(sale)))
+                .collect(),
+        }
+    }
+}
+
+/// The sales held in memory: those loaded in full, and the ids of the rest,
+/// which are only on disk.
+#[derive(Debug, Clone, Default)]
+pub struct Partial {
+    pub modified_at: Option<DateTime<Utc>>,
+    pub loaded: BTreeMap<usize, Sale>,
+    pub unloaded: BTreeSet<usize>,
+}
+
+/// The revision of the last store written.
+static WRITTEN: Mutex<u64> = Mutex::new(0);
+
+/// The lock on the store, once this instance holds it. The OS releases it
+/// when the app quits, even if it crashes.
+static LOCK: OnceLock<File> = OnceLock::new();
+
+pub fn path(format: Format) -> PathBuf {
+    data_dir().join(match format {
+        Format::Json => "sales.json",
+        Format::Binary => "sales.bin",
+    })
+}
+
+fn index_path(format: Format) -> PathBuf {
+    suffixed(&path(format), ".index")
+}
+
+/// Take the lock on the store, unless this instance holds it already.
+pub fn lock() -> Result<(), Error> {
+    if LOCK.get().is_some() {
+        return Ok(());
+    }
+
+    std::fs::create_dir_all(data_dir())?;
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(data_dir().join("sales.lock"))?;
+    match file.try_lock() {
+        Ok(()) => {
+            let _ = LOCK.set(file);
+            Ok(())
+        }
+        Err(TryLockError::WouldBlock) => Err(Error::Locked),
+        Err(TryLockError::Error(e)) => Err(e.into()),
+    }
+}
+
+fn checksum_path(format: Format) -> PathBuf {
+    suffixed(&path(format), ".sha256")
+}
+
+/// The checksum of the store being written in `format`, until it is.
+fn pending_path(format: Format) -> PathBuf {
+    suffixed(&checksum_path(format), ".new")
+}
+
+/// The format the store is found in on disk, which is the other format
+/// until the first save after switching.
+pub fn on_disk(format: Format) -> Format {
+    if !path(format).exists() && path(format.other()).exists() {
+        format.other()
+    } else {
+        format
+    }
+}
+
+/// Load the headers of every sale in the store in `format`.
+///
+/// The index is rebuilt from the store itself when it is missing or older
+/// than the store, e.g. because the last save was interrupted, and when the
+/// store was written by an older version of the app, upgrading the store.
+pub async fn load_index(format: Format) -> Result<Index, Error> {
+    match index(format) {
+        Err(Error::Format(damage)) => recover(format, damage),
+        result => result,
+    }
+}
+
+fn index(format: Format) -> Result<Index, Error> {
+    verify(on_disk(format))?;
+    if let Some(index) = read_index(format) {
+        return Ok(index);
+    }
+
+    let index = Index::new(&load(format)?);
+    tracing::info!(sales = index.headers.len(), "rebuilt the store index");
+    if path(format).exists() {
+        let _ = write_index(format, &index);
+    }
+    Ok(index)
+}
+
+/// Replace the damaged store in `format` with the most recent backup which
+/// can be read, failing with `damage` if there is none.
+fn recover(format: Format, damage: String) -> Result<Index, Error> {
+    tracing::warn!(damage, "the sales store is damaged");
+    lock()?;
+    let damaged = path(on_disk(format));
+    if damaged.exists() {
+        std::fs::rename(&damaged, suffixed(&damaged, ".corrupt"))?;
+    }
+
+    for snapshot in backup::list() {
+        let Ok(export) = data::read(&snapshot.path) else {
+            continue;
+        };
+        write(format, &export)?;
+        let mut index = Index::new(&export);
+        write_index(format, &index)?;
+        tracing::warn!(backup = %snapshot, "restored the sales store");
+        index.restored_from = Some(snapshot.taken_at);
+        return Ok(index);
+    }
+    Err(Error::Format(damage))
+}
+
+/// Load sale `id` in full from the store in `format`.
+pub async fn load_sale(format: Format, id: usize) -> Result<Sale, Error> {
+    let _written = WRITTEN.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut found = None;
+    query::scan(on_disk(format), query::Range::default(), |sale_id, sale| {
+        if sale_id == id {
+            found = Some(sale);
+        }
+    })?;
+    found.ok_or_else(|| Error::Format(format!("sale #{id} is missing")))
+}
+
+/// Every sale, merging the loaded sales of `partial` over the rest of the
+/// store in `format`.
+pub async fn complete(
+    format: Format,
+    partial: Partial,
+) -> Result<Export, Error> {
+    let _written = WRITTEN.lock().unwrap_or_else(PoisonError::into_inner);
+    merge(format, partial)
+}
+
+fn merge(format: Format, partial: Partial) -> Result<Export, Error> {
+    let mut sales = partial.loaded;
+    if !partial.unloaded.is_empty() {
+        query::scan(on_disk(format), query::Range::default(), |id, sale| {
+            if partial.unloaded.contains(&id) {
+                sales.entry(id).or_insert(sale);
+            }
+        })?;
+    }
+
+    Ok(Export {
+        version: data::VERSION,
+        modified_at: partial.modified_at,
+        sales,
+    })
+}
+
+/// Move the sales of the store in `format` created before `before` into the
+/// archive, leaving open sales and those in `keep`, which are held in memory
+/// and would be written back by the next save. Returns the ids moved and the
+/// archive's new index.
+pub async fn archive(
+    format: Format,
+    before: DateTime<Utc>,
+    keep: BTreeSet<usize>,
+) -> Result<(BTreeSet<usize>, archive::Index), Error> {
+    let _written = WRITTEN.lock().unwrap_or_else(PoisonError::into_inner);
+    lock()?;
+    let mut export = load(format)?;
+    let (old, kept) = std::mem::take(&mut export.sales)
+        .into_iter()
+        .partition::<BTreeMap<_, _>, _>(|(id, sale)| {
+            sale.created_at < before
+                && sale.order_status != OrderStatus::Open
+                && !keep.contains(id)
+        });
+    export.sales = kept;
+    if old.is_empty() {
+        return Ok((BTreeSet::new(), archive::Index::load()));
+    }
+
+    let ids = old.keys().copied().collect();
+    // Written to the archive first, so that a failure loses nothing
+    let index = archive::add(old)?;
+    write(format, &export)?;
+    write_index(format, &Index::new(&export))?;
+    Ok((ids, index))
+}
+
+/// Load the store in `format`, or an empty one if it doesn't exist yet.
+///
+/// If the store only exists in the other format, it is read from there and
+/// rewritten in `format`. A store written by an older version is upgraded
+/// and rewritten, keeping the original as e.g. `sales.json.v1`. A store which
+/// can't be read is moved aside to e.g. `sales.json.corrupt`, or `.v1` if it
+/// couldn't be upgraded, rather than being overwritten by the next save.
+fn load(format: Format) -> Result<Export, Error> {
+    let (found, converted) = if path(format).exists() {
+        (format, false)
+    } else if path(format.other()).exists() {
+        (format.other(), true)
+    } else {
+        return Ok(Export {
+            version: data::VERSION,
+            modified_at: None,
+            sales: Default::default(),
+        });
+    };
+
+    let (export, version) = read(found).inspect_err(|e| {
+        let path = path(found);
+        let aside = match e {
+            Error::Migration(version, _) => format!(".v{version}"),
+            _ => ".corrupt".to_string(),
+        };
+        let _ = std::fs::rename(&path, suffixed(&path, &aside));
+    })?;
+
+    let upgraded = version < data::VERSION;
+    if upgraded {
+        let path = path(found);
+        std::fs::copy(&path, suffixed(&path, &format!(".v{version}")))?;
+    }
+    if converted || upgraded {
+        lock()?;
+        write(format, &export)?;
+    }
+    if converted {
+        let _ = std::fs::remove_file(path(found));
+        let _ = std::fs::remove_file(checksum_path(found));
+        let _ = std::fs::remove_file(pending_path(found));
+    }
+    Ok(export)
+}
+
+/// Save `partial` as `revision` of the store in `format`, removing any copy
+/// in the other format. Revisions older than the last one saved are skipped.
+///
+/// Sales held at an older revision than stored are left as stored, failing
+/// with [`Error::Stale`] once the rest are written.
+pub async fn save(
+    format: Format,
+    revision: u64,
+    partial: Partial,
+) -> Result<(), Error> {
+    let mut written = WRITTEN.lock().unwrap_or_else(PoisonError::into_inner);
+    if revision < *written {
+        return Ok(());
+    }
+    lock()?;
+
+    let stale = store(format, partial)?;
+    *written = revision;
+    tracing::debug!(revision, "saved the store");
+    if stale.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Stale(stale))
+    }
+}
+
+/// Write `partial` over the store in `format`, except for the sales stored
+/// at a later revision, whose ids are returned.
+fn store(format: Format, mut partial: Partial) -> Result<Vec<usize>, Error> {
+    let stale = read_index(format)
+        .map(|index| index.stale(&partial.loaded))
+        .unwrap_or_default();
+    for id in &stale {
+        partial.loaded.remove(id);
+        partial.unloaded.insert(*id);
+    }
+    let merged = merge(format, partial)?;
+    write(format, &merged)?;
+    write_index(format, &Index::new(&merged))?;
+    if !stale.is_empty() {
+        tracing::warn!(?stale, "kept sales changed elsewhere as stored");
+    }
+
+    for stale in [
+        path(format.other()),
+        index_path(format.other()),
+        checksum_path(format.other()),
+        pending_path(format.other()),
+    ] {
+        if stale.exists() {
+            std::fs::remove_file(stale)?;
+        }
+    }
+    Ok(stale)
+}
+
+/// The index of the store in `format`, unless it is missing or stale.
+fn read_index(format: Format) -> Option<Index> {
+    let modified = |path: &Path| path.metadata().and_then(|m| m.modified());
+    if modified(&index_path(format)).ok()? < modified(&path(format)).ok()? {
+        return None;
+    }
+    let file = File::open(index_path(format)).ok()?;
+    serde_json::from_reader(BufReader::new(file))
+        .ok()
+        .filter(|index: &Index| index.version == data::VERSION)
+}
+
+fn write_index(format: Format, index: &Index) -> Result<(), Error> {
+    data::write_file(&index_path(format), &serde_json::to_vec(index)?)
+}
+
+/// Read the store in `format`, upgraded, and the version it was written by.
+fn read(format: Format) -> Result<(Export, u32), Error> {
+    verify(format)?;
+    let file = BufReader::new(File::open(path(format))?);
+    let raw: migrate::Raw = match format {
+        Format::Json => serde_json::from_reader(file)?,
+        Format::Binary => rmp_serde::from_read(GzDecoder::new(file))?,
+    };
+    let version = raw.version;
+    Ok((migrate::export(raw)?, version))
+}
+
+fn write(format: Format, export: &Export) -> Result<(), Error> {
+    std::fs::create_dir_all(data_dir())?;
+    let bytes = match format {
+        Format::Json => serde_json::to_vec_pretty(export)?,
+        Format::Binary => {
+            let mut encoder =
+                GzEncoder::new(Vec::new(), Compression::default());
+            rmp_serde::encode::write_named(&mut encoder, export)?;
+            encoder.finish()?
+        }
+    };
+    let checksum = hex(&Sha256::digest(&bytes));
+
+    data::write_file(&pending_path(format), checksum.as_bytes())?;
+    data::write_file(&path(format), &bytes)?;
+    std::fs::rename(pending_path(format), checksum_path(format))?;
+    Ok(())
+}
+
+/// The checksums the store in `format` may have, none if it was written
+/// before checksums were kept. Stores written by older versions of the app
+/// may have more than one, one per line.
+fn checksums(format: Format) -> Vec<String> {
+    std::fs::read_to_string(checksum_path(format))
+        .map(|text| text.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Check the store in `format` against its checksums, if it has any,
+/// settling the pending checksum of a save that was cut short after the
+/// store was swapped in.
+fn verify(format: Format) -> Result<(), Error> {
+    let accepted = checksums(format);
+    let pending = std::fs::read_to_string(pending_path(format)).ok();
+    if accepted.is_empty() && pending.is_none() || !path(format).exists() {
+        return Ok(());
+    }
+
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut File::open(path(format))?, &mut hasher)?;
+    let checksum = hex(&hasher.finalize());
+    if pending.as_deref() == Some(checksum.as_str()) {
+        tracing::info!("settled the checksum of an interrupted save");
+        std::fs::rename(pending_path(format), checksum_path(format))?;
+        Ok(())
+    } else if accepted.is_empty() || accepted.contains(&checksum) {
+        Ok(())
+    } else {
+        Err(Error::Format(format!(
+            "{} doesn't match its checksum",
+            path(format).display()
+        )))
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn suffixed(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_on, test_data_dir};
+
+    fn sale(name: &str, revision: u64) -> Sale {
+        Sale {
+            name: name.to_string(),
+            revision,
+            ..Sale::default()
+        }
+    }
+
+    fn partial(sales: impl IntoIterator<Item = (usize, Sale)>) -> Partial {
+        Partial {
+            loaded: sales.into_iter().collect(),
+            ..Partial::default()
+        }
+    }
+
+    fn export(sales: impl IntoIterator<Item = (usize, Sale)>) -> Export {
+        Export {
+            version: data::VERSION,
+            modified_at: None,
+            sales: sales.into_iter().collect(),
+        }
+    }
+
+    #[test]
+    fn a_store_changed_after_it_was_written_fails_its_checksum() {
+        test_data_dir("store-checksum");
+        write(Format::Json, &export([(1, sale("A", 1))])).unwrap();
+        verify(Format::Json).unwrap();
+
+        let json = std::fs::read_to_string(path(Format::Json)).unwrap();
+        std::fs::write(path(Format::Json), json.replace("\"A\"", "\"B\""))
+            .unwrap();
+        assert!(matches!(verify(Format::Json), Err(Error::Format(_))));
+    }
+
+    #[test]
+    fn a_damaged_store_is_restored_from_the_newest_good_backup() {
+        let dir = test_data_dir("store-recover");
+        write(Format::Json, &export([(1, sale("Today", 3))])).unwrap();
+        std::fs::write(path(Format::Json), "{ \"version\": 4, \"sa").unwrap();
+
+        std::fs::create_dir_all(backup::dir()).unwrap();
+        let older = export([(1, sale("Monday", 1))]);
+        let newer = export([(1, sale("Tuesday", 2))]);
+        data::write(&backup::dir().join("backup-20240101-120000.json"), &older)
+            .unwrap();
+        data::write(&backup::dir().join("backup-20240102-120000.json"), &newer)
+            .unwrap();
+        // The newest can't be read either
+        std::fs::write(backup::dir().join("backup-20240103-120000.json"), "{")
+            .unwrap();
+
+        let index = block_on(load_index(Format::Json)).unwrap();
+        assert_eq!(index.headers[&1].name, "Tuesday");
+        assert_eq!(
+            index.restored_from.map(|at| at.to_string()),
+            Some("2024-01-02 12:00:00".to_string())
+        );
+        assert!(dir.join("sales.json.corrupt").exists());
+        assert_eq!(load(Format::Json).unwrap().sales[&1].name, "Tuesday");
+    }
+
+    #[test]
+    fn a_save_cut_short_leaves_a_store_which_checks_out() {
+        test_data_dir("store-interrupted");
+        let old = export([(1, sale("Old", 1))]);
+        let new = export([(1, sale("New", 2))]);
+        write(Format::Json, &old).unwrap();
+
+        // Cut short with the new checksum pending and the new store not yet
+        // swapped in: the old store still checks out, the new one doesn't
+        let bytes = serde_json::to_vec_pretty(&new).unwrap();
+        let checksum = hex(&Sha256::digest(&bytes));
+        std::fs::write(pending_path(Format::Json), &checksum).unwrap();
+        std::fs::write(suffixed(&path(Format::Json), ".tmp"), &bytes).unwrap();
+        verify(Format::Json).unwrap();
+        assert_eq!(load(Format::Json).unwrap().sales[&1].name, "Old");
+        std::fs::write(path(Format::Json), b"{").unwrap();
+        assert!(verify(Format::Json).is_err());
+
+        // Cut short after the new store was swapped in: its pending
+        // checksum is settled as its own
+        std::fs::write(path(Format::Json), &bytes).unwrap();
+        verify(Format::Json).unwrap();
+        assert!(!pending_path(Format::Json).exists());
+        assert_eq!(checksums(Format::Json), vec![checksum]);
+        assert_eq!(load(Format::Json).unwrap().sales[&1].name, "New");
+    }
+
+    #[test]
+    fn sales_changed_elsewhere_are_kept_and_the_rest_saved() {
+        test_data_dir("store-stale");
+        store(
+            Format::Json,
+            partial([(1, sale("A", 2)), (2, sale("B", 1))]),
+        )
+        .unwrap();
+
+        // Sale 1 was saved at revision 2 elsewhere since this copy was taken
+        let stale = store(
+            Format::Json,
+            partial([(1, sale("A?", 1)), (2, sale("B!", 2))]),
+        )
+        .unwrap();
+        assert_eq!(stale, vec![1]);
+
+        let sales = load(Format::Json).unwrap().sales;
+        assert_eq!(sales[&1].name, "A");
+        assert_eq!(sales[&1].revision, 2);
+        assert_eq!(sales[&2].name, "B!");
+        let index = read_index(Format::Json).unwrap();
+        assert_eq!(index.headers[&1].revision, 2);
+        assert_eq!(index.headers[&2].revision, 2);
+    }
+}
@@ -0,0 +1,79 @@
+//! Free-form tags and color labels on sales, e.g. "catering" in blue
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::i18n::tr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Label {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+}
+
+impl Label {
+    pub const ALL: [Label; 6] = [
+        Label::Red,
+        Label::Orange,
+        Label::Yellow,
+        Label::Green,
+        Label::Blue,
+        Label::Purple,
+    ];
+
+    /// The color of the label as red, green and blue.
+    pub fn rgb(self) -> [u8; 3] {
+        match self {
+            Label::Red => [0xE5, 0x48, 0x4D],
+            Label::Orange => [0xF7, 0x6B, 0x15],
+            Label::Yellow => [0xF5, 0xC2, 0x18],
+            Label::Green => [0x30, 0xA4, 0x6C],
+            Label::Blue => [0x00, 0x90, 0xFF],
+            Label::Purple => [0x8E, 0x4E, 0xC6],
+        }
+    }
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Label::Red => tr("Red"),
+                Label::Orange => tr("Orange"),
+                Label::Yellow => tr("Yellow"),
+                Label::Green => tr("Green"),
+                Label::Blue => tr("Blue"),
+                Label::Purple => tr("Purple"),
+            }
+        )
+    }
+}
+
+/// A tag as stored: trimmed and lowercase, so "Catering" and "catering "
+/// are the same tag. Empty if there is nothing to tag with.
+pub fn normalize(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+/// Which sales to show: those with the tag and the label, if set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    pub tag: Option<String>,
+    pub label: Option<Label>,
+}
+
+impl Filter {
+    pub fn is_empty(&self) -> bool {
+        self.tag.is_none() && self.label.is_none()
+    }
+
+    pub fn matches(&self, tags: &[String], label: Option<Label>) -> bool {
+        self.tag.as_ref().is_none_or(|tag| tags.contains(tag))
+            && self.label.is_none_or(|wanted| label == Some(wanted))
+    }
+}
@@ -0,0 +1,147 @@
+//! Tax groups and how amounts are rounded to the cent
+//!
+//! Like the locale, the rounding rule is process-wide rather than passed to
+//! every calculation, and is set from the config at startup and whenever it
+//! is changed in settings.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::i18n::tr;
+
+static ROUNDING: AtomicU8 = AtomicU8::new(0);
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub enum TaxGroup {
+    Food,
+    Alcohol,
+    NonTaxable,
+    Other,
+}
+
+impl TaxGroup {
+    pub const ALL: [TaxGroup; 4] = [
+        TaxGroup::Food,
+        TaxGroup::Alcohol,
+        TaxGroup::NonTaxable,
+        TaxGroup::Other,
+    ];
+
+    pub fn tax_rate(&self) -> f32 {
+        match self {
+            TaxGroup::Food => 0.08,
+            TaxGroup::Alcohol => 0.10,
+            TaxGroup::NonTaxable => 0.0,
+            TaxGroup::Other => 0.08,
+        }
+    }
+}
+
+impl std::fmt::Display for TaxGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                TaxGroup::Food => tr("Food (8%)"),
+                TaxGroup::Alcohol => tr("Alcohol (10%)"),
+                TaxGroup::NonTaxable => tr("Non-taxable"),
+                TaxGroup::Other => tr("Other (8%)"),
+            }
+        )
+    }
+}
+
+/// How an amount exactly half a cent off is rounded.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Mode {
+    /// Away from zero, e.g. $0.125 to $0.13.
+    #[default]
+    HalfUp,
+    /// To the even cent, e.g. $0.125 to $0.12 but $0.135 to $0.14.
+    HalfEven,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 2] = [Mode::HalfUp, Mode::HalfEven];
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Mode::HalfUp => "Half up",
+                Mode::HalfEven => "Half even (banker's)",
+            }
+        )
+    }
+}
+
+/// Whether tax is rounded for each item, or once for each tax group.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Per {
+    #[default]
+    Group,
+    Line,
+}
+
+impl Per {
+    pub const ALL: [Per; 2] = [Per::Group, Per::Line];
+}
+
+impl fmt::Display for Per {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Per::Group => "Each tax group",
+                Per::Line => "Each item",
+            }
+        )
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(default)]
+pub struct Rounding {
+    pub mode: Mode,
+    pub per: Per,
+}
+
+pub fn set(rounding: Rounding) {
+    let bits = rounding.mode as u8 | (rounding.per as u8) << 1;
+    ROUNDING.store(bits, Ordering::Relaxed);
+}
+
+pub fn rounding() -> Rounding {
+    let bits = ROUNDING.load(Ordering::Relaxed);
+    Rounding {
+        mode: Mode::ALL[usize::from(bits & 1)],
+        per: Per::ALL[usize::from(bits >> 1 & 1)],
+    }
+}
+
+impl Rounding {
+    /// `amount` rounded to the cent by this rule.
+    pub fn round(self, amount: f32) -> f32 {
+        // Drop the noise of binary fractions first, so that e.g. 2.675 is
+        // treated as exactly half a cent rather than a little less
+        let cents = (f64::from(amount) * 100.0 * 1e3).round() / 1e3;
+        let cents = match self.mode {
+            Mode::HalfUp => cents.round(),
+            Mode::HalfEven => cents.round_ties_even(),
+        };
+        (cents / 100.0) as f32
+    }
+}
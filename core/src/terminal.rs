@@ -0,0 +1,97 @@
+//! Card payments taken on a Stripe Terminal or Square Terminal reader
+//!
+//! Only what is set up and recorded is here: talking to the reader is left to
+//! the frontend.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Provider {
+    #[default]
+    Off,
+    Stripe,
+    Square,
+}
+
+impl Provider {
+    pub const ALL: [Provider; 3] =
+        [Provider::Off, Provider::Stripe, Provider::Square];
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Provider::Off => "Off",
+                Provider::Stripe => "Stripe Terminal",
+                Provider::Square => "Square Terminal",
+            }
+        )
+    }
+}
+
+/// Which reader to take payments on.
+///
+/// For Stripe, `api_key` is a secret key and `reader` a reader id such as
+/// `tmr_…`. For Square, `api_key` is an access token and `reader` a device
+/// id, using the sandbox API if `sandbox` is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub provider: Provider,
+    pub api_key: String,
+    pub reader: String,
+    /// The ISO code of the currency to charge in, e.g. "USD".
+    pub currency: String,
+    pub sandbox: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            provider: Provider::Off,
+            api_key: String::new(),
+            reader: String::new(),
+            currency: "USD".to_string(),
+            sandbox: false,
+        }
+    }
+}
+
+impl Config {
+    pub fn is_enabled(&self) -> bool {
+        self.provider != Provider::Off
+            && !self.api_key.is_empty()
+            && !self.reader.is_empty()
+    }
+}
+
+/// A card payment recorded on a sale.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Card {
+    pub provider: Provider,
+    pub amount: f32,
+    pub brand: String,
+    pub last4: String,
+    /// The provider's id of the payment, for looking it up later.
+    pub reference: String,
+}
+
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} •••• {}", self.brand, self.last4)
+    }
+}
+
+/// A payment sent to the reader which the guest hasn't completed yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pending {
+    pub provider: Provider,
+    /// The Stripe payment intent or Square checkout id.
+    pub id: String,
+    pub amount: f32,
+}
@@ -0,0 +1,339 @@
+//! Pooling the day's tips and service charges and sharing them out
+//!
+//! The pool is every gratuity and service charge taken on the sales of a
+//! day. It is shared among the staff who worked that day, by the hours of
+//! their shifts, by a weight set for each of them (e.g. 2 for a server and
+//! 1 for a busser), or by both. Payouts are in whole cents and always add
+//! up to the pool.
+//!
+//! The tips suggested on a sale are here too, as percentages of the sale
+//! before or after tax.
+use chrono::{Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::data::{csv_escape, Error};
+use crate::query::{self, Range};
+use crate::sale::Totals;
+use crate::shift::Shifts;
+use crate::store::Format;
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Basis {
+    #[default]
+    Hours,
+    Weight,
+    /// Hours times weight, e.g. for a points system.
+    WeightedHours,
+}
+
+impl Basis {
+    pub const ALL: [Basis; 3] =
+        [Basis::Hours, Basis::Weight, Basis::WeightedHours];
+}
+
+impl fmt::Display for Basis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Basis::Hours => "By hours",
+                Basis::Weight => "By weight",
+                Basis::WeightedHours => "By hours × weight",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub basis: Basis,
+    /// The weight of each employee, by name; 1 if not set.
+    pub weights: BTreeMap<String, f32>,
+}
+
+impl Config {
+    pub fn weight(&self, employee: &str) -> f32 {
+        self.weights.get(employee).copied().unwrap_or(1.0)
+    }
+}
+
+/// What suggested tips are a percentage of.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Base {
+    /// The subtotal, before tax.
+    #[default]
+    Subtotal,
+    /// The subtotal with tax, leaving out service charges and fees.
+    Total,
+}
+
+impl Base {
+    pub const ALL: [Base; 2] = [Base::Subtotal, Base::Total];
+
+    /// The amount of `totals` tips are a percentage of.
+    pub fn of(self, totals: &Totals) -> f32 {
+        match self {
+            Base::Subtotal => totals.subtotal,
+            Base::Total => totals.subtotal + totals.tax,
+        }
+    }
+}
+
+impl fmt::Display for Base {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Base::Subtotal => "Before tax",
+                Base::Total => "After tax",
+            }
+        )
+    }
+}
+
+/// The tips offered with a click when taking a gratuity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Suggestions {
+    pub base: Base,
+    /// The percentages offered, in order.
+    pub percents: Vec<f32>,
+}
+
+impl Default for Suggestions {
+    fn default() -> Self {
+        Self {
+            base: Base::default(),
+            percents: vec![15.0, 18.0, 20.0],
+        }
+    }
+}
+
+impl Suggestions {
+    /// Offer `percent` too, unless it is already.
+    pub fn add(&mut self, percent: f32) {
+        if !self.percents.contains(&percent) {
+            self.percents.push(percent);
+            self.percents.sort_by(f32::total_cmp);
+        }
+    }
+}
+
+/// What was taken on a day, and who worked it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Pool {
+    pub day: NaiveDate,
+    pub gratuities: f32,
+    pub service_charges: f32,
+    /// The hours each employee worked on the day.
+    pub hours: BTreeMap<String, f32>,
+}
+
+/// The share of the pool of one employee.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Payout {
+    pub employee: String,
+    pub hours: f32,
+    pub weight: f32,
+    /// The part of the pool, from 0 to 1.
+    pub share: f32,
+    pub amount: f32,
+}
+
+impl Pool {
+    pub fn total(&self) -> f32 {
+        self.gratuities + self.service_charges
+    }
+
+    /// Share the pool out among the staff as `config` says.
+    pub fn payouts(&self, config: &Config) -> Vec<Payout> {
+        let points: Vec<(&String, f32, f32, f64)> = self
+            .hours
+            .iter()
+            .map(|(employee, &hours)| {
+                let weight = config.weight(employee).max(0.0);
+                let points = match config.basis {
+                    Basis::Hours => hours,
+                    Basis::Weight => weight,
+                    Basis::WeightedHours => hours * weight,
+                };
+                (employee, hours, weight, f64::from(points))
+            })
+            .collect();
+        let sum: f64 = points.iter().map(|(.., points)| points).sum();
+        let pool = (f64::from(self.total()) * 100.0).round() as i64;
+
+        // Cents rounded down first, with the ones left over going to the
+        // largest remainders
+        let exact: Vec<f64> = points
+            .iter()
+            .map(|(.., points)| {
+                if sum > 0.0 {
+                    pool as f64 * points / sum
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let mut cents: Vec<i64> =
+            exact.iter().map(|exact| exact.floor() as i64).collect();
+        let mut by_remainder: Vec<usize> = (0..exact.len()).collect();
+        by_remainder.sort_by(|&a, &b| {
+            (exact[b] - exact[b].floor())
+                .total_cmp(&(exact[a] - exact[a].floor()))
+        });
+        let left = if sum > 0.0 {
+            pool - cents.iter().sum::<i64>()
+        } else {
+            0
+        };
+        for &i in by_remainder.iter().take(left.max(0) as usize) {
+            cents[i] += 1;
+        }
+
+        points
+            .iter()
+            .zip(cents)
+            .map(|(&(employee, hours, weight, points), cents)| Payout {
+                employee: employee.clone(),
+                hours,
+                weight,
+                share: if sum > 0.0 {
+                    (points / sum) as f32
+                } else {
+                    0.0
+                },
+                amount: cents as f32 / 100.0,
+            })
+            .collect()
+    }
+}
+
+/// The pool of `day`, from the sales in the store in `format` and the
+/// archive, and the shifts worked on it.
+pub fn pool(format: Format, day: NaiveDate) -> Result<Pool, Error> {
+    let mut pool = Pool {
+        day,
+        ..Pool::default()
+    };
+
+    let range = Range {
+        from: Some(day),
+        to: Some(day),
+    };
+    let mut visit = |_, sale: crate::sale::Sale| {
+        let totals = sale.totals();
+        pool.gratuities += totals.gratuity;
+        pool.service_charges += totals.service_charge;
+    };
+    query::scan(format, range, &mut visit)?;
+    crate::archive::scan(range, &mut visit)?;
+
+    let start = day
+        .and_hms_opt(0, 0, 0)
+        .and_then(|start| start.and_local_timezone(Local).earliest());
+    let end = day
+        .succ_opt()
+        .and_then(|next| next.and_hms_opt(0, 0, 0))
+        .and_then(|end| end.and_local_timezone(Local).earliest());
+    if let (Some(start), Some(end)) = (start, end) {
+        let (start, end) = (start.with_timezone(&Utc), end.with_timezone(&Utc));
+        for shift in Shifts::load().iter() {
+            let from = shift.clock_in.max(start);
+            let to = shift.clock_out.unwrap_or_else(Utc::now).min(end);
+            if to > from {
+                *pool.hours.entry(shift.employee.clone()).or_default() +=
+                    (to - from).num_minutes() as f32 / 60.0;
+            }
+        }
+    }
+
+    Ok(pool)
+}
+
+/// The payouts of `pool` as CSV, with the pool itself on the last line.
+pub fn csv(pool: &Pool, payouts: &[Payout]) -> String {
+    let mut csv = String::from("day,employee,hours,weight,share,payout\n");
+    for payout in payouts {
+        csv.push_str(&format!(
+            "{},{},{:.2},{},{:.4},{:.2}\n",
+            pool.day,
+            csv_escape(&payout.employee),
+            payout.hours,
+            payout.weight,
+            payout.share,
+            payout.amount
+        ));
+    }
+    csv.push_str(&format!("{},Pool,,,,{:.2}\n", pool.day, pool.total()));
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool() -> Pool {
+        Pool {
+            day: NaiveDate::from_ymd_opt(2024, 3, 15).unwrap(),
+            gratuities: 80.0,
+            service_charges: 20.01,
+            hours: BTreeMap::from([
+                ("Alice".to_string(), 8.0),
+                ("Bob".to_string(), 4.0),
+                ("Carol".to_string(), 4.0),
+            ]),
+        }
+    }
+
+    fn amounts(payouts: &[Payout]) -> Vec<f32> {
+        payouts.iter().map(|payout| payout.amount).collect()
+    }
+
+    #[test]
+    fn shares_by_hours_add_up_to_the_pool() {
+        let payouts = pool().payouts(&Config::default());
+        assert_eq!(amounts(&payouts), [50.01, 25.0, 25.0]);
+        assert_eq!(payouts[0].share, 0.5);
+    }
+
+    #[test]
+    fn shares_by_weight_and_by_weighted_hours() {
+        let mut config = Config {
+            basis: Basis::Weight,
+            weights: BTreeMap::from([("Alice".to_string(), 2.0)]),
+        };
+        assert_eq!(amounts(&pool().payouts(&config)), [50.01, 25.0, 25.0]);
+
+        config.basis = Basis::WeightedHours;
+        config.weights.insert("Carol".to_string(), 0.0);
+        assert_eq!(amounts(&pool().payouts(&config)), [80.01, 20.0, 0.0]);
+    }
+
+    #[test]
+    fn suggested_tips_before_or_after_tax() {
+        let totals = Totals {
+            subtotal: 40.0,
+            tax: 3.2,
+            service_charge: 7.2,
+            gratuity: 5.0,
+            ..Totals::default()
+        };
+        assert_eq!(Base::Subtotal.of(&totals), 40.0);
+        assert_eq!(Base::Total.of(&totals), 43.2);
+
+        let mut suggestions = Suggestions::default();
+        suggestions.add(10.0);
+        suggestions.add(18.0);
+        assert_eq!(suggestions.percents, [10.0, 15.0, 18.0, 20.0]);
+    }
+}
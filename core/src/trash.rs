@@ -0,0 +1,119 @@
+//! Deleted sales, kept in `trash.json` for a while in case they are wanted
+//! back
+//!
+//! Deleting a sale moves it out of the store into the trash, from which it
+//! can be restored or purged for good. Sales deleted longer ago than the
+//! retention set in the settings are purged on their own.
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::PathBuf;
+
+use crate::data::{self, Error};
+use crate::data_dir;
+use crate::sale::Sale;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trashed {
+    pub deleted_at: DateTime<Utc>,
+    pub sale: Sale,
+}
+
+/// The deleted sales, by the id they had in the store.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Trash {
+    sales: BTreeMap<usize, Trashed>,
+}
+
+impl Trash {
+    fn path() -> PathBuf {
+        data_dir().join("trash.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save in the background, see [`data::save_json`].
+    pub fn save(
+        &self,
+    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        data::save_json(Self::path(), self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sales.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.sales.len()
+    }
+
+    /// The deleted sales, the last deleted first.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Trashed)> {
+        let mut sales: Vec<_> =
+            self.sales.iter().map(|(id, sale)| (*id, sale)).collect();
+        sales.sort_by_key(|(_, sale)| std::cmp::Reverse(sale.deleted_at));
+        sales.into_iter()
+    }
+
+    /// The highest id in the trash, so that new sales never reuse it.
+    pub fn last_id(&self) -> Option<usize> {
+        self.sales.keys().last().copied()
+    }
+
+    pub fn put(&mut self, id: usize, sale: Sale, deleted_at: DateTime<Utc>) {
+        self.sales.insert(id, Trashed { deleted_at, sale });
+    }
+
+    /// Take sale `id` back out of the trash.
+    pub fn take(&mut self, id: usize) -> Option<Sale> {
+        self.sales.remove(&id).map(|trashed| trashed.sale)
+    }
+
+    /// Purge the sales deleted more than `days` before `now`, returning
+    /// their ids.
+    pub fn purge_older(&mut self, days: u32, now: DateTime<Utc>) -> Vec<usize> {
+        let before = now - Duration::days(days.into());
+        let old: Vec<usize> = self
+            .sales
+            .iter()
+            .filter(|(_, trashed)| trashed.deleted_at < before)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in &old {
+            self.sales.remove(id);
+        }
+        old
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn sales_are_purged_after_the_retention() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 31, 12, 0, 0).unwrap();
+        let mut trash = Trash::default();
+        trash.put(3, Sale::default(), now - Duration::days(40));
+        trash.put(7, Sale::default(), now - Duration::days(2));
+        trash.put(5, Sale::default(), now - Duration::days(31));
+
+        let ids: Vec<usize> = trash.iter().map(|(id, _)| id).collect();
+        assert_eq!(ids, [7, 5, 3]);
+        assert_eq!(trash.last_id(), Some(7));
+
+        assert_eq!(trash.purge_older(30, now), [3, 5]);
+        assert_eq!(trash.len(), 1);
+        assert!(trash.take(7).is_some());
+        assert!(trash.take(7).is_none());
+        assert!(trash.is_empty());
+    }
+}
@@ -0,0 +1,230 @@
+//! Tax registration numbers, e.g. VAT IDs, and a check of their format
+//!
+//! The store's number is set up in settings for the country it is
+//! registered in, and a customer's is kept with them. Only the format is
+//! checked, by the rules of the country, not whether the number was ever
+//! issued. A number starting with the code of a country, as EU VAT IDs do,
+//! is checked by the rules of that country.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Country {
+    Austria,
+    France,
+    Germany,
+    Italy,
+    Netherlands,
+    Spain,
+    UnitedKingdom,
+    UnitedStates,
+    /// Any other country, whose numbers aren't checked beyond their
+    /// characters.
+    #[default]
+    Other,
+}
+
+impl Country {
+    pub const ALL: [Country; 9] = [
+        Country::Austria,
+        Country::France,
+        Country::Germany,
+        Country::Italy,
+        Country::Netherlands,
+        Country::Spain,
+        Country::UnitedKingdom,
+        Country::UnitedStates,
+        Country::Other,
+    ];
+
+    /// The code numbers of the country start with, e.g. "DE".
+    fn prefix(self) -> Option<&'static str> {
+        match self {
+            Country::Austria => Some("AT"),
+            Country::France => Some("FR"),
+            Country::Germany => Some("DE"),
+            Country::Italy => Some("IT"),
+            Country::Netherlands => Some("NL"),
+            Country::Spain => Some("ES"),
+            Country::UnitedKingdom => Some("GB"),
+            Country::UnitedStates | Country::Other => None,
+        }
+    }
+
+    /// What a number of the country looks like.
+    pub fn example(self) -> &'static str {
+        match self {
+            Country::Austria => "ATU12345678",
+            Country::France => "FR12345678901",
+            Country::Germany => "DE123456789",
+            Country::Italy => "IT12345678901",
+            Country::Netherlands => "NL123456789B01",
+            Country::Spain => "ESX1234567X",
+            Country::UnitedKingdom => "GB123456789",
+            Country::UnitedStates => "12-3456789",
+            Country::Other => "",
+        }
+    }
+
+    /// Whether `number`, without the prefix, is of the country's format.
+    fn matches(self, number: &str) -> bool {
+        if !number.is_ascii() {
+            return false;
+        }
+        let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+        let len = number.len();
+        match self {
+            Country::Austria => {
+                len == 9 && number.starts_with('U') && digits(&number[1..])
+            }
+            Country::France => {
+                len == 11
+                    && number[..2].chars().all(|c| c.is_ascii_alphanumeric())
+                    && digits(&number[2..])
+            }
+            Country::Germany | Country::UnitedStates => {
+                len == 9 && digits(number)
+            }
+            Country::Italy => len == 11 && digits(number),
+            Country::Netherlands => {
+                len == 12
+                    && digits(&number[..9])
+                    && &number[9..10] == "B"
+                    && digits(&number[10..])
+            }
+            Country::Spain => {
+                len == 9
+                    && number.chars().all(|c| c.is_ascii_alphanumeric())
+                    && digits(&number[1..8])
+            }
+            Country::UnitedKingdom => (len == 9 || len == 12) && digits(number),
+            Country::Other => {
+                (4..=20).contains(&len)
+                    && number.chars().all(|c| c.is_ascii_alphanumeric())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Country {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Country::Austria => "Austria",
+                Country::France => "France",
+                Country::Germany => "Germany",
+                Country::Italy => "Italy",
+                Country::Netherlands => "Netherlands",
+                Country::Spain => "Spain",
+                Country::UnitedKingdom => "United Kingdom",
+                Country::UnitedStates => "United States",
+                Country::Other => "Other",
+            }
+        )
+    }
+}
+
+/// The business the receipts are issued by, as registered for tax.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Registration {
+    pub country: Country,
+    /// The tax ID as typed, none if empty.
+    pub number: String,
+}
+
+/// `number` without spaces, dots and dashes, in capitals.
+pub fn normalize(number: &str) -> String {
+    number
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '.' | '-'))
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// Check the format of `number` for `country`, or for the country it
+/// starts with the code of, giving it back normalized with the code of
+/// its country, e.g. `DE123456789` for `de 123 456 789`.
+pub fn check(country: Country, number: &str) -> Result<String, String> {
+    let number = normalize(number);
+    if number.is_empty() {
+        return Err("The tax ID is empty".to_string());
+    }
+    let prefixed = Country::ALL.into_iter().find(|country| {
+        country
+            .prefix()
+            .is_some_and(|prefix| number.starts_with(prefix))
+    });
+    // A number of another country given with its code is checked by the
+    // rules of that one
+    let (country, rest) = match prefixed {
+        Some(prefixed) => {
+            let prefix = prefixed.prefix().unwrap_or_default();
+            let rest = &number[prefix.len()..];
+            if prefixed.matches(rest) || !country.matches(&number) {
+                (prefixed, rest)
+            } else {
+                (country, number.as_str())
+            }
+        }
+        None => (country, number.as_str()),
+    };
+    if !country.matches(rest) {
+        return Err(match country {
+            Country::Other => {
+                "A tax ID is 4 to 20 letters and digits".to_string()
+            }
+            country => format!(
+                "Not a tax ID of {country}, which look like {}",
+                country.example()
+            ),
+        });
+    }
+    Ok(format!("{}{rest}", country.prefix().unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_checked_by_their_country() {
+        let check = |country, number| check(country, number).ok();
+        assert_eq!(
+            check(Country::Germany, "de 123.456.789"),
+            Some("DE123456789".to_string())
+        );
+        // The code is added when left out
+        assert_eq!(
+            check(Country::Germany, "123456789"),
+            Some("DE123456789".to_string())
+        );
+        assert_eq!(check(Country::Germany, "DE12345678"), None);
+        assert_eq!(
+            check(Country::Netherlands, "NL123456789B01"),
+            Some("NL123456789B01".to_string())
+        );
+        assert_eq!(check(Country::Netherlands, "NL123456789X01"), None);
+        assert_eq!(
+            check(Country::Austria, "ATU12345678"),
+            Some("ATU12345678".to_string())
+        );
+        assert_eq!(
+            check(Country::UnitedStates, "12-3456789"),
+            Some("123456789".to_string())
+        );
+
+        // A customer abroad is checked by the rules of their country
+        assert_eq!(
+            check(Country::Germany, "FR AB123456789"),
+            Some("FRAB123456789".to_string())
+        );
+        assert_eq!(check(Country::Germany, "FR123"), None);
+        assert_eq!(check(Country::Other, "X1"), None);
+        assert!(check(Country::Other, " ").is_none());
+    }
+}
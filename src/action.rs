@@ -20,7 +20,11 @@
 //! In many instances, you will want to return both an `Instruction` and a
 //! [`Task`].  Say, for example, when you'd like to navigate to a different
 //! screen *and* focus the first input field. This can be achieved with e.g.
-//! `Action::instruction(Instruction::NavigateToScreen).with_task(focus_first_input())`.
+//! `Action::navigate(Screen::Settings).with_effect(focus_first_input())`.
+//!
+//! Navigation is the one instruction every screen shares, so it has its own
+//! helper: a screen whose `Instruction` can be made `From` a [`Screen`] gets
+//! [`Action::navigate`], and the app sets up and shows that screen.
 //!
 //! Generally speaking, an `Action` will alwys be created as a result of
 //! processing some other `Message`. For example, a
@@ -56,9 +60,14 @@ use iced::advanced::graphics::futures::MaybeSend;
 use iced::Task;
 use std::fmt;
 
+use crate::toast;
+use crate::Screen;
+
 pub struct Action<I, Message> {
     pub instruction: Option<I>,
     pub task: Task<Message>,
+    /// A notification to show over whatever screen is current.
+    pub toast: Option<(toast::Kind, String)>,
 }
 
 impl<I, Message> Action<I, Message> {
@@ -67,6 +76,7 @@ impl<I, Message> Action<I, Message> {
         Self {
             instruction: None,
             task: Task::none(),
+            toast: None,
         }
     }
 
@@ -75,6 +85,7 @@ impl<I, Message> Action<I, Message> {
         Self {
             instruction: Some(instruction),
             task,
+            toast: None,
         }
     }
 
@@ -84,14 +95,24 @@ impl<I, Message> Action<I, Message> {
         Self {
             instruction: Some(instruction),
             task: Task::none(),
+            toast: None,
         }
     }
 
-    /// Create a new `Action` with a [`Task`](iced::Task).
-    pub fn task(task: Task<Message>) -> Self {
+    /// Create a new `Action` asking the app to go to `screen`.
+    pub fn navigate(screen: Screen) -> Self
+    where
+        I: From<Screen>,
+    {
+        Self::instruction(I::from(screen))
+    }
+
+    /// Create a new `Action` with a [`Task`](iced::Task) for the runtime.
+    pub fn effect(task: Task<Message>) -> Self {
         Self {
             instruction: None,
             task,
+            toast: None,
         }
     }
 
@@ -107,6 +128,7 @@ impl<I, Message> Action<I, Message> {
         Action {
             instruction: self.instruction,
             task: self.task.map(f),
+            toast: self.toast,
         }
     }
 
@@ -122,6 +144,7 @@ impl<I, Message> Action<I, Message> {
         Action {
             instruction: self.instruction.map(f),
             task: self.task,
+            toast: self.toast,
         }
     }
 
@@ -131,9 +154,28 @@ impl<I, Message> Action<I, Message> {
         self
     }
 
-    /// Sets the [`Task`](iced::Task) of an `Action`.
-    pub fn with_task(mut self, task: Task<Message>) -> Self {
-        self.task = task;
+    /// Adds a [`Task`](iced::Task) to an `Action`, run alongside any it
+    /// already has.
+    pub fn with_effect(mut self, task: Task<Message>) -> Self
+    where
+        Message: 'static,
+    {
+        self.task = Task::batch([self.task, task]);
+        self
+    }
+
+    /// Create a new `Action` showing a toast notification.
+    pub fn toast(kind: toast::Kind, message: impl Into<String>) -> Self {
+        Self::none().with_toast(kind, message)
+    }
+
+    /// Sets the toast notification of an `Action`.
+    pub fn with_toast(
+        mut self,
+        kind: toast::Kind,
+        message: impl Into<String>,
+    ) -> Self {
+        self.toast = Some((kind, message.into()));
         self
     }
 }
@@ -144,6 +186,134 @@ impl<Instruction: fmt::Debug, Message> fmt::Debug
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Action")
             .field("instruction", &self.instruction)
+            .field("toast", &self.toast)
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iced::futures::executor::block_on;
+    use iced::futures::StreamExt;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Child {
+        Navigate(Screen),
+        Save,
+    }
+
+    impl From<Screen> for Child {
+        fn from(screen: Screen) -> Self {
+            Child::Navigate(screen)
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Parent {
+        Child(Child),
+    }
+
+    fn toast(
+        kind: toast::Kind,
+        message: &str,
+    ) -> Option<(toast::Kind, String)> {
+        Some((kind, message.to_string()))
+    }
+
+    /// The messages `task` gives, run to the end.
+    fn run<T: 'static>(task: Task<T>) -> Vec<T> {
+        let Some(stream) = iced_runtime::task::into_stream(task) else {
+            return Vec::new();
+        };
+        block_on(
+            stream
+                .filter_map(|action| async move {
+                    match action {
+                        iced_runtime::Action::Output(message) => Some(message),
+                        _ => None,
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn none_is_empty() {
+        let action = Action::<Child, ()>::none();
+        assert_eq!(action.instruction, None);
+        assert_eq!(action.toast, None);
+    }
+
+    #[test]
+    fn navigate_wraps_the_screen() {
+        let action = Action::<Child, ()>::navigate(Screen::Settings);
+        assert_eq!(action.instruction, Some(Child::Navigate(Screen::Settings)));
+    }
+
+    #[test]
+    fn effect_has_no_instruction() {
+        let action = Action::<Child, ()>::effect(Task::done(()));
+        assert_eq!(action.instruction, None);
+        assert_eq!(action.toast, None);
+    }
+
+    #[test]
+    fn map_keeps_instruction_and_toast() {
+        let action = Action::<Child, u8>::instruction(Child::Save)
+            .with_effect(Task::done(1))
+            .with_toast(toast::Kind::Success, "Saved")
+            .map(|m| m.to_string());
+
+        assert_eq!(action.instruction, Some(Child::Save));
+        assert_eq!(action.toast, toast(toast::Kind::Success, "Saved"));
+        assert_eq!(run(action.task), ["1"]);
+    }
+
+    #[test]
+    fn effects_are_added_to_earlier_ones() {
+        let action = Action::<Child, u8>::effect(Task::done(1))
+            .with_effect(Task::done(2));
+
+        let mut messages = run(action.task);
+        messages.sort();
+        assert_eq!(messages, [1, 2]);
+    }
+
+    #[test]
+    fn map_instruction_maps_only_the_instruction() {
+        let action = Action::<Child, ()>::navigate(Screen::List)
+            .with_toast(toast::Kind::Warning, "Careful")
+            .map_instruction(Parent::Child);
+
+        assert_eq!(
+            action.instruction,
+            Some(Parent::Child(Child::Navigate(Screen::List)))
+        );
+        assert_eq!(action.toast, toast(toast::Kind::Warning, "Careful"));
+    }
+
+    #[test]
+    fn map_instruction_of_none_stays_none() {
+        let action = Action::<Child, ()>::none().map_instruction(Parent::Child);
+        assert_eq!(action.instruction, None);
+    }
+
+    #[test]
+    fn later_calls_replace_earlier_ones() {
+        let action = Action::<Child, ()>::navigate(Screen::List)
+            .with_instruction(Child::Save)
+            .with_toast(toast::Kind::Error, "First")
+            .with_toast(toast::Kind::Success, "Second");
+
+        assert_eq!(action.instruction, Some(Child::Save));
+        assert_eq!(action.toast, toast(toast::Kind::Success, "Second"));
+    }
+
+    #[test]
+    fn toast_has_no_instruction() {
+        let action = Action::<Child, ()>::toast(toast::Kind::Error, "Oops");
+        assert_eq!(action.instruction, None);
+        assert_eq!(action.toast, toast(toast::Kind::Error, "Oops"));
+    }
+}
@@ -0,0 +1,152 @@
+//! The archive tab of the sales list, browsing old sales a month at a time
+use iced::widget::{
+    button, column, container, horizontal_space, row, scrollable, text,
+};
+use iced::{Alignment, Element, Fill, Font, Length, Task};
+use std::collections::BTreeMap;
+
+use crate::data::Error;
+use crate::i18n::{self, money, tr};
+use crate::sale::Sale;
+use crate::ticket;
+
+pub use receipts_core::archive::*;
+
+async fn load_month(month: String) -> Result<BTreeMap<usize, Sale>, Error> {
+    read(&month)
+}
+
+/// The archive tab of the sales list.
+#[derive(Debug, Default)]
+pub struct Archive {
+    pub index: Index,
+    /// The month opened, whose sales are the only ones loaded.
+    pub month: Option<String>,
+    pub sales: Option<Result<BTreeMap<usize, Sale>, String>>,
+    pub selected: Option<usize>,
+}
+
+impl Archive {
+    pub fn is_empty(&self) -> bool {
+        self.index.months.is_empty()
+    }
+
+    /// Use `index` after sales were archived, closing the month opened in
+    /// case it changed.
+    pub fn reindex(&mut self, index: Index) {
+        self.index = index;
+        self.month = None;
+        self.sales = None;
+        self.selected = None;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SelectMonth(String),
+    Loaded(String, Result<BTreeMap<usize, Sale>, String>),
+    SelectSale(usize),
+}
+
+pub fn update(archive: &mut Archive, message: Message) -> Task<Message> {
+    match message {
+        Message::SelectMonth(month) => {
+            archive.month = Some(month.clone());
+            archive.sales = None;
+            archive.selected = None;
+            Task::perform(load_month(month.clone()), move |sales| {
+                Message::Loaded(month.clone(), sales.map_err(|e| e.to_string()))
+            })
+        }
+        Message::Loaded(month, sales) => {
+            // Unless another month was opened while it was loading
+            if archive.month.as_ref() == Some(&month) {
+                archive.sales = Some(sales);
+            }
+            Task::none()
+        }
+        Message::SelectSale(id) => {
+            archive.selected = Some(id);
+            Task::none()
+        }
+    }
+}
+
+pub fn view(archive: &Archive) -> Element<'_, Message> {
+    let months = archive.index.months.iter().rev().fold(
+        row![].spacing(5),
+        |row, (month, count)| {
+            let selected = archive.month.as_ref() == Some(month);
+            row.push(
+                button(text(format!("{month} ({count})")).size(12))
+                    .padding([2, 8])
+                    .style(if selected {
+                        button::primary
+                    } else {
+                        button::secondary
+                    })
+                    .on_press(Message::SelectMonth(month.clone())),
+            )
+        },
+    );
+
+    let body: Element<_> = match &archive.sales {
+        None if archive.month.is_some() => text(tr("Loading sales…"))
+            .shaping(text::Shaping::Advanced)
+            .into(),
+        None => text(tr("Pick a month to see its sales")).into(),
+        Some(Err(error)) => text(error).style(text::danger).into(),
+        Some(Ok(sales)) => {
+            let list = sales.iter().rev().fold(
+                column![].spacing(5).width(Fill),
+                |col, (id, sale)| {
+                    col.push(
+                        button(
+                            row![
+                                text(format!("#{id} {}", sale.name))
+                                    .width(Fill),
+                                text(i18n::date(&sale.created_at)).size(12),
+                                text(money(sale.calculate_total()))
+                                    .align_x(Alignment::End)
+                                    .width(100.0),
+                            ]
+                            .spacing(10)
+                            .align_y(Alignment::Center),
+                        )
+                        .style(if archive.selected == Some(*id) {
+                            button::primary
+                        } else {
+                            button::secondary
+                        })
+                        .on_press(Message::SelectSale(*id))
+                        .width(Fill),
+                    )
+                },
+            );
+
+            let receipt: Element<_> = match archive
+                .selected
+                .and_then(|id| Some((id, sales.get(&id)?)))
+            {
+                Some((id, sale)) => container(scrollable(
+                    text(ticket::receipt(Some(id), sale, &[], "", None))
+                        .font(Font::MONOSPACE)
+                        .size(12)
+                        .shaping(text::Shaping::Advanced),
+                ))
+                .padding(15)
+                .width(Length::Shrink)
+                .height(Fill)
+                .style(container::rounded_box)
+                .into(),
+                None => horizontal_space().width(0).into(),
+            };
+
+            row![scrollable(list).height(Fill).width(Fill), receipt]
+                .spacing(20)
+                .into()
+        }
+    };
+
+    column![months.wrap(), body].spacing(20).height(Fill).into()
+}
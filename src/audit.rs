@@ -0,0 +1,40 @@
+//! An append-only log of changes made to sales after they were closed
+//!
+//! Each entry is a line of JSON in `audit.log`, so the log can be read with
+//! any text tool and is never rewritten.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::config::data_dir;
+use crate::data::Error;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub at: DateTime<Utc>,
+    pub sale_id: usize,
+    /// What changed, e.g. "Gratuity changed from $0.00 to $5.00".
+    pub change: String,
+}
+
+fn path() -> PathBuf {
+    data_dir().join("audit.log")
+}
+
+/// Append an entry for a change to sale `sale_id`.
+pub fn record(sale_id: usize, change: impl Into<String>) -> Result<(), Error> {
+    let entry = Entry {
+        at: Utc::now(),
+        sale_id,
+        change: change.into(),
+    };
+
+    std::fs::create_dir_all(data_dir())?;
+    let mut log = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path())?;
+    writeln!(log, "{}", serde_json::to_string(&entry)?)?;
+    Ok(())
+}
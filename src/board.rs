@@ -0,0 +1,57 @@
+//! An "order ready" board shown in a separate window for counter-service
+//! pickup
+use iced::widget::{button, column, container, text, Row};
+use iced::Alignment::Center;
+use iced::{Element, Fill};
+use std::collections::BTreeMap;
+
+use crate::sale::OrderStatus;
+use crate::store::Header;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    PickedUp(usize),
+}
+
+pub fn view<'a>(sales: BTreeMap<usize, Header>) -> Element<'a, Message> {
+    let ready: Vec<(usize, Header)> = sales
+        .into_iter()
+        .filter(|(_, sale)| sale.order_status == OrderStatus::Ready)
+        .collect();
+
+    let orders: Element<_> = if ready.is_empty() {
+        container(text("No orders ready").size(32))
+            .center(Fill)
+            .into()
+    } else {
+        ready
+            .into_iter()
+            .fold(Row::new().spacing(20), |row, (id, sale)| {
+                let number = text(format!("#{id}")).size(72);
+                let order = match sale.pager {
+                    Some(pager) => {
+                        column![number, text(format!("Pager {pager}")).size(28)]
+                    }
+                    None => column![number],
+                };
+
+                row.push(
+                    button(order.align_x(Center))
+                        .padding([20, 40])
+                        .style(button::success)
+                        .on_press(Message::PickedUp(id)),
+                )
+            })
+            .wrap()
+            .into()
+    };
+
+    container(
+        column![text("Orders ready").size(40), orders]
+            .spacing(30)
+            .width(Fill)
+            .height(Fill),
+    )
+    .padding(30)
+    .into()
+}
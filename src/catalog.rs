@@ -0,0 +1,4 @@
+//! The product catalog, and the screen managing it
+pub use receipts_core::catalog::*;
+
+pub mod manage;
@@ -0,0 +1,600 @@
+//! Manage catalog products and their stock
+use chrono::Weekday;
+use iced::widget::{
+    button, checkbox, column, container, horizontal_space, pick_list, row,
+    scrollable, text, text_input,
+};
+use iced::{Alignment, Element, Fill};
+
+use super::promotion::{self, Promotion, Slot};
+use super::{Catalog, Product, Tare, Tier};
+use crate::allergen::Allergen;
+use crate::i18n;
+use crate::style;
+use crate::tax::TaxGroup;
+use crate::{Action, Hotkey, Screen};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Back,
+    Add,
+    Remove(usize),
+    Update(usize, Field),
+}
+
+#[derive(Debug, Clone)]
+pub enum Field {
+    Name(String),
+    Price(String),
+    TaxGroup(TaxGroup),
+    Category(String),
+    Stock(String),
+    LowStock(String),
+    ByWeight(bool),
+    OpenPrice(bool),
+    Allergen(Allergen, bool),
+    AddTare,
+    RemoveTare(usize),
+    TareName(usize, String),
+    TareWeight(usize, String),
+    AddTier,
+    RemoveTier(usize),
+    TierMin(usize, String),
+    TierPrice(usize, String),
+    AddPromotion,
+    RemovePromotion(usize),
+    PromotionName(usize, String),
+    PromotionDay(usize, Weekday, bool),
+    PromotionFrom(usize, Slot),
+    PromotionUntil(usize, Slot),
+    PromotionPrice(usize, String),
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Navigate(Screen),
+    Save,
+}
+
+impl From<Screen> for Instruction {
+    fn from(screen: Screen) -> Self {
+        Instruction::Navigate(screen)
+    }
+}
+
+pub fn update(
+    catalog: &mut Catalog,
+    message: Message,
+) -> Action<Instruction, Message> {
+    match message {
+        Message::Back => Action::navigate(Screen::List),
+        Message::Add => {
+            let id = catalog.add();
+            Action::instruction(Instruction::Save)
+                .with_effect(text_input::focus(name_id(id)))
+        }
+        Message::Remove(id) => {
+            catalog.remove(id);
+            Action::instruction(Instruction::Save)
+        }
+        Message::Update(id, field) => {
+            let Some(product) = catalog.get_mut(id) else {
+                return Action::none();
+            };
+            match field {
+                Field::Name(name) => product.name = name,
+                Field::Price(price) => {
+                    product.price = if price.is_empty() {
+                        None
+                    } else {
+                        i18n::parse_number(&price)
+                    };
+                }
+                Field::TaxGroup(group) => product.tax_group = group,
+                Field::Category(category) => product.category = category,
+                Field::Stock(stock) => match stock.trim() {
+                    "" => product.stock = None,
+                    stock => match stock.parse() {
+                        Ok(stock) => product.stock = Some(stock),
+                        Err(_) => return Action::none(),
+                    },
+                },
+                Field::LowStock(low) => match low.trim().parse() {
+                    Ok(low) => product.low_stock = low,
+                    Err(_) => return Action::none(),
+                },
+                Field::ByWeight(by_weight) => product.by_weight = by_weight,
+                Field::OpenPrice(open) => product.open_price = open,
+                Field::Allergen(allergen, true) => {
+                    if !product.allergens.contains(&allergen) {
+                        product.allergens.push(allergen);
+                    }
+                }
+                Field::Allergen(allergen, false) => {
+                    product.allergens.retain(|a| *a != allergen);
+                }
+                Field::AddTare => product.tares.push(Tare {
+                    name: String::new(),
+                    weight: 0.0,
+                }),
+                Field::RemoveTare(index) => {
+                    if index < product.tares.len() {
+                        product.tares.remove(index);
+                    }
+                }
+                Field::TareName(index, name) => {
+                    if let Some(tare) = product.tares.get_mut(index) {
+                        tare.name = name;
+                    }
+                }
+                Field::AddTier => {
+                    let min = product.tiers.last().map_or(10, |t| t.min * 2);
+                    product.tiers.push(Tier {
+                        min,
+                        price: product.price.unwrap_or(0.0),
+                    });
+                }
+                Field::RemoveTier(index) => {
+                    if index < product.tiers.len() {
+                        product.tiers.remove(index);
+                    }
+                }
+                Field::TierMin(index, min) => {
+                    let Some(tier) = product.tiers.get_mut(index) else {
+                        return Action::none();
+                    };
+                    match min.trim().parse() {
+                        Ok(min) => tier.min = min,
+                        Err(_) => return Action::none(),
+                    }
+                }
+                Field::TierPrice(index, price) => {
+                    let Some(tier) = product.tiers.get_mut(index) else {
+                        return Action::none();
+                    };
+                    match i18n::parse_number(&price) {
+                        Some(price) => tier.price = price,
+                        None if price.is_empty() => tier.price = 0.0,
+                        None => return Action::none(),
+                    }
+                }
+                Field::AddPromotion => product
+                    .promotions
+                    .push(Promotion::new(product.price.unwrap_or(0.0))),
+                Field::RemovePromotion(index) => {
+                    if index < product.promotions.len() {
+                        product.promotions.remove(index);
+                    }
+                }
+                Field::PromotionName(index, name) => {
+                    if let Some(promotion) = product.promotions.get_mut(index) {
+                        promotion.name = name;
+                    }
+                }
+                Field::PromotionDay(index, day, on) => {
+                    if let Some(promotion) = product.promotions.get_mut(index) {
+                        promotion.days.retain(|d| *d != day);
+                        if on {
+                            promotion.days.push(day);
+                        }
+                    }
+                }
+                Field::PromotionFrom(index, slot) => {
+                    if let Some(promotion) = product.promotions.get_mut(index) {
+                        promotion.from = slot.0;
+                    }
+                }
+                Field::PromotionUntil(index, slot) => {
+                    if let Some(promotion) = product.promotions.get_mut(index) {
+                        promotion.until = slot.0;
+                    }
+                }
+                Field::PromotionPrice(index, price) => {
+                    let Some(promotion) = product.promotions.get_mut(index)
+                    else {
+                        return Action::none();
+                    };
+                    match i18n::parse_number(&price) {
+                        Some(price) => promotion.price = price,
+                        None if price.is_empty() => promotion.price = 0.0,
+                        None => return Action::none(),
+                    }
+                }
+                Field::TareWeight(index, weight) => {
+                    let Some(tare) = product.tares.get_mut(index) else {
+                        return Action::none();
+                    };
+                    match i18n::parse_number(&weight) {
+                        Some(weight) => tare.weight = weight,
+                        None if weight.is_empty() => tare.weight = 0.0,
+                        None => return Action::none(),
+                    }
+                }
+            }
+            Action::instruction(Instruction::Save)
+        }
+    }
+}
+
+pub fn view(catalog: &Catalog) -> Element<'_, Message> {
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text("Catalog").size(16),
+        horizontal_space(),
+        button("+ Add product")
+            .on_press(Message::Add)
+            .style(button::primary),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let body: Element<_> = if catalog.is_empty() {
+        container(text("No products yet")).center(Fill).into()
+    } else {
+        let column_headers = row![
+            text("Name").width(Fill),
+            text("Price").align_x(Alignment::End).width(100.0),
+            text("Tax Group").width(140.0),
+            text("Category").width(120.0),
+            text("Stock").align_x(Alignment::End).width(80.0),
+            text("Low at").align_x(Alignment::End).width(80.0),
+            horizontal_space().width(100),
+            horizontal_space().width(100),
+            horizontal_space().width(100),
+            horizontal_space().width(25),
+        ]
+        .spacing(5)
+        .padding([0, 10]);
+
+        scrollable(
+            catalog
+                .iter()
+                .fold(column![column_headers].spacing(5), |col, product| {
+                    col.push(product_row(product))
+                })
+                .padding(20),
+        )
+        .into()
+    };
+
+    container(
+        column![
+            header,
+            container(body)
+                .height(Fill)
+                .width(Fill)
+                .style(container::rounded_box)
+        ]
+        .spacing(20),
+    )
+    .padding(20)
+    .into()
+}
+
+fn product_row(product: &Product) -> Element<'_, Message> {
+    let id = product.id;
+    let warning: Element<_> = if product.is_out_of_stock() {
+        text("Out of stock").size(12).style(text::danger).into()
+    } else if product.is_low_on_stock() {
+        text("Low stock").size(12).style(text::danger).into()
+    } else {
+        horizontal_space().into()
+    };
+
+    let fields = row![
+        text_input(product.display_name(), &product.name)
+            .style(style::input)
+            .id(name_id(id))
+            .on_input(move |name| Message::Update(id, Field::Name(name)))
+            .width(Fill)
+            .padding(5),
+        text_input(
+            "Price",
+            &product.price.map_or(String::new(), |p| i18n::number(p, 2)),
+        )
+        .style(style::input)
+        .align_x(Alignment::End)
+        .on_input(move |price| Message::Update(id, Field::Price(price)))
+        .width(100.0)
+        .padding(5),
+        pick_list(&TaxGroup::ALL[..], Some(product.tax_group), move |g| {
+            Message::Update(id, Field::TaxGroup(g))
+        })
+        .width(140.0),
+        text_input("None", &product.category)
+            .style(style::input)
+            .on_input(move |category| {
+                Message::Update(id, Field::Category(category))
+            })
+            .width(120.0)
+            .padding(5),
+        text_input(
+            "Untracked",
+            &product.stock.map_or(String::new(), |s| s.to_string()),
+        )
+        .style(style::input)
+        .align_x(Alignment::End)
+        .on_input(move |stock| Message::Update(id, Field::Stock(stock)))
+        .width(80.0)
+        .padding(5),
+        text_input("0", &product.low_stock.to_string())
+            .style(style::input)
+            .align_x(Alignment::End)
+            .on_input(move |low| Message::Update(id, Field::LowStock(low)))
+            .width(80.0)
+            .padding(5),
+        container(warning).width(100.0),
+        checkbox("By weight", product.by_weight)
+            .on_toggle(move |by_weight| {
+                Message::Update(id, Field::ByWeight(by_weight))
+            })
+            .width(100.0),
+        checkbox("Open price", product.open_price)
+            .on_toggle(move |open| Message::Update(id, Field::OpenPrice(open)))
+            .width(100.0),
+        button(text("×").center())
+            .width(25.0)
+            .on_press(Message::Remove(id))
+            .style(button::danger),
+    ]
+    .spacing(5)
+    .align_y(Alignment::Center);
+
+    let content = column![fields, allergens(product)].spacing(5);
+    let content = if product.by_weight {
+        content.push(tares(product))
+    } else {
+        content.push(tiers(product))
+    };
+    let content = content.push(promotions(product));
+
+    container(content)
+        .style(container::rounded_box)
+        .padding(0)
+        .into()
+}
+
+fn allergens(product: &Product) -> Element<'_, Message> {
+    let id = product.id;
+    Allergen::ALL
+        .iter()
+        .fold(row![text("Allergens").size(12)], |row, &allergen| {
+            row.push(
+                checkbox(
+                    allergen.to_string(),
+                    product.allergens.contains(&allergen),
+                )
+                .on_toggle(move |on| {
+                    Message::Update(id, Field::Allergen(allergen, on))
+                })
+                .text_size(12)
+                .size(14),
+            )
+        })
+        .spacing(10)
+        .padding([0, 20])
+        .align_y(Alignment::Center)
+        .wrap()
+        .into()
+}
+
+/// The tare presets of a product sold by weight.
+fn tares(product: &Product) -> Element<'_, Message> {
+    let id = product.id;
+    let presets = product.tares.iter().enumerate().fold(
+        column![].spacing(5),
+        |col, (index, tare)| {
+            col.push(
+                row![
+                    text_input("Container", &tare.name)
+                        .style(style::input)
+                        .on_input(move |name| {
+                            Message::Update(id, Field::TareName(index, name))
+                        })
+                        .width(200.0)
+                        .padding(5),
+                    text_input("0", &i18n::number(tare.weight, 3))
+                        .style(style::input)
+                        .align_x(Alignment::End)
+                        .on_input(move |weight| {
+                            Message::Update(
+                                id,
+                                Field::TareWeight(index, weight),
+                            )
+                        })
+                        .width(80.0)
+                        .padding(5),
+                    text("kg"),
+                    button(text("×").center())
+                        .width(25.0)
+                        .on_press(Message::Update(id, Field::RemoveTare(index)))
+                        .style(button::danger),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center),
+            )
+        },
+    );
+
+    column![
+        text("Tare presets, the first is used by default").size(12),
+        presets,
+        button(text("+ Tare").size(12))
+            .on_press(Message::Update(id, Field::AddTare))
+            .style(button::secondary),
+    ]
+    .spacing(5)
+    .padding([5, 20])
+    .into()
+}
+
+/// Lower unit prices from a quantity on, for products sold by the unit.
+fn tiers(product: &Product) -> Element<'_, Message> {
+    let id = product.id;
+    product
+        .tiers
+        .iter()
+        .enumerate()
+        .fold(
+            row![text("Quantity prices").size(12)],
+            |row, (index, tier)| {
+                row.push(
+                    row![
+                        text_input("10", &tier.min.to_string())
+                            .style(style::input)
+                            .align_x(Alignment::End)
+                            .on_input(move |min| {
+                                Message::Update(id, Field::TierMin(index, min))
+                            })
+                            .width(50.0)
+                            .size(12)
+                            .padding(3),
+                        text("+ @").size(12),
+                        text_input("0.00", &i18n::number(tier.price, 2))
+                            .style(style::input)
+                            .align_x(Alignment::End)
+                            .on_input(move |price| {
+                                Message::Update(
+                                    id,
+                                    Field::TierPrice(index, price),
+                                )
+                            })
+                            .width(70.0)
+                            .size(12)
+                            .padding(3),
+                        button(text("×").size(12).center())
+                            .on_press(Message::Update(
+                                id,
+                                Field::RemoveTier(index)
+                            ))
+                            .padding([3, 6])
+                            .style(button::danger),
+                    ]
+                    .spacing(3)
+                    .align_y(Alignment::Center),
+                )
+            },
+        )
+        .push(
+            button(text("+ Tier").size(12))
+                .on_press(Message::Update(id, Field::AddTier))
+                .style(button::secondary),
+        )
+        .spacing(10)
+        .padding([0, 20])
+        .align_y(Alignment::Center)
+        .wrap()
+        .into()
+}
+
+/// Prices for some hours of some weekdays, e.g. happy hour.
+fn promotions(product: &Product) -> Element<'_, Message> {
+    let id = product.id;
+    let slots = Slot::all();
+
+    product
+        .promotions
+        .iter()
+        .enumerate()
+        .fold(column![].spacing(5), |col, (index, promotion)| {
+            let days = promotion::WEEK.iter().fold(
+                row![].spacing(5),
+                |row, &(day, name)| {
+                    row.push(
+                        checkbox(name, promotion.days.contains(&day))
+                            .on_toggle(move |on| {
+                                Message::Update(
+                                    id,
+                                    Field::PromotionDay(index, day, on),
+                                )
+                            })
+                            .text_size(12)
+                            .size(14),
+                    )
+                },
+            );
+
+            col.push(
+                row![
+                    text_input("Happy hour", &promotion.name)
+                        .style(style::input)
+                        .on_input(move |name| {
+                            Message::Update(
+                                id,
+                                Field::PromotionName(index, name),
+                            )
+                        })
+                        .width(120.0)
+                        .size(12)
+                        .padding(3),
+                    days,
+                    pick_list(
+                        slots.clone(),
+                        Some(Slot(promotion.from)),
+                        move |slot| {
+                            Message::Update(
+                                id,
+                                Field::PromotionFrom(index, slot),
+                            )
+                        }
+                    )
+                    .text_size(12)
+                    .padding([3, 6]),
+                    text("–").size(12),
+                    pick_list(
+                        slots.clone(),
+                        Some(Slot(promotion.until)),
+                        move |slot| {
+                            Message::Update(
+                                id,
+                                Field::PromotionUntil(index, slot),
+                            )
+                        }
+                    )
+                    .text_size(12)
+                    .padding([3, 6]),
+                    text("@").size(12),
+                    text_input("0.00", &i18n::number(promotion.price, 2))
+                        .style(style::input)
+                        .align_x(Alignment::End)
+                        .on_input(move |price| {
+                            Message::Update(
+                                id,
+                                Field::PromotionPrice(index, price),
+                            )
+                        })
+                        .width(70.0)
+                        .size(12)
+                        .padding(3),
+                    button(text("×").size(12).center())
+                        .on_press(Message::Update(
+                            id,
+                            Field::RemovePromotion(index)
+                        ))
+                        .padding([3, 6])
+                        .style(button::danger),
+                ]
+                .spacing(5)
+                .align_y(Alignment::Center)
+                .wrap(),
+            )
+        })
+        .push(
+            button(text("+ Promotion").size(12))
+                .on_press(Message::Update(id, Field::AddPromotion))
+                .style(button::secondary),
+        )
+        .padding([0, 20])
+        .into()
+}
+
+fn name_id(id: usize) -> text_input::Id {
+    text_input::Id::new(format!("product-{}", id))
+}
+
+pub fn handle_hotkey(hotkey: Hotkey) -> Action<Instruction, Message> {
+    match hotkey {
+        Hotkey::Escape => Action::navigate(Screen::List),
+        _ => Action::none(),
+    }
+}
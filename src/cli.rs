@@ -0,0 +1,258 @@
+//! Headless commands for exports and reports, e.g. nightly from cron
+//!
+//! When the app is started with a command it runs it against the same store,
+//! archive and config as the window would and exits, without opening a
+//! window. Exports go to standard output unless `--output` names a file.
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::archive;
+use crate::config::Config;
+use crate::data::{self, Export};
+use crate::i18n;
+use crate::query::{self, GroupBy, Range};
+use crate::sale::Sale;
+use crate::store;
+use crate::tag;
+use crate::tax;
+
+pub const USAGE: &str = "\
+Usage: receipts [COMMAND]
+
+Without a command, opens the app.
+
+Commands:
+  export  Write the sales as CSV or JSON
+            --format csv|json   (default: csv)
+            --since YYYY-MM-DD  --until YYYY-MM-DD
+            --output PATH       (default: standard output)
+  report  Print the totals of the sales, grouped
+            --group-by day|month|order-type|tax-group|shift  (default: day)
+            --format text|csv   (default: text)
+            --since YYYY-MM-DD  --until YYYY-MM-DD
+  help    Show this message";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Export {
+        format: Format,
+        range: Range,
+        output: Option<PathBuf>,
+    },
+    Report {
+        group_by: GroupBy,
+        format: Format,
+        range: Range,
+    },
+    Help,
+}
+
+/// The command in `args`, without the program name, or `None` to open the
+/// app.
+pub fn parse(
+    args: impl IntoIterator<Item = String>,
+) -> Result<Option<Command>, String> {
+    let mut args = args.into_iter();
+    let Some(command) = args.next() else {
+        return Ok(None);
+    };
+
+    let mut format = None;
+    let mut range = Range::default();
+    let mut output = None;
+    let mut group_by = GroupBy::Day;
+
+    let is_export = match command.as_str() {
+        "export" => true,
+        "report" => false,
+        "help" | "--help" | "-h" => return Ok(Some(Command::Help)),
+        other => return Err(format!("Unknown command: {other}")),
+    };
+
+    while let Some(option) = args.next() {
+        let mut value =
+            || args.next().ok_or_else(|| format!("{option} needs a value"));
+        match option.as_str() {
+            "--format" => {
+                format = Some(match (value()?.as_str(), is_export) {
+                    ("csv", _) => Format::Csv,
+                    ("json", true) => Format::Json,
+                    ("text", false) => Format::Text,
+                    (other, _) => {
+                        return Err(format!("Unknown format: {other}"))
+                    }
+                })
+            }
+            "--since" => range.from = Some(date(&value()?)?),
+            "--until" => range.to = Some(date(&value()?)?),
+            "--output" if is_export => output = Some(PathBuf::from(value()?)),
+            "--group-by" if !is_export => {
+                group_by = match value()?.as_str() {
+                    "day" => GroupBy::Day,
+                    "month" => GroupBy::Month,
+                    "order-type" => GroupBy::OrderType,
+                    "tax-group" => GroupBy::TaxGroup,
+                    "shift" => GroupBy::Shift,
+                    other => return Err(format!("Unknown grouping: {other}")),
+                }
+            }
+            other => {
+                return Err(format!("Unknown option for {command}: {other}"))
+            }
+        }
+    }
+
+    Ok(Some(if is_export {
+        Command::Export {
+            format: format.unwrap_or(Format::Csv),
+            range,
+            output,
+        }
+    } else {
+        Command::Report {
+            group_by,
+            format: format.unwrap_or(Format::Text),
+            range,
+        }
+    }))
+}
+
+fn date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Not a date (YYYY-MM-DD): {value}"))
+}
+
+/// Run `command`, returning the process exit code.
+pub fn run(command: Command) -> i32 {
+    let config = Config::load();
+    i18n::set(config.locale);
+    tax::set(config.tax_rounding);
+
+    let result = match command {
+        Command::Help => write(None, &format!("{USAGE}\n")),
+        Command::Export {
+            format,
+            range,
+            output,
+        } => export(&config, format, range, output),
+        Command::Report {
+            group_by,
+            format,
+            range,
+        } => report(&config, group_by, format, range),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+fn export(
+    config: &Config,
+    format: Format,
+    range: Range,
+    output: Option<PathBuf>,
+) -> Result<(), data::Error> {
+    let mut sales: BTreeMap<usize, Sale> = BTreeMap::new();
+    let mut add = |id, sale| {
+        sales.insert(id, sale);
+    };
+    query::scan(store::on_disk(config.store_format), range, &mut add)?;
+    archive::scan(range, &mut add)?;
+
+    let contents = match format {
+        Format::Json => {
+            data::to_json(&Export {
+                version: data::VERSION,
+                modified_at: None,
+                sales,
+            })? + "\n"
+        }
+        Format::Csv | Format::Text => {
+            data::to_csv(&sales, &config.custom_fields)
+        }
+    };
+    write(output, &contents)
+}
+
+fn report(
+    config: &Config,
+    group_by: GroupBy,
+    format: Format,
+    range: Range,
+) -> Result<(), data::Error> {
+    let rows = query::aggregate(
+        store::on_disk(config.store_format),
+        range,
+        group_by,
+        tag::Filter::default(),
+    )?;
+
+    let mut table = String::new();
+    match format {
+        Format::Csv | Format::Json => {
+            table.push_str("group,count,subtotal,tax,total,tips\n");
+            for row in &rows {
+                table.push_str(&format!(
+                    "{},{},{:.2},{:.2},{:.2},{:.2}\n",
+                    data::csv_escape(&row.key),
+                    row.count,
+                    row.subtotal,
+                    row.tax,
+                    row.total,
+                    row.tips
+                ));
+            }
+        }
+        Format::Text => {
+            let width = rows
+                .iter()
+                .map(|row| row.key.chars().count())
+                .chain([group_by.to_string().len()])
+                .max()
+                .unwrap_or_default();
+            table.push_str(&format!(
+                "{:<width$}  {:>6}  {:>10}  {:>10}  {:>10}  {:>10}\n",
+                group_by.to_string(),
+                "Count",
+                "Subtotal",
+                "Tax",
+                "Total",
+                "Tips"
+            ));
+            for row in &rows {
+                table.push_str(&format!(
+                    "{:<width$}  {:>6}  {:>10.2}  {:>10.2}  {:>10.2}  {:>10.2}\n",
+                    row.key,
+                    row.count,
+                    row.subtotal,
+                    row.tax,
+                    row.total,
+                    row.tips
+                ));
+            }
+        }
+    }
+    write(None, &table)
+}
+
+fn write(output: Option<PathBuf>, contents: &str) -> Result<(), data::Error> {
+    match output {
+        Some(path) => std::fs::write(path, contents)?,
+        None => std::io::stdout().lock().write_all(contents.as_bytes())?,
+    }
+    Ok(())
+}
@@ -0,0 +1,207 @@
+//! Persistent app configuration
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::PathBuf;
+
+use crate::accounting::Accounts;
+use crate::catalog::ZeroStock;
+use crate::currency;
+use crate::custom;
+use crate::data::{self, Error};
+use crate::delivery;
+use crate::denomination;
+use crate::density::Density;
+use crate::drawer;
+use crate::fiscal;
+use crate::hook;
+use crate::i18n::Locale;
+use crate::live;
+use crate::logo::Logo;
+use crate::mail;
+use crate::pin::ManagerPin;
+use crate::qr;
+use crate::quick_buttons;
+use crate::sale::OrderType;
+use crate::scale;
+use crate::schedule;
+use crate::service;
+use crate::shortcut::Keymap;
+use crate::store;
+use crate::summary;
+use crate::sync;
+use crate::tax;
+use crate::terminal;
+use crate::tips;
+use crate::vat;
+
+pub use receipts_core::data_dir;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Write a backup snapshot after this many saves.
+    pub backup_every: u32,
+    /// How many backup snapshots to keep around.
+    pub backups_kept: usize,
+    /// Move sales older than this many days into the archive, if set.
+    pub archive_after_days: Option<u32>,
+    /// Purge deleted sales from the trash after this many days, if set.
+    pub trash_days: Option<u32>,
+    pub custom_fields: Vec<custom::Field>,
+    pub sync: sync::Remote,
+    pub live_feed: live::Feed,
+    pub store_format: store::Format,
+    pub receipt_qr: qr::Config,
+    /// Fine print at the bottom of receipts, e.g. a return policy, by the
+    /// order type of the sale.
+    pub fine_print: BTreeMap<OrderType, String>,
+    /// Text printed at the top of receipts, e.g. the address and VAT number,
+    /// with placeholders as in [`layout`].
+    ///
+    /// [`layout`]: crate::layout
+    pub receipt_header: String,
+    /// Text printed at the bottom of receipts, e.g. a thank-you message.
+    pub receipt_footer: String,
+    /// The tax ID of the business, printed on receipts, and the country
+    /// customers' tax IDs are checked for unless they name theirs.
+    pub tax_registration: vat::Registration,
+    /// The layout of receipts in the template language of [`layout`], empty
+    /// for the built-in one.
+    ///
+    /// [`layout`]: crate::layout
+    pub receipt_layout: String,
+    /// The logo image, which is kept in a file of its own.
+    #[serde(skip)]
+    pub logo: Option<Logo>,
+    /// Show how the receipt will print beside the edit form.
+    pub receipt_preview: bool,
+    /// Service charge rules, the first matching a sale applying.
+    pub service_rules: Vec<service::Rule>,
+    /// The fee charged on delivery orders.
+    pub delivery: delivery::Config,
+    /// How tax and other amounts are rounded to the cent.
+    pub tax_rounding: tax::Rounding,
+    /// What saving a sale with more of a product than in stock does.
+    pub zero_stock: ZeroStock,
+    /// The scale items sold by weight are weighed on.
+    pub scale: scale::Config,
+    pub drawer: drawer::Config,
+    /// The notes and coins the drawer is counted by and change given in.
+    pub denominations: denomination::Config,
+    pub terminal: terminal::Config,
+    /// The currencies payments are taken in besides the base one.
+    pub currency: currency::Config,
+    /// Integrations run on sale events, see [`hook`].
+    pub hooks: hook::Config,
+    /// The module paid receipts are signed by, see [`fiscal`].
+    pub fiscal: fiscal::Config,
+    /// The accounts journals are exported with.
+    pub accounts: Accounts,
+    /// The mail server email is sent through.
+    pub smtp: mail::Smtp,
+    /// Emailing the summary of the day at close of business.
+    pub summary: summary::Config,
+    /// How the tip pool is shared out among the staff.
+    pub tip_pool: tips::Config,
+    /// The tips offered when taking a gratuity.
+    pub tip_suggestions: tips::Suggestions,
+    /// Reminders of scheduled orders.
+    pub schedule: schedule::Config,
+    /// Row density of the sales list and item tables.
+    pub density: Density,
+    /// The language and number and date formats of the UI.
+    pub locale: Locale,
+    /// Scale of the whole UI in percent, from 100 to 200.
+    pub ui_scale: u32,
+    /// Offer on-screen keys for number and code inputs, for touch screens.
+    pub keypad: bool,
+    /// The PIN asked for to void items of saved sales, if set.
+    pub manager_pin: ManagerPin,
+    /// Prices typed over the catalog price by more than this percentage
+    /// need the manager PIN, if set.
+    pub override_approval: Option<u32>,
+    /// The key chords of hotkeys changed from the default ones.
+    pub shortcuts: Keymap,
+    /// Read item names typed as [`shorthand`], e.g. `3 burger @ 9.99 food`.
+    ///
+    /// [`shorthand`]: crate::sale::shorthand
+    pub shorthand: bool,
+    /// The grid of products added with one tap on the edit screen.
+    pub quick_buttons: quick_buttons::Config,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            backup_every: 5,
+            backups_kept: 10,
+            archive_after_days: None,
+            trash_days: Some(30),
+            custom_fields: Vec::new(),
+            sync: sync::Remote::default(),
+            live_feed: live::Feed::default(),
+            store_format: store::Format::default(),
+            receipt_qr: qr::Config::default(),
+            fine_print: BTreeMap::new(),
+            receipt_header: String::new(),
+            receipt_footer: String::new(),
+            tax_registration: vat::Registration::default(),
+            receipt_layout: String::new(),
+            logo: None,
+            receipt_preview: true,
+            service_rules: Vec::new(),
+            delivery: delivery::Config::default(),
+            tax_rounding: tax::Rounding::default(),
+            zero_stock: ZeroStock::default(),
+            scale: scale::Config::default(),
+            drawer: drawer::Config::default(),
+            denominations: denomination::Config::default(),
+            terminal: terminal::Config::default(),
+            currency: currency::Config::default(),
+            hooks: hook::Config::default(),
+            fiscal: fiscal::Config::default(),
+            accounts: Accounts::default(),
+            smtp: mail::Smtp::default(),
+            summary: summary::Config::default(),
+            tip_pool: tips::Config::default(),
+            tip_suggestions: tips::Suggestions::default(),
+            schedule: schedule::Config::default(),
+            density: Density::default(),
+            locale: Locale::default(),
+            ui_scale: 100,
+            keypad: false,
+            manager_pin: ManagerPin::default(),
+            override_approval: None,
+            shortcuts: Keymap::default(),
+            shorthand: false,
+            quick_buttons: quick_buttons::Config::default(),
+        }
+    }
+}
+
+impl Config {
+    fn path() -> PathBuf {
+        data_dir().join("config.json")
+    }
+
+    /// Load the configuration, falling back to the defaults if there is none
+    /// or it can't be read.
+    pub fn load() -> Self {
+        let config: Self = std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self {
+            logo: Logo::load(),
+            ..config
+        }
+    }
+
+    /// Save in the background, see [`data::save_json`].
+    pub fn save(
+        &self,
+    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        data::save_json(Self::path(), self)
+    }
+}
@@ -0,0 +1,55 @@
+//! Fetch the exchange rates of the currencies payments are taken in
+//!
+//! Rates can also be typed in by hand in the settings. Fetching asks an
+//! exchange rate API for the rates of the base currency, by default a free
+//! one without an API key, and can be repeated every few hours. What was
+//! fetched is cached on disk, and used when the API can't be reached.
+use chrono::Utc;
+use iced::futures::channel::oneshot;
+use iced::futures::SinkExt;
+use iced::Subscription;
+
+pub use receipts_core::currency::*;
+
+/// How often the clock checks whether the rates are due again.
+const TICK: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// Fetch the rates of `base` from `url`, and cache them.
+pub async fn fetch(url: String, base: String) -> Result<Cache, String> {
+    let base = base.trim().to_uppercase();
+    tracing::info!(base, url, "fetching exchange rates");
+    let body = ureq::get(&url)
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    let cache = Cache {
+        base,
+        fetched_at: Utc::now(),
+        quotes: parse_rates(&body)?,
+    };
+    if let Err(e) = cache.save().await {
+        tracing::warn!("could not cache exchange rates: {e}");
+    }
+    Ok(cache)
+}
+
+/// Ticks every few minutes while the rates are refreshed on their own.
+pub fn clock() -> Subscription<()> {
+    Subscription::run_with_id(
+        "exchange rates",
+        iced::stream::channel(1, |mut output| async move {
+            loop {
+                let (tick, ticked) = oneshot::channel();
+                std::thread::spawn(move || {
+                    std::thread::sleep(TICK);
+                    let _ = tick.send(());
+                });
+                let _ = ticked.await;
+                if output.send(()).await.is_err() {
+                    return;
+                }
+            }
+        }),
+    )
+}
@@ -0,0 +1,398 @@
+//! Keep customers, and see what each of them has bought
+pub use receipts_core::customer::*;
+
+use chrono::Local;
+use iced::widget::{
+    button, column, container, horizontal_space, pick_list, row, scrollable,
+    text, text_input,
+};
+use iced::{Alignment, Element, Fill};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::i18n::{money, Locale};
+use crate::store::Header;
+use crate::style;
+use crate::toast;
+use crate::vat;
+use crate::{Action, Hotkey, Screen};
+
+/// What is being typed on the customers screen.
+#[derive(Debug, Default)]
+pub struct Form {
+    pub query: String,
+    pub name: String,
+    pub phone: String,
+    pub email: String,
+}
+
+/// The language receipts are written in for a customer, to pick from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Language(pub Option<Locale>);
+
+impl Language {
+    fn all() -> Vec<Language> {
+        std::iter::once(Language(None))
+            .chain(Locale::ALL.map(|locale| Language(Some(locale))))
+            .collect()
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(locale) => write!(f, "{locale}"),
+            None => write!(f, "Store language"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Back,
+    /// Go back to every customer.
+    Directory,
+    QueryInput(String),
+    NameInput(String),
+    PhoneInput(String),
+    EmailInput(String),
+    Add,
+    Remove(usize),
+    /// Show the purchase history of a customer.
+    Open(usize),
+    OpenSale(usize),
+    Export(usize),
+    /// Write the receipts of a customer in another language.
+    SelectLanguage(usize, Language),
+    TaxIdInput(usize, String),
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Navigate(Screen),
+    Save,
+    OpenSale(usize),
+    /// Export the purchase history of a customer.
+    Export(usize),
+}
+
+impl From<Screen> for Instruction {
+    fn from(screen: Screen) -> Self {
+        Instruction::Navigate(screen)
+    }
+}
+
+pub fn update(
+    customers: &mut Customers,
+    form: &mut Form,
+    message: Message,
+) -> Action<Instruction, Message> {
+    match message {
+        Message::Back => Action::navigate(Screen::List),
+        Message::Directory => Action::navigate(Screen::Customers),
+        Message::QueryInput(query) => {
+            form.query = query;
+            Action::none()
+        }
+        Message::NameInput(name) => {
+            form.name = name;
+            Action::none()
+        }
+        Message::PhoneInput(phone) => {
+            form.phone = phone;
+            Action::none()
+        }
+        Message::EmailInput(email) => {
+            form.email = email;
+            Action::none()
+        }
+        Message::Add => {
+            if form.name.trim().is_empty() {
+                return Action::toast(
+                    toast::Kind::Error,
+                    "Enter the customer's name",
+                );
+            }
+            customers.add(&form.name, &form.phone, &form.email);
+            *form = Form::default();
+            Action::instruction(Instruction::Save)
+        }
+        Message::Remove(id) => {
+            customers.remove(id);
+            Action::instruction(Instruction::Save)
+        }
+        Message::Open(id) => Action::navigate(Screen::Customer(id)),
+        Message::OpenSale(id) => Action::instruction(Instruction::OpenSale(id)),
+        Message::Export(id) => Action::instruction(Instruction::Export(id)),
+        Message::TaxIdInput(id, tax_id) => {
+            customers.set_tax_id(id, &tax_id);
+            Action::instruction(Instruction::Save)
+        }
+        Message::SelectLanguage(id, language) => {
+            customers.set_locale(id, language.0);
+            Action::instruction(Instruction::Save)
+        }
+    }
+}
+
+/// Every customer matching the search, with what they have spent.
+pub fn view<'a>(
+    customers: &'a Customers,
+    index: &Index,
+    headers: &BTreeMap<usize, Header>,
+    form: &'a Form,
+) -> Element<'a, Message> {
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text("Customers").size(16),
+        horizontal_space(),
+        text_input("Search", &form.query)
+            .style(style::input)
+            .on_input(Message::QueryInput)
+            .width(200.0)
+            .padding(5),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let add = row![
+        text_input("Name", &form.name)
+            .style(style::input)
+            .on_input(Message::NameInput)
+            .on_submit(Message::Add)
+            .padding(5),
+        text_input("Phone", &form.phone)
+            .style(style::input)
+            .on_input(Message::PhoneInput)
+            .on_submit(Message::Add)
+            .width(150.0)
+            .padding(5),
+        text_input("Email", &form.email)
+            .style(style::input)
+            .on_input(Message::EmailInput)
+            .on_submit(Message::Add)
+            .width(200.0)
+            .padding(5),
+        button("+ Add")
+            .on_press(Message::Add)
+            .style(button::primary),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let found = customers.search(&form.query);
+    let list: Element<_> = if found.is_empty() {
+        text(if form.query.is_empty() {
+            "No customers yet"
+        } else {
+            "No customer matches the search"
+        })
+        .size(12)
+        .into()
+    } else {
+        found
+            .into_iter()
+            .fold(
+                column![row![
+                    text("Name").width(Fill),
+                    text("Sales").align_x(Alignment::End).width(60.0),
+                    text("Lifetime").align_x(Alignment::End).width(100.0),
+                    horizontal_space().width(25.0),
+                ]
+                .spacing(10)]
+                .spacing(5),
+                |col, customer| {
+                    let history = History::new(
+                        index
+                            .sales(customer.id)
+                            .filter_map(|id| headers.get(&id)),
+                    );
+                    col.push(
+                        row![
+                            button(
+                                column![
+                                    text(customer.display_name()),
+                                    text(customer.contact()).size(12),
+                                ]
+                                .width(Fill)
+                            )
+                            .on_press(Message::Open(customer.id))
+                            .style(button::text)
+                            .padding(0),
+                            text(history.count)
+                                .align_x(Alignment::End)
+                                .width(60.0),
+                            text(money(history.lifetime))
+                                .align_x(Alignment::End)
+                                .width(100.0),
+                            button(text("×").center())
+                                .width(25.0)
+                                .on_press(Message::Remove(customer.id))
+                                .style(button::danger),
+                        ]
+                        .spacing(10)
+                        .align_y(Alignment::Center),
+                    )
+                },
+            )
+            .into()
+    };
+
+    container(scrollable(
+        column![
+            header,
+            container(add)
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(list)
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+        ]
+        .spacing(20),
+    ))
+    .padding(20)
+    .into()
+}
+
+/// The sales of a customer, the latest first, with their lifetime spend and
+/// average ticket. Their tax ID is checked for `country` unless it names
+/// another.
+pub fn history<'a>(
+    customer: &'a Customer,
+    sales: Vec<(usize, Header)>,
+    country: vat::Country,
+) -> Element<'a, Message> {
+    let header = row![
+        button(text("←").center())
+            .width(40)
+            .on_press(Message::Directory),
+        column![
+            text(customer.display_name()).size(16),
+            text(customer.contact()).size(12),
+        ],
+        horizontal_space(),
+        text("Receipts in").size(12),
+        pick_list(
+            Language::all(),
+            Some(Language(customer.locale)),
+            |language| Message::SelectLanguage(customer.id, language),
+        ),
+        button("Export")
+            .on_press(Message::Export(customer.id))
+            .style(button::secondary),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let tax_id = customer.tax_id.trim();
+    let status = match vat::check(country, tax_id) {
+        _ if tax_id.is_empty() => text(""),
+        Ok(_) => text("Printed on their receipts").style(text::success),
+        Err(error) => text(error).style(text::danger),
+    };
+    let id = customer.id;
+    let tax_id = row![
+        text("Tax ID"),
+        text_input(country.example(), &customer.tax_id)
+            .style(style::input)
+            .on_input(move |tax_id| Message::TaxIdInput(id, tax_id))
+            .width(200.0)
+            .padding(5),
+        status.size(12),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let history = History::new(sales.iter().map(|(_, header)| header));
+    let figure = |label, value: String| {
+        container(
+            column![text(label).size(12), text(value).size(20)]
+                .spacing(5)
+                .align_x(Alignment::Center),
+        )
+        .padding(15)
+        .width(Fill)
+        .style(container::rounded_box)
+    };
+    let figures = row![
+        figure("Sales", history.count.to_string()),
+        figure("Lifetime spend", money(history.lifetime)),
+        figure("Average ticket", money(history.average)),
+    ]
+    .spacing(20);
+
+    let list: Element<_> = if sales.is_empty() {
+        text("No sales for this customer yet").size(12).into()
+    } else {
+        sales
+            .into_iter()
+            .rev()
+            .fold(
+                column![row![
+                    text("Date").width(150.0),
+                    text("Sale").width(Fill),
+                    text("Total").align_x(Alignment::End).width(100.0),
+                ]
+                .spacing(10)]
+                .spacing(5),
+                |col, (id, sale)| {
+                    col.push(
+                        button(
+                            row![
+                                text(
+                                    sale.created_at
+                                        .with_timezone(&Local)
+                                        .format("%Y-%m-%d %H:%M")
+                                        .to_string()
+                                )
+                                .width(150.0),
+                                text(format!(
+                                    "{} • {}",
+                                    sale.name, sale.order_type
+                                ))
+                                .width(Fill),
+                                text(money(sale.total))
+                                    .align_x(Alignment::End)
+                                    .width(100.0),
+                            ]
+                            .spacing(10),
+                        )
+                        .on_press(Message::OpenSale(id))
+                        .style(button::text)
+                        .padding(0),
+                    )
+                },
+            )
+            .into()
+    };
+
+    container(scrollable(
+        column![
+            header,
+            tax_id,
+            figures,
+            container(list)
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+        ]
+        .spacing(20),
+    ))
+    .padding(20)
+    .into()
+}
+
+pub fn handle_hotkey(
+    hotkey: Hotkey,
+    screen: &Screen,
+) -> Action<Instruction, Message> {
+    match (hotkey, screen) {
+        (Hotkey::Escape, Screen::Customer(_)) => {
+            Action::navigate(Screen::Customers)
+        }
+        (Hotkey::Escape, _) => Action::navigate(Screen::List),
+        _ => Action::none(),
+    }
+}
@@ -0,0 +1,220 @@
+//! A hidden diagnostics screen for chasing performance issues
+//!
+//! Opened with Ctrl+Shift+D from the sales list. It shows how much the app
+//! holds in memory, how many messages it handles and how long the last
+//! update and view took, which is what usually matters on low-end POS
+//! hardware. For user reports it also shows where the data files are and
+//! the last lines logged.
+use iced::widget::{
+    button, column, container, horizontal_space, row, scrollable, text,
+};
+use iced::{Alignment, Element, Fill, Font};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::config::data_dir;
+use crate::logging;
+use crate::{Action, Hotkey, Screen};
+
+/// How many of the recent log lines are shown.
+const LOG_LINES: usize = 50;
+
+/// Throughput is averaged over this window.
+const WINDOW: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+pub struct Stats {
+    started: Instant,
+    messages: u64,
+    /// When the messages within the last [`WINDOW`] were handled.
+    recent: VecDeque<Instant>,
+    last_update: Duration,
+    slowest_update: Duration,
+    // Views only borrow the app, so their timings are recorded in cells
+    last_view: Cell<Duration>,
+    slowest_view: Cell<Duration>,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            messages: 0,
+            recent: VecDeque::new(),
+            last_update: Duration::ZERO,
+            slowest_update: Duration::ZERO,
+            last_view: Cell::new(Duration::ZERO),
+            slowest_view: Cell::new(Duration::ZERO),
+        }
+    }
+}
+
+impl Stats {
+    pub fn record_update(&mut self, took: Duration) {
+        let now = Instant::now();
+        self.messages += 1;
+        self.recent.push_back(now);
+        while self
+            .recent
+            .front()
+            .is_some_and(|handled| now - *handled > WINDOW)
+        {
+            self.recent.pop_front();
+        }
+        self.last_update = took;
+        self.slowest_update = self.slowest_update.max(took);
+    }
+
+    pub fn record_view(&self, took: Duration) {
+        self.last_view.set(took);
+        self.slowest_view.set(self.slowest_view.get().max(took));
+    }
+
+    /// Messages handled per second over the last [`WINDOW`].
+    fn throughput(&self) -> f32 {
+        let window = WINDOW.min(self.started.elapsed());
+        self.recent.len() as f32 / window.as_secs_f32().max(1.0)
+    }
+}
+
+/// What the app holds in memory, counted when the screen is drawn.
+#[derive(Debug, Default)]
+pub struct Counts {
+    pub sales_loaded: usize,
+    pub sales_unloaded: usize,
+    pub items: usize,
+    pub cached_images: usize,
+    pub pending_saves: usize,
+    pub toasts: usize,
+    pub sales_archived: usize,
+    pub outbox: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Back,
+    Reset,
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Navigate(Screen),
+}
+
+impl From<Screen> for Instruction {
+    fn from(screen: Screen) -> Self {
+        Instruction::Navigate(screen)
+    }
+}
+
+pub fn update(
+    stats: &mut Stats,
+    message: Message,
+) -> Action<Instruction, Message> {
+    match message {
+        Message::Back => Action::navigate(Screen::List),
+        Message::Reset => {
+            *stats = Stats::default();
+            Action::none()
+        }
+    }
+}
+
+pub fn view<'a>(
+    stats: &Stats,
+    counts: Counts,
+    store: &Path,
+) -> Element<'a, Message> {
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text("Diagnostics").size(16),
+        horizontal_space(),
+        button("Reset timings")
+            .on_press(Message::Reset)
+            .style(button::secondary),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let memory = column![
+        text("Memory").size(16),
+        stat("Sales loaded", counts.sales_loaded.to_string()),
+        stat("Sales not loaded", counts.sales_unloaded.to_string()),
+        stat("Sales archived", counts.sales_archived.to_string()),
+        stat("Items loaded", counts.items.to_string()),
+        stat("Cached images", counts.cached_images.to_string()),
+        stat("Pending saves", counts.pending_saves.to_string()),
+        stat("Toasts", counts.toasts.to_string()),
+        stat("Outbox entries", counts.outbox.to_string()),
+    ]
+    .spacing(5);
+
+    let timings = column![
+        text("Messages").size(16),
+        stat("Handled", stats.messages.to_string()),
+        stat("Per second", format!("{:.1}", stats.throughput())),
+        stat("Last update", duration(stats.last_update)),
+        stat("Slowest update", duration(stats.slowest_update)),
+        stat("Last view", duration(stats.last_view.get())),
+        stat("Slowest view", duration(stats.slowest_view.get())),
+        stat("Uptime", format!("{}s", stats.started.elapsed().as_secs())),
+    ]
+    .spacing(5);
+
+    let files = column![
+        text("Files").size(16),
+        stat("Data folder", data_dir().display().to_string()),
+        stat("Sales store", store.display().to_string()),
+        stat("Logs", logging::dir().display().to_string()),
+    ]
+    .spacing(5);
+
+    let recent = logging::recent();
+    let log = column![
+        text("Recent log").size(16),
+        if recent.is_empty() {
+            text("Nothing logged yet")
+        } else {
+            text(recent[recent.len().saturating_sub(LOG_LINES)..].join("\n"))
+                .font(Font::MONOSPACE)
+                .size(12)
+        },
+    ]
+    .spacing(5);
+
+    let boxed = |content| {
+        container(content)
+            .padding(20)
+            .width(Fill)
+            .style(container::rounded_box)
+    };
+
+    column![
+        header,
+        scrollable(
+            column![boxed(memory), boxed(timings), boxed(files), boxed(log)]
+                .spacing(20),
+        )
+        .height(Fill),
+    ]
+    .spacing(20)
+    .padding(20)
+    .into()
+}
+
+fn stat<'a>(label: &'a str, value: String) -> Element<'a, Message> {
+    row![text(label).width(150.0), text(value)].into()
+}
+
+fn duration(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
+
+pub fn handle_hotkey(hotkey: Hotkey) -> Action<Instruction, Message> {
+    match hotkey {
+        Hotkey::Escape => Action::navigate(Screen::List),
+        _ => Action::none(),
+    }
+}
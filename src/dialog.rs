@@ -0,0 +1,46 @@
+//! Dialogs shown over a screen, in its upper half
+//!
+//! On a tablet the on-screen keys take up the bottom of the screen, so a
+//! dialog sits near the top where its value stays in view.
+use iced::widget::{container, mouse_area, opaque, stack};
+use iced::{Background, Border, Color, Element, Fill, Theme};
+
+/// Show `dialog` over `content`, sending `on_dismiss` when the dimmed
+/// background is clicked.
+pub fn view<'a, Message: Clone + 'a>(
+    content: impl Into<Element<'a, Message>>,
+    dialog: impl Into<Element<'a, Message>>,
+    on_dismiss: Message,
+) -> Element<'a, Message> {
+    let dialog = container(opaque(container(dialog).padding(20).style(style)))
+        .align_top(Fill)
+        .padding(40);
+
+    stack![
+        content.into(),
+        opaque(
+            mouse_area(dialog.center_x(Fill).style(|_| container::Style {
+                background: Some(Background::Color(Color {
+                    a: 0.6,
+                    ..Color::BLACK
+                })),
+                ..container::Style::default()
+            }))
+            .on_press(on_dismiss)
+        ),
+    ]
+    .into()
+}
+
+fn style(theme: &Theme) -> container::Style {
+    let palette = theme.extended_palette();
+    container::Style {
+        background: Some(Background::Color(palette.background.base.color)),
+        border: Border {
+            width: 1.0,
+            radius: 8.0.into(),
+            color: palette.background.strong.color,
+        },
+        ..container::Style::default()
+    }
+}
@@ -0,0 +1,196 @@
+//! The cash drawer: the day's opening float, cash taken on sales, money paid
+//! in or out, and the count at the end of the day
+//!
+//! The drawer keeps its own record of every cash movement rather than adding
+//! up sales, so the cash expected in it is known without loading every sale.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::io::Write;
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config::data_dir;
+use crate::data::{self, Error};
+
+pub mod manage;
+
+/// The ESC/POS command pulsing the drawer kick-out of a receipt printer:
+/// pin 2, on for 50ms and off for 500ms.
+const PULSE: [u8; 5] = [0x1B, 0x70, 0x00, 0x19, 0xFA];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The receipt printer the drawer is plugged into, as `host:port` for a
+    /// network printer or a device path such as `/dev/usb/lp0`. Empty when
+    /// there is no drawer to open.
+    pub printer: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Kind {
+    /// Cash taken for sale `id`.
+    Sale(usize),
+    PaidIn,
+    PaidOut,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Movement {
+    pub at: DateTime<Utc>,
+    pub kind: Kind,
+    /// Always positive; the kind says which way the money went.
+    pub amount: f32,
+    /// Why money was paid in or out, e.g. "Milk from the corner shop".
+    pub note: String,
+}
+
+impl Movement {
+    /// The amount as it changes the cash in the drawer.
+    pub fn signed(&self) -> f32 {
+        match self.kind {
+            Kind::Sale(_) | Kind::PaidIn => self.amount,
+            Kind::PaidOut => -self.amount,
+        }
+    }
+}
+
+/// The cash counted at the end of a day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Count {
+    pub at: DateTime<Utc>,
+    pub counted: f32,
+}
+
+/// A day of trading, from the opening float to the count.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Day {
+    pub opened_at: DateTime<Utc>,
+    pub float: f32,
+    pub movements: Vec<Movement>,
+    pub count: Option<Count>,
+}
+
+impl Day {
+    /// The cash that should be in the drawer.
+    pub fn expected(&self) -> f32 {
+        self.float + self.movements.iter().map(Movement::signed).sum::<f32>()
+    }
+
+    /// The total of the movements `matching`.
+    pub fn total(&self, matching: impl Fn(Kind) -> bool) -> f32 {
+        self.movements
+            .iter()
+            .filter(|movement| matching(movement.kind))
+            .map(|movement| movement.amount)
+            .sum()
+    }
+
+    /// How much more cash was counted than expected, negative when short.
+    pub fn difference(&self) -> Option<f32> {
+        self.count
+            .as_ref()
+            .map(|count| count.counted - self.expected())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Drawer {
+    days: Vec<Day>,
+}
+
+impl Drawer {
+    fn path() -> PathBuf {
+        data_dir().join("drawer.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save in the background, see [`data::save_json`].
+    pub fn save(
+        &self,
+    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        data::save_json(Self::path(), self)
+    }
+
+    /// The day in progress, if the drawer has been opened and not counted.
+    pub fn today(&self) -> Option<&Day> {
+        self.days.last().filter(|day| day.count.is_none())
+    }
+
+    /// Start a day with `float` in the drawer.
+    pub fn open(&mut self, float: f32) {
+        if self.today().is_none() {
+            self.days.push(Day {
+                opened_at: Utc::now(),
+                float,
+                movements: Vec::new(),
+                count: None,
+            });
+        }
+    }
+
+    /// Record cash going in or out of the drawer, returning whether there
+    /// was a day in progress to record it on.
+    pub fn record(
+        &mut self,
+        kind: Kind,
+        amount: f32,
+        note: impl Into<String>,
+    ) -> bool {
+        let Some(day) = self.days.last_mut().filter(|d| d.count.is_none())
+        else {
+            return false;
+        };
+        day.movements.push(Movement {
+            at: Utc::now(),
+            kind,
+            amount,
+            note: note.into(),
+        });
+        true
+    }
+
+    /// End the day in progress with `counted` cash in the drawer.
+    pub fn close(&mut self, counted: f32) {
+        if let Some(day) = self.days.last_mut().filter(|d| d.count.is_none()) {
+            day.count = Some(Count {
+                at: Utc::now(),
+                counted,
+            });
+        }
+    }
+
+    /// Counted days, the latest first.
+    pub fn history(&self) -> impl Iterator<Item = &Day> {
+        self.days.iter().rev().filter(|day| day.count.is_some())
+    }
+}
+
+/// Open the drawer by sending the kick-out pulse to `printer`.
+pub fn kick(printer: &str) -> Result<(), Error> {
+    let printer = printer.trim();
+    if printer.is_empty() {
+        return Ok(());
+    }
+
+    // A device path has no port, while a network printer always has one
+    if printer.starts_with('/') || !printer.contains(':') {
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(printer)?
+            .write_all(&PULSE)?;
+    } else {
+        let mut stream = TcpStream::connect(printer)?;
+        stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+        stream.write_all(&PULSE)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,423 @@
+//! Open the day, pay cash in or out, and count the drawer at closing
+use iced::widget::{
+    button, column, container, horizontal_space, row, scrollable, text,
+    text_input,
+};
+use iced::{Alignment, Element, Fill};
+use std::collections::BTreeMap;
+
+use super::{Day, Drawer, Kind, Movement};
+use crate::denomination;
+use crate::i18n::{self, money};
+use crate::style;
+use crate::toast;
+use crate::{Action, Hotkey, Screen};
+
+/// What is being typed on the drawer screen.
+#[derive(Debug, Default)]
+pub struct Form {
+    pub float: String,
+    pub amount: String,
+    pub note: String,
+    pub counted: String,
+    /// How many of each denomination were counted, by its value in cents.
+    pub tally: BTreeMap<u32, String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Back,
+    FloatInput(String),
+    OpenDay,
+    AmountInput(String),
+    NoteInput(String),
+    PaidIn,
+    PaidOut,
+    CountedInput(String),
+    /// How many of a denomination, in cents, were counted.
+    TallyInput(u32, String),
+    CloseDay,
+    Kick,
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Navigate(Screen),
+    Save,
+    /// Cash was paid in or out: save and open the drawer to move it.
+    CashMoved,
+    /// Open the drawer without taking any cash.
+    Kick,
+    /// The day was counted: save it.
+    DayClosed,
+}
+
+impl From<Screen> for Instruction {
+    fn from(screen: Screen) -> Self {
+        Instruction::Navigate(screen)
+    }
+}
+
+pub fn update(
+    drawer: &mut Drawer,
+    form: &mut Form,
+    message: Message,
+) -> Action<Instruction, Message> {
+    match message {
+        Message::Back => Action::navigate(Screen::List),
+        Message::FloatInput(float) => {
+            form.float = float;
+            Action::none()
+        }
+        Message::OpenDay => {
+            let Some(float) = amount(&form.float) else {
+                return not_an_amount(&form.float);
+            };
+            drawer.open(float);
+            form.float.clear();
+            Action::instruction(Instruction::Save)
+        }
+        Message::AmountInput(amount) => {
+            form.amount = amount;
+            Action::none()
+        }
+        Message::NoteInput(note) => {
+            form.note = note;
+            Action::none()
+        }
+        Message::PaidIn | Message::PaidOut => {
+            let Some(value) = amount(&form.amount) else {
+                return not_an_amount(&form.amount);
+            };
+            let kind = match message {
+                Message::PaidIn => Kind::PaidIn,
+                _ => Kind::PaidOut,
+            };
+            drawer.record(kind, value, form.note.trim());
+            form.amount.clear();
+            form.note.clear();
+            Action::instruction(Instruction::CashMoved)
+        }
+        Message::CountedInput(counted) => {
+            form.counted = counted;
+            Action::none()
+        }
+        Message::TallyInput(cents, count) => {
+            form.tally.insert(cents, count);
+            let counted = form
+                .tally
+                .iter()
+                .filter_map(|(cents, count)| {
+                    Some((*cents, count.trim().parse().ok()?))
+                })
+                .collect::<Vec<_>>();
+            form.counted = if counted.is_empty() {
+                String::new()
+            } else {
+                i18n::number(denomination::total(counted), 2)
+            };
+            Action::none()
+        }
+        Message::CloseDay => {
+            let Some(counted) = amount(&form.counted) else {
+                return not_an_amount(&form.counted);
+            };
+            drawer.close(counted);
+            form.counted.clear();
+            form.tally.clear();
+            Action::instruction(Instruction::DayClosed)
+        }
+        Message::Kick => Action::instruction(Instruction::Kick),
+    }
+}
+
+fn amount(input: &str) -> Option<f32> {
+    i18n::parse_number(input).filter(|amount| *amount >= 0.0)
+}
+
+fn not_an_amount(input: &str) -> Action<Instruction, Message> {
+    Action::toast(
+        toast::Kind::Error,
+        format!("\"{}\" is not an amount", input.trim()),
+    )
+}
+
+/// The drawer screen, with the cash counted by `denominations` at closing.
+pub fn view<'a>(
+    drawer: &'a Drawer,
+    form: &'a Form,
+    denominations: Vec<u32>,
+) -> Element<'a, Message> {
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text("Cash drawer").size(16),
+        horizontal_space(),
+        button("Open drawer")
+            .on_press(Message::Kick)
+            .style(button::secondary),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let today: Element<_> = match drawer.today() {
+        None => column![
+            text("Start of day").size(16),
+            row![
+                text("Opening float").width(150.0),
+                text_input("0.00", &form.float)
+                    .style(style::input)
+                    .align_x(Alignment::End)
+                    .on_input(Message::FloatInput)
+                    .on_submit(Message::OpenDay)
+                    .width(100.0)
+                    .padding(5),
+                button("Start day")
+                    .on_press(Message::OpenDay)
+                    .style(button::success),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(10)
+        .into(),
+        Some(day) => column![
+            summary(day),
+            pay_in_out(form),
+            movements(day),
+            tally(form, denominations),
+            count(day, form),
+        ]
+        .spacing(20)
+        .into(),
+    };
+
+    container(scrollable(
+        column![
+            header,
+            container(today)
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(history(drawer))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+        ]
+        .spacing(20),
+    ))
+    .padding(20)
+    .into()
+}
+
+fn summary(day: &Day) -> Element<'_, Message> {
+    let line = |label, amount: f32| {
+        row![
+            text(label).width(150.0),
+            text(money(amount)).align_x(Alignment::End).width(100.0),
+        ]
+    };
+
+    column![
+        text(format!(
+            "Day started {}",
+            day.opened_at.format("%Y-%m-%d %H:%M")
+        ))
+        .size(16),
+        line("Opening float", day.float),
+        line(
+            "Cash sales",
+            day.total(|kind| matches!(kind, Kind::Sale(_)))
+        ),
+        line("Paid in", day.total(|kind| kind == Kind::PaidIn)),
+        line("Paid out", -day.total(|kind| kind == Kind::PaidOut)),
+        line("Expected in drawer", day.expected()),
+    ]
+    .spacing(5)
+    .into()
+}
+
+fn pay_in_out(form: &Form) -> Element<'_, Message> {
+    row![
+        text("Pay in or out").width(150.0),
+        text_input("0.00", &form.amount)
+            .style(style::input)
+            .align_x(Alignment::End)
+            .on_input(Message::AmountInput)
+            .width(100.0)
+            .padding(5),
+        text_input("Reason", &form.note)
+            .style(style::input)
+            .on_input(Message::NoteInput)
+            .padding(5),
+        button("Paid in")
+            .on_press(Message::PaidIn)
+            .style(button::secondary),
+        button("Paid out")
+            .on_press(Message::PaidOut)
+            .style(button::secondary),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+fn movements(day: &Day) -> Element<'_, Message> {
+    if day.movements.is_empty() {
+        return text("No cash in or out yet").size(12).into();
+    }
+
+    day.movements
+        .iter()
+        .rev()
+        .fold(column![].spacing(2), |col, movement| {
+            col.push(movement_row(movement))
+        })
+        .into()
+}
+
+fn movement_row(movement: &Movement) -> Element<'_, Message> {
+    let what = match movement.kind {
+        Kind::Sale(id) => format!("Sale #{id}"),
+        Kind::PaidIn => "Paid in".to_string(),
+        Kind::PaidOut => "Paid out".to_string(),
+    };
+
+    row![
+        text(movement.at.format("%H:%M").to_string()).width(60.0),
+        text(what).width(100.0),
+        text(&movement.note).width(Fill),
+        text(money(movement.signed()))
+            .align_x(Alignment::End)
+            .width(100.0),
+    ]
+    .spacing(10)
+    .into()
+}
+
+/// A count of each note and coin in the drawer, adding up to the counted
+/// cash.
+fn tally(form: &Form, denominations: Vec<u32>) -> Element<'_, Message> {
+    let cell = |cents: u32| {
+        let count = form.tally.get(&cents).map_or("", String::as_str);
+        let subtotal = count
+            .trim()
+            .parse()
+            .map_or(0.0, |count| denomination::total([(cents, count)]));
+        row![
+            text(denomination::label(cents))
+                .align_x(Alignment::End)
+                .width(60.0),
+            text("×"),
+            text_input("0", count)
+                .style(style::input)
+                .align_x(Alignment::End)
+                .on_input(move |count| Message::TallyInput(cents, count))
+                .width(60.0)
+                .padding(5),
+            text(money(subtotal)).align_x(Alignment::End).width(100.0),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+    };
+
+    // Notes on the left and coins on the right, or near enough
+    let half = denominations.len().div_ceil(2);
+    let column_of = |values: &[u32]| {
+        values
+            .iter()
+            .fold(column![].spacing(5), |col, cents| col.push(cell(*cents)))
+    };
+
+    column![
+        text("Count by denomination").size(16),
+        row![
+            column_of(&denominations[..half]),
+            column_of(&denominations[half..]),
+        ]
+        .spacing(40),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn count<'a>(day: &Day, form: &'a Form) -> Element<'a, Message> {
+    let counted = amount(&form.counted);
+    let difference: Element<_> = match counted {
+        Some(counted) => over_short(counted - day.expected()),
+        None => horizontal_space().width(0).into(),
+    };
+
+    row![
+        text("Counted cash").width(150.0),
+        text_input("0.00", &form.counted)
+            .style(style::input)
+            .align_x(Alignment::End)
+            .on_input(Message::CountedInput)
+            .on_submit(Message::CloseDay)
+            .width(100.0)
+            .padding(5),
+        difference,
+        horizontal_space(),
+        button("Close day")
+            .on_press(Message::CloseDay)
+            .style(button::danger),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// The difference between counted and expected cash, e.g. "$2.50 short".
+fn over_short<'a>(difference: f32) -> Element<'a, Message> {
+    if difference.abs() < 0.005 {
+        text("Balanced").style(text::success).into()
+    } else if difference > 0.0 {
+        text(format!("{} over", money(difference)))
+            .style(text::danger)
+            .into()
+    } else {
+        text(format!("{} short", money(-difference)))
+            .style(text::danger)
+            .into()
+    }
+}
+
+fn history(drawer: &Drawer) -> Element<'_, Message> {
+    let days = drawer.history().take(14).fold(
+        column![
+            text("Past days").size(16),
+            row![
+                text("Day").width(Fill),
+                text("Expected").align_x(Alignment::End).width(100.0),
+                text("Counted").align_x(Alignment::End).width(100.0),
+                horizontal_space().width(120.0),
+            ]
+            .spacing(10),
+        ]
+        .spacing(5),
+        |col, day| {
+            let counted = day.count.as_ref().map_or(0.0, |count| count.counted);
+            col.push(
+                row![
+                    text(i18n::date(&day.opened_at)).width(Fill),
+                    text(money(day.expected()))
+                        .align_x(Alignment::End)
+                        .width(100.0),
+                    text(money(counted)).align_x(Alignment::End).width(100.0),
+                    container(over_short(day.difference().unwrap_or(0.0)))
+                        .width(120.0),
+                ]
+                .spacing(10),
+            )
+        },
+    );
+
+    days.into()
+}
+
+pub fn handle_hotkey(hotkey: Hotkey) -> Action<Instruction, Message> {
+    match hotkey {
+        Hotkey::Escape => Action::navigate(Screen::List),
+        _ => Action::none(),
+    }
+}
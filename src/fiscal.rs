@@ -0,0 +1,86 @@
+//! Signing paid receipts with a fiscal module
+//!
+//! When a sale is paid in full, the [`Fiscal`] module set up in the config
+//! file is asked for the fiscal number and signature of its receipt, which
+//! are kept on the sale and printed on the receipt, the QR code holding
+//! what the module asks for. Signing runs in the background, and a receipt
+//! it fails for is left unsigned, with the error shown.
+//!
+//! Modules are compiled in and picked with `fiscal.provider`:
+//!
+//! - `Off`: [`Null`], receipts aren't signed.
+//! - `Mock`: [`mock::Mock`], numbering receipts and signing them with a fixed
+//!   key, to try fiscal receipts out and as a template for a real module.
+use chrono::{DateTime, Utc};
+
+use crate::sale::Sale;
+
+pub mod mock;
+
+pub use receipts_core::fiscal::*;
+
+/// What a fiscal module signs.
+#[derive(Debug, Clone)]
+pub struct Receipt {
+    /// The id of the sale.
+    pub id: usize,
+    pub total: f32,
+    pub paid_at: DateTime<Utc>,
+}
+
+impl Receipt {
+    pub fn new(id: usize, sale: &Sale) -> Self {
+        Self {
+            id,
+            total: sale.calculate_total(),
+            paid_at: Utc::now(),
+        }
+    }
+}
+
+pub trait Fiscal: Send {
+    /// The name of the module, as logged.
+    fn name(&self) -> &'static str;
+
+    /// Number and sign `receipt`, or `None` if receipts aren't signed.
+    fn sign(&mut self, receipt: &Receipt) -> Result<Option<Signature>, String>;
+}
+
+/// Leaves receipts unsigned, where there is no fiscalization.
+pub struct Null;
+
+impl Fiscal for Null {
+    fn name(&self) -> &'static str {
+        "null"
+    }
+
+    fn sign(&mut self, _: &Receipt) -> Result<Option<Signature>, String> {
+        Ok(None)
+    }
+}
+
+/// The module `config` sets up.
+pub fn module(config: &Config) -> Box<dyn Fiscal> {
+    match config.provider {
+        Provider::Off => Box::new(Null),
+        Provider::Mock => Box::new(mock::Mock::load()),
+    }
+}
+
+/// Sign `receipt` with the module of `config`.
+pub async fn sign(
+    config: Config,
+    receipt: Receipt,
+) -> Result<Option<Signature>, String> {
+    let mut module = module(&config);
+    let signature = module.sign(&receipt)?;
+    if let Some(signature) = &signature {
+        tracing::info!(
+            module = module.name(),
+            sale = receipt.id,
+            number = signature.number,
+            "receipt signed"
+        );
+    }
+    Ok(signature)
+}
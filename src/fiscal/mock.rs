@@ -0,0 +1,109 @@
+//! A stand-in fiscal module, numbering receipts one after the other and
+//! signing them with a fixed key
+//!
+//! The last number given is kept in `fiscal_mock.json`, so numbers carry on
+//! after a restart. Its signatures mean nothing to any tax authority.
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::PathBuf;
+
+use super::{Fiscal, Receipt, Signature};
+use crate::config::data_dir;
+
+const KEY: &[u8] = b"iced_receipts mock fiscal key";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Mock {
+    /// The number of the last receipt signed.
+    last: u64,
+    /// Where the last number is kept, `None` to keep it in memory only.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl Mock {
+    fn path() -> PathBuf {
+        data_dir().join("fiscal_mock.json")
+    }
+
+    pub fn load() -> Self {
+        let mock: Self = std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(Self::path()),
+            ..mock
+        }
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        std::fs::create_dir_all(data_dir()).map_err(|e| e.to_string())?;
+        let json = serde_json::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| e.to_string())
+    }
+}
+
+impl Fiscal for Mock {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    fn sign(&mut self, receipt: &Receipt) -> Result<Option<Signature>, String> {
+        self.last += 1;
+        self.save()?;
+
+        let number = format!("MOCK-{:06}", self.last);
+        let signed = format!(
+            "{number}|{}|{:.2}|{}",
+            receipt.id,
+            receipt.total,
+            receipt.paid_at.to_rfc3339()
+        );
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(KEY).expect("HMAC accepts any key");
+        mac.update(signed.as_bytes());
+        let signature = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(mac.finalize().into_bytes());
+
+        Ok(Some(Signature {
+            qr: Some(format!("{signed}|{signature}")),
+            number,
+            signature,
+            signed_at: receipt.paid_at,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    #[test]
+    fn receipts_are_numbered_in_turn_and_signed() {
+        let mut mock = Mock::default();
+        let receipt = Receipt {
+            id: 42,
+            total: 18.5,
+            paid_at: Utc.with_ymd_and_hms(2024, 3, 15, 18, 42, 0).unwrap(),
+        };
+
+        let first = mock.sign(&receipt).unwrap().unwrap();
+        let second = mock.sign(&receipt).unwrap().unwrap();
+        assert_eq!(first.number, "MOCK-000001");
+        assert_eq!(second.number, "MOCK-000002");
+        assert_ne!(first.signature, second.signature);
+        assert_eq!(
+            first.qr.as_deref().map(|qr| qr.split('|').count()),
+            Some(5)
+        );
+        assert!(first.qr.unwrap().starts_with("MOCK-000001|42|18.50|"));
+    }
+}
@@ -0,0 +1,176 @@
+//! Gift cards, sold as items and redeemed as a tender on later sales
+//!
+//! A card is issued when a sale with a gift card item is saved, holding the
+//! item's total as its balance. Sales draw cards down through their
+//! [`Tender`]s when saved. Saving a sale again only moves the difference, so
+//! editing a sale never charges a card twice.
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::future::Future;
+use std::path::PathBuf;
+
+use crate::config::data_dir;
+use crate::data::{self, Error};
+use crate::sale::Sale;
+
+pub mod lookup;
+
+/// Characters used in codes, leaving out those easily mistaken for others.
+const CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+/// Codes are printed in groups of this many characters.
+const CODE_GROUP: usize = 4;
+const CODE_LENGTH: usize = 12;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Card {
+    pub code: String,
+    /// The value the card was issued with.
+    pub value: f32,
+    pub balance: f32,
+    pub issued_at: DateTime<Utc>,
+    /// The sale the card was sold on.
+    pub sale_id: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Cards {
+    cards: BTreeMap<String, Card>,
+}
+
+impl Cards {
+    fn path() -> PathBuf {
+        data_dir().join("gift_cards.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save in the background, see [`data::save_json`].
+    pub fn save(
+        &self,
+    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        data::save_json(Self::path(), self)
+    }
+
+    /// The card with `code`, however it was typed.
+    pub fn get(&self, code: &str) -> Option<&Card> {
+        self.cards.get(&canonical(code))
+    }
+
+    /// Cards whose code contains `query`, most recently issued first.
+    pub fn search(&self, query: &str) -> Vec<&Card> {
+        let query = strip(query);
+        let mut cards: Vec<&Card> = self
+            .cards
+            .values()
+            .filter(|card| strip(&card.code).contains(&query))
+            .collect();
+        cards.sort_by_key(|card| std::cmp::Reverse(card.issued_at));
+        cards
+    }
+
+    /// How much tender `except` of `draft` can still draw from card `code`,
+    /// counting what the stored version of the sale already drew as
+    /// available again, or `None` if there is no such card.
+    pub fn available(
+        &self,
+        code: &str,
+        stored: Option<&Sale>,
+        draft: &Sale,
+        except: usize,
+    ) -> Option<f32> {
+        let card = self.get(code)?;
+        let code = &card.code;
+        let others: f32 = draft
+            .tenders
+            .iter()
+            .filter(|tender| tender.id != except)
+            .filter(|tender| canonical(&tender.code) == *code)
+            .map(|tender| tender.amount)
+            .sum();
+
+        Some(card.balance + stored.map_or(0.0, |s| drawn(s, code)) - others)
+    }
+
+    /// Issue the cards sold on `sale`, and move card balances by how much
+    /// its tenders changed since the `stored` version of it.
+    pub fn settle(
+        &mut self,
+        sale_id: usize,
+        stored: Option<&Sale>,
+        sale: &Sale,
+    ) {
+        for item in &sale.items {
+            let Some(code) = &item.gift_card else {
+                continue;
+            };
+            if item.total() > 0.0 && !self.cards.contains_key(code) {
+                self.cards.insert(
+                    code.clone(),
+                    Card {
+                        code: code.clone(),
+                        value: item.total(),
+                        balance: item.total(),
+                        issued_at: Utc::now(),
+                        sale_id,
+                    },
+                );
+            }
+        }
+
+        let codes: BTreeSet<String> = stored
+            .into_iter()
+            .chain([sale])
+            .flat_map(|sale| &sale.tenders)
+            .map(|tender| canonical(&tender.code))
+            .collect();
+        for code in codes {
+            if let Some(card) = self.cards.get_mut(&code) {
+                card.balance -= drawn(sale, &code)
+                    - stored.map_or(0.0, |stored| drawn(stored, &code));
+            }
+        }
+    }
+}
+
+/// A new random card code, e.g. `K7QD-2MXP-9HTA`.
+pub fn new_code() -> String {
+    let mut rng = rand::thread_rng();
+    let code: String = (0..CODE_LENGTH)
+        .map(|_| CODE_CHARS[rng.gen_range(0..CODE_CHARS.len())] as char)
+        .collect();
+    canonical(&code)
+}
+
+/// `code` as printed, whatever the case and grouping it was typed in.
+pub fn canonical(code: &str) -> String {
+    strip(code)
+        .chars()
+        .collect::<Vec<_>>()
+        .chunks(CODE_GROUP)
+        .map(|group| group.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn strip(code: &str) -> String {
+    code.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// How much `sale` draws from card `code`.
+fn drawn(sale: &Sale, code: &str) -> f32 {
+    sale.tenders
+        .iter()
+        .filter(|tender| canonical(&tender.code) == code)
+        .map(|tender| tender.amount)
+        .sum()
+}
@@ -0,0 +1,183 @@
+//! Look up gift cards to check their balance
+use iced::widget::{
+    button, column, container, horizontal_space, row, scrollable, text,
+    text_input,
+};
+use iced::{Alignment, Element, Fill, Length};
+
+use super::{Card, Cards};
+use crate::dialog;
+use crate::i18n;
+use crate::keypad;
+use crate::style;
+use crate::{Action, Hotkey, Screen};
+
+#[derive(Debug, Default)]
+pub struct Lookup {
+    pub query: String,
+    /// Whether the code is being typed on the on-screen keyboard.
+    pub keyboard: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Back,
+    QueryInput(String),
+    OpenKeyboard,
+    CloseKeyboard,
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Navigate(Screen),
+}
+
+impl From<Screen> for Instruction {
+    fn from(screen: Screen) -> Self {
+        Instruction::Navigate(screen)
+    }
+}
+
+pub fn update(
+    lookup: &mut Lookup,
+    message: Message,
+) -> Action<Instruction, Message> {
+    match message {
+        Message::Back => Action::navigate(Screen::List),
+        Message::QueryInput(query) => {
+            lookup.query = query;
+            Action::none()
+        }
+        Message::OpenKeyboard => {
+            lookup.keyboard = true;
+            Action::none()
+        }
+        Message::CloseKeyboard => {
+            lookup.keyboard = false;
+            Action::none()
+        }
+    }
+}
+
+pub fn view<'a>(
+    lookup: &'a Lookup,
+    cards: &'a Cards,
+    keypad: bool,
+) -> Element<'a, Message> {
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text("Gift cards").size(16),
+        horizontal_space(),
+        text_input("Card code", &lookup.query)
+            .style(style::input)
+            .on_input(Message::QueryInput)
+            .width(200.0)
+            .padding(5),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+    let header = if keypad {
+        header.push(
+            button(text("ABC").size(12).center())
+                .width(40.0)
+                .on_press(Message::OpenKeyboard)
+                .style(button::secondary),
+        )
+    } else {
+        header
+    };
+
+    let found = cards.search(&lookup.query);
+    let body: Element<_> = if found.is_empty() {
+        container(text(if lookup.query.is_empty() {
+            "No gift cards sold yet"
+        } else {
+            "No gift card with that code"
+        }))
+        .center(Fill)
+        .into()
+    } else {
+        let column_headers = row![
+            text("Code").width(Fill),
+            text("Issued").width(150.0),
+            text("Value").align_x(Alignment::End).width(100.0),
+            text("Balance").align_x(Alignment::End).width(100.0),
+        ]
+        .spacing(5);
+
+        scrollable(
+            found
+                .into_iter()
+                .fold(column![column_headers].spacing(5), |col, card| {
+                    col.push(card_row(card))
+                })
+                .padding(20),
+        )
+        .into()
+    };
+
+    let screen = container(
+        column![
+            header,
+            container(body)
+                .height(Fill)
+                .width(Fill)
+                .style(container::rounded_box)
+        ]
+        .spacing(20),
+    )
+    .padding(20);
+
+    if lookup.keyboard && keypad {
+        dialog::view(screen, keyboard(lookup), Message::CloseKeyboard)
+    } else {
+        screen.into()
+    }
+}
+
+fn keyboard(lookup: &Lookup) -> Element<'_, Message> {
+    column![
+        text("Card code"),
+        row![
+            text(&lookup.query).size(28).width(Fill),
+            button("Done")
+                .on_press(Message::CloseKeyboard)
+                .style(button::success),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        keypad::keyboard(&lookup.query, Message::QueryInput),
+    ]
+    .spacing(10)
+    .width(Length::Shrink)
+    .into()
+}
+
+fn card_row(card: &Card) -> Element<'_, Message> {
+    row![
+        text(&card.code).width(Fill),
+        text(i18n::date(&card.issued_at)).width(150.0),
+        text(format!("${:.2}", card.value))
+            .align_x(Alignment::End)
+            .width(100.0),
+        text(format!("${:.2}", card.balance))
+            .align_x(Alignment::End)
+            .width(100.0),
+    ]
+    .spacing(5)
+    .into()
+}
+
+pub fn handle_hotkey(
+    lookup: &mut Lookup,
+    hotkey: Hotkey,
+) -> Action<Instruction, Message> {
+    match hotkey {
+        Hotkey::Escape if lookup.keyboard => {
+            lookup.keyboard = false;
+            Action::none()
+        }
+        Hotkey::Escape => Action::navigate(Screen::List),
+        _ => Action::none(),
+    }
+}
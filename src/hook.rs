@@ -0,0 +1,116 @@
+//! Hooks run on events of the sale lifecycle, for custom integrations
+//!
+//! A [`Hook`] is told about every [`Event`]: a sale being saved, a payment
+//! being taken and the cash drawer day being closed. Hooks run one after the
+//! other on a thread of their own, so a slow one never holds up the UI, and
+//! one failing is only logged.
+//!
+//! Hooks are compiled in, each behind a cargo feature, and set up from the
+//! `hooks` section of the config file when the app starts:
+//!
+//! - `webhook`: POST every event as JSON to `webhook_url`.
+//!
+//! Loading hooks at run time, e.g. as WebAssembly modules, isn't supported.
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc;
+
+use crate::currency;
+use crate::drawer;
+use crate::sale::Sale;
+use crate::terminal;
+
+#[cfg(feature = "webhook")]
+mod webhook;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Where the `webhook` hook sends events, empty for nowhere.
+    pub webhook_url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A sale was saved, `created` if it is new.
+    SaleSaved {
+        id: usize,
+        created: bool,
+        sale: Box<Sale>,
+    },
+    PaymentTaken {
+        id: usize,
+        payment: Payment,
+    },
+    /// The cash drawer was counted at the end of the day.
+    DayClosed {
+        day: drawer::Day,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Payment {
+    Cash(f32),
+    Card(terminal::Card),
+    Foreign(currency::Payment),
+}
+
+pub trait Hook: Send {
+    /// The name of the hook, as logged when it fails.
+    fn name(&self) -> &'static str;
+
+    fn handle(&mut self, event: &Event) -> Result<(), String>;
+}
+
+/// A handle to the hooks set up. The thread running them stops once every
+/// handle has been dropped.
+#[derive(Debug, Clone, Default)]
+pub struct Registry(Option<mpsc::Sender<Event>>);
+
+impl Registry {
+    /// Start running `hooks`, if there are any.
+    pub fn new(hooks: Vec<Box<dyn Hook>>) -> Self {
+        if hooks.is_empty() {
+            return Self(None);
+        }
+        tracing::info!(count = hooks.len(), "running hooks");
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || run(hooks, receiver));
+        Self(Some(sender))
+    }
+
+    /// Tell every hook about `event`.
+    pub fn dispatch(&self, event: Event) {
+        if let Some(sender) = &self.0 {
+            let _ = sender.send(event);
+        }
+    }
+}
+
+/// The hooks compiled in which `config` sets up.
+pub fn compiled_in(config: &Config) -> Vec<Box<dyn Hook>> {
+    #[cfg_attr(not(feature = "webhook"), allow(unused_mut))]
+    let mut hooks: Vec<Box<dyn Hook>> = Vec::new();
+
+    if !config.webhook_url.is_empty() {
+        #[cfg(feature = "webhook")]
+        hooks.push(Box::new(webhook::Webhook::new(&config.webhook_url)));
+        #[cfg(not(feature = "webhook"))]
+        tracing::warn!(
+            "a webhook URL is set, but the webhook hook isn't built in"
+        );
+    }
+
+    hooks
+}
+
+fn run(mut hooks: Vec<Box<dyn Hook>>, events: mpsc::Receiver<Event>) {
+    for event in events {
+        for hook in &mut hooks {
+            if let Err(e) = hook.handle(&event) {
+                tracing::warn!(hook = hook.name(), "hook failed: {e}");
+            }
+        }
+    }
+}
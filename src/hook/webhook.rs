@@ -0,0 +1,37 @@
+//! POST every event as JSON to a URL
+use std::time::Duration;
+
+use super::{Event, Hook};
+
+/// How long the receiving server may take to answer.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct Webhook {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl Webhook {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            agent: ureq::AgentBuilder::new().timeout(TIMEOUT).build(),
+        }
+    }
+}
+
+impl Hook for Webhook {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn handle(&mut self, event: &Event) -> Result<(), String> {
+        let json = serde_json::to_string(event).map_err(|e| e.to_string())?;
+        self.agent
+            .post(&self.url)
+            .set("Content-Type", "application/json")
+            .send_string(&json)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,140 @@
+//! On-screen number pad and keyboard for touch screens without a keyboard
+//!
+//! Every key press produces the whole new value of the input the keys are
+//! attached to, so a screen gets the same message as when the value is typed.
+use iced::widget::{button, column, horizontal_space, row, text};
+use iced::{Alignment, Element};
+
+use crate::i18n;
+
+#[derive(Debug, Clone, Copy)]
+enum Key {
+    Char(char),
+    Decimal,
+    Space,
+    Backspace,
+    Clear,
+}
+
+impl Key {
+    fn label(self) -> String {
+        match self {
+            Key::Char(c) => c.to_string(),
+            Key::Decimal => i18n::locale().decimal_separator().to_string(),
+            Key::Space => "␣".to_string(),
+            Key::Backspace => "⌫".to_string(),
+            Key::Clear => "C".to_string(),
+        }
+    }
+
+    /// `value` after pressing the key.
+    fn press(self, value: &str) -> String {
+        let mut value = value.to_string();
+        match self {
+            Key::Char(c) => value.push(c),
+            Key::Decimal => {
+                let separator = i18n::locale().decimal_separator();
+                if !value.contains(separator) {
+                    if value.is_empty() {
+                        value.push('0');
+                    }
+                    value.push(separator);
+                }
+            }
+            Key::Space => value.push(' '),
+            Key::Backspace => {
+                value.pop();
+            }
+            Key::Clear => value.clear(),
+        }
+        value
+    }
+}
+
+/// A number pad typing into `value`, with a decimal key if `decimals`.
+pub fn view<'a, Message: Clone + 'a>(
+    value: &str,
+    decimals: bool,
+    on_input: impl Fn(String) -> Message,
+) -> Element<'a, Message> {
+    let rows: [Vec<Option<Key>>; 4] = [
+        chars("789"),
+        chars("456"),
+        chars("123"),
+        vec![
+            decimals.then_some(Key::Decimal),
+            Some(Key::Char('0')),
+            Some(Key::Backspace),
+        ],
+    ];
+
+    keys(rows, 60.0, value, &on_input)
+        .push(key(Key::Clear, 190.0, value, &on_input))
+        .align_x(Alignment::Center)
+        .into()
+}
+
+/// A keyboard of digits and capital letters typing into `value`, for codes
+/// and names.
+pub fn keyboard<'a, Message: Clone + 'a>(
+    value: &str,
+    on_input: impl Fn(String) -> Message,
+) -> Element<'a, Message> {
+    let rows: [Vec<Option<Key>>; 4] = [
+        chars("1234567890"),
+        chars("QWERTYUIOP"),
+        chars("ASDFGHJKL-"),
+        [chars("ZXCVBNM"), vec![Some(Key::Backspace)]].concat(),
+    ];
+
+    keys(rows, 44.0, value, &on_input)
+        .push(
+            row![
+                key(Key::Clear, 94.0, value, &on_input),
+                key(Key::Space, 292.0, value, &on_input),
+            ]
+            .spacing(5),
+        )
+        .align_x(Alignment::Center)
+        .into()
+}
+
+fn chars(chars: &str) -> Vec<Option<Key>> {
+    chars.chars().map(|c| Some(Key::Char(c))).collect()
+}
+
+/// Rows of keys `width` wide, with a gap wherever a key is `None`.
+fn keys<'a, Message: Clone + 'a>(
+    rows: [Vec<Option<Key>>; 4],
+    width: f32,
+    value: &str,
+    on_input: &impl Fn(String) -> Message,
+) -> iced::widget::Column<'a, Message> {
+    rows.into_iter().fold(column![].spacing(5), |column, keys| {
+        column.push(keys.into_iter().fold(
+            row![].spacing(5),
+            |row, k| match k {
+                Some(k) => row.push(key(k, width, value, on_input)),
+                None => row.push(horizontal_space().width(width)),
+            },
+        ))
+    })
+}
+
+fn key<'a, Message: Clone + 'a>(
+    key: Key,
+    width: f32,
+    value: &str,
+    on_input: &impl Fn(String) -> Message,
+) -> iced::widget::Button<'a, Message> {
+    button(
+        text(key.label())
+            .size(20)
+            .shaping(text::Shaping::Advanced)
+            .center(),
+    )
+    .width(width)
+    .height(50)
+    .on_press(on_input(key.press(value)))
+    .style(button::secondary)
+}
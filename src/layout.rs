@@ -0,0 +1,710 @@
+//! Receipt layouts written in a small template language, and their editor
+//!
+//! A layout is plain text, one line of the receipt per line of the layout.
+//! Text is printed as written, with placeholders such as `{name}` filled in
+//! from the sale. Lines starting with `@` are directives which print a part
+//! of the receipt, e.g. `@items` or `@totals`, and lines starting with `#`
+//! are comments. A line starting with a language code in brackets, e.g.
+//! `[de] Danke!`, is only printed in that language. Text with a translation,
+//! like the `RECEIPT` of the built-in layout, is printed translated, and
+//! dates and amounts are written as the language writes them.
+//!
+//! A line whose placeholders are all empty is left out, so that e.g.
+//! `@line Guests | {guests}` only prints for sales with guests. The layout is
+//! stored in the config, an empty one standing for the built-in [`DEFAULT`].
+//!
+//! The header and footer text set up in settings, e.g. a VAT number or a
+//! return policy, print where the layout has `@header` and `@footer`, with
+//! their placeholders filled in the same way.
+use iced::widget::{
+    button, column, container, horizontal_space, row, scrollable, text,
+    text_editor,
+};
+use iced::{Alignment, Element, Fill, Font, Length};
+use std::fmt;
+use std::sync::LazyLock;
+
+use crate::config::Config;
+use crate::custom;
+use crate::i18n::{self, tr, Locale};
+use crate::receipt;
+use crate::sale::Sale;
+use crate::ticket::{self, wrap, Line, WIDTH};
+use crate::{Action, Hotkey, Screen};
+
+/// The layout receipts are printed in unless another one is set up.
+pub const DEFAULT: &str = "\
+@image
+@header
+@center RECEIPT
+@rule =
+@line {receipt} | {date} {time}
+{name}
+{address}
+@tax_ids
+@line Guests | {guests}
+@rule
+@items quantity name total
+@rule
+@totals
+@fields
+@rule =
+@fine_print
+@footer
+@qr
+";
+
+static BUILT_IN: LazyLock<Layout> =
+    LazyLock::new(|| compile(DEFAULT).expect("the built-in layout compiles"));
+
+/// A mistake in a layout, on the line it was found on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+/// A value of the sale filled in for a placeholder.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Placeholder {
+    /// "Receipt #12", or "New sale" before it is saved.
+    Receipt,
+    Number,
+    Date,
+    Time,
+    Name,
+    /// The address of a delivery order.
+    Address,
+    Guests,
+    OrderType,
+    Total,
+    Due,
+}
+
+impl Placeholder {
+    const ALL: [(&'static str, Placeholder); 11] = [
+        ("receipt", Placeholder::Receipt),
+        ("number", Placeholder::Number),
+        ("receipt_no", Placeholder::Number),
+        ("date", Placeholder::Date),
+        ("time", Placeholder::Time),
+        ("name", Placeholder::Name),
+        ("address", Placeholder::Address),
+        ("guests", Placeholder::Guests),
+        ("order_type", Placeholder::OrderType),
+        ("total", Placeholder::Total),
+        ("due", Placeholder::Due),
+    ];
+
+    fn value(self, id: Option<usize>, sale: &Sale) -> String {
+        match self {
+            Placeholder::Receipt => id
+                .map_or(tr("New sale").to_string(), |id| {
+                    format!("{} #{id}", tr("Receipt"))
+                }),
+            Placeholder::Number => {
+                id.map_or(String::new(), |id| id.to_string())
+            }
+            Placeholder::Date => i18n::date(&sale.created_at),
+            Placeholder::Time => i18n::time(&sale.created_at),
+            Placeholder::Name => sale.name.clone(),
+            Placeholder::Address => sale
+                .delivery()
+                .map_or(String::new(), |d| d.address.trim().to_string()),
+            Placeholder::Guests => {
+                sale.guests.map_or(String::new(), |g| g.to_string())
+            }
+            Placeholder::OrderType => sale.order_type.to_string(),
+            Placeholder::Total => i18n::money(sale.calculate_total()),
+            Placeholder::Due => i18n::money(sale.calculate_due()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+/// Text with placeholders.
+#[derive(Debug, Clone, PartialEq)]
+struct Template(Vec<Part>);
+
+impl Template {
+    /// The text filled in, or `None` if it has placeholders and they are all
+    /// empty.
+    fn fill(&self, id: Option<usize>, sale: &Sale) -> Option<String> {
+        let mut filled = String::new();
+        let mut placeholders = 0;
+        let mut empty = 0;
+        for part in &self.0 {
+            match part {
+                Part::Text(text) => {
+                    let words = text.trim();
+                    filled.push_str(&text.replacen(
+                        words,
+                        i18n::translate(words),
+                        1,
+                    ));
+                }
+                Part::Placeholder(placeholder) => {
+                    let value = placeholder.value(id, sale);
+                    placeholders += 1;
+                    empty += usize::from(value.is_empty());
+                    filled.push_str(&value);
+                }
+            }
+        }
+        (placeholders == 0 || empty < placeholders).then_some(filled)
+    }
+}
+
+/// A column of the item lines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Column {
+    Quantity,
+    Name,
+    Price,
+    Total,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Directive {
+    Text(Template),
+    /// `@center text`
+    Center(Template),
+    /// `@line left | right`, pushed apart to the edges.
+    Line(Template, Template),
+    /// `@rule` or e.g. `@rule =`, across the whole width.
+    Rule(char),
+    /// `@logo` up to `@end`, printed centered as written.
+    Logo(Vec<String>),
+    /// The logo image set up in the settings, if any.
+    Image,
+    /// `@items` with the columns to show, e.g. `@items quantity name`.
+    Items(Vec<Column>),
+    Totals,
+    /// Custom fields printed on receipts, after a rule.
+    Fields,
+    /// The fine print for the order type, followed by a rule.
+    FinePrint,
+    /// The header text set up in settings, centered.
+    Header,
+    /// The footer text set up in settings, centered.
+    Footer,
+    /// The tax IDs of the business and of the customer, if known.
+    TaxIds,
+    Qr,
+}
+
+/// The text set up to print on receipts besides the sale.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Notes<'a> {
+    pub header: &'a str,
+    /// The fine print for the order type of the sale.
+    pub fine_print: &'a str,
+    pub footer: &'a str,
+    /// The tax ID of the business.
+    pub tax_id: &'a str,
+    pub customer_tax_id: &'a str,
+}
+
+/// A compiled layout, ready to render receipts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    /// The directives in order, with the language each is limited to.
+    directives: Vec<(Option<Locale>, Directive)>,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        BUILT_IN.clone()
+    }
+}
+
+/// Compile the layout written in `source`.
+pub fn compile(source: &str) -> Result<Layout, Error> {
+    let mut directives = Vec::new();
+    // The logo being read, with the line it started on
+    let mut logo: Option<(usize, Option<Locale>, Vec<String>)> = None;
+
+    for (index, raw) in source.lines().enumerate() {
+        let number = index + 1;
+        let error = |message: String| Error {
+            line: number,
+            message,
+        };
+        let raw = raw.trim_end();
+
+        if let Some((_, locale, lines)) = &mut logo {
+            if raw.trim() == "@end" {
+                let locale = *locale;
+                let lines = std::mem::take(lines);
+                directives.push((locale, Directive::Logo(lines)));
+                logo = None;
+            } else {
+                lines.push(raw.to_string());
+            }
+            continue;
+        }
+
+        if raw.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let (locale, rest) = match raw.strip_prefix('[') {
+            Some(rest) => {
+                let (code, rest) = rest.split_once(']').ok_or_else(|| {
+                    error("the language code is never closed with ]".into())
+                })?;
+                let locale = Locale::ALL
+                    .into_iter()
+                    .find(|locale| locale.code() == code.trim())
+                    .ok_or_else(|| {
+                        error(format!("unknown language code [{code}]"))
+                    })?;
+                (Some(locale), rest.trim_start())
+            }
+            None => (None, raw),
+        };
+
+        let Some(directive) = rest.strip_prefix('@') else {
+            let text = template(rest).map_err(error)?;
+            directives.push((locale, Directive::Text(text)));
+            continue;
+        };
+
+        let (name, argument) = directive
+            .split_once(char::is_whitespace)
+            .map_or((directive, ""), |(name, arg)| (name, arg.trim()));
+        let no_argument = |directive: Directive| {
+            if argument.is_empty() {
+                Ok(directive)
+            } else {
+                Err(error(format!("@{name} takes nothing after it")))
+            }
+        };
+
+        let directive = match name {
+            "center" => Directive::Center(template(argument).map_err(error)?),
+            "line" => {
+                let (left, right) =
+                    argument.split_once('|').ok_or_else(|| {
+                        error(
+                            "@line needs a left and a right side split by |"
+                                .into(),
+                        )
+                    })?;
+                Directive::Line(
+                    template(left.trim()).map_err(error)?,
+                    template(right.trim()).map_err(error)?,
+                )
+            }
+            "rule" => {
+                let mut chars = argument.chars();
+                match (chars.next(), chars.next()) {
+                    (None, _) => Directive::Rule('-'),
+                    (Some(c), None) => Directive::Rule(c),
+                    _ => {
+                        return Err(error(
+                            "@rule takes a single character".into(),
+                        ))
+                    }
+                }
+            }
+            "logo" => {
+                if !argument.is_empty() {
+                    return Err(error("@logo takes nothing after it".into()));
+                }
+                logo = Some((number, locale, Vec::new()));
+                continue;
+            }
+            "end" => return Err(error("@end without a @logo".into())),
+            "items" if argument.is_empty() => Directive::Items(vec![
+                Column::Quantity,
+                Column::Name,
+                Column::Total,
+            ]),
+            "items" => Directive::Items(
+                argument
+                    .split_whitespace()
+                    .map(|column| match column {
+                        "quantity" => Ok(Column::Quantity),
+                        "name" => Ok(Column::Name),
+                        "price" => Ok(Column::Price),
+                        "total" => Ok(Column::Total),
+                        _ => Err(error(format!(
+                            "unknown column \"{column}\", expected \
+                             quantity, name, price or total"
+                        ))),
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            "image" => no_argument(Directive::Image)?,
+            "totals" => no_argument(Directive::Totals)?,
+            "fields" => no_argument(Directive::Fields)?,
+            "fine_print" => no_argument(Directive::FinePrint)?,
+            "header" => no_argument(Directive::Header)?,
+            "footer" => no_argument(Directive::Footer)?,
+            "tax_ids" => no_argument(Directive::TaxIds)?,
+            "qr" => no_argument(Directive::Qr)?,
+            _ => return Err(error(format!("unknown directive @{name}"))),
+        };
+        directives.push((locale, directive));
+    }
+
+    if let Some((line, ..)) = logo {
+        return Err(Error {
+            line,
+            message: "@logo is never closed with @end".to_string(),
+        });
+    }
+
+    Ok(Layout { directives })
+}
+
+/// Check the placeholders of header or footer `text`, line by line.
+pub fn check(text: &str) -> Result<(), Error> {
+    for (index, line) in text.lines().enumerate() {
+        template(line).map_err(|message| Error {
+            line: index + 1,
+            message,
+        })?;
+    }
+    Ok(())
+}
+
+/// Header or footer `text` with its placeholders filled in, centered. Lines
+/// whose placeholders are all empty are left out, and lines which don't
+/// [`check`] are printed as written.
+fn notes(text: &str, id: Option<usize>, sale: &Sale) -> Vec<Line> {
+    text.lines()
+        .filter_map(|line| match template(line.trim()) {
+            Ok(template) => template.fill(id, sale),
+            Err(_) => Some(line.trim().to_string()),
+        })
+        .flat_map(|line| wrap(&line, WIDTH))
+        .map(Line::Center)
+        .collect()
+}
+
+/// Read text with `{placeholders}`, `{{` standing for a brace.
+fn template(source: &str) -> Result<Template, String> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut rest = source;
+
+    while let Some(start) = rest.find(['{', '}']) {
+        literal.push_str(&rest[..start]);
+        let brace = &rest[start..];
+        if brace.starts_with("{{") || brace.starts_with("}}") {
+            literal.push_str(&brace[..1]);
+            rest = &brace[2..];
+            continue;
+        }
+        if brace.starts_with('}') {
+            return Err("} without a {, write }} for a brace".to_string());
+        }
+        let end = brace.find('}').ok_or_else(|| {
+            "a placeholder is never closed with }".to_string()
+        })?;
+        let name = brace[1..end].trim();
+        let placeholder = Placeholder::ALL
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|(_, placeholder)| *placeholder)
+            .ok_or_else(|| format!("unknown placeholder {{{name}}}"))?;
+        if !literal.is_empty() {
+            parts.push(Part::Text(std::mem::take(&mut literal)));
+        }
+        parts.push(Part::Placeholder(placeholder));
+        rest = &brace[end + 1..];
+    }
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        parts.push(Part::Text(literal));
+    }
+
+    Ok(Template(parts))
+}
+
+impl Layout {
+    /// Render the receipt for a sale, with `notes`, `qr` and the logo as
+    /// text where the layout asks for them.
+    pub fn render(
+        &self,
+        id: Option<usize>,
+        sale: &Sale,
+        fields: &[custom::Field],
+        notes: &Notes,
+        qr: Option<&str>,
+        logo: Option<&str>,
+    ) -> String {
+        ticket::join(&self.lines(id, sale, fields, notes, qr, logo))
+    }
+
+    /// Lay out the receipt for a sale as [`render`](Self::render) prints
+    /// it.
+    pub fn lines(
+        &self,
+        id: Option<usize>,
+        sale: &Sale,
+        fields: &[custom::Field],
+        notes: &Notes,
+        qr: Option<&str>,
+        logo: Option<&str>,
+    ) -> Vec<Line> {
+        let locale = i18n::locale();
+        let mut lines = Vec::new();
+        let wrapped = |text: &str| {
+            wrap(text, WIDTH)
+                .into_iter()
+                .map(Line::Text)
+                .collect::<Vec<_>>()
+        };
+        let centered = |text: &str| Line::Center(text.to_string());
+
+        for (only_in, directive) in &self.directives {
+            if only_in.is_some_and(|only_in| only_in != locale) {
+                continue;
+            }
+            match directive {
+                Directive::Text(text) => {
+                    if let Some(text) = text.fill(id, sale) {
+                        lines.extend(wrapped(&text));
+                    }
+                }
+                Directive::Center(text) => {
+                    if let Some(text) = text.fill(id, sale) {
+                        lines.push(Line::Center(text));
+                    }
+                }
+                Directive::Line(left, right) => {
+                    if let (Some(left), Some(right)) =
+                        (left.fill(id, sale), right.fill(id, sale))
+                    {
+                        lines.push(Line::Split(left, right));
+                    }
+                }
+                Directive::Rule(c) => lines.push(Line::Rule(*c)),
+                Directive::Logo(logo) => {
+                    lines.extend(logo.iter().map(|l| centered(l.trim())))
+                }
+                Directive::Image => {
+                    if let Some(logo) = logo {
+                        lines.extend(logo.lines().map(centered));
+                    }
+                }
+                Directive::Items(columns) => {
+                    lines.extend(ticket::items(sale, columns))
+                }
+                Directive::Totals => lines.extend(ticket::totals(sale)),
+                Directive::Fields => lines.extend(ticket::fields(sale, fields)),
+                Directive::FinePrint => {
+                    let fine_print = notes.fine_print.trim();
+                    if !fine_print.is_empty() {
+                        for paragraph in fine_print.lines() {
+                            lines.extend(wrapped(paragraph));
+                        }
+                        lines.push(Line::Rule('='));
+                    }
+                }
+                Directive::Header => {
+                    lines.extend(self::notes(notes.header, id, sale))
+                }
+                Directive::Footer => {
+                    lines.extend(self::notes(notes.footer, id, sale))
+                }
+                Directive::TaxIds => {
+                    for (label, tax_id) in [
+                        (tr("Tax ID"), notes.tax_id),
+                        (tr("Customer tax ID"), notes.customer_tax_id),
+                    ] {
+                        if !tax_id.trim().is_empty() {
+                            lines.push(Line::Split(
+                                label.to_string(),
+                                tax_id.trim().to_string(),
+                            ));
+                        }
+                    }
+                }
+                Directive::Qr => {
+                    if let Some(qr) = qr {
+                        lines.extend(qr.lines().map(centered));
+                    }
+                }
+            }
+        }
+
+        lines
+    }
+}
+
+/// The layout set up in `config`, falling back to the built-in one if it
+/// doesn't compile.
+pub fn configured(config: &Config) -> Layout {
+    if config.receipt_layout.trim().is_empty() {
+        return Layout::default();
+    }
+    compile(&config.receipt_layout).unwrap_or_default()
+}
+
+/// The state of the layout editor screen.
+#[derive(Debug)]
+pub struct Editor {
+    pub content: text_editor::Content,
+    /// Why the layout as typed doesn't compile, in which case the last one
+    /// which did is kept.
+    pub error: Option<Error>,
+    /// The made-up sale shown in the preview.
+    pub sample: Sale,
+}
+
+impl Editor {
+    pub fn new(source: &str) -> Self {
+        let source = if source.trim().is_empty() {
+            DEFAULT
+        } else {
+            source
+        };
+        Self {
+            content: text_editor::Content::with_text(source),
+            error: compile(source).err(),
+            sample: Sale::sample(),
+        }
+    }
+}
+
+impl Default for Editor {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Back,
+    Edit(text_editor::Action),
+    Reset,
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Navigate(Screen),
+    /// Save the layout as written, empty for the built-in one.
+    Save(String),
+}
+
+impl From<Screen> for Instruction {
+    fn from(screen: Screen) -> Self {
+        Instruction::Navigate(screen)
+    }
+}
+
+pub fn update(
+    editor: &mut Editor,
+    message: Message,
+) -> Action<Instruction, Message> {
+    match message {
+        Message::Back => Action::navigate(Screen::Settings),
+        Message::Edit(action) => {
+            let is_edit = action.is_edit();
+            editor.content.perform(action);
+            if !is_edit {
+                return Action::none();
+            }
+
+            let source = editor.content.text();
+            match compile(&source) {
+                Ok(_) => {
+                    editor.error = None;
+                    let source = if source.trim() == DEFAULT.trim() {
+                        String::new()
+                    } else {
+                        source
+                    };
+                    Action::instruction(Instruction::Save(source))
+                }
+                Err(error) => {
+                    editor.error = Some(error);
+                    Action::none()
+                }
+            }
+        }
+        Message::Reset => {
+            editor.content = text_editor::Content::with_text(DEFAULT);
+            editor.error = None;
+            Action::instruction(Instruction::Save(String::new()))
+        }
+    }
+}
+
+pub fn view<'a>(
+    editor: &'a Editor,
+    config: &'a Config,
+) -> Element<'a, Message> {
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text("Receipt layout").size(16),
+        horizontal_space(),
+        button("Reset to default")
+            .on_press(Message::Reset)
+            .style(button::secondary),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let status = match &editor.error {
+        Some(error) => text(format!("{error} (not saved)")).style(text::danger),
+        None => text("Saved").style(text::success),
+    };
+
+    let help = text(
+        "Directives: @center text, @line left | right, @rule, @rule =, \
+         @image for the logo image, @logo … @end for text art, \
+         @items quantity name price total, @totals, @fields, \
+         @fine_print, @header and @footer for the text set up in \
+         settings, @tax_ids, @qr. Placeholders: {receipt}, {number} or \
+         {receipt_no}, {date}, {time}, {name}, {address}, {guests}, \
+         {order_type}, {total}, {due}. \
+         Start a line with e.g. [de] to print it only in German, \
+         or with # for a comment.",
+    )
+    .size(12);
+
+    let source = column![
+        text_editor(&editor.content)
+            .on_action(Message::Edit)
+            .font(Font::MONOSPACE)
+            .height(Fill),
+        status.size(12),
+        help,
+    ]
+    .spacing(10)
+    .width(Fill);
+
+    let preview = container(scrollable(
+        container(receipt::of(Some(1042), &editor.sample, config)).padding(10),
+    ))
+    .width(Length::Shrink)
+    .height(Fill);
+
+    column![header, row![source, preview].spacing(20).height(Fill)]
+        .spacing(20)
+        .padding(20)
+        .into()
+}
+
+pub fn handle_hotkey(hotkey: Hotkey) -> Action<Instruction, Message> {
+    match hotkey {
+        Hotkey::Escape => Action::navigate(Screen::Settings),
+        _ => Action::none(),
+    }
+}
@@ -1,67 +1,350 @@
 //! List sales and navigate to sale details or editing
-use iced::widget::{button, column, container, horizontal_space, row, text};
+//!
+//! Sales are grouped by the day they were made, the latest first, under a
+//! header with the day's count and total which stays at the top while its
+//! sales are scrolled through. Every row is the same height, so where each
+//! day starts is known without measuring, for the sticky header and for
+//! jumping to a date.
+//!
+//! Only the latest days are listed at first, a page of about [`PAGE`] sales
+//! in whole days, with more drawn on request. Paging only saves drawing the
+//! rows: the [`Header`] of every sale is read from the index at startup and
+//! kept in memory, and only sales in full are fetched from the store once
+//! opened. The headers are grouped by day as the sales or the filter
+//! change, not on every redraw.
+use chrono::{Local, NaiveDate, Utc};
+use iced::widget::scrollable::{AbsoluteOffset, Viewport};
+use iced::widget::{
+    button, column, container, horizontal_space, image, row, scrollable, stack,
+    text, text_input, tooltip, Column,
+};
 use iced::Alignment::Center;
-use iced::{Element, Fill};
-use std::collections::HashMap;
+use iced::{Element, Fill, Task};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
-use crate::Sale;
+use crate::archive::{self, Archive};
+use crate::density::Density;
+use crate::i18n::{self, money, tr};
+use crate::outbox::Outbox;
+use crate::schedule;
+use crate::store::{Header, SaveStatus};
+use crate::style;
+use crate::sync;
+use crate::tag::{self, Label};
+use crate::template::Templates;
 
 #[derive(Debug, Clone)]
 pub enum Message {
     NewSale,
+    QuickSale,
     SelectSale(usize),
+    OpenSettings,
+    OpenReports,
+    OpenGiftCards,
+    OpenCatalog,
+    OpenDrawer,
+    OpenShifts,
+    OpenRecurring,
+    OpenTrash,
+    OpenCustomers,
+    NewReturn,
+    Sync,
+    SelectTab(Tab),
+    FilterTag(Option<String>),
+    FilterLabel(Option<Label>),
+    UseTemplate(usize),
+    RenameTemplate(usize, String),
+    RemoveTemplate(usize),
+    Archive(archive::Message),
+    /// Fold or unfold the sales of a day.
+    ToggleDay(NaiveDate),
+    JumpInput(String),
+    JumpToDate,
+    Scrolled(Viewport),
+    /// List the next page of older sales.
+    LoadMore,
 }
 
-pub fn view(sales: &HashMap<usize, Sale>) -> Element<'_, Message> {
-    let main_content: Element<_> = if sales.is_empty() {
-        container(
-            button(
-                text("Create your first sale →")
-                    .shaping(text::Shaping::Advanced),
-            )
-            .on_press(Message::NewSale),
-        )
-        .center(Fill)
-        .into()
-    } else {
-        let mut sales_list = column![].spacing(10).width(Fill);
+/// How many more sales each page lists.
+pub const PAGE: usize = 100;
 
-        for (id, sale) in sales {
-            let total = sale.calculate_total();
-            sales_list = sales_list.push(
-                button(
-                    row![column![
-                        text(format!("{}", sale.name)).size(13),
-                        text(format!("Total: ${:.2}", total)).size(12).style(
-                            |theme: &iced::Theme| text::Style {
-                                color: Some(
-                                    theme.palette().text.scale_alpha(0.8)
-                                ),
-                            }
-                        )
-                    ]
-                    .width(Fill)
-                    .padding(10)]
-                    .width(Fill),
-                )
-                .style(button::secondary)
-                .on_press(Message::SelectSale(*id))
-                .width(Fill),
-            );
+/// The height of the header of a day.
+const DAY_HEIGHT: f32 = 32.0;
+
+/// How the days of the sales list are shown.
+#[derive(Debug, Clone)]
+pub struct Days {
+    /// Days whose sales are folded away.
+    pub collapsed: BTreeSet<NaiveDate>,
+    /// The date being typed to jump to.
+    pub jump: String,
+    /// How far down the list is scrolled.
+    pub offset: f32,
+    /// How many sales are listed, rounded up to whole days.
+    pub shown: usize,
+    /// The sales matching the filter by day, see [`group`].
+    pub grouped: Vec<Day>,
+}
+
+impl Default for Days {
+    fn default() -> Self {
+        Self {
+            collapsed: BTreeSet::new(),
+            jump: String::new(),
+            offset: 0.0,
+            shown: PAGE,
+            grouped: Vec::new(),
         }
+    }
+}
+
+pub fn scrollable_id() -> scrollable::Id {
+    scrollable::Id::new("sales")
+}
+
+/// A day and its sales.
+pub type Day = (NaiveDate, Vec<(usize, Header)>);
+
+/// The sales grouped by the day they were made, the latest day and sale
+/// first.
+pub fn by_day(sales: BTreeMap<usize, Header>) -> Vec<Day> {
+    let mut days: BTreeMap<NaiveDate, Vec<(usize, Header)>> = BTreeMap::new();
+    for (id, sale) in sales {
+        let day = sale.created_at.with_timezone(&Local).date_naive();
+        days.entry(day).or_default().push((id, sale));
+    }
+    days.into_iter()
+        .rev()
+        .map(|(day, mut sales)| {
+            sales.sort_by(|(a_id, a), (b_id, b)| {
+                (b.created_at, b_id).cmp(&(a.created_at, a_id))
+            });
+            (day, sales)
+        })
+        .collect()
+}
+
+/// Group the `sales` matching `filter` by day to be listed, as they or the
+/// filter changed.
+pub fn group(
+    days: &mut Days,
+    sales: BTreeMap<usize, Header>,
+    filter: &tag::Filter,
+) {
+    days.grouped = by_day(
+        sales
+            .into_iter()
+            .filter(|(_, sale)| filter.matches(&sale.tags, sale.label))
+            .collect(),
+    );
+}
+
+/// The latest of `days` holding at least `shown` sales, or all of them,
+/// and how many sales are left out.
+fn page(days: &[Day], shown: usize) -> (&[Day], usize) {
+    let mut count = 0;
+    let whole = days
+        .iter()
+        .position(|(_, sales)| {
+            count += sales.len();
+            count >= shown
+        })
+        .map_or(days.len(), |last| last + 1);
+    let left = days[whole..].iter().map(|(_, sales)| sales.len()).sum();
+    (&days[..whole], left)
+}
+
+/// Where the header of each day starts in the list.
+fn starts(
+    days: &[Day],
+    collapsed: &BTreeSet<NaiveDate>,
+    density: Density,
+) -> Vec<(NaiveDate, f32)> {
+    let spacing = density.list_spacing();
+    let row = density.list_row_height() + spacing;
+    let mut start = 0.0;
+    days.iter()
+        .map(|(day, sales)| {
+            let at = (*day, start);
+            start += DAY_HEIGHT + spacing;
+            if !collapsed.contains(day) {
+                start += row * sales.len() as f32;
+            }
+            at
+        })
+        .collect()
+}
+
+/// Scroll the sales to the day typed, or the latest one before it without
+/// sales, unfolding it.
+pub fn jump<T>(days: &mut Days, density: Density) -> Result<Task<T>, String> {
+    let typed = days.jump.trim();
+    let date = NaiveDate::parse_from_str(typed, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid date \"{typed}\", use YYYY-MM-DD"))?;
+    let sales = &days.grouped;
+    let Some(last) = sales.iter().position(|(day, _)| *day <= date) else {
+        return Err(format!("There are no sales on or before {typed}"));
+    };
+    let day = sales[last].0;
+
+    // List enough pages to reach the day
+    let through: usize = sales[..=last].iter().map(|(_, s)| s.len()).sum();
+    days.shown = days.shown.max(through);
+    days.collapsed.remove(&day);
+    let start = starts(&days.grouped, &days.collapsed, density)
+        .into_iter()
+        .find_map(|(at, start)| (at == day).then_some(start))
+        .unwrap_or_default();
+    days.jump.clear();
+    Ok(scrollable::scroll_to(
+        scrollable_id(),
+        AbsoluteOffset { x: 0.0, y: start },
+    ))
+}
+
+/// Which list is shown. The tabs only appear once there are scheduled
+/// orders, templates or archived sales.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Tab {
+    #[default]
+    Sales,
+    Upcoming,
+    Templates,
+    Archive,
+}
 
+#[allow(clippy::too_many_arguments)]
+pub fn view<'a>(
+    sales: BTreeMap<usize, Header>,
+    saves: &'a HashMap<usize, SaveStatus>,
+    templates: &'a Templates,
+    archive: &'a Archive,
+    tab: Tab,
+    filter: &tag::Filter,
+    loading: bool,
+    sync_status: &'a sync::Status,
+    outbox: &'a Outbox,
+    store_error: Option<&'a str>,
+    logo: Option<&'a image::Handle>,
+    density: Density,
+    days: &'a Days,
+    trashed: usize,
+) -> Element<'a, Message> {
+    let main_content: Element<_> = if loading {
+        container(text(tr("Loading sales…")).shaping(text::Shaping::Advanced))
+            .center(Fill)
+            .into()
+    } else if sales.is_empty() && templates.is_empty() && archive.is_empty() {
         column![
             row![
-                horizontal_space(),
-                button(text("New Sale").size(14))
-                    .style(button::success)
-                    .on_press(Message::NewSale),
+                settings_button(logo),
+                status(sync_status, outbox, store_error)
             ]
+            .spacing(10)
             .align_y(Center),
-            sales_list,
+            container(
+                button(
+                    text(tr("Create your first sale →"))
+                        .shaping(text::Shaping::Advanced),
+                )
+                .on_press(Message::NewSale),
+            )
+            .center(Fill)
         ]
+        .into()
+    } else {
+        let toolbar = row![
+            settings_button(logo),
+            button(text(tr("Reports")).size(14))
+                .style(button::secondary)
+                .on_press(Message::OpenReports),
+            button(text(tr("Gift cards")).size(14))
+                .style(button::secondary)
+                .on_press(Message::OpenGiftCards),
+            button(text(tr("Catalog")).size(14))
+                .style(button::secondary)
+                .on_press(Message::OpenCatalog),
+            button(text(tr("Cash drawer")).size(14))
+                .style(button::secondary)
+                .on_press(Message::OpenDrawer),
+            button(text(tr("Shifts")).size(14))
+                .style(button::secondary)
+                .on_press(Message::OpenShifts),
+            button(text(tr("Customers")).size(14))
+                .style(button::secondary)
+                .on_press(Message::OpenCustomers),
+            button(text(tr("Return")).size(14))
+                .style(button::secondary)
+                .on_press(Message::NewReturn),
+        ]
+        .push_maybe((trashed > 0).then(|| {
+            button(text(format!("{} ({trashed})", tr("Trash"))).size(14))
+                .style(button::secondary)
+                .on_press(Message::OpenTrash)
+        }))
+        .push(status(sync_status, outbox, store_error))
+        .push(horizontal_space())
+        .push(
+            button(text(tr("Quick sale")).size(14))
+                .style(button::secondary)
+                .on_press(Message::QuickSale),
+        )
+        .push(
+            button(text(tr("New Sale")).size(14))
+                .style(button::success)
+                .on_press(Message::NewSale),
+        )
+        .spacing(10)
+        .align_y(Center);
+
+        let upcoming = schedule::upcoming(sales.clone());
+        let has_upcoming = !upcoming.is_empty();
+        // Back to the sales once the last upcoming order is picked up
+        let tab = match tab {
+            Tab::Upcoming if !has_upcoming => Tab::Sales,
+            tab => tab,
+        };
+
+        let content = match tab {
+            Tab::Sales => {
+                let tags: BTreeSet<String> = sales
+                    .values()
+                    .flat_map(|sale| sale.tags.iter().cloned())
+                    .collect();
+                let has_labels =
+                    sales.values().any(|sale| sale.label.is_some());
+
+                let tools =
+                    if tags.is_empty() && !has_labels && filter.is_empty() {
+                        row![horizontal_space(), jump_to_date(days)]
+                    } else {
+                        row![filters(tags, filter), jump_to_date(days)]
+                    };
+                column![
+                    tools.spacing(10).align_y(Center),
+                    sales_list(saves, days, density)
+                ]
+                .spacing(10)
+                .height(Fill)
+                .into()
+            }
+            Tab::Upcoming => upcoming_list(upcoming, density),
+            Tab::Templates => template_list(templates),
+            Tab::Archive => archive::view(archive).map(Message::Archive),
+        };
+
+        if !has_upcoming && templates.is_empty() && archive.is_empty() {
+            column![toolbar, content]
+        } else {
+            column![
+                toolbar,
+                tabs(tab, has_upcoming, templates, archive),
+                content
+            ]
+        }
         .spacing(20)
         .width(Fill)
+        .height(Fill)
         .into()
     };
 
@@ -69,3 +352,516 @@ pub fn view(sales: &HashMap<usize, Sale>) -> Element<'_, Message> {
         .padding(20)
         .into()
 }
+
+fn tabs<'a>(
+    selected: Tab,
+    has_upcoming: bool,
+    templates: &Templates,
+    archive: &Archive,
+) -> Element<'a, Message> {
+    let tab = |label, tab| {
+        button(text(label).size(14))
+            .style(if tab == selected {
+                button::primary
+            } else {
+                button::text
+            })
+            .on_press(Message::SelectTab(tab))
+    };
+
+    let tabs = row![tab(tr("Sales"), Tab::Sales)].spacing(5);
+    let tabs = if has_upcoming {
+        tabs.push(tab(tr("Upcoming"), Tab::Upcoming))
+    } else {
+        tabs
+    };
+    let tabs = if templates.is_empty() {
+        tabs
+    } else {
+        tabs.push(tab(tr("Templates"), Tab::Templates))
+    };
+    if archive.is_empty() {
+        tabs
+    } else {
+        tabs.push(tab(tr("Archive"), Tab::Archive))
+    }
+    .into()
+}
+
+/// The tags and labels to narrow the sales down to.
+fn filters<'a>(
+    tags: BTreeSet<String>,
+    filter: &tag::Filter,
+) -> Element<'a, Message> {
+    let tag = |label: String, tag: Option<String>| {
+        let selected = filter.tag == tag;
+        button(text(label).size(12))
+            .padding([2, 8])
+            .style(if selected {
+                button::primary
+            } else {
+                button::secondary
+            })
+            .on_press(Message::FilterTag(tag))
+    };
+
+    let filters = tags.into_iter().fold(
+        row![
+            tag::swatches(filter.label, Message::FilterLabel),
+            tag(tr("All tags").to_string(), None),
+        ]
+        .spacing(5)
+        .align_y(Center),
+        |row, name| row.push(tag(name.clone(), Some(name))),
+    );
+    container(filters.wrap()).width(Fill).into()
+}
+
+fn jump_to_date(days: &Days) -> Element<'_, Message> {
+    row![
+        text_input("YYYY-MM-DD", &days.jump)
+            .style(style::input)
+            .on_input(Message::JumpInput)
+            .on_submit(Message::JumpToDate)
+            .size(12)
+            .width(110.0),
+        button(text(tr("Jump to date")).size(12))
+            .padding([2, 8])
+            .style(button::secondary)
+            .on_press_maybe(
+                (!days.jump.trim().is_empty()).then_some(Message::JumpToDate)
+            ),
+    ]
+    .spacing(5)
+    .align_y(Center)
+    .into()
+}
+
+/// The sales by day, with the header of the day scrolled into staying on
+/// top.
+fn sales_list<'a>(
+    saves: &HashMap<usize, SaveStatus>,
+    days: &'a Days,
+    density: Density,
+) -> Element<'a, Message> {
+    let (sales, left) = page(&days.grouped, days.shown);
+    let starts = starts(sales, &days.collapsed, density);
+    let sticky = starts
+        .iter()
+        .rev()
+        .find(|(_, start)| *start < days.offset)
+        .map(|(day, _)| *day);
+
+    let mut headers = BTreeMap::new();
+    let mut list = column![].spacing(density.list_spacing()).width(Fill);
+    for (day, sales) in sales {
+        let day = *day;
+        let collapsed = days.collapsed.contains(&day);
+        let total: f32 = sales.iter().map(|(_, sale)| sale.total).sum();
+        let header = (day, collapsed, sales.len(), total);
+        headers.insert(day, header);
+        list = list.push(day_header(header));
+        if !collapsed {
+            list = sale_rows(list, sales, saves, density);
+        }
+    }
+    if left > 0 {
+        list = list.push(
+            container(
+                button(
+                    text(format!(
+                        "{} ({left} {})",
+                        tr("Load more"),
+                        tr("older")
+                    ))
+                    .size(12),
+                )
+                .style(button::secondary)
+                .on_press(Message::LoadMore),
+            )
+            .center_x(Fill)
+            .padding(10),
+        );
+    }
+
+    let list = scrollable(list.padding([0, 10]))
+        .id(scrollable_id())
+        .on_scroll(Message::Scrolled)
+        .height(Fill);
+    match sticky.and_then(|day| headers.get(&day)) {
+        Some(header) => {
+            stack![list, container(day_header(*header)).padding([0, 10])].into()
+        }
+        None => list.into(),
+    }
+}
+
+/// The header of a day, with its count and total, folding it on press.
+fn day_header<'a>(
+    (day, collapsed, count, total): (NaiveDate, bool, usize, f32),
+) -> Element<'a, Message> {
+    button(
+        row![
+            text(if collapsed { "▸" } else { "▾" })
+                .shaping(text::Shaping::Advanced),
+            text(i18n::day(day)).size(14),
+            horizontal_space(),
+            text(format!(
+                "{count} {} • {}: {}",
+                tr("sale(s)"),
+                tr("Total"),
+                money(total)
+            ))
+            .size(12)
+            .shaping(text::Shaping::Advanced),
+        ]
+        .spacing(10)
+        .align_y(Center),
+    )
+    .style(button::primary)
+    .on_press(Message::ToggleDay(day))
+    .width(Fill)
+    .height(DAY_HEIGHT)
+    .into()
+}
+
+fn sale_rows<'a>(
+    mut sales_list: Column<'a, Message>,
+    sales: &'a [(usize, Header)],
+    saves: &HashMap<usize, SaveStatus>,
+    density: Density,
+) -> Column<'a, Message> {
+    let today = Local::now().date_naive();
+
+    for (id, sale) in sales {
+        let id = *id;
+        let badge: Element<_> = match saves.get(&id) {
+            Some(status @ SaveStatus::Failed) => {
+                text(status.to_string()).size(12).style(text::danger).into()
+            }
+            Some(status) => text(status.to_string())
+                .size(12)
+                .shaping(text::Shaping::Advanced)
+                .into(),
+            None => horizontal_space().width(0).into(),
+        };
+        let amounts = amounts(sale, today, density);
+        sales_list = sales_list.push(
+            button(
+                row![
+                    column![
+                        row![
+                            text(&sale.name).size(density.text_size(13.0)),
+                            tag::chips(&sale.tags, sale.label),
+                        ]
+                        .spacing(10)
+                        .align_y(Center),
+                        amounts,
+                    ]
+                    .width(Fill)
+                    .padding(density.list_padding()),
+                    badge,
+                ]
+                .align_y(Center)
+                .padding([0, 10])
+                .width(Fill),
+            )
+            .style(button::secondary)
+            .on_press(Message::SelectSale(id))
+            .width(Fill)
+            .height(density.list_row_height()),
+        );
+    }
+
+    sales_list
+}
+
+/// The total of a sale, and for an invoice what is left and when it is
+/// due, in red once overdue.
+fn amounts<'a>(
+    sale: &Header,
+    today: NaiveDate,
+    density: Density,
+) -> Element<'a, Message> {
+    let total = format!("{}: {}", tr("Total"), money(sale.total));
+    match &sale.invoice {
+        Some(invoice) => {
+            let line = text(format!(
+                "{total} • {}: {} • {} {}",
+                tr("Balance"),
+                money(invoice.balance),
+                tr("due"),
+                invoice.due
+            ))
+            .size(density.text_size(12.0));
+            if invoice.is_overdue(today) {
+                line.style(text::danger).into()
+            } else {
+                line.style(muted).into()
+            }
+        }
+        None => text(total)
+            .size(density.text_size(12.0))
+            .style(muted)
+            .into(),
+    }
+}
+
+/// Scheduled orders not picked up yet, the soonest first, with those
+/// overdue in red.
+fn upcoming_list<'a>(
+    upcoming: Vec<(usize, Header)>,
+    density: Density,
+) -> Element<'a, Message> {
+    let now = Utc::now();
+    upcoming
+        .into_iter()
+        .fold(
+            column![].spacing(density.list_spacing()).width(Fill),
+            |col, (id, sale)| {
+                let due = sale.due_at.unwrap_or(now);
+                let due_label = text(schedule::label(due))
+                    .size(density.text_size(13.0))
+                    .width(140.0);
+                col.push(
+                    button(
+                        row![
+                            if due < now {
+                                due_label.style(text::danger)
+                            } else {
+                                due_label
+                            },
+                            column![
+                                text(sale.name).size(density.text_size(13.0)),
+                                text(format!(
+                                    "{} • {}: {}",
+                                    sale.order_type,
+                                    tr("Total"),
+                                    money(sale.total)
+                                ))
+                                .size(density.text_size(12.0))
+                                .style(muted)
+                            ]
+                            .width(Fill)
+                            .padding(density.list_padding()),
+                        ]
+                        .spacing(10)
+                        .align_y(Center)
+                        .padding([0, 10])
+                        .width(Fill),
+                    )
+                    .style(button::secondary)
+                    .on_press(Message::SelectSale(id))
+                    .width(Fill),
+                )
+            },
+        )
+        .into()
+}
+
+fn template_list(templates: &Templates) -> Element<'_, Message> {
+    let recurring = row![
+        horizontal_space(),
+        button(text(tr("Recurring sales")).size(14))
+            .style(button::secondary)
+            .on_press(Message::OpenRecurring),
+    ];
+
+    templates
+        .iter()
+        .fold(
+            column![recurring].spacing(10).width(Fill),
+            |col, template| {
+                col.push(
+                    container(
+                        row![
+                            text_input(template.display_name(), &template.name)
+                                .style(style::input)
+                                .on_input(|name| {
+                                    Message::RenameTemplate(template.id, name)
+                                })
+                                .width(Fill)
+                                .padding(5),
+                            text(format!(
+                                "{} {} • {}",
+                                template.sale.items.len(),
+                                tr("item(s)"),
+                                money(template.sale.calculate_total())
+                            ))
+                            .size(12)
+                            .style(muted)
+                            .shaping(text::Shaping::Advanced),
+                            button(text(tr("Use")).size(14))
+                                .style(button::success)
+                                .on_press(Message::UseTemplate(template.id)),
+                            button(text("×").center())
+                                .width(25.0)
+                                .on_press(Message::RemoveTemplate(template.id))
+                                .style(button::danger),
+                        ]
+                        .spacing(10)
+                        .align_y(Center),
+                    )
+                    .padding(10)
+                    .style(container::rounded_box),
+                )
+            },
+        )
+        .into()
+}
+
+fn muted(theme: &iced::Theme) -> text::Style {
+    text::Style {
+        color: Some(theme.palette().text.scale_alpha(0.8)),
+    }
+}
+
+/// The settings button, after the logo if one is set up.
+fn settings_button(logo: Option<&image::Handle>) -> Element<'_, Message> {
+    let settings = button(text(tr("Settings")).size(14))
+        .style(button::secondary)
+        .on_press(Message::OpenSettings);
+
+    match logo {
+        Some(logo) => row![image(logo.clone()).height(32), settings]
+            .spacing(10)
+            .align_y(Center)
+            .into(),
+        None => settings.into(),
+    }
+}
+
+fn status<'a>(
+    sync_status: &'a sync::Status,
+    outbox: &'a Outbox,
+    store_error: Option<&'a str>,
+) -> Element<'a, Message> {
+    let sync: Element<_> = match sync_status {
+        sync::Status::Off => horizontal_space().width(0).into(),
+        sync::Status::Failed(error) => tooltip(
+            button(text(sync_status.to_string()).size(12))
+                .style(button::danger)
+                .on_press(Message::Sync),
+            container(text(error).size(12))
+                .padding(5)
+                .style(container::rounded_box),
+            tooltip::Position::Bottom,
+        )
+        .into(),
+        _ => button(
+            text(sync_status.to_string())
+                .size(12)
+                .shaping(text::Shaping::Advanced),
+        )
+        .style(button::text)
+        .on_press(Message::Sync)
+        .into(),
+    };
+
+    let sync: Element<_> = if outbox.is_empty() {
+        sync
+    } else {
+        row![
+            sync,
+            button(
+                text(format!(
+                    "{} {}",
+                    outbox.entries().len(),
+                    tr("waiting to send")
+                ))
+                .size(12),
+            )
+            .style(button::text)
+            .on_press(Message::OpenSettings),
+        ]
+        .align_y(Center)
+        .into()
+    };
+
+    match store_error {
+        Some(error) => row![sync, text(error).size(12).style(text::danger)]
+            .spacing(10)
+            .align_y(Center)
+            .into(),
+        None => sync,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Datelike, Local, TimeZone, Utc};
+
+    use super::*;
+    use crate::sale::{Sale, SaleItem};
+
+    fn sale(day: u32, hour: u32) -> Header {
+        Header::new(&Sale {
+            items: vec![SaleItem::new("Coffee", 3.0, 1)],
+            created_at: Local
+                .with_ymd_and_hms(2024, 3, day, hour, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc),
+            ..Sale::default()
+        })
+    }
+
+    #[test]
+    fn sales_are_grouped_by_day_latest_first() {
+        let sales = BTreeMap::from([
+            (1, sale(14, 9)),
+            (2, sale(15, 12)),
+            (3, sale(14, 17)),
+            (4, sale(15, 8)),
+            (5, sale(12, 10)),
+        ]);
+        let days = by_day(sales);
+        let ids: Vec<(u32, Vec<usize>)> = days
+            .iter()
+            .map(|(day, sales)| {
+                (day.day(), sales.iter().map(|(id, _)| *id).collect())
+            })
+            .collect();
+        assert_eq!(ids, [(15, vec![2, 4]), (14, vec![3, 1]), (12, vec![5])]);
+
+        let density = Density::Compact;
+        let header = DAY_HEIGHT + density.list_spacing();
+        let row = density.list_row_height() + density.list_spacing();
+        let collapsed = BTreeSet::from([days[1].0]);
+        let starts: Vec<f32> = starts(&days, &collapsed, density)
+            .into_iter()
+            .map(|(_, start)| start)
+            .collect();
+        assert_eq!(starts, [0.0, header + 2.0 * row, 2.0 * header + 2.0 * row]);
+    }
+
+    #[test]
+    fn pages_hold_whole_days() {
+        let sales = BTreeMap::from([
+            (1, sale(14, 9)),
+            (2, sale(15, 12)),
+            (3, sale(14, 17)),
+            (4, sale(15, 8)),
+            (5, sale(12, 10)),
+        ]);
+        let grouped = by_day(sales.clone());
+        let days = |shown| {
+            let (days, left) = page(&grouped, shown);
+            (days.len(), left)
+        };
+        assert_eq!(days(1), (1, 3));
+        assert_eq!(days(2), (1, 3));
+        assert_eq!(days(3), (2, 1));
+        assert_eq!(days(PAGE), (3, 0));
+
+        let mut list = Days {
+            jump: "2024-03-13".to_string(),
+            shown: 1,
+            ..Days::default()
+        };
+        group(&mut list, sales, &tag::Filter::default());
+        let _ = jump::<()>(&mut list, Density::Compact).unwrap();
+        assert_eq!(list.shown, 5);
+        list.jump = "2024-03-01".to_string();
+        assert!(jump::<()>(&mut list, Density::Compact).is_err());
+    }
+}
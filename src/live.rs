@@ -0,0 +1,173 @@
+//! Live feed of sale events over WebSocket
+//!
+//! When enabled, the app listens for WebSocket connections on the configured
+//! port and sends every connected client one JSON text frame per sale event,
+//! so a kitchen display or customer-facing screen can follow sales as they
+//! happen. Clients only listen; anything they send is ignored.
+//!
+//! # Event schema
+//!
+//! Every event is an object tagged with the schema [`VERSION`]:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "event": "sale_created",
+//!   "sale_id": 3,
+//!   "at": "2024-11-02T18:04:11.532Z",
+//!   "sale": { "name": "Table 4", "items": [...], ... }
+//! }
+//! ```
+//!
+//! - `event` is one of `sale_created`, `sale_updated` or `sale_paid`
+//!   (see [`Kind`]).
+//! - `sale_id` is the id the sale is listed under in the app.
+//! - `at` is when the event happened, in RFC 3339 UTC.
+//! - `sale` is the whole sale after the change, in the same format as the
+//!   sales of an export.
+//!
+//! Fields may be added to an event without bumping the version, so clients
+//! should ignore fields they don't know. Removing or changing the meaning of
+//! a field bumps the version.
+use chrono::{DateTime, Utc};
+use iced::futures::SinkExt;
+use iced::Subscription;
+use serde::{Deserialize, Serialize};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+use tungstenite::WebSocket;
+
+use crate::sale::Sale;
+
+/// The current version of the event schema.
+pub const VERSION: u32 = 1;
+
+/// How long the server waits for an event before checking for new clients.
+const POLL: Duration = Duration::from_millis(100);
+
+/// How long a client may stall a handshake or write before it is dropped.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Feed {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for Feed {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 9001,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Kind {
+    /// A new sale was saved.
+    #[serde(rename = "sale_created")]
+    Created,
+    /// A stored sale was edited, or its order status changed.
+    #[serde(rename = "sale_updated")]
+    Updated,
+    /// A sale was settled, which happens when it is picked up.
+    #[serde(rename = "sale_paid")]
+    Paid,
+}
+
+#[derive(Debug, Serialize)]
+struct SaleEvent<'a> {
+    version: u32,
+    event: Kind,
+    sale_id: usize,
+    at: DateTime<Utc>,
+    sale: &'a Sale,
+}
+
+/// A handle to a running feed. The server shuts down once every handle to it
+/// has been dropped.
+#[derive(Debug, Clone)]
+pub struct Bus(mpsc::Sender<String>);
+
+impl Bus {
+    /// Broadcast an event about `sale` to every connected client.
+    pub fn publish(&self, event: Kind, sale_id: usize, sale: &Sale) {
+        let event = SaleEvent {
+            version: VERSION,
+            event,
+            sale_id,
+            at: Utc::now(),
+            sale,
+        };
+        if let Ok(json) = serde_json::to_string(&event) {
+            let _ = self.0.send(json);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// The server is listening and events can be published on the bus.
+    Ready(Bus),
+    Failed(String),
+}
+
+/// Run a feed server on `port` for as long as the subscription is active.
+pub fn feed(port: u16) -> Subscription<Event> {
+    Subscription::run_with_id(
+        port,
+        iced::stream::channel(1, move |mut output| async move {
+            let event = match TcpListener::bind(("0.0.0.0", port)).and_then(
+                |listener| {
+                    listener.set_nonblocking(true)?;
+                    Ok(listener)
+                },
+            ) {
+                Ok(listener) => {
+                    tracing::info!(port, "live feed listening");
+                    let (sender, receiver) = mpsc::channel();
+                    std::thread::spawn(move || serve(listener, receiver));
+                    Event::Ready(Bus(sender))
+                }
+                Err(e) => Event::Failed(format!(
+                    "Could not listen on port {port}: {e}"
+                )),
+            };
+            let _ = output.send(event).await;
+        }),
+    )
+}
+
+/// Accept clients on `listener` and broadcast every event received to them
+/// until the bus is dropped.
+fn serve(listener: TcpListener, events: mpsc::Receiver<String>) {
+    let mut clients: Vec<WebSocket<TcpStream>> = Vec::new();
+
+    loop {
+        match events.recv_timeout(POLL) {
+            Ok(json) => clients.retain_mut(|client| {
+                client
+                    .send(tungstenite::Message::text(json.clone()))
+                    .is_ok()
+            }),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        while let Ok((stream, _)) = listener.accept() {
+            if let Some(client) = handshake(stream) {
+                clients.push(client);
+            }
+        }
+    }
+}
+
+fn handshake(stream: TcpStream) -> Option<WebSocket<TcpStream>> {
+    stream.set_nonblocking(false).ok()?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    tungstenite::accept(stream).ok()
+}
@@ -0,0 +1,85 @@
+//! Logging of what the app does, to help debug user reports
+//!
+//! Messages, operations, storage and calls to external services are logged
+//! with `tracing` to a file in the `logs` folder next to the config, a new
+//! one each day, keeping the last [`KEEP_FILES`]. The most recent lines are
+//! also kept in memory for the diagnostics screen. `RUST_LOG` overrides what
+//! is logged, e.g. `RUST_LOG=receipts=debug` to log every message.
+use std::collections::VecDeque;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::writer::MakeWriterExt;
+use tracing_subscriber::EnvFilter;
+
+use crate::config::data_dir;
+
+/// How many days of log files to keep.
+const KEEP_FILES: usize = 7;
+/// How many lines the diagnostics screen can show.
+const KEEP_RECENT: usize = 200;
+const DEFAULT_FILTER: &str = "receipts=info,receipts_core=info";
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+pub fn dir() -> PathBuf {
+    data_dir().join("logs")
+}
+
+/// Start logging. Lines are written in the background until the returned
+/// guard is dropped, so it should live as long as the app.
+pub fn init() -> Option<WorkerGuard> {
+    std::fs::create_dir_all(dir()).ok()?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("receipts")
+        .filename_suffix("log")
+        .max_log_files(KEEP_FILES)
+        .build(dir())
+        .ok()?;
+    let (file, guard) = tracing_appender::non_blocking(appender);
+
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_ansi(false)
+        .with_writer(file.and(|| Recent))
+        .try_init()
+        .ok()?;
+
+    Some(guard)
+}
+
+/// The last lines logged, oldest first.
+pub fn recent() -> Vec<String> {
+    RECENT
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .cloned()
+        .collect()
+}
+
+/// Keeps the lines written to it in [`RECENT`].
+struct Recent;
+
+impl io::Write for Recent {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut recent = RECENT.lock().unwrap_or_else(PoisonError::into_inner);
+        for line in String::from_utf8_lossy(buf).lines() {
+            if recent.len() == KEEP_RECENT {
+                recent.pop_front();
+            }
+            recent.push_back(line.to_string());
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+//! A logo image shown in the app and printed on receipts
+//!
+//! The image picked in the settings is copied to `logo.png` next to the
+//! config, so that it doesn't matter if the original is moved later. For
+//! receipts it is scaled to the paper width and dithered to black and white,
+//! like a thermal printer prints it: as text made of block characters for
+//! plain-text receipts, and as a PNG at the printer's resolution for the
+//! print page.
+use base64::Engine;
+use iced::widget::image::Handle;
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GrayImage, ImageFormat, Luma};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use crate::config::data_dir;
+use crate::data::Error;
+use crate::ticket::WIDTH;
+
+/// How many dots a 58mm thermal printer prints per line, at 8 dots per mm.
+const PRINT_DOTS: u32 = 384;
+const DOTS_PER_MM: u32 = 8;
+/// The tallest the logo is printed, in dots.
+const PRINT_HEIGHT: u32 = 192;
+/// The tallest the logo is on plain-text receipts, in lines.
+const TEXT_LINES: u32 = 8;
+
+fn path() -> PathBuf {
+    data_dir().join("logo.png")
+}
+
+#[derive(Debug, Clone)]
+pub struct Logo {
+    /// The logo in color, as shown in the app.
+    pub handle: Handle,
+    /// The logo as block characters, at most the width of a ticket.
+    pub text: String,
+    /// The dithered logo as a PNG, and its width in mm when printed.
+    pub png: Vec<u8>,
+    pub print_width_mm: u32,
+}
+
+impl Logo {
+    fn new(image: DynamicImage) -> Result<Self, Error> {
+        let rgba = image.to_rgba8();
+        let handle =
+            Handle::from_rgba(rgba.width(), rgba.height(), rgba.into_raw());
+
+        let printed = dither(&image, PRINT_DOTS, PRINT_HEIGHT);
+        let mut png = Vec::new();
+        printed
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .map_err(|e| Error::Format(e.to_string()))?;
+
+        Ok(Self {
+            handle,
+            text: text(&dither(&image, WIDTH as u32, TEXT_LINES * 2)),
+            png,
+            print_width_mm: printed.width().div_ceil(DOTS_PER_MM),
+        })
+    }
+
+    /// The logo saved in the data directory, if there is one.
+    pub fn load() -> Option<Self> {
+        let image = image::open(path()).ok()?;
+        Self::new(image).ok()
+    }
+
+    /// The printed logo as a `data:` URL, for the print page.
+    pub fn data_url(&self) -> String {
+        format!(
+            "data:image/png;base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(&self.png)
+        )
+    }
+}
+
+/// Read the image at `source` and save it as the logo.
+pub async fn import(source: PathBuf) -> Result<Logo, Error> {
+    let image = read(&source)?;
+    std::fs::create_dir_all(data_dir())?;
+    image
+        .save_with_format(path(), ImageFormat::Png)
+        .map_err(|e| Error::Io(e.to_string()))?;
+    Logo::new(image)
+}
+
+fn read(source: &Path) -> Result<DynamicImage, Error> {
+    let bytes = std::fs::read(source)?;
+    image::load_from_memory(&bytes).map_err(|e| {
+        Error::Format(format!("{} is not a PNG image: {e}", source.display()))
+    })
+}
+
+/// Stop using a logo.
+pub fn remove() -> Result<(), Error> {
+    match std::fs::remove_file(path()) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// `image` scaled down to fit `width` by `height` dots, in black and white
+/// with Floyd-Steinberg dithering. Transparent parts are left white.
+fn dither(image: &DynamicImage, width: u32, height: u32) -> GrayImage {
+    let rgba = image.to_rgba8();
+    let (from_width, from_height) = rgba.dimensions();
+    let scale = (width as f32 / from_width as f32)
+        .min(height as f32 / from_height as f32)
+        .min(1.0);
+    let width = ((from_width as f32 * scale).round() as u32).max(1);
+    let height = ((from_height as f32 * scale).round() as u32).max(1);
+    let rgba = imageops::resize(&rgba, width, height, FilterType::Triangle);
+
+    let mut levels: Vec<f32> = rgba
+        .pixels()
+        .map(|pixel| {
+            let [r, g, b, a] = pixel.0.map(f32::from);
+            let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+            // Over white paper
+            luma * a / 255.0 + (255.0 - a)
+        })
+        .collect();
+
+    let (w, h) = (width as usize, height as usize);
+    let mut dithered = GrayImage::new(width, height);
+    for y in 0..h {
+        for x in 0..w {
+            let old = levels[y * w + x];
+            let new = if old < 128.0 { 0.0 } else { 255.0 };
+            dithered.put_pixel(x as u32, y as u32, Luma([new as u8]));
+
+            let error = old - new;
+            let mut spread = |dx: isize, dy: usize, share: f32| {
+                let x = x as isize + dx;
+                if x >= 0 && (x as usize) < w && y + dy < h {
+                    levels[(y + dy) * w + x as usize] += error * share;
+                }
+            };
+            spread(1, 0, 7.0 / 16.0);
+            spread(-1, 1, 3.0 / 16.0);
+            spread(0, 1, 5.0 / 16.0);
+            spread(1, 1, 1.0 / 16.0);
+        }
+    }
+    dithered
+}
+
+/// A black and white image as text, two dots per character: one on top of
+/// the other.
+fn text(image: &GrayImage) -> String {
+    let black =
+        |x: u32, y: u32| y < image.height() && image.get_pixel(x, y).0[0] == 0;
+
+    (0..image.height())
+        .step_by(2)
+        .map(|y| {
+            (0..image.width())
+                .map(|x| match (black(x, y), black(x, y + 1)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
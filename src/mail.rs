@@ -0,0 +1,240 @@
+//! Sending email through an SMTP server
+//!
+//! Just enough of SMTP to hand a plain-text message to the server set up in
+//! the settings: over TLS (usually port 465), upgraded with STARTTLS (port
+//! 587) or, for a relay on the local network, unencrypted. The password is
+//! stored in plain text in the config file.
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::outbox::Failure;
+
+/// How long the server may take to answer.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Security {
+    #[default]
+    Tls,
+    StartTls,
+    None,
+}
+
+impl Security {
+    pub const ALL: [Security; 3] =
+        [Security::Tls, Security::StartTls, Security::None];
+}
+
+impl fmt::Display for Security {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Security::Tls => "TLS",
+                Security::StartTls => "STARTTLS",
+                Security::None => "None",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Smtp {
+    pub host: String,
+    pub port: u16,
+    pub security: Security,
+    /// Empty if the server doesn't ask to log in.
+    pub username: String,
+    pub password: String,
+    /// The address mail is sent from.
+    pub from: String,
+}
+
+impl Default for Smtp {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: 465,
+            security: Security::Tls,
+            username: String::new(),
+            password: String::new(),
+            from: String::new(),
+        }
+    }
+}
+
+impl Smtp {
+    pub fn is_enabled(&self) -> bool {
+        !self.host.is_empty() && !self.from.is_empty()
+    }
+}
+
+/// A plain-text message.
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Send `mail` through the server of `smtp`.
+pub async fn send(smtp: Smtp, mail: Mail) -> Result<(), Failure> {
+    if !smtp.is_enabled() {
+        return Err(Failure::Other("No mail server is set up".to_string()));
+    }
+    tracing::info!(host = smtp.host, to = mail.to, "sending mail");
+
+    let mut tcp = connect(&smtp)?;
+    match smtp.security {
+        Security::Tls => deliver(&mut tls(&smtp, tcp)?, &smtp, &mail, true),
+        Security::StartTls => {
+            reply(&mut tcp, 220)?;
+            command(&mut tcp, "EHLO localhost", 250)?;
+            command(&mut tcp, "STARTTLS", 220)?;
+            deliver(&mut tls(&smtp, tcp)?, &smtp, &mail, false)
+        }
+        Security::None => deliver(&mut tcp, &smtp, &mail, true),
+    }
+}
+
+fn connect(smtp: &Smtp) -> Result<TcpStream, Failure> {
+    let offline = |e: std::io::Error| {
+        Failure::Offline(format!("Could not reach {}: {e}", smtp.host))
+    };
+    let address = (smtp.host.as_str(), smtp.port)
+        .to_socket_addrs()
+        .map_err(offline)?
+        .next()
+        .ok_or_else(|| Failure::Other(format!("Unknown host {}", smtp.host)))?;
+    let tcp = TcpStream::connect_timeout(&address, TIMEOUT).map_err(offline)?;
+    tcp.set_read_timeout(Some(TIMEOUT)).map_err(offline)?;
+    tcp.set_write_timeout(Some(TIMEOUT)).map_err(offline)?;
+    Ok(tcp)
+}
+
+fn tls(
+    smtp: &Smtp,
+    tcp: TcpStream,
+) -> Result<rustls::StreamOwned<rustls::ClientConnection, TcpStream>, Failure> {
+    let roots = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.into(),
+    };
+    let config = rustls::ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let name = rustls::pki_types::ServerName::try_from(smtp.host.clone())
+        .map_err(|e| Failure::Other(e.to_string()))?;
+    let connection = rustls::ClientConnection::new(Arc::new(config), name)
+        .map_err(|e| Failure::Other(e.to_string()))?;
+    Ok(rustls::StreamOwned::new(connection, tcp))
+}
+
+/// Log in if there is a username and hand over `mail`, after the server's
+/// greeting if `greeted` is still to come.
+fn deliver(
+    stream: &mut impl ReadWrite,
+    smtp: &Smtp,
+    mail: &Mail,
+    greeted: bool,
+) -> Result<(), Failure> {
+    use base64::Engine;
+
+    if greeted {
+        reply(stream, 220)?;
+    }
+    command(stream, "EHLO localhost", 250)?;
+    if !smtp.username.is_empty() {
+        let plain = base64::engine::general_purpose::STANDARD
+            .encode(format!("\0{}\0{}", smtp.username, smtp.password));
+        command(stream, &format!("AUTH PLAIN {plain}"), 235)?;
+    }
+    command(stream, &format!("MAIL FROM:<{}>", smtp.from), 250)?;
+    command(stream, &format!("RCPT TO:<{}>", mail.to), 250)?;
+    command(stream, "DATA", 354)?;
+    command(stream, &message(smtp, mail), 250)?;
+    let _ = command(stream, "QUIT", 221);
+    Ok(())
+}
+
+trait ReadWrite: Read + Write {}
+
+impl<T: Read + Write> ReadWrite for T {}
+
+/// `mail` with its headers, ending with the line of a single dot which ends
+/// the data.
+fn message(smtp: &Smtp, mail: &Mail) -> String {
+    let mut message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\n\
+         MIME-Version: 1.0\r\n\
+         Content-Type: text/plain; charset=utf-8\r\n\
+         Content-Transfer-Encoding: 8bit\r\n\r\n",
+        smtp.from,
+        mail.to,
+        mail.subject,
+        Local::now().to_rfc2822(),
+    );
+    for line in mail.body.lines() {
+        // A line starting with a dot would otherwise end the data early
+        if line.starts_with('.') {
+            message.push('.');
+        }
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+    message.push('.');
+    message
+}
+
+/// Send `line` and wait for the reply, which must have the code `expected`.
+fn command(
+    stream: &mut impl ReadWrite,
+    line: &str,
+    expected: u16,
+) -> Result<(), Failure> {
+    stream
+        .write_all(format!("{line}\r\n").as_bytes())
+        .and_then(|()| stream.flush())
+        .map_err(|e| Failure::Offline(e.to_string()))?;
+    reply(stream, expected)
+}
+
+/// Read a reply, which may span several lines, and check its code.
+fn reply(stream: &mut impl Read, expected: u16) -> Result<(), Failure> {
+    loop {
+        let mut line = Vec::new();
+        let mut byte = [0];
+        while !line.ends_with(b"\r\n") {
+            match stream.read(&mut byte) {
+                Ok(0) => {
+                    return Err(Failure::Offline(
+                        "The mail server closed the connection".to_string(),
+                    ))
+                }
+                Ok(_) => line.push(byte[0]),
+                Err(e) => return Err(Failure::Offline(e.to_string())),
+            }
+        }
+        let line = String::from_utf8_lossy(&line);
+        let code = line.get(..3).and_then(|code| code.parse::<u16>().ok());
+        // "250-" continues the reply, "250 " ends it
+        if line.as_bytes().get(3) == Some(&b'-') {
+            continue;
+        }
+        return match code {
+            Some(code) if code == expected => Ok(()),
+            _ => Err(Failure::Other(format!(
+                "The mail server said: {}",
+                line.trim_end()
+            ))),
+        };
+    }
+}
@@ -1,67 +1,355 @@
+use chrono::{DateTime, Local, Utc};
 use iced::event;
-use iced::keyboard::key::Named;
-use iced::keyboard::{self, Key, Modifiers};
-use iced::widget::focus_next;
-use iced::{Element, Size, Subscription, Task};
-use std::collections::HashMap;
+use iced::keyboard;
+use iced::widget::{horizontal_space, image, stack, text_input, Space};
+use iced::{window, Element, Fill, Size, Subscription, Task};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::future::Future;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
 
 mod action;
+mod archive;
+mod audit;
+mod board;
+mod catalog;
+mod cli;
+mod config;
+mod currency;
+mod customer;
+mod density;
+mod diagnostics;
+mod dialog;
+mod drawer;
+mod fiscal;
+mod gift_card;
+mod hook;
+mod keypad;
+mod layout;
 mod list;
+mod live;
+mod logging;
+mod logo;
+mod mail;
+mod outbox;
+mod pin;
+mod print;
+mod prompt;
+mod qr;
+mod quick_buttons;
+mod receipt;
+mod recurring;
+mod report;
+mod returns;
 mod sale;
-mod tax;
+mod scale;
+mod schedule;
+mod secret;
+mod settings;
+mod shift;
+mod shortcut;
+mod style;
+mod summary;
+mod sync;
+mod tag;
+mod template;
+mod terminal;
+mod ticket;
+mod toast;
+mod trash;
+
+use receipts_core::{
+    accounting, allergen, backup, custom, data, delivery, denomination, i18n,
+    invoice, query, service, store, tax, tips, vat,
+};
 
 pub use action::Action;
 use sale::Sale;
+use shortcut::Hotkey;
 
 fn main() -> iced::Result {
-    iced::application(App::title, App::update, App::view)
-        .window_size(Size::new(800.0, 600.0))
+    let log = logging::init();
+
+    match cli::parse(std::env::args().skip(1)) {
+        Ok(Some(command)) => {
+            let code = cli::run(command);
+            drop(log);
+            std::process::exit(code);
+        }
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("{e}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    }
+
+    iced::daemon(App::title, App::update, App::view)
         .theme(App::theme)
+        .scale_factor(App::scale_factor)
         .antialiasing(true)
-        .centered()
         .subscription(App::subscription)
         .run_with(App::new)
 }
 
-#[derive(Debug)]
-enum Screen {
+/// A screen of the main window, which screens can navigate to with
+/// [`Action::navigate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Screen {
     List,
     Sale(sale::Mode, Option<usize>),
+    Settings,
+    Report,
+    GiftCards,
+    Catalog,
+    Drawer,
+    Shifts,
+    Recurring,
+    Customers,
+    /// The purchase history of a customer.
+    Customer(usize),
+    /// A sale typed as shorthand from the keyboard.
+    QuickSale,
+    Return,
+    Layout,
+    /// Setting up the quick buttons of the edit screen.
+    QuickButtons,
+    Diagnostics,
+    /// Deleted sales, to restore or purge.
+    Trash,
+    /// A draft whose sale was changed elsewhere, to keep a side or merge.
+    Conflict,
 }
 
 #[derive(Debug)]
 enum Message {
     List(list::Message),
     Sale(Option<usize>, sale::Message),
+    Settings(settings::Message),
+    Report(report::Message),
+    GiftCards(gift_card::lookup::Message),
+    Catalog(catalog::manage::Message),
+    Drawer(drawer::manage::Message),
+    Shifts(shift::Message),
+    Recurring(recurring::Message),
+    Customers(customer::Message),
+    QuickSale(sale::quick::Message),
+    Return(returns::Message),
+    Layout(layout::Message),
+    QuickButtons(quick_buttons::Message),
+    Diagnostics(diagnostics::Message),
+    Trash(trash::Message),
+    Conflict(sale::conflict::Message),
+    Board(board::Message),
+    /// A key chord, which may be bound to a hotkey.
+    KeyPressed(shortcut::Chord, event::Status),
     Hotkey(Hotkey),
+    WindowClosed(window::Id),
+    Synced(Result<sync::Outcome, outbox::Failure>),
+    Live(live::Event),
+    Loaded(Result<store::Index, data::Error>),
+    Fetched(usize, Result<Box<Sale>, data::Error>),
+    Saved(u64, Result<(), data::Error>),
+    /// The stored copy of sale `id`, loaded as the save of it was refused
+    /// for being older.
+    StoredFetched(usize, Result<Box<Sale>, data::Error>),
+    StoreConverted(u64, Result<(), data::Error>),
+    BackedUp(Result<backup::Snapshot, data::Error>),
+    Restored(backup::Snapshot, Result<data::Export, data::Error>),
+    Exported(usize, Result<String, data::Error>),
+    /// The print page of sale `id` was opened, under a progress toast.
+    Printed(usize, usize, Result<PathBuf, data::Error>),
+    Kicked(Result<(), data::Error>),
+    LogoLoaded(Result<logo::Logo, data::Error>),
+    CardStarted(usize, Result<terminal::Pending, String>),
+    /// The weight of item `id` of a sale was read off the scale.
+    ScaleRead(Option<usize>, usize, Result<scale::Reading, String>),
+    CardPolled(usize, Result<terminal::Poll, String>),
+    CardCancelled(outbox::Job, Result<(), outbox::Failure>),
+    OutboxTick,
+    SummaryTick,
+    ScheduleTick,
+    RecurringTick,
+    RatesTick,
+    SummarySent(outbox::Job, Result<(), outbox::Failure>),
+    ReceiptSent(outbox::Job, Result<(), outbox::Failure>),
+    /// The fiscal module signed the receipt of sale `id`, once paid.
+    Fiscalized(usize, Result<Option<fiscal::Signature>, String>),
+    /// Exchange rates fetched, by hand from the settings or not.
+    RatesFetched(bool, Result<currency::Cache, String>),
+    Archived(Result<(BTreeSet<usize>, archive::Index), data::Error>),
+    /// A file beside the store was saved, e.g. "the catalog".
+    Persisted(&'static str, Result<(), data::Error>),
+    TrashSaved(TrashChange, Result<(), data::Error>),
+    ImportRead(Result<data::Export, data::Error>),
+    SaleImportRead(Result<data::Export, data::Error>),
+    DismissToast(usize),
+    CloseShortcuts,
+}
+
+/// A change to the trash, settled once the trash is saved or undone if it
+/// can't be.
+#[derive(Debug, Clone)]
+enum TrashChange {
+    /// Sale `id` was moved into it.
+    Deleted(usize),
+    /// Sale `id` was taken out of it, and restored as `restored`.
+    Restored { id: usize, restored: usize },
+    /// These sales were deleted from it for good.
+    Purged(Vec<(usize, trash::Trashed)>),
 }
 
 #[derive(Debug)]
 enum Instruction {
     Sale(Option<usize>, sale::Instruction),
+    Settings(settings::Instruction),
+    Report(report::Instruction),
+    GiftCards(gift_card::lookup::Instruction),
+    Catalog(catalog::manage::Instruction),
+    Drawer(drawer::manage::Instruction),
+    Shifts(shift::Instruction),
+    Trash(trash::Instruction),
+    Conflict(sale::conflict::Instruction),
+    Recurring(recurring::Instruction),
+    Customers(customer::Instruction),
+    QuickSale(sale::quick::Instruction),
+    Return(returns::Instruction),
+    Layout(layout::Instruction),
+    QuickButtons(quick_buttons::Instruction),
+    Diagnostics(diagnostics::Instruction),
 }
 
 struct App {
     screen: Screen,
+    /// Sales loaded in full.
     sales: HashMap<usize, sale::Sale>,
+    /// Headers of the sales which are still only in the store.
+    headers: HashMap<usize, store::Header>,
+    /// Sales picked up on the board while being loaded.
+    pickups: HashSet<usize>,
+    /// Sales being loaded in full from the store.
+    fetching: HashSet<usize>,
     draft: (Option<usize>, sale::Sale),
+    /// What is being typed on the screen of this sale.
+    sale_form: (Option<usize>, sale::Form),
+    /// Card payments waiting on the terminal, by sale.
+    card_waits: HashMap<usize, terminal::Pending>,
+    /// The stored sale the draft was taken from, to tell if it changed since.
+    draft_base: Option<sale::Sale>,
+    /// The draft held back from saving over a sale changed elsewhere.
+    conflict: Option<sale::conflict::Conflict>,
+    settings: settings::Settings,
+    report: report::Report,
+    gift_cards: gift_card::Cards,
+    card_lookup: gift_card::lookup::Lookup,
+    catalog: catalog::Catalog,
+    /// The item names sold before, suggested while typing one.
+    item_names: sale::names::Names,
+    drawer: drawer::Drawer,
+    /// What is being typed on the drawer screen.
+    till: drawer::manage::Form,
+    shifts: shift::Shifts,
+    /// What is being typed on the shifts screen.
+    roster: shift::Form,
+    /// Deleted sales, until restored or purged.
+    trash: trash::Trash,
+    trash_form: trash::Form,
+    /// Whether the cheat sheet of the shortcuts is shown.
+    shortcuts: bool,
+    refund: returns::Return,
+    layout_editor: layout::Editor,
+    templates: template::Templates,
+    recurrences: recurring::Recurrences,
+    /// The recurrence being set up on the recurring sales screen.
+    recurring_form: recurring::Form,
+    customers: customer::Customers,
+    /// What is being typed on the customers screen.
+    customer_form: customer::Form,
+    /// The line being typed on the quick sale screen.
+    quick_input: String,
+    /// The sales of each customer, as of the last change to the sales.
+    customer_index: customer::Index,
+    list_tab: list::Tab,
+    list_filter: tag::Filter,
+    /// What the sales listed were last grouped by day at: the revision of
+    /// the store, how many sales were held and the filter.
+    list_grouped: Option<(u64, usize, usize, tag::Filter)>,
+    list_days: list::Days,
+    /// Scheduled orders already reminded of since the app started.
+    reminded: BTreeSet<usize>,
+    /// Calls to external services to retry once back online.
+    outbox: outbox::Outbox,
+    /// Sales moved out of the store, loaded a month at a time.
+    archive: archive::Archive,
+    stats: diagnostics::Stats,
     next_sale_id: AtomicUsize,
+    saves_since_backup: u32,
+    modified_at: Option<DateTime<Utc>>,
+    loading: bool,
+    store_revision: u64,
+    store_error: Option<String>,
+    /// Sales whose latest change hasn't been confirmed written yet.
+    saves: HashMap<usize, store::SaveStatus>,
+    sync_status: sync::Status,
+    live: Option<live::Bus>,
+    hooks: hook::Registry,
+    toasts: toast::Toasts,
+    /// The QR code for the sale on screen, with the payload it encodes.
+    receipt_qr: Option<(String, image::Handle)>,
+    main_window: window::Id,
+    board_window: Option<window::Id>,
 }
 
 impl App {
-    fn theme(&self) -> iced::Theme {
+    fn theme(&self, _: window::Id) -> iced::Theme {
         iced::Theme::Light
     }
 
-    fn title(&self) -> String {
+    fn scale_factor(&self, _: window::Id) -> f64 {
+        f64::from(self.settings.config.ui_scale.clamp(100, 200)) / 100.0
+    }
+
+    fn title(&self, window: window::Id) -> String {
+        if Some(window) == self.board_window {
+            return "iced Receipts • Orders Ready".to_string();
+        }
+
         match self.screen {
             Screen::List => "iced Receipts".to_string(),
+            Screen::Settings => "iced Receipts • Settings".to_string(),
+            Screen::Report => "iced Receipts • Reports".to_string(),
+            Screen::GiftCards => "iced Receipts • Gift cards".to_string(),
+            Screen::Catalog => "iced Receipts • Catalog".to_string(),
+            Screen::Drawer => "iced Receipts • Cash drawer".to_string(),
+            Screen::Shifts => "iced Receipts • Shifts".to_string(),
+            Screen::Trash => "iced Receipts • Trash".to_string(),
+            Screen::Conflict => {
+                "iced Receipts • Sale changed elsewhere".to_string()
+            }
+            Screen::Recurring => "iced Receipts • Recurring sales".to_string(),
+            Screen::Customers => "iced Receipts • Customers".to_string(),
+            Screen::Customer(id) => format!(
+                "iced Receipts • {}",
+                self.customers
+                    .get(id)
+                    .map_or("Customer", |customer| customer.display_name())
+            ),
+            Screen::QuickSale => "iced Receipts • Quick sale".to_string(),
+            Screen::Return => "iced Receipts • Return".to_string(),
+            Screen::Layout => "iced Receipts • Receipt layout".to_string(),
+            Screen::QuickButtons => "iced Receipts • Quick buttons".to_string(),
+            Screen::Diagnostics => "iced Receipts • Diagnostics".to_string(),
             Screen::Sale(mode, id) => {
                 let sale_name = if self.draft.0 == id {
                     self.draft.1.name.clone()
                 } else {
-                    self.sales[&id.unwrap()].name.clone()
+                    let id = id.unwrap();
+                    self.sales
+                        .get(&id)
+                        .map(|sale| &sale.name)
+                        .or_else(|| self.headers.get(&id).map(|h| &h.name))
+                        .cloned()
+                        .unwrap_or_default()
                 };
 
                 let sale_name = format!(
@@ -87,168 +375,3202 @@ impl App {
     }
 
     fn new() -> (Self, Task<Message>) {
-        let initial_id = 0;
-        (
-            Self {
-                screen: Screen::List,
-                sales: HashMap::new(),
-                draft: (None, Sale::default()),
-                next_sale_id: AtomicUsize::new(initial_id + 1),
+        let (main_window, open) = window::open(window::Settings {
+            size: Size::new(800.0, 600.0),
+            position: window::Position::Centered,
+            ..window::Settings::default()
+        });
+
+        let config = config::Config::load();
+        i18n::set(config.locale);
+        tax::set(config.tax_rounding);
+        tracing::info!(data_dir = %config::data_dir().display(), "started");
+        let hooks = hook::Registry::new(hook::compiled_in(&config.hooks));
+        let load = Task::perform(
+            store::load_index(config.store_format),
+            Message::Loaded,
+        );
+
+        let mut app = Self {
+            screen: Screen::List,
+            sales: HashMap::new(),
+            headers: HashMap::new(),
+            pickups: HashSet::new(),
+            fetching: HashSet::new(),
+            draft: (None, Sale::default()),
+            sale_form: (None, sale::Form::default()),
+            card_waits: HashMap::new(),
+            draft_base: None,
+            conflict: None,
+            settings: settings::Settings::new(config),
+            report: report::Report::default(),
+            gift_cards: gift_card::Cards::load(),
+            card_lookup: gift_card::lookup::Lookup::default(),
+            catalog: catalog::Catalog::load(),
+            item_names: sale::names::Names::load(),
+            drawer: drawer::Drawer::load(),
+            till: drawer::manage::Form::default(),
+            shifts: shift::Shifts::load(),
+            roster: shift::Form::default(),
+            trash: trash::Trash::load(),
+            trash_form: trash::Form::default(),
+            shortcuts: false,
+            refund: returns::Return::default(),
+            layout_editor: layout::Editor::default(),
+            templates: template::Templates::load(),
+            recurrences: recurring::Recurrences::load(),
+            recurring_form: recurring::Form::default(),
+            customers: customer::Customers::load(),
+            customer_form: customer::Form::default(),
+            quick_input: String::new(),
+            customer_index: customer::Index::default(),
+            list_tab: list::Tab::default(),
+            list_days: list::Days::default(),
+            list_filter: tag::Filter::default(),
+            list_grouped: None,
+            reminded: BTreeSet::new(),
+            outbox: outbox::Outbox::load(),
+            archive: archive::Archive {
+                index: archive::Index::load(),
+                ..archive::Archive::default()
             },
-            Task::none(),
+            stats: diagnostics::Stats::default(),
+            next_sale_id: AtomicUsize::new(1),
+            saves_since_backup: 0,
+            modified_at: None,
+            loading: true,
+            store_revision: 0,
+            store_error: None,
+            saves: HashMap::new(),
+            sync_status: sync::Status::Off,
+            live: None,
+            hooks,
+            toasts: toast::Toasts::default(),
+            receipt_qr: None,
+            main_window,
+            board_window: None,
+        };
+
+        let locked = match store::lock() {
+            Ok(()) => Task::none(),
+            Err(e) => app.store_failed(e.to_string()),
+        };
+
+        let purged = app.purge_trash();
+        let rates = if app.settings.config.currency.is_due(Utc::now()) {
+            app.fetch_rates(false)
+        } else {
+            Task::none()
+        };
+
+        (
+            app,
+            Task::batch([open.discard().chain(load), locked, rates, purged]),
         )
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
+        let started = Instant::now();
+        tracing::debug!(?message);
+        let task = self.dispatch(message);
+        self.refresh_sale_form();
+        self.refresh_receipt_qr();
+        self.refresh_list();
+        self.stats.record_update(started.elapsed());
+        task
+    }
+
+    /// Group the sales listed by day again once they or the filter changed,
+    /// rather than on every redraw. Every change to a sale listed is saved
+    /// as a new revision of the store, and all of them replaced starts over.
+    fn refresh_list(&mut self) {
+        let at = (
+            self.store_revision,
+            self.headers.len(),
+            self.sales.len(),
+            self.list_filter.clone(),
+        );
+        if self.list_grouped.as_ref() == Some(&at) {
+            return;
+        }
+        let headers = self.headers();
+        list::group(&mut self.list_days, headers, &self.list_filter);
+        self.list_grouped = Some(at);
+    }
+
+    /// Start on a blank form when another sale is shown than the one it was
+    /// typed on.
+    fn refresh_sale_form(&mut self) {
+        if let Screen::Sale(_, id) = self.screen {
+            if self.sale_form.0 != id {
+                self.sale_form = (id, sale::Form::default());
+            }
+        }
+    }
+
+    /// Render the QR code for the sale on screen again if what it encodes
+    /// has changed.
+    fn refresh_receipt_qr(&mut self) {
+        let payload = match self.screen {
+            Screen::Sale(sale::Mode::View, Some(id)) => {
+                self.sales.get(&id).and_then(|sale| {
+                    qr::payload(&self.settings.config.receipt_qr, id, sale)
+                })
+            }
+            _ => None,
+        };
+
+        match payload {
+            Some(payload)
+                if self
+                    .receipt_qr
+                    .as_ref()
+                    .is_some_and(|(cached, _)| *cached == payload) => {}
+            Some(payload) => {
+                self.receipt_qr =
+                    qr::image(&payload).map(|handle| (payload, handle));
+            }
+            None => self.receipt_qr = None,
+        }
+    }
+
+    fn dispatch(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::List(list::Message::NewSale) => {
-                self.draft = (None, Sale::default());
+                self.start_draft(None, Sale::default());
                 self.screen = Screen::Sale(sale::Mode::Edit, None);
-                return focus_next();
+                return sale::edit::Focus::SaleName.task();
+            }
+            Message::List(list::Message::SelectTab(tab)) => {
+                self.list_tab = tab;
+            }
+            Message::List(list::Message::Archive(msg)) => {
+                return archive::update(&mut self.archive, msg)
+                    .map(|msg| Message::List(list::Message::Archive(msg)));
+            }
+            Message::List(list::Message::FilterTag(tag)) => {
+                self.list_filter.tag = tag;
+            }
+            Message::List(list::Message::FilterLabel(label)) => {
+                self.list_filter.label = label;
+            }
+            Message::List(list::Message::UseTemplate(id)) => {
+                if let Some(template) = self.templates.get(id) {
+                    self.start_draft(None, template.draft());
+                    self.screen = Screen::Sale(sale::Mode::Edit, None);
+                    return sale::edit::focus_form(&self.draft.1);
+                }
+            }
+            Message::List(list::Message::RenameTemplate(id, name)) => {
+                self.templates.rename(id, name);
+                return self.save_templates();
+            }
+            Message::List(list::Message::RemoveTemplate(id)) => {
+                self.templates.remove(id);
+                self.recurrences.remove_template(id);
+                if self.templates.is_empty() {
+                    self.list_tab = list::Tab::Sales;
+                }
+                return self.save_recurrences().chain(self.save_templates());
             }
             Message::List(list::Message::SelectSale(id)) => {
-                self.screen = Screen::Sale(sale::Mode::View, Some(id));
+                return self.show_sale(id);
             }
-            Message::Hotkey(hotkey) => match self.screen {
-                Screen::List => {}
-                Screen::Sale(mode, sale_id) => {
-                    let sale = if self.draft.0 == sale_id {
-                        &mut self.draft.1
-                    } else {
-                        self.sales
-                            .get_mut(&sale_id.unwrap())
-                            .expect("Sale should exist")
-                    };
+            Message::List(list::Message::OpenSettings) => {
+                return self.navigate(Screen::Settings);
+            }
+            Message::List(list::Message::OpenReports) => {
+                return self.navigate(Screen::Report);
+            }
+            Message::List(list::Message::OpenGiftCards) => {
+                return self.navigate(Screen::GiftCards);
+            }
+            Message::GiftCards(msg) => {
+                let action =
+                    gift_card::lookup::update(&mut self.card_lookup, msg)
+                        .map_instruction(Instruction::GiftCards)
+                        .map(Message::GiftCards);
 
-                    let action = sale::handle_hotkey(sale, mode, hotkey)
-                        .map_instruction(move |o| Instruction::Sale(sale_id, o))
-                        .map(move |m| Message::Sale(sale_id, m));
+                return self.run(action);
+            }
+            Message::List(list::Message::OpenCatalog) => {
+                return self.navigate(Screen::Catalog);
+            }
+            Message::Catalog(msg) => {
+                let action = catalog::manage::update(&mut self.catalog, msg)
+                    .map_instruction(Instruction::Catalog)
+                    .map(Message::Catalog);
 
-                    let instruction_task =
-                        if let Some(instruction) = action.instruction {
-                            self.perform(instruction)
-                        } else {
-                            Task::none()
-                        };
+                return self.run(action);
+            }
+            Message::List(list::Message::OpenDrawer) => {
+                return self.navigate(Screen::Drawer);
+            }
+            Message::Drawer(msg) => {
+                let action = drawer::manage::update(
+                    &mut self.drawer,
+                    &mut self.till,
+                    msg,
+                )
+                .map_instruction(Instruction::Drawer)
+                .map(Message::Drawer);
 
-                    return instruction_task.chain(action.task);
-                }
-            },
-            Message::Sale(sale_id, msg) => {
-                let sale = if self.draft.0 == sale_id {
-                    &mut self.draft.1
-                } else {
-                    self.sales
-                        .get_mut(&sale_id.unwrap())
-                        .expect("Sale should exist")
-                };
+                return self.run(action);
+            }
+            Message::List(list::Message::OpenShifts) => {
+                return self.navigate(Screen::Shifts);
+            }
+            Message::List(list::Message::OpenTrash) => {
+                return self.navigate(Screen::Trash);
+            }
+            Message::Conflict(msg) => {
+                let action = sale::conflict::update(msg)
+                    .map_instruction(Instruction::Conflict)
+                    .map(Message::Conflict);
 
-                let action = sale::update(sale, msg)
-                    .map_instruction(move |o| Instruction::Sale(sale_id, o))
-                    .map(move |m| Message::Sale(sale_id, m));
+                return self.run(action);
+            }
+            Message::Trash(msg) => {
+                let action =
+                    trash::update(&self.trash, &mut self.trash_form, msg)
+                        .map_instruction(Instruction::Trash)
+                        .map(Message::Trash);
 
-                let instruction_task =
-                    if let Some(instruction) = action.instruction {
-                        self.perform(instruction)
-                    } else {
-                        Task::none()
-                    };
+                return self.run(action);
+            }
+            Message::List(list::Message::OpenRecurring) => {
+                return self.navigate(Screen::Recurring);
+            }
+            Message::Recurring(msg) => {
+                let action = recurring::update(
+                    &mut self.recurrences,
+                    &mut self.recurring_form,
+                    msg,
+                )
+                .map_instruction(Instruction::Recurring)
+                .map(Message::Recurring);
 
-                return instruction_task.chain(action.task);
+                return self.run(action);
             }
-        }
-        Task::none()
-    }
+            Message::List(list::Message::OpenCustomers) => {
+                self.customer_form = customer::Form::default();
+                return self.navigate(Screen::Customers);
+            }
+            Message::Customers(msg) => {
+                let action = customer::update(
+                    &mut self.customers,
+                    &mut self.customer_form,
+                    msg,
+                )
+                .map_instruction(Instruction::Customers)
+                .map(Message::Customers);
 
-    fn view(&self) -> Element<Message> {
-        match &self.screen {
-            Screen::List => list::view(&self.sales).map(Message::List),
-            Screen::Sale(mode, id) => {
-                let sale = if self.draft.0 == *id {
-                    &self.draft.1
-                } else {
-                    &self.sales[&id.unwrap()]
+                return self.run(action);
+            }
+            Message::List(list::Message::QuickSale) => {
+                return self.navigate(Screen::QuickSale);
+            }
+            Message::QuickSale(msg) => {
+                let action = sale::quick::update(
+                    &mut self.draft.1,
+                    &mut self.quick_input,
+                    &self.catalog,
+                    msg,
+                )
+                .map_instruction(Instruction::QuickSale)
+                .map(Message::QuickSale);
+
+                return self.run(action);
+            }
+            Message::Shifts(msg) => {
+                let action =
+                    shift::update(&mut self.shifts, &mut self.roster, msg)
+                        .map_instruction(Instruction::Shifts)
+                        .map(Message::Shifts);
+
+                return self.run(action);
+            }
+            Message::List(list::Message::NewReturn) => {
+                return self.navigate(Screen::Return);
+            }
+            Message::Return(msg) => {
+                let action = returns::update(&mut self.refund, msg)
+                    .map_instruction(Instruction::Return)
+                    .map(Message::Return);
+
+                return self.run(action);
+            }
+            Message::Layout(msg) => {
+                let action = layout::update(&mut self.layout_editor, msg)
+                    .map_instruction(Instruction::Layout)
+                    .map(Message::Layout);
+
+                return self.run(action);
+            }
+            Message::QuickButtons(msg) => {
+                let config = &mut self.settings.config.quick_buttons;
+                let action = quick_buttons::update(config, msg)
+                    .map_instruction(Instruction::QuickButtons)
+                    .map(Message::QuickButtons);
+
+                return self.run(action);
+            }
+            Message::Diagnostics(msg) => {
+                let action = diagnostics::update(&mut self.stats, msg)
+                    .map_instruction(Instruction::Diagnostics)
+                    .map(Message::Diagnostics);
+
+                return self.run(action);
+            }
+            Message::Report(msg) => {
+                let action = report::update(&mut self.report, msg)
+                    .map_instruction(Instruction::Report)
+                    .map(Message::Report);
+
+                return self.run(action);
+            }
+            Message::Settings(msg) => {
+                let action = settings::update(&mut self.settings, msg)
+                    .map_instruction(Instruction::Settings)
+                    .map(Message::Settings);
+
+                return self.run(action);
+            }
+            Message::Board(board::Message::PickedUp(id)) => {
+                if let Some(sale) = self.sales.get_mut(&id) {
+                    sale.mark_picked_up();
+                    self.publish(live::Kind::Paid, id);
+                    return self.sale_changed(id);
+                }
+                if self.headers.contains_key(&id) && self.pickups.insert(id) {
+                    return self.fetch(id);
+                }
+            }
+            Message::List(list::Message::Sync) => return self.sync(),
+            Message::List(list::Message::ToggleDay(day)) => {
+                if !self.list_days.collapsed.remove(&day) {
+                    self.list_days.collapsed.insert(day);
+                }
+            }
+            Message::List(list::Message::JumpInput(date)) => {
+                self.list_days.jump = date;
+            }
+            Message::List(list::Message::JumpToDate) => {
+                return match list::jump(
+                    &mut self.list_days,
+                    self.settings.config.density,
+                ) {
+                    Ok(task) => task,
+                    Err(e) => self.toast(toast::Kind::Error, e),
                 };
-                sale::view(sale, *mode).map(|msg| Message::Sale(*id, msg))
             }
-        }
-    }
+            Message::List(list::Message::LoadMore) => {
+                self.list_days.shown += list::PAGE;
+            }
+            Message::List(list::Message::Scrolled(viewport)) => {
+                self.list_days.offset = viewport.absolute_offset().y;
+            }
+            Message::Synced(Ok(outcome)) => {
+                self.sync_status = sync::Status::Synced(Local::now());
+                let saved = self.sent(&outbox::Job::Sync);
+                if let sync::Outcome::Pulled(theirs) = outcome {
+                    self.replace_sales(theirs.sales);
+                    self.modified_at = theirs.modified_at;
 
-    fn perform(&mut self, instruction: Instruction) -> Task<Message> {
-        match instruction {
-            Instruction::Sale(sale_id, instruction) => match instruction {
-                sale::Instruction::Back => match self.screen {
-                    Screen::List => {}
-                    Screen::Sale(mode, _) => match mode {
-                        sale::Mode::Edit => {
-                            self.screen =
-                                Screen::Sale(sale::Mode::View, sale_id)
-                        }
-                        sale::Mode::View => self.screen = Screen::List,
-                    },
-                },
-                sale::Instruction::Save => {
-                    let final_id = match self.draft.0 {
-                        Some(id) => {
-                            // Editing existing sale
-                            self.sales.insert(id, self.draft.1.clone());
-                            id
+                    // The sale on screen may have been deleted remotely
+                    if let Screen::Sale(sale::Mode::View, Some(id)) =
+                        self.screen
+                    {
+                        if !self.sales.contains_key(&id) {
+                            self.screen = Screen::List;
                         }
-                        None => {
-                            // Creating new sale
-                            let new_id = self
-                                .next_sale_id
-                                .fetch_add(1, Ordering::SeqCst);
-                            self.sales.insert(
-                                new_id,
-                                std::mem::take(&mut self.draft.1),
-                            );
-                            self.draft.1 = Sale::default();
-                            new_id
+                    }
+                    return saved.chain(self.save_store(Message::Saved));
+                }
+                if let sync::Outcome::Diverged(conflicts) = outcome {
+                    return saved.chain(self.diverged(conflicts));
+                }
+                return saved;
+            }
+            Message::Synced(Err(failure)) => {
+                self.sync_status = sync::Status::Failed(failure.to_string());
+                return self.not_sent(outbox::Job::Sync, failure);
+            }
+            Message::Live(live::Event::Ready(bus)) => {
+                self.live = Some(bus);
+                self.settings.live_status = Some(Ok(format!(
+                    "Listening on port {}",
+                    self.settings.config.live_feed.port
+                )));
+            }
+            Message::Live(live::Event::Failed(e)) => {
+                self.live = None;
+                self.settings.live_status = Some(Err(e));
+            }
+            Message::Loaded(Ok(index)) => {
+                self.loading = false;
+                self.sales.clear();
+                self.list_grouped = None;
+                let restored = index.restored_from.map(|taken_at| {
+                    self.toast(
+                        toast::Kind::Warning,
+                        format!(
+                            "The sales file was damaged and has been restored \
+                             from the backup of {}",
+                            taken_at.format("%Y-%m-%d %H:%M")
+                        ),
+                    )
+                });
+                self.headers = index.headers.into_iter().collect();
+                self.refresh_next_sale_id();
+                self.modified_at = index.modified_at;
+                return Task::batch([
+                    self.sync(),
+                    self.archive_old(),
+                    self.make_recurring(),
+                    restored.unwrap_or_else(Task::none),
+                ]);
+            }
+            Message::Loaded(Err(e)) => {
+                self.loading = false;
+                return self.store_failed(format!("Could not load sales: {e}"));
+            }
+            Message::Archived(Ok((ids, index))) => {
+                self.headers.retain(|id, _| !ids.contains(id));
+                self.archive.reindex(index);
+                if !ids.is_empty() {
+                    return self.toast(
+                        toast::Kind::Success,
+                        format!("Archived {} sale(s)", ids.len()),
+                    );
+                }
+            }
+            Message::Persisted(_, Ok(())) => {}
+            Message::TrashSaved(change, result) => {
+                return self.trash_saved(change, result);
+            }
+            Message::Persisted(what, Err(e)) => {
+                return self.toast(
+                    toast::Kind::Error,
+                    format!("Could not save {what}: {e}"),
+                );
+            }
+            Message::Archived(Err(e)) => {
+                return self.toast(
+                    toast::Kind::Error,
+                    format!("Could not archive sales: {e}"),
+                );
+            }
+            Message::Fetched(id, Ok(sale)) => {
+                self.fetching.remove(&id);
+                // Unless all sales were replaced while it was loading
+                if self.headers.remove(&id).is_some() {
+                    self.sales.insert(id, (*sale).with_fresh_ids());
+                    if self.pickups.remove(&id) {
+                        self.sales.get_mut(&id).unwrap().mark_picked_up();
+                        self.publish(live::Kind::Paid, id);
+                        return self.sale_changed(id);
+                    }
+                }
+            }
+            Message::Fetched(id, Err(e)) => {
+                self.fetching.remove(&id);
+                self.pickups.remove(&id);
+                if self.refund.found == Some(id) {
+                    self.refund.found = None;
+                }
+                if let Screen::Sale(_, Some(shown)) = self.screen {
+                    if shown == id && !self.sales.contains_key(&id) {
+                        self.screen = Screen::List;
+                    }
+                }
+                return self.toast(
+                    toast::Kind::Error,
+                    format!("Could not load sale #{id}: {e}"),
+                );
+            }
+            Message::Saved(revision, result) => {
+                return self.saved(revision, result);
+            }
+            Message::StoredFetched(id, Ok(theirs)) => {
+                let editing =
+                    matches!(self.screen, Screen::Sale(sale::Mode::Edit, _))
+                        || self.conflict.is_some();
+                let Some(mine) = self.sales.get(&id) else {
+                    return Task::none();
+                };
+                if editing {
+                    return Task::none();
+                }
+                self.conflict = Some(sale::conflict::Conflict::between(
+                    id,
+                    mine.clone(),
+                    *theirs,
+                ));
+                self.screen = Screen::Conflict;
+            }
+            Message::StoredFetched(id, Err(e)) => {
+                return self.toast(
+                    toast::Kind::Error,
+                    format!("Could not load sale #{id}: {e}"),
+                );
+            }
+            Message::StoreConverted(revision, result) => {
+                if result.is_err() {
+                    return self.saved(revision, result);
+                }
+                let converted = format!(
+                    "Sales are now stored as {}",
+                    self.settings.config.store_format
+                );
+                return self
+                    .saved(revision, result)
+                    .chain(self.toast(toast::Kind::Success, converted));
+            }
+            Message::BackedUp(result) => {
+                self.settings.backups = backup::list();
+                self.settings.selected_backup =
+                    self.settings.backups.first().cloned();
+                self.settings.backup_status = Some(
+                    result
+                        .map(|snapshot| format!("Last backup: {snapshot}"))
+                        .map_err(|e| format!("Backup failed: {e}")),
+                );
+                if let Some(Err(e)) = &self.settings.backup_status {
+                    return self.toast(toast::Kind::Error, e.clone());
+                }
+            }
+            Message::Restored(snapshot, Ok(export)) => {
+                self.replace_sales(export.sales);
+                self.draft = (None, Sale::default());
+                let restored = format!(
+                    "Restored {} sale(s) from {snapshot}",
+                    self.sales.len()
+                );
+                self.settings.backup_status = Some(Ok(restored.clone()));
+                return self
+                    .toast(toast::Kind::Success, restored)
+                    .chain(self.sales_changed());
+            }
+            Message::Restored(_, Err(e)) => {
+                let error = format!("Restore failed: {e}");
+                self.settings.backup_status = Some(Err(error.clone()));
+                return self.toast(toast::Kind::Error, error);
+            }
+            Message::Exported(progress, result) => {
+                self.toasts.dismiss(progress);
+                return match result {
+                    Ok(exported) => self.toast(toast::Kind::Success, exported),
+                    Err(e) => self.toast(
+                        toast::Kind::Error,
+                        format!("Export failed: {e}"),
+                    ),
+                };
+            }
+            Message::Printed(progress, id, result) => {
+                self.toasts.dismiss(progress);
+                if let Err(e) = result {
+                    return self.toast(
+                        toast::Kind::Error,
+                        format!("Printing failed: {e}"),
+                    );
+                }
+                return self.issued(id, sale::Via::Print);
+            }
+            Message::LogoLoaded(Ok(logo)) => {
+                self.settings.config.logo = Some(logo);
+                self.settings.logo_path.clear();
+                return self.toast(toast::Kind::Success, "Logo loaded");
+            }
+            Message::LogoLoaded(Err(e)) => {
+                return self.toast(
+                    toast::Kind::Error,
+                    format!("Could not load the logo: {e}"),
+                );
+            }
+            Message::Kicked(result) => {
+                if let Err(e) = result {
+                    return self.toast(
+                        toast::Kind::Error,
+                        format!("Could not open the drawer: {e}"),
+                    );
+                }
+            }
+            Message::ScaleRead(sale_id, id, Ok(reading)) => {
+                let kg = i18n::number(reading.kg, 3);
+                return Task::done(Message::Sale(
+                    sale_id,
+                    sale::Message::Edit(sale::edit::Message::UpdateItem(
+                        id,
+                        sale::edit::Field::Quantity(kg),
+                    )),
+                ));
+            }
+            Message::ScaleRead(_, _, Err(e)) => {
+                return self.toast(
+                    toast::Kind::Error,
+                    format!("Could not read the scale: {e}"),
+                );
+            }
+            Message::CardStarted(id, Ok(pending)) => {
+                if self.sales.contains_key(&id) {
+                    self.card_waits.insert(id, pending.clone());
+                    return self.poll_card(id, pending);
+                }
+            }
+            Message::CardStarted(_, Err(e)) => {
+                return self.toast(
+                    toast::Kind::Error,
+                    format!("Could not reach the card terminal: {e}"),
+                );
+            }
+            Message::CardPolled(id, result) => {
+                let Some(sale) = self.sales.get_mut(&id) else {
+                    return Task::none();
+                };
+                match result {
+                    Ok(terminal::Poll::Waiting) => {
+                        // Unless waiting was cancelled in the meantime
+                        if let Some(pending) = self.card_waits.get(&id).cloned()
+                        {
+                            return self.poll_card(id, pending);
                         }
+                    }
+                    // Recorded even if cancelled, as the card was charged
+                    Ok(terminal::Poll::Paid(card)) => {
+                        tracing::info!(sale = id, %card, "card payment taken");
+                        self.hooks.dispatch(hook::Event::PaymentTaken {
+                            id,
+                            payment: hook::Payment::Card(card.clone()),
+                        });
+                        self.card_waits.remove(&id);
+                        let paid = format!(
+                            "Paid {} by {card}",
+                            i18n::money(card.amount)
+                        );
+                        sale.card = Some(card);
+                        return self
+                            .toast(toast::Kind::Success, paid)
+                            .chain(self.sale_changed(id))
+                            .chain(self.fiscalize(id));
+                    }
+                    Ok(terminal::Poll::Failed(e)) | Err(e) => {
+                        self.card_waits.remove(&id);
+                        return self.toast(
+                            toast::Kind::Error,
+                            format!("Card payment failed: {e}"),
+                        );
+                    }
+                }
+            }
+            Message::CardCancelled(job, Ok(())) => return self.sent(&job),
+            Message::CardCancelled(job, Err(failure)) => {
+                let toast = self.toast(
+                    toast::Kind::Error,
+                    format!("Could not cancel on the card terminal: {failure}"),
+                );
+                return toast.chain(self.not_sent(job, failure));
+            }
+            Message::OutboxTick => return self.retry_outbox(),
+            Message::SummaryTick => {
+                let summary = &mut self.settings.config.summary;
+                let Some(day) = summary.due(Local::now().naive_local()) else {
+                    return Task::none();
+                };
+                summary.last_sent = Some(day);
+                return self
+                    .save_config()
+                    .chain(self.send(outbox::Job::SendSummary(day)));
+            }
+            Message::ScheduleTick => {
+                let due = self.settings.config.schedule.reminders(
+                    self.headers(),
+                    Utc::now(),
+                    &self.reminded,
+                );
+                let mut toasts = Vec::new();
+                for (id, header) in due {
+                    self.reminded.insert(id);
+                    let name = match header.name.as_str() {
+                        "" => format!("Sale #{id}"),
+                        name => name.to_string(),
                     };
-                    self.screen =
-                        Screen::Sale(sale::Mode::View, Some(final_id));
+                    let at = header.due_at.unwrap_or_default();
+                    toasts.push(self.toast(
+                        toast::Kind::Warning,
+                        format!(
+                            "{name} is due at {}",
+                            at.with_timezone(&Local).format("%H:%M")
+                        ),
+                    ));
                 }
-                sale::Instruction::StartEdit => {
-                    if let Some(id) = sale_id {
-                        // Start editing existing sale
-                        self.draft = (Some(id), self.sales[&id].clone());
+                return Task::batch(toasts);
+            }
+            Message::RecurringTick => return self.make_recurring(),
+            Message::SummarySent(job, Ok(())) => {
+                let toast = self.toast(
+                    toast::Kind::Success,
+                    format!(
+                        "Emailed the summary to {}",
+                        self.settings.config.summary.to
+                    ),
+                );
+                return toast.chain(self.sent(&job));
+            }
+            Message::SummarySent(job, Err(failure)) => {
+                let toast = self.toast(
+                    toast::Kind::Error,
+                    format!("Could not email the summary: {failure}"),
+                );
+                return toast.chain(self.not_sent(job, failure));
+            }
+            Message::ReceiptSent(job, Ok(())) => {
+                let outbox::Job::SendReceipt { sale, to, .. } = &job else {
+                    return Task::none();
+                };
+                let toast = self.toast(
+                    toast::Kind::Success,
+                    format!("Emailed the receipt to {to}"),
+                );
+                let issued = self.issued(*sale, sale::Via::Email(to.clone()));
+                return toast.chain(issued).chain(self.sent(&job));
+            }
+            Message::ReceiptSent(job, Err(failure)) => {
+                let toast = self.toast(
+                    toast::Kind::Error,
+                    format!("Could not email the receipt: {failure}"),
+                );
+                return toast.chain(self.not_sent(job, failure));
+            }
+            Message::Fiscalized(id, Ok(Some(signature))) => {
+                if let Some(sale) = self.sales.get_mut(&id) {
+                    sale.fiscal = Some(signature);
+                    return self.sale_changed(id);
+                }
+            }
+            Message::Fiscalized(_, Ok(None)) => {}
+            Message::Fiscalized(id, Err(e)) => {
+                return self.toast(
+                    toast::Kind::Error,
+                    format!("Could not sign the receipt of #{id}: {e}"),
+                );
+            }
+            Message::RatesTick => {
+                if self.settings.config.currency.is_due(Utc::now()) {
+                    return self.fetch_rates(false);
+                }
+            }
+            Message::RatesFetched(manual, Ok(cache)) => {
+                let set = self.settings.config.currency.apply(&cache);
+                let saved = self.save_config();
+                let unknown = self.settings.config.currency.rates.len() - set;
+                if !manual {
+                    tracing::info!(set, unknown, "refreshed exchange rates");
+                    return saved;
+                }
+                return saved.chain(if unknown == 0 {
+                    self.toast(
+                        toast::Kind::Success,
+                        format!("Fetched the rates of {set} currencies"),
+                    )
+                } else {
+                    self.toast(
+                        toast::Kind::Warning,
+                        format!(
+                            "Fetched the rates of {set} currencies, \
+                             {unknown} weren't known"
+                        ),
+                    )
+                });
+            }
+            Message::RatesFetched(manual, Err(e)) => {
+                let currency = &mut self.settings.config.currency;
+                // Fall back to the rates last fetched, if they are newer
+                // than the ones in use or were asked for
+                let cached = currency::Cache::load().filter(|cache| {
+                    cache.base.eq_ignore_ascii_case(currency.base.trim())
+                        && (manual
+                            || currency
+                                .fetched_at
+                                .is_none_or(|at| at < cache.fetched_at))
+                });
+                let Some(cache) = cached else {
+                    return if manual {
+                        self.toast(
+                            toast::Kind::Error,
+                            format!("Could not fetch exchange rates: {e}"),
+                        )
+                    } else {
+                        tracing::warn!("could not refresh exchange rates: {e}");
+                        Task::none()
+                    };
+                };
+                currency.apply(&cache);
+                let saved = self.save_config();
+                if !manual {
+                    tracing::warn!("could not refresh exchange rates: {e}");
+                    return saved;
+                }
+                return saved.chain(self.toast(
+                    toast::Kind::Warning,
+                    format!(
+                        "Could not fetch exchange rates: {e}. Using the ones \
+                         fetched {}",
+                        cache
+                            .fetched_at
+                            .with_timezone(&Local)
+                            .format("%Y-%m-%d %H:%M")
+                    ),
+                ));
+            }
+            Message::ImportRead(Ok(export)) => {
+                self.settings.pending_import =
+                    Some(data::validate(export, &self.sale_ids()));
+            }
+            Message::ImportRead(Err(e)) => {
+                self.settings.pending_import = None;
+                return self
+                    .toast(toast::Kind::Error, format!("Import failed: {e}"));
+            }
+            Message::SaleImportRead(result) => {
+                let sale = match result.and_then(data::single_sale) {
+                    Ok(sale) => sale,
+                    Err(e) => {
+                        return self.toast(
+                            toast::Kind::Error,
+                            format!("Import failed: {e}"),
+                        );
                     }
-                    self.screen = Screen::Sale(sale::Mode::Edit, sale_id);
+                };
+                // Sales get a new id and creation time when imported, so
+                // duplicates can only be told by their contents
+                let total = sale.calculate_total();
+                let duplicate = self.headers().into_iter().find(|(_, h)| {
+                    h.name == sale.name && (h.total - total).abs() < 0.005
+                });
+                let toast = match duplicate {
+                    Some((id, _)) => self.toast(
+                        toast::Kind::Warning,
+                        format!("This looks like sale #{id}, already here"),
+                    ),
+                    None => self.toast(
+                        toast::Kind::Success,
+                        "Review the imported sale and save it",
+                    ),
+                };
+
+                // Opened as a new draft, so it gets a new id once saved
+                self.start_draft(None, sale);
+                self.screen = Screen::Sale(sale::Mode::Edit, None);
+                return toast;
+            }
+            Message::DismissToast(id) => self.toasts.dismiss(id),
+            Message::CloseShortcuts => self.shortcuts = false,
+            Message::KeyPressed(chord, status) => {
+                let keymap = &self.settings.config.shortcuts;
+                if let Some(hotkey) = keymap.hotkey(&chord, status) {
+                    return self.update(Message::Hotkey(hotkey));
                 }
-                sale::Instruction::Cancel => {
-                    match sale_id {
-                        Some(id) => {
-                            // Restore draft from original sale
-                            self.draft = (Some(id), self.sales[&id].clone());
-                        }
-                        None => {
-                            // Reset to blank draft
-                            self.draft = (None, Sale::default());
-                        }
+            }
+            Message::Hotkey(Hotkey::Help) => self.shortcuts = !self.shortcuts,
+            Message::Hotkey(Hotkey::Escape) if self.shortcuts => {
+                self.shortcuts = false;
+            }
+            Message::WindowClosed(id) => {
+                if id == self.main_window {
+                    return iced::exit();
+                }
+                if Some(id) == self.board_window {
+                    self.board_window = None;
+                }
+            }
+            Message::Hotkey(hotkey) => match self.screen {
+                Screen::List => {
+                    if let Hotkey::Diagnostics = hotkey {
+                        return self.navigate(Screen::Diagnostics);
                     }
-                    self.screen = Screen::Sale(sale::Mode::View, sale_id);
                 }
-            },
-        }
-        Task::none()
-    }
+                Screen::Diagnostics => {
+                    let action = diagnostics::handle_hotkey(hotkey)
+                        .map_instruction(Instruction::Diagnostics)
+                        .map(Message::Diagnostics);
 
-    fn subscription(&self) -> Subscription<Message> {
-        event::listen_with(handle_event)
-    }
-}
+                    return self.run(action);
+                }
+                Screen::Report => {
+                    let action = report::handle_hotkey(hotkey)
+                        .map_instruction(Instruction::Report)
+                        .map(Message::Report);
 
-#[derive(Debug)]
-pub enum Hotkey {
-    Escape,
-    Tab(Modifiers),
-}
+                    return self.run(action);
+                }
+                Screen::Catalog => {
+                    let action = catalog::manage::handle_hotkey(hotkey)
+                        .map_instruction(Instruction::Catalog)
+                        .map(Message::Catalog);
+
+                    return self.run(action);
+                }
+                Screen::Drawer => {
+                    let action = drawer::manage::handle_hotkey(hotkey)
+                        .map_instruction(Instruction::Drawer)
+                        .map(Message::Drawer);
+
+                    return self.run(action);
+                }
+                Screen::Trash => {
+                    let action =
+                        trash::handle_hotkey(&mut self.trash_form, hotkey)
+                            .map_instruction(Instruction::Trash)
+                            .map(Message::Trash);
+
+                    return self.run(action);
+                }
+                Screen::Shifts => {
+                    let action = shift::handle_hotkey(hotkey)
+                        .map_instruction(Instruction::Shifts)
+                        .map(Message::Shifts);
+
+                    return self.run(action);
+                }
+                Screen::Recurring => {
+                    let action = recurring::handle_hotkey(hotkey)
+                        .map_instruction(Instruction::Recurring)
+                        .map(Message::Recurring);
+
+                    return self.run(action);
+                }
+                Screen::Customers | Screen::Customer(_) => {
+                    let action = customer::handle_hotkey(hotkey, &self.screen)
+                        .map_instruction(Instruction::Customers)
+                        .map(Message::Customers);
+
+                    return self.run(action);
+                }
+                Screen::QuickSale => {
+                    let action = sale::quick::handle_hotkey(
+                        &mut self.draft.1,
+                        &mut self.quick_input,
+                        &self.catalog,
+                        hotkey,
+                    )
+                    .map_instruction(Instruction::QuickSale)
+                    .map(Message::QuickSale);
+
+                    return self.run(action);
+                }
+                Screen::Return => {
+                    let action = returns::handle_hotkey(hotkey)
+                        .map_instruction(Instruction::Return)
+                        .map(Message::Return);
+
+                    return self.run(action);
+                }
+                Screen::Conflict => {
+                    let action = sale::conflict::handle_hotkey(hotkey)
+                        .map_instruction(Instruction::Conflict)
+                        .map(Message::Conflict);
+
+                    return self.run(action);
+                }
+                Screen::Layout => {
+                    let action = layout::handle_hotkey(hotkey)
+                        .map_instruction(Instruction::Layout)
+                        .map(Message::Layout);
+
+                    return self.run(action);
+                }
+                Screen::QuickButtons => {
+                    let action = quick_buttons::handle_hotkey(hotkey)
+                        .map_instruction(Instruction::QuickButtons)
+                        .map(Message::QuickButtons);
+
+                    return self.run(action);
+                }
+                Screen::GiftCards => {
+                    let action = gift_card::lookup::handle_hotkey(
+                        &mut self.card_lookup,
+                        hotkey,
+                    )
+                    .map_instruction(Instruction::GiftCards)
+                    .map(Message::GiftCards);
+
+                    return self.run(action);
+                }
+                Screen::Settings => {
+                    let action = settings::handle_hotkey(hotkey)
+                        .map_instruction(Instruction::Settings)
+                        .map(Message::Settings);
+
+                    return self.run(action);
+                }
+                Screen::Sale(mode, sale_id) => {
+                    let sale = if self.draft.0 == sale_id {
+                        &mut self.draft.1
+                    } else {
+                        match self.sales.get_mut(&sale_id.unwrap()) {
+                            Some(sale) => sale,
+                            None => return Task::none(),
+                        }
+                    };
+                    if self.sale_form.0 != sale_id {
+                        self.sale_form = (sale_id, sale::Form::default());
+                    }
+                    let action = sale::handle_hotkey(
+                        sale,
+                        &mut self.sale_form.1,
+                        mode,
+                        hotkey,
+                    )
+                    .map_instruction(move |o| Instruction::Sale(sale_id, o))
+                    .map(move |m| Message::Sale(sale_id, m));
+
+                    return self.run(action);
+                }
+            },
+            Message::Sale(sale_id, msg) => {
+                let sale = if self.draft.0 == sale_id {
+                    &mut self.draft.1
+                } else {
+                    match self.sales.get_mut(&sale_id.unwrap()) {
+                        Some(sale) => sale,
+                        // Still being loaded from the store
+                        None => return Task::none(),
+                    }
+                };
+
+                if self.sale_form.0 != sale_id {
+                    self.sale_form = (sale_id, sale::Form::default());
+                }
+                let form = &mut self.sale_form.1;
+
+                let was_picked_up =
+                    sale.order_status == sale::OrderStatus::PickedUp;
+                // What a stored sale holds before the message, as most of
+                // them only change what is shown or typed
+                let before = (self.draft.0 != sale_id).then(|| sale.stored());
+                let action = sale::update(sale, form, msg)
+                    .map_instruction(move |o| Instruction::Sale(sale_id, o))
+                    .map(move |m| Message::Sale(sale_id, m));
+                if self.draft.0 == sale_id {
+                    sale.apply_service_rules(
+                        &self.settings.config.service_rules,
+                    );
+                    sale.apply_delivery(&self.settings.config.delivery);
+                }
+
+                // Changes to a stored sale (rather than a draft) are final
+                let changed_task = match (sale_id, before) {
+                    (Some(id), Some(before))
+                        if self.sales[&id].stored() != before =>
+                    {
+                        let picked_up = self.sales[&id].order_status
+                            == sale::OrderStatus::PickedUp;
+                        self.publish(
+                            if picked_up && !was_picked_up {
+                                live::Kind::Paid
+                            } else {
+                                live::Kind::Updated
+                            },
+                            id,
+                        );
+                        self.sale_changed(id)
+                    }
+                    _ => Task::none(),
+                };
+
+                return changed_task.chain(self.run(action));
+            }
+        }
+        Task::none()
+    }
+
+    fn view(&self, window: window::Id) -> Element<'_, Message> {
+        let started = Instant::now();
+        let view = self.view_window(window);
+        self.stats.record_view(started.elapsed());
+        view
+    }
+
+    fn view_window(&self, window: window::Id) -> Element<'_, Message> {
+        if Some(window) == self.board_window {
+            return board::view(self.headers()).map(Message::Board);
+        }
+
+        let screen = match &self.screen {
+            Screen::List => list::view(
+                self.headers(),
+                &self.saves,
+                &self.templates,
+                &self.archive,
+                self.list_tab,
+                &self.list_filter,
+                self.loading,
+                &self.sync_status,
+                &self.outbox,
+                self.store_error.as_deref(),
+                self.settings.config.logo.as_ref().map(|logo| &logo.handle),
+                self.settings.config.density,
+                &self.list_days,
+                self.trash.len(),
+            )
+            .map(Message::List),
+            Screen::Report => report::view(&self.report).map(Message::Report),
+            Screen::QuickSale => {
+                sale::quick::view(&self.draft.1, &self.quick_input)
+                    .map(Message::QuickSale)
+            }
+            Screen::Customers => customer::view(
+                &self.customers,
+                &self.customer_index,
+                &self.headers(),
+                &self.customer_form,
+            )
+            .map(Message::Customers),
+            Screen::Customer(id) => match self.customers.get(*id) {
+                Some(customer) => customer::history(
+                    customer,
+                    self.customer_sales(*id),
+                    self.settings.config.tax_registration.country,
+                ),
+                None => customer::view(
+                    &self.customers,
+                    &self.customer_index,
+                    &self.headers(),
+                    &self.customer_form,
+                ),
+            }
+            .map(Message::Customers),
+            Screen::GiftCards => gift_card::lookup::view(
+                &self.card_lookup,
+                &self.gift_cards,
+                self.settings.config.keypad,
+            )
+            .map(Message::GiftCards),
+            Screen::Catalog => {
+                catalog::manage::view(&self.catalog).map(Message::Catalog)
+            }
+            Screen::Drawer => drawer::manage::view(
+                &self.drawer,
+                &self.till,
+                self.settings.config.denominations.of(&self
+                    .settings
+                    .config
+                    .currency
+                    .base),
+            )
+            .map(Message::Drawer),
+            Screen::Recurring => recurring::view(
+                &self.recurrences,
+                &self.templates,
+                &self.recurring_form,
+            )
+            .map(Message::Recurring),
+            Screen::Shifts => {
+                shift::view(&self.shifts, &self.roster).map(Message::Shifts)
+            }
+            Screen::Trash => trash::view(
+                &self.trash,
+                &self.trash_form,
+                self.settings.config.trash_days,
+            )
+            .map(Message::Trash),
+            Screen::Conflict => match &self.conflict {
+                Some(conflict) => {
+                    sale::conflict::view(conflict).map(Message::Conflict)
+                }
+                None => horizontal_space().into(),
+            },
+            Screen::Return => returns::view(
+                &self.refund,
+                self.refund.found.and_then(|id| self.sales.get(&id)),
+            )
+            .map(Message::Return),
+            Screen::Layout => {
+                layout::view(&self.layout_editor, &self.settings.config)
+                    .map(Message::Layout)
+            }
+            Screen::QuickButtons => quick_buttons::view(
+                &self.settings.config.quick_buttons,
+                &self.catalog,
+                &self.item_names,
+            )
+            .map(Message::QuickButtons),
+            Screen::Settings => settings::view(&self.settings, &self.outbox)
+                .map(Message::Settings),
+            Screen::Diagnostics => {
+                let counts = diagnostics::Counts {
+                    sales_loaded: self.sales.len(),
+                    sales_unloaded: self.headers.len(),
+                    items: self.sales.values().map(|s| s.items.len()).sum(),
+                    cached_images: usize::from(self.receipt_qr.is_some()),
+                    pending_saves: self.saves.len(),
+                    toasts: self.toasts.len(),
+                    sales_archived: self.archive.index.months.values().sum(),
+                    outbox: self.outbox.entries().len(),
+                };
+                diagnostics::view(
+                    &self.stats,
+                    counts,
+                    &store::path(self.settings.config.store_format),
+                )
+                .map(Message::Diagnostics)
+            }
+            Screen::Sale(mode, id) => {
+                let sale = if self.draft.0 == *id {
+                    Some(&self.draft.1)
+                } else {
+                    self.sales.get(&id.unwrap())
+                };
+                let save_status =
+                    id.and_then(|id| self.saves.get(&id).copied());
+                match sale {
+                    Some(sale) => sale::view(
+                        sale,
+                        &self.sale_form.1,
+                        *id,
+                        *mode,
+                        &self.settings.config,
+                        save_status,
+                        self.receipt_qr.as_ref().map(|(_, handle)| handle),
+                        &self.catalog,
+                        &self.customers,
+                        &self.item_names,
+                        id.map_or_else(Vec::new, |id| self.linked_to(id)),
+                        id.is_some_and(|id| self.card_waits.contains_key(&id)),
+                    ),
+                    None => sale::loading(),
+                }
+                .map(|msg| Message::Sale(*id, msg))
+            }
+        };
+
+        let screen = if self.shortcuts {
+            let sheet = dialog::view(
+                Space::new(Fill, Fill),
+                shortcut::view(
+                    &self.screen,
+                    &self.settings.config.shortcuts,
+                    (),
+                ),
+                (),
+            )
+            .map(|()| Message::CloseShortcuts);
+            stack![screen, sheet].into()
+        } else {
+            screen
+        };
+
+        self.toasts.view(screen, Message::DismissToast)
+    }
+
+    /// Go to `screen`, setting it up first where it needs it.
+    fn navigate(&mut self, screen: Screen) -> Task<Message> {
+        self.screen = screen;
+        match screen {
+            Screen::Settings => {
+                self.settings.backups = backup::list();
+                self.settings.selected_backup =
+                    self.settings.backups.first().cloned();
+            }
+            Screen::Report => {
+                self.report.format = self.settings.config.store_format;
+                self.report.tips = self.settings.config.tip_pool.clone();
+                let action = report::run(&mut self.report)
+                    .map_instruction(Instruction::Report)
+                    .map(Message::Report);
+                return self.run(action);
+            }
+            Screen::GiftCards => {
+                self.card_lookup = gift_card::lookup::Lookup::default();
+            }
+            Screen::Drawer => {
+                self.till = drawer::manage::Form::default();
+            }
+            Screen::Shifts => {
+                self.roster = shift::Form {
+                    format: self.settings.config.store_format,
+                    ..shift::Form::default()
+                };
+                return shift::load(&self.shifts, &self.roster)
+                    .map(Message::Shifts);
+            }
+            Screen::Recurring => {
+                self.recurring_form = recurring::Form::default();
+            }
+            Screen::Trash => {
+                self.trash_form = trash::Form::default();
+                return self.purge_trash();
+            }
+            Screen::Customers | Screen::Customer(_) => {
+                self.customer_index = customer::Index::new(&self.headers());
+            }
+            Screen::QuickSale => {
+                self.start_draft(None, Sale::default());
+                self.quick_input.clear();
+                return text_input::focus(sale::quick::input_id());
+            }
+            Screen::Return => {
+                self.refund = returns::Return::default();
+            }
+            Screen::Layout => {
+                self.layout_editor =
+                    layout::Editor::new(&self.settings.config.receipt_layout);
+            }
+            Screen::List
+            | Screen::Sale(..)
+            | Screen::Catalog
+            | Screen::QuickButtons
+            | Screen::Diagnostics
+            | Screen::Conflict => {}
+        }
+        Task::none()
+    }
+
+    /// Carry out what a screen asked for: its instruction, its toast and its
+    /// task.
+    fn run(&mut self, action: Action<Instruction, Message>) -> Task<Message> {
+        let toast = match action.toast {
+            Some((kind, message)) => self.toast(kind, message),
+            None => Task::none(),
+        };
+        let instruction_task = match action.instruction {
+            Some(instruction) => self.perform(instruction),
+            None => Task::none(),
+        };
+
+        Task::batch([instruction_task.chain(action.task), toast])
+    }
+
+    fn perform(&mut self, instruction: Instruction) -> Task<Message> {
+        tracing::debug!(?instruction);
+        match instruction {
+            Instruction::Sale(sale_id, instruction) => match instruction {
+                sale::Instruction::Back => match self.screen {
+                    Screen::List
+                    | Screen::Settings
+                    | Screen::Report
+                    | Screen::GiftCards
+                    | Screen::Catalog
+                    | Screen::Drawer
+                    | Screen::Shifts
+                    | Screen::Recurring
+                    | Screen::Customers
+                    | Screen::Customer(_)
+                    | Screen::QuickSale
+                    | Screen::Return
+                    | Screen::Layout
+                    | Screen::QuickButtons
+                    | Screen::Diagnostics
+                    | Screen::Trash
+                    | Screen::Conflict => {}
+                    Screen::Sale(mode, _) => match mode {
+                        sale::Mode::Edit => {
+                            self.screen =
+                                Screen::Sale(sale::Mode::View, sale_id)
+                        }
+                        sale::Mode::View => self.screen = Screen::List,
+                    },
+                },
+                sale::Instruction::Save => return self.save_draft(false),
+                sale::Instruction::ApproveOverrides(pin) => {
+                    if !self.settings.config.manager_pin.matches(&pin.0) {
+                        return self
+                            .toast(toast::Kind::Error, "Wrong manager PIN");
+                    }
+                    self.sale_form.1.approval_pin = None;
+                    return self.save_draft(true);
+                }
+                // Only once the checks of the edit screen passed
+                sale::Instruction::SaveAndClose => {
+                    let task = self.perform(Instruction::Sale(
+                        sale_id,
+                        sale::Instruction::Save,
+                    ));
+                    // Saving leaves the edit screen unless it was refused
+                    if let Screen::Sale(sale::Mode::View, _) = self.screen {
+                        self.screen = Screen::List;
+                    }
+                    return task;
+                }
+                sale::Instruction::RetrySave => {
+                    let task = self.save_store(Message::Saved);
+                    for status in self.saves.values_mut() {
+                        *status =
+                            store::SaveStatus::Saving(self.store_revision);
+                    }
+                    return task;
+                }
+                sale::Instruction::StartEdit => {
+                    if let Some(id) = sale_id {
+                        // Start editing existing sale
+                        self.start_draft(Some(id), self.sales[&id].clone());
+                    }
+                    self.screen = Screen::Sale(sale::Mode::Edit, sale_id);
+                }
+                sale::Instruction::Cancel => {
+                    // Discard the draft; an existing sale is shown as stored
+                    self.draft = (None, Sale::default());
+                    self.screen = Screen::Sale(sale::Mode::View, sale_id);
+                }
+                sale::Instruction::Delete => {
+                    let id = sale_id.unwrap();
+                    let Some(sale) = self.sales.remove(&id) else {
+                        return Task::none();
+                    };
+                    self.trash.put(id, sale, Utc::now());
+                    self.saves.remove(&id);
+                    self.screen = Screen::List;
+                    return self.save_trash(TrashChange::Deleted(id));
+                }
+                sale::Instruction::CopyTicket => {
+                    let sale = if self.draft.0 == sale_id {
+                        &self.draft.1
+                    } else {
+                        &self.sales[&sale_id.unwrap()]
+                    };
+                    return iced::clipboard::write(ticket::kitchen(
+                        sale_id, sale,
+                    ));
+                }
+                sale::Instruction::CopyReceipt => {
+                    let sale = if self.draft.0 == sale_id {
+                        &self.draft.1
+                    } else {
+                        &self.sales[&sale_id.unwrap()]
+                    };
+                    return iced::clipboard::write(ticket::configured_receipt(
+                        sale_id,
+                        sale,
+                        &self.settings.config,
+                    ));
+                }
+                sale::Instruction::CopyJson => {
+                    let id = sale_id.unwrap();
+                    let export = data::single(id, &self.sales[&id]);
+                    return match data::to_json(&export) {
+                        Ok(json) => {
+                            iced::clipboard::write(json).chain(self.toast(
+                                toast::Kind::Success,
+                                "Copied the sale as JSON",
+                            ))
+                        }
+                        Err(e) => self.toast(
+                            toast::Kind::Error,
+                            format!("Export failed: {e}"),
+                        ),
+                    };
+                }
+                sale::Instruction::SaveJson => {
+                    let id = sale_id.unwrap();
+                    let export = data::single(id, &self.sales[&id]);
+                    let dir = config::data_dir().join("exports");
+                    let path = dir.join(format!("sale-{id}.json"));
+                    let exported =
+                        format!("Saved the sale to {}", path.display());
+                    let progress = self
+                        .toasts
+                        .progress(format!("Saving to {}…", path.display()));
+                    let write = async move {
+                        std::fs::create_dir_all(&dir)?;
+                        data::write(&path, &export).map(|_| exported)
+                    };
+                    return Task::perform(write, move |result| {
+                        Message::Exported(progress, result)
+                    });
+                }
+                sale::Instruction::Print => {
+                    let id = sale_id.unwrap();
+                    let sale = &self.sales[&id];
+                    let receipt = i18n::with(self.receipt_locale(sale), || {
+                        ticket::printed_receipt(
+                            sale_id,
+                            sale,
+                            &self.settings.config,
+                        )
+                    });
+                    let logo = self.settings.config.logo.clone();
+                    let title = format!("Receipt #{id}");
+                    let progress =
+                        self.toasts.progress("Opening the print dialog…");
+                    let print = async move {
+                        print::open(
+                            &format!("receipt-{id}"),
+                            print::html(&title, &receipt, logo.as_ref()),
+                        )
+                    };
+                    return Task::perform(print, move |result| {
+                        Message::Printed(progress, id, result)
+                    });
+                }
+                sale::Instruction::EmailReceipt => {
+                    let id = sale_id.unwrap();
+                    let sale = &self.sales[&id];
+                    let Some(to) = sale
+                        .customer
+                        .and_then(|customer| self.customers.get(customer))
+                        .map(|customer| customer.email.trim().to_string())
+                        .filter(|email| !email.is_empty())
+                    else {
+                        return self.toast(
+                            toast::Kind::Error,
+                            "The customer of the sale has no email address",
+                        );
+                    };
+                    let receipt = i18n::with(self.receipt_locale(sale), || {
+                        ticket::configured_receipt(
+                            sale_id,
+                            sale,
+                            &self.settings.config,
+                        )
+                    });
+                    return self.send(outbox::Job::SendReceipt {
+                        sale: id,
+                        to,
+                        receipt,
+                    });
+                }
+                sale::Instruction::TakeCash => {
+                    let id = sale_id.unwrap();
+                    let due = self.sales[&id].calculate_due();
+                    let note = format!("Sale #{id}");
+                    if !self.drawer.record(drawer::Kind::Sale(id), due, note) {
+                        return self.toast(
+                            toast::Kind::Error,
+                            "Start the day on the cash drawer screen first",
+                        );
+                    }
+                    if let Some(sale) = self.sales.get_mut(&id) {
+                        sale.cash = Some(due);
+                    }
+                    self.hooks.dispatch(hook::Event::PaymentTaken {
+                        id,
+                        payment: hook::Payment::Cash(due),
+                    });
+                    return self
+                        .save_drawer()
+                        .chain(self.kick())
+                        .chain(self.sale_changed(id))
+                        .chain(self.fiscalize(id));
+                }
+                sale::Instruction::TakeForeign(code, tendered) => {
+                    let id = sale_id.unwrap();
+                    let due = self.sales[&id].calculate_due();
+                    let Some(payment) = currency::Payment::new(
+                        &self.settings.config.currency,
+                        &code,
+                        tendered,
+                        due,
+                    ) else {
+                        return self.toast(
+                            toast::Kind::Error,
+                            format!(
+                                "{tendered:.2} {code} doesn't cover the \
+                                 ${due:.2} due"
+                            ),
+                        );
+                    };
+                    let change = payment.change > 0.0;
+                    if change
+                        && !self.drawer.record(
+                            drawer::Kind::PaidOut,
+                            payment.change,
+                            format!("Change for sale #{id}, paid in {code}"),
+                        )
+                    {
+                        return self.toast(
+                            toast::Kind::Error,
+                            "Start the day on the cash drawer screen first",
+                        );
+                    }
+                    if let Some(sale) = self.sales.get_mut(&id) {
+                        sale.foreign = Some(payment.clone());
+                    }
+                    self.sale_form.1.foreign_entry = currency::Entry::default();
+                    self.hooks.dispatch(hook::Event::PaymentTaken {
+                        id,
+                        payment: hook::Payment::Foreign(payment),
+                    });
+                    if !change {
+                        return self.sale_changed(id).chain(self.fiscalize(id));
+                    }
+                    return self
+                        .save_drawer()
+                        .chain(self.kick())
+                        .chain(self.sale_changed(id))
+                        .chain(self.fiscalize(id));
+                }
+                sale::Instruction::ReadScale(id) => {
+                    let sale = if self.draft.0 == sale_id {
+                        &self.draft.1
+                    } else {
+                        return Task::none();
+                    };
+                    let Some(item) = sale.items.iter().find(|i| i.id == id)
+                    else {
+                        return Task::none();
+                    };
+                    let port = self.settings.config.scale.port.clone();
+                    return Task::perform(
+                        scale::weigh(port, item.name.clone()),
+                        move |result| Message::ScaleRead(sale_id, id, result),
+                    );
+                }
+                sale::Instruction::PayByCard => {
+                    let id = sale_id.unwrap();
+                    let due = self.sales[&id].calculate_due();
+                    let config = self.settings.config.terminal.clone();
+                    return Task::perform(
+                        terminal::start(config, due),
+                        move |result| Message::CardStarted(id, result),
+                    );
+                }
+                sale::Instruction::CancelCard => {
+                    let id = sale_id.unwrap();
+                    if let Some(pending) = self.card_waits.remove(&id) {
+                        return self.send(outbox::Job::CancelCard(
+                            pending.provider,
+                            pending.id,
+                        ));
+                    }
+                }
+                sale::Instruction::Open(id) => return self.show_sale(id),
+                sale::Instruction::Reorder => {
+                    let id = sale_id.unwrap();
+                    self.start_draft(None, self.sales[&id].reorder(id));
+                    self.screen = Screen::Sale(sale::Mode::Edit, None);
+                    return sale::edit::focus_form(&self.draft.1);
+                }
+                sale::Instruction::ApplyTender(tender_id) => {
+                    let stored = sale_id.and_then(|id| self.sales.get(&id));
+                    let draft = &self.draft.1;
+                    let Some(tender) =
+                        draft.tenders.iter().find(|t| t.id == tender_id)
+                    else {
+                        return Task::none();
+                    };
+                    let code = gift_card::canonical(&tender.code);
+                    let due = draft.calculate_due() + tender.amount;
+
+                    let amount = match self
+                        .gift_cards
+                        .available(&code, stored, draft, tender_id)
+                    {
+                        None => {
+                            return self.toast(
+                                toast::Kind::Error,
+                                format!("No gift card with code {code}"),
+                            )
+                        }
+                        Some(available) if available <= 0.0 => {
+                            return self.toast(
+                                toast::Kind::Error,
+                                format!("Gift card {code} has no balance left"),
+                            )
+                        }
+                        Some(available) => available.min(due).max(0.0),
+                    };
+
+                    if let Some(tender) = self
+                        .draft
+                        .1
+                        .tenders
+                        .iter_mut()
+                        .find(|t| t.id == tender_id)
+                    {
+                        tender.code = code;
+                        tender.amount = amount;
+                    }
+                }
+                sale::Instruction::SaveAsTemplate => {
+                    let sale = &self.sales[&sale_id.unwrap()];
+                    let saved = format!(
+                        "Saved template \"{}\"",
+                        self.templates
+                            .add(sale.name.clone(), sale)
+                            .display_name()
+                    );
+                    return self
+                        .save_templates()
+                        .chain(self.toast(toast::Kind::Success, saved));
+                }
+                sale::Instruction::Void { item, reason, pin } => {
+                    let id = sale_id.unwrap();
+                    let manager_pin = &self.settings.config.manager_pin;
+                    let Some(sale) = self.sales.get_mut(&id) else {
+                        return Task::none();
+                    };
+                    if !manager_pin.matches(&pin.0) {
+                        if let Some(input) = &mut self.sale_form.1.void_input {
+                            input.pin.clear();
+                        }
+                        return self
+                            .toast(toast::Kind::Error, "Wrong manager PIN");
+                    }
+                    self.sale_form.1.void_input = None;
+                    let stored = sale.clone();
+                    let Some(voided) = sale.void(item, &reason, Utc::now())
+                    else {
+                        return Task::none();
+                    };
+                    // What the voided line took from stock goes back
+                    self.catalog.settle(Some(&stored), sale);
+                    let change = format!(
+                        "Voided {} x {} (${:.2}): {reason}",
+                        voided.quantity_label(),
+                        voided.name,
+                        voided.total()
+                    );
+                    if let Err(e) = audit::record(id, &change) {
+                        tracing::warn!("could not record the void: {e}");
+                    }
+                    let stock_saved =
+                        self.persist("stock counts", self.catalog.save());
+                    self.publish(live::Kind::Updated, id);
+                    return self
+                        .sale_changed(id)
+                        .chain(stock_saved)
+                        .chain(self.toast(toast::Kind::Success, change));
+                }
+                sale::Instruction::AdjustGratuity { to, pin } => {
+                    let id = sale_id.unwrap();
+                    let manager_pin = &self.settings.config.manager_pin;
+                    match pin {
+                        None if manager_pin.is_set() => {
+                            self.sale_form.1.gratuity_pin = Some(String::new());
+                            return text_input::focus(
+                                sale::show::gratuity_pin_id(),
+                            );
+                        }
+                        Some(pin) if !manager_pin.matches(&pin.0) => {
+                            self.sale_form.1.gratuity_pin = Some(String::new());
+                            return self.toast(
+                                toast::Kind::Error,
+                                "Wrong manager PIN",
+                            );
+                        }
+                        _ => {}
+                    }
+                    let approved = manager_pin.is_set();
+                    let Some(sale) = self.sales.get_mut(&id) else {
+                        return Task::none();
+                    };
+                    let from = sale.gratuity_amount.unwrap_or(0.0);
+                    sale.gratuity_amount = Some(to);
+                    self.sale_form.1.gratuity_input = None;
+                    self.sale_form.1.gratuity_pin = None;
+
+                    let change =
+                        format!("Gratuity changed from ${from:.2} to ${to:.2}");
+                    let record = if approved {
+                        format!("{change}, approved by a manager")
+                    } else {
+                        change.clone()
+                    };
+                    let recorded = match audit::record(id, &record) {
+                        Ok(()) => self.toast(toast::Kind::Success, change),
+                        Err(e) => self.toast(
+                            toast::Kind::Error,
+                            format!("Could not record the change: {e}"),
+                        ),
+                    };
+                    self.publish(live::Kind::Updated, id);
+                    return self.sale_changed(id).chain(recorded);
+                }
+                sale::Instruction::PaymentRecorded(amount) => {
+                    let id = sale_id.unwrap();
+                    let balance = self.sales[&id].calculate_balance();
+                    let change = format!(
+                        "Payment of ${amount:.2} recorded, ${balance:.2} left"
+                    );
+                    return match audit::record(id, &change) {
+                        Ok(()) => self.toast(toast::Kind::Success, change),
+                        Err(e) => self.toast(
+                            toast::Kind::Error,
+                            format!("Could not record the payment: {e}"),
+                        ),
+                    };
+                }
+                sale::Instruction::ExportStatement => {
+                    let id = sale_id.unwrap();
+                    let customer = self.sales[&id]
+                        .invoice
+                        .as_ref()
+                        .map(|invoice| invoice.customer.trim().to_string())
+                        .unwrap_or_default();
+                    if customer.is_empty() {
+                        return self.toast(
+                            toast::Kind::Error,
+                            "Give the invoice a customer first",
+                        );
+                    }
+                    let format = self.settings.config.store_format;
+                    let partial = self.partial();
+                    let dir = config::data_dir().join("exports");
+                    let path = dir.join(invoice::file_name(&customer));
+                    let exported = format!(
+                        "Saved the statement of {customer} to {}",
+                        path.display()
+                    );
+                    let progress = self
+                        .toasts
+                        .progress(format!("Saving to {}…", path.display()));
+                    let write = async move {
+                        let export = store::complete(format, partial).await?;
+                        let mut sales: Vec<_> =
+                            export.sales.into_iter().collect();
+                        archive::scan(query::Range::default(), |id, sale| {
+                            sales.push((id, sale))
+                        })?;
+                        std::fs::create_dir_all(&dir)?;
+                        std::fs::write(
+                            &path,
+                            invoice::statement(
+                                &customer,
+                                sales.iter().map(|(id, sale)| (*id, sale)),
+                            ),
+                        )?;
+                        Ok(exported)
+                    };
+                    return Task::perform(write, move |result| {
+                        Message::Exported(progress, result)
+                    });
+                }
+                sale::Instruction::TogglePreview => {
+                    self.settings.config.receipt_preview =
+                        !self.settings.config.receipt_preview;
+                    return self.save_config();
+                }
+                sale::Instruction::ShowBoard => {
+                    if self.board_window.is_none() {
+                        let (id, open) = window::open(window::Settings {
+                            size: Size::new(800.0, 400.0),
+                            ..window::Settings::default()
+                        });
+                        self.board_window = Some(id);
+                        return open.discard();
+                    }
+                }
+            },
+            Instruction::Report(report::Instruction::Export(
+                format,
+                range,
+                path,
+            )) => {
+                let store_format = self.settings.config.store_format;
+                let accounts = self.settings.config.accounts.clone();
+                let progress = self
+                    .toasts
+                    .progress(format!("Exporting to {}…", path.display()));
+                let write = async move {
+                    let journals = accounting::journals(store_format, range)?;
+                    std::fs::write(
+                        &path,
+                        accounting::write(&journals, format, &accounts),
+                    )?;
+                    Ok(format!(
+                        "Exported {} day(s) to {}",
+                        journals.len(),
+                        path.display()
+                    ))
+                };
+                return Task::perform(write, move |result| {
+                    Message::Exported(progress, result)
+                });
+            }
+            Instruction::Report(report::Instruction::SaveTips(config)) => {
+                self.settings.config.tip_pool = config;
+                return self.save_config();
+            }
+            Instruction::Report(report::Instruction::ExportPool(
+                pool,
+                path,
+            )) => {
+                let payouts = pool.payouts(&self.settings.config.tip_pool);
+                let progress = self
+                    .toasts
+                    .progress(format!("Exporting to {}…", path.display()));
+                let write = async move {
+                    std::fs::write(&path, tips::csv(&pool, &payouts))?;
+                    Ok(format!(
+                        "Exported the payouts of {} to {}",
+                        pool.day,
+                        path.display()
+                    ))
+                };
+                return Task::perform(write, move |result| {
+                    Message::Exported(progress, result)
+                });
+            }
+            Instruction::Report(report::Instruction::Navigate(screen))
+            | Instruction::GiftCards(
+                gift_card::lookup::Instruction::Navigate(screen),
+            )
+            | Instruction::Diagnostics(diagnostics::Instruction::Navigate(
+                screen,
+            )) => return self.navigate(screen),
+            Instruction::Catalog(instruction) => match instruction {
+                catalog::manage::Instruction::Navigate(screen) => {
+                    return self.navigate(screen)
+                }
+                catalog::manage::Instruction::Save => {
+                    return self.persist("the catalog", self.catalog.save());
+                }
+            },
+            Instruction::Drawer(instruction) => match instruction {
+                drawer::manage::Instruction::Navigate(screen) => {
+                    return self.navigate(screen)
+                }
+                drawer::manage::Instruction::Save => return self.save_drawer(),
+                drawer::manage::Instruction::CashMoved => {
+                    return self.save_drawer().chain(self.kick())
+                }
+                drawer::manage::Instruction::Kick => return self.kick(),
+                drawer::manage::Instruction::DayClosed => {
+                    if let Some(day) = self.drawer.history().next() {
+                        self.hooks.dispatch(hook::Event::DayClosed {
+                            day: day.clone(),
+                        });
+                    }
+                    return self.save_drawer();
+                }
+            },
+            Instruction::Shifts(instruction) => match instruction {
+                shift::Instruction::Navigate(screen) => {
+                    return self.navigate(screen)
+                }
+                shift::Instruction::Save => {
+                    return self.persist("shifts", self.shifts.save());
+                }
+            },
+            Instruction::QuickSale(instruction) => match instruction {
+                sale::quick::Instruction::Navigate(screen) => {
+                    return self.navigate(screen);
+                }
+                sale::quick::Instruction::Save => {
+                    let saved = self.perform(Instruction::Sale(
+                        None,
+                        sale::Instruction::Save,
+                    ));
+                    // Saving shows the sale, where it is paid for
+                    return saved
+                        .chain(text_input::focus(sale::show::tendered_id()));
+                }
+            },
+            Instruction::Customers(instruction) => match instruction {
+                customer::Instruction::Navigate(screen) => {
+                    return self.navigate(screen);
+                }
+                customer::Instruction::Save => {
+                    return self.persist("customers", self.customers.save());
+                }
+                customer::Instruction::OpenSale(id) => {
+                    return self.show_sale(id);
+                }
+                customer::Instruction::Export(id) => {
+                    let Some(customer) = self.customers.get(id) else {
+                        return Task::none();
+                    };
+                    let csv = customer::export_csv(&self.customer_sales(id));
+                    let dir = config::data_dir().join("exports");
+                    let path = dir.join(customer.file_name());
+                    let exported = format!(
+                        "Saved the history of {} to {}",
+                        customer.display_name(),
+                        path.display()
+                    );
+                    let progress = self
+                        .toasts
+                        .progress(format!("Saving to {}…", path.display()));
+                    let write = async move {
+                        std::fs::create_dir_all(&dir)?;
+                        std::fs::write(&path, csv)?;
+                        Ok(exported)
+                    };
+                    return Task::perform(write, move |result| {
+                        Message::Exported(progress, result)
+                    });
+                }
+            },
+            Instruction::Conflict(instruction) => match instruction {
+                sale::conflict::Instruction::Navigate(screen) => {
+                    return self.navigate(screen);
+                }
+                sale::conflict::Instruction::Back => {
+                    let Some(conflict) = self.conflict.take() else {
+                        return self.navigate(Screen::List);
+                    };
+                    // Back to the draft held back, if it was one
+                    let mode = if self.draft.0 == Some(conflict.id) {
+                        sale::Mode::Edit
+                    } else {
+                        sale::Mode::View
+                    };
+                    return self
+                        .navigate(Screen::Sale(mode, Some(conflict.id)));
+                }
+                sale::conflict::Instruction::Resolve(resolution) => {
+                    let Some(conflict) = self.conflict.take() else {
+                        return Task::none();
+                    };
+                    let id = conflict.id;
+                    // Saved past both sides, whichever is kept
+                    let revision =
+                        conflict.mine.revision.max(conflict.theirs.revision);
+                    let mut sale = match resolution {
+                        sale::conflict::Resolution::Keep(
+                            sale::conflict::Side::Theirs,
+                        ) => {
+                            self.draft = (None, Sale::default());
+                            let mut theirs = conflict.theirs;
+                            theirs.revision = revision;
+                            self.sales.insert(id, theirs.with_fresh_ids());
+                            self.screen =
+                                Screen::Sale(sale::Mode::View, Some(id));
+                            return self.sale_changed(id).chain(self.toast(
+                                toast::Kind::Success,
+                                format!("Kept sale #{id} as changed elsewhere"),
+                            ));
+                        }
+                        sale::conflict::Resolution::Keep(
+                            sale::conflict::Side::Mine,
+                        ) => conflict.mine,
+                        sale::conflict::Resolution::Merge => conflict.merge(),
+                    };
+                    sale.revision = revision;
+                    // Saved over the stored sale now that it was chosen to
+                    self.draft_base = self.sales.get(&id).cloned();
+                    self.draft = (Some(id), sale);
+                    return self.perform(Instruction::Sale(
+                        Some(id),
+                        sale::Instruction::Save,
+                    ));
+                }
+            },
+            Instruction::Trash(instruction) => match instruction {
+                trash::Instruction::Navigate(screen) => {
+                    return self.navigate(screen)
+                }
+                trash::Instruction::Restore(id) => {
+                    let Some(sale) = self.trash.take(id) else {
+                        return Task::none();
+                    };
+                    // Another sale may have been given the id since
+                    let restored = if self.sale_ids().contains(&id) {
+                        self.next_sale_id.fetch_add(1, Ordering::SeqCst)
+                    } else {
+                        id
+                    };
+                    self.sales.insert(restored, sale.with_fresh_ids());
+                    return self
+                        .save_trash(TrashChange::Restored { id, restored });
+                }
+                trash::Instruction::Purge(ids) => {
+                    return self.purge(&ids);
+                }
+            },
+            Instruction::Recurring(instruction) => match instruction {
+                recurring::Instruction::Navigate(screen) => {
+                    return self.navigate(screen)
+                }
+                recurring::Instruction::Save => {
+                    return self
+                        .save_recurrences()
+                        .chain(self.make_recurring());
+                }
+            },
+            Instruction::QuickButtons(instruction) => match instruction {
+                quick_buttons::Instruction::Navigate(screen) => {
+                    return self.navigate(screen)
+                }
+                quick_buttons::Instruction::Save(config) => {
+                    self.settings.config.quick_buttons = config;
+                    return self.save_config();
+                }
+            },
+            Instruction::Layout(instruction) => match instruction {
+                layout::Instruction::Navigate(screen) => {
+                    return self.navigate(screen)
+                }
+                layout::Instruction::Save(source) => {
+                    self.settings.config.receipt_layout = source;
+                    return self.save_config();
+                }
+            },
+            Instruction::Return(instruction) => match instruction {
+                returns::Instruction::Navigate(screen) => {
+                    return self.navigate(screen)
+                }
+                returns::Instruction::Find(id) => {
+                    if self.sales.contains_key(&id) {
+                        self.refund.found = Some(id);
+                    } else if self.headers.contains_key(&id) {
+                        self.refund.found = Some(id);
+                        return self.fetch(id);
+                    } else {
+                        return self.toast(
+                            toast::Kind::Error,
+                            format!("No sale with receipt number {id}"),
+                        );
+                    }
+                }
+                returns::Instruction::Create(id, quantities) => {
+                    let Some(original) = self.sales.get(&id) else {
+                        return Task::none();
+                    };
+                    let mut refund = original.returned(id, &quantities);
+                    refund.shift = self.shifts.on_till();
+                    self.catalog.settle(None, &refund);
+                    let stock_saved =
+                        self.persist("stock counts", self.catalog.save());
+
+                    let new_id =
+                        self.next_sale_id.fetch_add(1, Ordering::SeqCst);
+                    self.sales.insert(new_id, refund);
+                    self.publish(live::Kind::Created, new_id);
+                    self.screen = Screen::Sale(sale::Mode::View, Some(new_id));
+                    return Task::batch([
+                        self.sale_changed(new_id),
+                        stock_saved,
+                    ]);
+                }
+            },
+            Instruction::Settings(instruction) => match instruction {
+                settings::Instruction::Navigate(screen) => {
+                    return self.navigate(screen)
+                }
+                settings::Instruction::Export(path) => {
+                    let format = self.settings.config.store_format;
+                    let partial = store::Partial {
+                        modified_at: Some(Utc::now()),
+                        ..self.partial()
+                    };
+                    let exported = format!(
+                        "Exported {} sale(s) to {}",
+                        self.sale_ids().len(),
+                        path.display()
+                    );
+                    let progress = self
+                        .toasts
+                        .progress(format!("Exporting to {}…", path.display()));
+                    let write = async move {
+                        let export = store::complete(format, partial).await?;
+                        data::write(&path, &export).map(|_| exported)
+                    };
+                    return Task::perform(write, move |result| {
+                        Message::Exported(progress, result)
+                    });
+                }
+                settings::Instruction::ExportCsv(path) => {
+                    let format = self.settings.config.store_format;
+                    let partial = self.partial();
+                    let fields = self.settings.config.custom_fields.clone();
+                    let exported = format!(
+                        "Exported {} sale(s) to {}",
+                        self.sale_ids().len(),
+                        path.display()
+                    );
+                    let progress = self
+                        .toasts
+                        .progress(format!("Exporting to {}…", path.display()));
+                    let write = async move {
+                        let export = store::complete(format, partial).await?;
+                        data::export_csv(&path, &export.sales, &fields)
+                            .map(|_| exported)
+                    };
+                    return Task::perform(write, move |result| {
+                        Message::Exported(progress, result)
+                    });
+                }
+                settings::Instruction::ValidateImport(path) => {
+                    return Task::perform(
+                        async move { data::read(&path) },
+                        Message::ImportRead,
+                    );
+                }
+                settings::Instruction::LoadLogo(path) => {
+                    return Task::perform(
+                        logo::import(path),
+                        Message::LogoLoaded,
+                    );
+                }
+                settings::Instruction::RemoveLogo => {
+                    if let Err(e) = logo::remove() {
+                        return self.toast(
+                            toast::Kind::Error,
+                            format!("Could not remove the logo: {e}"),
+                        );
+                    }
+                    self.settings.config.logo = None;
+                }
+                settings::Instruction::FetchRates => {
+                    return self.fetch_rates(true);
+                }
+                settings::Instruction::ImportSale(path) => {
+                    return Task::perform(
+                        async move { data::read(&path) },
+                        Message::SaleImportRead,
+                    );
+                }
+                settings::Instruction::ApplyImport => {
+                    if let Some(import) = self.settings.pending_import.take() {
+                        // Keep new ids clear of every id in the file
+                        if let Some(max) = import.export.sales.keys().max() {
+                            self.next_sale_id
+                                .fetch_max(max + 1, Ordering::SeqCst);
+                        }
+                        let in_use = self.sale_ids();
+                        let revisions = self.revisions();
+                        let imported = data::apply(
+                            import,
+                            self.settings.conflict,
+                            &mut self.sales,
+                            &in_use,
+                            || self.next_sale_id.fetch_add(1, Ordering::SeqCst),
+                        );
+                        // Overwritten sales are loaded in full now
+                        self.headers
+                            .retain(|id, _| !self.sales.contains_key(id));
+                        self.supersede(&revisions);
+                        return self
+                            .toast(
+                                toast::Kind::Success,
+                                format!("Imported {imported} sale(s)"),
+                            )
+                            .chain(self.sales_changed());
+                    }
+                }
+                settings::Instruction::SaveConfig => {
+                    if !self.settings.config.live_feed.enabled {
+                        // Dropping the bus shuts the server down
+                        self.live = None;
+                    }
+                    return self.save_config();
+                }
+                settings::Instruction::ConvertStore => {
+                    return self
+                        .save_config()
+                        .chain(self.save_store(Message::StoreConverted));
+                }
+                settings::Instruction::BackupNow => return self.back_up(),
+                settings::Instruction::SendSummary => {
+                    let today = Local::now().date_naive();
+                    return self.send(outbox::Job::SendSummary(today));
+                }
+                settings::Instruction::RetryOutbox => {
+                    self.outbox.retry_now();
+                    return self.retry_outbox();
+                }
+                settings::Instruction::Archive => return self.archive_old(),
+                settings::Instruction::RestoreBackup(snapshot) => {
+                    let restore = backup::restore(snapshot.clone());
+                    return Task::perform(restore, move |result| {
+                        Message::Restored(snapshot.clone(), result)
+                    });
+                }
+            },
+        }
+        Task::none()
+    }
+
+    /// Save the draft over its sale, or as a new one, unless it changed
+    /// elsewhere, or needs price overrides `approved` first.
+    fn save_draft(&mut self, approved: bool) -> Task<Message> {
+        // Never save silently over changes made elsewhere
+        if let Some(conflict) = self.draft_conflict() {
+            tracing::warn!(
+                sale = conflict.id,
+                "the sale changed while it was edited"
+            );
+            self.conflict = Some(conflict);
+            self.screen = Screen::Conflict;
+            return Task::none();
+        }
+
+        let stored = self.draft.0.and_then(|id| self.sales.get(&id));
+        let overrides = self.draft.1.overrides_since(stored);
+        let limit = self
+            .settings
+            .config
+            .override_approval
+            .filter(|_| self.settings.config.manager_pin.is_set());
+        if !approved
+            && limit.is_some_and(|limit| {
+                overrides
+                    .iter()
+                    .any(|item| item.overrides_by_more_than(limit))
+            })
+        {
+            self.sale_form.1.approval_pin = Some(String::new());
+            return text_input::focus(sale::edit::approval_pin_id());
+        }
+        let overrides: Vec<String> = overrides
+            .into_iter()
+            .filter_map(|item| {
+                let (list, price) = item.price_override()?;
+                Some(format!(
+                    "Price of {} changed from ${list:.2} to ${price:.2}{}",
+                    item.name,
+                    if approved { ", approved" } else { "" }
+                ))
+            })
+            .collect();
+        let short = self
+            .catalog
+            .shortfalls(stored, &self.draft.1)
+            .iter()
+            .map(|product| product.display_name())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let stock_warning = if short.is_empty() {
+            Task::none()
+        } else {
+            match self.settings.config.zero_stock {
+                catalog::ZeroStock::Block => {
+                    return self.toast(
+                        toast::Kind::Error,
+                        format!("Not enough in stock: {short}"),
+                    );
+                }
+                catalog::ZeroStock::Warn => self.toast(
+                    toast::Kind::Warning,
+                    format!("Sold more than in stock: {short}"),
+                ),
+            }
+        };
+
+        self.draft.1.settle_tenders();
+        let draft_id = self
+            .draft
+            .0
+            .unwrap_or_else(|| self.next_sale_id.load(Ordering::SeqCst));
+        let stored = self.draft.0.and_then(|id| self.sales.get(&id));
+        self.gift_cards.settle(draft_id, stored, &self.draft.1);
+        self.catalog.settle(stored, &self.draft.1);
+        let cards_saved = self.persist("gift cards", self.gift_cards.save());
+        let stock_saved = self.persist("stock counts", self.catalog.save());
+        self.item_names.record(&self.draft.1);
+        let names_saved = self.persist("item names", self.item_names.save());
+
+        let final_id = match self.draft.0 {
+            Some(id) => {
+                // Editing existing sale, saved past its revision
+                let stored =
+                    self.sales.get(&id).map_or(0, |sale| sale.revision);
+                self.draft.1.revision = self.draft.1.revision.max(stored);
+                self.sales.insert(id, std::mem::take(&mut self.draft.1));
+                self.draft.0 = None;
+                self.publish(live::Kind::Updated, id);
+                id
+            }
+            None => {
+                // Creating new sale
+                let new_id = self.next_sale_id.fetch_add(1, Ordering::SeqCst);
+                self.draft.1.created_at = Utc::now();
+                self.draft.1.shift = self.shifts.on_till();
+                self.draft.1.revision = 0;
+                self.sales.insert(new_id, std::mem::take(&mut self.draft.1));
+                self.draft.1 = Sale::default();
+                self.publish(live::Kind::Created, new_id);
+                new_id
+            }
+        };
+        self.screen = Screen::Sale(sale::Mode::View, Some(final_id));
+        for change in overrides {
+            if let Err(e) = audit::record(final_id, change) {
+                tracing::warn!("could not record the price override: {e}");
+            }
+        }
+
+        self.saves_since_backup += 1;
+        let backup =
+            if self.saves_since_backup >= self.settings.config.backup_every {
+                self.back_up()
+            } else {
+                Task::none()
+            };
+        Task::batch([
+            self.sale_changed(final_id),
+            backup,
+            cards_saved,
+            stock_saved,
+            names_saved,
+            stock_warning,
+        ])
+    }
+
+    /// Persist the sales after they changed and sync them if enabled.
+    fn sales_changed(&mut self) -> Task<Message> {
+        self.modified_at = Some(Utc::now());
+        self.customer_index = customer::Index::new(&self.headers());
+        Task::batch([self.save_store(Message::Saved), self.sync()])
+    }
+
+    /// Persist a sale after it changed, showing it as saving until the write
+    /// is confirmed.
+    fn sale_changed(&mut self, id: usize) -> Task<Message> {
+        if let Some(sale) = self.sales.get_mut(&id) {
+            sale.revision += 1;
+        }
+        let task = self.sales_changed();
+        self.saves
+            .insert(id, store::SaveStatus::Saving(self.store_revision));
+        task
+    }
+
+    /// Write the sales to the store in the background as a new revision.
+    fn save_store(
+        &mut self,
+        on_saved: fn(u64, Result<(), data::Error>) -> Message,
+    ) -> Task<Message> {
+        self.store_revision += 1;
+        let revision = self.store_revision;
+        Task::perform(
+            store::save(
+                self.settings.config.store_format,
+                revision,
+                self.partial(),
+            ),
+            move |result| on_saved(revision, result),
+        )
+    }
+
+    /// Settle the save status of every sale written by `revision`.
+    fn saved(
+        &mut self,
+        revision: u64,
+        result: Result<(), data::Error>,
+    ) -> Task<Message> {
+        match result {
+            Ok(()) => {
+                // Every revision holds all sales, so earlier failures are
+                // fixed by any later write
+                self.saves.retain(|_, status| match *status {
+                    store::SaveStatus::Saving(r) => r > revision,
+                    store::SaveStatus::Failed => false,
+                });
+                self.store_error = None;
+                Task::none()
+            }
+            // Written but for the sales changed elsewhere, which are kept or
+            // saved over once chosen between
+            Err(data::Error::Stale(ids)) => {
+                self.saves.retain(|id, status| {
+                    ids.contains(id)
+                        || match *status {
+                            store::SaveStatus::Saving(r) => r > revision,
+                            store::SaveStatus::Failed => false,
+                        }
+                });
+                for id in &ids {
+                    self.saves.insert(*id, store::SaveStatus::Failed);
+                }
+                self.store_error = None;
+                // Asked about one sale at a time
+                if self.conflict.is_some() {
+                    return Task::none();
+                }
+                let list = ids
+                    .iter()
+                    .map(|id| format!("#{id}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let changed = self.toast(
+                    toast::Kind::Warning,
+                    format!("Not saved, sale changed elsewhere: {list}"),
+                );
+                let Some(&id) = ids.first() else {
+                    return changed;
+                };
+                let fetch = Task::perform(
+                    store::load_sale(self.settings.config.store_format, id),
+                    move |result| {
+                        Message::StoredFetched(id, result.map(Box::new))
+                    },
+                );
+                changed.chain(fetch)
+            }
+            Err(e) => {
+                for status in self.saves.values_mut() {
+                    if let store::SaveStatus::Saving(r) = *status {
+                        if r <= revision {
+                            *status = store::SaveStatus::Failed;
+                        }
+                    }
+                }
+                self.store_failed(format!("Could not save sales: {e}"))
+            }
+        }
+    }
+
+    /// Replace all sales, e.g. with those of a backup or a remote copy.
+    fn replace_sales(&mut self, sales: BTreeMap<usize, Sale>) {
+        let revisions = self.revisions();
+        self.list_grouped = None;
+        self.sales = sales
+            .into_iter()
+            .map(|(id, sale)| (id, sale.with_fresh_ids()))
+            .collect();
+        self.headers.clear();
+        self.pickups.clear();
+        self.supersede(&revisions);
+        self.refresh_next_sale_id();
+    }
+
+    /// The revision of every sale as last saved, whether loaded or not.
+    fn revisions(&self) -> HashMap<usize, u64> {
+        self.headers
+            .iter()
+            .map(|(id, header)| (*id, header.revision))
+            .chain(self.sales.iter().map(|(id, sale)| (*id, sale.revision)))
+            .collect()
+    }
+
+    /// Move the sales in memory past the `revisions` they replace, as they
+    /// were put in on purpose, e.g. restored from a backup, and the store
+    /// would refuse them as stale otherwise.
+    fn supersede(&mut self, revisions: &HashMap<usize, u64>) {
+        for (id, sale) in &mut self.sales {
+            if let Some(&revision) = revisions.get(id) {
+                if revision > sale.revision {
+                    sale.revision = revision + 1;
+                }
+            }
+        }
+    }
+
+    fn refresh_next_sale_id(&mut self) {
+        let next_id = self
+            .sale_ids()
+            .last()
+            .copied()
+            .max(self.archive.index.last_id)
+            .max(self.trash.last_id())
+            .map_or(1, |id| id + 1);
+        self.next_sale_id.store(next_id, Ordering::SeqCst);
+    }
+
+    /// Move the sales older than set in the settings into the archive, unless
+    /// they are loaded in full, being loaded or on screen.
+    fn archive_old(&self) -> Task<Message> {
+        let Some(days) = self.settings.config.archive_after_days else {
+            return Task::none();
+        };
+        let before = Utc::now() - chrono::Duration::days(days.into());
+        let shown = match self.screen {
+            Screen::Sale(_, id) => id,
+            _ => None,
+        };
+        let keep = self
+            .sales
+            .keys()
+            .chain(&self.fetching)
+            .copied()
+            .chain(shown)
+            .chain(self.draft.0)
+            .collect();
+
+        Task::perform(
+            store::archive(self.settings.config.store_format, before, keep),
+            Message::Archived,
+        )
+    }
+
+    /// The ids of all sales, whether loaded or not.
+    fn sale_ids(&self) -> BTreeSet<usize> {
+        self.sales
+            .keys()
+            .chain(self.headers.keys())
+            .copied()
+            .collect()
+    }
+
+    /// The header of every sale, whether loaded or not.
+    fn headers(&self) -> BTreeMap<usize, store::Header> {
+        self.headers
+            .iter()
+            .map(|(id, header)| (*id, header.clone()))
+            .chain(
+                self.sales
+                    .iter()
+                    .map(|(id, sale)| (*id, store::Header::new(sale))),
+            )
+            .collect()
+    }
+
+    /// The sales of customer `id` in the store, the oldest first.
+    fn customer_sales(&self, id: usize) -> Vec<(usize, store::Header)> {
+        let headers = self.headers();
+        self.customer_index
+            .sales(id)
+            .filter_map(|sale| Some((sale, headers.get(&sale)?.clone())))
+            .collect()
+    }
+
+    /// The language the receipts of `sale` are written in: the one of its
+    /// customer, if they picked one, or else the store's.
+    fn receipt_locale(&self, sale: &Sale) -> i18n::Locale {
+        sale.customer
+            .and_then(|customer| self.customers.get(customer))
+            .and_then(|customer| customer.locale)
+            .unwrap_or_else(i18n::locale)
+    }
+
+    /// The sales in memory, to be merged with the rest of the store.
+    fn partial(&self) -> store::Partial {
+        store::Partial {
+            modified_at: self.modified_at,
+            loaded: self
+                .sales
+                .iter()
+                .map(|(id, sale)| (*id, sale.clone()))
+                .collect(),
+            unloaded: self.headers.keys().copied().collect(),
+        }
+    }
+
+    /// Start editing `sale`, a stored sale if it has an `id`.
+    fn start_draft(&mut self, id: Option<usize>, sale: Sale) {
+        self.draft_base = id.map(|_| sale.clone());
+        self.draft = (id, sale);
+        self.draft
+            .1
+            .apply_service_rules(&self.settings.config.service_rules);
+        self.draft.1.apply_delivery(&self.settings.config.delivery);
+    }
+
+    /// Stop at the first of the sales a sync found saved on both sides, to
+    /// keep a side or merge, unless a sale is being edited.
+    fn diverged(
+        &mut self,
+        conflicts: Vec<sale::conflict::Conflict>,
+    ) -> Task<Message> {
+        let ids = conflicts
+            .iter()
+            .map(|conflict| format!("#{}", conflict.id))
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.sync_status =
+            sync::Status::Failed(format!("Sale changed elsewhere: {ids}"));
+        let changed = self.toast(
+            toast::Kind::Warning,
+            format!("Not synced, sale changed here and elsewhere: {ids}"),
+        );
+
+        let editing = matches!(self.screen, Screen::Sale(sale::Mode::Edit, _))
+            || self.conflict.is_some();
+        let Some(conflict) = conflicts.into_iter().next() else {
+            return changed;
+        };
+        if editing {
+            return changed;
+        }
+        // The local copy is settled against as the stored one
+        if !self.sales.contains_key(&conflict.id) {
+            self.headers.remove(&conflict.id);
+            self.sales.insert(conflict.id, conflict.mine.clone());
+        }
+        self.conflict = Some(conflict);
+        self.screen = Screen::Conflict;
+        changed
+    }
+
+    /// The conflict of saving the draft, if its stored sale was changed
+    /// elsewhere since the draft was taken.
+    fn draft_conflict(&self) -> Option<sale::conflict::Conflict> {
+        let id = self.draft.0?;
+        sale::conflict::Conflict::new(
+            id,
+            self.draft_base.clone()?,
+            self.draft.1.clone(),
+            self.sales.get(&id)?.clone(),
+        )
+    }
+
+    /// Show sale `id`, loading it first if only its header is in memory.
+    fn show_sale(&mut self, id: usize) -> Task<Message> {
+        self.screen = Screen::Sale(sale::Mode::View, Some(id));
+        if self.sales.contains_key(&id) {
+            Task::none()
+        } else {
+            self.fetch(id)
+        }
+    }
+
+    /// Links from other sales to sale `id`, by the id of the other sale.
+    fn linked_to(&self, id: usize) -> Vec<sale::Link> {
+        let links = self
+            .sales
+            .iter()
+            .map(|(other, sale)| (*other, sale.link))
+            .chain(
+                self.headers
+                    .iter()
+                    .map(|(other, header)| (*other, header.link)),
+            );
+
+        let mut linked: Vec<_> = links
+            .filter_map(|(other, link)| {
+                link.filter(|link| link.sale == id).map(|link| sale::Link {
+                    relation: link.relation,
+                    sale: other,
+                })
+            })
+            .collect();
+        linked.sort_by_key(|link| link.sale);
+        linked
+    }
+
+    /// Load a sale in full from the store in the background.
+    fn fetch(&mut self, id: usize) -> Task<Message> {
+        self.fetching.insert(id);
+        Task::perform(
+            store::load_sale(self.settings.config.store_format, id),
+            move |result| Message::Fetched(id, result.map(Box::new)),
+        )
+    }
+
+    /// Make the sales of every recurring run that is due, as open sales.
+    fn make_recurring(&mut self) -> Task<Message> {
+        // New ids are only known once the store is loaded
+        if self.loading {
+            return Task::none();
+        }
+        let due = self.recurrences.take_due(Local::now().date_naive());
+        if due.is_empty() {
+            return Task::none();
+        }
+
+        let mut made = 0;
+        for (template, run) in due {
+            let Some(template) = self.templates.get(template) else {
+                continue;
+            };
+            let mut sale = template.draft();
+            sale.name = format!("{} {run}", template.display_name());
+            if let Some(invoice) = &mut sale.invoice {
+                invoice.issued = run;
+            }
+            sale.apply_service_rules(&self.settings.config.service_rules);
+            sale.apply_delivery(&self.settings.config.delivery);
+            self.catalog.settle(None, &sale);
+
+            let id = self.next_sale_id.fetch_add(1, Ordering::SeqCst);
+            self.sales.insert(id, sale);
+            self.publish(live::Kind::Created, id);
+            made += 1;
+        }
+
+        let saved = self
+            .save_recurrences()
+            .chain(self.persist("stock counts", self.catalog.save()));
+        if made == 0 {
+            return saved;
+        }
+        Task::batch([
+            saved,
+            self.toast(
+                toast::Kind::Success,
+                format!("Made {made} recurring sale(s)"),
+            ),
+            self.sales_changed(),
+        ])
+    }
+
+    /// Write a file beside the store in the background, as given by `save`,
+    /// telling if it fails.
+    fn persist(
+        &self,
+        what: &'static str,
+        save: impl Future<Output = Result<(), data::Error>> + Send + 'static,
+    ) -> Task<Message> {
+        Task::perform(save, move |result| Message::Persisted(what, result))
+    }
+
+    fn save_config(&self) -> Task<Message> {
+        self.persist("settings", self.settings.config.save())
+    }
+
+    fn save_recurrences(&self) -> Task<Message> {
+        self.persist("recurring sales", self.recurrences.save())
+    }
+
+    fn save_templates(&self) -> Task<Message> {
+        self.persist("templates", self.templates.save())
+    }
+
+    fn save_drawer(&self) -> Task<Message> {
+        self.persist("the cash drawer", self.drawer.save())
+    }
+
+    /// Open the cash drawer through the receipt printer, if one is set up.
+    fn kick(&self) -> Task<Message> {
+        let printer = self.settings.config.drawer.printer.clone();
+        Task::perform(async move { drawer::kick(&printer) }, Message::Kicked)
+    }
+
+    /// Run a call to an external service which can be retried from the
+    /// outbox.
+    fn send(&mut self, job: outbox::Job) -> Task<Message> {
+        match job.clone() {
+            outbox::Job::Sync => {
+                if !self.settings.config.sync.is_enabled() {
+                    return self.sent(&job);
+                }
+                self.sync()
+            }
+            outbox::Job::CancelCard(provider, id) => {
+                let config = self.settings.config.terminal.clone();
+                Task::perform(
+                    terminal::cancel(config, provider, id),
+                    move |result| Message::CardCancelled(job.clone(), result),
+                )
+            }
+            outbox::Job::SendSummary(day) => {
+                let config = &self.settings.config;
+                Task::perform(
+                    summary::send(
+                        config.smtp.clone(),
+                        config.summary.to.clone(),
+                        config.store_format,
+                        day,
+                    ),
+                    move |result| Message::SummarySent(job.clone(), result),
+                )
+            }
+            outbox::Job::SendReceipt { sale, to, receipt } => {
+                let locale =
+                    self.sales.get(&sale).map_or_else(i18n::locale, |sale| {
+                        self.receipt_locale(sale)
+                    });
+                let subject = i18n::with(locale, || {
+                    format!("{} #{sale}", i18n::tr("Receipt"))
+                });
+                let mail = mail::Mail {
+                    to,
+                    subject,
+                    body: receipt,
+                };
+                Task::perform(
+                    mail::send(self.settings.config.smtp.clone(), mail),
+                    move |result| Message::ReceiptSent(job.clone(), result),
+                )
+            }
+        }
+    }
+
+    /// Purge the sales deleted longer ago than set in the settings.
+    fn purge_trash(&mut self) -> Task<Message> {
+        let Some(days) = self.settings.config.trash_days else {
+            return Task::none();
+        };
+        let mut trash = self.trash.clone();
+        let purged = trash.purge_older(days, Utc::now());
+        if purged.is_empty() {
+            return Task::none();
+        }
+        tracing::info!(sales = purged.len(), "purging the trash");
+        self.purge(&purged)
+    }
+
+    /// Delete sales `ids` in the trash for good.
+    fn purge(&mut self, ids: &[usize]) -> Task<Message> {
+        let purged = self
+            .trash
+            .iter()
+            .filter(|(id, _)| ids.contains(id))
+            .map(|(id, trashed)| (id, trashed.clone()))
+            .collect();
+        for id in ids {
+            self.trash.take(*id);
+        }
+        self.save_trash(TrashChange::Purged(purged))
+    }
+
+    /// Save the trash in the background after `change`.
+    fn save_trash(&self, change: TrashChange) -> Task<Message> {
+        Task::perform(self.trash.save(), move |result| {
+            Message::TrashSaved(change.clone(), result)
+        })
+    }
+
+    /// Settle `change` to the trash now that it was saved, or undo it.
+    fn trash_saved(
+        &mut self,
+        change: TrashChange,
+        result: Result<(), data::Error>,
+    ) -> Task<Message> {
+        match (change, result) {
+            (TrashChange::Deleted(id), Ok(())) => {
+                if let Err(e) = audit::record(id, "Moved to the trash") {
+                    tracing::warn!("could not record the delete: {e}");
+                }
+                self.sales_changed().chain(self.toast(
+                    toast::Kind::Success,
+                    format!("Moved sale #{id} to the trash"),
+                ))
+            }
+            (TrashChange::Deleted(id), Err(e)) => {
+                if let Some(sale) = self.trash.take(id) {
+                    self.sales.insert(id, sale);
+                }
+                self.toast(
+                    toast::Kind::Error,
+                    format!("Could not move the sale to the trash: {e}"),
+                )
+            }
+            (TrashChange::Restored { id, restored }, Ok(())) => {
+                let change = if restored == id {
+                    "Restored from the trash".to_string()
+                } else {
+                    format!("Restored from the trash, was #{id}")
+                };
+                if let Err(e) = audit::record(restored, change) {
+                    tracing::warn!("could not record the restore: {e}");
+                }
+                self.sale_changed(restored).chain(self.toast(
+                    toast::Kind::Success,
+                    format!("Restored sale #{restored}"),
+                ))
+            }
+            (TrashChange::Restored { id, restored }, Err(e)) => {
+                if let Some(sale) = self.sales.remove(&restored) {
+                    self.trash.put(id, sale, Utc::now());
+                }
+                self.toast(
+                    toast::Kind::Error,
+                    format!("Could not restore the sale: {e}"),
+                )
+            }
+            (TrashChange::Purged(purged), Ok(())) => {
+                for (id, _) in purged {
+                    if let Err(e) = audit::record(id, "Purged from the trash") {
+                        tracing::warn!("could not record the purge: {e}");
+                    }
+                }
+                Task::none()
+            }
+            (TrashChange::Purged(purged), Err(e)) => {
+                for (id, trashed) in purged {
+                    self.trash.put(id, trashed.sale, trashed.deleted_at);
+                }
+                self.toast(
+                    toast::Kind::Error,
+                    format!("Could not purge the trash: {e}"),
+                )
+            }
+        }
+    }
+
+    /// Have the receipt of sale `id` signed by the fiscal module, now that
+    /// it is paid.
+    fn fiscalize(&self, id: usize) -> Task<Message> {
+        let config = &self.settings.config.fiscal;
+        let Some(sale) = self.sales.get(&id) else {
+            return Task::none();
+        };
+        if !config.is_enabled() || sale.fiscal.is_some() {
+            return Task::none();
+        }
+        Task::perform(
+            fiscal::sign(config.clone(), fiscal::Receipt::new(id, sale)),
+            move |result| Message::Fiscalized(id, result),
+        )
+    }
+
+    /// Note that a receipt of sale `id` was handed out, by whoever has the
+    /// till.
+    fn issued(&mut self, id: usize, via: sale::Via) -> Task<Message> {
+        let by = self
+            .shifts
+            .on_till()
+            .and_then(|shift| self.shifts.get(shift))
+            .map(|shift| shift.employee.clone());
+        let Some(sale) = self.sales.get_mut(&id) else {
+            return Task::none();
+        };
+        sale.issued.push(sale::Issue {
+            at: Utc::now(),
+            by,
+            via,
+        });
+        self.sale_changed(id)
+    }
+
+    /// Run the calls in the outbox which are due for a retry.
+    fn retry_outbox(&mut self) -> Task<Message> {
+        let due = self.outbox.due();
+        Task::batch(due.into_iter().map(|job| self.send(job)))
+    }
+
+    /// Drop `job` from the outbox after it went through, if it was queued.
+    fn sent(&mut self, job: &outbox::Job) -> Task<Message> {
+        if !self.outbox.entries().iter().any(|entry| entry.job == *job) {
+            return Task::none();
+        }
+        self.outbox.done(job);
+        self.save_outbox()
+    }
+
+    /// Queue `job` to retry if it failed for being offline.
+    fn not_sent(
+        &mut self,
+        job: outbox::Job,
+        failure: outbox::Failure,
+    ) -> Task<Message> {
+        match failure {
+            outbox::Failure::Offline(e) => {
+                self.outbox.failed(job, e);
+                self.save_outbox()
+            }
+            outbox::Failure::Other(_) => self.sent(&job),
+        }
+    }
+
+    fn save_outbox(&self) -> Task<Message> {
+        self.persist("the outbox", self.outbox.save())
+    }
+
+    /// Ask the card terminal whether `pending` was paid, after a moment.
+    fn poll_card(
+        &self,
+        id: usize,
+        pending: terminal::Pending,
+    ) -> Task<Message> {
+        let config = self.settings.config.terminal.clone();
+        Task::perform(terminal::poll(config, pending), move |result| {
+            Message::CardPolled(id, result)
+        })
+    }
+
+    /// Fetch the exchange rates of the currencies payments are taken in.
+    fn fetch_rates(&self, manual: bool) -> Task<Message> {
+        let currency = &self.settings.config.currency;
+        Task::perform(
+            currency::fetch(currency.url(), currency.base.clone()),
+            move |result| Message::RatesFetched(manual, result),
+        )
+    }
+
+    fn store_failed(&mut self, error: String) -> Task<Message> {
+        self.store_error = Some(error.clone());
+        self.toast(toast::Kind::Error, error)
+    }
+
+    fn toast(
+        &mut self,
+        kind: toast::Kind,
+        message: impl Into<String>,
+    ) -> Task<Message> {
+        let message = message.into();
+        if kind == toast::Kind::Error {
+            tracing::warn!("{message}");
+        }
+        self.toasts.push(kind, message).map(Message::DismissToast)
+    }
+
+    fn sync(&mut self) -> Task<Message> {
+        let remote = self.settings.config.sync.clone();
+        if !remote.is_enabled() {
+            self.sync_status = sync::Status::Off;
+            return Task::none();
+        }
+
+        self.sync_status = sync::Status::Syncing;
+        let format = self.settings.config.store_format;
+        let partial = self.partial();
+        let run = async move {
+            let local = store::complete(format, partial)
+                .await
+                .map_err(|e| e.to_string())?;
+            sync::run(remote, local).await
+        };
+        Task::perform(run, Message::Synced)
+    }
+
+    /// Broadcast an event about a stored sale on the live feed, if running,
+    /// and tell the hooks it was saved.
+    fn publish(&self, event: live::Kind, sale_id: usize) {
+        let Some(sale) = self.sales.get(&sale_id) else {
+            return;
+        };
+        if let Some(bus) = &self.live {
+            bus.publish(event, sale_id, sale);
+        }
+        self.hooks.dispatch(hook::Event::SaleSaved {
+            id: sale_id,
+            created: event == live::Kind::Created,
+            sale: Box::new(sale.clone()),
+        });
+    }
+
+    /// Write a backup snapshot of all sales in the background, rotating out
+    /// old ones.
+    fn back_up(&mut self) -> Task<Message> {
+        self.saves_since_backup = 0;
+        let format = self.settings.config.store_format;
+        let partial = self.partial();
+        let keep = self.settings.config.backups_kept;
+        let write = async move {
+            backup::write(store::complete(format, partial).await?, keep).await
+        };
+        Task::perform(write, Message::BackedUp)
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let feed = &self.settings.config.live_feed;
+        let live = if feed.enabled {
+            live::feed(feed.port).map(Message::Live)
+        } else {
+            Subscription::none()
+        };
+
+        let outbox = if self.outbox.is_empty() {
+            Subscription::none()
+        } else {
+            outbox::worker().map(|()| Message::OutboxTick)
+        };
+
+        let summary = if self.settings.config.summary.is_enabled() {
+            summary::clock().map(|()| Message::SummaryTick)
+        } else {
+            Subscription::none()
+        };
+
+        let recurring = if self.recurrences.is_empty() {
+            Subscription::none()
+        } else {
+            recurring::clock().map(|()| Message::RecurringTick)
+        };
+
+        let schedule = if self.settings.config.schedule.is_enabled() {
+            schedule::clock().map(|()| Message::ScheduleTick)
+        } else {
+            Subscription::none()
+        };
+
+        let rates = if self.settings.config.currency.refresh_hours > 0 {
+            currency::clock().map(|()| Message::RatesTick)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch([
+            event::listen_with(handle_event),
+            window::close_events().map(Message::WindowClosed),
+            live,
+            outbox,
+            summary,
+            schedule,
+            recurring,
+            rates,
+        ])
+    }
+}
 
 fn handle_event(
     event: event::Event,
-    _: event::Status,
+    status: event::Status,
     _: iced::window::Id,
 ) -> Option<Message> {
     match event {
@@ -256,13 +3578,8 @@ fn handle_event(
             key,
             modifiers,
             ..
-        }) => match key {
-            Key::Named(Named::Escape) => Some(Message::Hotkey(Hotkey::Escape)),
-            Key::Named(Named::Tab) => {
-                Some(Message::Hotkey(Hotkey::Tab(modifiers)))
-            }
-            _ => None,
-        },
+        }) => shortcut::Chord::pressed(&key, modifiers)
+            .map(|chord| Message::KeyPressed(chord, status)),
         _ => None,
     }
 }
@@ -0,0 +1,220 @@
+//! Calls to external services which failed while offline, kept to retry
+//!
+//! When a sync, a cancellation on the card terminal or an email can't reach
+//! the network, it is put in the outbox, saved next to the config so that it
+//! survives a restart. While anything is queued a worker ticks every few
+//! seconds and the app runs the entries that are due again, backing off
+//! exponentially after each failure. Failures other than being offline,
+//! e.g. a rejected password, are shown as before rather than queued.
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use iced::futures::channel::oneshot;
+use iced::futures::SinkExt;
+use iced::Subscription;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+
+use crate::config::data_dir;
+use crate::data::{self, Error};
+use crate::terminal;
+
+/// How often the worker checks for entries which are due.
+const TICK: std::time::Duration = std::time::Duration::from_secs(5);
+const FIRST_RETRY_SECS: i64 = 15;
+const LAST_RETRY_SECS: i64 = 60 * 60;
+
+/// Why a call to an external service failed.
+#[derive(Debug, Clone)]
+pub enum Failure {
+    /// The service couldn't be reached, so the call is worth retrying.
+    Offline(String),
+    Other(String),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Offline(e) | Failure::Other(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<String> for Failure {
+    fn from(e: String) -> Self {
+        Failure::Other(e)
+    }
+}
+
+impl From<ureq::Error> for Failure {
+    fn from(e: ureq::Error) -> Self {
+        match e {
+            ureq::Error::Transport(_) => Failure::Offline(e.to_string()),
+            ureq::Error::Status(..) => Failure::Other(e.to_string()),
+        }
+    }
+}
+
+/// A call to run again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Job {
+    Sync,
+    /// Clear a payment off the card terminal, by its id.
+    CancelCard(terminal::Provider, String),
+    /// Email the summary of the day.
+    SendSummary(NaiveDate),
+    /// Email the receipt of a sale, as it was when asked for.
+    SendReceipt {
+        sale: usize,
+        to: String,
+        receipt: String,
+    },
+}
+
+impl fmt::Display for Job {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Job::Sync => write!(f, "Sync"),
+            Job::CancelCard(provider, _) => {
+                write!(f, "Cancel card payment on {provider}")
+            }
+            Job::SendSummary(day) => write!(f, "Email the summary of {day}"),
+            Job::SendReceipt { sale, to, .. } => {
+                write!(f, "Email the receipt of #{sale} to {to}")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub job: Job,
+    pub attempts: u32,
+    pub retry_at: DateTime<Utc>,
+    pub error: String,
+    /// Whether it is being retried right now.
+    #[serde(skip)]
+    pub running: bool,
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} • {} attempt(s) • next at {}",
+            self.job,
+            self.attempts,
+            self.retry_at.with_timezone(&Local).format("%H:%M:%S")
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Outbox {
+    entries: Vec<Entry>,
+}
+
+impl Outbox {
+    fn path() -> PathBuf {
+        data_dir().join("outbox.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save in the background, see [`data::save_json`].
+    pub fn save(
+        &self,
+    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        data::save_json(Self::path(), self)
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Queue `job` after it failed with `error`, or push back its next retry
+    /// if it is queued already.
+    pub fn failed(&mut self, job: Job, error: String) {
+        let entry = match self.entries.iter_mut().find(|e| e.job == job) {
+            Some(entry) => entry,
+            None => {
+                self.entries.push(Entry {
+                    job,
+                    attempts: 0,
+                    retry_at: Utc::now(),
+                    error: String::new(),
+                    running: false,
+                });
+                self.entries.last_mut().unwrap()
+            }
+        };
+
+        entry.attempts += 1;
+        entry.error = error;
+        entry.running = false;
+        let backoff = FIRST_RETRY_SECS
+            .saturating_mul(1 << entry.attempts.min(16).saturating_sub(1))
+            .min(LAST_RETRY_SECS);
+        entry.retry_at = Utc::now() + Duration::seconds(backoff);
+        tracing::warn!(
+            job = %entry.job,
+            attempts = entry.attempts,
+            error = entry.error,
+            "queued for a retry"
+        );
+    }
+
+    /// Drop `job` once it went through.
+    pub fn done(&mut self, job: &Job) {
+        self.entries.retain(|entry| entry.job != *job);
+    }
+
+    /// The jobs due for a retry, marked as running.
+    pub fn due(&mut self) -> Vec<Job> {
+        let now = Utc::now();
+        self.entries
+            .iter_mut()
+            .filter(|entry| !entry.running && entry.retry_at <= now)
+            .map(|entry| {
+                entry.running = true;
+                entry.job.clone()
+            })
+            .collect()
+    }
+
+    /// Make every job due now, e.g. when asked to retry from the settings.
+    pub fn retry_now(&mut self) {
+        for entry in &mut self.entries {
+            entry.retry_at = Utc::now();
+        }
+    }
+}
+
+/// Tick while there are entries to retry.
+pub fn worker() -> Subscription<()> {
+    Subscription::run_with_id(
+        "outbox",
+        iced::stream::channel(1, |mut output| async move {
+            loop {
+                let (tick, ticked) = oneshot::channel();
+                std::thread::spawn(move || {
+                    std::thread::sleep(TICK);
+                    let _ = tick.send(());
+                });
+                let _ = ticked.await;
+                if output.send(()).await.is_err() {
+                    return;
+                }
+            }
+        }),
+    )
+}
@@ -0,0 +1,100 @@
+//! The manager PIN, kept in the config as a salted hash
+//!
+//! The PIN itself is never written anywhere. Checking a typed PIN hashes it
+//! with the same salt, many rounds over, so that the short PINs used at a
+//! till take a while to guess from a copy of the config. Configs written
+//! before the PIN was hashed hold it as plain text, which is read in and
+//! hashed on the next save.
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// How many rounds of SHA-256 a PIN is hashed with.
+const ROUNDS: u32 = 10_000;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "Stored")]
+pub struct ManagerPin {
+    salt: String,
+    /// Empty when no PIN is set.
+    hash: String,
+}
+
+/// A PIN as found in the config, hashed or from before it was.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Stored {
+    Plain(String),
+    Hashed { salt: String, hash: String },
+}
+
+impl From<Stored> for ManagerPin {
+    fn from(stored: Stored) -> Self {
+        match stored {
+            Stored::Plain(pin) => ManagerPin::new(&pin),
+            Stored::Hashed { salt, hash } => ManagerPin { salt, hash },
+        }
+    }
+}
+
+impl ManagerPin {
+    /// `pin` hashed with a new salt, or no PIN if it is empty.
+    pub fn new(pin: &str) -> Self {
+        let pin = pin.trim();
+        if pin.is_empty() {
+            return Self::default();
+        }
+        let salt: [u8; 16] = rand::thread_rng().gen();
+        let salt = hex(&salt);
+        let hash = hash(&salt, pin);
+        Self { salt, hash }
+    }
+
+    pub fn is_set(&self) -> bool {
+        !self.hash.is_empty()
+    }
+
+    /// Whether `pin` is the PIN set. Anything matches when none is.
+    pub fn matches(&self, pin: &str) -> bool {
+        !self.is_set() || hash(&self.salt, pin.trim()) == self.hash
+    }
+}
+
+fn hash(salt: &str, pin: &str) -> String {
+    let mut digest = Sha256::digest(format!("{salt}:{pin}").as_bytes());
+    for _ in 1..ROUNDS {
+        digest = Sha256::digest(digest);
+    }
+    hex(&digest)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pins_are_kept_hashed() {
+        let pin = ManagerPin::new(" 4321 ");
+        assert!(pin.is_set());
+        assert!(pin.matches("4321"));
+        assert!(!pin.matches("1234"));
+        assert!(!pin.matches(""));
+
+        let json = serde_json::to_string(&pin).unwrap();
+        assert!(!json.contains("4321"));
+        assert_eq!(serde_json::from_str::<ManagerPin>(&json).unwrap(), pin);
+        // The same PIN set again gets another salt
+        assert_ne!(ManagerPin::new("4321"), pin);
+
+        // As written before PINs were hashed
+        let plain: ManagerPin = serde_json::from_str("\"4321\"").unwrap();
+        assert!(plain.matches("4321"));
+        let none: ManagerPin = serde_json::from_str("\"\"").unwrap();
+        assert!(!none.is_set());
+        assert!(none.matches("anything"));
+    }
+}
@@ -0,0 +1,87 @@
+//! Print receipts through the operating system's print dialog
+//!
+//! The receipt is written to a temporary HTML page that prints itself when
+//! opened, and the page is opened in the default browser. That brings up the
+//! system print dialog for any printer the OS knows about, without the app
+//! needing a PDF renderer or printer drivers of its own.
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::data::Error;
+use crate::logo::Logo;
+
+/// `receipt` as an HTML page that opens the print dialog once loaded, with
+/// the dithered `logo` above it at the size it prints on a thermal printer.
+pub fn html(title: &str, receipt: &str, logo: Option<&Logo>) -> String {
+    let logo = logo.map_or(String::new(), |logo| {
+        format!(
+            "<img src=\"{}\" style=\"display: block; width: {}mm; \
+             image-rendering: pixelated\">\n",
+            logo.data_url(),
+            logo.print_width_mm
+        )
+    });
+    format!(
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>{}</title>\n\
+         <style>\n\
+         @page {{ margin: 10mm; }}\n\
+         body {{ margin: 0; }}\n\
+         pre {{ font: 10pt/1.2 monospace; }}\n\
+         </style>\n\
+         </head>\n\
+         <body onload=\"window.print()\">\n\
+         {}<pre>{}</pre>\n\
+         </body>\n\
+         </html>\n",
+        escape(title),
+        logo,
+        escape(receipt)
+    )
+}
+
+/// Write `page` to a temporary file and open it in the default browser.
+pub fn open(name: &str, page: String) -> Result<PathBuf, Error> {
+    let dir = std::env::temp_dir().join("iced_receipts");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{name}.html"));
+    std::fs::write(&path, page)?;
+
+    let status = opener(&path).status()?;
+    if status.success() {
+        Ok(path)
+    } else {
+        Err(Error::Io(format!("could not open {}", path.display())))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn opener(path: &Path) -> Command {
+    let mut command = Command::new("cmd");
+    command.args(["/C", "start", ""]).arg(path);
+    command
+}
+
+#[cfg(target_os = "macos")]
+fn opener(path: &Path) -> Command {
+    let mut command = Command::new("open");
+    command.arg(path);
+    command
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn opener(path: &Path) -> Command {
+    let mut command = Command::new("xdg-open");
+    command.arg(path);
+    command
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
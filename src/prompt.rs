@@ -0,0 +1,54 @@
+//! A prompt for one amount, to show as a [`dialog`](crate::dialog)
+//!
+//! The amount is typed into an input, or on the number pad below it when the
+//! on-screen keypad is enabled. What it is for is up to the screen showing
+//! it, which only enables applying it once the amount is valid.
+use iced::widget::{button, column, horizontal_space, row, text, text_input};
+use iced::{Alignment, Element, Length};
+
+use crate::i18n::tr;
+use crate::keypad;
+use crate::style;
+
+/// A prompt titled `title` for the amount typed as `value` into the input
+/// `id`, sending `on_confirm` when applied if it is valid, and `on_cancel`.
+pub fn view<'a, Message: Clone + 'a>(
+    title: impl text::IntoFragment<'a>,
+    value: &'a str,
+    id: text_input::Id,
+    keypad: bool,
+    on_input: impl Fn(String) -> Message + Clone + 'a,
+    on_confirm: Option<Message>,
+    on_cancel: Message,
+) -> Element<'a, Message> {
+    let content = column![
+        text(title),
+        text_input("0", value)
+            .style(style::input)
+            .id(id)
+            .align_x(Alignment::End)
+            .on_input(on_input.clone())
+            .on_submit_maybe(on_confirm.clone())
+            .size(28)
+            .width(200)
+            .padding(5),
+        row![
+            horizontal_space(),
+            button(tr("Cancel"))
+                .on_press(on_cancel)
+                .style(button::secondary),
+            button(tr("Apply"))
+                .on_press_maybe(on_confirm)
+                .style(button::success),
+        ]
+        .spacing(10),
+    ]
+    .spacing(10)
+    .width(Length::Shrink);
+
+    if keypad {
+        content.push(keypad::view(value, true, on_input)).into()
+    } else {
+        content.into()
+    }
+}
@@ -0,0 +1,81 @@
+//! QR codes on receipts for a digital copy of the sale
+use iced::widget::image::Handle;
+use qrcode::render::unicode;
+use qrcode::{Color, EcLevel, QrCode};
+use serde::{Deserialize, Serialize};
+
+use crate::sale::Sale;
+
+/// The size of a module of the code in pixels, when shown as an image.
+const MODULE_SIZE: usize = 4;
+/// The blank border around the code, in modules.
+const QUIET_ZONE: usize = 2;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub enabled: bool,
+    /// The URL of the digital copy of a receipt, where `{number}` is replaced
+    /// by the receipt number (or the number is appended if there is no
+    /// placeholder). Without a URL the code holds a summary of the sale.
+    pub url: String,
+}
+
+/// What the QR code on the receipt for sale `id` holds, if enabled. A
+/// receipt signed by a fiscal module holds what the module asks for instead,
+/// enabled or not.
+pub fn payload(config: &Config, id: usize, sale: &Sale) -> Option<String> {
+    if let Some(qr) = sale.fiscal.as_ref().and_then(|fiscal| fiscal.qr.clone())
+    {
+        return Some(qr);
+    }
+    if !config.enabled {
+        return None;
+    }
+
+    let url = config.url.trim();
+    Some(if url.is_empty() {
+        format!(
+            "Receipt #{id}\n{}\n{}\nTotal ${:.2}",
+            sale.name,
+            sale.created_at.format("%Y-%m-%d %H:%M UTC"),
+            sale.calculate_total()
+        )
+    } else if url.contains("{number}") {
+        url.replace("{number}", &id.to_string())
+    } else {
+        format!("{url}{id}")
+    })
+}
+
+/// Render `payload` as text, two modules per character, for plain-text
+/// receipts.
+pub fn text(payload: &str) -> Option<String> {
+    let code = QrCode::with_error_correction_level(payload, EcLevel::L).ok()?;
+    Some(code.render::<unicode::Dense1x2>().quiet_zone(false).build())
+}
+
+/// Render `payload` as an image for showing on screen.
+pub fn image(payload: &str) -> Option<Handle> {
+    let code = QrCode::with_error_correction_level(payload, EcLevel::L).ok()?;
+    let modules = code.width();
+    let colors = code.to_colors();
+    let size = (modules + 2 * QUIET_ZONE) * MODULE_SIZE;
+
+    let mut pixels = vec![255; size * size * 4];
+    for (index, color) in colors.iter().enumerate() {
+        if *color == Color::Light {
+            continue;
+        }
+        let x = (index % modules + QUIET_ZONE) * MODULE_SIZE;
+        let y = (index / modules + QUIET_ZONE) * MODULE_SIZE;
+        for row in y..y + MODULE_SIZE {
+            let start = (row * size + x) * 4;
+            for pixel in pixels[start..start + MODULE_SIZE * 4].chunks_mut(4) {
+                pixel.copy_from_slice(&[0, 0, 0, 255]);
+            }
+        }
+    }
+
+    Some(Handle::from_rgba(size as u32, size as u32, pixels))
+}
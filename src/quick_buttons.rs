@@ -0,0 +1,394 @@
+//! The grid of quick buttons on the edit screen, and the screen setting it up
+//!
+//! Each button adds a product of the catalog to the sale with one tap, for
+//! fast bar service. The buttons are for the products pinned on the setup
+//! screen, in the order they were pinned, or with none pinned for the ones
+//! sold the most, as counted by the [`Names`] sold before. Products given a
+//! category in the catalog also get pages of buttons of their own, one tab
+//! per category, each split into pages of as many buttons as the grid holds.
+use iced::widget::{
+    button, checkbox, column, container, horizontal_space, pick_list, row,
+    scrollable, text,
+};
+use iced::{Alignment, Element, Fill, Length};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+use crate::catalog::{Catalog, Product};
+use crate::density::Density;
+use crate::i18n::money;
+use crate::sale::names::Names;
+use crate::{Action, Hotkey, Screen};
+
+/// The column counts the grid can be laid out in.
+const COLUMNS: [u32; 7] = [2, 3, 4, 5, 6, 7, 8];
+
+/// The button counts the grid can hold.
+const COUNTS: [usize; 6] = [4, 6, 8, 12, 16, 24];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Whether the grid is shown on the edit screen.
+    pub enabled: bool,
+    pub columns: u32,
+    /// How many buttons the grid holds at most.
+    pub count: usize,
+    /// The ids of the products pinned, in order, none for the most sold.
+    pub pinned: Vec<usize>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            columns: 4,
+            count: 12,
+            pinned: Vec::new(),
+        }
+    }
+}
+
+/// The products to show buttons for.
+pub fn products<'a>(
+    config: &Config,
+    catalog: &'a Catalog,
+    names: &Names,
+) -> Vec<&'a Product> {
+    if !config.pinned.is_empty() {
+        return config
+            .pinned
+            .iter()
+            .filter_map(|id| catalog.get(*id))
+            .take(config.count)
+            .collect();
+    }
+
+    // Products sold as often stay in catalog order
+    let mut products: Vec<&Product> = catalog.iter().collect();
+    products.sort_by_key(|product| Reverse(names.uses(&product.name)));
+    products.truncate(config.count);
+    products
+}
+
+/// The products on `page` of the buttons of `category`, and how many pages
+/// it has. Without a category, there is only the one page of [`products`].
+pub fn page<'a>(
+    config: &Config,
+    catalog: &'a Catalog,
+    names: &Names,
+    category: Option<&str>,
+    page: usize,
+) -> (Vec<&'a Product>, usize) {
+    let Some(category) = category else {
+        return (products(config, catalog, names), 1);
+    };
+    let in_category: Vec<&Product> = catalog
+        .iter()
+        .filter(|product| product.category.trim() == category)
+        .collect();
+    let count = config.count.max(1);
+    let pages = in_category.len().div_ceil(count).max(1);
+    let products = in_category
+        .into_iter()
+        .skip(page.min(pages - 1) * count)
+        .take(count)
+        .collect();
+    (products, pages)
+}
+
+/// The buttons for `products`, `columns` to a row, each sending what
+/// `on_press` makes of its product, if anything.
+pub fn grid<'a, Message: Clone + 'a>(
+    products: Vec<&'a Product>,
+    columns: u32,
+    density: Density,
+    on_press: impl Fn(&'a Product) -> Option<Message>,
+) -> Element<'a, Message> {
+    let size = density.text_size(14.0);
+    let rows = products.chunks(columns.max(1) as usize).map(|chunk| {
+        let buttons = chunk.iter().map(|product| {
+            let label = column![
+                text(product.display_name()).size(size),
+                text(product.price.map(money).unwrap_or_default())
+                    .size(size - 2.0),
+            ]
+            .align_x(Alignment::Center);
+            button(container(label).center_x(Fill))
+                .width(Length::FillPortion(1))
+                .padding(10)
+                .on_press_maybe(on_press(product))
+                .style(if product.is_out_of_stock() {
+                    button::secondary
+                } else {
+                    button::primary
+                })
+                .into()
+        });
+        // Keep the buttons of a short last row as wide as the others
+        let gaps = (chunk.len()..columns as usize)
+            .map(|_| horizontal_space().width(Length::FillPortion(1)).into());
+        row(buttons.chain(gaps)).spacing(5).into()
+    });
+
+    column(rows).spacing(5).into()
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Back,
+    Toggle(bool),
+    SelectColumns(u32),
+    SelectCount(usize),
+    Pin(usize),
+    Unpin(usize),
+    /// Move a pinned product by this many places.
+    Move(usize, isize),
+    /// Unpin every product, going back to the most sold.
+    Reset,
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Navigate(Screen),
+    Save(Config),
+}
+
+impl From<Screen> for Instruction {
+    fn from(screen: Screen) -> Self {
+        Instruction::Navigate(screen)
+    }
+}
+
+/// Change the grid set up on the screen, saving it as changed.
+pub fn update(
+    config: &mut Config,
+    message: Message,
+) -> Action<Instruction, Message> {
+    match message {
+        Message::Back => return Action::navigate(Screen::Settings),
+        Message::Toggle(enabled) => config.enabled = enabled,
+        Message::SelectColumns(columns) => config.columns = columns,
+        Message::SelectCount(count) => config.count = count,
+        Message::Pin(id) => {
+            if !config.pinned.contains(&id) {
+                config.pinned.push(id);
+            }
+        }
+        Message::Unpin(id) => config.pinned.retain(|pinned| *pinned != id),
+        Message::Move(id, by) => {
+            if let Some(index) = config.pinned.iter().position(|p| *p == id) {
+                let to = index
+                    .saturating_add_signed(by)
+                    .min(config.pinned.len() - 1);
+                let id = config.pinned.remove(index);
+                config.pinned.insert(to, id);
+            }
+        }
+        Message::Reset => config.pinned.clear(),
+    }
+    Action::instruction(Instruction::Save(config.clone()))
+}
+
+pub fn view<'a>(
+    config: &'a Config,
+    catalog: &'a Catalog,
+    names: &'a Names,
+) -> Element<'a, Message> {
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text("Quick buttons").size(16),
+        horizontal_space(),
+        button("Show the most sold")
+            .on_press_maybe(
+                (!config.pinned.is_empty()).then_some(Message::Reset)
+            )
+            .style(button::secondary),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let options = row![
+        checkbox("Show on the edit screen", config.enabled)
+            .on_toggle(Message::Toggle),
+        horizontal_space(),
+        text("Columns"),
+        pick_list(&COLUMNS[..], Some(config.columns), Message::SelectColumns),
+        text("Buttons"),
+        pick_list(&COUNTS[..], Some(config.count), Message::SelectCount),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let list = catalog.iter().fold(column![].spacing(5), |col, product| {
+        let place = config.pinned.iter().position(|id| *id == product.id);
+        let actions: Element<_> = match place {
+            Some(index) => row![
+                text(format!("#{}", index + 1)).width(40.0),
+                button(text("↑").center())
+                    .width(30)
+                    .on_press(Message::Move(product.id, -1))
+                    .style(button::secondary),
+                button(text("↓").center())
+                    .width(30)
+                    .on_press(Message::Move(product.id, 1))
+                    .style(button::secondary),
+                button("Unpin")
+                    .on_press(Message::Unpin(product.id))
+                    .style(button::danger),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center)
+            .into(),
+            None => button("Pin")
+                .on_press(Message::Pin(product.id))
+                .style(button::secondary)
+                .into(),
+        };
+        col.push(
+            row![
+                text(product.display_name()).width(Fill),
+                text(format!("sold {}×", names.uses(&product.name)))
+                    .size(12)
+                    .width(80.0),
+                actions,
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        )
+    });
+    let list: Element<_> = if catalog.is_empty() {
+        text("Add products to the catalog to make buttons of them.").into()
+    } else {
+        scrollable(list).height(Fill).into()
+    };
+
+    let help = text(if config.pinned.is_empty() {
+        "Nothing is pinned, so the buttons are for the products sold the \
+         most. Pin products to choose the buttons and their order."
+    } else {
+        "The buttons are for the products pinned, in this order."
+    })
+    .size(12);
+
+    let preview = grid(
+        products(config, catalog, names),
+        config.columns,
+        Density::default(),
+        |_| None::<Message>,
+    );
+
+    column![
+        header,
+        options,
+        help,
+        row![
+            container(list).width(Fill).height(Fill),
+            container(scrollable(preview)).width(Fill).height(Fill),
+        ]
+        .spacing(20)
+        .height(Fill),
+    ]
+    .spacing(20)
+    .padding(20)
+    .into()
+}
+
+pub fn handle_hotkey(hotkey: Hotkey) -> Action<Instruction, Message> {
+    match hotkey {
+        Hotkey::Escape => Action::navigate(Screen::Settings),
+        _ => Action::none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sale::{Sale, SaleItem};
+
+    #[test]
+    fn buttons_are_the_pinned_products_or_the_most_sold() {
+        let mut catalog = Catalog::default();
+        let [beer, wine, water] = ["Beer", "Wine", "Water"].map(|name| {
+            let id = catalog.add();
+            catalog.get_mut(id).unwrap().name = name.to_string();
+            id
+        });
+        let mut names = Names::default();
+        names.record(&Sale {
+            items: vec![
+                SaleItem::new("Wine", 6.0, 1),
+                SaleItem::new("Water", 2.0, 1),
+            ],
+            ..Sale::default()
+        });
+        names.record(&Sale {
+            items: vec![SaleItem::new("wine", 6.0, 2)],
+            ..Sale::default()
+        });
+        let ids = |config: &Config, catalog: &Catalog| {
+            products(config, catalog, &names)
+                .iter()
+                .map(|product| product.id)
+                .collect::<Vec<_>>()
+        };
+
+        let mut config = Config {
+            count: 2,
+            ..Config::default()
+        };
+        assert_eq!(ids(&config, &catalog), [wine, water]);
+
+        update(&mut config, Message::Pin(water));
+        update(&mut config, Message::Pin(beer));
+        update(&mut config, Message::Pin(beer));
+        assert_eq!(ids(&config, &catalog), [water, beer]);
+        update(&mut config, Message::Move(beer, -1));
+        assert_eq!(ids(&config, &catalog), [beer, water]);
+        update(&mut config, Message::Move(beer, -1));
+        assert_eq!(config.pinned, [beer, water]);
+
+        // Removed products are left out
+        catalog.remove(beer);
+        assert_eq!(ids(&config, &catalog), [water]);
+        update(&mut config, Message::Reset);
+        assert_eq!(ids(&config, &catalog), [wine, water]);
+    }
+
+    #[test]
+    fn categories_are_split_into_pages() {
+        let mut catalog = Catalog::default();
+        for (name, category) in [
+            ("Beer", "Drinks"),
+            ("Cake", "Desserts"),
+            ("Wine", " Drinks "),
+            ("Water", "Drinks"),
+            ("Bread", ""),
+        ] {
+            let id = catalog.add();
+            let product = catalog.get_mut(id).unwrap();
+            product.name = name.to_string();
+            product.category = category.to_string();
+        }
+        assert_eq!(catalog.categories(), ["Drinks", "Desserts"]);
+
+        let config = Config {
+            count: 2,
+            ..Config::default()
+        };
+        let names = Names::default();
+        let page = |category, page| {
+            let (products, pages) =
+                super::page(&config, &catalog, &names, category, page);
+            let names: Vec<&str> =
+                products.iter().map(|p| p.name.as_str()).collect();
+            (names, pages)
+        };
+        assert_eq!(page(Some("Drinks"), 0), (vec!["Beer", "Wine"], 2));
+        assert_eq!(page(Some("Drinks"), 1), (vec!["Water"], 2));
+        // Past the last page is the last page
+        assert_eq!(page(Some("Drinks"), 5), (vec!["Water"], 2));
+        assert_eq!(page(Some("Desserts"), 0), (vec!["Cake"], 1));
+        assert_eq!(page(None, 3), (vec!["Beer", "Cake"], 1));
+    }
+}
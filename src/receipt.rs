@@ -0,0 +1,57 @@
+//! The receipt of a sale drawn on screen as it prints
+//!
+//! The receipt is laid out by the same [`Line`]s as the printed text, in a
+//! monospace font on a slip of paper, so what the edit screen, the show
+//! screen and the layout editor show is what the printer will print.
+use iced::widget::{column, container, text};
+use iced::{Background, Border, Color, Element, Font, Shadow, Theme, Vector};
+
+use crate::config::Config;
+use crate::sale::Sale;
+use crate::ticket::{self, Line};
+
+/// The color of rules, lighter than the text like a dashed cut line.
+const RULE: Color = Color::from_rgb(0.55, 0.55, 0.55);
+
+/// `lines` on a slip of receipt paper.
+pub fn view<'a, Message: 'a>(lines: &[Line]) -> Element<'a, Message> {
+    let slip = lines.iter().fold(column![], |slip, line| {
+        let printed = text(line.text())
+            .font(Font::MONOSPACE)
+            .size(12)
+            .shaping(text::Shaping::Advanced);
+        slip.push(match line {
+            Line::Rule(_) => printed.color(RULE),
+            _ => printed,
+        })
+    });
+
+    container(slip).padding(15).style(paper).into()
+}
+
+/// The receipt of sale `id`, in the layout set up in `config`.
+pub fn of<'a, Message: 'a>(
+    id: Option<usize>,
+    sale: &Sale,
+    config: &Config,
+) -> Element<'a, Message> {
+    view(&ticket::configured_lines(id, sale, config))
+}
+
+/// Black on white whatever the theme, as receipts print.
+fn paper(_theme: &Theme) -> container::Style {
+    container::Style {
+        background: Some(Background::Color(Color::WHITE)),
+        text_color: Some(Color::BLACK),
+        border: Border {
+            color: Color::from_rgb(0.85, 0.85, 0.85),
+            width: 1.0,
+            radius: 2.0.into(),
+        },
+        shadow: Shadow {
+            color: Color::from_rgba(0.0, 0.0, 0.0, 0.2),
+            offset: Vector::new(0.0, 2.0),
+            blur_radius: 6.0,
+        },
+    }
+}
@@ -0,0 +1,440 @@
+//! Recurring sales made from a template on a schedule, e.g. a monthly
+//! catering invoice
+//!
+//! A recurrence makes a new open sale from its template on every run, from
+//! its start day on. Runs missed while the app was closed are made up for
+//! the next time it checks, one sale for each, so no invoice is skipped.
+use chrono::{Local, Months, NaiveDate};
+use iced::futures::channel::oneshot;
+use iced::futures::SinkExt;
+use iced::widget::{
+    button, column, container, horizontal_space, pick_list, row, scrollable,
+    text, text_input,
+};
+use iced::{Alignment, Element, Fill, Subscription};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+
+use crate::config::data_dir;
+use crate::data::{self, Error};
+use crate::style;
+use crate::template::Templates;
+use crate::toast;
+use crate::{Action, Hotkey, Screen};
+
+/// How often the clock checks for runs coming due.
+const TICK: std::time::Duration = std::time::Duration::from_secs(600);
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    #[default]
+    Monthly,
+}
+
+impl Frequency {
+    pub const ALL: [Frequency; 3] =
+        [Frequency::Daily, Frequency::Weekly, Frequency::Monthly];
+
+    /// The day of run `n` of a recurrence starting on `start`. Monthly runs
+    /// fall on the last day of shorter months, and back on the start day
+    /// after them.
+    pub fn nth(self, start: NaiveDate, n: u32) -> Option<NaiveDate> {
+        match self {
+            Frequency::Daily => {
+                start.checked_add_days(chrono::Days::new(u64::from(n)))
+            }
+            Frequency::Weekly => {
+                start.checked_add_days(chrono::Days::new(7 * u64::from(n)))
+            }
+            Frequency::Monthly => start.checked_add_months(Months::new(n)),
+        }
+    }
+}
+
+impl fmt::Display for Frequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Frequency::Daily => "Every day",
+                Frequency::Weekly => "Every week",
+                Frequency::Monthly => "Every month",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recurrence {
+    pub id: usize,
+    /// The template the sales are made from.
+    pub template: usize,
+    pub frequency: Frequency,
+    pub start: NaiveDate,
+    /// How many runs have been made.
+    pub runs: u32,
+}
+
+impl Recurrence {
+    /// The day of the next run.
+    pub fn next_run(&self) -> Option<NaiveDate> {
+        self.frequency.nth(self.start, self.runs)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Recurrences {
+    recurrences: Vec<Recurrence>,
+}
+
+impl Recurrences {
+    fn path() -> PathBuf {
+        data_dir().join("recurrences.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save in the background, see [`data::save_json`].
+    pub fn save(
+        &self,
+    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        data::save_json(Self::path(), self)
+    }
+
+    pub fn add(
+        &mut self,
+        template: usize,
+        frequency: Frequency,
+        start: NaiveDate,
+    ) {
+        let id = self.recurrences.iter().map(|r| r.id + 1).max().unwrap_or(0);
+        self.recurrences.push(Recurrence {
+            id,
+            template,
+            frequency,
+            start,
+            runs: 0,
+        });
+    }
+
+    pub fn remove(&mut self, id: usize) {
+        self.recurrences.retain(|recurrence| recurrence.id != id);
+    }
+
+    /// Stop the recurrences of a template that was removed.
+    pub fn remove_template(&mut self, template: usize) {
+        self.recurrences
+            .retain(|recurrence| recurrence.template != template);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recurrences.is_empty()
+    }
+
+    /// The recurrences, the next to run first.
+    pub fn iter(&self) -> impl Iterator<Item = &Recurrence> {
+        let mut recurrences: Vec<_> = self.recurrences.iter().collect();
+        recurrences.sort_by_key(|recurrence| recurrence.next_run());
+        recurrences.into_iter()
+    }
+
+    /// Count every run due by `today` as made, returning the template and
+    /// day of each, in order.
+    pub fn take_due(&mut self, today: NaiveDate) -> Vec<(usize, NaiveDate)> {
+        let mut due = Vec::new();
+        for recurrence in &mut self.recurrences {
+            while let Some(run) =
+                recurrence.next_run().filter(|run| *run <= today)
+            {
+                due.push((recurrence.template, run));
+                recurrence.runs += 1;
+            }
+        }
+        due.sort_by_key(|(_, run)| *run);
+        due
+    }
+}
+
+/// A template to pick, by name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice {
+    pub id: usize,
+    pub name: String,
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// The recurrence being set up on the screen.
+#[derive(Debug, Default)]
+pub struct Form {
+    pub template: Option<Choice>,
+    pub frequency: Frequency,
+    pub start: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Back,
+    SelectTemplate(Choice),
+    SelectFrequency(Frequency),
+    StartInput(String),
+    Add,
+    Remove(usize),
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Navigate(Screen),
+    Save,
+}
+
+impl From<Screen> for Instruction {
+    fn from(screen: Screen) -> Self {
+        Instruction::Navigate(screen)
+    }
+}
+
+pub fn update(
+    recurrences: &mut Recurrences,
+    form: &mut Form,
+    message: Message,
+) -> Action<Instruction, Message> {
+    match message {
+        Message::Back => Action::navigate(Screen::List),
+        Message::SelectTemplate(choice) => {
+            form.template = Some(choice);
+            Action::none()
+        }
+        Message::SelectFrequency(frequency) => {
+            form.frequency = frequency;
+            Action::none()
+        }
+        Message::StartInput(start) => {
+            form.start = start;
+            Action::none()
+        }
+        Message::Add => {
+            let Some(template) = &form.template else {
+                return Action::toast(
+                    toast::Kind::Error,
+                    "Pick the template to make the sales from",
+                );
+            };
+            let start = match form.start.trim() {
+                "" => Local::now().date_naive(),
+                start => match start.parse() {
+                    Ok(start) => start,
+                    Err(_) => {
+                        return Action::toast(
+                            toast::Kind::Error,
+                            "Type the first day as e.g. 2024-04-01",
+                        )
+                    }
+                },
+            };
+            recurrences.add(template.id, form.frequency, start);
+            *form = Form::default();
+            Action::instruction(Instruction::Save)
+        }
+        Message::Remove(id) => {
+            recurrences.remove(id);
+            Action::instruction(Instruction::Save)
+        }
+    }
+}
+
+pub fn view<'a>(
+    recurrences: &'a Recurrences,
+    templates: &'a Templates,
+    form: &'a Form,
+) -> Element<'a, Message> {
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text("Recurring sales").size(16),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let choices: Vec<Choice> = templates
+        .iter()
+        .map(|template| Choice {
+            id: template.id,
+            name: template.display_name().to_string(),
+        })
+        .collect();
+    let add = column![
+        row![
+            pick_list(choices, form.template.clone(), Message::SelectTemplate)
+                .placeholder("Template"),
+            pick_list(
+                &Frequency::ALL[..],
+                Some(form.frequency),
+                Message::SelectFrequency
+            ),
+            text("from"),
+            text_input("Today", &form.start)
+                .style(style::input)
+                .on_input(Message::StartInput)
+                .on_submit(Message::Add)
+                .width(120.0)
+                .padding(5),
+            horizontal_space(),
+            button("+ Add")
+                .on_press(Message::Add)
+                .style(button::primary),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        text(
+            "A new open sale is made from the template on every run, \
+             named after it with the day of the run."
+        )
+        .size(12),
+    ]
+    .spacing(10);
+
+    let list: Element<_> = if recurrences.is_empty() {
+        text("Nothing recurs yet").size(12).into()
+    } else {
+        recurrences
+            .iter()
+            .fold(
+                column![row![
+                    text("Template").width(Fill),
+                    text("Repeats").width(120.0),
+                    text("Next run").width(100.0),
+                    horizontal_space().width(25.0),
+                ]
+                .spacing(10)]
+                .spacing(5),
+                |col, recurrence| {
+                    let name = templates
+                        .get(recurrence.template)
+                        .map_or("Removed template", |t| t.display_name());
+                    col.push(
+                        row![
+                            text(name).width(Fill),
+                            text(recurrence.frequency.to_string()).width(120.0),
+                            text(
+                                recurrence
+                                    .next_run()
+                                    .map_or(String::new(), |run| {
+                                        run.to_string()
+                                    })
+                            )
+                            .width(100.0),
+                            button(text("×").center())
+                                .width(25.0)
+                                .on_press(Message::Remove(recurrence.id))
+                                .style(button::danger),
+                        ]
+                        .spacing(10)
+                        .align_y(Alignment::Center),
+                    )
+                },
+            )
+            .into()
+    };
+
+    container(scrollable(
+        column![
+            header,
+            container(add)
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(list)
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+        ]
+        .spacing(20),
+    ))
+    .padding(20)
+    .into()
+}
+
+pub fn handle_hotkey(hotkey: Hotkey) -> Action<Instruction, Message> {
+    match hotkey {
+        Hotkey::Escape => Action::navigate(Screen::List),
+        _ => Action::none(),
+    }
+}
+
+/// Tick every few minutes, to check for runs coming due.
+pub fn clock() -> Subscription<()> {
+    Subscription::run_with_id(
+        "recurring",
+        iced::stream::channel(1, |mut output| async move {
+            loop {
+                let (tick, ticked) = oneshot::channel();
+                std::thread::spawn(move || {
+                    std::thread::sleep(TICK);
+                    let _ = tick.send(());
+                });
+                let _ = ticked.await;
+                if output.send(()).await.is_err() {
+                    return;
+                }
+            }
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn monthly_runs_keep_to_the_start_day() {
+        let runs: Vec<_> = (0..4)
+            .filter_map(|n| Frequency::Monthly.nth(day(1, 31), n))
+            .collect();
+        assert_eq!(runs, [day(1, 31), day(2, 29), day(3, 31), day(4, 30)]);
+    }
+
+    #[test]
+    fn missed_runs_are_all_made_up() {
+        let mut recurrences = Recurrences::default();
+        recurrences.add(7, Frequency::Weekly, day(3, 1));
+        recurrences.add(8, Frequency::Monthly, day(3, 10));
+
+        assert_eq!(
+            recurrences.take_due(day(3, 15)),
+            [
+                (7, day(3, 1)),
+                (7, day(3, 8)),
+                (8, day(3, 10)),
+                (7, day(3, 15))
+            ]
+        );
+        assert!(recurrences.take_due(day(3, 21)).is_empty());
+        assert_eq!(
+            recurrences
+                .iter()
+                .map(Recurrence::next_run)
+                .collect::<Vec<_>>(),
+            [Some(day(3, 22)), Some(day(4, 10))]
+        );
+    }
+}
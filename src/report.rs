@@ -0,0 +1,581 @@
+//! Sales reports over a date range, grouped by day, month, order type, tax
+//! group or shift, journals of the range for accounting software, and the
+//! tip pool of a day shared out among the staff
+use chrono::{Local, NaiveDate};
+use iced::widget::{
+    button, column, container, horizontal_space, pick_list, row, scrollable,
+    text, text_input,
+};
+use iced::{Alignment, Element, Fill, Task};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::accounting;
+use crate::i18n::{self, money};
+use crate::query::{self, GroupBy, Range, Row};
+use crate::store;
+use crate::style;
+use crate::tag::{self, Label};
+use crate::tips::{self, Basis, Pool};
+use crate::{Action, Hotkey, Screen};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Tab {
+    #[default]
+    Totals,
+    TipPool,
+}
+
+#[derive(Debug, Default)]
+pub struct Report {
+    pub tab: Tab,
+    pub from: String,
+    pub to: String,
+    pub group_by: GroupBy,
+    /// Only sales with this tag, if any is typed.
+    pub tag: String,
+    pub label: Option<Label>,
+    pub rows: Option<Result<Vec<Row>, String>>,
+    pub loading: bool,
+    /// The format of the store to query.
+    pub format: store::Format,
+    /// The accounting software to export journals for.
+    pub accounting: accounting::Format,
+    pub export_path: String,
+    /// The day to pool the tips of, today if empty.
+    pub pool_day: String,
+    pub pool: Option<Result<Pool, String>>,
+    /// How tips are shared out, as in the settings.
+    pub tips: tips::Config,
+    /// The weights as typed, by employee.
+    pub weights: BTreeMap<String, String>,
+    pub pool_path: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Back,
+    FromInput(String),
+    ToInput(String),
+    SelectGroupBy(GroupBy),
+    TagInput(String),
+    SelectLabel(Option<Label>),
+    Run,
+    Loaded(Result<Vec<Row>, String>),
+    SelectAccounting(accounting::Format),
+    ExportPathInput(String),
+    Export,
+    SelectTab(Tab),
+    PoolDayInput(String),
+    SelectBasis(Basis),
+    WeightInput(String, String),
+    CalculatePool,
+    PoolLoaded(Result<Pool, String>),
+    PoolPathInput(String),
+    ExportPool,
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Navigate(Screen),
+    /// Write the journal of each day in the range to a file.
+    Export(accounting::Format, Range, PathBuf),
+    /// Keep how tips are shared out in the settings.
+    SaveTips(tips::Config),
+    /// Write the payouts of the pool to a CSV file.
+    ExportPool(Box<Pool>, PathBuf),
+}
+
+impl From<Screen> for Instruction {
+    fn from(screen: Screen) -> Self {
+        Instruction::Navigate(screen)
+    }
+}
+
+pub fn update(
+    report: &mut Report,
+    message: Message,
+) -> Action<Instruction, Message> {
+    match message {
+        Message::Back => Action::navigate(Screen::List),
+        Message::FromInput(from) => {
+            report.from = from;
+            Action::none()
+        }
+        Message::ToInput(to) => {
+            report.to = to;
+            Action::none()
+        }
+        Message::SelectGroupBy(group_by) => {
+            report.group_by = group_by;
+            run(report)
+        }
+        Message::TagInput(tag) => {
+            report.tag = tag;
+            Action::none()
+        }
+        Message::SelectLabel(label) => {
+            report.label = label;
+            run(report)
+        }
+        Message::Run => run(report),
+        Message::Loaded(rows) => {
+            report.loading = false;
+            report.rows = Some(rows);
+            Action::none()
+        }
+        Message::SelectAccounting(format) => {
+            report.accounting = format;
+            Action::none()
+        }
+        Message::ExportPathInput(path) => {
+            report.export_path = path;
+            Action::none()
+        }
+        Message::Export => {
+            let range = match range(report) {
+                Ok(range) => range,
+                Err(e) => {
+                    report.rows = Some(Err(e));
+                    return Action::none();
+                }
+            };
+            let path = match report.export_path.trim() {
+                "" => "journal",
+                path => path,
+            };
+            Action::instruction(Instruction::Export(
+                report.accounting,
+                range,
+                PathBuf::from(path)
+                    .with_extension(report.accounting.extension()),
+            ))
+        }
+        Message::SelectTab(tab) => {
+            report.tab = tab;
+            if tab == Tab::TipPool && report.pool.is_none() {
+                return calculate_pool(report);
+            }
+            Action::none()
+        }
+        Message::PoolDayInput(day) => {
+            report.pool_day = day;
+            Action::none()
+        }
+        Message::SelectBasis(basis) => {
+            report.tips.basis = basis;
+            Action::instruction(Instruction::SaveTips(report.tips.clone()))
+        }
+        Message::WeightInput(employee, input) => {
+            match i18n::parse_number(&input).filter(|weight| *weight >= 0.0) {
+                Some(1.0) => {
+                    report.tips.weights.remove(&employee);
+                }
+                Some(weight) => {
+                    report.tips.weights.insert(employee.clone(), weight);
+                }
+                None => {}
+            }
+            report.weights.insert(employee, input);
+            Action::instruction(Instruction::SaveTips(report.tips.clone()))
+        }
+        Message::CalculatePool => calculate_pool(report),
+        Message::PoolLoaded(pool) => {
+            report.loading = false;
+            if let Ok(pool) = &pool {
+                report.weights = pool
+                    .hours
+                    .keys()
+                    .map(|employee| {
+                        let weight = report.tips.weight(employee);
+                        (employee.clone(), i18n::number(weight, 1))
+                    })
+                    .collect();
+            }
+            report.pool = Some(pool);
+            Action::none()
+        }
+        Message::PoolPathInput(path) => {
+            report.pool_path = path;
+            Action::none()
+        }
+        Message::ExportPool => {
+            let Some(Ok(pool)) = &report.pool else {
+                return Action::none();
+            };
+            let path = match report.pool_path.trim() {
+                "" => format!("tips-{}", pool.day),
+                path => path.to_string(),
+            };
+            Action::instruction(Instruction::ExportPool(
+                Box::new(pool.clone()),
+                PathBuf::from(path).with_extension("csv"),
+            ))
+        }
+    }
+}
+
+/// Work out the tip pool of the day typed, in the background.
+fn calculate_pool(report: &mut Report) -> Action<Instruction, Message> {
+    let day = match parse_date(&report.pool_day) {
+        Ok(day) => day.unwrap_or_else(|| Local::now().date_naive()),
+        Err(e) => {
+            report.pool = Some(Err(e));
+            return Action::none();
+        }
+    };
+    let format = report.format;
+
+    report.loading = true;
+    Action::effect(Task::perform(
+        async move { tips::pool(format, day).map_err(|e| e.to_string()) },
+        Message::PoolLoaded,
+    ))
+}
+
+/// Run the report's query in the background.
+pub fn run(report: &mut Report) -> Action<Instruction, Message> {
+    let range = match range(report) {
+        Ok(range) => range,
+        Err(e) => {
+            report.rows = Some(Err(e));
+            return Action::none();
+        }
+    };
+    let group_by = report.group_by;
+    let format = report.format;
+    let tag = tag::normalize(&report.tag);
+    let filter = tag::Filter {
+        tag: (!tag.is_empty()).then_some(tag),
+        label: report.label,
+    };
+
+    report.loading = true;
+    Action::effect(Task::perform(
+        async move {
+            query::aggregate(format, range, group_by, filter)
+                .map_err(|e| e.to_string())
+        },
+        Message::Loaded,
+    ))
+}
+
+fn range(report: &Report) -> Result<Range, String> {
+    Ok(Range {
+        from: parse_date(&report.from)?,
+        to: parse_date(&report.to)?,
+    })
+}
+
+fn parse_date(date: &str) -> Result<Option<NaiveDate>, String> {
+    let date = date.trim();
+    if date.is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map(Some)
+        .map_err(|_| format!("Invalid date \"{date}\", use YYYY-MM-DD"))
+}
+
+fn tabs<'a>(selected: Tab) -> Element<'a, Message> {
+    let tab = |label, tab| {
+        button(text(label).size(14))
+            .style(if tab == selected {
+                button::primary
+            } else {
+                button::text
+            })
+            .on_press(Message::SelectTab(tab))
+    };
+
+    row![tab("Totals", Tab::Totals), tab("Tip pool", Tab::TipPool)]
+        .spacing(5)
+        .into()
+}
+
+pub fn view(report: &Report) -> Element<'_, Message> {
+    if report.tab == Tab::TipPool {
+        return tip_pool(report);
+    }
+
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text("Reports").size(16),
+        tabs(report.tab),
+        horizontal_space(),
+        text_input("From (YYYY-MM-DD)", &report.from)
+            .style(style::input)
+            .on_input(Message::FromInput)
+            .on_submit(Message::Run)
+            .width(150.0)
+            .padding(5),
+        text_input("To (YYYY-MM-DD)", &report.to)
+            .style(style::input)
+            .on_input(Message::ToInput)
+            .on_submit(Message::Run)
+            .width(150.0)
+            .padding(5),
+        pick_list(
+            &GroupBy::ALL[..],
+            Some(report.group_by),
+            Message::SelectGroupBy
+        ),
+        button("Run").on_press_maybe((!report.loading).then_some(Message::Run)),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let filters = row![
+        horizontal_space(),
+        tag::swatches(report.label, Message::SelectLabel),
+        text_input("Tag", &report.tag)
+            .style(style::input)
+            .on_input(Message::TagInput)
+            .on_submit(Message::Run)
+            .width(150.0)
+            .padding(5),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let export = row![
+        text("Journals for").size(12),
+        pick_list(
+            &accounting::Format::ALL[..],
+            Some(report.accounting),
+            Message::SelectAccounting
+        ),
+        text_input("path/to/journal", &report.export_path)
+            .style(style::input)
+            .on_input(Message::ExportPathInput)
+            .on_submit(Message::Export)
+            .padding(5),
+        button("Export").on_press(Message::Export),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let column_headers = row![
+        text(report.group_by.to_string()).width(Fill),
+        text(if report.group_by == GroupBy::TaxGroup {
+            "Items"
+        } else {
+            "Sales"
+        })
+        .align_x(Alignment::End)
+        .width(80.0),
+        text("Subtotal").align_x(Alignment::End).width(100.0),
+        text("Tax").align_x(Alignment::End).width(100.0),
+        text("Total").align_x(Alignment::End).width(100.0),
+        text("Tips").align_x(Alignment::End).width(100.0),
+    ]
+    .spacing(5)
+    .padding([0, 10]);
+
+    let body: Element<_> = match &report.rows {
+        _ if report.loading => text("Loading…").into(),
+        None => text("Pick a date range and run the report").into(),
+        Some(Err(error)) => text(error).style(text::danger).into(),
+        Some(Ok(rows)) if rows.is_empty() => {
+            text("No sales in this range").into()
+        }
+        Some(Ok(rows)) => {
+            let totals = Row {
+                key: "Total".to_string(),
+                ..Row::default()
+            };
+            let totals = rows.iter().fold(totals, |mut sum, row| {
+                sum.add(row);
+                sum
+            });
+
+            rows.iter()
+                .fold(column![column_headers].spacing(5), |col, row| {
+                    col.push(
+                        container(report_row(row))
+                            .style(container::rounded_box)
+                            .padding([5, 10]),
+                    )
+                })
+                .push(container(report_row(&totals)).padding([5, 10]))
+                .into()
+        }
+    };
+
+    container(
+        column![
+            header,
+            filters,
+            container(scrollable(container(body).padding(20).width(Fill)))
+                .height(Fill)
+                .style(container::rounded_box),
+            export,
+        ]
+        .spacing(20),
+    )
+    .padding(20)
+    .into()
+}
+
+fn tip_pool(report: &Report) -> Element<'_, Message> {
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text("Reports").size(16),
+        tabs(report.tab),
+        horizontal_space(),
+        text_input("Day (YYYY-MM-DD)", &report.pool_day)
+            .style(style::input)
+            .on_input(Message::PoolDayInput)
+            .on_submit(Message::CalculatePool)
+            .width(150.0)
+            .padding(5),
+        pick_list(
+            &Basis::ALL[..],
+            Some(report.tips.basis),
+            Message::SelectBasis
+        ),
+        button("Calculate").on_press_maybe(
+            (!report.loading).then_some(Message::CalculatePool)
+        ),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let body: Element<_> = match &report.pool {
+        _ if report.loading => text("Loading…").into(),
+        None => text("Pick a day and calculate its tip pool").into(),
+        Some(Err(error)) => text(error).style(text::danger).into(),
+        Some(Ok(pool)) => payouts(report, pool),
+    };
+
+    let export = row![
+        text("Payouts to").size(12),
+        text_input("path/to/tips.csv", &report.pool_path)
+            .style(style::input)
+            .on_input(Message::PoolPathInput)
+            .on_submit(Message::ExportPool)
+            .padding(5),
+        button("Export").on_press_maybe(
+            matches!(report.pool, Some(Ok(_))).then_some(Message::ExportPool)
+        ),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    container(
+        column![
+            header,
+            container(scrollable(container(body).padding(20).width(Fill)))
+                .height(Fill)
+                .style(container::rounded_box),
+            export,
+        ]
+        .spacing(20),
+    )
+    .padding(20)
+    .into()
+}
+
+fn payouts<'a>(report: &'a Report, pool: &'a Pool) -> Element<'a, Message> {
+    let line = |label, amount: f32| {
+        row![
+            text(label).width(150.0),
+            text(money(amount)).align_x(Alignment::End).width(100.0),
+        ]
+    };
+    let summary = column![
+        text(format!("Tip pool of {}", pool.day)).size(16),
+        line("Gratuities", pool.gratuities),
+        line("Service charges", pool.service_charges),
+        line("Pool", pool.total()),
+    ]
+    .spacing(5);
+
+    if pool.hours.is_empty() {
+        return column![summary, text("No one worked a shift on this day")]
+            .spacing(20)
+            .into();
+    }
+
+    let headers = row![
+        text("Employee").width(Fill),
+        text("Hours").align_x(Alignment::End).width(80.0),
+        text("Weight").align_x(Alignment::End).width(80.0),
+        text("Share").align_x(Alignment::End).width(80.0),
+        text("Payout").align_x(Alignment::End).width(100.0),
+    ]
+    .spacing(5)
+    .padding([0, 10]);
+
+    let table = pool.payouts(&report.tips).into_iter().fold(
+        column![headers].spacing(5),
+        |col, payout| {
+            let weight = report
+                .weights
+                .get(&payout.employee)
+                .map_or("1", String::as_str);
+            let employee = payout.employee.clone();
+            col.push(
+                container(
+                    row![
+                        text(payout.employee).width(Fill),
+                        text(i18n::number(payout.hours, 2))
+                            .align_x(Alignment::End)
+                            .width(80.0),
+                        text_input("1", weight)
+                            .style(style::input)
+                            .align_x(Alignment::End)
+                            .on_input(move |weight| {
+                                Message::WeightInput(employee.clone(), weight)
+                            })
+                            .width(80.0)
+                            .padding(2),
+                        text(format!(
+                            "{}%",
+                            i18n::number(payout.share * 100.0, 1)
+                        ))
+                        .align_x(Alignment::End)
+                        .width(80.0),
+                        text(money(payout.amount))
+                            .align_x(Alignment::End)
+                            .width(100.0),
+                    ]
+                    .spacing(5)
+                    .align_y(Alignment::Center),
+                )
+                .style(container::rounded_box)
+                .padding([5, 10]),
+            )
+        },
+    );
+
+    column![summary, table].spacing(20).into()
+}
+
+fn report_row<'a>(row: &Row) -> Element<'a, Message> {
+    row![
+        text(row.key.clone()).width(Fill),
+        text(row.count).align_x(Alignment::End).width(80.0),
+        text(format!("${:.2}", row.subtotal))
+            .align_x(Alignment::End)
+            .width(100.0),
+        text(format!("${:.2}", row.tax))
+            .align_x(Alignment::End)
+            .width(100.0),
+        text(format!("${:.2}", row.total))
+            .align_x(Alignment::End)
+            .width(100.0),
+        text(format!("${:.2}", row.tips))
+            .align_x(Alignment::End)
+            .width(100.0),
+    ]
+    .spacing(5)
+    .into()
+}
+
+pub fn handle_hotkey(hotkey: Hotkey) -> Action<Instruction, Message> {
+    match hotkey {
+        Hotkey::Escape => Action::navigate(Screen::List),
+        _ => Action::none(),
+    }
+}
@@ -0,0 +1,237 @@
+//! Return items of a past sale, found by its receipt number
+use iced::widget::{
+    button, column, container, horizontal_space, row, scrollable, text,
+    text_input,
+};
+use iced::{Alignment, Element, Fill};
+use std::collections::BTreeMap;
+
+use crate::i18n::{self, money};
+use crate::sale::{Sale, SaleItem};
+use crate::style;
+use crate::toast;
+use crate::{Action, Hotkey, Screen};
+
+#[derive(Debug, Default)]
+pub struct Return {
+    pub receipt: String,
+    /// The sale found by its receipt number.
+    pub found: Option<usize>,
+    /// How many of each item to return, as typed, by item id.
+    pub quantities: BTreeMap<usize, String>,
+}
+
+impl Return {
+    /// The quantities to return which are valid numbers.
+    pub fn picked(&self) -> BTreeMap<usize, u32> {
+        self.quantities
+            .iter()
+            .filter_map(|(id, quantity)| {
+                Some((*id, quantity.trim().parse().ok()?))
+            })
+            .filter(|(_, quantity)| *quantity > 0)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Back,
+    ReceiptInput(String),
+    Find,
+    QuantityInput(usize, String),
+    Create,
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Navigate(Screen),
+    /// Look up the sale with this receipt number.
+    Find(usize),
+    /// Save the return of the picked items of the sale.
+    Create(usize, BTreeMap<usize, u32>),
+}
+
+impl From<Screen> for Instruction {
+    fn from(screen: Screen) -> Self {
+        Instruction::Navigate(screen)
+    }
+}
+
+pub fn update(
+    state: &mut Return,
+    message: Message,
+) -> Action<Instruction, Message> {
+    match message {
+        Message::Back => Action::navigate(Screen::List),
+        Message::ReceiptInput(receipt) => {
+            state.receipt = receipt;
+            Action::none()
+        }
+        Message::Find => {
+            let receipt = state.receipt.trim().trim_start_matches('#');
+            match receipt.parse() {
+                Ok(id) => {
+                    state.found = None;
+                    state.quantities.clear();
+                    Action::instruction(Instruction::Find(id))
+                }
+                Err(_) => Action::toast(
+                    toast::Kind::Error,
+                    format!("\"{receipt}\" is not a receipt number"),
+                ),
+            }
+        }
+        Message::QuantityInput(item, quantity) => {
+            if quantity.is_empty() || quantity.parse::<u32>().is_ok() {
+                state.quantities.insert(item, quantity);
+            }
+            Action::none()
+        }
+        Message::Create => match state.found {
+            Some(id) if !state.picked().is_empty() => {
+                Action::instruction(Instruction::Create(id, state.picked()))
+            }
+            _ => Action::none(),
+        },
+    }
+}
+
+/// The return screen, given the sale found if it is loaded.
+pub fn view<'a>(
+    state: &'a Return,
+    sale: Option<&'a Sale>,
+) -> Element<'a, Message> {
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text("Return").size(16),
+        horizontal_space(),
+        text_input("Receipt number", &state.receipt)
+            .style(style::input)
+            .on_input(Message::ReceiptInput)
+            .on_submit(Message::Find)
+            .width(200.0)
+            .padding(5),
+        button("Find").on_press(Message::Find),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let body: Element<_> = match (state.found, sale) {
+        (None, _) => {
+            container(text("Find the sale by the number on its receipt"))
+                .center(Fill)
+                .into()
+        }
+        (Some(_), None) => container(text("Loading…")).center(Fill).into(),
+        (Some(id), Some(sale)) if sale.is_return() => {
+            container(text(format!("#{id} is a return itself")))
+                .center(Fill)
+                .into()
+        }
+        (Some(id), Some(sale)) => picker(state, id, sale),
+    };
+
+    container(
+        column![
+            header,
+            container(body)
+                .height(Fill)
+                .width(Fill)
+                .style(container::rounded_box)
+        ]
+        .spacing(20),
+    )
+    .padding(20)
+    .into()
+}
+
+fn picker<'a>(
+    state: &'a Return,
+    id: usize,
+    sale: &'a Sale,
+) -> Element<'a, Message> {
+    let column_headers = row![
+        text("Item Name").width(Fill),
+        text("Sold").align_x(Alignment::End).width(80.0),
+        text("Price").align_x(Alignment::End).width(100.0),
+        text("Return").align_x(Alignment::End).width(80.0),
+    ]
+    .spacing(5);
+
+    let items = sale
+        .items
+        .iter()
+        .filter(|item| item.gift_card.is_none())
+        .fold(column![column_headers].spacing(5), |col, item| {
+            col.push(item_row(state, item))
+        });
+
+    let refund = sale.returned(id, &state.picked());
+    let totals = column![
+        total("Subtotal", refund.calculate_subtotal()),
+        total("Service Charge", refund.calculate_service_charge()),
+        total("Tax", refund.calculate_tax()),
+        row![
+            text("Refund").size(16),
+            horizontal_space(),
+            text(money(refund.calculate_total())).size(16),
+        ],
+        row![
+            horizontal_space(),
+            button("Save return")
+                .on_press_maybe(
+                    (!refund.items.is_empty()).then_some(Message::Create)
+                )
+                .style(button::danger),
+        ],
+    ]
+    .spacing(2);
+
+    column![
+        text(format!(
+            "Receipt #{id} • {} • {}",
+            sale.name,
+            i18n::date(&sale.created_at)
+        ))
+        .size(16),
+        scrollable(items).height(Fill),
+        totals,
+    ]
+    .spacing(20)
+    .padding(20)
+    .into()
+}
+
+fn item_row<'a>(state: &'a Return, item: &'a SaleItem) -> Element<'a, Message> {
+    let id = item.id;
+    row![
+        text(&item.name).width(Fill),
+        text(item.quantity_label())
+            .align_x(Alignment::End)
+            .width(80.0),
+        text(money(item.price()))
+            .align_x(Alignment::End)
+            .width(100.0),
+        text_input("0", state.quantities.get(&id).map_or("", String::as_str))
+            .style(style::input)
+            .align_x(Alignment::End)
+            .on_input(move |quantity| Message::QuantityInput(id, quantity))
+            .width(80.0)
+            .padding(5),
+    ]
+    .spacing(5)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+fn total<'a>(label: &'a str, amount: f32) -> Element<'a, Message> {
+    row![text(label), horizontal_space(), text(money(amount))].into()
+}
+
+pub fn handle_hotkey(hotkey: Hotkey) -> Action<Instruction, Message> {
+    match hotkey {
+        Hotkey::Escape => Action::navigate(Screen::List),
+        _ => Action::none(),
+    }
+}
@@ -1,211 +1,902 @@
 //! View and edit sales
-use iced::widget::{focus_next, text_input};
-use iced::Element;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use chrono::{DateTime, Local, Utc};
+use iced::widget::{
+    button, column, container, image, rich_text, row, span, text, text_input,
+};
+use iced::{Element, Fill};
+use std::collections::BTreeSet;
 
+use crate::catalog::{Catalog, Product};
+use crate::config::Config;
+use crate::currency;
+use crate::customer::Customers;
+use crate::gift_card;
+use crate::i18n::{self, tr};
+use crate::invoice::{Invoice, Payment};
+use crate::schedule;
+use crate::secret::Secret;
+use crate::store::SaveStatus;
+use crate::tag;
 use crate::tax::TaxGroup;
+use crate::tips;
+use crate::toast;
 use crate::{Action, Hotkey};
 
+pub use receipts_core::sale::*;
+
+pub mod conflict;
 pub mod edit;
+pub mod quick;
 pub mod show;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Mode {
     View,
     Edit,
 }
 
 #[derive(Debug, Clone)]
-pub struct SaleItem {
-    pub id: usize,
-    pub name: String,
-    price: Option<f32>,
-    quantity: Option<u32>,
-    pub tax_group: TaxGroup,
-}
-
-impl Default for SaleItem {
-    fn default() -> Self {
-        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
-
-        Self {
-            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
-            name: String::new(),
-            price: None,
-            quantity: None,
-            tax_group: TaxGroup::Food,
-        }
-    }
-}
-
-impl SaleItem {
-    pub fn price(&self) -> f32 {
-        self.price.unwrap_or(0.0)
-    }
-    pub fn quantity(&self) -> f32 {
-        self.quantity.unwrap_or(0) as f32
-    }
-    pub fn price_string(&self) -> String {
-        self.price.map_or(String::new(), |p| format!("{:.2}", p))
-    }
-    pub fn quantity_string(&self) -> String {
-        self.quantity.map_or(String::new(), |q| q.to_string())
-    }
+pub enum Message {
+    Show(show::Message),
+    Edit(edit::Message),
 }
 
 #[derive(Debug, Clone)]
-pub struct Sale {
-    pub items: Vec<SaleItem>,
-    pub service_charge_percent: Option<f32>,
-    pub gratuity_amount: Option<f32>,
-    pub name: String,
-}
-
-impl Default for Sale {
-    fn default() -> Self {
-        Self {
-            items: Vec::new(),
-            service_charge_percent: None,
-            gratuity_amount: None,
-            name: String::new(),
-        }
-    }
+pub enum Instruction {
+    Back,
+    Save,
+    /// Save and go straight back to the list.
+    SaveAndClose,
+    StartEdit,
+    Cancel,
+    ShowBoard,
+    CopyTicket,
+    CopyReceipt,
+    CopyJson,
+    /// Move the sale to the trash.
+    Delete,
+    SaveJson,
+    /// Print the receipt through the system print dialog.
+    Print,
+    /// Email the receipt to the customer of the sale.
+    EmailReceipt,
+    /// Take the amount due in cash and open the drawer.
+    TakeCash,
+    /// Take the amount due on the card terminal.
+    PayByCard,
+    /// Read the weight of item `id` off the scale.
+    ReadScale(usize),
+    /// Take this amount in this foreign currency, giving change from the
+    /// drawer.
+    TakeForeign(String, f32),
+    /// Stop waiting for the card terminal.
+    CancelCard,
+    /// Show the linked sale with this id.
+    Open(usize),
+    /// Start a new sale with the same items.
+    Reorder,
+    SaveAsTemplate,
+    RetrySave,
+    /// Work out how much gift card tender `id` pays.
+    ApplyTender(usize),
+    /// Adjust the gratuity of the saved sale to this amount, if the manager
+    /// PIN is right or none is set. Asks for it first if it is set and
+    /// `pin` is `None`.
+    AdjustGratuity {
+        to: f32,
+        pin: Option<Secret>,
+    },
+    /// Void an item of the saved sale, if the manager PIN is right.
+    Void {
+        item: usize,
+        reason: String,
+        pin: Secret,
+    },
+    /// Show or hide the receipt preview while editing.
+    TogglePreview,
+    /// A payment of this amount was recorded on the invoice.
+    PaymentRecorded(f32),
+    /// Export the statement of the invoice's customer.
+    ExportStatement,
+    /// Save the draft with its price overrides, if the manager PIN is right.
+    ApproveOverrides(Secret),
 }
 
-impl Sale {
-    pub fn calculate_subtotal(&self) -> f32 {
-        self.items
-            .iter()
-            .map(|item| item.price() * item.quantity())
-            .sum()
-    }
-
-    pub fn calculate_tax(&self) -> f32 {
-        self.items
-            .iter()
-            .map(|item| {
-                item.price() * item.quantity() * item.tax_group.tax_rate()
-            })
-            .sum()
-    }
-
-    pub fn calculate_service_charge(&self) -> f32 {
-        let subtotal = self.calculate_subtotal();
-        match self.service_charge_percent {
-            Some(percent) => subtotal * (percent / 100.0),
-            None => 0.0,
-        }
-    }
-
-    pub fn calculate_total(&self) -> f32 {
-        let subtotal = self.calculate_subtotal();
-        let tax = self.calculate_tax();
-        let service_charge = self.calculate_service_charge();
-        let gratuity = self.gratuity_amount.unwrap_or(0.0);
+/// What is being typed on the sale screens, for the sale on screen.
+#[derive(Debug, Default)]
+pub struct Form {
+    /// A new gratuity being typed on the show screen.
+    pub gratuity_input: Option<String>,
+    /// The manager PIN being typed to approve the new gratuity, while asked
+    /// for on applying it.
+    pub gratuity_pin: Option<String>,
+    /// An item being voided on the show screen.
+    pub void_input: Option<VoidInput>,
+    /// A new tag being typed on the edit screen.
+    pub tag_input: String,
+    /// The due time being typed on the edit screen, until it is valid.
+    pub due_input: Option<String>,
+    /// A payment towards the invoice being typed on the show screen.
+    pub payment_input: String,
+    /// The cash handed over being typed on the show screen, to work out the
+    /// change.
+    pub tendered: String,
+    /// A payment in a foreign currency being typed on the show screen.
+    pub foreign_entry: currency::Entry,
+    /// Items ticked on the edit screen, to remove at once.
+    pub selected_items: BTreeSet<usize>,
+    /// The sections folded away, by id.
+    pub collapsed: BTreeSet<usize>,
+    /// The on-screen keypad, while open on the edit screen.
+    pub keypad: Option<Keypad>,
+    /// The item whose name is being typed on the edit screen, to suggest
+    /// names under it.
+    pub completing: Option<usize>,
+    /// The page of quick buttons shown on the edit screen.
+    pub buttons: ButtonPage,
+    /// An open price product being added, waiting for its price.
+    pub open_price: Option<OpenPrice>,
+    /// The manager PIN being typed to approve price overrides, while asked
+    /// for on saving.
+    pub approval_pin: Option<String>,
+}
 
-        subtotal + tax + service_charge + gratuity
-    }
+/// An item being voided on the show screen.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VoidInput {
+    /// The id of the item.
+    pub item: usize,
+    pub reason: String,
+    /// The manager PIN, as typed.
+    pub pin: String,
 }
 
+/// The on-screen keypad, open for one input.
 #[derive(Debug, Clone)]
-pub enum Message {
-    Show(show::Message),
-    Edit(edit::Message),
+pub struct Keypad {
+    pub target: KeypadTarget,
+    /// What was entered on the keypad so far.
+    pub value: String,
 }
 
+/// The price of an open price product, being typed before it is added.
 #[derive(Debug, Clone)]
-pub enum Instruction {
-    Back,
-    Save,
-    StartEdit,
-    Cancel,
+pub struct OpenPrice {
+    pub product: Product,
+    pub price: String,
+}
+
+/// A page of the quick buttons on the edit screen.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ButtonPage {
+    /// The category of the products, or `None` for the most sold or pinned
+    /// ones.
+    pub category: Option<String>,
+    pub page: usize,
+}
+
+/// The input the on-screen keypad types into.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeypadTarget {
+    Quantity(usize),
+    Price(usize),
+    Gratuity,
+    TenderCode(usize),
 }
 
 pub fn update(
     sale: &mut Sale,
+    form: &mut Form,
     message: Message,
 ) -> Action<Instruction, Message> {
     match message {
         Message::Show(msg) => match msg {
-            show::Message::Back => Action::instruction(Instruction::Back),
+            show::Message::Back => {
+                form.gratuity_input = None;
+                form.gratuity_pin = None;
+                Action::instruction(Instruction::Back)
+            }
+            show::Message::AdjustGratuity => {
+                form.gratuity_input = Some(
+                    sale.gratuity_amount
+                        .map_or(String::new(), |g| i18n::number(g, 2)),
+                );
+                Action::effect(text_input::focus(show::gratuity_id()))
+            }
+            show::Message::GratuityInput(input) => {
+                form.gratuity_input = Some(input);
+                Action::none()
+            }
+            show::Message::ApplyGratuity => {
+                let Some(input) = &form.gratuity_input else {
+                    return Action::none();
+                };
+                let Some(gratuity) = (match input.trim() {
+                    "" => Some(0.0),
+                    input => i18n::parse_number(input),
+                }) else {
+                    return Action::toast(
+                        toast::Kind::Error,
+                        format!("\"{input}\" is not an amount"),
+                    );
+                };
+
+                if gratuity == sale.gratuity_amount.unwrap_or(0.0) {
+                    form.gratuity_input = None;
+                    form.gratuity_pin = None;
+                    return Action::none();
+                }
+                // Kept until the adjustment is approved
+                Action::instruction(Instruction::AdjustGratuity {
+                    to: gratuity,
+                    pin: form.gratuity_pin.clone().map(Secret),
+                })
+            }
+            show::Message::GratuityPinInput(pin) => {
+                form.gratuity_pin = Some(pin.0);
+                Action::none()
+            }
+            show::Message::CancelGratuity => {
+                form.gratuity_input = None;
+                form.gratuity_pin = None;
+                Action::none()
+            }
+            show::Message::VoidItem(item) => {
+                form.void_input = Some(VoidInput {
+                    item,
+                    ..VoidInput::default()
+                });
+                Action::effect(text_input::focus(show::void_reason_id()))
+            }
+            show::Message::VoidReasonInput(reason) => {
+                if let Some(input) = &mut form.void_input {
+                    input.reason = reason;
+                }
+                Action::none()
+            }
+            show::Message::VoidPinInput(pin) => {
+                if let Some(input) = &mut form.void_input {
+                    input.pin = pin.0;
+                }
+                Action::none()
+            }
+            show::Message::ConfirmVoid => match &form.void_input {
+                Some(input) if !input.reason.trim().is_empty() => {
+                    Action::instruction(Instruction::Void {
+                        item: input.item,
+                        reason: input.reason.trim().to_string(),
+                        pin: Secret(input.pin.clone()),
+                    })
+                }
+                _ => Action::none(),
+            },
+            show::Message::CancelVoid => {
+                form.void_input = None;
+                Action::none()
+            }
+            show::Message::TenderedInput(tendered) => {
+                form.tendered = tendered;
+                Action::none()
+            }
+            show::Message::PaymentInput(input) => {
+                form.payment_input = input;
+                Action::none()
+            }
+            show::Message::RecordPayment => {
+                let balance = sale.calculate_balance();
+                let Some(invoice) = &mut sale.invoice else {
+                    return Action::none();
+                };
+                let input = form.payment_input.trim();
+                let Some(amount) =
+                    i18n::parse_number(input).filter(|a| *a > 0.0)
+                else {
+                    return Action::toast(
+                        toast::Kind::Error,
+                        format!("\"{input}\" is not an amount"),
+                    );
+                };
+                if amount > balance + 0.005 {
+                    return Action::toast(
+                        toast::Kind::Error,
+                        tr("That is more than the balance"),
+                    );
+                }
+
+                invoice.payments.push(Payment {
+                    date: Local::now().date_naive(),
+                    amount,
+                });
+                form.payment_input.clear();
+                Action::instruction(Instruction::PaymentRecorded(amount))
+            }
+            show::Message::SelectCurrency(code) => {
+                form.foreign_entry.code = Some(code);
+                Action::none()
+            }
+            show::Message::ForeignAmountInput(amount) => {
+                form.foreign_entry.amount = amount;
+                Action::none()
+            }
+            show::Message::TakeForeign => {
+                let Some(code) = form.foreign_entry.code.clone() else {
+                    return Action::toast(
+                        toast::Kind::Error,
+                        tr("Pick the currency paid in"),
+                    );
+                };
+                let input = form.foreign_entry.amount.trim();
+                let Some(tendered) =
+                    i18n::parse_number(input).filter(|t| *t > 0.0)
+                else {
+                    return Action::toast(
+                        toast::Kind::Error,
+                        format!("\"{input}\" is not an amount"),
+                    );
+                };
+                Action::instruction(Instruction::TakeForeign(code, tendered))
+            }
+            show::Message::ExportStatement => {
+                Action::instruction(Instruction::ExportStatement)
+            }
             show::Message::StartEdit => {
                 Action::instruction(Instruction::StartEdit)
-                    .with_task(focus_next())
+                    .with_effect(edit::focus_form(sale))
+            }
+            show::Message::ToggleSection(id) => {
+                toggle_section(form, id);
+                Action::none()
+            }
+            show::Message::MarkReady => {
+                sale.order_status = OrderStatus::Ready;
+                Action::instruction(Instruction::ShowBoard)
+            }
+            show::Message::MarkPickedUp => {
+                sale.mark_picked_up();
+                Action::none()
+            }
+            show::Message::CopyTicket => {
+                Action::instruction(Instruction::CopyTicket)
+            }
+            show::Message::Delete => Action::instruction(Instruction::Delete),
+            show::Message::TogglePreview => {
+                Action::instruction(Instruction::TogglePreview)
+            }
+            show::Message::CopyReceipt => {
+                Action::instruction(Instruction::CopyReceipt)
+            }
+            show::Message::CopyJson => {
+                Action::instruction(Instruction::CopyJson)
+            }
+            show::Message::SaveJson => {
+                Action::instruction(Instruction::SaveJson)
+            }
+            show::Message::Print => Action::instruction(Instruction::Print),
+            show::Message::EmailReceipt => {
+                Action::instruction(Instruction::EmailReceipt)
+            }
+            show::Message::TakeCash => {
+                let short = i18n::parse_number(&form.tendered)
+                    .is_some_and(|t| t + 0.005 < sale.calculate_due());
+                if short {
+                    return Action::toast(toast::Kind::Error, tr("Not enough"));
+                }
+                form.tendered.clear();
+                Action::instruction(Instruction::TakeCash)
+            }
+            show::Message::PayByCard => {
+                Action::instruction(Instruction::PayByCard)
+            }
+            show::Message::CancelCard => {
+                Action::instruction(Instruction::CancelCard)
+            }
+            show::Message::OpenSale(id) => {
+                Action::instruction(Instruction::Open(id))
+            }
+            show::Message::Reorder => Action::instruction(Instruction::Reorder),
+            show::Message::SaveAsTemplate => {
+                Action::instruction(Instruction::SaveAsTemplate)
+            }
+            show::Message::RetrySave => {
+                Action::instruction(Instruction::RetrySave)
             }
         },
         Message::Edit(msg) => match msg {
-            edit::Message::Cancel => Action::instruction(Instruction::Cancel),
-            edit::Message::Save => Action::instruction(Instruction::Save),
+            edit::Message::Cancel => {
+                form.keypad = None;
+                form.selected_items.clear();
+                Action::instruction(Instruction::Cancel)
+            }
+            edit::Message::Save => {
+                form.keypad = None;
+                // Refunds are the only sales meant to pay money out
+                let is_refund = sale
+                    .link
+                    .is_some_and(|link| link.relation == Relation::RefundOf);
+                if !is_refund && sale.calculate_total() < 0.0 {
+                    return Action::toast(
+                        toast::Kind::Error,
+                        tr("Comps and adjustments can't take the total \
+                            below zero"),
+                    );
+                }
+                if form.due_input.is_some() && sale.due_at.is_none() {
+                    return Action::toast(
+                        toast::Kind::Error,
+                        tr("Type the due time as e.g. 2024-03-16 11:00"),
+                    );
+                }
+                form.due_input = None;
+                form.selected_items.clear();
+                Action::instruction(Instruction::Save)
+            }
+            edit::Message::OpenKeypad(target) => {
+                form.keypad = Some(Keypad {
+                    target,
+                    value: String::new(),
+                });
+                Action::none()
+            }
+            edit::Message::KeypadInput(value) => {
+                let Some(keypad) = &mut form.keypad else {
+                    return Action::none();
+                };
+                keypad.value = value.clone();
+                let message = edit::keypad_message(keypad.target, value);
+                update(sale, form, Message::Edit(message))
+            }
+            edit::Message::TogglePreview => {
+                Action::instruction(Instruction::TogglePreview)
+            }
+            edit::Message::CloseKeypad => match form.keypad.take() {
+                // A code entered on the keyboard is looked up straight away
+                Some(Keypad {
+                    target: KeypadTarget::TenderCode(id),
+                    ..
+                }) => update(
+                    sale,
+                    form,
+                    Message::Edit(edit::Message::ApplyTender(id)),
+                ),
+                _ => Action::none(),
+            },
             edit::Message::NameInput(name) => {
                 sale.name = name;
                 Action::none()
             }
+            edit::Message::SelectOrderType(order_type) => {
+                sale.order_type = order_type;
+                if order_type != OrderType::Takeout {
+                    sale.pager = None;
+                }
+                Action::none()
+            }
+            edit::Message::GuestsInput(guests) => {
+                match guests.trim() {
+                    "" => sale.guests = None,
+                    guests => match guests.parse() {
+                        Ok(guests) => sale.guests = Some(guests),
+                        Err(_) => return Action::none(),
+                    },
+                }
+                Action::none()
+            }
+            edit::Message::PagerInput(pager) => {
+                sale.pager = pager.trim().parse().ok();
+                Action::none()
+            }
+            edit::Message::DeliveryAddressInput(address) => {
+                sale.delivery.get_or_insert_default().address = address;
+                Action::none()
+            }
+            edit::Message::DeliveryPhoneInput(phone) => {
+                sale.delivery.get_or_insert_default().phone = phone;
+                Action::none()
+            }
+            edit::Message::DeliveryDistanceInput(distance) => {
+                sale.delivery.get_or_insert_default().distance =
+                    i18n::parse_number(&distance).filter(|d| *d >= 0.0);
+                Action::none()
+            }
+            edit::Message::DueInput(input) => {
+                if input.trim().is_empty() {
+                    sale.due_at = None;
+                    form.due_input = None;
+                } else {
+                    sale.due_at = schedule::parse(&input);
+                    form.due_input = Some(input);
+                }
+                Action::none()
+            }
+            edit::Message::SelectCustomer(choice) => {
+                sale.customer = choice.as_ref().map(|choice| choice.id);
+                sale.customer_tax_id = choice
+                    .as_ref()
+                    .map_or(String::new(), |choice| choice.tax_id.clone());
+                if let (Some(choice), Some(invoice)) =
+                    (choice, &mut sale.invoice)
+                {
+                    if invoice.customer.trim().is_empty() {
+                        invoice.customer = choice.name;
+                    }
+                }
+                Action::none()
+            }
+            edit::Message::ToggleInvoice(invoiced) => {
+                sale.invoice =
+                    invoiced.then(|| Invoice::new(Local::now().date_naive()));
+                Action::none()
+            }
+            edit::Message::InvoiceCustomerInput(customer) => {
+                if let Some(invoice) = &mut sale.invoice {
+                    invoice.customer = customer;
+                }
+                Action::none()
+            }
+            edit::Message::SelectTerms(terms) => {
+                if let Some(invoice) = &mut sale.invoice {
+                    invoice.terms = terms;
+                }
+                Action::none()
+            }
+            edit::Message::TagInput(input) => {
+                form.tag_input = input;
+                Action::none()
+            }
+            edit::Message::AddTag => {
+                let tag = tag::normalize(&form.tag_input);
+                if !tag.is_empty() && !sale.tags.contains(&tag) {
+                    sale.tags.push(tag);
+                }
+                form.tag_input.clear();
+                Action::none()
+            }
+            edit::Message::RemoveTag(tag) => {
+                sale.tags.retain(|t| *t != tag);
+                Action::none()
+            }
+            edit::Message::SelectLabel(label) => {
+                sale.label = label;
+                Action::none()
+            }
+            edit::Message::AllergyNoteInput(note) => {
+                sale.allergy_note = note;
+                Action::none()
+            }
+            edit::Message::CustomFieldInput(id, value) => {
+                if value.is_empty() {
+                    sale.custom_fields.remove(&id);
+                } else {
+                    sale.custom_fields.insert(id, value);
+                }
+                Action::none()
+            }
             edit::Message::NameSubmit => {
                 if sale.items.is_empty() {
                     sale.items.push(SaleItem::default());
                 }
-                Action::task(focus_next())
+                let item = &sale.items[0];
+                let field =
+                    edit::first_empty(item).unwrap_or(edit::FormField::Name);
+                edit::focus(edit::Focus::Item(field, item.id))
+            }
+            edit::Message::AddItem(section) => {
+                sale.items.push(SaleItem {
+                    section,
+                    ..SaleItem::default()
+                });
+                Action::none()
+            }
+            edit::Message::AddGiftCard => {
+                let code = gift_card::new_code();
+                sale.items.push(SaleItem {
+                    name: format!("Gift card {code}"),
+                    quantity: Some(1),
+                    tax_group: TaxGroup::NonTaxable,
+                    gift_card: Some(code),
+                    ..SaleItem::default()
+                });
+                Action::none()
+            }
+            edit::Message::AddProduct(product) => {
+                if product.open_price {
+                    // The line is only made once its price is typed
+                    form.open_price = Some(OpenPrice {
+                        product,
+                        price: String::new(),
+                    });
+                    return Action::effect(text_input::focus(
+                        edit::open_price_id(),
+                    ));
+                }
+                let item = from_product(product, sale.created_at);
+                add_product_item(sale, item)
+            }
+            edit::Message::OpenPriceInput(price) => {
+                if let Some(open) = &mut form.open_price {
+                    open.price = price;
+                }
+                Action::none()
+            }
+            edit::Message::ConfirmOpenPrice => {
+                let Some((open, price)) =
+                    form.open_price.as_ref().and_then(|open| {
+                        Some((open, edit::parse_price(&open.price)?))
+                    })
+                else {
+                    return Action::none();
+                };
+                let item = SaleItem {
+                    price: Some(price),
+                    list_price: None,
+                    tiers: Vec::new(),
+                    promotion: None,
+                    ..from_product(open.product.clone(), sale.created_at)
+                };
+                form.open_price = None;
+                add_product_item(sale, item)
+            }
+            edit::Message::CancelOpenPrice => {
+                form.open_price = None;
+                Action::none()
+            }
+            edit::Message::ApprovalPinInput(pin) => {
+                form.approval_pin = Some(pin.0);
+                Action::none()
+            }
+            edit::Message::ApproveOverrides => match &form.approval_pin {
+                Some(pin) if !pin.is_empty() => Action::instruction(
+                    Instruction::ApproveOverrides(Secret(pin.clone())),
+                ),
+                _ => Action::none(),
+            },
+            edit::Message::CancelApproval => {
+                form.approval_pin = None;
+                Action::none()
+            }
+            edit::Message::ShowButtons(page) => {
+                form.buttons = page;
+                Action::none()
+            }
+            edit::Message::AddTender => {
+                let tender = Tender::default();
+                let id = tender.id;
+                sale.tenders.push(tender);
+                edit::focus(edit::Focus::Tender(id))
+            }
+            edit::Message::TenderCodeInput(id, code) => {
+                if let Some(tender) =
+                    sale.tenders.iter_mut().find(|t| t.id == id)
+                {
+                    tender.code = code;
+                    tender.amount = 0.0;
+                }
+                Action::none()
+            }
+            edit::Message::ApplyTender(id) => {
+                Action::instruction(Instruction::ApplyTender(id))
             }
-            edit::Message::AddItem => {
-                sale.items.push(SaleItem::default());
+            edit::Message::RemoveTender(id) => {
+                sale.tenders.retain(|tender| tender.id != id);
+                Action::none()
+            }
+            edit::Message::AddSection => {
+                let section = Section::default();
+                let id = section.id;
+                sale.sections.push(section);
+                edit::focus(edit::Focus::Section(id))
+            }
+            edit::Message::RemoveSection(id) => {
+                sale.sections.retain(|section| section.id != id);
+                for item in &mut sale.items {
+                    if item.section == Some(id) {
+                        item.section = None;
+                    }
+                }
+                Action::none()
+            }
+            edit::Message::RenameSection(id, name) => {
+                if let Some(section) =
+                    sale.sections.iter_mut().find(|s| s.id == id)
+                {
+                    section.name = name;
+                }
+                Action::none()
+            }
+            edit::Message::MoveSection(id, offset) => {
+                if let Some(index) =
+                    sale.sections.iter().position(|s| s.id == id)
+                {
+                    let target = index as isize + offset;
+                    if target >= 0 && (target as usize) < sale.sections.len() {
+                        sale.sections.swap(index, target as usize);
+                    }
+                }
+                Action::none()
+            }
+            edit::Message::ToggleSection(id) => {
+                toggle_section(form, id);
                 Action::none()
             }
             edit::Message::RemoveItem(id) => {
                 sale.items.retain(|item| item.id != id);
+                form.selected_items.remove(&id);
+                Action::none()
+            }
+            edit::Message::SelectItem(id, selected) => {
+                if selected {
+                    form.selected_items.insert(id);
+                } else {
+                    form.selected_items.remove(&id);
+                }
+                Action::none()
+            }
+            edit::Message::SelectAllItems(selected) => {
+                form.selected_items = if selected {
+                    sale.items.iter().map(|item| item.id).collect()
+                } else {
+                    BTreeSet::new()
+                };
+                Action::none()
+            }
+            edit::Message::RemoveSelected => {
+                let selected = std::mem::take(&mut form.selected_items);
+                sale.items.retain(|item| !selected.contains(&item.id));
+                Action::none()
+            }
+            edit::Message::DuplicateItem(id) => {
+                if let Some(index) = sale.items.iter().position(|i| i.id == id)
+                {
+                    let copy = SaleItem {
+                        id: SaleItem::default().id,
+                        ..sale.items[index].clone()
+                    };
+                    sale.items.insert(index + 1, copy);
+                }
                 Action::none()
             }
             edit::Message::UpdateItem(id, update) => {
+                form.completing =
+                    matches!(update, edit::Field::Name(_)).then_some(id);
                 if let Some(item) = sale.items.iter_mut().find(|i| i.id == id) {
                     match update {
                         edit::Field::Name(name) => item.name = name,
                         edit::Field::Price(price) => {
-                            item.price = if price.is_empty() {
+                            // A price typed by hand overrides tiers and
+                            // promotions
+                            item.tiers.clear();
+                            item.promotion = None;
+                            item.price = match price.trim() {
+                                "" => None,
+                                "-" => Some(-0.0),
+                                price => i18n::parse_number(price),
+                            };
+                            if !item.is_negative() {
+                                item.adjustment = false;
+                            }
+                        }
+                        edit::Field::Quantity(reading)
+                            if item.weighing.is_some() =>
+                        {
+                            let weighing = item.weighing.as_mut().unwrap();
+                            weighing.gross = if reading.is_empty() {
                                 None
                             } else {
-                                price.parse().ok()
+                                i18n::parse_number(&reading)
                             };
                         }
                         edit::Field::Quantity(qty) => {
                             item.quantity = if qty.is_empty() {
                                 None
                             } else {
-                                qty.parse().ok()
+                                i18n::parse_number(&qty)
+                                    .filter(|q| *q >= 0.0 && q.fract() == 0.0)
+                                    .map(|q| q as u32)
                             };
+                            if let Some(tier) = item.tier() {
+                                item.price = Some(tier.price);
+                            }
                         }
                         edit::Field::TaxGroup(group) => item.tax_group = group,
+                        edit::Field::Adjustment(adjustment) => {
+                            item.adjustment = adjustment;
+                        }
+                        edit::Field::Section(section) => item.section = section,
+                        edit::Field::Tare(tare) => {
+                            if let Some(weighing) = &mut item.weighing {
+                                weighing.tare = tare;
+                            }
+                        }
                     }
                 }
                 Action::none()
             }
+            edit::Message::ReadScale(id) => {
+                Action::instruction(Instruction::ReadScale(id))
+            }
+            edit::Message::ExpandItem(id) => {
+                let line =
+                    sale.items.iter().find(|i| i.id == id).and_then(|item| {
+                        let line = shorthand::parse(&item.name)
+                            .filter(|line| line.is_shorthand(&item.name))?;
+                        // A quantity already typed stays unless one was
+                        // typed in the name too
+                        let quantity = item.weighing.is_none()
+                            && (line.quantity != 1 || item.quantity.is_none());
+                        Some((line, quantity))
+                    });
+                if let Some((line, quantity)) = line {
+                    let mut set = |field| {
+                        update(
+                            sale,
+                            form,
+                            Message::Edit(edit::Message::UpdateItem(id, field)),
+                        );
+                    };
+                    set(edit::Field::Name(line.name));
+                    if quantity {
+                        set(edit::Field::Quantity(line.quantity.to_string()));
+                    }
+                    if let Some(price) = line.price {
+                        set(edit::Field::Price(i18n::number(price, 2)));
+                    }
+                    if let Some(tax_group) = line.tax_group {
+                        set(edit::Field::TaxGroup(tax_group));
+                    }
+                }
+                update(sale, form, Message::Edit(edit::Message::SubmitItem(id)))
+            }
+            edit::Message::PickSuggestion(id, suggestion) => {
+                let at = sale.created_at;
+                if let Some(item) = sale.items.iter_mut().find(|i| i.id == id) {
+                    match suggestion {
+                        edit::Suggestion::Product(product) => {
+                            let picked = from_product(product, at);
+                            // A quantity already typed stays, unless the
+                            // product is weighed
+                            let quantity = if picked.weighing.is_some() {
+                                None
+                            } else {
+                                item.quantity.or(picked.quantity)
+                            };
+                            *item = SaleItem {
+                                id,
+                                section: item.section,
+                                quantity,
+                                ..picked
+                            };
+                        }
+                        edit::Suggestion::Sold(name) => {
+                            item.name = name.name;
+                            item.price = name.price;
+                            item.tax_group = name.tax_group;
+                            item.tiers.clear();
+                            item.promotion = None;
+                        }
+                    }
+                }
+                update(sale, form, Message::Edit(edit::Message::SubmitItem(id)))
+            }
             edit::Message::SubmitItem(id) => {
                 // try to move to the next 'field' in this list. if all items
                 // are filled out, add a new item and move to it instead
-                if let Some(item) = sale.items.iter().find(|i| i.id == id) {
-                    return if item.name.is_empty() {
-                        Action::task(text_input::focus(edit::form_id(
-                            "name", id,
-                        )))
-                    } else if item.quantity.is_none() {
-                        Action::task(text_input::focus(edit::form_id(
-                            "quantity", id,
-                        )))
-                    } else if item.price.is_none() {
-                        Action::task(text_input::focus(edit::form_id(
-                            "price", id,
-                        )))
-                    } else {
-                        sale.items.push(SaleItem::default());
-                        Action::task(text_input::focus(edit::form_id(
-                            "name",
-                            id + 1,
-                        )))
-                    };
-                } else {
-                    Action::none()
+                form.completing = None;
+                let Some(item) = sale.items.iter().find(|i| i.id == id) else {
+                    return Action::none();
+                };
+                if let Some(field) = edit::first_empty(item) {
+                    return edit::focus(edit::Focus::Item(field, id));
                 }
+                let next = SaleItem {
+                    section: item.section,
+                    ..SaleItem::default()
+                };
+                let next_id = next.id;
+                sale.items.push(next);
+                edit::focus(edit::Focus::Item(edit::FormField::Name, next_id))
             }
             edit::Message::UpdateServiceCharge(val) => {
-                sale.service_charge_percent = Some(val);
+                sale.service_charge_percent = val;
                 Action::none()
             }
             edit::Message::UpdateGratuity(val) => {
@@ -216,20 +907,892 @@ pub fn update(
     }
 }
 
-pub fn view(sale: &Sale, mode: Mode) -> Element<Message> {
+fn toggle_section(form: &mut Form, id: usize) {
+    if !form.collapsed.remove(&id) {
+        form.collapsed.insert(id);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn view<'a>(
+    sale: &'a Sale,
+    form: &'a Form,
+    id: Option<usize>,
+    mode: Mode,
+    config: &'a Config,
+    save_status: Option<SaveStatus>,
+    qr: Option<&'a image::Handle>,
+    catalog: &'a Catalog,
+    customers: &'a Customers,
+    names: &'a names::Names,
+    linked_by: Vec<Link>,
+    waiting: bool,
+) -> Element<'a, Message> {
     match mode {
-        Mode::View => show::view(sale).map(Message::Show),
-        Mode::Edit => edit::view(sale).map(Message::Edit),
+        Mode::View => {
+            let email = sale
+                .customer
+                .and_then(|id| customers.get(id))
+                .map(|customer| customer.email.trim())
+                .filter(|email| !email.is_empty());
+            show::view(
+                sale,
+                form,
+                id,
+                config,
+                save_status,
+                qr,
+                linked_by,
+                email,
+                waiting,
+            )
+            .map(Message::Show)
+        }
+        Mode::Edit => {
+            edit::view(sale, form, id, config, catalog, customers, names)
+                .map(Message::Edit)
+        }
+    }
+}
+
+/// Shown while a stored sale is being loaded in full.
+pub fn loading<'a>() -> Element<'a, Message> {
+    container(text(tr("Loading sale…")).shaping(text::Shaping::Advanced))
+        .center(Fill)
+        .into()
+}
+
+/// The name of a voided `item`, struck through, and why it was voided.
+pub fn voided<'a, Message: Clone + 'static>(
+    item: &'a SaleItem,
+    size: f32,
+) -> Element<'a, Message> {
+    let reason = item.void.as_ref().map_or("", |void| void.reason.as_str());
+    column![
+        rich_text([span(&item.name).strikethrough(true)]).size(size),
+        text(format!("{}: {reason}", tr("Void")))
+            .size(size - 4.0)
+            .style(text::danger),
+    ]
+    .into()
+}
+
+/// A button for each tip suggested on `sale`, giving its amount to
+/// `on_press`.
+pub fn suggested_tips<'a, Message: Clone + 'a>(
+    sale: &Sale,
+    suggestions: &tips::Suggestions,
+    on_press: impl Fn(f32) -> Message,
+) -> Element<'a, Message> {
+    suggestions
+        .percents
+        .iter()
+        .fold(row![].spacing(5), |row, percent| {
+            let amount = sale.suggested_gratuity(*percent, suggestions.base);
+            row.push(
+                button(text(format!("{percent}%")).size(12))
+                    .on_press(on_press(amount))
+                    .style(button::secondary),
+            )
+        })
+        .into()
+}
+
+/// Add `item` of a product to `sale`, moving on to its weight if it is
+/// weighed.
+fn add_product_item(
+    sale: &mut Sale,
+    item: SaleItem,
+) -> Action<Instruction, Message> {
+    if item.weighing.is_some() {
+        // Weighed items start out waiting for the scale reading
+        let id = item.id;
+        sale.items.push(item);
+        return edit::focus(edit::Focus::Item(edit::FormField::Quantity, id));
+    }
+    sale.items.push(item);
+    Action::none()
+}
+
+/// One of `product`, at its price when the sale was made at `at`: that of
+/// a promotion running then, or else its regular price and tiers. Products
+/// sold by weight have no quantity until the scale is read.
+pub fn from_product(product: Product, at: DateTime<Utc>) -> SaleItem {
+    let (price, tiers, promotion) = match product.promotion_at(at) {
+        Some(promotion) => (
+            Some(promotion.price),
+            Vec::new(),
+            Some(promotion.display_name().to_string()),
+        ),
+        None => (product.price, product.price_tiers(), None),
+    };
+    let weighing = product.by_weight.then(|| Weighing {
+        gross: None,
+        tare: product.tares.first().map_or(0.0, |t| t.weight),
+    });
+
+    SaleItem {
+        quantity: weighing.is_none().then_some(1),
+        tiers,
+        promotion,
+        name: product.name,
+        price,
+        tax_group: product.tax_group,
+        product: Some(product.id),
+        list_price: product.price,
+        allergens: product.allergens,
+        weighing,
+        ..SaleItem::default()
     }
 }
 
 pub fn handle_hotkey(
-    _: &Sale,
+    sale: &mut Sale,
+    form: &mut Form,
     mode: Mode,
     hotkey: Hotkey,
 ) -> Action<Instruction, Message> {
-    match mode {
-        Mode::View => show::handle_hotkey(hotkey).map(Message::Show),
-        Mode::Edit => edit::handle_hotkey(hotkey).map(Message::Edit),
+    match (mode, hotkey) {
+        (Mode::View, _) => show::handle_hotkey(form, hotkey).map(Message::Show),
+        // Saved as by the button, closing only if it is saved
+        (Mode::Edit, Hotkey::SaveAndClose) => {
+            update(sale, form, Message::Edit(edit::Message::Save))
+                .map_instruction(|instruction| match instruction {
+                    Instruction::Save => Instruction::SaveAndClose,
+                    instruction => instruction,
+                })
+        }
+        (Mode::Edit, _) => edit::handle_hotkey(hotkey).map(Message::Edit),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::Tier;
+    use edit::Field;
+
+    fn edit(
+        sale: &mut Sale,
+        form: &mut Form,
+        message: edit::Message,
+    ) -> Action<Instruction, Message> {
+        update(sale, form, Message::Edit(message))
+    }
+
+    fn show(
+        sale: &mut Sale,
+        form: &mut Form,
+        message: show::Message,
+    ) -> Action<Instruction, Message> {
+        update(sale, form, Message::Show(message))
+    }
+
+    fn item(price: f32, quantity: u32) -> SaleItem {
+        SaleItem {
+            name: "Item".to_string(),
+            price: Some(price),
+            quantity: Some(quantity),
+            ..SaleItem::default()
+        }
+    }
+
+    #[test]
+    fn save_refuses_a_negative_total() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            items: vec![item(5.0, 1), item(-8.0, 1)],
+            ..Sale::default()
+        };
+
+        let action = edit(&mut sale, &mut form, edit::Message::Save);
+        assert!(action.instruction.is_none());
+        assert_eq!(
+            action.toast.map(|(kind, _)| kind),
+            Some(toast::Kind::Error)
+        );
+    }
+
+    #[test]
+    fn the_save_and_close_hotkey_checks_as_save_does() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            items: vec![item(5.0, 1), item(-8.0, 1)],
+            ..Sale::default()
+        };
+        form.selected_items.insert(sale.items[0].id);
+
+        let action = handle_hotkey(
+            &mut sale,
+            &mut form,
+            Mode::Edit,
+            Hotkey::SaveAndClose,
+        );
+        assert!(action.instruction.is_none());
+        assert_eq!(
+            action.toast.map(|(kind, _)| kind),
+            Some(toast::Kind::Error)
+        );
+
+        sale.items.pop();
+        let action = handle_hotkey(
+            &mut sale,
+            &mut form,
+            Mode::Edit,
+            Hotkey::SaveAndClose,
+        );
+        assert!(matches!(
+            action.instruction,
+            Some(Instruction::SaveAndClose)
+        ));
+        assert!(form.selected_items.is_empty());
+    }
+
+    #[test]
+    fn refunds_may_save_a_negative_total() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            items: vec![item(-8.0, 1)],
+            link: Some(Link {
+                relation: Relation::RefundOf,
+                sale: 1,
+            }),
+            ..Sale::default()
+        };
+
+        let action = edit(&mut sale, &mut form, edit::Message::Save);
+        assert!(matches!(action.instruction, Some(Instruction::Save)));
+    }
+
+    #[test]
+    fn save_clears_the_selection() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            items: vec![item(5.0, 1)],
+            ..Sale::default()
+        };
+        form.selected_items.insert(sale.items[0].id);
+
+        edit(&mut sale, &mut form, edit::Message::Save);
+        assert!(form.selected_items.is_empty());
+    }
+
+    #[test]
+    fn typed_prices_are_parsed() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            items: vec![SaleItem::default()],
+            ..Sale::default()
+        };
+        let id = sale.items[0].id;
+        let mut price = |typed: &str| {
+            edit(
+                &mut sale,
+                &mut form,
+                edit::Message::UpdateItem(id, Field::Price(typed.to_string())),
+            );
+            sale.items[0].price_string()
+        };
+
+        assert_eq!(price("12.5"), "12.50");
+        assert_eq!(price("-"), "-");
+        assert_eq!(price("-3"), "-3.00");
+        assert_eq!(price(""), "");
+    }
+
+    #[test]
+    fn a_positive_price_is_no_adjustment() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            items: vec![item(-5.0, 1)],
+            ..Sale::default()
+        };
+        let id = sale.items[0].id;
+
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::UpdateItem(id, Field::Adjustment(true)),
+        );
+        assert!(sale.items[0].adjustment);
+
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::UpdateItem(id, Field::Price("5".to_string())),
+        );
+        assert!(!sale.items[0].adjustment);
+    }
+
+    #[test]
+    fn quantity_picks_the_price_tier() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            items: vec![SaleItem {
+                tiers: vec![
+                    Tier { min: 1, price: 4.0 },
+                    Tier { min: 6, price: 3.5 },
+                ],
+                ..item(4.0, 1)
+            }],
+            ..Sale::default()
+        };
+        let id = sale.items[0].id;
+        let quantity = |sale: &mut Sale, typed: &str| {
+            edit(
+                sale,
+                &mut Form::default(),
+                edit::Message::UpdateItem(
+                    id,
+                    Field::Quantity(typed.to_string()),
+                ),
+            );
+        };
+
+        quantity(&mut sale, "6");
+        assert_eq!(sale.items[0].price(), 3.5);
+        assert_eq!(sale.items[0].total(), 21.0);
+
+        // Until a price is typed by hand
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::UpdateItem(id, Field::Price("5".to_string())),
+        );
+        quantity(&mut sale, "2");
+        assert_eq!(sale.items[0].price(), 5.0);
+    }
+
+    #[test]
+    fn fractional_quantities_are_ignored() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            items: vec![item(1.0, 3)],
+            ..Sale::default()
+        };
+        let id = sale.items[0].id;
+
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::UpdateItem(id, Field::Quantity("1.5".to_string())),
+        );
+        assert_eq!(sale.items[0].quantity_string(), "");
+    }
+
+    #[test]
+    fn duplicate_goes_below_the_original() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            items: vec![item(1.0, 1), item(2.0, 1)],
+            ..Sale::default()
+        };
+        let id = sale.items[0].id;
+
+        edit(&mut sale, &mut form, edit::Message::DuplicateItem(id));
+        assert_eq!(sale.items.len(), 3);
+        assert_eq!(sale.items[1].price(), 1.0);
+        assert_ne!(sale.items[1].id, id);
+        assert_eq!(sale.items[2].price(), 2.0);
+    }
+
+    #[test]
+    fn remove_selected_removes_only_those() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            items: vec![item(1.0, 1), item(2.0, 1), item(3.0, 1)],
+            ..Sale::default()
+        };
+        let (first, last) = (sale.items[0].id, sale.items[2].id);
+
+        edit(&mut sale, &mut form, edit::Message::SelectItem(first, true));
+        edit(&mut sale, &mut form, edit::Message::SelectItem(last, true));
+        edit(&mut sale, &mut form, edit::Message::RemoveSelected);
+
+        assert_eq!(sale.items.len(), 1);
+        assert_eq!(sale.items[0].price(), 2.0);
+        assert!(form.selected_items.is_empty());
+    }
+
+    #[test]
+    fn select_all_toggles_every_item() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            items: vec![item(1.0, 1), item(2.0, 1)],
+            ..Sale::default()
+        };
+
+        edit(&mut sale, &mut form, edit::Message::SelectAllItems(true));
+        assert_eq!(form.selected_items.len(), 2);
+        edit(&mut sale, &mut form, edit::Message::SelectAllItems(false));
+        assert!(form.selected_items.is_empty());
+    }
+
+    #[test]
+    fn removing_a_section_keeps_its_items() {
+        let mut form = Form::default();
+        let section = Section::default();
+        let id = section.id;
+        let mut sale = Sale {
+            sections: vec![section],
+            items: vec![SaleItem {
+                section: Some(id),
+                ..item(1.0, 1)
+            }],
+            ..Sale::default()
+        };
+
+        edit(&mut sale, &mut form, edit::Message::RemoveSection(id));
+        assert!(sale.sections.is_empty());
+        assert_eq!(sale.items[0].section, None);
+    }
+
+    #[test]
+    fn submitting_a_complete_item_adds_one_to_its_section() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            items: vec![SaleItem {
+                section: Some(7),
+                ..item(1.0, 1)
+            }],
+            ..Sale::default()
+        };
+        let id = sale.items[0].id;
+
+        edit(&mut sale, &mut form, edit::Message::SubmitItem(id));
+        assert_eq!(sale.items.len(), 2);
+        assert_eq!(sale.items[1].section, Some(7));
+    }
+
+    #[test]
+    fn dine_in_has_no_pager() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            order_type: OrderType::Takeout,
+            pager: Some(12),
+            ..Sale::default()
+        };
+
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::SelectOrderType(OrderType::DineIn),
+        );
+        assert_eq!(sale.pager, None);
+    }
+
+    #[test]
+    fn invalid_guests_are_ignored() {
+        let mut form = Form::default();
+        let mut sale = Sale::default();
+
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::GuestsInput("4".to_string()),
+        );
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::GuestsInput("four".to_string()),
+        );
+        assert_eq!(sale.guests, Some(4));
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::GuestsInput(" ".to_string()),
+        );
+        assert_eq!(sale.guests, None);
+    }
+
+    #[test]
+    fn tags_are_normalized_once() {
+        let mut form = Form::default();
+        let mut sale = Sale::default();
+        for typed in ["Catering", "catering "] {
+            edit(
+                &mut sale,
+                &mut form,
+                edit::Message::TagInput(typed.to_string()),
+            );
+            edit(&mut sale, &mut form, edit::Message::AddTag);
+        }
+        assert_eq!(sale.tags, vec!["catering".to_string()]);
+        assert!(form.tag_input.is_empty());
+    }
+
+    #[test]
+    fn applying_a_gratuity_asks_to_adjust_it() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            gratuity_amount: Some(2.0),
+            ..Sale::default()
+        };
+
+        show(&mut sale, &mut form, show::Message::AdjustGratuity);
+        show(
+            &mut sale,
+            &mut form,
+            show::Message::GratuityInput("3.5".to_string()),
+        );
+        let action = show(&mut sale, &mut form, show::Message::ApplyGratuity);
+
+        assert!(matches!(
+            action.instruction,
+            Some(Instruction::AdjustGratuity { to, pin: None }) if to == 3.5
+        ));
+        // Left as it is until approved
+        assert_eq!(sale.gratuity_amount, Some(2.0));
+        assert_eq!(form.gratuity_input.as_deref(), Some("3.5"));
+
+        // With the manager PIN asked for, it comes along
+        form.gratuity_pin = Some(String::new());
+        show(
+            &mut sale,
+            &mut form,
+            show::Message::GratuityPinInput(Secret("1234".to_string())),
+        );
+        let action = show(&mut sale, &mut form, show::Message::ApplyGratuity);
+        assert!(matches!(
+            action.instruction,
+            Some(Instruction::AdjustGratuity { pin: Some(pin), .. })
+                if pin.0 == "1234"
+        ));
+
+        show(&mut sale, &mut form, show::Message::CancelGratuity);
+        assert_eq!(form.gratuity_input, None);
+        assert_eq!(form.gratuity_pin, None);
+    }
+
+    #[test]
+    fn sections_are_folded_on_screen_only() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            sections: vec![Section::default()],
+            ..Sale::default()
+        };
+        let id = sale.sections[0].id;
+        let stored = serde_json::to_value(&sale).unwrap();
+
+        show(&mut sale, &mut form, show::Message::ToggleSection(id));
+        assert!(form.collapsed.contains(&id));
+        edit(&mut sale, &mut form, edit::Message::ToggleSection(id));
+        assert!(form.collapsed.is_empty());
+
+        show(&mut sale, &mut form, show::Message::ToggleSection(id));
+        assert_eq!(serde_json::to_value(&sale).unwrap(), stored);
+    }
+
+    #[test]
+    fn only_changes_to_what_is_stored_need_saving() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            items: vec![item(10.0, 1)],
+            ..Sale::default()
+        };
+        let stored = sale.stored();
+
+        show(&mut sale, &mut form, show::Message::TogglePreview);
+        show(&mut sale, &mut form, show::Message::ToggleSection(0));
+        show(&mut sale, &mut form, show::Message::AdjustGratuity);
+        show(
+            &mut sale,
+            &mut form,
+            show::Message::GratuityInput("3".to_string()),
+        );
+        show(
+            &mut sale,
+            &mut form,
+            show::Message::TenderedInput("20".to_string()),
+        );
+        assert_eq!(sale.stored(), stored);
+
+        // The gratuity is only set once the adjustment is approved
+        show(&mut sale, &mut form, show::Message::ApplyGratuity);
+        assert_eq!(sale.stored(), stored);
+    }
+
+    #[test]
+    fn an_invalid_gratuity_is_refused() {
+        let mut form = Form::default();
+        let mut sale = Sale::default();
+
+        show(
+            &mut sale,
+            &mut form,
+            show::Message::GratuityInput("lots".to_string()),
+        );
+        let action = show(&mut sale, &mut form, show::Message::ApplyGratuity);
+
+        assert!(action.instruction.is_none());
+        assert_eq!(
+            action.toast.map(|(kind, _)| kind),
+            Some(toast::Kind::Error)
+        );
+        assert_eq!(sale.gratuity_amount, None);
+    }
+
+    #[test]
+    fn voiding_needs_a_reason() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            items: vec![item(3.5, 2)],
+            ..Sale::default()
+        };
+        let id = sale.items[0].id;
+
+        show(&mut sale, &mut form, show::Message::VoidItem(id));
+        let action = show(&mut sale, &mut form, show::Message::ConfirmVoid);
+        assert!(action.instruction.is_none());
+
+        show(
+            &mut sale,
+            &mut form,
+            show::Message::VoidReasonInput(" Spilled ".into()),
+        );
+        show(
+            &mut sale,
+            &mut form,
+            show::Message::VoidPinInput(Secret("1234".to_string())),
+        );
+        let action = show(&mut sale, &mut form, show::Message::ConfirmVoid);
+        assert!(matches!(
+            action.instruction,
+            Some(Instruction::Void { item, reason, pin })
+                if item == id && reason == "Spilled" && pin.0 == "1234"
+        ));
+        // Left to the app, which checks the PIN
+        assert!(!sale.items[0].is_void());
+    }
+
+    #[test]
+    fn picking_up_frees_the_pager() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            order_type: OrderType::Takeout,
+            pager: Some(3),
+            ..Sale::default()
+        };
+
+        show(&mut sale, &mut form, show::Message::MarkReady);
+        assert_eq!(sale.order_status, OrderStatus::Ready);
+        show(&mut sale, &mut form, show::Message::MarkPickedUp);
+        assert_eq!(sale.order_status, OrderStatus::PickedUp);
+        assert_eq!(sale.pager, None);
+    }
+
+    #[test]
+    fn quick_sale_lines_are_priced_from_the_catalog() {
+        let mut catalog = Catalog::default();
+        let id = catalog.add();
+        let latte = catalog.get_mut(id).unwrap();
+        latte.name = "Latte".to_string();
+        latte.price = Some(4.5);
+        latte.tiers = vec![Tier { min: 3, price: 4.0 }];
+
+        let mut sale = Sale::default();
+        let mut input = String::new();
+        let mut quick = |input: &mut String, message| {
+            quick::update(&mut sale, input, &catalog, message)
+        };
+
+        input.push_str("3x lat");
+        quick(&mut input, quick::Message::Add);
+        input.push_str("2 x muffin 3,25");
+        quick(&mut input, quick::Message::Add);
+        input.push_str("scone");
+        let refused = quick(&mut input, quick::Message::Add);
+        assert!(refused.toast.is_some());
+        assert_eq!(input, "scone");
+
+        input.clear();
+        let saved = quick(&mut input, quick::Message::Save);
+        assert!(matches!(saved.instruction, Some(quick::Instruction::Save)));
+
+        let lines: Vec<_> = sale
+            .items
+            .iter()
+            .map(|item| (item.name.as_str(), item.quantity, item.price()))
+            .collect();
+        assert_eq!(lines, [("Latte", Some(3), 4.0), ("muffin", Some(2), 3.25)]);
+        assert_eq!(sale.items[0].product, Some(id));
+    }
+
+    #[test]
+    fn item_names_typed_as_shorthand_fill_the_row() {
+        let mut sale = Sale {
+            items: vec![SaleItem::default()],
+            ..Sale::default()
+        };
+        let id = sale.items[0].id;
+        let name = |sale: &mut Sale, typed: &str| {
+            let form = &mut Form::default();
+            edit(
+                sale,
+                form,
+                edit::Message::UpdateItem(id, Field::Name(typed.to_string())),
+            );
+            edit(sale, form, edit::Message::ExpandItem(id));
+        };
+
+        name(&mut sale, "3 burger @ 9.99 food");
+        let item = &sale.items[0];
+        assert_eq!(item.name, "burger");
+        assert_eq!(item.quantity, Some(3));
+        assert_eq!(item.price, Some(9.99));
+        assert_eq!(item.tax_group, TaxGroup::Food);
+        // Filled out, so on to a new row
+        assert_eq!(sale.items.len(), 2);
+
+        // The quantity already typed stays
+        name(&mut sale, "Burger 2");
+        assert_eq!(sale.items[0].name, "Burger");
+        assert_eq!(sale.items[0].price, Some(2.0));
+        assert_eq!(sale.items[0].quantity, Some(3));
+        // A plain name is left as typed
+        name(&mut sale, "7up");
+        assert_eq!(sale.items[0].name, "7up");
+    }
+
+    #[test]
+    fn focus_goes_to_the_first_field_left_empty() {
+        let mut priced = item(2.0, 1);
+        priced.price = None;
+        let sale = Sale {
+            items: vec![item(5.0, 1), SaleItem::default(), priced.clone()],
+            ..Sale::default()
+        };
+
+        assert_eq!(
+            edit::first_invalid(&sale),
+            Some(edit::Focus::Item(edit::FormField::Price, priced.id))
+        );
+        // A blank row alone is not invalid
+        let blank = Sale {
+            items: vec![SaleItem::default()],
+            ..Sale::default()
+        };
+        assert_eq!(edit::first_invalid(&blank), None);
+    }
+
+    #[test]
+    fn picking_a_suggestion_fills_in_the_item() {
+        let mut form = Form::default();
+        let mut sale = Sale {
+            items: vec![SaleItem::default()],
+            ..Sale::default()
+        };
+        let id = sale.items[0].id;
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::UpdateItem(id, Field::Name("lat".to_string())),
+        );
+        assert_eq!(form.completing, Some(id));
+
+        let mut catalog = Catalog::default();
+        let latte = catalog.add();
+        let product = catalog.get_mut(latte).unwrap();
+        product.name = "Latte".to_string();
+        product.price = Some(4.5);
+        let suggestions =
+            edit::suggestions("lat", &catalog, &names::Names::default());
+        assert_eq!(suggestions.len(), 1);
+
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::PickSuggestion(id, suggestions[0].clone()),
+        );
+        let item = &sale.items[0];
+        assert_eq!(item.id, id);
+        assert_eq!(item.name, "Latte");
+        assert_eq!(item.price, Some(4.5));
+        assert_eq!(item.product, Some(latte));
+        assert_eq!(form.completing, None);
+        // Filled out, so on to a new row
+        assert_eq!(sale.items.len(), 2);
+    }
+
+    #[test]
+    fn open_price_products_are_added_at_the_price_typed() {
+        let mut form = Form::default();
+        let mut catalog = Catalog::default();
+        let id = catalog.add();
+        let misc = catalog.get_mut(id).unwrap();
+        misc.name = "Misc".to_string();
+        misc.price = Some(1.0);
+        misc.open_price = true;
+        let misc = misc.clone();
+        let mut sale = Sale::default();
+
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::AddProduct(misc.clone()),
+        );
+        assert!(sale.items.is_empty());
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::OpenPriceInput("abc".to_string()),
+        );
+        edit(&mut sale, &mut form, edit::Message::ConfirmOpenPrice);
+        assert!(sale.items.is_empty());
+        assert!(form.open_price.is_some());
+
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::OpenPriceInput("3.75".to_string()),
+        );
+        edit(&mut sale, &mut form, edit::Message::ConfirmOpenPrice);
+        assert!(form.open_price.is_none());
+        assert_eq!(sale.items.len(), 1);
+        assert_eq!(sale.items[0].price, Some(3.75));
+        assert_eq!(sale.items[0].product, Some(id));
+
+        edit(&mut sale, &mut form, edit::Message::AddProduct(misc));
+        edit(&mut sale, &mut form, edit::Message::CancelOpenPrice);
+        assert_eq!(sale.items.len(), 1);
+    }
+
+    #[test]
+    fn prices_typed_over_the_catalog_are_overrides() {
+        let mut form = Form::default();
+        let mut catalog = Catalog::default();
+        let id = catalog.add();
+        let wine = catalog.get_mut(id).unwrap();
+        wine.name = "Wine".to_string();
+        wine.price = Some(10.0);
+        let mut sale = Sale::default();
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::AddProduct(wine.clone()),
+        );
+        let item = sale.items[0].id;
+        assert_eq!(sale.items[0].price_override(), None);
+        let stored = sale.clone();
+
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::UpdateItem(item, Field::Price("8".to_string())),
+        );
+        let line = &sale.items[0];
+        assert_eq!(line.price_override(), Some((10.0, 8.0)));
+        assert!(line.overrides_by_more_than(10));
+        assert!(!line.overrides_by_more_than(20));
+        assert_eq!(sale.overrides_since(Some(&stored)).len(), 1);
+        // Saved as is, it isn't a new override
+        assert!(sale.overrides_since(Some(&sale.clone())).is_empty());
+
+        edit(
+            &mut sale,
+            &mut form,
+            edit::Message::UpdateItem(item, Field::Price("10".to_string())),
+        );
+        assert_eq!(sale.items[0].price_override(), None);
     }
 }
@@ -0,0 +1,174 @@
+//! Keeping or merging a sale edited here and elsewhere at once
+//!
+//! Shown instead of saving a draft when its sale changed since it was taken,
+//! e.g. marked picked up on the order board or replaced by a sync, and when a
+//! sync finds a sale saved on both sides. Nothing is saved until one side is
+//! kept or both are merged.
+use iced::widget::{
+    button, column, container, horizontal_space, row, scrollable, text,
+};
+use iced::{Alignment, Element, Fill, Theme};
+
+use super::SaleItem;
+use crate::i18n::{money, tr};
+use crate::{Action, Hotkey, Screen};
+
+pub use receipts_core::sale::conflict::*;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Go back to editing the draft.
+    Back,
+    Keep(Side),
+    Merge,
+}
+
+/// How the conflict is settled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Save one side over the other.
+    Keep(Side),
+    /// Save the stored sale with the changes made here.
+    Merge,
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Navigate(Screen),
+    /// Leave the sale as it is for now.
+    Back,
+    Resolve(Resolution),
+}
+
+impl From<Screen> for Instruction {
+    fn from(screen: Screen) -> Self {
+        Instruction::Navigate(screen)
+    }
+}
+
+pub fn update(message: Message) -> Action<Instruction, Message> {
+    match message {
+        Message::Back => Action::instruction(Instruction::Back),
+        Message::Keep(side) => {
+            Action::instruction(Instruction::Resolve(Resolution::Keep(side)))
+        }
+        Message::Merge => {
+            Action::instruction(Instruction::Resolve(Resolution::Merge))
+        }
+    }
+}
+
+pub fn view(conflict: &Conflict) -> Element<'_, Message> {
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text(format!("{} #{}", tr("Sale changed elsewhere"), conflict.id))
+            .size(16),
+        horizontal_space(),
+        button(tr("Keep mine"))
+            .on_press(Message::Keep(Side::Mine))
+            .style(button::secondary),
+        button(tr("Keep theirs"))
+            .on_press(Message::Keep(Side::Theirs))
+            .style(button::secondary),
+        button(tr("Merge items"))
+            .on_press(Message::Merge)
+            .style(button::success),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let explanation = text(tr(
+        "The sale was changed elsewhere while you edited it, e.g. on the \
+         order board or by a sync. Merging keeps the lines added and removed \
+         on both sides.",
+    ))
+    .size(12);
+
+    let fields = conflict.fields();
+    let fields: Element<_> = if fields.is_empty() {
+        text(tr("Only the lines differ")).size(12).into()
+    } else {
+        fields
+            .into_iter()
+            .fold(
+                column![row![
+                    text("").width(150.0),
+                    text(tr("Mine")).width(Fill),
+                    text(tr("Theirs")).width(Fill),
+                ]
+                .spacing(10)]
+                .spacing(5),
+                |col, field| {
+                    col.push(
+                        row![
+                            text(field.name).width(150.0),
+                            text(field.mine).width(Fill),
+                            text(field.theirs).width(Fill),
+                        ]
+                        .spacing(10),
+                    )
+                },
+            )
+            .into()
+    };
+
+    let sides = row![
+        lines(tr("Mine"), conflict.lines(Side::Mine)),
+        lines(tr("Theirs"), conflict.lines(Side::Theirs)),
+    ]
+    .spacing(20);
+
+    container(scrollable(
+        column![
+            header,
+            explanation,
+            container(fields)
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            sides,
+        ]
+        .spacing(20),
+    ))
+    .padding(20)
+    .into()
+}
+
+/// The lines one side added and removed.
+fn lines<'a>(title: &'a str, lines: Lines<'a>) -> Element<'a, Message> {
+    let line =
+        |sign: &str, item: &SaleItem, style: fn(&Theme) -> text::Style| {
+            row![
+                text(format!("{sign} {} {}", item.quantity_label(), item.name))
+                    .style(style)
+                    .width(Fill),
+                text(money(item.total())).style(style),
+            ]
+            .spacing(10)
+        };
+
+    let changes = if lines.added.is_empty() && lines.removed.is_empty() {
+        column![text(tr("No lines changed")).size(12)]
+    } else {
+        let added = lines.added.into_iter().fold(column![], |col, item| {
+            col.push(line("+", item, text::success))
+        });
+        lines
+            .removed
+            .into_iter()
+            .fold(added, |col, item| col.push(line("−", item, text::danger)))
+    };
+
+    container(column![text(title).size(16), changes.spacing(5)].spacing(10))
+        .padding(20)
+        .width(Fill)
+        .style(container::rounded_box)
+        .into()
+}
+
+pub fn handle_hotkey(hotkey: Hotkey) -> Action<Instruction, Message> {
+    match hotkey {
+        Hotkey::Escape => update(Message::Back),
+        _ => Action::none(),
+    }
+}
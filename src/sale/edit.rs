@@ -1,24 +1,101 @@
 //! Edit new and existing sales
 use iced::widget::{
-    button, column, container, focus_next, focus_previous, horizontal_space,
-    pick_list, row, scrollable, text, text_input,
+    button, checkbox, column, container, focus_next, focus_previous,
+    horizontal_space, pick_list, row, scrollable, text, text_input, tooltip,
 };
-use iced::Alignment::Center;
-use iced::{Alignment, Element, Fill};
+use iced::{Alignment, Element, Fill, Length, Task};
+use std::fmt;
 
-use super::{Action, Instruction, Sale, TaxGroup};
+use super::names::{Name, Names};
+use super::{
+    Action, ButtonPage, Form, Instruction, Keypad, KeypadTarget, OrderType,
+    Sale, SaleItem, Section, TaxGroup, Tender, Weighing,
+};
+use crate::allergen;
+use crate::catalog::{Catalog, Product, Tare};
+use crate::config::Config;
+use crate::custom;
+use crate::customer::{self, Customers};
+use crate::delivery;
+use crate::density::Density;
+use crate::dialog;
+use crate::i18n::{self, money, tr};
+use crate::invoice::{self, Terms};
+use crate::keypad;
+use crate::prompt;
+use crate::quick_buttons;
+use crate::receipt;
+use crate::schedule;
+use crate::secret::Secret;
+use crate::style;
+use crate::tag::{self, Label};
 use crate::Hotkey;
 
 #[derive(Debug, Clone)]
 pub enum Message {
     NameInput(String),
     NameSubmit,
-    AddItem,
+    SelectOrderType(OrderType),
+    PagerInput(String),
+    DeliveryAddressInput(String),
+    DeliveryPhoneInput(String),
+    DeliveryDistanceInput(String),
+    GuestsInput(String),
+    DueInput(String),
+    /// The customer the sale is made for, or `None` for no one.
+    SelectCustomer(Option<customer::Choice>),
+    ToggleInvoice(bool),
+    InvoiceCustomerInput(String),
+    SelectTerms(Terms),
+    AllergyNoteInput(String),
+    TagInput(String),
+    AddTag,
+    RemoveTag(String),
+    SelectLabel(Option<Label>),
+    CustomFieldInput(usize, String),
+    AddItem(Option<usize>),
     RemoveItem(usize),
+    /// Copy an item into a new line right below it.
+    DuplicateItem(usize),
+    SelectItem(usize, bool),
+    SelectAllItems(bool),
+    RemoveSelected,
+    AddSection,
+    RemoveSection(usize),
+    RenameSection(usize, String),
+    MoveSection(usize, isize),
+    ToggleSection(usize),
     UpdateItem(usize, Field),
     SubmitItem(usize),
-    UpdateServiceCharge(f32),
+    /// Read the name of an item as shorthand, then move on as on submit.
+    ExpandItem(usize),
+    /// Fill in an item from a name suggested while typing it.
+    PickSuggestion(usize, Suggestion),
+    /// Read the weight of an item off the scale.
+    ReadScale(usize),
+    /// A service charge typed over the rules, or `None` to follow them.
+    UpdateServiceCharge(Option<f32>),
     UpdateGratuity(f32),
+    AddGiftCard,
+    AddProduct(Product),
+    OpenPriceInput(String),
+    /// Add the open price product at the price typed.
+    ConfirmOpenPrice,
+    CancelOpenPrice,
+    ApprovalPinInput(Secret),
+    /// Save with the price overrides, approved by the manager PIN typed.
+    ApproveOverrides,
+    CancelApproval,
+    /// Show another page of quick buttons.
+    ShowButtons(ButtonPage),
+    AddTender,
+    TenderCodeInput(usize, String),
+    ApplyTender(usize),
+    RemoveTender(usize),
+    OpenKeypad(KeypadTarget),
+    KeypadInput(String),
+    CloseKeypad,
+    TogglePreview,
     Save,
     Cancel,
 }
@@ -29,21 +106,185 @@ pub enum Field {
     Price(String),
     Quantity(String),
     TaxGroup(TaxGroup),
+    /// Whether a negative item is left out of tax.
+    Adjustment(bool),
+    Section(Option<usize>),
+    /// The container weight taken off a weighed item's scale reading.
+    Tare(f32),
 }
 
-pub fn view(sale: &Sale) -> Element<Message> {
-    let header = row![
+/// A name suggested under the item name being typed.
+#[derive(Debug, Clone)]
+pub enum Suggestion {
+    Product(Product),
+    /// A name sold on an earlier sale.
+    Sold(Name),
+}
+
+impl Suggestion {
+    fn name(&self) -> &str {
+        match self {
+            Suggestion::Product(product) => product.display_name(),
+            Suggestion::Sold(name) => &name.name,
+        }
+    }
+
+    fn price(&self) -> Option<f32> {
+        match self {
+            Suggestion::Product(product) => product.price,
+            Suggestion::Sold(name) => name.price,
+        }
+    }
+}
+
+/// How many names are suggested at most.
+const SUGGESTIONS: usize = 5;
+
+/// The names to suggest for `typed`: products of the catalog first, then
+/// names sold before which aren't products.
+pub fn suggestions(
+    typed: &str,
+    catalog: &Catalog,
+    names: &Names,
+) -> Vec<Suggestion> {
+    let typed = typed.trim().to_lowercase();
+    if typed.is_empty() {
+        return Vec::new();
+    }
+    let mut products: Vec<&Product> = catalog
+        .iter()
+        .filter(|product| {
+            let name = product.name.to_lowercase();
+            name != typed && name.contains(&typed)
+        })
+        .collect();
+    products.sort_by_key(|product| {
+        !product.name.to_lowercase().starts_with(&typed)
+    });
+
+    let sold = names
+        .matching(&typed, SUGGESTIONS)
+        .into_iter()
+        .filter(|name| {
+            !catalog
+                .iter()
+                .any(|product| product.name.eq_ignore_ascii_case(&name.name))
+        })
+        .map(|name| Suggestion::Sold(name.clone()));
+    products
+        .into_iter()
+        .map(|product| Suggestion::Product(product.clone()))
+        .chain(sold)
+        .take(SUGGESTIONS)
+        .collect()
+}
+
+/// The message typing `value` into the input `target` sends.
+pub fn keypad_message(target: KeypadTarget, value: String) -> Message {
+    match target {
+        KeypadTarget::Quantity(id) => {
+            Message::UpdateItem(id, Field::Quantity(value))
+        }
+        KeypadTarget::Price(id) => Message::UpdateItem(id, Field::Price(value)),
+        KeypadTarget::Gratuity => {
+            Message::UpdateGratuity(i18n::parse_number(&value).unwrap_or(0.0))
+        }
+        KeypadTarget::TenderCode(id) => Message::TenderCodeInput(id, value),
+    }
+}
+
+/// An entry of the section picker shown on each item row.
+#[derive(Debug, Clone, PartialEq)]
+struct SectionChoice {
+    id: Option<usize>,
+    name: String,
+}
+
+impl fmt::Display for SectionChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+pub fn view<'a>(
+    sale: &'a Sale,
+    form: &'a Form,
+    id: Option<usize>,
+    config: &'a Config,
+    catalog: &'a Catalog,
+    customers: &'a Customers,
+    names: &'a Names,
+) -> Element<'a, Message> {
+    let fields = &config.custom_fields;
+    let density = config.density;
+    let keypad = config.keypad;
+
+    let details = row![
         horizontal_space().width(40),
-        text_input("Sale Name", &sale.name)
+        text_input(tr("Sale Name"), &sale.name)
+            .style(style::input)
+            .id(Focus::SaleName.id())
             .on_input(Message::NameInput)
             .on_submit(Message::NameSubmit)
             .padding(5),
+        pick_list(
+            &OrderType::ALL[..],
+            Some(sale.order_type),
+            Message::SelectOrderType
+        )
+        .width(110.0),
+        text_input(
+            tr("Guests"),
+            &sale.guests.map_or(String::new(), |g| g.to_string()),
+        )
+        .style(style::input)
+        .on_input(Message::GuestsInput)
+        .width(70.0)
+        .padding(5),
+        text_input(
+            tr("Due at"),
+            &form.due_input.clone().unwrap_or_else(|| {
+                sale.due_at.map_or(String::new(), schedule::label)
+            }),
+        )
+        .style(style::input)
+        .on_input(Message::DueInput)
+        .width(140.0)
+        .padding(5),
+    ]
+    .spacing(5)
+    .align_y(Alignment::Center);
+
+    let details = if sale.order_type == OrderType::Takeout {
+        details.push(
+            text_input(
+                tr("Pager"),
+                &sale.pager.map_or(String::new(), |p| p.to_string()),
+            )
+            .style(style::input)
+            .on_input(Message::PagerInput)
+            .width(70.0)
+            .padding(5),
+        )
+    } else {
+        details
+    };
+
+    let header = row![
+        details,
         horizontal_space(),
         row![
-            button("Cancel")
+            button(tr("Preview"))
+                .on_press(Message::TogglePreview)
+                .style(if config.receipt_preview {
+                    button::primary
+                } else {
+                    button::secondary
+                }),
+            button(tr("Cancel"))
                 .on_press(Message::Cancel)
                 .style(button::danger),
-            button("Save")
+            button(tr("Save"))
                 .on_press(Message::Save)
                 .style(button::success),
         ]
@@ -52,179 +293,1266 @@ pub fn view(sale: &Sale) -> Element<Message> {
     .spacing(5)
     .align_y(Alignment::Center);
 
-    let column_headers = row![
-        text("Item Name").width(Fill),
-        text("Qty").align_x(Alignment::Center).width(80.0),
-        text("Price").align_x(Alignment::End).width(100.0),
-        text("Tax Group").width(140.0),
-        text("Total").align_x(Alignment::End).width(100.0),
-        horizontal_space().width(25),
+    let has_sections = !sale.sections.is_empty();
+    let section_choices: Vec<SectionChoice> = std::iter::once(SectionChoice {
+        id: None,
+        name: tr("No section").to_string(),
+    })
+    .chain(sale.sections.iter().map(|section| SectionChoice {
+        id: Some(section.id),
+        name: section.display_name().to_string(),
+    }))
+    .collect();
+
+    let all_selected = !sale.items.is_empty()
+        && sale
+            .items
+            .iter()
+            .all(|i| form.selected_items.contains(&i.id));
+    let mut column_headers = row![
+        checkbox("", all_selected)
+            .on_toggle_maybe(
+                (!sale.items.is_empty()).then_some(Message::SelectAllItems)
+            )
+            .spacing(0),
+        text(tr("Item Name")).width(Fill),
+        text(tr("Qty")).align_x(Alignment::Center).width(80.0),
+        text(tr("Price")).align_x(Alignment::End).width(100.0),
+        text(tr("Tax Group")).width(140.0),
     ]
     .spacing(2)
     .padding([0, 10]);
+    if has_sections {
+        column_headers = column_headers.push(text(tr("Section")).width(120.0));
+    }
+    let column_headers = column_headers
+        .push(text(tr("Total")).align_x(Alignment::End).width(100.0))
+        .push(horizontal_space().width(25));
 
-    let items_list = sale.items.iter().fold(
-        column![column_headers].spacing(5).width(Fill),
-        |col, item| {
-            col.push(
-                container(
-                    row![
-                        text_input("Item name", &item.name)
-                            .id(form_id("name", item.id))
-                            .on_input(|s| Message::UpdateItem(
-                                item.id,
-                                Field::Name(s)
-                            ))
-                            .on_submit(Message::SubmitItem(item.id))
-                            .width(Fill)
-                            .padding(5),
-                        text_input("Quantity", &item.quantity_string())
-                            .id(form_id("quantity", item.id))
-                            .align_x(Alignment::Center)
-                            .on_input(|s| Message::UpdateItem(
-                                item.id.clone(),
-                                Field::Quantity(s)
-                            ))
-                            .on_submit(Message::SubmitItem(item.id))
-                            .width(80.0)
-                            .padding(5),
-                        text_input("Price", &item.price_string())
-                            .id(form_id("price", item.id))
-                            .align_x(Alignment::End)
-                            .on_input(|s| Message::UpdateItem(
-                                item.id,
-                                Field::Price(s)
-                            ))
-                            .on_submit(Message::SubmitItem(item.id))
-                            .width(100.0)
-                            .padding(5),
-                        pick_list(
-                            &TaxGroup::ALL[..],
-                            Some(item.tax_group),
-                            move |tax_group| {
-                                Message::UpdateItem(
-                                    item.id,
-                                    Field::TaxGroup(tax_group),
-                                )
-                            }
-                        )
-                        .width(140.0),
-                        text(format!("${:.2}", item.price() * item.quantity()))
-                            .align_x(Alignment::End)
-                            .width(100.0),
-                        button(text("×").center())
-                            .width(25.0)
-                            .on_press(Message::RemoveItem(item.id))
-                            .style(button::danger)
-                    ]
-                    .spacing(5)
-                    .align_y(Alignment::Center),
-                )
-                .style(container::rounded_box)
-                .padding(0),
-            )
+    let column_headers: Element<_> = if form.selected_items.is_empty() {
+        column_headers.into()
+    } else {
+        column![
+            row![
+                text(format!(
+                    "{} {}",
+                    form.selected_items.len(),
+                    tr("selected")
+                )),
+                horizontal_space(),
+                button(text(tr("Remove selected")).size(14))
+                    .on_press(Message::RemoveSelected)
+                    .style(button::danger),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+            .padding([0, 10]),
+            column_headers,
+        ]
+        .spacing(5)
+        .into()
+    };
+
+    let items_list = sale.courses().into_iter().fold(
+        column![column_headers]
+            .spacing(density.table_spacing())
+            .width(Fill),
+        |col, (section, items)| {
+            let col = match section {
+                Some(section) => col.push(section_header(
+                    section,
+                    form.collapsed.contains(&section.id),
+                    sale.calculate_section_subtotal(Some(section.id)),
+                )),
+                None => col,
+            };
+
+            if section
+                .is_some_and(|section| form.collapsed.contains(&section.id))
+            {
+                return col;
+            }
+
+            items.into_iter().fold(col, |col, item| {
+                let tares = item
+                    .product
+                    .and_then(|id| catalog.get(id))
+                    .map_or(&[][..], |product| &product.tares[..]);
+                let col = col.push(item_row(
+                    item,
+                    form.selected_items.contains(&item.id),
+                    has_sections,
+                    &section_choices,
+                    tares,
+                    keypad,
+                    config.shorthand,
+                    config.scale.is_enabled(),
+                    density,
+                ));
+                let found = if form.completing == Some(item.id) {
+                    suggestions(&item.name, catalog, names)
+                } else {
+                    Vec::new()
+                };
+                if found.is_empty() {
+                    col
+                } else {
+                    col.push(suggestion_list(item.id, found, density))
+                }
+            })
         },
     );
 
     let totals = column![
         row![
-            text("Subtotal").width(150.0),
+            text(tr("Subtotal")).width(150.0),
             horizontal_space(),
-            text(format!("${:.2}", sale.calculate_subtotal()))
+            text(money(sale.calculate_subtotal()))
         ],
         row![
-            text("Service Charge").width(150.0),
+            text(tr("Service Charge")).width(150.0),
             row![
                 text_input(
-                    "0.0",
+                    &sale.service_rule.map_or("0.0".to_string(), |r| {
+                        i18n::number(r.percent, 1)
+                    }),
                     &sale
                         .service_charge_percent
-                        .map_or(String::new(), |p| format!("{:.1}", p)),
+                        .map_or(String::new(), |p| i18n::number(p, 1)),
                 )
+                .style(style::input)
                 .width(60.0)
                 .padding(5)
                 .on_input(|s| Message::UpdateServiceCharge(if s.is_empty() {
-                    0.0
+                    None
                 } else {
-                    s.parse().ok().unwrap_or(0.0)
+                    Some(i18n::parse_number(&s).unwrap_or(0.0))
                 }))
                 .on_submit(Message::Save),
-                text("%")
+                text("%"),
+                service_rule(sale),
             ]
-            .spacing(5),
+            .spacing(5)
+            .align_y(Alignment::Center),
             horizontal_space(),
-            text(format!("${:.2}", sale.calculate_service_charge()))
+            text(money(sale.calculate_service_charge()))
         ],
+        taxes(sale),
         row![
-            text("Tax").width(150.0),
-            horizontal_space(),
-            text(format!("${:.2}", sale.calculate_tax()))
-        ],
-        row![
-            text("Gratuity").width(150.0),
+            text(tr("Gratuity")).width(150.0),
             text_input(
                 "0.00",
                 &sale
                     .gratuity_amount
-                    .map_or(String::new(), |g| format!("{:.2}", g)),
+                    .map_or(String::new(), |g| i18n::number(g, 2)),
             )
+            .style(style::input)
             .width(100.0)
             .padding(5)
             .on_input(|s| Message::UpdateGratuity(if s.is_empty() {
                 0.0
             } else {
-                s.parse().ok().unwrap_or(0.0)
+                i18n::parse_number(&s).unwrap_or(0.0)
             }))
             .on_submit(Message::Save),
+            keypad_button(keypad, KeypadTarget::Gratuity),
+            super::suggested_tips(
+                sale,
+                &config.tip_suggestions,
+                Message::UpdateGratuity
+            ),
             horizontal_space(),
-            text(format!("${:.2}", sale.gratuity_amount.unwrap_or(0.0)))
+            text(money(sale.gratuity_amount.unwrap_or(0.0)))
         ],
+        delivery_fee(sale),
         row![
-            text("Total").width(150.0).size(16),
+            text(tr("Total")).width(150.0).size(16),
             horizontal_space(),
-            text(format!("${:.2}", sale.calculate_total())).size(16)
+            text(money(sale.calculate_total())).size(16)
         ]
     ]
     .spacing(2)
     .width(Fill);
 
-    container(
+    let totals = sale
+        .tenders
+        .iter()
+        .fold(totals, |col, tender| col.push(tender_row(tender, keypad)));
+    let totals = if sale.tenders.is_empty() {
+        totals
+    } else {
+        totals.push(row![
+            text(tr("Due")).width(150.0).size(16),
+            horizontal_space(),
+            text(money(sale.calculate_due())).size(16)
+        ])
+    };
+    let totals = totals.push(
+        button(text(tr("+ Gift card payment")).size(12))
+            .on_press(Message::AddTender)
+            .style(button::secondary),
+    );
+
+    let allergy_note = text_input(tr("Allergy note"), &sale.allergy_note)
+        .style(style::input)
+        .on_input(Message::AllergyNoteInput)
+        .padding(5);
+    let header = column![header]
+        .push_maybe(delivery(sale, config))
+        .push(billing(sale, customers))
+        .push(row![horizontal_space().width(40), allergy_note].spacing(5))
+        .push(row![horizontal_space().width(40), tags(sale, form)].spacing(5))
+        .spacing(10);
+
+    let content = if fields.is_empty() {
+        column![header]
+    } else {
         column![
             header,
+            container(custom_fields(sale, fields))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box)
+        ]
+    };
+
+    let body = content
+        .push(
             container(scrollable(
                 column![
-                    button("+ Add Item")
-                        .on_press(Message::AddItem)
-                        .style(button::primary),
+                    add_buttons(catalog),
+                    quick_buttons(form, config, catalog, names),
                     items_list,
                 ]
                 .spacing(10)
-                .padding(20)
+                .padding(20),
             ))
             .height(Fill)
             .style(container::rounded_box),
-            container(totals).padding(20).style(container::rounded_box)
-        ]
+        )
+        .push(container(totals).padding(20).style(container::rounded_box))
         .spacing(20)
-        .height(Fill),
+        .height(Fill);
+
+    let body: Element<_> = if config.receipt_preview {
+        row![body, preview(sale, id, config)].spacing(20).into()
+    } else {
+        body.into()
+    };
+
+    let screen = container(body).padding(20);
+    if let Some(pin) = &form.approval_pin {
+        return dialog::view(
+            screen,
+            approval_dialog(pin),
+            Message::CancelApproval,
+        );
+    }
+    if let Some(open) = &form.open_price {
+        let confirm = parse_price(&open.price)
+            .is_some()
+            .then_some(Message::ConfirmOpenPrice);
+        return dialog::view(
+            screen,
+            prompt::view(
+                format!("{} – {}", open.product.display_name(), tr("Price")),
+                &open.price,
+                open_price_id(),
+                keypad,
+                Message::OpenPriceInput,
+                confirm,
+                Message::CancelOpenPrice,
+            ),
+            Message::CancelOpenPrice,
+        );
+    }
+    match &form.keypad {
+        Some(open) if keypad => dialog::view(
+            screen,
+            keypad_dialog(sale, open),
+            Message::CloseKeypad,
+        ),
+        _ => screen.into(),
+    }
+}
+
+/// The receipt as it will print, beside the form.
+fn preview<'a>(
+    sale: &Sale,
+    id: Option<usize>,
+    config: &Config,
+) -> Element<'a, Message> {
+    container(scrollable(
+        container(receipt::of(id, sale, config)).padding(10),
+    ))
+    .width(Length::Shrink)
+    .height(Fill)
+    .into()
+}
+
+fn add_buttons(catalog: &Catalog) -> Element<'_, Message> {
+    let buttons = row![
+        button(tr("+ Add Item"))
+            .on_press(Message::AddItem(None))
+            .style(button::primary),
+        button(tr("+ Add Section"))
+            .on_press(Message::AddSection)
+            .style(button::secondary),
+        button(tr("+ Gift Card"))
+            .on_press(Message::AddGiftCard)
+            .style(button::secondary),
+    ]
+    .spacing(10);
+
+    if catalog.is_empty() {
+        buttons.into()
+    } else {
+        let products: Vec<Product> = catalog.iter().cloned().collect();
+        buttons
+            .push(
+                pick_list(products, None::<Product>, Message::AddProduct)
+                    .placeholder(tr("+ From catalog…"))
+                    .width(250.0),
+            )
+            .into()
+    }
+}
+
+/// The products added with one tap, if set up and there are any, with a
+/// tab for each category of the catalog.
+fn quick_buttons<'a>(
+    form: &'a Form,
+    config: &'a Config,
+    catalog: &'a Catalog,
+    names: &'a Names,
+) -> Element<'a, Message> {
+    let setup = &config.quick_buttons;
+    let categories = catalog.categories();
+    if !setup.enabled || catalog.is_empty() {
+        return horizontal_space().width(0).into();
+    }
+
+    // A category emptied since is shown as the first tab again
+    let category = form
+        .buttons
+        .category
+        .as_deref()
+        .filter(|category| categories.contains(category));
+    let (products, pages) =
+        quick_buttons::page(setup, catalog, names, category, form.buttons.page);
+    let page = form.buttons.page.min(pages - 1);
+    let grid =
+        quick_buttons::grid(products, setup.columns, config.density, |p| {
+            Some(Message::AddProduct(p.clone()))
+        });
+    if categories.is_empty() {
+        return grid;
+    }
+
+    let tab = |label: String, to: Option<&str>| {
+        button(text(label))
+            .on_press(Message::ShowButtons(ButtonPage {
+                category: to.map(str::to_string),
+                page: 0,
+            }))
+            .style(if to == category {
+                button::primary
+            } else {
+                button::secondary
+            })
+    };
+    let tabs = categories.iter().fold(
+        row![tab(tr("Top").to_string(), None)].spacing(5),
+        |tabs, to| tabs.push(tab(to.to_string(), Some(to))),
+    );
+    let turn = |to: usize| ButtonPage {
+        category: category.map(str::to_string),
+        page: to,
+    };
+    let pager = row![
+        button(text("‹").center())
+            .width(40)
+            .on_press_maybe(
+                (page > 0).then(|| Message::ShowButtons(turn(page - 1)))
+            )
+            .style(button::secondary),
+        text(format!("{}/{}", page + 1, pages)),
+        button(text("›").center())
+            .width(40)
+            .on_press_maybe(
+                (page + 1 < pages)
+                    .then(|| Message::ShowButtons(turn(page + 1)))
+            )
+            .style(button::secondary),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let tabs = if pages > 1 {
+        tabs.push(horizontal_space()).push(pager)
+    } else {
+        tabs
+    };
+    column![tabs.align_y(Alignment::Center), grid]
+        .spacing(5)
+        .into()
+}
+
+fn custom_fields<'a>(
+    sale: &'a Sale,
+    fields: &'a [custom::Field],
+) -> Element<'a, Message> {
+    fields
+        .iter()
+        .fold(column![].spacing(5), |col, field| {
+            let value = sale.custom_field(field.id);
+            let input: Element<_> = match field.kind {
+                custom::Kind::Choice => pick_list(
+                    field.choices(),
+                    Some(value.to_string()).filter(|v| !v.is_empty()),
+                    move |choice| Message::CustomFieldInput(field.id, choice),
+                )
+                .placeholder(tr("Choose…"))
+                .width(Fill)
+                .into(),
+                custom::Kind::Text | custom::Kind::Number => {
+                    text_input(field.display_name(), value)
+                        .style(style::input)
+                        .on_input(move |input| {
+                            // Keep the current value if the input is invalid
+                            let input = if field.accepts(&input) {
+                                input
+                            } else {
+                                value.to_string()
+                            };
+                            Message::CustomFieldInput(field.id, input)
+                        })
+                        .padding(5)
+                        .into()
+                }
+            };
+
+            col.push(
+                row![text(field.display_name()).width(150.0), input]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+            )
+        })
+        .into()
+}
+
+/// A line per taxed group, or a single tax line if nothing is taxed.
+fn taxes(sale: &Sale) -> Element<'_, Message> {
+    let breakdown = sale.tax_breakdown();
+    if breakdown.is_empty() {
+        return row![
+            text(tr("Tax")).width(150.0),
+            horizontal_space(),
+            text(money(0.0))
+        ]
+        .into();
+    }
+
+    breakdown
+        .into_iter()
+        .fold(column![].spacing(2), |col, (group, tax)| {
+            col.push(row![
+                text(format!("{}: {group}", tr("Tax"))),
+                horizontal_space(),
+                text(money(tax))
+            ])
+        })
+        .into()
+}
+
+/// Where a delivery order goes, and how far away it is when the fee goes
+/// by distance.
+fn delivery<'a>(
+    sale: &'a Sale,
+    config: &Config,
+) -> Option<Element<'a, Message>> {
+    let delivery = sale.delivery()?;
+    let details = row![
+        horizontal_space().width(40),
+        text_input(tr("Address"), &delivery.address)
+            .style(style::input)
+            .on_input(Message::DeliveryAddressInput)
+            .padding(5),
+        text_input(tr("Phone"), &delivery.phone)
+            .style(style::input)
+            .on_input(Message::DeliveryPhoneInput)
+            .width(150.0)
+            .padding(5),
+    ]
+    .spacing(5)
+    .align_y(Alignment::Center);
+
+    Some(
+        if config.delivery.pricing == delivery::Pricing::ByDistance {
+            details
+                .push(
+                    text_input(
+                        "0.0",
+                        &delivery
+                            .distance
+                            .map_or(String::new(), |d| i18n::number(d, 1)),
+                    )
+                    .style(style::input)
+                    .on_input(Message::DeliveryDistanceInput)
+                    .width(70.0)
+                    .padding(5),
+                )
+                .push(text("km"))
+                .into()
+        } else {
+            details.into()
+        },
     )
-    .padding(20)
+}
+
+/// The customer the sale is for, and whether it is invoiced, and to whom on
+/// what terms.
+fn billing<'a>(
+    sale: &'a Sale,
+    customers: &'a Customers,
+) -> Element<'a, Message> {
+    let selected = sale.customer.and_then(|id| customers.get(id));
+    let picker = pick_list(
+        customers.choices(),
+        selected.map(customer::Customer::choice),
+        |choice| Message::SelectCustomer(Some(choice)),
+    )
+    .placeholder(tr("Customer"));
+    let row = row![horizontal_space().width(40), picker]
+        .spacing(5)
+        .align_y(Alignment::Center);
+    let row = if sale.customer.is_some() {
+        row.push(
+            button(text("×").center())
+                .width(25.0)
+                .on_press(Message::SelectCustomer(None))
+                .style(button::secondary),
+        )
+    } else {
+        row
+    };
+
+    let row = row.push(
+        checkbox(tr("Invoice"), sale.invoice.is_some())
+            .on_toggle(Message::ToggleInvoice),
+    );
+
+    match &sale.invoice {
+        Some(invoice) => row
+            .push(
+                text_input(tr("Bill to"), &invoice.customer)
+                    .style(style::input)
+                    .on_input(Message::InvoiceCustomerInput)
+                    .padding(5),
+            )
+            .push(pick_list(
+                &invoice::TERMS[..],
+                Some(invoice.terms),
+                Message::SelectTerms,
+            ))
+            .push(text(format!("{} {}", tr("due"), invoice.due())))
+            .into(),
+        None => row.into(),
+    }
+}
+
+fn delivery_fee(sale: &Sale) -> Element<'_, Message> {
+    match sale.delivery() {
+        Some(_) => row![
+            text(tr("Delivery fee")).width(150.0),
+            horizontal_space(),
+            text(money(sale.calculate_delivery_fee()))
+        ]
+        .into(),
+        None => column![].into(),
+    }
+}
+
+fn tender_row(tender: &Tender, keypad: bool) -> Element<'_, Message> {
+    row![
+        text(tr("Gift card")).width(150.0),
+        text_input(tr("Card code"), &tender.code)
+            .style(style::input)
+            .id(Focus::Tender(tender.id).id())
+            .on_input(|code| Message::TenderCodeInput(tender.id, code))
+            .on_submit(Message::ApplyTender(tender.id))
+            .width(200.0)
+            .padding(5),
+        keypad_button(keypad, KeypadTarget::TenderCode(tender.id)),
+        button(text(tr("Apply")).size(12))
+            .on_press(Message::ApplyTender(tender.id))
+            .style(button::secondary),
+        horizontal_space(),
+        text(money(-tender.amount)),
+        button(text("×").center())
+            .width(25.0)
+            .on_press(Message::RemoveTender(tender.id))
+            .style(button::danger),
+    ]
+    .spacing(5)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+fn section_header(
+    section: &Section,
+    collapsed: bool,
+    subtotal: f32,
+) -> Element<'_, Message> {
+    let toggle = if collapsed { "▸" } else { "▾" };
+
+    row![
+        button(text(toggle).shaping(text::Shaping::Advanced).center())
+            .width(30.0)
+            .on_press(Message::ToggleSection(section.id))
+            .style(button::text),
+        text_input(tr("Section name"), &section.name)
+            .style(style::input)
+            .id(Focus::Section(section.id).id())
+            .on_input(|name| Message::RenameSection(section.id, name))
+            .width(Fill)
+            .padding(5),
+        button(text("↑").shaping(text::Shaping::Advanced).center())
+            .width(30.0)
+            .on_press(Message::MoveSection(section.id, -1))
+            .style(button::secondary),
+        button(text("↓").shaping(text::Shaping::Advanced).center())
+            .width(30.0)
+            .on_press(Message::MoveSection(section.id, 1))
+            .style(button::secondary),
+        button(tr("+ Item"))
+            .on_press(Message::AddItem(Some(section.id)))
+            .style(button::primary),
+        text(money(subtotal)).align_x(Alignment::End).width(100.0),
+        button(text("×").center())
+            .width(25.0)
+            .on_press(Message::RemoveSection(section.id))
+            .style(button::danger)
+    ]
+    .spacing(5)
+    .align_y(Alignment::Center)
+    .into()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn item_row<'a>(
+    item: &'a SaleItem,
+    selected: bool,
+    has_sections: bool,
+    section_choices: &[SectionChoice],
+    tares: &[Tare],
+    keypad: bool,
+    shorthand: bool,
+    scale: bool,
+    density: Density,
+) -> Element<'a, Message> {
+    let size = density.text_size(16.0);
+    let padding = density.input_padding();
+
+    // Voided on the saved sale: kept as it was
+    if item.is_void() {
+        return row![
+            container(super::voided(item, size)).width(Fill),
+            text(item.quantity_label())
+                .size(size)
+                .align_x(Alignment::Center)
+                .width(80.0),
+            text(money(item.price()))
+                .size(size)
+                .align_x(Alignment::End)
+                .width(100.0),
+            text(money(0.0))
+                .size(size)
+                .align_x(Alignment::End)
+                .width(100.0),
+        ]
+        .spacing(5)
+        .align_y(Alignment::Center)
+        .into();
+    }
+
+    let mut row = row![
+        checkbox("", selected)
+            .on_toggle(|selected| Message::SelectItem(item.id, selected))
+            .size(size)
+            .spacing(0),
+        allergen_badge(item, size),
+        promotion_badge(item, size),
+        override_badge(item, size),
+        text_input(tr("Item name"), &item.name)
+            .style(style::input)
+            .id(Focus::Item(FormField::Name, item.id).id())
+            .on_input(|s| Message::UpdateItem(item.id, Field::Name(s)))
+            .on_submit(if shorthand {
+                Message::ExpandItem(item.id)
+            } else {
+                Message::SubmitItem(item.id)
+            })
+            .width(Fill)
+            .size(size)
+            .padding(padding),
+        text_input(
+            if item.weighing.is_some() {
+                tr("kg")
+            } else {
+                tr("Quantity")
+            },
+            &item.quantity_string(),
+        )
+        .style(style::input)
+        .id(Focus::Item(FormField::Quantity, item.id).id())
+        .align_x(Alignment::Center)
+        .on_input(|s| Message::UpdateItem(item.id, Field::Quantity(s)))
+        .on_submit(Message::SubmitItem(item.id))
+        .width(80.0)
+        .size(size)
+        .padding(padding),
+        text_input(tr("Price"), &item.price_string())
+            .style(style::input)
+            .id(Focus::Item(FormField::Price, item.id).id())
+            .align_x(Alignment::End)
+            .on_input(|s| Message::UpdateItem(item.id, Field::Price(s)))
+            .on_submit(Message::SubmitItem(item.id))
+            .width(100.0)
+            .size(size)
+            .padding(padding),
+        pick_list(&TaxGroup::ALL[..], Some(item.tax_group), move |tax_group| {
+            Message::UpdateItem(item.id, Field::TaxGroup(tax_group))
+        })
+        .width(140.0)
+        .text_size(size)
+        .padding([padding, 10.0]),
+    ]
+    .spacing(5)
+    .align_y(Alignment::Center);
+
+    if has_sections {
+        let selected = section_choices
+            .iter()
+            .find(|choice| choice.id == item.section)
+            .cloned();
+        row = row.push(
+            pick_list(section_choices.to_vec(), selected, move |choice| {
+                Message::UpdateItem(item.id, Field::Section(choice.id))
+            })
+            .width(120.0)
+            .text_size(size)
+            .padding([padding, 10.0]),
+        );
+    }
+
+    let row = row.push(keypad_button(keypad, KeypadTarget::Quantity(item.id)));
+    let row = if item.is_negative() {
+        row.push(
+            checkbox(tr("No tax"), item.adjustment)
+                .on_toggle(|adjustment| {
+                    Message::UpdateItem(item.id, Field::Adjustment(adjustment))
+                })
+                .text_size(size),
+        )
+    } else {
+        row
+    };
+    let row = row
+        .push(
+            text(money(item.total()))
+                .size(size)
+                .style(if item.is_negative() {
+                    text::danger
+                } else {
+                    text::default
+                })
+                .align_x(Alignment::End)
+                .width(100.0),
+        )
+        .push(tooltip(
+            button(
+                text("⧉")
+                    .size(size)
+                    .center()
+                    .shaping(text::Shaping::Advanced),
+            )
+            .width(25.0)
+            .padding([padding, 0.0])
+            // A gift card can only be sold once
+            .on_press_maybe(
+                item.gift_card
+                    .is_none()
+                    .then_some(Message::DuplicateItem(item.id)),
+            )
+            .style(button::secondary),
+            container(text(tr("Duplicate line")).size(12))
+                .padding(5)
+                .style(container::rounded_box),
+            tooltip::Position::Bottom,
+        ))
+        .push(
+            button(text("×").size(size).center())
+                .width(25.0)
+                .padding([padding, 10.0])
+                .on_press(Message::RemoveItem(item.id))
+                .style(button::danger),
+        );
+
+    let content: Element<_> = match item.weighing {
+        Some(weighing) => {
+            column![row, weighing_row(item.id, weighing, tares, scale, density)]
+                .spacing(density.table_spacing())
+                .into()
+        }
+        None if item.tiers.len() > 1 => column![row, tier_hint(item, density)]
+            .spacing(density.table_spacing())
+            .into(),
+        None => row.into(),
+    };
+
+    container(content)
+        .style(container::rounded_box)
+        .padding(0)
+        .into()
+}
+
+/// The names suggested under the name of item `id`, with their prices.
+fn suggestion_list<'a>(
+    id: usize,
+    suggestions: Vec<Suggestion>,
+    density: Density,
+) -> Element<'a, Message> {
+    let size = density.text_size(14.0);
+    let list = suggestions.into_iter().fold(column![], |col, suggestion| {
+        let label = row![
+            text(suggestion.name().to_string()).size(size),
+            horizontal_space(),
+            text(suggestion.price().map(money).unwrap_or_default()).size(size),
+        ]
+        .spacing(10);
+        col.push(
+            button(label)
+                .width(Fill)
+                .padding([2.0, 10.0])
+                .on_press(Message::PickSuggestion(id, suggestion))
+                .style(button::text),
+        )
+    });
+
+    container(list)
+        .style(container::rounded_box)
+        .padding([0, 40])
+        .into()
+}
+
+/// Opens the on-screen keypad for `target`, if the keypad is enabled.
+fn keypad_button<'a>(
+    keypad: bool,
+    target: KeypadTarget,
+) -> Element<'a, Message> {
+    if !keypad {
+        return horizontal_space().width(0).into();
+    }
+
+    let label = match target {
+        KeypadTarget::TenderCode(_) => "ABC",
+        _ => "123",
+    };
+    button(text(label).size(12).center())
+        .width(40.0)
+        .on_press(Message::OpenKeypad(target))
+        .style(button::secondary)
+        .into()
+}
+
+/// The on-screen keys with what they are typing into.
+fn keypad_dialog<'a>(sale: &'a Sale, open: &'a Keypad) -> Element<'a, Message> {
+    let label: Element<_> = match open.target {
+        KeypadTarget::Quantity(id) | KeypadTarget::Price(id) => {
+            let Some(item) = sale.items.iter().find(|item| item.id == id)
+            else {
+                return horizontal_space().width(0).into();
+            };
+            let tab = |label, target| {
+                button(text(label).size(12))
+                    .on_press(Message::OpenKeypad(target))
+                    .style(if open.target == target {
+                        button::primary
+                    } else {
+                        button::secondary
+                    })
+            };
+            column![
+                text(if item.name.is_empty() {
+                    tr("Item")
+                } else {
+                    &item.name
+                }),
+                row![
+                    tab(tr("Qty"), KeypadTarget::Quantity(id)),
+                    tab(tr("Price"), KeypadTarget::Price(id)),
+                ]
+                .spacing(5),
+            ]
+            .spacing(5)
+            .into()
+        }
+        KeypadTarget::Gratuity => text(tr("Gratuity")).into(),
+        KeypadTarget::TenderCode(_) => text(tr("Card code")).into(),
+    };
+
+    // Weighed quantities and amounts take decimals, counts don't
+    let keys = match open.target {
+        KeypadTarget::Quantity(id) => keypad::view(
+            &open.value,
+            sale.items
+                .iter()
+                .any(|item| item.id == id && item.weighing.is_some()),
+            Message::KeypadInput,
+        ),
+        KeypadTarget::Price(_) | KeypadTarget::Gratuity => {
+            keypad::view(&open.value, true, Message::KeypadInput)
+        }
+        KeypadTarget::TenderCode(_) => {
+            keypad::keyboard(&open.value, Message::KeypadInput)
+        }
+    };
+
+    column![
+        label,
+        row![
+            text(&open.value).size(28).width(Fill),
+            button(tr("Done"))
+                .on_press(Message::CloseKeypad)
+                .style(button::success),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        keys,
+    ]
+    .spacing(10)
+    .width(Length::Shrink)
     .into()
 }
 
+/// A warning sign on items with allergens, naming them on hover.
+fn allergen_badge<'a>(item: &SaleItem, size: f32) -> Element<'a, Message> {
+    if item.allergens.is_empty() {
+        return horizontal_space().width(0).into();
+    }
+
+    tooltip(
+        text("⚠")
+            .size(size)
+            .style(text::danger)
+            .shaping(text::Shaping::Advanced),
+        container(text(allergen::list(&item.allergens)).size(12))
+            .padding(5)
+            .style(container::rounded_box),
+        tooltip::Position::Bottom,
+    )
+    .into()
+}
+
+/// Marks an item added at a promotion price, e.g. during happy hour.
+fn promotion_badge<'a>(item: &'a SaleItem, size: f32) -> Element<'a, Message> {
+    let Some(promotion) = &item.promotion else {
+        return horizontal_space().width(0).into();
+    };
+
+    tooltip(
+        text("%").size(size).style(text::success),
+        container(text(promotion).size(12))
+            .padding(5)
+            .style(container::rounded_box),
+        tooltip::Position::Bottom,
+    )
+    .into()
+}
+
+/// Warns of a price typed over the catalog price.
+fn override_badge<'a>(item: &SaleItem, size: f32) -> Element<'a, Message> {
+    let Some((list, _)) = item.price_override() else {
+        return horizontal_space().width(0).into();
+    };
+
+    tooltip(
+        text("!").size(size).style(text::danger),
+        container(
+            text(format!("{} {}", tr("Catalog price"), money(list))).size(12),
+        )
+        .padding(5)
+        .style(container::rounded_box),
+        tooltip::Position::Bottom,
+    )
+    .into()
+}
+
+/// The tare and net weight under an item sold by weight, with a button to
+/// read it off the scale if there is one.
+fn weighing_row<'a>(
+    id: usize,
+    weighing: Weighing,
+    tares: &[Tare],
+    scale: bool,
+    density: Density,
+) -> Element<'a, Message> {
+    let size = density.text_size(14.0);
+    let no_tare = Tare {
+        name: tr("No tare").to_string(),
+        weight: 0.0,
+    };
+    let choices: Vec<Tare> = std::iter::once(no_tare)
+        .chain(tares.iter().cloned())
+        .collect();
+    let selected = choices
+        .iter()
+        .find(|tare| tare.weight == weighing.tare)
+        .cloned();
+
+    let tare: Element<_> = if tares.is_empty() {
+        text(format!(
+            "{} {} kg",
+            tr("Tare"),
+            i18n::number(weighing.tare, 3)
+        ))
+        .size(size)
+        .into()
+    } else {
+        pick_list(choices, selected, move |tare| {
+            Message::UpdateItem(id, Field::Tare(tare.weight))
+        })
+        .text_size(size)
+        .padding([density.input_padding(), 10.0])
+        .into()
+    };
+
+    row![
+        tare,
+        text(format!(
+            "{} {} kg",
+            tr("Net"),
+            i18n::number(weighing.net(), 3)
+        ))
+        .size(size),
+    ]
+    .push_maybe(scale.then(|| {
+        button(text(tr("Read scale")).size(size))
+            .on_press(Message::ReadScale(id))
+            .padding([density.input_padding(), 10.0])
+            .style(button::secondary)
+    }))
+    .spacing(10)
+    .padding([0.0, 10.0])
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// The label swatches and tags of a sale, with an input for a new tag.
+fn tags<'a>(sale: &'a Sale, form: &'a Form) -> Element<'a, Message> {
+    let tags = sale.tags.iter().fold(
+        row![
+            tag::swatches(sale.label, Message::SelectLabel),
+            text_input(tr("Add a tag"), &form.tag_input)
+                .style(style::input)
+                .on_input(Message::TagInput)
+                .on_submit(Message::AddTag)
+                .width(150.0)
+                .padding(5),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        |row, tag| {
+            row.push(
+                button(text(format!("{tag} ×")).size(12))
+                    .on_press(Message::RemoveTag(tag.clone()))
+                    .padding([2, 8])
+                    .style(button::secondary),
+            )
+        },
+    );
+
+    tags.wrap().into()
+}
+
+/// Which rule sets the service charge, or that one typed on the sale
+/// overrides it.
+fn service_rule(sale: &Sale) -> Element<'_, Message> {
+    match (sale.service_rule, sale.service_charge_percent) {
+        (Some(rule), None) => text(format!("{} {rule}", tr("Auto:")))
+            .size(12)
+            .style(text::success)
+            .into(),
+        (Some(_), Some(_)) => button(text(tr("Use rule")).size(12))
+            .on_press(Message::UpdateServiceCharge(None))
+            .padding([2, 5])
+            .style(button::text)
+            .into(),
+        (None, _) => horizontal_space().width(0).into(),
+    }
+}
+
+/// The price tiers of an item, with the one applied to its quantity
+/// highlighted.
+fn tier_hint(item: &SaleItem, density: Density) -> Element<'_, Message> {
+    let size = density.text_size(12.0);
+    let applied = item.tier();
+
+    item.tiers
+        .iter()
+        .fold(row![text(tr("Quantity prices")).size(size)], |row, tier| {
+            let hint = text(tier.to_string()).size(size);
+            row.push(if Some(*tier) == applied {
+                hint.style(text::success)
+            } else {
+                hint
+            })
+        })
+        .spacing(10)
+        .padding([0.0, 10.0])
+        .into()
+}
+
 pub fn handle_hotkey(hotkey: Hotkey) -> Action<Instruction, Message> {
     match hotkey {
-        Hotkey::Tab(modifier) => {
-            if modifier.shift() {
-                Action::task(focus_previous())
-            } else {
-                Action::task(focus_next())
+        Hotkey::NextField => Action::effect(focus_next()),
+        Hotkey::PreviousField => Action::effect(focus_previous()),
+        _ => Action::none(),
+    }
+}
+
+pub fn approval_pin_id() -> text_input::Id {
+    text_input::Id::new("approval-pin")
+}
+
+/// Asking for the manager PIN to save prices typed far off the catalog.
+fn approval_dialog(pin: &str) -> Element<'_, Message> {
+    let approve = (!pin.is_empty()).then_some(Message::ApproveOverrides);
+    column![
+        text(tr("Approve price overrides")),
+        text(tr(
+            "Some prices were changed from the catalog price by more than \
+             allowed without a manager."
+        ))
+        .size(12),
+        text_input(tr("Manager PIN"), pin)
+            .style(style::input)
+            .id(approval_pin_id())
+            .secure(true)
+            .on_input(|pin| Message::ApprovalPinInput(Secret(pin)))
+            .on_submit_maybe(approve.clone())
+            .padding(5),
+        row![
+            horizontal_space(),
+            button(tr("Cancel"))
+                .on_press(Message::CancelApproval)
+                .style(button::secondary),
+            button(tr("Approve"))
+                .on_press_maybe(approve)
+                .style(button::success),
+        ]
+        .spacing(10),
+    ]
+    .spacing(10)
+    .width(360)
+    .into()
+}
+
+pub fn open_price_id() -> text_input::Id {
+    text_input::Id::new("open-price")
+}
+
+/// The price typed for an open price product, if it is one.
+pub fn parse_price(price: &str) -> Option<f32> {
+    i18n::parse_number(price.trim()).filter(|price| price.is_finite())
+}
+
+/// An input of an item row, used to say which one should take focus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormField {
+    Name,
+    Quantity,
+    Price,
+}
+
+impl FormField {
+    fn key(self) -> &'static str {
+        match self {
+            FormField::Name => "name",
+            FormField::Quantity => "quantity",
+            FormField::Price => "price",
+        }
+    }
+}
+
+/// A text input of the form which can be focused from `update`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    SaleName,
+    /// A field of the item with the given id.
+    Item(FormField, usize),
+    Tender(usize),
+    Section(usize),
+}
+
+impl Focus {
+    pub fn id(self) -> text_input::Id {
+        match self {
+            Focus::SaleName => text_input::Id::new("sale-name"),
+            Focus::Item(field, id) => {
+                text_input::Id::new(format!("{}-{}", field.key(), id))
+            }
+            Focus::Tender(id) => text_input::Id::new(format!("tender-{}", id)),
+            Focus::Section(id) => {
+                text_input::Id::new(format!("section-{}", id))
             }
         }
-        _ => Action::none(),
     }
+
+    pub fn task<T>(self) -> Task<T> {
+        text_input::focus(self.id())
+    }
+}
+
+/// An `Action` moving focus to `target`.
+pub fn focus<I, T>(target: Focus) -> Action<I, T> {
+    Action::effect(target.task())
+}
+
+/// The first field of the item still to be filled in, in the order of the
+/// row.
+pub fn first_empty(item: &SaleItem) -> Option<FormField> {
+    if item.name.is_empty() {
+        Some(FormField::Name)
+    } else if item.quantity_string().is_empty() {
+        Some(FormField::Quantity)
+    } else if item.price.is_none() {
+        Some(FormField::Price)
+    } else {
+        None
+    }
+}
+
+/// The first field left empty on an item that was started, e.g. a template
+/// line with no price yet. Blank and voided rows are skipped.
+pub fn first_invalid(sale: &Sale) -> Option<Focus> {
+    sale.items
+        .iter()
+        .filter(|item| !item.is_void())
+        .find_map(|item| match first_empty(item)? {
+            // A row with nothing typed in is only the next empty row
+            FormField::Name
+                if item.quantity_string().is_empty()
+                    && item.price.is_none() =>
+            {
+                None
+            }
+            field => Some(Focus::Item(field, item.id)),
+        })
 }
 
-pub fn form_id(field: &str, id: usize) -> text_input::Id {
-    text_input::Id::new(format!("{}-{}", field, id))
+/// Focus the first invalid field of `sale`, or its name when there is none.
+pub fn focus_form<T>(sale: &Sale) -> Task<T> {
+    first_invalid(sale).unwrap_or(Focus::SaleName).task()
 }
@@ -0,0 +1,238 @@
+//! A sale typed from the keyboard, one shorthand line at a time
+//!
+//! Each line is typed as [`shorthand`], e.g. `2x latte 4.50`, and added with
+//! Enter. Products in the catalog can be typed by name alone, at their
+//! catalog price. Ctrl+Enter saves the sale and goes to its payment.
+use iced::widget::{
+    button, column, container, horizontal_space, row, scrollable, text,
+    text_input,
+};
+use iced::{Alignment, Element, Fill};
+
+use super::{from_product, shorthand, Sale, SaleItem};
+use crate::catalog::Catalog;
+use crate::i18n::{money, number, tr};
+use crate::style;
+use crate::toast;
+use crate::{Action, Hotkey, Screen};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Back,
+    Input(String),
+    /// Add the line typed.
+    Add,
+    Remove(usize),
+    /// Save the sale and take payment.
+    Save,
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Navigate(Screen),
+    /// Save the sale and show it for payment.
+    Save,
+}
+
+impl From<Screen> for Instruction {
+    fn from(screen: Screen) -> Self {
+        Instruction::Navigate(screen)
+    }
+}
+
+pub fn input_id() -> text_input::Id {
+    text_input::Id::new("quick-sale")
+}
+
+pub fn update(
+    sale: &mut Sale,
+    input: &mut String,
+    catalog: &Catalog,
+    message: Message,
+) -> Action<Instruction, Message> {
+    match message {
+        Message::Back => Action::navigate(Screen::List),
+        Message::Input(typed) => {
+            *input = typed;
+            Action::none()
+        }
+        Message::Add => {
+            if input.trim().is_empty() {
+                return Action::none();
+            }
+            match item(input, catalog, sale) {
+                Ok(item) => {
+                    sale.items.push(item);
+                    input.clear();
+                    Action::none()
+                }
+                Err(error) => Action::toast(toast::Kind::Error, error),
+            }
+        }
+        Message::Remove(id) => {
+            sale.items.retain(|item| item.id != id);
+            Action::effect(text_input::focus(input_id()))
+        }
+        Message::Save => {
+            // A line still being typed is part of the sale
+            if !input.trim().is_empty() {
+                match item(input, catalog, sale) {
+                    Ok(item) => {
+                        sale.items.push(item);
+                        input.clear();
+                    }
+                    Err(error) => {
+                        return Action::toast(toast::Kind::Error, error)
+                    }
+                }
+            }
+            if sale.items.is_empty() {
+                return Action::toast(
+                    toast::Kind::Error,
+                    tr("Add a line to the sale first"),
+                );
+            }
+            Action::instruction(Instruction::Save)
+        }
+    }
+}
+
+/// The item typed as `input`, priced from the catalog unless a price was
+/// typed.
+fn item(
+    input: &str,
+    catalog: &Catalog,
+    sale: &Sale,
+) -> Result<SaleItem, String> {
+    let Some(line) = shorthand::parse(input) else {
+        return Err(format!("\"{}\" is not a line", input.trim()));
+    };
+
+    let Some(product) = catalog.find(&line.name) else {
+        let Some(price) = line.price else {
+            return Err(format!(
+                "\"{}\" is not in the catalog: type its price too",
+                line.name
+            ));
+        };
+        let mut item = SaleItem::new(&line.name, price, line.quantity);
+        if let Some(tax_group) = line.tax_group {
+            item.tax_group = tax_group;
+        }
+        return Ok(item);
+    };
+    if product.by_weight {
+        return Err(format!(
+            "{} is sold by weight: add it on the sale screen",
+            product.display_name()
+        ));
+    }
+
+    let mut item = from_product(product.clone(), sale.created_at);
+    item.quantity = Some(line.quantity);
+    if let Some(tax_group) = line.tax_group {
+        item.tax_group = tax_group;
+    }
+    match line.price {
+        // A price typed by hand overrides tiers
+        Some(price) => {
+            item.price = Some(price);
+            item.tiers.clear();
+        }
+        None => {
+            if let Some(tier) = item.tier() {
+                item.price = Some(tier.price);
+            }
+        }
+    }
+    Ok(item)
+}
+
+pub fn view<'a>(sale: &'a Sale, input: &'a str) -> Element<'a, Message> {
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text(tr("Quick sale")).size(16),
+        horizontal_space(),
+        button(tr("Save and pay"))
+            .on_press_maybe(
+                (!sale.items.is_empty() || !input.trim().is_empty())
+                    .then_some(Message::Save),
+            )
+            .style(button::success),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let entry = column![
+        text_input("2x latte 4.50", input)
+            .id(input_id())
+            .style(style::input)
+            .on_input(Message::Input)
+            .on_submit(Message::Add)
+            .size(20)
+            .padding(10),
+        text(tr(
+            "Enter adds the line • Ctrl+Enter saves and takes payment • \
+             Products in the catalog need no price"
+        ))
+        .size(12),
+    ]
+    .spacing(5);
+
+    let lines: Element<_> = if sale.items.is_empty() {
+        text(tr("No lines yet")).size(12).into()
+    } else {
+        sale.items
+            .iter()
+            .fold(column![].spacing(5), |col, item| {
+                col.push(
+                    row![
+                        text(number(item.quantity(), 0)).width(40.0),
+                        text(&item.name).width(Fill),
+                        text(money(item.price())).width(100.0),
+                        text(money(item.total()))
+                            .align_x(Alignment::End)
+                            .width(100.0),
+                        button(text("×").center())
+                            .width(25.0)
+                            .on_press(Message::Remove(item.id))
+                            .style(button::danger),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                )
+            })
+            .push(row![
+                text(tr("Total")).size(16).width(Fill),
+                text(money(sale.calculate_total())).size(16),
+            ])
+            .into()
+    };
+
+    container(scrollable(
+        column![
+            header,
+            entry,
+            container(lines)
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+        ]
+        .spacing(20),
+    ))
+    .padding(20)
+    .into()
+}
+
+pub fn handle_hotkey(
+    sale: &mut Sale,
+    input: &mut String,
+    catalog: &Catalog,
+    hotkey: Hotkey,
+) -> Action<Instruction, Message> {
+    match hotkey {
+        Hotkey::Escape => Action::navigate(Screen::List),
+        Hotkey::Submit => update(sale, input, catalog, Message::Save),
+        _ => Action::none(),
+    }
+}
@@ -1,117 +1,1140 @@
 //! A read-only view of a sale.
+use chrono::Local;
 use iced::widget::{
-    button, column, container, horizontal_space, row, scrollable, text,
+    button, column, container, horizontal_space, image, pick_list, row,
+    scrollable, text, text_input,
 };
 use iced::Length::Fill;
 use iced::{Alignment, Element, Length};
 
-use super::{Instruction, Sale};
-use crate::{Action, Hotkey};
+use super::{
+    Form, Instruction, Link, OrderStatus, OrderType, Sale, SaleItem, Section,
+    VoidInput,
+};
+use crate::allergen;
+use crate::config::Config;
+use crate::currency;
+use crate::denomination;
+use crate::density::Density;
+use crate::dialog;
+use crate::i18n::{self, money, number, tr};
+use crate::invoice;
+use crate::prompt;
+use crate::receipt;
+use crate::schedule;
+use crate::secret::Secret;
+use crate::store::SaveStatus;
+use crate::style;
+use crate::tag;
+use crate::tips;
+use crate::{custom, Action, Hotkey};
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Back,
     StartEdit,
+    ToggleSection(usize),
+    MarkReady,
+    MarkPickedUp,
+    CopyTicket,
+    CopyReceipt,
+    /// Show or hide the receipt beside the sale.
+    TogglePreview,
+    /// Move the sale to the trash.
+    Delete,
+    CopyJson,
+    SaveJson,
+    Print,
+    EmailReceipt,
+    TakeCash,
+    TenderedInput(String),
+    PayByCard,
+    CancelCard,
+    OpenSale(usize),
+    Reorder,
+    SaveAsTemplate,
+    RetrySave,
+    AdjustGratuity,
+    GratuityInput(String),
+    ApplyGratuity,
+    GratuityPinInput(Secret),
+    CancelGratuity,
+    /// Start voiding the item with this id.
+    VoidItem(usize),
+    VoidReasonInput(String),
+    VoidPinInput(Secret),
+    ConfirmVoid,
+    CancelVoid,
+    PaymentInput(String),
+    RecordPayment,
+    SelectCurrency(String),
+    ForeignAmountInput(String),
+    TakeForeign,
+    ExportStatement,
 }
 
-pub fn view(sale: &Sale) -> Element<Message> {
+#[allow(clippy::too_many_arguments)]
+pub fn view<'a>(
+    sale: &'a Sale,
+    form: &'a Form,
+    id: Option<usize>,
+    config: &'a Config,
+    save_status: Option<SaveStatus>,
+    qr: Option<&'a image::Handle>,
+    linked_by: Vec<Link>,
+    email: Option<&'a str>,
+    waiting: bool,
+) -> Element<'a, Message> {
+    let fields = &config.custom_fields;
+    let density = config.density;
+    let keypad = config.keypad;
+
+    let order = match (sale.order_type, sale.pager, sale.delivery()) {
+        (OrderType::Takeout, Some(pager), _) => {
+            format!("{} • {} {pager}", tr("Takeout"), tr("Pager"))
+        }
+        (_, _, Some(delivery)) => {
+            format!("{} • {}", tr("Delivery"), delivery.summary())
+        }
+        (order_type, ..) => order_type.to_string(),
+    };
+    let order = match sale.guests {
+        Some(guests) => format!("{order} • {guests} {}", tr("guests")),
+        None => order,
+    };
+    let order = match sale.due_at {
+        Some(due) => {
+            format!("{order} • {} {}", tr("Due at"), schedule::label(due))
+        }
+        None => order,
+    };
+
     let header = row![
         button(text("←").center()).width(40).on_press(Message::Back),
         text(&sale.name).size(16),
+        text(order).shaping(text::Shaping::Advanced),
+        save_badge(save_status),
         horizontal_space(),
-        button("Edit").on_press(Message::StartEdit)
+        button(tr("Preview"))
+            .on_press(Message::TogglePreview)
+            .style(if config.receipt_preview {
+                button::primary
+            } else {
+                button::secondary
+            }),
+        button(tr("Copy ticket"))
+            .on_press(Message::CopyTicket)
+            .style(button::secondary),
+        button(tr("Copy receipt"))
+            .on_press(Message::CopyReceipt)
+            .style(button::secondary),
+        button(tr("Print…"))
+            .on_press(Message::Print)
+            .style(button::secondary),
+        email_receipt(config, email),
+        take_cash(sale, waiting),
+        pay_by_card(sale, config, waiting),
+        button(tr("Copy as JSON"))
+            .on_press(Message::CopyJson)
+            .style(button::secondary),
+        button(tr("Save as JSON"))
+            .on_press(Message::SaveJson)
+            .style(button::secondary),
+        button(tr("Save as template"))
+            .on_press(Message::SaveAsTemplate)
+            .style(button::secondary),
+        button(tr("Reorder"))
+            .on_press(Message::Reorder)
+            .style(button::secondary),
+        button(tr("Delete"))
+            .on_press(Message::Delete)
+            .style(button::danger),
+        match sale.order_status {
+            OrderStatus::Open => button(tr("Order ready"))
+                .on_press(Message::MarkReady)
+                .style(button::success),
+            OrderStatus::Ready => button(tr("Picked up"))
+                .on_press(Message::MarkPickedUp)
+                .style(button::secondary),
+            OrderStatus::PickedUp =>
+                button(tr("Picked up")).style(button::text),
+        },
+        button(tr("Edit")).on_press(Message::StartEdit)
     ]
     .spacing(10)
     .align_y(Alignment::Center);
 
     let column_headers = row![
-        text("Item Name").width(Fill),
-        text("Qty").align_x(Alignment::Center).width(80.0),
-        text("Price").align_x(Alignment::End).width(100.0),
-        text("Tax Group").width(140.0),
-        text("Total").align_x(Alignment::End).width(100.0),
+        text(tr("Item Name")).width(Fill),
+        text(tr("Qty")).align_x(Alignment::Center).width(80.0),
+        text(tr("Price")).align_x(Alignment::End).width(100.0),
+        text(tr("Tax Group")).width(140.0),
+        text(tr("Total")).align_x(Alignment::End).width(100.0),
+        horizontal_space().width(60.0),
     ]
     .spacing(2);
 
-    let items_list = sale.items.iter().fold(
-        column![column_headers].spacing(5).width(Length::Fill),
-        |col, item| {
-            col.push(
-                container(
-                    row![
-                        text(&item.name).width(Fill),
-                        text(item.quantity().to_string())
-                            .align_x(Alignment::Center)
-                            .width(80.0),
-                        text(format!("${:.2}", item.price()))
-                            .align_x(Alignment::End)
-                            .width(100.0),
-                        text(format!("{}", item.tax_group)).width(140.0),
-                        text(format!("${:.2}", item.price() * item.quantity()))
-                            .align_x(Alignment::End)
-                            .width(100.0)
-                    ]
-                    .spacing(5)
-                    .align_y(Alignment::Center),
-                )
-                .style(container::rounded_box)
-                .padding(0),
-            )
+    let items_list = sale.courses().into_iter().fold(
+        column![column_headers]
+            .spacing(density.table_spacing())
+            .width(Length::Fill),
+        |col, (section, items)| {
+            let col = match section {
+                Some(section) => col.push(section_header(
+                    section,
+                    form.collapsed.contains(&section.id),
+                    sale.calculate_section_subtotal(Some(section.id)),
+                )),
+                None => col,
+            };
+
+            if section
+                .is_some_and(|section| form.collapsed.contains(&section.id))
+            {
+                return col;
+            }
+
+            items
+                .into_iter()
+                .fold(col, |col, item| col.push(item_row(item, density)))
         },
     );
 
     let totals = column![
         row![
-            text("Subtotal").width(150.0),
+            text(tr("Subtotal")).width(150.0),
             horizontal_space(),
-            text(format!("${:.2}", sale.calculate_subtotal()))
+            text(money(sale.calculate_subtotal()))
         ],
         row![
-            text("Service Charge").width(150.0),
+            text(tr("Service Charge")).width(150.0),
             text(format!(
                 "{}%",
-                sale.service_charge_percent.map_or(0.0, |p| p)
+                number(sale.service_charge_rate().unwrap_or(0.0), 1)
             )),
             horizontal_space(),
-            text(format!("${:.2}", sale.calculate_service_charge()))
-        ],
-        row![
-            text("Tax").width(150.0),
-            horizontal_space(),
-            text(format!("${:.2}", sale.calculate_tax()))
+            text(money(sale.calculate_service_charge()))
         ],
+        taxes(sale),
+        gratuity(sale, form, &config.tip_suggestions),
+        delivery_fee(sale),
         row![
-            text("Gratuity").width(150.0),
-            text(format!("${:.2}", sale.gratuity_amount.unwrap_or(0.0))),
+            text(tr("Total")).width(150.0).size(16),
             horizontal_space(),
-            text(format!("${:.2}", sale.gratuity_amount.unwrap_or(0.0)))
-        ],
-        row![
-            text("Total").width(150.0).size(16),
-            horizontal_space(),
-            text(format!("${:.2}", sale.calculate_total())).size(16)
+            text(money(sale.calculate_total())).size(16)
         ]
     ]
     .spacing(2)
     .width(Length::Fill);
 
-    container(
+    let totals = sale.tenders.iter().fold(totals, |col, tender| {
+        col.push(row![
+            text(tr("Gift card")).width(150.0),
+            text(&tender.code),
+            horizontal_space(),
+            text(money(-tender.amount))
+        ])
+    });
+    let totals = if sale.tenders.is_empty() {
+        totals
+    } else {
+        totals.push(row![
+            text(tr("Due")).width(150.0).size(16),
+            horizontal_space(),
+            text(money(sale.calculate_due())).size(16)
+        ])
+    };
+
+    let totals = match sale.calculate_per_guest() {
+        Some(per_guest) => totals.push(per_guest_row(sale, per_guest)),
+        None => totals,
+    };
+
+    let totals = match sale.cash {
+        Some(cash) => totals.push(row![
+            text(tr("Paid cash")).width(150.0),
+            horizontal_space(),
+            text(money(cash))
+        ]),
+        None => totals,
+    };
+
+    let totals = match &sale.card {
+        Some(card) => totals.push(row![
+            text(tr("Paid by card")).width(150.0),
+            text(card.to_string()).shaping(text::Shaping::Advanced),
+            horizontal_space(),
+            text(money(card.amount))
+        ]),
+        None => totals,
+    };
+
+    let totals = totals
+        .push_maybe(change_due(sale, form, config, waiting))
+        .push_maybe(foreign(sale, form, config, waiting))
+        .push_maybe(invoice(sale, form))
+        .push_maybe(sale.fiscal.as_ref().map(|fiscal| {
+            row![
+                text(tr("Fiscal no.")).width(150.0),
+                horizontal_space(),
+                text(&fiscal.number)
+            ]
+        }))
+        .push_maybe(issued(sale));
+
+    let header = match links(sale, linked_by) {
+        Some(links) => column![header, links].spacing(10),
+        None => column![header],
+    };
+
+    let header = if sale.tags.is_empty() && sale.label.is_none() {
+        header
+    } else {
+        header.push(tag::chips(&sale.tags, sale.label))
+    };
+
+    let header = if sale.allergy_note.is_empty() {
+        header
+    } else {
+        column![
+            header,
+            container(
+                text(format!("⚠ {}: {}", tr("Allergy"), sale.allergy_note))
+                    .shaping(text::Shaping::Advanced)
+                    .style(text::danger),
+            )
+            .padding(10)
+            .width(Fill)
+            .style(container::rounded_box)
+        ]
+        .spacing(20)
+    };
+
+    let content = if fields.is_empty() {
+        column![header]
+    } else {
         column![
             header,
+            container(custom_fields(sale, fields))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box)
+        ]
+    };
+
+    let sale_pane = content
+        .push(
             container(scrollable(column![items_list,].spacing(10).padding(20)))
                 .height(Length::Fill)
                 .style(container::rounded_box),
-            container(totals).padding(20).style(container::rounded_box)
+        )
+        .push(
+            container(match qr {
+                Some(qr) => row![totals, image(qr).width(120)]
+                    .spacing(20)
+                    .align_y(Alignment::Center)
+                    .into(),
+                None => Element::from(totals),
+            })
+            .padding(20)
+            .style(container::rounded_box),
+        )
+        .spacing(20)
+        .height(Length::Fill);
+
+    let screen = container(if config.receipt_preview {
+        row![
+            sale_pane,
+            container(scrollable(
+                container(receipt::of(id, sale, config)).padding(10),
+            ))
+            .width(Length::Shrink)
+            .height(Fill),
         ]
         .spacing(20)
-        .height(Length::Fill),
+        .into()
+    } else {
+        Element::from(sale_pane)
+    })
+    .padding(20);
+
+    if let Some(input) = &form.void_input {
+        return dialog::view(
+            screen,
+            void_dialog(sale, input, config.manager_pin.is_set()),
+            Message::CancelVoid,
+        );
+    }
+
+    if let Some(pin) = &form.gratuity_pin {
+        return dialog::view(
+            screen,
+            gratuity_approval(pin),
+            Message::CancelGratuity,
+        );
+    }
+
+    match &form.gratuity_input {
+        Some(input) if keypad => dialog::view(
+            screen,
+            gratuity_dialog(input),
+            Message::CancelGratuity,
+        ),
+        _ => screen.into(),
+    }
+}
+
+/// Links to the sale this one was made from and to those made from it,
+/// e.g. "Refund of #12" or "Refunded by #15".
+fn links<'a>(
+    sale: &Sale,
+    linked_by: Vec<Link>,
+) -> Option<Element<'a, Message>> {
+    let links: Vec<_> = sale
+        .link
+        .map(|link| (link.relation.to_string(), link.sale))
+        .into_iter()
+        .chain(
+            linked_by
+                .into_iter()
+                .map(|by| (by.relation.inverse().to_string(), by.sale)),
+        )
+        .collect();
+
+    if links.is_empty() {
+        return None;
+    }
+
+    let row = links
+        .into_iter()
+        .fold(row![].spacing(10), |row, (label, id)| {
+            row.push(
+                button(text(format!("{label} #{id}")).size(12))
+                    .on_press(Message::OpenSale(id))
+                    .padding([2, 5])
+                    .style(button::text),
+            )
+        });
+    Some(row.into())
+}
+
+/// The total per guest, with how the amount due splits evenly to the cent.
+/// A line per taxed group, or a single tax line if nothing is taxed.
+fn taxes(sale: &Sale) -> Element<'_, Message> {
+    let breakdown = sale.tax_breakdown();
+    if breakdown.is_empty() {
+        return row![
+            text(tr("Tax")).width(150.0),
+            horizontal_space(),
+            text(money(0.0))
+        ]
+        .into();
+    }
+
+    breakdown
+        .into_iter()
+        .fold(column![].spacing(2), |col, (group, tax)| {
+            col.push(row![
+                text(format!("{}: {group}", tr("Tax"))),
+                horizontal_space(),
+                text(money(tax))
+            ])
+        })
+        .into()
+}
+
+fn per_guest_row(sale: &Sale, per_guest: f32) -> Element<'_, Message> {
+    let split = sale
+        .even_split()
+        .iter()
+        .map(|(count, amount)| format!("{count} × {}", money(*amount)))
+        .collect::<Vec<_>>()
+        .join(" + ");
+
+    row![
+        text(format!(
+            "{} ({})",
+            tr("Per guest"),
+            sale.guests.unwrap_or(0)
+        ))
+        .width(150.0),
+        text(if split.is_empty() {
+            String::new()
+        } else {
+            format!("{}: {split}", tr("Split evenly"))
+        })
+        .size(12),
+        horizontal_space(),
+        text(money(per_guest))
+    ]
+    .align_y(Alignment::Center)
+    .into()
+}
+
+/// Take payment in cash, while any is due and none was taken yet.
+/// Email the receipt to the customer of the sale, once there is a mail
+/// server and their address.
+fn email_receipt<'a>(
+    config: &Config,
+    email: Option<&'a str>,
+) -> Element<'a, Message> {
+    if !config.smtp.is_enabled() {
+        return horizontal_space().width(0).into();
+    }
+
+    button(tr("Email receipt"))
+        .on_press_maybe(email.map(|_| Message::EmailReceipt))
+        .style(button::secondary)
+        .into()
+}
+
+/// When and by whom the receipt was printed or emailed, and where to.
+fn issued(sale: &Sale) -> Option<Element<'_, Message>> {
+    if !sale.is_reprint() {
+        return None;
+    }
+
+    let history = sale.issued.iter().enumerate().fold(
+        column![text(tr("Receipt history")).size(14)].spacing(2),
+        |col, (n, issue)| {
+            let at = issue.at.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+            let by = issue.by.as_deref().unwrap_or("—");
+            col.push(row![
+                text(at.to_string()).width(150.0),
+                text(issue.via.to_string()).width(Fill),
+                text(by),
+                text(if n == 0 { "" } else { "COPY" })
+                    .width(60.0)
+                    .align_x(Alignment::End),
+            ])
+        },
+    );
+    Some(history.into())
+}
+
+fn take_cash(sale: &Sale, waiting: bool) -> Element<'_, Message> {
+    if sale.cash.is_some()
+        || sale.card.is_some()
+        || sale.foreign.is_some()
+        || waiting
+        || sale.calculate_due() < 0.005
+    {
+        return horizontal_space().width(0).into();
+    }
+
+    button(tr("Paid cash"))
+        .on_press(Message::TakeCash)
+        .style(button::success)
+        .into()
+}
+
+/// While cash can be taken, the cash handed over and the change due, with
+/// the notes and coins to give it in.
+fn change_due<'a>(
+    sale: &'a Sale,
+    form: &'a Form,
+    config: &Config,
+    waiting: bool,
+) -> Option<Element<'a, Message>> {
+    let due = sale.calculate_due();
+    if sale.cash.is_some()
+        || sale.card.is_some()
+        || sale.foreign.is_some()
+        || waiting
+        || due < 0.005
+    {
+        return None;
+    }
+
+    let tendered = i18n::parse_number(&form.tendered).filter(|t| *t > 0.0);
+    let change = match tendered {
+        Some(tendered) if tendered + 0.005 < due => {
+            text(tr("Not enough")).style(text::danger)
+        }
+        Some(tendered) => text(format!(
+            "{} {} • {}",
+            tr("Change"),
+            money(tendered - due),
+            pieces(tendered - due, config)
+        )),
+        None => text(""),
+    };
+
+    Some(
+        row![
+            text(tr("Cash tendered")).width(150.0),
+            text_input("0.00", &form.tendered)
+                .id(tendered_id())
+                .style(style::input)
+                .align_x(Alignment::End)
+                .on_input(Message::TenderedInput)
+                .on_submit(Message::TakeCash)
+                .width(100.0)
+                .padding(5),
+            change,
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+        .into(),
     )
-    .padding(20)
+}
+
+/// The fewest notes and coins of the base currency making up `change`, e.g.
+/// "1 × 10 • 2 × 0.25".
+fn pieces(change: f32, config: &Config) -> String {
+    let denominations = config.denominations.of(&config.currency.base);
+    denomination::change(change, &denominations)
+        .into_iter()
+        .map(|(cents, count)| {
+            format!("{count} × {}", denomination::label(cents))
+        })
+        .collect::<Vec<_>>()
+        .join(" • ")
+}
+
+/// Take payment on the card terminal, if one is set up, or wait for it.
+fn pay_by_card<'a>(
+    sale: &'a Sale,
+    config: &Config,
+    waiting: bool,
+) -> Element<'a, Message> {
+    if waiting {
+        return row![
+            text(tr("Waiting for card…")).shaping(text::Shaping::Advanced),
+            button(tr("Cancel"))
+                .on_press(Message::CancelCard)
+                .style(button::danger),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+        .into();
+    }
+    if !config.terminal.is_enabled()
+        || sale.cash.is_some()
+        || sale.card.is_some()
+        || sale.foreign.is_some()
+        || sale.calculate_due() < 0.005
+    {
+        return horizontal_space().width(0).into();
+    }
+
+    button(tr("Pay by card"))
+        .on_press(Message::PayByCard)
+        .style(button::success)
+        .into()
+}
+
+/// The payment taken in a foreign currency, or while anything is due and
+/// nothing was paid yet, one to take, with what it comes to.
+fn foreign<'a>(
+    sale: &'a Sale,
+    form: &'a Form,
+    config: &'a Config,
+    waiting: bool,
+) -> Option<Element<'a, Message>> {
+    if let Some(payment) = &sale.foreign {
+        return Some(
+            column![
+                row![
+                    text(tr("Paid in")).width(150.0),
+                    text(format!(
+                        "{payment} @ {} {}",
+                        number(payment.rate, 4),
+                        payment.base
+                    )),
+                    horizontal_space(),
+                    text(money(payment.amount))
+                ],
+                row![
+                    text(tr("Change")).width(150.0),
+                    text(pieces(payment.change, config)),
+                    horizontal_space(),
+                    text(money(payment.change))
+                ],
+            ]
+            .spacing(2)
+            .into(),
+        );
+    }
+
+    let codes = config.currency.codes();
+    let due = sale.calculate_due();
+    if codes.is_empty()
+        || sale.cash.is_some()
+        || sale.card.is_some()
+        || waiting
+        || due < 0.005
+    {
+        return None;
+    }
+
+    let entry = &form.foreign_entry;
+    let rate = entry
+        .code
+        .as_deref()
+        .and_then(|code| Some((code, config.currency.rate(code)?)));
+    let tendered = i18n::parse_number(&entry.amount).filter(|t| *t > 0.0);
+    let preview = match (rate, tendered) {
+        (Some((code, _)), Some(tendered)) => {
+            match currency::Payment::new(&config.currency, code, tendered, due)
+            {
+                Some(payment) => text(format!(
+                    "= {} • {} {}",
+                    money(payment.amount),
+                    tr("change"),
+                    money(payment.change)
+                )),
+                None => text(tr("Not enough")).style(text::danger),
+            }
+        }
+        (Some((code, rate)), None) => {
+            text(format!("{} {:.2} {code}", tr("Due"), due / rate))
+        }
+        (None, _) => text(""),
+    };
+
+    Some(
+        row![
+            text(tr("Pay in")).width(150.0),
+            pick_list(codes, entry.code.clone(), Message::SelectCurrency)
+                .placeholder(tr("Currency")),
+            text_input("0.00", &entry.amount)
+                .style(style::input)
+                .on_input(Message::ForeignAmountInput)
+                .on_submit(Message::TakeForeign)
+                .width(100.0)
+                .padding(5),
+            preview,
+            horizontal_space(),
+            button(tr("Take payment"))
+                .on_press(Message::TakeForeign)
+                .style(button::success),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+        .into(),
+    )
+}
+
+/// Who the sale is invoiced to, the payments made on it so far and what is
+/// left, with a payment to record while anything is.
+fn invoice<'a>(sale: &'a Sale, form: &'a Form) -> Option<Element<'a, Message>> {
+    let invoice = sale.invoice.as_ref()?;
+    let summary = invoice::Summary::new(sale)?;
+    let overdue = summary.is_overdue(Local::now().date_naive());
+
+    let customer = match invoice.customer.trim() {
+        "" => tr("No customer"),
+        customer => customer,
+    };
+    let heading = row![
+        text(format!(
+            "{} {customer} • {} {} • {}",
+            tr("Invoice to"),
+            tr("issued"),
+            invoice.issued,
+            invoice.terms
+        )),
+        horizontal_space(),
+        text(format!("{} {}", tr("due"), summary.due)),
+    ]
+    .spacing(10);
+    let heading = if overdue {
+        heading.push(text(tr("Overdue")).style(text::danger))
+    } else {
+        heading
+    };
+
+    let payments = invoice.payments.iter().fold(
+        column![heading].spacing(2),
+        |col, payment| {
+            col.push(row![
+                text(tr("Payment")).width(150.0),
+                text(payment.date.to_string()),
+                horizontal_space(),
+                text(money(-payment.amount))
+            ])
+        },
+    );
+    let balance = text(money(summary.balance)).size(16);
+    let payments = payments.push(row![
+        text(tr("Balance")).width(150.0).size(16),
+        horizontal_space(),
+        if overdue {
+            balance.style(text::danger)
+        } else {
+            balance
+        }
+    ]);
+
+    let actions = row![horizontal_space()].spacing(10);
+    let actions = if summary.balance >= 0.005 {
+        actions
+            .push(
+                text_input("0.00", &form.payment_input)
+                    .style(style::input)
+                    .on_input(Message::PaymentInput)
+                    .on_submit(Message::RecordPayment)
+                    .width(100.0)
+                    .padding(5),
+            )
+            .push(
+                button(tr("Record payment"))
+                    .on_press(Message::RecordPayment)
+                    .style(button::success),
+            )
+    } else {
+        actions
+    };
+    let actions = actions.push(
+        button(tr("Export statement"))
+            .on_press(Message::ExportStatement)
+            .style(button::secondary),
+    );
+
+    Some(
+        column![payments, actions.align_y(Alignment::Center)]
+            .spacing(10)
+            .padding([10, 0])
+            .into(),
+    )
+}
+
+fn delivery_fee(sale: &Sale) -> Element<'_, Message> {
+    match sale.delivery() {
+        Some(_) => row![
+            text(tr("Delivery fee")).width(150.0),
+            horizontal_space(),
+            text(money(sale.calculate_delivery_fee()))
+        ]
+        .into(),
+        None => column![].into(),
+    }
+}
+
+/// The gratuity row, which can be adjusted without editing the whole sale,
+/// e.g. when a tip is written on the card slip after closing.
+fn gratuity<'a>(
+    sale: &'a Sale,
+    form: &'a Form,
+    suggestions: &tips::Suggestions,
+) -> Element<'a, Message> {
+    let amount = text(money(sale.gratuity_amount.unwrap_or(0.0)));
+
+    match &form.gratuity_input {
+        Some(input) => row![
+            text(tr("Gratuity")).width(150.0),
+            text_input("0.00", input)
+                .style(style::input)
+                .id(gratuity_id())
+                .on_input(Message::GratuityInput)
+                .on_submit(Message::ApplyGratuity)
+                .width(100.0)
+                .padding(5),
+            super::suggested_tips(sale, suggestions, |amount| {
+                Message::GratuityInput(i18n::number(amount, 2))
+            }),
+            button(text(tr("Apply")).size(12))
+                .on_press(Message::ApplyGratuity)
+                .style(button::success),
+            button(text(tr("Cancel")).size(12))
+                .on_press(Message::CancelGratuity)
+                .style(button::secondary),
+            horizontal_space(),
+            amount,
+        ]
+        .spacing(5)
+        .align_y(Alignment::Center)
+        .into(),
+        None => row![
+            text(tr("Gratuity")).width(150.0),
+            button(text(tr("Adjust")).size(12))
+                .on_press(Message::AdjustGratuity)
+                .style(button::text)
+                .padding(0),
+            horizontal_space(),
+            amount,
+        ]
+        .align_y(Alignment::Center)
+        .into(),
+    }
+}
+
+/// The gratuity adjustment on the on-screen keypad.
+fn gratuity_dialog(input: &str) -> Element<'_, Message> {
+    prompt::view(
+        tr("Gratuity"),
+        input,
+        text_input::Id::new("gratuity-prompt"),
+        true,
+        Message::GratuityInput,
+        Some(Message::ApplyGratuity),
+        Message::CancelGratuity,
+    )
+}
+
+/// Asking for the manager PIN to adjust the gratuity of a saved sale.
+fn gratuity_approval(pin: &str) -> Element<'_, Message> {
+    let approve = (!pin.is_empty()).then_some(Message::ApplyGratuity);
+    column![
+        text(tr("Approve gratuity change")),
+        text(tr("Changing the gratuity of a saved sale needs a manager."))
+            .size(12),
+        text_input(tr("Manager PIN"), pin)
+            .style(style::input)
+            .id(gratuity_pin_id())
+            .secure(true)
+            .on_input(|pin| Message::GratuityPinInput(Secret(pin)))
+            .on_submit_maybe(approve.clone())
+            .padding(5),
+        row![
+            horizontal_space(),
+            button(tr("Cancel"))
+                .on_press(Message::CancelGratuity)
+                .style(button::secondary),
+            button(tr("Approve"))
+                .on_press_maybe(approve)
+                .style(button::success),
+        ]
+        .spacing(10),
+    ]
+    .spacing(10)
+    .width(360)
+    .into()
+}
+
+pub fn tendered_id() -> text_input::Id {
+    text_input::Id::new("tendered")
+}
+
+pub fn gratuity_id() -> text_input::Id {
+    text_input::Id::new("gratuity")
+}
+
+pub fn gratuity_pin_id() -> text_input::Id {
+    text_input::Id::new("gratuity-pin")
+}
+
+pub fn void_reason_id() -> text_input::Id {
+    text_input::Id::new("void-reason")
+}
+
+/// Asking why an item is voided, and for the manager PIN if one is set.
+fn void_dialog<'a>(
+    sale: &'a Sale,
+    input: &'a VoidInput,
+    pin: bool,
+) -> Element<'a, Message> {
+    let name = sale
+        .items
+        .iter()
+        .find(|item| item.id == input.item)
+        .map_or(String::new(), |item| {
+            format!("{} x {}", item.quantity_label(), item.name)
+        });
+    let confirm = (!input.reason.trim().is_empty()
+        && (!pin || !input.pin.is_empty()))
+    .then_some(Message::ConfirmVoid);
+
+    column![
+        text(format!("{} {name}", tr("Void"))),
+        text_input(tr("Reason, e.g. sent back"), &input.reason)
+            .style(style::input)
+            .id(void_reason_id())
+            .on_input(Message::VoidReasonInput)
+            .on_submit_maybe(confirm.clone())
+            .padding(5),
+    ]
+    .push_maybe(pin.then(|| {
+        text_input(tr("Manager PIN"), &input.pin)
+            .style(style::input)
+            .secure(true)
+            .on_input(|pin| Message::VoidPinInput(Secret(pin)))
+            .on_submit_maybe(confirm.clone())
+            .padding(5)
+    }))
+    .push(
+        text(tr(
+            "The line stays on the sale, struck through, and is left out of \
+             the totals.",
+        ))
+        .size(12),
+    )
+    .push(
+        row![
+            horizontal_space(),
+            button(tr("Cancel"))
+                .on_press(Message::CancelVoid)
+                .style(button::secondary),
+            button(tr("Void"))
+                .on_press_maybe(confirm)
+                .style(button::danger),
+        ]
+        .spacing(10),
+    )
+    .spacing(20)
+    .width(400)
+    .into()
+}
+
+fn save_badge<'a>(save_status: Option<SaveStatus>) -> Element<'a, Message> {
+    match save_status {
+        None => horizontal_space().width(0).into(),
+        Some(status @ SaveStatus::Saving(_)) => text(status.to_string())
+            .size(12)
+            .shaping(text::Shaping::Advanced)
+            .into(),
+        Some(status @ SaveStatus::Failed) => row![
+            text(status.to_string()).size(12).style(text::danger),
+            button(text(tr("Retry")).size(12))
+                .on_press(Message::RetrySave)
+                .style(button::danger),
+        ]
+        .spacing(5)
+        .align_y(Alignment::Center)
+        .into(),
+    }
+}
+
+fn custom_fields<'a>(
+    sale: &'a Sale,
+    fields: &'a [custom::Field],
+) -> Element<'a, Message> {
+    fields
+        .iter()
+        .fold(column![].spacing(5), |col, field| {
+            col.push(row![
+                text(field.display_name()).width(150.0),
+                text(sale.custom_field(field.id)),
+            ])
+        })
+        .into()
+}
+
+fn section_header(
+    section: &Section,
+    collapsed: bool,
+    subtotal: f32,
+) -> Element<'_, Message> {
+    let toggle = if collapsed { "▸" } else { "▾" };
+
+    button(
+        row![
+            text(toggle).shaping(text::Shaping::Advanced).width(20.0),
+            text(section.display_name()).width(Fill),
+            text(money(subtotal)).align_x(Alignment::End).width(100.0),
+        ]
+        .spacing(5)
+        .align_y(Alignment::Center),
+    )
+    .on_press(Message::ToggleSection(section.id))
+    .style(button::text)
+    .padding([5, 0])
+    .width(Fill)
     .into()
 }
 
-pub fn handle_hotkey(hotkey: Hotkey) -> Action<Instruction, Message> {
+fn item_row(item: &SaleItem, density: Density) -> Element<'_, Message> {
+    let size = density.text_size(16.0);
+    // Comps and adjustments in red
+    let style = if item.is_negative() {
+        text::danger
+    } else {
+        text::default
+    };
+
+    if item.is_void() {
+        return container(
+            row![
+                container(super::voided(item, size)).width(Fill),
+                text(item.quantity_label())
+                    .size(size)
+                    .align_x(Alignment::Center)
+                    .width(80.0),
+                text(money(item.price()))
+                    .size(size)
+                    .align_x(Alignment::End)
+                    .width(100.0),
+                horizontal_space().width(140.0),
+                text(money(0.0))
+                    .size(size)
+                    .align_x(Alignment::End)
+                    .width(100.0),
+                horizontal_space().width(60.0),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+        )
+        .style(container::rounded_box)
+        .padding(0)
+        .into();
+    }
+
+    container(
+        row![
+            row![
+                text(&item.name).size(size).style(style),
+                allergens(item, size)
+            ]
+            .spacing(10)
+            .width(Fill),
+            text(item.quantity_label())
+                .size(size)
+                .align_x(Alignment::Center)
+                .width(80.0),
+            text(money(item.price()))
+                .size(size)
+                .style(style)
+                .align_x(Alignment::End)
+                .width(100.0),
+            text(if item.adjustment {
+                tr("No tax").to_string()
+            } else {
+                item.tax_group.to_string()
+            })
+            .size(size)
+            .width(140.0),
+            text(money(item.total()))
+                .size(size)
+                .style(style)
+                .align_x(Alignment::End)
+                .width(100.0),
+            button(text(tr("Void")).size(12).center())
+                .on_press(Message::VoidItem(item.id))
+                .style(button::text)
+                .width(60.0)
+        ]
+        .spacing(5)
+        .align_y(Alignment::Center),
+    )
+    .style(container::rounded_box)
+    .padding(0)
+    .into()
+}
+
+fn allergens(item: &SaleItem, size: f32) -> Element<'_, Message> {
+    if item.allergens.is_empty() {
+        return horizontal_space().width(0).into();
+    }
+
+    text(format!("⚠ {}", allergen::list(&item.allergens)))
+        .size(size - 4.0)
+        .style(text::danger)
+        .shaping(text::Shaping::Advanced)
+        .into()
+}
+
+pub fn handle_hotkey(
+    form: &mut Form,
+    hotkey: Hotkey,
+) -> Action<Instruction, Message> {
     match hotkey {
+        // Escape first leaves a void or gratuity adjustment, then the sale
+        Hotkey::Escape if form.void_input.take().is_some() => Action::none(),
+        Hotkey::Escape if form.gratuity_pin.take().is_some() => Action::none(),
+        Hotkey::Escape if form.gratuity_input.take().is_some() => {
+            Action::none()
+        }
         Hotkey::Escape => Action::instruction(Instruction::Back),
         _ => Action::none(),
     }
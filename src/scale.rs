@@ -0,0 +1,210 @@
+//! Reading the weight of items sold by weight off a scale
+//!
+//! The scale is plugged in over serial or USB, or reached over the network,
+//! and asked for the weight with `W`, as scales in NCI or Toledo mode
+//! answer. A serial port is used as it is set up, so its baud rate is set
+//! with `stty` beforehand. Every reading is logged as the scale sent it, in
+//! `scale.log`, for weights and measures inspections.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config::data_dir;
+use crate::data::Error;
+
+/// What asks the scale for the weight.
+const REQUEST: &[u8] = b"W\r";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The scale, as a device path such as `/dev/ttyUSB0` or `host:port` for
+    /// a network scale. Empty when there is no scale.
+    pub port: String,
+}
+
+impl Config {
+    pub fn is_enabled(&self) -> bool {
+        !self.port.trim().is_empty()
+    }
+}
+
+/// A scale the weight can be read off.
+pub trait Scale {
+    /// Ask for the weight, returning the answer as the scale sent it.
+    fn request(&mut self) -> Result<String, Error>;
+}
+
+/// The scale at a device path or network address.
+pub struct Port(pub String);
+
+impl Scale for Port {
+    fn request(&mut self) -> Result<String, Error> {
+        let port = self.0.trim();
+
+        // A device path has no port, while a network scale always has one
+        if port.starts_with('/') || !port.contains(':') {
+            let mut device = std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(port)?;
+            device.write_all(REQUEST)?;
+            answer(&mut device)
+        } else {
+            let mut stream = TcpStream::connect(port)?;
+            stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+            stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+            stream.write_all(REQUEST)?;
+            answer(&mut stream)
+        }
+    }
+}
+
+/// The first line the scale sends back.
+fn answer(from: &mut impl Read) -> Result<String, Error> {
+    let mut answer = Vec::new();
+    let mut byte = [0];
+    while answer.len() < 64 {
+        if from.read(&mut byte)? == 0 {
+            break;
+        }
+        match byte[0] {
+            b'\r' | b'\n' if answer.is_empty() => {}
+            b'\r' | b'\n' => break,
+            byte => answer.push(byte),
+        }
+    }
+    Ok(String::from_utf8_lossy(&answer).into_owned())
+}
+
+/// A weight read off the scale.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reading {
+    /// The answer of the scale, as it was sent.
+    pub raw: String,
+    pub kg: f32,
+}
+
+impl Reading {
+    /// Read the weight in an answer such as `  1.234kg`, `ST,GS,+0.500 kg`
+    /// or `2.50 lb`, in kg.
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim();
+        // Toledo scales mark a moving weight with `?`, others with `US`
+        if raw.contains('?') || raw.starts_with("US") {
+            return Err("The scale hasn't settled".to_string());
+        }
+
+        let start = raw
+            .find(|c: char| c.is_ascii_digit())
+            .ok_or_else(|| format!("The scale sent no weight: \"{raw}\""))?;
+        let negative = raw[..start].trim_end().ends_with('-');
+        let end = raw[start..]
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .map_or(raw.len(), |end| start + end);
+        let weight: f32 = raw[start..end]
+            .parse()
+            .map_err(|_| format!("The scale sent no weight: \"{raw}\""))?;
+        if negative || weight <= 0.0 {
+            return Err("There is nothing on the scale".to_string());
+        }
+
+        let unit = raw[end..]
+            .trim_start()
+            .split(|c: char| !c.is_ascii_alphabetic())
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let kg = match unit.as_str() {
+            "" | "kg" => weight,
+            "g" => weight / 1000.0,
+            "lb" | "lbs" => weight * 0.453_592_37,
+            "oz" => weight * 0.028_349_523,
+            unit => return Err(format!("Unknown unit of weight: {unit}")),
+        };
+        Ok(Self {
+            raw: raw.to_string(),
+            kg,
+        })
+    }
+}
+
+/// Ask `scale` for the weight.
+pub fn read(scale: &mut impl Scale) -> Result<Reading, String> {
+    let raw = scale.request().map_err(|e| e.to_string())?;
+    Reading::parse(&raw)
+}
+
+/// Read the weight of `item` off the scale at `port`, and log it.
+pub async fn weigh(port: String, item: String) -> Result<Reading, String> {
+    let reading = read(&mut Port(port))?;
+    tracing::info!(item, raw = reading.raw, kg = reading.kg, "scale read");
+    log(&reading, &item)
+        .map_err(|e| format!("Could not log the reading: {e}"))?;
+    Ok(reading)
+}
+
+/// A reading as logged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub at: DateTime<Utc>,
+    pub item: String,
+    #[serde(flatten)]
+    pub reading: Reading,
+}
+
+fn path() -> PathBuf {
+    data_dir().join("scale.log")
+}
+
+/// Append `reading` of `item` to the log.
+fn log(reading: &Reading, item: &str) -> Result<(), Error> {
+    let entry = Entry {
+        at: Utc::now(),
+        item: item.to_string(),
+        reading: reading.clone(),
+    };
+
+    std::fs::create_dir_all(data_dir())?;
+    let mut log = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path())?;
+    writeln!(log, "{}", serde_json::to_string(&entry)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scale answering with what was put on it.
+    struct Mock(&'static [u8]);
+
+    impl Scale for Mock {
+        fn request(&mut self) -> Result<String, Error> {
+            answer(&mut self.0)
+        }
+    }
+
+    #[test]
+    fn weights_are_read_in_kg() {
+        let kg = |answer| read(&mut Mock(answer)).map(|reading| reading.kg);
+
+        assert_eq!(kg(b"  1.234kg\r\n"), Ok(1.234));
+        assert_eq!(kg(b"\r\nST,GS,+0.500 kg\r"), Ok(0.5));
+        assert_eq!(kg(b"250 g"), Ok(0.25));
+        assert_eq!(kg(b"2.000LB"), Ok(0.907_184_7));
+        assert!(kg(b"?  1.200kg").is_err());
+        assert!(kg(b"-0.010kg").is_err());
+        assert!(kg(b"0.000").is_err());
+        assert!(kg(b"ERR").is_err());
+        assert!(kg(b"1.2 st").is_err());
+
+        let reading = read(&mut Mock(b" 0.750kg\r1.000kg\r")).unwrap();
+        assert_eq!(reading.raw, "0.750kg");
+    }
+}
@@ -0,0 +1,163 @@
+//! Orders scheduled for later, e.g. a catering pickup tomorrow at 11:00
+//!
+//! A sale can be given the time it is due at. Upcoming orders are listed on
+//! a tab of their own, the soonest first, and a reminder is raised a while
+//! before each one is due, as set in the settings.
+use chrono::{DateTime, Duration, Local, NaiveDateTime, Utc};
+use iced::futures::channel::oneshot;
+use iced::futures::SinkExt;
+use iced::Subscription;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::sale::OrderStatus;
+use crate::store::Header;
+
+/// How due times are typed and shown, in local time.
+pub const FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// How often the clock checks for orders coming due.
+const TICK: std::time::Duration = std::time::Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// How many minutes before an order is due to raise its reminder, 0
+    /// for no reminders.
+    pub remind_before: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self { remind_before: 15 }
+    }
+}
+
+impl Config {
+    pub fn is_enabled(&self) -> bool {
+        self.remind_before > 0
+    }
+
+    /// The upcoming orders of `headers` to remind of at `now`, leaving out
+    /// those already `reminded` of.
+    pub fn reminders(
+        &self,
+        headers: BTreeMap<usize, Header>,
+        now: DateTime<Utc>,
+        reminded: &BTreeSet<usize>,
+    ) -> Vec<(usize, Header)> {
+        let before = Duration::minutes(i64::from(self.remind_before));
+        upcoming(headers)
+            .into_iter()
+            .filter(|(id, _)| self.is_enabled() && !reminded.contains(id))
+            .filter(|(_, header)| {
+                header
+                    .due_at
+                    .is_some_and(|due| due - before <= now && now < due)
+            })
+            .collect()
+    }
+}
+
+/// The due time typed as `YYYY-MM-DD HH:MM` in local time, if it is valid.
+pub fn parse(input: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(input.trim(), FORMAT)
+        .ok()?
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|due| due.with_timezone(&Utc))
+}
+
+/// The due time as typed, in local time.
+pub fn label(due: DateTime<Utc>) -> String {
+    due.with_timezone(&Local).format(FORMAT).to_string()
+}
+
+/// The orders with a due time which haven't been picked up, the soonest
+/// first.
+pub fn upcoming(headers: BTreeMap<usize, Header>) -> Vec<(usize, Header)> {
+    let mut upcoming: Vec<_> = headers
+        .into_iter()
+        .filter(|(_, header)| {
+            header.due_at.is_some()
+                && header.order_status != OrderStatus::PickedUp
+        })
+        .collect();
+    upcoming.sort_by_key(|(id, header)| (header.due_at, *id));
+    upcoming
+}
+
+/// Tick every minute, to check for orders coming due.
+pub fn clock() -> Subscription<()> {
+    Subscription::run_with_id(
+        "schedule",
+        iced::stream::channel(1, |mut output| async move {
+            loop {
+                let (tick, ticked) = oneshot::channel();
+                std::thread::spawn(move || {
+                    std::thread::sleep(TICK);
+                    let _ = tick.send(());
+                });
+                let _ = ticked.await;
+                if output.send(()).await.is_err() {
+                    return;
+                }
+            }
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sale::Sale;
+
+    fn header(due_at: Option<&str>, order_status: OrderStatus) -> Header {
+        Header::new(&Sale {
+            due_at: due_at.and_then(parse),
+            order_status,
+            ..Sale::default()
+        })
+    }
+
+    #[test]
+    fn reminds_once_shortly_before_due() {
+        let headers = BTreeMap::from([
+            (1, header(Some("2024-03-16 11:00"), OrderStatus::Open)),
+            (2, header(Some("2024-03-16 10:50"), OrderStatus::Ready)),
+            (3, header(Some("2024-03-16 10:55"), OrderStatus::PickedUp)),
+            (4, header(None, OrderStatus::Open)),
+        ]);
+        let ids = |upcoming: Vec<(usize, Header)>| -> Vec<usize> {
+            upcoming.into_iter().map(|(id, _)| id).collect()
+        };
+        assert_eq!(ids(upcoming(headers.clone())), [2, 1]);
+
+        let config = Config::default();
+        let at = |time| parse(time).unwrap();
+        let mut reminded = BTreeSet::new();
+        assert_eq!(
+            ids(config.reminders(
+                headers.clone(),
+                at("2024-03-16 10:30"),
+                &reminded
+            )),
+            [] as [usize; 0]
+        );
+        assert_eq!(
+            ids(config.reminders(
+                headers.clone(),
+                at("2024-03-16 10:45"),
+                &reminded
+            )),
+            [2, 1]
+        );
+
+        // Not again once reminded, nor once due
+        reminded.insert(1);
+        assert_eq!(
+            ids(config.reminders(headers, at("2024-03-16 10:50"), &reminded)),
+            [] as [usize; 0]
+        );
+    }
+}
@@ -0,0 +1,27 @@
+//! Typed text kept out of the logs, e.g. a PIN or a password
+//!
+//! Every message and instruction is logged with its debug output, so the
+//! ones carrying what is typed into a PIN, password or API key input hold it
+//! as a [`Secret`], which leaves it out.
+use std::fmt;
+
+#[derive(Clone, Default, PartialEq)]
+pub struct Secret(pub String);
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_left_out_of_debug_output() {
+        let secret = Secret("hunter2".to_string());
+        assert_eq!(format!("{secret:?}"), "Secret(..)");
+        assert_eq!(format!("{:?}", Some(secret)), "Some(Secret(..))");
+    }
+}
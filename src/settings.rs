@@ -0,0 +1,2365 @@
+//! App settings and data management
+use iced::widget::{
+    button, checkbox, column, container, horizontal_space, image, pick_list,
+    row, scrollable, slider, text, text_editor, text_input,
+};
+use iced::{Alignment, Element, Fill};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::accounting::{Account, Accounts};
+use crate::backup::Snapshot;
+use crate::catalog::ZeroStock;
+use crate::config::Config;
+use crate::currency;
+use crate::custom;
+use crate::data::{Conflict, Import};
+use crate::delivery;
+use crate::denomination;
+use crate::density::Density;
+use crate::i18n::{self, Locale};
+use crate::layout;
+use crate::logo::Logo;
+use crate::mail;
+use crate::outbox::Outbox;
+use crate::pin::ManagerPin;
+use crate::sale::OrderType;
+use crate::scale;
+use crate::schedule;
+use crate::secret::Secret;
+use crate::service;
+use crate::shortcut::Keymap;
+use crate::store;
+use crate::style;
+use crate::sync;
+use crate::tax;
+use crate::terminal;
+use crate::tips;
+use crate::toast;
+use crate::vat;
+use crate::{Action, Hotkey, Screen};
+
+#[derive(Debug)]
+pub struct Settings {
+    pub config: Config,
+    pub data_path: String,
+    /// The path of a logo image to load, as typed.
+    pub logo_path: String,
+    /// A note or coin to add to the base currency, as typed.
+    pub denomination: String,
+    /// A tip percentage to suggest, as typed.
+    pub tip_percent: String,
+    /// The key chords of hotkeys, as typed.
+    pub shortcuts: BTreeMap<Hotkey, String>,
+    /// The manager PIN set, as typed to change it.
+    pub current_pin: String,
+    /// The manager PIN to change it to, as typed.
+    pub new_pin: String,
+    /// The header and footer of receipts, as typed.
+    pub receipt_header: text_editor::Content,
+    pub receipt_footer: text_editor::Content,
+    pub conflict: Conflict,
+    pub pending_import: Option<Import>,
+    pub backups: Vec<Snapshot>,
+    pub selected_backup: Option<Snapshot>,
+    pub backup_status: Option<Result<String, String>>,
+    pub live_status: Option<Result<String, String>>,
+}
+
+impl Settings {
+    pub fn new(config: Config) -> Self {
+        Self {
+            receipt_header: text_editor::Content::with_text(
+                &config.receipt_header,
+            ),
+            receipt_footer: text_editor::Content::with_text(
+                &config.receipt_footer,
+            ),
+            config,
+            data_path: "receipts-export.json".to_string(),
+            logo_path: String::new(),
+            denomination: String::new(),
+            tip_percent: String::new(),
+            shortcuts: BTreeMap::new(),
+            current_pin: String::new(),
+            new_pin: String::new(),
+            conflict: Conflict::default(),
+            pending_import: None,
+            backups: Vec::new(),
+            selected_backup: None,
+            backup_status: None,
+            live_status: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Back,
+    DataPathInput(String),
+    Export,
+    ExportCsv,
+    ValidateImport,
+    ImportSale,
+    SelectConflict(Conflict),
+    ApplyImport,
+    CancelImport,
+    SelectStoreFormat(store::Format),
+    BackupEveryInput(String),
+    BackupsKeptInput(String),
+    SelectBackup(Snapshot),
+    BackupNow,
+    RestoreBackup,
+    ArchiveAfterInput(String),
+    TrashDaysInput(String),
+    ArchiveNow,
+    AddCustomField,
+    RemoveCustomField(usize),
+    CustomFieldName(usize, String),
+    CustomFieldKind(usize, custom::Kind),
+    CustomFieldOptions(usize, String),
+    CustomFieldOnReceipt(usize, bool),
+    ReceiptQrToggled(bool),
+    ReceiptQrUrlInput(String),
+    SelectTaxCountry(vat::Country),
+    TaxIdInput(String),
+    FinePrintInput(OrderType, String),
+    EditReceiptHeader(text_editor::Action),
+    EditReceiptFooter(text_editor::Action),
+    EditLayout,
+    EditQuickButtons,
+    LogoPathInput(String),
+    LoadLogo,
+    RemoveLogo,
+    SelectZeroStock(ZeroStock),
+    SelectRoundingMode(tax::Mode),
+    SelectRoundingPer(tax::Per),
+    AccountInput(Account, String),
+    XeroTaxRateInput(String),
+    AddServiceRule,
+    RemoveServiceRule(usize),
+    ServiceRuleOrders(usize, service::Orders),
+    ServiceRuleGuests(usize, String),
+    ServiceRulePercent(usize, String),
+    SelectTipBase(tips::Base),
+    TipPercentInput(String),
+    AddTipPercent,
+    /// Stop suggesting the tip at this index.
+    RemoveTipPercent(usize),
+    SelectDeliveryPricing(delivery::Pricing),
+    DeliveryFeeInput(String),
+    DeliveryPerKmInput(String),
+    DrawerPrinterInput(String),
+    ScalePortInput(String),
+    DenominationInput(String),
+    AddDenomination,
+    /// Stop counting the drawer by a denomination, in cents.
+    RemoveDenomination(u32),
+    RemindBeforeInput(String),
+    SelectDensity(Density),
+    SelectLocale(Locale),
+    UiScaleChanged(u32),
+    KeypadToggled(bool),
+    CurrentPinInput(Secret),
+    NewPinInput(Secret),
+    /// Set the manager PIN typed, once the current one is.
+    ChangeManagerPin,
+    OverrideApprovalInput(String),
+    ShortcutInput(Hotkey, String),
+    ResetShortcuts,
+    ShorthandToggled(bool),
+    Sync(SyncField),
+    Terminal(TerminalField),
+    Currency(CurrencyField),
+    Mail(MailField),
+    SummaryToInput(String),
+    SummaryAtInput(String),
+    SendSummary,
+    RetryOutbox,
+    LiveFeedToggled(bool),
+    LiveFeedPortInput(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum SyncField {
+    Backend(sync::Backend),
+    Url(String),
+    Username(String),
+    Password(Secret),
+    Bucket(String),
+    Region(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum TerminalField {
+    Provider(terminal::Provider),
+    ApiKey(Secret),
+    Reader(String),
+    Currency(String),
+    Sandbox(bool),
+}
+
+#[derive(Debug, Clone)]
+pub enum CurrencyField {
+    Base(String),
+    Code(usize, String),
+    Rate(usize, String),
+    Add,
+    Remove(usize),
+    Fetch,
+    Api(String),
+    RefreshHours(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum MailField {
+    Host(String),
+    Port(String),
+    Security(mail::Security),
+    Username(String),
+    Password(Secret),
+    From(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Navigate(Screen),
+    Export(PathBuf),
+    ExportCsv(PathBuf),
+    ValidateImport(PathBuf),
+    ImportSale(PathBuf),
+    ApplyImport,
+    SaveConfig,
+    ConvertStore,
+    BackupNow,
+    RestoreBackup(Snapshot),
+    Archive,
+    RetryOutbox,
+    /// Email the summary of today right away.
+    SendSummary,
+    LoadLogo(PathBuf),
+    RemoveLogo,
+    /// Fetch the rates of the currencies payments are taken in.
+    FetchRates,
+}
+
+impl From<Screen> for Instruction {
+    fn from(screen: Screen) -> Self {
+        Instruction::Navigate(screen)
+    }
+}
+
+pub fn update(
+    settings: &mut Settings,
+    message: Message,
+) -> Action<Instruction, Message> {
+    match message {
+        Message::Back => Action::navigate(Screen::List),
+        Message::DataPathInput(path) => {
+            settings.data_path = path;
+            settings.pending_import = None;
+            Action::none()
+        }
+        Message::Export => Action::instruction(Instruction::Export(
+            PathBuf::from(&settings.data_path),
+        )),
+        Message::ExportCsv => Action::instruction(Instruction::ExportCsv(
+            PathBuf::from(&settings.data_path).with_extension("csv"),
+        )),
+        Message::ValidateImport => Action::instruction(
+            Instruction::ValidateImport(PathBuf::from(&settings.data_path)),
+        ),
+        Message::ImportSale => Action::instruction(Instruction::ImportSale(
+            PathBuf::from(&settings.data_path),
+        )),
+        Message::SelectConflict(conflict) => {
+            settings.conflict = conflict;
+            Action::none()
+        }
+        Message::ApplyImport => Action::instruction(Instruction::ApplyImport),
+        Message::CancelImport => {
+            settings.pending_import = None;
+            Action::none()
+        }
+        Message::SelectStoreFormat(format) => {
+            if format == settings.config.store_format {
+                return Action::none();
+            }
+            settings.config.store_format = format;
+            Action::instruction(Instruction::ConvertStore)
+        }
+        Message::BackupEveryInput(every) => match every.trim().parse() {
+            Ok(every) if every > 0 => {
+                settings.config.backup_every = every;
+                Action::instruction(Instruction::SaveConfig)
+            }
+            _ => Action::none(),
+        },
+        Message::BackupsKeptInput(kept) => match kept.trim().parse() {
+            Ok(kept) if kept > 0 => {
+                settings.config.backups_kept = kept;
+                Action::instruction(Instruction::SaveConfig)
+            }
+            _ => Action::none(),
+        },
+        Message::ArchiveAfterInput(days) => match days.trim() {
+            "" => {
+                settings.config.archive_after_days = None;
+                Action::instruction(Instruction::SaveConfig)
+            }
+            days => match days.parse() {
+                Ok(days) if days > 0 => {
+                    settings.config.archive_after_days = Some(days);
+                    Action::instruction(Instruction::SaveConfig)
+                }
+                _ => Action::none(),
+            },
+        },
+        Message::TrashDaysInput(days) => match days.trim() {
+            "" => {
+                settings.config.trash_days = None;
+                Action::instruction(Instruction::SaveConfig)
+            }
+            days => match days.parse() {
+                Ok(days) if days > 0 => {
+                    settings.config.trash_days = Some(days);
+                    Action::instruction(Instruction::SaveConfig)
+                }
+                _ => Action::none(),
+            },
+        },
+        Message::ArchiveNow => Action::instruction(Instruction::Archive),
+        Message::RetryOutbox => Action::instruction(Instruction::RetryOutbox),
+        Message::SelectBackup(snapshot) => {
+            settings.selected_backup = Some(snapshot);
+            Action::none()
+        }
+        Message::BackupNow => Action::instruction(Instruction::BackupNow),
+        Message::RestoreBackup => match &settings.selected_backup {
+            Some(snapshot) => Action::instruction(Instruction::RestoreBackup(
+                snapshot.clone(),
+            )),
+            None => Action::none(),
+        },
+        Message::AddCustomField => {
+            let fields = &mut settings.config.custom_fields;
+            fields.push(custom::Field::new(custom::next_id(fields)));
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::RemoveCustomField(id) => {
+            settings.config.custom_fields.retain(|field| field.id != id);
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::CustomFieldName(id, name) => {
+            update_field(settings, id, |field| field.name = name)
+        }
+        Message::CustomFieldKind(id, kind) => {
+            update_field(settings, id, |field| field.kind = kind)
+        }
+        Message::CustomFieldOptions(id, options) => {
+            update_field(settings, id, |field| {
+                field.options =
+                    options.split(',').map(|o| o.trim().to_string()).collect()
+            })
+        }
+        Message::CustomFieldOnReceipt(id, on_receipt) => {
+            update_field(settings, id, |field| field.on_receipt = on_receipt)
+        }
+        Message::Sync(field) => {
+            let remote = &mut settings.config.sync;
+            match field {
+                SyncField::Backend(backend) => remote.backend = backend,
+                SyncField::Url(url) => remote.url = url,
+                SyncField::Username(username) => remote.username = username,
+                SyncField::Password(password) => remote.password = password.0,
+                SyncField::Bucket(bucket) => remote.bucket = bucket,
+                SyncField::Region(region) => remote.region = region,
+            }
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::Terminal(field) => {
+            let terminal = &mut settings.config.terminal;
+            match field {
+                TerminalField::Provider(provider) => {
+                    terminal.provider = provider;
+                }
+                TerminalField::ApiKey(key) => terminal.api_key = key.0,
+                TerminalField::Reader(reader) => terminal.reader = reader,
+                TerminalField::Currency(currency) => {
+                    terminal.currency = currency.trim().to_uppercase();
+                }
+                TerminalField::Sandbox(sandbox) => terminal.sandbox = sandbox,
+            }
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::Currency(field) => {
+            let currency = &mut settings.config.currency;
+            match field {
+                CurrencyField::Base(base) => {
+                    currency.base = base.trim().to_uppercase();
+                }
+                CurrencyField::Code(index, code) => {
+                    if let Some(rate) = currency.rates.get_mut(index) {
+                        rate.code = code.trim().to_uppercase();
+                    }
+                }
+                CurrencyField::Rate(index, input) => {
+                    let Some(value) = amount(&input) else {
+                        return Action::none();
+                    };
+                    if let Some(rate) = currency.rates.get_mut(index) {
+                        rate.rate = value;
+                    }
+                }
+                CurrencyField::Add => currency.rates.push(currency::Rate {
+                    code: String::new(),
+                    rate: 0.0,
+                }),
+                CurrencyField::Remove(index) => {
+                    if index < currency.rates.len() {
+                        currency.rates.remove(index);
+                    }
+                }
+                CurrencyField::Fetch => {
+                    return Action::instruction(Instruction::FetchRates)
+                }
+                CurrencyField::Api(api) => {
+                    currency.api = api.trim().to_string()
+                }
+                CurrencyField::RefreshHours(hours) => match hours.trim() {
+                    "" => currency.refresh_hours = 0,
+                    hours => match hours.parse() {
+                        Ok(hours) => currency.refresh_hours = hours,
+                        Err(_) => return Action::none(),
+                    },
+                },
+            }
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::Mail(field) => {
+            let smtp = &mut settings.config.smtp;
+            match field {
+                MailField::Host(host) => smtp.host = host.trim().to_string(),
+                MailField::Port(port) => {
+                    if let Ok(port) = port.parse() {
+                        smtp.port = port;
+                    }
+                }
+                MailField::Security(security) => {
+                    smtp.port = match security {
+                        mail::Security::Tls => 465,
+                        mail::Security::StartTls => 587,
+                        mail::Security::None => 25,
+                    };
+                    smtp.security = security;
+                }
+                MailField::Username(username) => smtp.username = username,
+                MailField::Password(password) => smtp.password = password.0,
+                MailField::From(from) => smtp.from = from.trim().to_string(),
+            }
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::SummaryToInput(to) => {
+            settings.config.summary.to = to.trim().to_string();
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::SummaryAtInput(at) => {
+            settings.config.summary.at = at;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::SendSummary => Action::instruction(Instruction::SendSummary),
+        Message::ReceiptQrToggled(enabled) => {
+            settings.config.receipt_qr.enabled = enabled;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::ReceiptQrUrlInput(url) => {
+            settings.config.receipt_qr.url = url;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::SelectTaxCountry(country) => {
+            settings.config.tax_registration.country = country;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::TaxIdInput(number) => {
+            settings.config.tax_registration.number = number;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::FinePrintInput(order_type, text) => {
+            if text.is_empty() {
+                settings.config.fine_print.remove(&order_type);
+            } else {
+                settings.config.fine_print.insert(order_type, text);
+            }
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::EditReceiptHeader(action) => {
+            let is_edit = action.is_edit();
+            settings.receipt_header.perform(action);
+            if !is_edit {
+                return Action::none();
+            }
+            settings.config.receipt_header = settings.receipt_header.text();
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::EditReceiptFooter(action) => {
+            let is_edit = action.is_edit();
+            settings.receipt_footer.perform(action);
+            if !is_edit {
+                return Action::none();
+            }
+            settings.config.receipt_footer = settings.receipt_footer.text();
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::EditLayout => Action::navigate(Screen::Layout),
+        Message::EditQuickButtons => Action::navigate(Screen::QuickButtons),
+        Message::LogoPathInput(path) => {
+            settings.logo_path = path;
+            Action::none()
+        }
+        Message::LoadLogo => {
+            let path = settings.logo_path.trim();
+            if path.is_empty() {
+                Action::none()
+            } else {
+                Action::instruction(Instruction::LoadLogo(PathBuf::from(path)))
+            }
+        }
+        Message::RemoveLogo => Action::instruction(Instruction::RemoveLogo),
+        Message::SelectZeroStock(zero_stock) => {
+            settings.config.zero_stock = zero_stock;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::AddServiceRule => {
+            settings.config.service_rules.push(service::Rule::default());
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::RemoveServiceRule(index) => {
+            if index < settings.config.service_rules.len() {
+                settings.config.service_rules.remove(index);
+            }
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::ServiceRuleOrders(index, orders) => {
+            update_rule(settings, index, |rule| rule.orders = orders)
+        }
+        Message::ServiceRuleGuests(index, guests) => match guests.trim() {
+            "" => update_rule(settings, index, |rule| rule.min_guests = 0),
+            guests => match guests.parse() {
+                Ok(guests) => update_rule(settings, index, |rule| {
+                    rule.min_guests = guests
+                }),
+                Err(_) => Action::none(),
+            },
+        },
+        Message::ServiceRulePercent(index, percent) => {
+            match i18n::parse_number(&percent) {
+                Some(percent) => {
+                    update_rule(settings, index, |rule| rule.percent = percent)
+                }
+                None if percent.is_empty() => {
+                    update_rule(settings, index, |rule| rule.percent = 0.0)
+                }
+                None => Action::none(),
+            }
+        }
+        Message::SelectTipBase(base) => {
+            settings.config.tip_suggestions.base = base;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::TipPercentInput(input) => {
+            settings.tip_percent = input;
+            Action::none()
+        }
+        Message::AddTipPercent => {
+            let Some(percent) =
+                amount(&settings.tip_percent).filter(|percent| *percent > 0.0)
+            else {
+                return Action::none();
+            };
+            settings.config.tip_suggestions.add(percent);
+            settings.tip_percent.clear();
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::RemoveTipPercent(index) => {
+            let percents = &mut settings.config.tip_suggestions.percents;
+            if index < percents.len() {
+                percents.remove(index);
+            }
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::SelectDeliveryPricing(pricing) => {
+            settings.config.delivery.pricing = pricing;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::DeliveryFeeInput(fee) => match amount(&fee) {
+            Some(fee) => {
+                settings.config.delivery.fee = fee;
+                Action::instruction(Instruction::SaveConfig)
+            }
+            None => Action::none(),
+        },
+        Message::DeliveryPerKmInput(per_km) => match amount(&per_km) {
+            Some(per_km) => {
+                settings.config.delivery.per_km = per_km;
+                Action::instruction(Instruction::SaveConfig)
+            }
+            None => Action::none(),
+        },
+        Message::RemindBeforeInput(minutes) => match minutes.trim() {
+            "" => {
+                settings.config.schedule.remind_before = 0;
+                Action::instruction(Instruction::SaveConfig)
+            }
+            minutes => match minutes.parse() {
+                Ok(minutes) => {
+                    settings.config.schedule.remind_before = minutes;
+                    Action::instruction(Instruction::SaveConfig)
+                }
+                Err(_) => Action::none(),
+            },
+        },
+        Message::DrawerPrinterInput(printer) => {
+            settings.config.drawer.printer = printer;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::ScalePortInput(port) => {
+            settings.config.scale.port = port;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::DenominationInput(input) => {
+            settings.denomination = input;
+            Action::none()
+        }
+        Message::AddDenomination => {
+            let Some(value) =
+                amount(&settings.denomination).filter(|value| *value > 0.0)
+            else {
+                return Action::none();
+            };
+            let config = &mut settings.config;
+            config
+                .denominations
+                .add(&config.currency.base, denomination::cents(value));
+            settings.denomination.clear();
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::RemoveDenomination(cents) => {
+            let config = &mut settings.config;
+            config.denominations.remove(&config.currency.base, cents);
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::SelectDensity(density) => {
+            settings.config.density = density;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::UiScaleChanged(scale) => {
+            settings.config.ui_scale = scale;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::CurrentPinInput(pin) => {
+            settings.current_pin = pin.0;
+            Action::none()
+        }
+        Message::NewPinInput(pin) => {
+            settings.new_pin = pin.0;
+            Action::none()
+        }
+        Message::ChangeManagerPin => {
+            let current = std::mem::take(&mut settings.current_pin);
+            if !settings.config.manager_pin.matches(&current) {
+                return Action::toast(toast::Kind::Error, "Wrong manager PIN");
+            }
+            let pin = std::mem::take(&mut settings.new_pin);
+            settings.config.manager_pin = ManagerPin::new(&pin);
+            let changed = if settings.config.manager_pin.is_set() {
+                "Changed the manager PIN"
+            } else {
+                "Removed the manager PIN"
+            };
+            Action::instruction(Instruction::SaveConfig)
+                .with_toast(toast::Kind::Success, changed)
+        }
+        Message::OverrideApprovalInput(percent) => match percent.trim() {
+            "" => {
+                settings.config.override_approval = None;
+                Action::instruction(Instruction::SaveConfig)
+            }
+            percent => match percent.parse() {
+                Ok(percent) => {
+                    settings.config.override_approval = Some(percent);
+                    Action::instruction(Instruction::SaveConfig)
+                }
+                Err(_) => Action::none(),
+            },
+        },
+        Message::ShortcutInput(hotkey, input) => {
+            let keymap = &mut settings.config.shortcuts;
+            let parsed = keymap.parse(hotkey, &input);
+            settings.shortcuts.insert(hotkey, input);
+            match parsed {
+                Ok(chords) => {
+                    keymap.bind(hotkey, chords);
+                    Action::instruction(Instruction::SaveConfig)
+                }
+                Err(_) => Action::none(),
+            }
+        }
+        Message::ResetShortcuts => {
+            settings.config.shortcuts = Keymap::default();
+            settings.shortcuts.clear();
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::KeypadToggled(keypad) => {
+            settings.config.keypad = keypad;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::ShorthandToggled(shorthand) => {
+            settings.config.shorthand = shorthand;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::SelectRoundingMode(mode) => {
+            settings.config.tax_rounding.mode = mode;
+            tax::set(settings.config.tax_rounding);
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::SelectRoundingPer(per) => {
+            settings.config.tax_rounding.per = per;
+            tax::set(settings.config.tax_rounding);
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::AccountInput(account, name) => {
+            *settings.config.accounts.get_mut(account) = name;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::XeroTaxRateInput(rate) => {
+            settings.config.accounts.xero_tax_rate = rate;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::SelectLocale(locale) => {
+            settings.config.locale = locale;
+            i18n::set(locale);
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::LiveFeedToggled(enabled) => {
+            settings.config.live_feed.enabled = enabled;
+            settings.live_status = None;
+            Action::instruction(Instruction::SaveConfig)
+        }
+        Message::LiveFeedPortInput(port) => match port.trim().parse() {
+            Ok(port) if port > 0 => {
+                settings.config.live_feed.port = port;
+                settings.live_status = None;
+                Action::instruction(Instruction::SaveConfig)
+            }
+            _ => Action::none(),
+        },
+    }
+}
+
+/// An amount typed in, empty standing for none.
+fn amount(input: &str) -> Option<f32> {
+    match input.trim() {
+        "" => Some(0.0),
+        input => i18n::parse_number(input).filter(|amount| *amount >= 0.0),
+    }
+}
+
+fn update_rule(
+    settings: &mut Settings,
+    index: usize,
+    f: impl FnOnce(&mut service::Rule),
+) -> Action<Instruction, Message> {
+    match settings.config.service_rules.get_mut(index) {
+        Some(rule) => {
+            f(rule);
+            Action::instruction(Instruction::SaveConfig)
+        }
+        None => Action::none(),
+    }
+}
+
+fn update_field(
+    settings: &mut Settings,
+    id: usize,
+    f: impl FnOnce(&mut custom::Field),
+) -> Action<Instruction, Message> {
+    match settings
+        .config
+        .custom_fields
+        .iter_mut()
+        .find(|field| field.id == id)
+    {
+        Some(field) => {
+            f(field);
+            Action::instruction(Instruction::SaveConfig)
+        }
+        None => Action::none(),
+    }
+}
+
+pub fn view<'a>(
+    settings: &'a Settings,
+    outbox: &'a Outbox,
+) -> Element<'a, Message> {
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text("Settings").size(16),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let data = column![
+        text("Data").size(16),
+        row![
+            text("File").width(150.0),
+            text_input("path/to/export.json", &settings.data_path)
+                .style(style::input)
+                .on_input(Message::DataPathInput)
+                .padding(5),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        row![
+            horizontal_space(),
+            button("Export all").on_press(Message::Export),
+            button("Export CSV")
+                .on_press(Message::ExportCsv)
+                .style(button::secondary),
+            button("Import…")
+                .on_press(Message::ValidateImport)
+                .style(button::secondary),
+            button("Import one sale")
+                .on_press(Message::ImportSale)
+                .style(button::secondary),
+        ]
+        .spacing(10),
+        row![
+            text("Store sales as").width(150.0),
+            pick_list(
+                &store::Format::ALL[..],
+                Some(settings.config.store_format),
+                Message::SelectStoreFormat
+            ),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(10);
+
+    let data = match &settings.pending_import {
+        Some(import) => data.push(import_review(import, settings.conflict)),
+        None => data,
+    };
+
+    let backups = column![
+        text("Backups").size(16),
+        row![
+            text("Back up every").width(150.0),
+            text_input("5", &settings.config.backup_every.to_string())
+                .style(style::input)
+                .on_input(Message::BackupEveryInput)
+                .width(60.0)
+                .padding(5),
+            text("saves"),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        row![
+            text("Keep last").width(150.0),
+            text_input("10", &settings.config.backups_kept.to_string())
+                .style(style::input)
+                .on_input(Message::BackupsKeptInput)
+                .width(60.0)
+                .padding(5),
+            text("backups"),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        row![
+            text("Snapshot").width(150.0),
+            pick_list(
+                settings.backups.as_slice(),
+                settings.selected_backup.clone(),
+                Message::SelectBackup
+            )
+            .placeholder("No backups yet")
+            .width(Fill),
+            button("Restore")
+                .on_press_maybe(
+                    settings
+                        .selected_backup
+                        .as_ref()
+                        .map(|_| Message::RestoreBackup)
+                )
+                .style(button::danger),
+            button("Back up now")
+                .on_press(Message::BackupNow)
+                .style(button::secondary),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(10);
+
+    let backups = match &settings.backup_status {
+        Some(Ok(status)) => backups.push(text(status).style(text::success)),
+        Some(Err(error)) => backups.push(text(error).style(text::danger)),
+        None => backups,
+    };
+
+    let archive = column![
+        text("Archive").size(16),
+        row![
+            text("Archive sales after").width(150.0),
+            text_input(
+                "Never",
+                &settings
+                    .config
+                    .archive_after_days
+                    .map_or(String::new(), |days| days.to_string())
+            )
+            .style(style::input)
+            .on_input(Message::ArchiveAfterInput)
+            .width(60.0)
+            .padding(5),
+            text("days"),
+            horizontal_space(),
+            button("Archive now")
+                .on_press_maybe(
+                    settings
+                        .config
+                        .archive_after_days
+                        .map(|_| Message::ArchiveNow)
+                )
+                .style(button::secondary),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        text(
+            "Closed sales older than this move to the archive tab of the \
+             list and are only loaded from there."
+        )
+        .size(12),
+        row![
+            text("Purge the trash after").width(150.0),
+            text_input(
+                "Never",
+                &settings
+                    .config
+                    .trash_days
+                    .map_or(String::new(), |days| days.to_string())
+            )
+            .style(style::input)
+            .on_input(Message::TrashDaysInput)
+            .width(60.0)
+            .padding(5),
+            text("days"),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        text("Deleted sales can be restored from the trash until then.")
+            .size(12),
+    ]
+    .spacing(10);
+
+    container(scrollable(
+        column![
+            header,
+            container(data)
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(backups)
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(archive)
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(custom_fields(&settings.config.custom_fields))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(display(&settings.config))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(receipts(settings))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(service_rules(&settings.config.service_rules))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(suggested_tips(settings))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(delivery(settings.config.delivery))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(voids(settings))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(shortcuts(settings))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(scheduled(settings.config.schedule))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(rounding(settings.config.tax_rounding))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(accounting(&settings.config.accounts))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(inventory(
+                settings.config.zero_stock,
+                &settings.config.scale
+            ))
+            .padding(20)
+            .width(Fill)
+            .style(container::rounded_box),
+            container(drawer(settings))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(sync_settings(&settings.config.sync))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(terminal_settings(&settings.config.terminal))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(currencies(&settings.config.currency))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(mail_settings(&settings.config.smtp))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(summary_settings(&settings.config))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(outbox_status(outbox))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(live_feed(settings))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+        ]
+        .spacing(20),
+    ))
+    .padding(20)
+    .into()
+}
+
+fn custom_fields(fields: &[custom::Field]) -> Element<'_, Message> {
+    let list = fields.iter().fold(
+        column![row![
+            text("Custom fields").size(16),
+            horizontal_space(),
+            button("+ Add Field")
+                .on_press(Message::AddCustomField)
+                .style(button::primary),
+        ]
+        .align_y(Alignment::Center)]
+        .spacing(10),
+        |col, field| {
+            let row = row![
+                text_input("Field name", &field.name)
+                    .style(style::input)
+                    .on_input(move |name| Message::CustomFieldName(
+                        field.id, name
+                    ))
+                    .width(Fill)
+                    .padding(5),
+                pick_list(&custom::Kind::ALL[..], Some(field.kind), move |k| {
+                    Message::CustomFieldKind(field.id, k)
+                })
+                .width(100.0),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center);
+
+            let row = if field.kind == custom::Kind::Choice {
+                row.push(
+                    text_input(
+                        "Options, comma separated",
+                        &field.options.join(", "),
+                    )
+                    .style(style::input)
+                    .on_input(move |options| {
+                        Message::CustomFieldOptions(field.id, options)
+                    })
+                    .width(Fill)
+                    .padding(5),
+                )
+            } else {
+                row
+            };
+
+            col.push(
+                row.push(checkbox("On receipt", field.on_receipt).on_toggle(
+                    move |on| Message::CustomFieldOnReceipt(field.id, on),
+                ))
+                .push(
+                    button(text("×").center())
+                        .width(25.0)
+                        .on_press(Message::RemoveCustomField(field.id))
+                        .style(button::danger),
+                ),
+            )
+        },
+    );
+
+    list.into()
+}
+
+fn sync_settings(remote: &sync::Remote) -> Element<'_, Message> {
+    let field =
+        |label, placeholder, value, on_input: fn(String) -> SyncField| {
+            row![
+                text(label).width(150.0),
+                text_input(placeholder, value)
+                    .style(style::input)
+                    .on_input(move |value| Message::Sync(on_input(value)))
+                    .padding(5),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+        };
+
+    let settings = column![
+        text("Sync").size(16),
+        row![
+            text("Sync with").width(150.0),
+            pick_list(&sync::Backend::ALL[..], Some(remote.backend), |b| {
+                Message::Sync(SyncField::Backend(b))
+            }),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(10);
+
+    match remote.backend {
+        sync::Backend::Off => settings,
+        sync::Backend::WebDav => settings
+            .push(field(
+                "File URL",
+                "https://dav.example.com/receipts/sales.json",
+                &remote.url,
+                SyncField::Url,
+            ))
+            .push(field("Username", "", &remote.username, SyncField::Username))
+            .push(
+                row![
+                    text("Password").width(150.0),
+                    text_input("", &remote.password)
+                        .style(style::input)
+                        .secure(true)
+                        .on_input(|p| Message::Sync(SyncField::Password(
+                            Secret(p)
+                        )))
+                        .padding(5),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            ),
+        sync::Backend::S3 => settings
+            .push(field(
+                "Endpoint",
+                "https://s3.us-east-1.amazonaws.com",
+                &remote.url,
+                SyncField::Url,
+            ))
+            .push(field("Bucket", "", &remote.bucket, SyncField::Bucket))
+            .push(field(
+                "Region",
+                "us-east-1",
+                &remote.region,
+                SyncField::Region,
+            ))
+            .push(field(
+                "Access key id",
+                "",
+                &remote.username,
+                SyncField::Username,
+            ))
+            .push(
+                row![
+                    text("Secret key").width(150.0),
+                    text_input("", &remote.password)
+                        .style(style::input)
+                        .secure(true)
+                        .on_input(|p| Message::Sync(SyncField::Password(
+                            Secret(p)
+                        )))
+                        .padding(5),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            ),
+    }
+    .into()
+}
+
+fn mail_settings(smtp: &mail::Smtp) -> Element<'_, Message> {
+    let field =
+        |label, placeholder, value, on_input: fn(String) -> MailField| {
+            row![
+                text(label).width(150.0),
+                text_input(placeholder, value)
+                    .style(style::input)
+                    .on_input(move |value| Message::Mail(on_input(value)))
+                    .padding(5),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+        };
+
+    column![
+        text("Email").size(16),
+        field(
+            "Mail server",
+            "smtp.example.com",
+            &smtp.host,
+            MailField::Host
+        ),
+        row![
+            text("Security").width(150.0),
+            pick_list(&mail::Security::ALL[..], Some(smtp.security), |s| {
+                Message::Mail(MailField::Security(s))
+            }),
+            text("Port"),
+            text_input("465", &smtp.port.to_string())
+                .style(style::input)
+                .on_input(|port| Message::Mail(MailField::Port(port)))
+                .width(80.0)
+                .padding(5),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        field("Username", "", &smtp.username, MailField::Username),
+        row![
+            text("Password").width(150.0),
+            text_input("", &smtp.password)
+                .style(style::input)
+                .secure(true)
+                .on_input(|p| Message::Mail(MailField::Password(Secret(p))))
+                .padding(5),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        field("Send from", "shop@example.com", &smtp.from, MailField::From),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn summary_settings(config: &Config) -> Element<'_, Message> {
+    let summary = &config.summary;
+    let can_send = config.smtp.is_enabled() && summary.is_enabled();
+
+    let settings = column![
+        row![
+            text("Daily summary").size(16),
+            horizontal_space(),
+            button("Send now")
+                .on_press_maybe(can_send.then_some(Message::SendSummary))
+                .style(button::secondary),
+        ]
+        .align_y(Alignment::Center),
+        row![
+            text("Email to").width(150.0),
+            text_input("owner@example.com", &summary.to)
+                .style(style::input)
+                .on_input(Message::SummaryToInput)
+                .padding(5),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        row![
+            text("Close of business").width(150.0),
+            text_input("22:00", &summary.at)
+                .style(style::input)
+                .on_input(Message::SummaryAtInput)
+                .width(80.0)
+                .padding(5),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(10);
+
+    let note = if summary.closing().is_none() {
+        text("Enter the time as HH:MM, e.g. 22:00").style(text::danger)
+    } else if !config.smtp.is_enabled() {
+        text("Set up a mail server above to send the summary")
+    } else {
+        match summary.last_sent {
+            Some(day) => text(format!("Last sent for {day}")),
+            None => text("Sent once a day, after the close of business"),
+        }
+    };
+
+    settings.push(note.size(12)).into()
+}
+
+/// The calls waiting to be retried once back online.
+fn outbox_status(outbox: &Outbox) -> Element<'_, Message> {
+    let header = row![
+        text("Outbox").size(16),
+        horizontal_space(),
+        button("Retry now")
+            .on_press_maybe(
+                (!outbox.is_empty()).then_some(Message::RetryOutbox)
+            )
+            .style(button::secondary),
+    ]
+    .align_y(Alignment::Center);
+
+    if outbox.is_empty() {
+        return column![header, text("Nothing waiting to be sent").size(12)]
+            .spacing(10)
+            .into();
+    }
+
+    outbox
+        .entries()
+        .iter()
+        .fold(column![header].spacing(10), |col, entry| {
+            col.push(column![
+                text(entry.to_string()).shaping(text::Shaping::Advanced),
+                text(&entry.error).size(12).style(text::danger),
+            ])
+        })
+        .into()
+}
+
+fn currencies(config: &currency::Config) -> Element<'_, Message> {
+    let base = row![
+        text("Prices in").width(150.0),
+        text_input("USD", &config.base)
+            .style(style::input)
+            .on_input(|base| Message::Currency(CurrencyField::Base(base)))
+            .width(60.0)
+            .padding(5),
+        horizontal_space(),
+        button("Fetch rates")
+            .on_press_maybe(
+                (!config.rates.is_empty())
+                    .then_some(Message::Currency(CurrencyField::Fetch)),
+            )
+            .style(button::secondary),
+        button("+ Add Currency")
+            .on_press(Message::Currency(CurrencyField::Add))
+            .style(button::primary),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let fetching = column![
+        row![
+            text("Rates from").width(150.0),
+            text_input(currency::DEFAULT_API, &config.api)
+                .style(style::input)
+                .on_input(|api| Message::Currency(CurrencyField::Api(api)))
+                .padding(5),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        row![
+            text("Refresh every").width(150.0),
+            text_input(
+                "off",
+                &if config.refresh_hours > 0 {
+                    config.refresh_hours.to_string()
+                } else {
+                    String::new()
+                }
+            )
+            .style(style::input)
+            .align_x(Alignment::End)
+            .on_input(|hours| {
+                Message::Currency(CurrencyField::RefreshHours(hours))
+            })
+            .width(60.0)
+            .padding(5),
+            text("hours"),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        text(
+            "{base} in the address stands for the currency prices are in. \
+             The last rates fetched are kept for when the address can't be \
+             reached. Leave the interval empty to only fetch them by hand."
+        )
+        .size(12),
+    ]
+    .spacing(10);
+
+    let fetched = match config.fetched_at {
+        Some(at) => format!(
+            "Rates last fetched {}.",
+            at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+        ),
+        None => "Type the rates in, or fetch them.".to_string(),
+    };
+
+    config
+        .rates
+        .iter()
+        .enumerate()
+        .fold(
+            column![
+                text("Foreign currencies").size(16),
+                base,
+                text(format!(
+                    "Payments can be taken in these currencies on the sale, \
+                     with change given in {}. {fetched}",
+                    config.base
+                ))
+                .size(12),
+                fetching,
+            ]
+            .spacing(10),
+            |col, (index, rate)| {
+                col.push(
+                    row![
+                        text("1").width(150.0).align_x(Alignment::End),
+                        text_input("EUR", &rate.code)
+                            .style(style::input)
+                            .on_input(move |code| {
+                                Message::Currency(CurrencyField::Code(
+                                    index, code,
+                                ))
+                            })
+                            .width(60.0)
+                            .padding(5),
+                        text("="),
+                        text_input("0.0000", &i18n::number(rate.rate, 4))
+                            .style(style::input)
+                            .align_x(Alignment::End)
+                            .on_input(move |rate| {
+                                Message::Currency(CurrencyField::Rate(
+                                    index, rate,
+                                ))
+                            })
+                            .width(100.0)
+                            .padding(5),
+                        text(&config.base),
+                        horizontal_space(),
+                        button(text("×").center())
+                            .width(25.0)
+                            .on_press(Message::Currency(CurrencyField::Remove(
+                                index,
+                            )))
+                            .style(button::danger),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                )
+            },
+        )
+        .into()
+}
+
+fn terminal_settings(config: &terminal::Config) -> Element<'_, Message> {
+    let settings = column![
+        text("Card terminal").size(16),
+        row![
+            text("Take cards with").width(150.0),
+            pick_list(
+                &terminal::Provider::ALL[..],
+                Some(config.provider),
+                |p| { Message::Terminal(TerminalField::Provider(p)) }
+            ),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(10);
+
+    if config.provider == terminal::Provider::Off {
+        return settings.into();
+    }
+
+    let (key, reader, placeholder) = match config.provider {
+        terminal::Provider::Square => ("Access token", "Device id", ""),
+        _ => ("Secret key", "Reader id", "tmr_…"),
+    };
+    let settings = settings
+        .push(
+            row![
+                text(key).width(150.0),
+                text_input("", &config.api_key)
+                    .style(style::input)
+                    .secure(true)
+                    .on_input(|key| Message::Terminal(TerminalField::ApiKey(
+                        Secret(key)
+                    )))
+                    .padding(5),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        )
+        .push(
+            row![
+                text(reader).width(150.0),
+                text_input(placeholder, &config.reader)
+                    .style(style::input)
+                    .on_input(|reader| {
+                        Message::Terminal(TerminalField::Reader(reader))
+                    })
+                    .padding(5),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        )
+        .push(
+            row![
+                text("Currency").width(150.0),
+                text_input("USD", &config.currency)
+                    .style(style::input)
+                    .on_input(|currency| {
+                        Message::Terminal(TerminalField::Currency(currency))
+                    })
+                    .width(60.0)
+                    .padding(5),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        );
+
+    if config.provider == terminal::Provider::Square {
+        settings
+            .push(
+                checkbox("Use the Square sandbox", config.sandbox).on_toggle(
+                    |sandbox| {
+                        Message::Terminal(TerminalField::Sandbox(sandbox))
+                    },
+                ),
+            )
+            .into()
+    } else {
+        settings.into()
+    }
+}
+
+fn receipts(settings: &Settings) -> Element<'_, Message> {
+    let config = &settings.config;
+    let qr = &config.receipt_qr;
+    let fine_print = OrderType::ALL.iter().fold(
+        column![text("Fine print at the bottom of receipts, by order type")
+            .size(12)]
+        .spacing(10),
+        |col, &order_type| {
+            col.push(
+                row![
+                    text(order_type.to_string()).width(150.0),
+                    text_input(
+                        "e.g. Returns accepted within 30 days",
+                        config
+                            .fine_print
+                            .get(&order_type)
+                            .map_or("", String::as_str),
+                    )
+                    .style(style::input)
+                    .on_input(move |text| {
+                        Message::FinePrintInput(order_type, text)
+                    })
+                    .padding(5),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            )
+        },
+    );
+
+    column![
+        row![
+            text("Receipts").size(16),
+            horizontal_space(),
+            button("Edit layout…")
+                .on_press(Message::EditLayout)
+                .style(button::secondary),
+        ]
+        .align_y(Alignment::Center),
+        row![
+            text("Logo").width(150.0),
+            text_input("path/to/logo.png", &settings.logo_path)
+                .style(style::input)
+                .on_input(Message::LogoPathInput)
+                .on_submit(Message::LoadLogo)
+                .padding(5),
+            button("Load").on_press(Message::LoadLogo),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        logo(config.logo.as_ref()),
+        tax_registration(&config.tax_registration),
+        checkbox("QR code on receipts", qr.enabled)
+            .on_toggle(Message::ReceiptQrToggled),
+        row![
+            text("QR code URL").width(150.0),
+            text_input("https://example.com/receipts/{number}", &qr.url)
+                .style(style::input)
+                .on_input(Message::ReceiptQrUrlInput)
+                .padding(5),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        text(
+            "{number} is replaced by the receipt number. \
+             Without a URL the code holds a summary of the sale."
+        )
+        .size(12),
+        notes(
+            "Header",
+            &settings.receipt_header,
+            &config.receipt_header,
+            Message::EditReceiptHeader,
+        ),
+        notes(
+            "Footer",
+            &settings.receipt_footer,
+            &config.receipt_footer,
+            Message::EditReceiptFooter,
+        ),
+        text(
+            "Printed centered where the layout has @header and @footer, as \
+             the built-in one does. {receipt_no}, {date}, {time} and the \
+             other placeholders of layouts are filled in from the sale."
+        )
+        .size(12),
+        fine_print,
+    ]
+    .spacing(10)
+    .into()
+}
+
+/// The tax ID of the business and the country it is checked for.
+fn tax_registration(registration: &vat::Registration) -> Element<'_, Message> {
+    let number = registration.number.trim();
+    let status = match vat::check(registration.country, number) {
+        _ if number.is_empty() => None,
+        Ok(checked) => Some(
+            text(format!("Printed on receipts as {checked}"))
+                .style(text::success),
+        ),
+        Err(error) => Some(text(error).style(text::danger)),
+    };
+    column![row![
+        text("Tax ID").width(150.0),
+        pick_list(
+            vat::Country::ALL,
+            Some(registration.country),
+            Message::SelectTaxCountry,
+        ),
+        text_input(registration.country.example(), &registration.number)
+            .style(style::input)
+            .on_input(Message::TaxIdInput)
+            .padding(5),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center),]
+    .push_maybe(status.map(|status| {
+        row![horizontal_space().width(150.0), status.size(12)].spacing(10)
+    }))
+    .spacing(5)
+    .into()
+}
+
+/// An editor for the header or footer text of receipts, saved as typed.
+fn notes<'a>(
+    label: &'a str,
+    content: &'a text_editor::Content,
+    saved: &str,
+    on_action: fn(text_editor::Action) -> Message,
+) -> Element<'a, Message> {
+    let editor = column![text_editor(content)
+        .placeholder("e.g. VAT no. DE123456789")
+        .on_action(on_action)
+        .height(80)]
+    .spacing(5);
+    let editor = match layout::check(saved) {
+        Ok(()) => editor,
+        Err(error) => {
+            editor.push(text(error.to_string()).style(text::danger).size(12))
+        }
+    };
+    row![text(label).width(150.0), editor].spacing(10).into()
+}
+
+fn logo(logo: Option<&Logo>) -> Element<'_, Message> {
+    match logo {
+        Some(logo) => row![
+            horizontal_space().width(150.0),
+            image(logo.handle.clone()).height(48),
+            button("Remove")
+                .on_press(Message::RemoveLogo)
+                .style(button::danger),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center)
+        .into(),
+        None => text(
+            "A PNG image shown above the sales list and printed at the top \
+             of receipts, in black and white.",
+        )
+        .size(12)
+        .into(),
+    }
+}
+
+fn display(config: &Config) -> Element<'_, Message> {
+    column![
+        text("Display").size(16),
+        row![
+            text("Language").width(150.0),
+            pick_list(
+                &Locale::ALL[..],
+                Some(config.locale),
+                Message::SelectLocale
+            ),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        row![
+            text("Text size").width(150.0),
+            slider(100..=200, config.ui_scale, Message::UiScaleChanged)
+                .step(25u32)
+                .width(200.0),
+            text(format!("{}%", config.ui_scale)),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        row![
+            text("Row density").width(150.0),
+            pick_list(
+                &Density::ALL[..],
+                Some(config.density),
+                Message::SelectDensity
+            ),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        checkbox(
+            "On-screen keypad and keyboard, for touch screens",
+            config.keypad
+        )
+        .on_toggle(Message::KeypadToggled),
+        checkbox(
+            "Read item names typed as shorthand, e.g. 3 burger @ 9.99 food",
+            config.shorthand
+        )
+        .on_toggle(Message::ShorthandToggled),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn rounding<'a>(rounding: tax::Rounding) -> Element<'a, Message> {
+    column![
+        text("Rounding").size(16),
+        row![
+            text("Half cents").width(150.0),
+            pick_list(
+                &tax::Mode::ALL[..],
+                Some(rounding.mode),
+                Message::SelectRoundingMode
+            ),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        row![
+            text("Round tax for").width(150.0),
+            pick_list(
+                &tax::Per::ALL[..],
+                Some(rounding.per),
+                Message::SelectRoundingPer
+            ),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        text(
+            "Item totals, the service charge and tax are rounded to the \
+             cent before they are added up, so the lines of a receipt \
+             always add up to its total."
+        )
+        .size(12),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn accounting(accounts: &Accounts) -> Element<'_, Message> {
+    let settings = Account::ALL.iter().fold(
+        column![
+            text("Accounting").size(16),
+            text(
+                "The accounts journals exported from the reports screen are \
+                 booked to: names for QuickBooks, or codes for Xero."
+            )
+            .size(12),
+        ]
+        .spacing(10),
+        |col, &account| {
+            col.push(
+                row![
+                    text(account.to_string()).width(150.0),
+                    text_input("", accounts.get(account))
+                        .style(style::input)
+                        .on_input(move |name| {
+                            Message::AccountInput(account, name)
+                        })
+                        .padding(5),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            )
+        },
+    );
+
+    settings
+        .push(
+            row![
+                text("Xero tax rate").width(150.0),
+                text_input("Tax Exempt", &accounts.xero_tax_rate)
+                    .style(style::input)
+                    .on_input(Message::XeroTaxRateInput)
+                    .padding(5),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        )
+        .into()
+}
+
+fn inventory(
+    zero_stock: ZeroStock,
+    scale: &scale::Config,
+) -> Element<'_, Message> {
+    column![
+        row![
+            text("Inventory").size(16),
+            horizontal_space(),
+            button("Quick buttons…")
+                .on_press(Message::EditQuickButtons)
+                .style(button::secondary),
+        ]
+        .align_y(Alignment::Center),
+        row![
+            text("Selling past stock").width(150.0),
+            pick_list(
+                &ZeroStock::ALL[..],
+                Some(zero_stock),
+                Message::SelectZeroStock
+            ),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        row![
+            text("Scale").width(150.0),
+            text_input("/dev/ttyUSB0 or 192.168.1.60:4001", &scale.port)
+                .style(style::input)
+                .on_input(Message::ScalePortInput)
+                .padding(5),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        text(
+            "The scale items sold by weight are read off, answering W with \
+             the weight as in NCI or Toledo mode. Set the baud rate of a \
+             serial port with stty. Leave empty to type weights in."
+        )
+        .size(12),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn service_rules(rules: &[service::Rule]) -> Element<'_, Message> {
+    rules
+        .iter()
+        .enumerate()
+        .fold(
+            column![
+                row![
+                    text("Service charge rules").size(16),
+                    horizontal_space(),
+                    button("+ Add Rule")
+                        .on_press(Message::AddServiceRule)
+                        .style(button::primary),
+                ]
+                .align_y(Alignment::Center),
+                text(
+                    "The first rule matching a sale sets its service charge, \
+                     unless one is typed on the sale. Add e.g. 0% for \
+                     takeout above a rule for all orders."
+                )
+                .size(12),
+            ]
+            .spacing(10),
+            |col, (index, rule)| {
+                col.push(
+                    row![
+                        pick_list(
+                            &service::Orders::ALL[..],
+                            Some(rule.orders),
+                            move |orders| Message::ServiceRuleOrders(
+                                index, orders
+                            )
+                        )
+                        .width(140.0),
+                        text("from"),
+                        text_input(
+                            "any",
+                            &if rule.min_guests == 0 {
+                                String::new()
+                            } else {
+                                rule.min_guests.to_string()
+                            }
+                        )
+                        .style(style::input)
+                        .align_x(Alignment::End)
+                        .on_input(move |guests| {
+                            Message::ServiceRuleGuests(index, guests)
+                        })
+                        .width(60.0)
+                        .padding(5),
+                        text("guests:"),
+                        text_input("0.0", &i18n::number(rule.percent, 1))
+                            .style(style::input)
+                            .align_x(Alignment::End)
+                            .on_input(move |percent| {
+                                Message::ServiceRulePercent(index, percent)
+                            })
+                            .width(60.0)
+                            .padding(5),
+                        text("%"),
+                        horizontal_space(),
+                        button(text("×").center())
+                            .width(25.0)
+                            .on_press(Message::RemoveServiceRule(index))
+                            .style(button::danger),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                )
+            },
+        )
+        .into()
+}
+
+fn suggested_tips(settings: &Settings) -> Element<'_, Message> {
+    let suggestions = &settings.config.tip_suggestions;
+    let percents = suggestions
+        .percents
+        .iter()
+        .enumerate()
+        .fold(row![].spacing(5), |row, (index, percent)| {
+            row.push(
+                button(text(format!("{percent}% ×")))
+                    .on_press(Message::RemoveTipPercent(index))
+                    .style(button::secondary),
+            )
+        })
+        .push(
+            text_input("0", &settings.tip_percent)
+                .style(style::input)
+                .align_x(Alignment::End)
+                .on_input(Message::TipPercentInput)
+                .on_submit(Message::AddTipPercent)
+                .width(60.0)
+                .padding(5),
+        )
+        .push(text("%"))
+        .push(
+            button("+ Add")
+                .on_press(Message::AddTipPercent)
+                .style(button::primary),
+        )
+        .wrap();
+
+    column![
+        text("Suggested tips").size(16),
+        row![
+            text("Figured on").width(150.0),
+            pick_list(
+                &tips::Base::ALL[..],
+                Some(suggestions.base),
+                Message::SelectTipBase
+            ),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        percents,
+        text(
+            "Offered beside the gratuity of a sale, as a percentage of the \
+             subtotal before or after tax. Click one to remove it."
+        )
+        .size(12),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn delivery<'a>(config: delivery::Config) -> Element<'a, Message> {
+    let fee = row![
+        text("Delivery fee").width(150.0),
+        pick_list(
+            &delivery::Pricing::ALL[..],
+            Some(config.pricing),
+            Message::SelectDeliveryPricing
+        ),
+        text_input("0.00", &i18n::number(config.fee, 2))
+            .style(style::input)
+            .align_x(Alignment::End)
+            .on_input(Message::DeliveryFeeInput)
+            .width(80.0)
+            .padding(5),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+    let fee = if config.pricing == delivery::Pricing::ByDistance {
+        fee.push(text("plus"))
+            .push(
+                text_input("0.00", &i18n::number(config.per_km, 2))
+                    .style(style::input)
+                    .align_x(Alignment::End)
+                    .on_input(Message::DeliveryPerKmInput)
+                    .width(80.0)
+                    .padding(5),
+            )
+            .push(text("per km"))
+    } else {
+        fee
+    };
+
+    column![
+        text("Delivery").size(16),
+        fee,
+        text(
+            "Added untaxed to the total of delivery orders. By distance, \
+             the distance is typed on the sale next to the address."
+        )
+        .size(12),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn voids(settings: &Settings) -> Element<'_, Message> {
+    let config = &settings.config;
+    let current: Element<_> = if config.manager_pin.is_set() {
+        text_input("Current PIN", &settings.current_pin)
+            .style(style::input)
+            .secure(true)
+            .on_input(|pin| Message::CurrentPinInput(Secret(pin)))
+            .on_submit(Message::ChangeManagerPin)
+            .width(120.0)
+            .padding(5)
+            .into()
+    } else {
+        text("none set").size(12).width(120.0).into()
+    };
+    column![
+        text("Voids").size(16),
+        row![
+            text("Manager PIN").width(150.0),
+            current,
+            text_input("New PIN", &settings.new_pin)
+                .style(style::input)
+                .secure(true)
+                .on_input(|pin| Message::NewPinInput(Secret(pin)))
+                .on_submit(Message::ChangeManagerPin)
+                .width(120.0)
+                .padding(5),
+            button(if settings.new_pin.trim().is_empty() {
+                "Remove"
+            } else {
+                "Change"
+            })
+            .on_press_maybe(
+                (config.manager_pin.is_set()
+                    || !settings.new_pin.trim().is_empty())
+                .then_some(Message::ChangeManagerPin)
+            )
+            .style(button::secondary),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        text(
+            "Asked for to void an item of a saved sale, which stays on the \
+             sale struck through and is recorded in the audit log. Changing \
+             or removing it takes the current one. Without a PIN, items \
+             are voided without one."
+        )
+        .size(12),
+        row![
+            text("Price overrides").width(150.0),
+            text_input(
+                "never",
+                &config
+                    .override_approval
+                    .map_or(String::new(), |percent| percent.to_string())
+            )
+            .style(style::input)
+            .on_input(Message::OverrideApprovalInput)
+            .width(60.0)
+            .padding(5),
+            text("% off the catalog price need the PIN"),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        text(
+            "Prices typed over the catalog price are flagged on the sale and \
+             recorded in the audit log when it is saved. Leave empty to \
+             save them without a PIN."
+        )
+        .size(12),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn shortcuts(settings: &Settings) -> Element<'_, Message> {
+    let keymap = &settings.config.shortcuts;
+    let hotkeys =
+        Hotkey::ALL
+            .into_iter()
+            .fold(column![].spacing(5), |col, hotkey| {
+                let typed = settings.shortcuts.get(&hotkey);
+                let input =
+                    typed.cloned().unwrap_or_else(|| keymap.keys(hotkey));
+                let error =
+                    typed.and_then(|typed| keymap.parse(hotkey, typed).err());
+                col.push(
+                    row![
+                        text(hotkey.to_string()).width(200.0),
+                        text_input("none", &input)
+                            .style(style::input)
+                            .on_input(move |input| {
+                                Message::ShortcutInput(hotkey, input)
+                            })
+                            .width(200.0)
+                            .padding(5),
+                    ]
+                    .push_maybe(
+                        error.map(|error| {
+                            text(error).size(12).style(text::danger)
+                        }),
+                    )
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                )
+            });
+
+    column![
+        row![
+            text("Shortcuts").size(16),
+            horizontal_space(),
+            button("Reset to defaults")
+                .on_press_maybe(
+                    (*keymap != Keymap::default())
+                        .then_some(Message::ResetShortcuts)
+                )
+                .style(button::secondary),
+        ]
+        .align_y(Alignment::Center),
+        hotkeys,
+        text(
+            "Keys like Ctrl+Shift+S, F1 or ?, several separated by commas. \
+             A key can only do one thing: one taken already isn't saved. \
+             Press F1 on any screen for what its shortcuts do there."
+        )
+        .size(12),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn scheduled<'a>(config: schedule::Config) -> Element<'a, Message> {
+    column![
+        text("Scheduled orders").size(16),
+        row![
+            text("Remind").width(150.0),
+            text_input(
+                "off",
+                &if config.is_enabled() {
+                    config.remind_before.to_string()
+                } else {
+                    String::new()
+                }
+            )
+            .style(style::input)
+            .align_x(Alignment::End)
+            .on_input(Message::RemindBeforeInput)
+            .width(60.0)
+            .padding(5),
+            text("minutes before an order is due"),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        text(
+            "Sales given a due time on the edit screen are listed under \
+             Upcoming, the soonest first. Leave empty for no reminders."
+        )
+        .size(12),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn drawer(settings: &Settings) -> Element<'_, Message> {
+    let drawer = &settings.config.drawer;
+    let base = &settings.config.currency.base;
+    let denominations = settings
+        .config
+        .denominations
+        .of(base)
+        .into_iter()
+        .fold(row![].spacing(5), |row, cents| {
+            row.push(
+                button(text(format!("{} ×", denomination::label(cents))))
+                    .on_press(Message::RemoveDenomination(cents))
+                    .style(button::secondary),
+            )
+        })
+        .push(
+            text_input("0.00", &settings.denomination)
+                .style(style::input)
+                .align_x(Alignment::End)
+                .on_input(Message::DenominationInput)
+                .on_submit(Message::AddDenomination)
+                .width(80.0)
+                .padding(5),
+        )
+        .push(
+            button("+ Add")
+                .on_press(Message::AddDenomination)
+                .style(button::primary),
+        )
+        .wrap();
+
+    column![
+        text("Cash drawer").size(16),
+        row![
+            text("Printer").width(150.0),
+            text_input("192.168.1.50:9100 or /dev/usb/lp0", &drawer.printer)
+                .style(style::input)
+                .on_input(Message::DrawerPrinterInput)
+                .padding(5),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        text(
+            "The receipt printer the drawer is plugged into. \
+             Leave empty when there is no drawer to open."
+        )
+        .size(12),
+        text(format!("Notes and coins in {base}")),
+        denominations,
+        text(
+            "The drawer is counted by these at closing, and change is \
+             given in the fewest of them. Click one to remove it."
+        )
+        .size(12),
+    ]
+    .spacing(10)
+    .into()
+}
+
+fn live_feed(settings: &Settings) -> Element<'_, Message> {
+    let feed = &settings.config.live_feed;
+
+    let live = column![
+        text("Live feed").size(16),
+        checkbox("Broadcast sale events over WebSocket", feed.enabled)
+            .on_toggle(Message::LiveFeedToggled),
+        row![
+            text("Port").width(150.0),
+            text_input("9001", &feed.port.to_string())
+                .style(style::input)
+                .on_input(Message::LiveFeedPortInput)
+                .width(80.0)
+                .padding(5),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+    ]
+    .spacing(10);
+
+    match &settings.live_status {
+        Some(Ok(status)) => live.push(text(status).style(text::success)),
+        Some(Err(error)) => live.push(text(error).style(text::danger)),
+        None => live,
+    }
+    .into()
+}
+
+fn import_review(import: &Import, conflict: Conflict) -> Element<'_, Message> {
+    let summary = text(format!(
+        "{} sale(s) in file (version {}): {} new, {} with an id already in use",
+        import.export.sales.len(),
+        import.export.version,
+        import.new.len(),
+        import.conflicts.len(),
+    ));
+
+    let review = column![summary].spacing(10);
+    let review = if import.conflicts.is_empty() {
+        review
+    } else {
+        review.push(
+            row![
+                text("Duplicate ids").width(150.0),
+                pick_list(
+                    &Conflict::ALL[..],
+                    Some(conflict),
+                    Message::SelectConflict
+                )
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+        )
+    };
+
+    review
+        .push(
+            row![
+                horizontal_space(),
+                button("Cancel")
+                    .on_press(Message::CancelImport)
+                    .style(button::danger),
+                button("Apply import")
+                    .on_press(Message::ApplyImport)
+                    .style(button::success),
+            ]
+            .spacing(10),
+        )
+        .into()
+}
+
+pub fn handle_hotkey(hotkey: Hotkey) -> Action<Instruction, Message> {
+    match hotkey {
+        Hotkey::Escape => Action::navigate(Screen::List),
+        _ => Action::none(),
+    }
+}
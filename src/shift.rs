@@ -0,0 +1,265 @@
+//! Clock employees in and out, and see the sales and tips of each shift
+pub use receipts_core::shift::*;
+
+use chrono::{Local, Utc};
+use iced::widget::{
+    button, column, container, horizontal_space, pick_list, row, scrollable,
+    text, text_input,
+};
+use iced::{Alignment, Element, Fill, Task};
+use std::collections::BTreeMap;
+
+use crate::i18n::money;
+use crate::query::{self, Range, Row};
+use crate::store;
+use crate::style;
+use crate::toast;
+use crate::{Action, Hotkey, Screen};
+
+/// How many ended shifts the screen lists.
+const HISTORY: usize = 14;
+
+/// What is being typed on the shifts screen, and the figures of the shifts
+/// listed.
+#[derive(Debug, Default)]
+pub struct Form {
+    pub employee: String,
+    /// The format of the store to query.
+    pub format: store::Format,
+    pub totals: BTreeMap<usize, Row>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Back,
+    EmployeeInput(String),
+    SelectEmployee(String),
+    ClockIn,
+    ClockOut(usize),
+    TakeTill(usize),
+    Loaded(Result<BTreeMap<usize, Row>, String>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Navigate(Screen),
+    Save,
+}
+
+impl From<Screen> for Instruction {
+    fn from(screen: Screen) -> Self {
+        Instruction::Navigate(screen)
+    }
+}
+
+pub fn update(
+    shifts: &mut Shifts,
+    form: &mut Form,
+    message: Message,
+) -> Action<Instruction, Message> {
+    match message {
+        Message::Back => Action::navigate(Screen::List),
+        Message::EmployeeInput(employee)
+        | Message::SelectEmployee(employee) => {
+            form.employee = employee;
+            Action::none()
+        }
+        Message::ClockIn => {
+            if form.employee.trim().is_empty() {
+                return Action::toast(
+                    toast::Kind::Error,
+                    "Enter who is clocking in",
+                );
+            }
+            shifts.clock_in(&form.employee);
+            form.employee.clear();
+            Action::instruction(Instruction::Save)
+        }
+        Message::ClockOut(id) => {
+            shifts.clock_out(id);
+            Action::new(Instruction::Save, load(shifts, form))
+        }
+        Message::TakeTill(id) => {
+            shifts.take_till(id);
+            Action::instruction(Instruction::Save)
+        }
+        Message::Loaded(Ok(totals)) => {
+            form.totals = totals;
+            Action::none()
+        }
+        Message::Loaded(Err(e)) => Action::toast(
+            toast::Kind::Error,
+            format!("Could not total the shifts: {e}"),
+        ),
+    }
+}
+
+/// Total the sales of the shifts listed, in the background.
+pub fn load(shifts: &Shifts, form: &Form) -> Task<Message> {
+    let from = shifts
+        .open()
+        .chain(shifts.history().take(HISTORY))
+        .map(|shift| shift.clock_in.with_timezone(&Local).date_naive())
+        .min();
+    let Some(from) = from else {
+        return Task::none();
+    };
+    let format = form.format;
+    Task::perform(
+        async move {
+            query::by_shift(
+                format,
+                Range {
+                    from: Some(from),
+                    to: None,
+                },
+            )
+            .map_err(|e| e.to_string())
+        },
+        Message::Loaded,
+    )
+}
+
+pub fn view<'a>(shifts: &'a Shifts, form: &'a Form) -> Element<'a, Message> {
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text("Shifts").size(16),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let employees = shifts.employees();
+    let clock_in = row![
+        text("Clock in").width(150.0),
+        text_input("Name", &form.employee)
+            .style(style::input)
+            .on_input(Message::EmployeeInput)
+            .on_submit(Message::ClockIn)
+            .padding(5),
+        pick_list(employees, None::<String>, Message::SelectEmployee)
+            .placeholder("Clocked in before"),
+        button("Clock in")
+            .on_press(Message::ClockIn)
+            .style(button::success),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    container(scrollable(
+        column![
+            header,
+            container(column![clock_in, open(shifts, form)].spacing(20))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+            container(history(shifts, form))
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+        ]
+        .spacing(20),
+    ))
+    .padding(20)
+    .into()
+}
+
+fn columns<'a>(first: &'a str) -> Element<'a, Message> {
+    row![
+        text(first).width(Fill),
+        text("Hours").align_x(Alignment::End).width(60.0),
+        text("Sales").align_x(Alignment::End).width(60.0),
+        text("Total").align_x(Alignment::End).width(100.0),
+        text("Tips").align_x(Alignment::End).width(100.0),
+        horizontal_space().width(200.0),
+    ]
+    .spacing(10)
+    .into()
+}
+
+/// The hours, sales, total and tips of `shift`.
+fn figures<'a>(shift: &Shift, form: &Form) -> iced::widget::Row<'a, Message> {
+    let worked = shift.clock_out.unwrap_or_else(Utc::now) - shift.clock_in;
+    let totals = form.totals.get(&shift.id).cloned().unwrap_or_default();
+
+    row![
+        text(format!("{:.1}", worked.num_minutes() as f32 / 60.0))
+            .align_x(Alignment::End)
+            .width(60.0),
+        text(totals.count).align_x(Alignment::End).width(60.0),
+        text(money(totals.total))
+            .align_x(Alignment::End)
+            .width(100.0),
+        text(money(totals.tips))
+            .align_x(Alignment::End)
+            .width(100.0),
+    ]
+    .spacing(10)
+}
+
+fn open<'a>(shifts: &'a Shifts, form: &'a Form) -> Element<'a, Message> {
+    if shifts.open().next().is_none() {
+        return text("No one is clocked in, so sales aren't tied to a shift")
+            .size(12)
+            .into();
+    }
+
+    shifts
+        .open()
+        .fold(column![columns("Clocked in")].spacing(5), |col, shift| {
+            let till: Element<_> = if shifts.on_till() == Some(shift.id) {
+                text("On the till").style(text::success).width(100.0).into()
+            } else {
+                button("Take till")
+                    .on_press(Message::TakeTill(shift.id))
+                    .style(button::secondary)
+                    .width(100.0)
+                    .into()
+            };
+            col.push(
+                row![
+                    text(format!(
+                        "{} since {}",
+                        shift.employee,
+                        shift.clock_in.with_timezone(&Local).format("%H:%M")
+                    ))
+                    .width(Fill),
+                    figures(shift, form),
+                    till,
+                    button("Clock out")
+                        .on_press(Message::ClockOut(shift.id))
+                        .style(button::danger)
+                        .width(90.0),
+                ]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            )
+        })
+        .into()
+}
+
+fn history<'a>(shifts: &'a Shifts, form: &'a Form) -> Element<'a, Message> {
+    shifts
+        .history()
+        .take(HISTORY)
+        .fold(
+            column![text("Past shifts").size(16), columns("Shift")].spacing(5),
+            |col, shift| {
+                col.push(
+                    row![
+                        text(shift.label()).width(Fill),
+                        figures(shift, form),
+                        horizontal_space().width(200.0),
+                    ]
+                    .spacing(10),
+                )
+            },
+        )
+        .into()
+}
+
+pub fn handle_hotkey(hotkey: Hotkey) -> Action<Instruction, Message> {
+    match hotkey {
+        Hotkey::Escape => Action::navigate(Screen::List),
+        _ => Action::none(),
+    }
+}
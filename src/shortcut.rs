@@ -0,0 +1,490 @@
+//! The keyboard shortcuts, in one place
+//!
+//! Each [`Hotkey`] is bound to key chords by the [`Keymap`], which is kept
+//! in the config and can be changed in the settings. What each screen does
+//! with a hotkey is listed here too, for the cheat sheet of [`Hotkey::Help`].
+//! Screens act on a hotkey in their `handle_hotkey`: one it now acts on
+//! belongs in [`of`] as well.
+use iced::event;
+use iced::keyboard::key::Named;
+use iced::keyboard::{Key, Modifiers};
+use iced::widget::{button, column, container, horizontal_space, row, text};
+use iced::{Alignment, Element, Fill, Font};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::i18n::tr;
+use crate::sale;
+use crate::Screen;
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum Hotkey {
+    Escape,
+    NextField,
+    PreviousField,
+    Diagnostics,
+    SaveAndClose,
+    /// Save and move on.
+    Submit,
+    /// Show the shortcuts of the screen.
+    Help,
+}
+
+impl Hotkey {
+    pub const ALL: [Hotkey; 7] = [
+        Hotkey::Escape,
+        Hotkey::NextField,
+        Hotkey::PreviousField,
+        Hotkey::Diagnostics,
+        Hotkey::SaveAndClose,
+        Hotkey::Submit,
+        Hotkey::Help,
+    ];
+
+    /// The chords the hotkey is bound to out of the box.
+    fn default_chords(self) -> Vec<Chord> {
+        let chord = |key: &str, command, shift| Chord {
+            key: key.to_string(),
+            command,
+            alt: false,
+            shift,
+        };
+        match self {
+            Hotkey::Escape => vec![chord("Esc", false, false)],
+            Hotkey::NextField => vec![chord("Tab", false, false)],
+            Hotkey::PreviousField => vec![chord("Tab", false, true)],
+            Hotkey::Diagnostics => vec![chord("d", true, true)],
+            Hotkey::SaveAndClose => vec![chord("s", true, true)],
+            Hotkey::Submit => vec![chord("Enter", true, false)],
+            Hotkey::Help => {
+                vec![chord("F1", false, false), chord("?", false, false)]
+            }
+        }
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Hotkey::Escape => "Back, or close a dialog",
+                Hotkey::NextField => "Next field",
+                Hotkey::PreviousField => "Previous field",
+                Hotkey::Diagnostics => "Open diagnostics",
+                Hotkey::SaveAndClose => "Save and close",
+                Hotkey::Submit => "Save and move on",
+                Hotkey::Help => "Show the shortcuts",
+            }
+        )
+    }
+}
+
+/// The keys not typed as text, by the name they are written with.
+const NAMED: [(Named, &str); 24] = [
+    (Named::Escape, "Esc"),
+    (Named::Tab, "Tab"),
+    (Named::Enter, "Enter"),
+    (Named::Space, "Space"),
+    (Named::Backspace, "Backspace"),
+    (Named::Delete, "Delete"),
+    (Named::Insert, "Insert"),
+    (Named::Home, "Home"),
+    (Named::End, "End"),
+    (Named::PageUp, "PageUp"),
+    (Named::PageDown, "PageDown"),
+    (Named::ArrowUp, "Up"),
+    (Named::ArrowDown, "Down"),
+    (Named::ArrowLeft, "Left"),
+    (Named::ArrowRight, "Right"),
+    (Named::F1, "F1"),
+    (Named::F2, "F2"),
+    (Named::F3, "F3"),
+    (Named::F4, "F4"),
+    (Named::F5, "F5"),
+    (Named::F6, "F6"),
+    (Named::F7, "F7"),
+    (Named::F8, "F8"),
+    (Named::F9, "F9"),
+];
+
+/// A key pressed with modifiers, e.g. Ctrl+Shift+S, written as such in the
+/// config.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Chord {
+    /// The name of a named key, or else the character, in lowercase.
+    key: String,
+    /// Ctrl, or Cmd on macOS.
+    command: bool,
+    alt: bool,
+    /// Only for named keys and letters: other characters are typed with
+    /// or without it already.
+    shift: bool,
+}
+
+impl Chord {
+    /// The chord of `key` pressed with `modifiers`, if it can be bound.
+    pub fn pressed(key: &Key, modifiers: Modifiers) -> Option<Self> {
+        let key = match key {
+            Key::Named(named) => NAMED
+                .iter()
+                .find(|(key, _)| key == named)
+                .map(|(_, name)| name.to_string())?,
+            Key::Character(c) => c.to_lowercase(),
+            Key::Unidentified => return None,
+        };
+        Some(Self::new(
+            key,
+            modifiers.command(),
+            modifiers.alt(),
+            modifiers.shift(),
+        ))
+    }
+
+    fn new(key: String, command: bool, alt: bool, shift: bool) -> Self {
+        let shifted =
+            key.chars().count() > 1 || key.chars().all(char::is_alphabetic);
+        Self {
+            key,
+            command,
+            alt,
+            shift: shift && shifted,
+        }
+    }
+
+    /// Whether the chord types text, so is left to a text input taking it.
+    fn types(&self) -> bool {
+        !self.command
+            && !self.alt
+            && (self.key.chars().count() == 1 || self.key == "Space")
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.command {
+            let command = if cfg!(target_os = "macos") {
+                "Cmd"
+            } else {
+                "Ctrl"
+            };
+            write!(f, "{command}+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.key.chars().count() == 1 {
+            write!(f, "{}", self.key.to_uppercase())
+        } else {
+            write!(f, "{}", self.key)
+        }
+    }
+}
+
+impl FromStr for Chord {
+    type Err = String;
+
+    fn from_str(chord: &str) -> Result<Self, Self::Err> {
+        let chord = chord.trim();
+        // The last part is the key, so that `Ctrl++` is Ctrl and plus
+        let (modifiers, key) = match chord.strip_suffix('+') {
+            Some(rest) if rest.is_empty() || rest.ends_with('+') => {
+                (rest.trim_end_matches('+'), "+")
+            }
+            _ => chord.rsplit_once('+').unwrap_or(("", chord)),
+        };
+
+        let (mut command, mut alt, mut shift) = (false, false, false);
+        for modifier in modifiers.split('+').filter(|m| !m.trim().is_empty()) {
+            match modifier.trim().to_lowercase().as_str() {
+                "ctrl" | "cmd" | "command" => command = true,
+                "alt" | "option" => alt = true,
+                "shift" => shift = true,
+                _ => return Err(format!("\"{modifier}\" is not a modifier")),
+            }
+        }
+
+        let key = key.trim();
+        let named = NAMED
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(key))
+            .map(|(_, name)| name.to_string());
+        let key = match named {
+            _ if key.chars().count() == 1 => key.to_lowercase(),
+            Some(name) => name,
+            None if key.eq_ignore_ascii_case("escape") => "Esc".to_string(),
+            None => return Err(format!("\"{key}\" is not a key")),
+        };
+        Ok(Self::new(key, command, alt, shift))
+    }
+}
+
+impl TryFrom<String> for Chord {
+    type Error = String;
+
+    fn try_from(chord: String) -> Result<Self, Self::Error> {
+        chord.parse()
+    }
+}
+
+impl From<Chord> for String {
+    fn from(chord: Chord) -> Self {
+        chord.to_string()
+    }
+}
+
+/// The chords each hotkey is bound to, where changed from the default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Keymap(BTreeMap<Hotkey, Vec<Chord>>);
+
+impl Keymap {
+    /// The chords `hotkey` is bound to.
+    pub fn chords(&self, hotkey: Hotkey) -> Vec<Chord> {
+        self.0
+            .get(&hotkey)
+            .cloned()
+            .unwrap_or_else(|| hotkey.default_chords())
+    }
+
+    /// The hotkey `chord` gives, if any.
+    pub fn hotkey(
+        &self,
+        chord: &Chord,
+        status: event::Status,
+    ) -> Option<Hotkey> {
+        if chord.types() && status == event::Status::Captured {
+            return None;
+        }
+        Hotkey::ALL
+            .into_iter()
+            .find(|hotkey| self.chords(*hotkey).contains(chord))
+    }
+
+    /// The chords of `hotkey` as written, e.g. "F1, ?".
+    pub fn keys(&self, hotkey: Hotkey) -> String {
+        self.chords(hotkey)
+            .iter()
+            .map(Chord::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The chords written in `input` for `hotkey`, unless one isn't a chord
+    /// or is bound to another hotkey already.
+    pub fn parse(
+        &self,
+        hotkey: Hotkey,
+        input: &str,
+    ) -> Result<Vec<Chord>, String> {
+        let chords = input
+            .split(", ")
+            .filter(|chord| !chord.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<Chord>, _>>()?;
+
+        for chord in &chords {
+            if let Some(other) = Hotkey::ALL.into_iter().find(|other| {
+                *other != hotkey && self.chords(*other).contains(chord)
+            }) {
+                return Err(format!("{chord} is already \"{other}\""));
+            }
+        }
+        Ok(chords)
+    }
+
+    /// Bind `hotkey` to `chords`, the default ones not being kept.
+    pub fn bind(&mut self, hotkey: Hotkey, chords: Vec<Chord>) {
+        if chords == hotkey.default_chords() {
+            self.0.remove(&hotkey);
+        } else {
+            self.0.insert(hotkey, chords);
+        }
+    }
+}
+
+/// A hotkey and what it does on a screen.
+#[derive(Debug)]
+pub struct Shortcut {
+    pub hotkey: Hotkey,
+    /// What pressing it does, e.g. "Save and close".
+    pub does: &'static str,
+}
+
+/// The shortcuts of `screen`, those of every screen last.
+pub fn of(screen: &Screen) -> Vec<Shortcut> {
+    let shortcut = |hotkey, does| Shortcut { hotkey, does };
+    let back = |does| vec![shortcut(Hotkey::Escape, does)];
+
+    let mut shortcuts = match screen {
+        Screen::List => {
+            vec![shortcut(Hotkey::Diagnostics, tr("Open diagnostics"))]
+        }
+        Screen::Sale(sale::Mode::View, _) => {
+            back(tr("Close a dialog, or go back"))
+        }
+        Screen::Sale(sale::Mode::Edit, _) => vec![
+            shortcut(Hotkey::NextField, tr("Next field")),
+            shortcut(Hotkey::PreviousField, tr("Previous field")),
+            shortcut(Hotkey::SaveAndClose, tr("Save and close")),
+        ],
+        Screen::QuickSale => vec![
+            shortcut(Hotkey::Submit, tr("Save the sale")),
+            shortcut(Hotkey::Escape, tr("Back to the sales")),
+        ],
+        Screen::Conflict => back(tr("Back to the sale")),
+        Screen::Layout | Screen::QuickButtons => back(tr("Back to settings")),
+        Screen::Customer(_) => back(tr("Back to the customers")),
+        Screen::Trash => back(tr("Cancel purging, or go back")),
+        Screen::GiftCards => back(tr("Close the keyboard, or go back")),
+        Screen::Settings
+        | Screen::Report
+        | Screen::Catalog
+        | Screen::Drawer
+        | Screen::Shifts
+        | Screen::Recurring
+        | Screen::Customers
+        | Screen::Return
+        | Screen::Diagnostics => back(tr("Back to the sales")),
+    };
+    shortcuts.push(shortcut(Hotkey::Help, tr("Show or hide these shortcuts")));
+    shortcuts
+}
+
+/// The cheat sheet of the shortcuts of `screen`, with the keys of `keymap`.
+pub fn view<'a, Message: Clone + 'a>(
+    screen: &Screen,
+    keymap: &Keymap,
+    on_close: Message,
+) -> Element<'a, Message> {
+    let shortcuts = of(screen)
+        .into_iter()
+        .filter(|shortcut| !keymap.chords(shortcut.hotkey).is_empty())
+        .fold(column![].spacing(10), |col, shortcut| {
+            col.push(
+                row![
+                    container(
+                        text(keymap.keys(shortcut.hotkey))
+                            .font(Font::MONOSPACE)
+                            .size(14)
+                    )
+                    .padding([2, 8])
+                    .style(container::rounded_box),
+                    horizontal_space(),
+                    text(shortcut.does),
+                ]
+                .spacing(20)
+                .align_y(Alignment::Center),
+            )
+        });
+
+    column![
+        text(tr("Keyboard shortcuts")).size(16),
+        shortcuts,
+        row![
+            horizontal_space(),
+            button(tr("Close"))
+                .on_press(on_close)
+                .style(button::secondary),
+        ],
+    ]
+    .spacing(20)
+    .width(Fill)
+    .max_width(420)
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(chord: &str) -> Chord {
+        chord.parse().unwrap()
+    }
+
+    #[test]
+    fn question_mark_is_left_to_text_inputs() {
+        let keymap = Keymap::default();
+        let question =
+            Chord::pressed(&Key::Character("?".into()), Modifiers::SHIFT)
+                .unwrap();
+        assert_eq!(
+            keymap.hotkey(&question, event::Status::Ignored),
+            Some(Hotkey::Help)
+        );
+        assert_eq!(keymap.hotkey(&question, event::Status::Captured), None);
+
+        let save = Chord::pressed(
+            &Key::Character("S".into()),
+            Modifiers::COMMAND | Modifiers::SHIFT,
+        )
+        .unwrap();
+        assert_eq!(
+            keymap.hotkey(&save, event::Status::Captured),
+            Some(Hotkey::SaveAndClose)
+        );
+    }
+
+    #[test]
+    fn chords_are_written_as_they_are_read() {
+        for written in ["Esc", "Shift+Tab", "Alt+F2", "?", "Ctrl++"] {
+            let chord = chord(written);
+            assert_eq!(chord.to_string().replace("Cmd", "Ctrl"), written);
+        }
+        assert_eq!(chord("ctrl+shift+s"), chord("Cmd+Shift+S"));
+        assert_eq!(chord("escape"), chord("Esc"));
+        assert!("Hyper+S".parse::<Chord>().is_err());
+        assert!("Ctrl+Nope".parse::<Chord>().is_err());
+    }
+
+    #[test]
+    fn a_chord_is_bound_to_one_hotkey() {
+        let mut keymap = Keymap::default();
+        let taken = chord("Ctrl+Shift+S");
+        assert_eq!(
+            keymap.parse(Hotkey::Submit, "Ctrl+Shift+S"),
+            Err(format!("{taken} is already \"Save and close\""))
+        );
+
+        let chords = keymap.parse(Hotkey::Submit, "F5, Ctrl+Enter").unwrap();
+        keymap.bind(Hotkey::Submit, chords);
+        assert_eq!(
+            keymap.hotkey(&chord("F5"), event::Status::Ignored),
+            Some(Hotkey::Submit)
+        );
+
+        // Back to the default, which isn't kept
+        keymap.bind(Hotkey::Submit, vec![chord("Ctrl+Enter")]);
+        assert_eq!(keymap, Keymap::default());
+    }
+
+    #[test]
+    fn every_screen_lists_the_help_shortcut() {
+        let edit = of(&Screen::Sale(sale::Mode::Edit, None));
+        assert!(edit
+            .iter()
+            .any(|shortcut| shortcut.hotkey == Hotkey::SaveAndClose));
+        assert_eq!(
+            of(&Screen::List).last().map(|shortcut| shortcut.hotkey),
+            Some(Hotkey::Help)
+        );
+    }
+}
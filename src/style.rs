@@ -0,0 +1,27 @@
+//! Widget styles shared across screens
+use iced::widget::text_input;
+use iced::{Background, Border, Theme};
+
+/// A text input whose focus is easy to spot, so keyboard users can tell
+/// where Tab has landed.
+pub fn input(theme: &Theme, status: text_input::Status) -> text_input::Style {
+    let style = text_input::default(theme, status);
+
+    match status {
+        text_input::Status::Focused => {
+            let palette = theme.extended_palette();
+            text_input::Style {
+                background: Background::Color(
+                    palette.primary.weak.color.scale_alpha(0.15),
+                ),
+                border: Border {
+                    width: 3.0,
+                    color: palette.primary.strong.color,
+                    ..style.border
+                },
+                ..style
+            }
+        }
+        _ => style,
+    }
+}
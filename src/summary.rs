@@ -0,0 +1,194 @@
+//! The summary of the day's sales, emailed at close of business
+//!
+//! Once it is past the closing time set in the settings, the summary of the
+//! day is sent to the configured address through the mail server of
+//! [`mail`], once a day. It can also be sent right away from the settings.
+//! Sending while offline is retried from the outbox.
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use iced::futures::channel::oneshot;
+use iced::futures::SinkExt;
+use iced::Subscription;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::i18n;
+use crate::mail::{self, Mail};
+use crate::outbox::Failure;
+use crate::query::{self, GroupBy, Range, Row};
+use crate::store;
+use crate::tag;
+
+/// How often the clock checks whether it is time to send.
+const TICK: std::time::Duration = std::time::Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Where the summary is sent, empty for nowhere.
+    pub to: String,
+    /// The close of business, as `HH:MM`.
+    pub at: String,
+    /// The last day the summary was sent for.
+    pub last_sent: Option<NaiveDate>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            to: String::new(),
+            at: "22:00".to_string(),
+            last_sent: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn is_enabled(&self) -> bool {
+        !self.to.is_empty()
+    }
+
+    /// The closing time, if it is valid.
+    pub fn closing(&self) -> Option<NaiveTime> {
+        NaiveTime::parse_from_str(self.at.trim(), "%H:%M").ok()
+    }
+
+    /// The day whose summary is due to be sent at `now`, if any.
+    pub fn due(&self, now: NaiveDateTime) -> Option<NaiveDate> {
+        let closing = self.closing()?;
+        let today = now.date();
+        (self.is_enabled()
+            && now.time() >= closing
+            && self.last_sent != Some(today))
+        .then_some(today)
+    }
+}
+
+/// Email the summary of `day` from the store in `format` to `to`.
+pub async fn send(
+    smtp: mail::Smtp,
+    to: String,
+    format: store::Format,
+    day: NaiveDate,
+) -> Result<(), Failure> {
+    let body = text(format, day).map_err(|e| Failure::Other(e.to_string()))?;
+    let mail = Mail {
+        to,
+        subject: format!("Sales summary for {day}"),
+        body,
+    };
+    mail::send(smtp, mail).await
+}
+
+/// The summary of the sales of `day`, by order type and tax group.
+pub fn text(
+    format: store::Format,
+    day: NaiveDate,
+) -> Result<String, crate::data::Error> {
+    let range = Range {
+        from: Some(day),
+        to: Some(day),
+    };
+    let aggregate = |group_by| {
+        query::aggregate(format, range, group_by, tag::Filter::default())
+    };
+    let order_types = aggregate(GroupBy::OrderType)?;
+    let tax_groups = aggregate(GroupBy::TaxGroup)?;
+
+    let total = order_types.iter().fold(Row::default(), |mut total, row| {
+        total.add(row);
+        total
+    });
+
+    let mut text =
+        format!("Sales summary for {}\n\n", day.format("%A %Y-%m-%d"));
+    let _ = writeln!(text, "Sales:     {}", total.count);
+    let _ = writeln!(text, "Subtotal:  {}", i18n::money(total.subtotal));
+    let _ = writeln!(text, "Tax:       {}", i18n::money(total.tax));
+    let _ = writeln!(text, "Total:     {}", i18n::money(total.total));
+
+    for (title, rows) in [
+        ("By order type", &order_types),
+        ("By tax group", &tax_groups),
+    ] {
+        let _ = writeln!(text, "\n{title}");
+        if rows.is_empty() {
+            let _ = writeln!(text, "  No sales");
+        }
+        for row in rows {
+            let _ = writeln!(
+                text,
+                "  {:<16} {:>5}  {:>12}",
+                row.key,
+                row.count,
+                i18n::money(row.total)
+            );
+        }
+    }
+
+    Ok(text)
+}
+
+/// Tick every minute, to check whether the summary is due.
+pub fn clock() -> Subscription<()> {
+    Subscription::run_with_id(
+        "summary",
+        iced::stream::channel(1, |mut output| async move {
+            loop {
+                let (tick, ticked) = oneshot::channel();
+                std::thread::spawn(move || {
+                    std::thread::sleep(TICK);
+                    let _ = tick.send(());
+                });
+                let _ = ticked.await;
+                if output.send(()).await.is_err() {
+                    return;
+                }
+            }
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    fn at(on: u32, time: &str) -> NaiveDateTime {
+        day(on).and_time(NaiveTime::parse_from_str(time, "%H:%M").unwrap())
+    }
+
+    #[test]
+    fn due_once_a_day_after_closing() {
+        let mut config = Config {
+            to: "owner@example.com".to_string(),
+            at: "21:30".to_string(),
+            last_sent: None,
+        };
+
+        assert_eq!(config.due(at(15, "21:29")), None);
+        assert_eq!(config.due(at(15, "21:30")), Some(day(15)));
+
+        config.last_sent = Some(day(15));
+        assert_eq!(config.due(at(15, "23:59")), None);
+        assert_eq!(config.due(at(16, "21:45")), Some(day(16)));
+    }
+
+    #[test]
+    fn never_due_without_an_address_or_a_valid_time() {
+        let config = Config {
+            to: String::new(),
+            ..Config::default()
+        };
+        assert_eq!(config.due(at(15, "23:00")), None);
+
+        let config = Config {
+            to: "owner@example.com".to_string(),
+            at: "late".to_string(),
+            last_sent: None,
+        };
+        assert_eq!(config.due(at(15, "23:00")), None);
+    }
+}
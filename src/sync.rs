@@ -0,0 +1,270 @@
+//! Optional sync of the sales store with a WebDAV or S3-compatible endpoint
+//!
+//! Syncing is a single round trip: the remote copy of the store is fetched
+//! and whichever of the local and remote copies was modified last wins. A
+//! newer remote copy replaces the local sales, otherwise the local copy is
+//! uploaded. Neither is written if it holds an older
+//! [`revision`](crate::sale::Sale::revision) of a sale than the copy it
+//! replaces, or the same one changed apart: the sync stops at that sale
+//! instead, for it to be kept from one side or merged.
+use chrono::{DateTime, Local, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+use crate::data::{self, Export};
+use crate::outbox::Failure;
+use crate::sale::conflict::{self, Conflict};
+
+/// The object key the store is kept under on S3-compatible endpoints.
+const S3_KEY: &str = "iced_receipts/sales.json";
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum Backend {
+    #[default]
+    Off,
+    WebDav,
+    S3,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 3] = [Backend::Off, Backend::WebDav, Backend::S3];
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Backend::Off => "Off",
+                Backend::WebDav => "WebDAV",
+                Backend::S3 => "S3-compatible",
+            }
+        )
+    }
+}
+
+/// Where and how to sync.
+///
+/// For WebDAV, `url` is the full URL of the file to sync to and `username`
+/// and `password` are used for basic authentication. For S3, `url` is the
+/// endpoint (e.g. `https://s3.eu-west-1.amazonaws.com`) and `username` and
+/// `password` are the access key id and secret access key. Credentials are
+/// stored in plain text in the config file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Remote {
+    pub backend: Backend,
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    pub bucket: String,
+    pub region: String,
+}
+
+impl Remote {
+    pub fn is_enabled(&self) -> bool {
+        self.backend != Backend::Off && !self.url.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    /// The remote copy was newer and should replace the local sales.
+    Pulled(Export),
+    /// The local copy was newer and has been uploaded.
+    Pushed,
+    UpToDate,
+    /// Either copy would undo changes to these sales made on the other, so
+    /// nothing was written.
+    Diverged(Vec<Conflict>),
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum Status {
+    #[default]
+    Off,
+    Syncing,
+    Synced(DateTime<Local>),
+    Failed(String),
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Off => write!(f, "Sync off"),
+            Status::Syncing => write!(f, "Syncing…"),
+            Status::Synced(at) => write!(f, "Synced {}", at.format("%H:%M")),
+            Status::Failed(_) => write!(f, "Sync failed"),
+        }
+    }
+}
+
+/// Sync `local` with the remote, keeping whichever copy was modified last.
+pub async fn run(
+    remote: Remote,
+    mut local: Export,
+) -> Result<Outcome, Failure> {
+    tracing::info!(backend = ?remote.backend, "syncing");
+    if let Some(mut theirs) = get(&remote)? {
+        if theirs.modified_at == local.modified_at {
+            return Ok(Outcome::UpToDate);
+        }
+        let pull = theirs.modified_at > local.modified_at;
+        let stale = if pull {
+            conflict::overwritten(&theirs.sales, &local.sales)
+        } else {
+            conflict::overwritten(&local.sales, &theirs.sales)
+        };
+        if !stale.is_empty() {
+            tracing::warn!(?stale, "sales changed on both sides");
+            return Ok(Outcome::Diverged(
+                stale
+                    .into_iter()
+                    .filter_map(|id| {
+                        Some(Conflict::between(
+                            id,
+                            local.sales.remove(&id)?,
+                            theirs.sales.remove(&id)?,
+                        ))
+                    })
+                    .collect(),
+            ));
+        }
+        if pull {
+            tracing::info!(sales = theirs.sales.len(), "pulled newer sales");
+            return Ok(Outcome::Pulled(theirs));
+        }
+    }
+
+    let json =
+        serde_json::to_string_pretty(&local).map_err(|e| e.to_string())?;
+    put(&remote, json)?;
+    tracing::info!(sales = local.sales.len(), "pushed the sales");
+    Ok(Outcome::Pushed)
+}
+
+/// Fetch the remote copy of the store, if there is one.
+fn get(remote: &Remote) -> Result<Option<Export>, Failure> {
+    let response = match request(remote, "GET", b"")?.call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(404, _)) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let json = response
+        .into_string()
+        .map_err(|e| Failure::Offline(e.to_string()))?;
+    data::parse(&json)
+        .map(Some)
+        .map_err(|e| Failure::Other(e.to_string()))
+}
+
+fn put(remote: &Remote, json: String) -> Result<(), Failure> {
+    request(remote, "PUT", json.as_bytes())?
+        .set("Content-Type", "application/json")
+        .send_bytes(json.as_bytes())
+        .map(|_| ())
+        .map_err(Failure::from)
+}
+
+fn request(
+    remote: &Remote,
+    method: &str,
+    body: &[u8],
+) -> Result<ureq::Request, String> {
+    match remote.backend {
+        Backend::Off => Err("Sync is turned off".to_string()),
+        Backend::WebDav => {
+            let request = ureq::request(method, &remote.url);
+            Ok(if remote.username.is_empty() {
+                request
+            } else {
+                request.set(
+                    "Authorization",
+                    &basic_auth(&remote.username, &remote.password),
+                )
+            })
+        }
+        Backend::S3 => s3_request(remote, method, body),
+    }
+}
+
+fn basic_auth(username: &str, password: &str) -> String {
+    use base64::Engine;
+
+    let credentials = format!("{username}:{password}");
+    format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode(credentials)
+    )
+}
+
+/// Build a path-style S3 request signed with AWS Signature Version 4.
+fn s3_request(
+    remote: &Remote,
+    method: &str,
+    body: &[u8],
+) -> Result<ureq::Request, String> {
+    let endpoint = url::Url::parse(&remote.url).map_err(|e| e.to_string())?;
+    let host = match (endpoint.host_str(), endpoint.port()) {
+        (Some(host), Some(port)) => format!("{host}:{port}"),
+        (Some(host), None) => host.to_string(),
+        (None, _) => return Err("The sync URL has no host".to_string()),
+    };
+    let path = format!("/{}/{S3_KEY}", remote.bucket);
+    let region = if remote.region.is_empty() {
+        "us-east-1"
+    } else {
+        &remote.region
+    };
+
+    let now = Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let payload_hash = hex(&Sha256::digest(body));
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+
+    let canonical_request = format!(
+        "{method}\n{path}\n\nhost:{host}\nx-amz-content-sha256:{payload_hash}\n\
+         x-amz-date:{amz_date}\n\n{signed_headers}\n{payload_hash}"
+    );
+    let scope = format!("{date}/{region}/s3/aws4_request");
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = [date.as_str(), region, "s3", "aws4_request"].iter().fold(
+        format!("AWS4{}", remote.password).into_bytes(),
+        |key, part| hmac(&key, part.as_bytes()),
+    );
+    let signature = hex(&hmac(&key, string_to_sign.as_bytes()));
+
+    let url = format!("{}{}", remote.url.trim_end_matches('/'), path);
+    Ok(ureq::request(method, &url)
+        .set("x-amz-date", &amz_date)
+        .set("x-amz-content-sha256", &payload_hash)
+        .set(
+            "Authorization",
+            &format!(
+                "AWS4-HMAC-SHA256 Credential={}/{scope}, \
+                 SignedHeaders={signed_headers}, Signature={signature}",
+                remote.username
+            ),
+        ))
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -0,0 +1,99 @@
+//! Tags and color labels on sales, as chips and swatches
+use iced::widget::{button, container, row, text};
+use iced::{border, Background, Border, Color, Element, Theme};
+
+pub use receipts_core::tag::*;
+
+/// The color of `label` on screen.
+pub fn color(label: Label) -> Color {
+    let [r, g, b] = label.rgb();
+    Color::from_rgb8(r, g, b)
+}
+
+/// A tag shown as a rounded chip.
+pub fn chip<'a, Message: 'a>(tag: &str) -> Element<'a, Message> {
+    container(text(tag.to_string()).size(12))
+        .padding([1, 8])
+        .style(|theme: &Theme| {
+            let palette = theme.extended_palette();
+            container::Style {
+                background: Some(palette.background.strong.color.into()),
+                border: border::rounded(10),
+                ..container::Style::default()
+            }
+        })
+        .into()
+}
+
+/// A dot in the color of `label`.
+pub fn dot<'a, Message: 'a>(label: Label) -> Element<'a, Message> {
+    container(row![])
+        .width(12)
+        .height(12)
+        .style(move |_: &Theme| container::Style {
+            background: Some(color(label).into()),
+            border: border::rounded(6),
+            ..container::Style::default()
+        })
+        .into()
+}
+
+/// The label and tags of a sale as a row of chips.
+pub fn chips<'a, Message: 'a>(
+    tags: &[String],
+    label: Option<Label>,
+) -> Element<'a, Message> {
+    let chips = row![].spacing(5).align_y(iced::Alignment::Center);
+    let chips = match label {
+        Some(label) => chips.push(dot(label)),
+        None => chips,
+    };
+    tags.iter()
+        .fold(chips, |chips, tag| chips.push(chip(tag)))
+        .into()
+}
+
+/// A choice of label, or of none, as color swatches.
+pub fn swatches<'a, Message: Clone + 'a>(
+    selected: Option<Label>,
+    on_select: impl Fn(Option<Label>) -> Message,
+) -> Element<'a, Message> {
+    let swatch = |label: Option<Label>| {
+        let is_selected = label == selected;
+        button(
+            text(if label.is_none() { "∅" } else { "" })
+                .size(10)
+                .center(),
+        )
+        .width(20)
+        .height(20)
+        .padding(0)
+        .on_press(on_select(label))
+        .style(move |theme: &Theme, _| {
+            let palette = theme.extended_palette();
+            button::Style {
+                background: Some(Background::Color(
+                    label.map_or(palette.background.weak.color, color),
+                )),
+                text_color: palette.background.base.text,
+                border: Border {
+                    width: if is_selected { 3.0 } else { 1.0 },
+                    color: if is_selected {
+                        palette.background.base.text
+                    } else {
+                        palette.background.strong.color
+                    },
+                    radius: 10.0.into(),
+                },
+                ..button::Style::default()
+            }
+        })
+    };
+
+    Label::ALL
+        .iter()
+        .fold(row![swatch(None)].spacing(5), |row, &label| {
+            row.push(swatch(Some(label)))
+        })
+        .into()
+}
@@ -0,0 +1,116 @@
+//! Reusable sale templates, e.g. "Lunch Special Table"
+//!
+//! Templates are kept in their own file rather than in the store, so they
+//! never show up as sales in lists, reports or exports.
+use chrono::{Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::PathBuf;
+
+use crate::config::data_dir;
+use crate::data::{self, Error};
+use crate::sale::{OrderStatus, Sale};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Template {
+    pub id: usize,
+    pub name: String,
+    pub sale: Sale,
+}
+
+impl Template {
+    /// A new draft sale from the template, invoiced today if it is an
+    /// invoice.
+    pub fn draft(&self) -> Sale {
+        Sale {
+            created_at: Utc::now(),
+            invoice: self
+                .sale
+                .invoice
+                .as_ref()
+                .map(|invoice| invoice.reissued(Local::now().date_naive())),
+            ..self.sale.clone().with_fresh_ids()
+        }
+    }
+
+    pub fn display_name(&self) -> &str {
+        if self.name.is_empty() {
+            "Untitled template"
+        } else {
+            &self.name
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Templates {
+    templates: Vec<Template>,
+}
+
+impl Templates {
+    fn path() -> PathBuf {
+        data_dir().join("templates.json")
+    }
+
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save in the background, see [`data::save_json`].
+    pub fn save(
+        &self,
+    ) -> impl Future<Output = Result<(), Error>> + Send + 'static {
+        data::save_json(Self::path(), self)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Template> {
+        self.templates.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Template> {
+        self.templates.iter().find(|template| template.id == id)
+    }
+
+    /// Add a template of `sale`, leaving out what only applies to the sale
+    /// itself: its pickup status, pager, gift cards sold, tenders, cash,
+    /// shift and due time.
+    pub fn add(&mut self, name: String, sale: &Sale) -> &Template {
+        let id = self.templates.iter().map(|t| t.id + 1).max().unwrap_or(0);
+        let mut sale = Sale {
+            order_status: OrderStatus::default(),
+            pager: None,
+            tenders: Vec::new(),
+            cash: None,
+            foreign: None,
+            link: None,
+            shift: None,
+            due_at: None,
+            invoice: sale
+                .invoice
+                .as_ref()
+                .map(|invoice| invoice.reissued(invoice.issued)),
+            ..sale.clone()
+        };
+        sale.items.retain(|item| item.gift_card.is_none());
+
+        self.templates.push(Template { id, name, sale });
+        self.templates.last().unwrap()
+    }
+
+    pub fn rename(&mut self, id: usize, name: String) {
+        if let Some(template) = self.templates.iter_mut().find(|t| t.id == id) {
+            template.name = name;
+        }
+    }
+
+    pub fn remove(&mut self, id: usize) {
+        self.templates.retain(|template| template.id != id);
+    }
+}
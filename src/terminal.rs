@@ -0,0 +1,276 @@
+//! Optional card payments through a Stripe Terminal or Square Terminal reader
+//!
+//! Taking a card payment is three steps against the provider's API: the
+//! amount due is sent to the reader, which is then polled until the guest has
+//! paid or the payment failed, and a [`Card`] payment with the brand and last
+//! four digits is recorded on the sale. Waiting can be cancelled, which also
+//! clears the reader. The API key is stored in plain text in the config file.
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::outbox::Failure;
+
+pub use receipts_core::terminal::*;
+
+/// How long to wait between asking whether the guest has paid.
+const POLL_EVERY: Duration = Duration::from_secs(2);
+
+const STRIPE_API: &str = "https://api.stripe.com/v1";
+const SQUARE_API: &str = "https://connect.squareup.com/v2";
+const SQUARE_SANDBOX_API: &str = "https://connect.squareupsandbox.com/v2";
+const SQUARE_VERSION: &str = "2024-01-18";
+
+#[derive(Debug, Clone)]
+pub enum Poll {
+    Waiting,
+    Paid(Card),
+    /// The card was declined or the payment was cancelled on the reader.
+    Failed(String),
+}
+
+/// Send `amount` to the reader.
+pub async fn start(config: Config, amount: f32) -> Result<Pending, String> {
+    let cents = ((amount * 100.0).round() as i64).to_string();
+    let currency = config.currency.to_lowercase();
+    tracing::info!(provider = ?config.provider, cents, "sending a payment to the reader");
+
+    let id = match config.provider {
+        Provider::Off => return Err("No card terminal is set up".to_string()),
+        Provider::Stripe => {
+            let intent = stripe(&config, "POST", "/payment_intents")
+                .send_form(&[
+                    ("amount", &cents),
+                    ("currency", &currency),
+                    ("payment_method_types[]", "card_present"),
+                    ("capture_method", "automatic"),
+                ])
+                .map_err(error)
+                .and_then(json)?;
+            let id = string(&intent["id"])?;
+            stripe(
+                &config,
+                "POST",
+                &format!(
+                    "/terminal/readers/{}/process_payment_intent",
+                    config.reader
+                ),
+            )
+            .send_form(&[("payment_intent", &id)])
+            .map_err(error)?;
+            id
+        }
+        Provider::Square => {
+            let body = serde_json::json!({
+                "idempotency_key": idempotency_key(),
+                "checkout": {
+                    "amount_money": {
+                        "amount": cents.parse::<i64>().unwrap_or(0),
+                        "currency": config.currency.to_uppercase(),
+                    },
+                    "device_options": { "device_id": config.reader },
+                },
+            });
+            let checkout = square(&config, "POST", "/terminals/checkouts")
+                .set("Content-Type", "application/json")
+                .send_string(&body.to_string())
+                .map_err(error)
+                .and_then(json)?;
+            string(&checkout["checkout"]["id"])?
+        }
+    };
+
+    tracing::info!(id, "waiting for the guest to pay");
+    Ok(Pending {
+        provider: config.provider,
+        id,
+        amount,
+    })
+}
+
+/// Wait a moment, then ask whether `pending` has been paid.
+pub async fn poll(config: Config, pending: Pending) -> Result<Poll, String> {
+    std::thread::sleep(POLL_EVERY);
+
+    match pending.provider {
+        Provider::Off => Err("No card terminal is set up".to_string()),
+        Provider::Stripe => {
+            let intent = stripe(
+                &config,
+                "GET",
+                &format!("/payment_intents/{}", pending.id),
+            )
+            .query("expand[]", "latest_charge")
+            .call()
+            .map_err(error)
+            .and_then(json)?;
+
+            match intent["status"].as_str() {
+                Some("succeeded") => {
+                    let card = &intent["latest_charge"]
+                        ["payment_method_details"]["card_present"];
+                    Ok(Poll::Paid(Card {
+                        provider: Provider::Stripe,
+                        amount: pending.amount,
+                        brand: brand(card["brand"].as_str()),
+                        last4: card["last4"].as_str().unwrap_or("").to_string(),
+                        reference: pending.id,
+                    }))
+                }
+                Some("canceled") => {
+                    Ok(Poll::Failed("The payment was cancelled".to_string()))
+                }
+                _ => match intent["last_payment_error"]["message"].as_str() {
+                    Some(message) => Ok(Poll::Failed(message.to_string())),
+                    None => Ok(Poll::Waiting),
+                },
+            }
+        }
+        Provider::Square => {
+            let checkout = square(
+                &config,
+                "GET",
+                &format!("/terminals/checkouts/{}", pending.id),
+            )
+            .call()
+            .map_err(error)
+            .and_then(json)?;
+            let checkout = &checkout["checkout"];
+
+            match checkout["status"].as_str() {
+                Some("COMPLETED") => {
+                    let payment_id = string(&checkout["payment_ids"][0])?;
+                    let payment = square(
+                        &config,
+                        "GET",
+                        &format!("/payments/{payment_id}"),
+                    )
+                    .call()
+                    .map_err(error)
+                    .and_then(json)?;
+                    let card = &payment["payment"]["card_details"]["card"];
+                    Ok(Poll::Paid(Card {
+                        provider: Provider::Square,
+                        amount: pending.amount,
+                        brand: brand(card["card_brand"].as_str()),
+                        last4: card["last_4"]
+                            .as_str()
+                            .unwrap_or("")
+                            .to_string(),
+                        reference: payment_id,
+                    }))
+                }
+                Some("CANCELED") => Ok(Poll::Failed(
+                    match checkout["cancel_reason"].as_str() {
+                        Some("TIMED_OUT") => "The reader timed out",
+                        _ => "The payment was cancelled",
+                    }
+                    .to_string(),
+                )),
+                _ => Ok(Poll::Waiting),
+            }
+        }
+    }
+}
+
+/// Stop waiting for the payment `id` on the reader of `provider`, clearing
+/// the reader.
+pub async fn cancel(
+    config: Config,
+    provider: Provider,
+    id: String,
+) -> Result<(), Failure> {
+    tracing::info!(?provider, id, "cancelling a payment on the reader");
+    match provider {
+        Provider::Off => Ok(()),
+        Provider::Stripe => {
+            stripe(
+                &config,
+                "POST",
+                &format!("/terminal/readers/{}/cancel_action", config.reader),
+            )
+            .call()
+            .map_err(failure)?;
+            stripe(&config, "POST", &format!("/payment_intents/{id}/cancel"))
+                .call()
+                .map(|_| ())
+                .map_err(failure)
+        }
+        Provider::Square => square(
+            &config,
+            "POST",
+            &format!("/terminals/checkouts/{id}/cancel"),
+        )
+        .call()
+        .map(|_| ())
+        .map_err(failure),
+    }
+}
+
+fn stripe(config: &Config, method: &str, path: &str) -> ureq::Request {
+    ureq::request(method, &format!("{STRIPE_API}{path}"))
+        .set("Authorization", &format!("Bearer {}", config.api_key))
+}
+
+fn square(config: &Config, method: &str, path: &str) -> ureq::Request {
+    let api = if config.sandbox {
+        SQUARE_SANDBOX_API
+    } else {
+        SQUARE_API
+    };
+    ureq::request(method, &format!("{api}{path}"))
+        .set("Authorization", &format!("Bearer {}", config.api_key))
+        .set("Square-Version", SQUARE_VERSION)
+}
+
+fn json(response: ureq::Response) -> Result<Value, String> {
+    let body = response.into_string().map_err(|e| e.to_string())?;
+    serde_json::from_str(&body).map_err(|e| e.to_string())
+}
+
+fn string(value: &Value) -> Result<String, String> {
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "Unexpected response from the terminal API".to_string())
+}
+
+/// The error message of the provider, if it sent one.
+fn error(e: ureq::Error) -> String {
+    match e {
+        ureq::Error::Status(status, response) => {
+            let body = response.into_string().unwrap_or_default();
+            let body: Value = serde_json::from_str(&body).unwrap_or_default();
+            body["error"]["message"]
+                .as_str()
+                .or(body["errors"][0]["detail"].as_str())
+                .map_or_else(|| format!("HTTP {status}"), str::to_string)
+        }
+        e => e.to_string(),
+    }
+}
+
+/// Like [`error`], telling apart a reader which can't be reached.
+fn failure(e: ureq::Error) -> Failure {
+    match e {
+        ureq::Error::Transport(_) => Failure::from(e),
+        e => Failure::Other(error(e)),
+    }
+}
+
+/// A card brand as printed, e.g. "visa" or "VISA" as "Visa".
+fn brand(brand: Option<&str>) -> String {
+    let brand = brand.unwrap_or("Card").replace('_', " ").to_lowercase();
+    let mut chars = brand.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// A unique key so that a retried request can't charge twice.
+fn idempotency_key() -> String {
+    use rand::Rng;
+
+    let bytes: [u8; 16] = rand::thread_rng().gen();
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
@@ -0,0 +1,736 @@
+//! Plain-text kitchen tickets and customer receipts
+//!
+//! Receipts are laid out as [`Line`]s first, which print as text and are
+//! drawn on screen by [`receipt`](crate::receipt), so both look the same.
+use crate::config::Config;
+use crate::custom;
+use crate::i18n::{self, money, tr};
+use crate::layout::{self, Column, Layout, Notes};
+use crate::qr;
+use crate::sale::{OrderType, Sale};
+use crate::schedule;
+use crate::vat;
+
+/// The width of a ticket in characters, matching common 58mm thermal
+/// printers.
+pub const WIDTH: usize = 32;
+
+/// A line of a receipt, as laid out before it is printed or drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Line {
+    Text(String),
+    Center(String),
+    /// A left and a right side, pushed apart to the edges.
+    Split(String, String),
+    /// A rule across the whole width, e.g. dashed.
+    Rule(char),
+}
+
+impl Line {
+    /// The line as printed.
+    pub fn text(&self) -> String {
+        match self {
+            Line::Text(text) => text.clone(),
+            Line::Center(text) => center(text),
+            Line::Split(left, right) => line(left, right),
+            Line::Rule(c) => c.to_string().repeat(WIDTH),
+        }
+    }
+}
+
+/// `lines` as printed.
+pub fn join(lines: &[Line]) -> String {
+    lines.iter().map(Line::text).collect::<Vec<_>>().join("\n")
+}
+
+/// Render the kitchen ticket for a sale, listing its items in course order.
+pub fn kitchen(id: Option<usize>, sale: &Sale) -> String {
+    let mut lines = vec![center("KITCHEN TICKET"), "=".repeat(WIDTH)];
+
+    let order = id.map_or("New order".to_string(), |id| format!("Order #{id}"));
+    lines.push(format!("{order} - {}", sale.order_type));
+    if !sale.name.is_empty() {
+        lines.push(sale.name.clone());
+    }
+    if sale.order_type == OrderType::Takeout {
+        if let Some(pager) = sale.pager {
+            lines.push(format!("PAGER {pager}"));
+        }
+    }
+    if let Some(due) = sale.due_at {
+        lines.push(format!("DUE {}", schedule::label(due)));
+    }
+    if let Some(delivery) = sale.delivery() {
+        lines.push("DELIVER TO".to_string());
+        lines.extend(wrap(delivery.address.trim(), WIDTH));
+        if !delivery.phone.trim().is_empty() {
+            lines.push(format!("TEL {}", delivery.phone.trim()));
+        }
+    }
+    lines.push("-".repeat(WIDTH));
+
+    let note = sale.allergy_note.trim();
+    if !note.is_empty() {
+        lines.push(center("!!! ALLERGY !!!"));
+        lines.extend(wrap(note, WIDTH));
+        lines.push("-".repeat(WIDTH));
+    }
+
+    for (section, items) in sale.courses() {
+        if items.is_empty() {
+            continue;
+        }
+        if let Some(section) = section {
+            lines.push(format!("-- {} --", section.display_name()));
+        }
+        for item in items {
+            let void = if item.is_void() { " VOID" } else { "" };
+            lines.push(format!(
+                "{:>3} x {}{void}",
+                item.quantity_label(),
+                item.name
+            ));
+            if !item.allergens.is_empty() {
+                let names: Vec<_> =
+                    item.allergens.iter().map(|a| a.ticket_name()).collect();
+                lines.extend(
+                    wrap(&format!("(!) {}", names.join(", ")), WIDTH - 6)
+                        .into_iter()
+                        .map(|line| format!("      {line}")),
+                );
+            }
+        }
+    }
+    lines.push("=".repeat(WIDTH));
+
+    lines.join("\n")
+}
+
+/// Render the customer receipt for a sale in the built-in layout, with
+/// `fine_print` and `qr` printed at the bottom.
+pub fn receipt(
+    id: Option<usize>,
+    sale: &Sale,
+    fields: &[custom::Field],
+    fine_print: &str,
+    qr: Option<&str>,
+) -> String {
+    let notes = Notes {
+        fine_print,
+        customer_tax_id: &sale.customer_tax_id,
+        ..Notes::default()
+    };
+    Layout::default().render(id, sale, fields, &notes, qr, None)
+}
+
+/// A line per item of a sale, showing `columns`.
+pub fn items(sale: &Sale, columns: &[Column]) -> Vec<Line> {
+    sale.items
+        .iter()
+        .map(|item| {
+            let mut left = Vec::new();
+            let mut right = Vec::new();
+            for column in columns {
+                match column {
+                    Column::Quantity => {
+                        left.push(format!("{:>3} x", item.quantity_label()))
+                    }
+                    Column::Name => left.push(item.name.clone()),
+                    Column::Price => {
+                        right.push(format!("@{}", money(item.price())))
+                    }
+                    Column::Total => right.push(money(item.total())),
+                }
+            }
+            // Voided lines stay on the receipt, marked instead of priced
+            if item.is_void() {
+                right = vec![tr("VOID").to_string()];
+            }
+            Line::Split(left.join(" "), right.join(" "))
+        })
+        .collect()
+}
+
+/// The subtotal, charges, total and payments of a sale.
+pub fn totals(sale: &Sale) -> Vec<Line> {
+    let split = |left: &str, right: &str| {
+        Line::Split(left.to_string(), right.to_string())
+    };
+    let mut lines =
+        vec![split(tr("Subtotal"), &money(sale.calculate_subtotal()))];
+    if let Some(percent) = sale.service_charge_rate() {
+        lines.push(split(
+            &format!("{} {percent}%", tr("Service charge")),
+            &money(sale.calculate_service_charge()),
+        ));
+    }
+    lines.push(split(tr("Tax"), &money(sale.calculate_tax())));
+    if let Some(gratuity) = sale.gratuity_amount {
+        lines.push(split(tr("Gratuity"), &money(gratuity)));
+    }
+    if sale.delivery().is_some() {
+        lines.push(split(
+            tr("Delivery fee"),
+            &money(sale.calculate_delivery_fee()),
+        ));
+    }
+    lines.push(split(tr("TOTAL"), &money(sale.calculate_total())));
+    for tender in &sale.tenders {
+        lines.push(split(
+            &format!("{} {}", tr("Gift card"), tender.code),
+            &money(-tender.amount),
+        ));
+    }
+    if !sale.tenders.is_empty() {
+        lines.push(split(tr("DUE"), &money(sale.calculate_due())));
+    }
+    if let Some(per_guest) = sale.calculate_per_guest() {
+        lines.push(split(tr("Per guest"), &money(per_guest)));
+    }
+    if let Some(card) = &sale.card {
+        lines.push(split(
+            &format!("{} ****{}", card.brand, card.last4),
+            &money(card.amount),
+        ));
+    }
+    if let Some(payment) = &sale.foreign {
+        lines.push(split(
+            &format!(
+                "{} {} {}",
+                tr("Paid"),
+                i18n::number(payment.tendered, 2),
+                payment.currency
+            ),
+            &money(payment.amount),
+        ));
+        lines.push(Line::Text(format!(
+            "  {} 1 {} = {:.4} {}",
+            tr("at"),
+            payment.currency,
+            payment.rate,
+            payment.base
+        )));
+        lines.push(split(tr("Change"), &money(payment.change)));
+    }
+    if let Some(invoice) = &sale.invoice {
+        lines.push(split(
+            &format!("{} {}", tr("Invoice"), invoice.terms),
+            &format!("{} {}", tr("due"), i18n::day(invoice.due())),
+        ));
+        for payment in &invoice.payments {
+            lines.push(split(
+                &format!("{} {}", tr("Paid"), i18n::day(payment.date)),
+                &money(-payment.amount),
+            ));
+        }
+        lines.push(split(tr("BALANCE"), &money(sale.calculate_balance())));
+    }
+    lines
+}
+
+/// The custom fields of a sale printed on receipts, after a rule, or nothing
+/// if none of them are filled in.
+pub fn fields(sale: &Sale, fields: &[custom::Field]) -> Vec<Line> {
+    let printed: Vec<_> = fields
+        .iter()
+        .filter(|field| field.on_receipt)
+        .filter(|field| !sale.custom_field(field.id).is_empty())
+        .collect();
+    if printed.is_empty() {
+        return Vec::new();
+    }
+
+    let mut lines = vec![Line::Rule('-')];
+    for field in printed {
+        lines.push(Line::Text(format!(
+            "{}: {}",
+            field.display_name(),
+            sale.custom_field(field.id)
+        )));
+    }
+    lines
+}
+
+/// The receipt for a sale in the layout, and with the logo, fields, fine
+/// print and QR code, set up in `config`.
+pub fn configured_receipt(
+    id: Option<usize>,
+    sale: &Sale,
+    config: &Config,
+) -> String {
+    join(&configured_lines(id, sale, config))
+}
+
+/// The lines of [`configured_receipt`], to draw it on screen.
+pub fn configured_lines(
+    id: Option<usize>,
+    sale: &Sale,
+    config: &Config,
+) -> Vec<Line> {
+    let logo = config.logo.as_ref().map(|logo| logo.text.as_str());
+    configured(id, sale, config, logo)
+}
+
+/// Like [`configured_receipt`] but without the logo, for the print page,
+/// which shows it as an image instead.
+pub fn printed_receipt(
+    id: Option<usize>,
+    sale: &Sale,
+    config: &Config,
+) -> String {
+    join(&configured(id, sale, config, None))
+}
+
+fn configured(
+    id: Option<usize>,
+    sale: &Sale,
+    config: &Config,
+    logo: Option<&str>,
+) -> Vec<Line> {
+    let qr = id
+        .and_then(|id| qr::payload(&config.receipt_qr, id, sale))
+        .and_then(|payload| qr::text(&payload));
+    let registration = &config.tax_registration;
+    // Printed as checked, or as typed if it doesn't check
+    let tax_id = vat::check(registration.country, &registration.number)
+        .unwrap_or_else(|_| registration.number.clone());
+    let notes = Notes {
+        header: &config.receipt_header,
+        fine_print: config
+            .fine_print
+            .get(&sale.order_type)
+            .map_or("", String::as_str),
+        footer: &config.receipt_footer,
+        tax_id: &tax_id,
+        customer_tax_id: &sale.customer_tax_id,
+    };
+    let mut receipt = layout::configured(config).lines(
+        id,
+        sale,
+        &config.custom_fields,
+        &notes,
+        qr.as_deref(),
+        logo,
+    );
+
+    if let Some(fiscal) = &sale.fiscal {
+        receipt
+            .push(Line::Split("Fiscal no.".to_string(), fiscal.number.clone()));
+        // The signature has no spaces to wrap at
+        let signature: Vec<char> = fiscal.signature.chars().collect();
+        for chunk in signature.chunks(WIDTH) {
+            receipt.push(Line::Text(chunk.iter().collect()));
+        }
+    }
+
+    // A receipt handed out again says so, top and bottom
+    if sale.is_reprint() {
+        let banner = Line::Center("*** COPY ***".to_string());
+        let blank = Line::Text(String::new());
+        receipt.splice(0..0, [banner.clone(), blank.clone()]);
+        receipt.extend([blank, banner]);
+    }
+    receipt
+}
+
+/// `left` and `right` on one line, pushed apart to the edges of the ticket.
+pub fn line(left: &str, right: &str) -> String {
+    let width = WIDTH.saturating_sub(right.chars().count() + 1);
+    let left: String = left.chars().take(width).collect();
+    format!("{left:<width$} {right}")
+}
+
+/// `text` broken into lines at most `width` characters wide.
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for word in text.split_whitespace() {
+        let current = lines.last_mut().unwrap();
+        if !current.is_empty()
+            && current.chars().count() + word.chars().count() + 1 > width
+        {
+            lines.push(word.to_string());
+        } else {
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+    }
+    lines
+}
+
+pub fn center(text: &str) -> String {
+    format!("{text:^WIDTH$}").trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    use super::*;
+    use crate::allergen::Allergen;
+    use crate::currency;
+    use crate::delivery::Delivery;
+    use crate::fiscal::Signature;
+    use crate::invoice::{Invoice, Payment};
+    use crate::sale::{
+        Issue, OrderType, SaleItem, Section, Tender, Via, Weighing,
+    };
+    use crate::tax::TaxGroup;
+    use crate::terminal::{Card, Provider};
+
+    /// A sale made at a fixed time, so that receipts render the same on
+    /// every run.
+    fn sale(items: Vec<SaleItem>) -> Sale {
+        Sale {
+            items,
+            created_at: Utc.with_ymd_and_hms(2024, 3, 15, 18, 42, 0).unwrap(),
+            ..Sale::default()
+        }
+    }
+
+    #[test]
+    fn receipt_of_the_sample_sale() {
+        let sale = Sale {
+            created_at: Utc.with_ymd_and_hms(2024, 3, 15, 18, 42, 0).unwrap(),
+            ..Sale::sample()
+        };
+        insta::assert_snapshot!(receipt(Some(1042), &sale, &[], "", None));
+    }
+
+    #[test]
+    fn receipt_lines_are_laid_out_as_printed() {
+        let sale = Sale {
+            created_at: Utc.with_ymd_and_hms(2024, 3, 15, 18, 42, 0).unwrap(),
+            ..Sale::sample()
+        };
+        let config = Config::default();
+        let lines = configured_lines(Some(1042), &sale, &config);
+
+        assert_eq!(lines[0], Line::Center("RECEIPT".to_string()));
+        assert_eq!(lines[1], Line::Rule('='));
+        assert!(lines.contains(&Line::Split(
+            "  1 x House salad".to_string(),
+            "$7.00".to_string()
+        )));
+        assert_eq!(
+            join(&lines),
+            configured_receipt(Some(1042), &sale, &config)
+        );
+    }
+
+    #[test]
+    fn receipt_with_long_names_and_wide_amounts() {
+        let mut sale = sale(vec![
+            SaleItem::new(
+                "Slow-roasted heritage pork shoulder with apple cider jus",
+                28.75,
+                12,
+            ),
+            SaleItem::new("Bottle of the reserve", 1250.0, 2),
+            SaleItem::new("Water", 0.0, 4),
+        ]);
+        sale.name = "Corporate dinner for the regional sales team".to_string();
+        insta::assert_snapshot!(receipt(Some(7), &sale, &[], "", None));
+    }
+
+    #[test]
+    fn receipt_with_charges_and_payments() {
+        let mut wine = SaleItem::new("Wine pairing", 35.0, 6);
+        wine.tax_group = TaxGroup::Alcohol;
+        let mut sale = sale(vec![SaleItem::new("Tasting menu", 65.0, 6), wine]);
+        sale.name = "Table 12".to_string();
+        sale.guests = Some(6);
+        sale.service_charge_percent = Some(18.0);
+        sale.gratuity_amount = Some(20.0);
+        sale.tenders = vec![Tender {
+            code: "GC-4821".to_string(),
+            amount: 100.0,
+            ..Tender::default()
+        }];
+        sale.card = Some(Card {
+            provider: Provider::Stripe,
+            amount: 675.1,
+            brand: "Visa".to_string(),
+            last4: "4242".to_string(),
+            reference: "pi_123".to_string(),
+        });
+        insta::assert_snapshot!(receipt(Some(88), &sale, &[], "", None));
+    }
+
+    #[test]
+    fn receipt_with_comps_and_rounding() {
+        let mut weighed = SaleItem::new("Cheese by weight", 18.99, 0);
+        weighed.weighing = Some(Weighing {
+            gross: Some(0.387),
+            tare: 0.012,
+        });
+        let mut adjustment = SaleItem::new("Loyalty credit", -5.0, 1);
+        adjustment.adjustment = true;
+
+        let sale = sale(vec![
+            SaleItem::new("Espresso", 2.675, 3),
+            weighed,
+            SaleItem::new("Comp: burnt toast", -3.5, 1),
+            adjustment,
+        ]);
+        insta::assert_snapshot!(receipt(Some(3), &sale, &[], "", None));
+    }
+
+    #[test]
+    fn receipt_with_a_voided_item() {
+        let mut sale = sale(vec![
+            SaleItem::new("Ribeye steak", 42.0, 1),
+            SaleItem::new("Red wine", 11.5, 2),
+        ]);
+        let id = sale.items[0].id;
+        sale.void(id, "Sent back", Utc::now());
+        insta::assert_snapshot!(receipt(Some(8), &sale, &[], "", None));
+    }
+
+    #[test]
+    fn receipt_with_fields_and_fine_print() {
+        let mut sale = sale(vec![SaleItem::new("Catering tray", 120.0, 1)]);
+        sale.custom_fields.insert(1, "PO-99812".to_string());
+        sale.custom_fields.insert(2, "not printed".to_string());
+        let fields = [
+            custom::Field {
+                name: "Purchase order".to_string(),
+                on_receipt: true,
+                ..custom::Field::new(1)
+            },
+            custom::Field {
+                name: "Internal note".to_string(),
+                ..custom::Field::new(2)
+            },
+        ];
+        let fine_print = "Thank you for your order! Returns accepted within \
+                          14 days with this receipt.";
+        insta::assert_snapshot!(receipt(
+            Some(501),
+            &sale,
+            &fields,
+            fine_print,
+            None
+        ));
+    }
+
+    #[test]
+    fn receipt_of_a_part_paid_invoice() {
+        let issued = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let mut sale = sale(vec![SaleItem::new("Catering tray", 120.0, 2)]);
+        sale.invoice = Some(Invoice {
+            customer: "Acme Co".to_string(),
+            payments: vec![Payment {
+                date: NaiveDate::from_ymd_opt(2024, 3, 12).unwrap(),
+                amount: 100.0,
+            }],
+            ..Invoice::new(issued)
+        });
+        insta::assert_snapshot!(receipt(Some(502), &sale, &[], "", None));
+    }
+
+    #[test]
+    fn receipt_paid_in_a_foreign_currency() {
+        let mut sale = sale(vec![SaleItem::new("Souvenir mug", 14.5, 3)]);
+        sale.foreign = Some(currency::Payment {
+            currency: "EUR".to_string(),
+            base: "USD".to_string(),
+            rate: 1.08,
+            tendered: 45.0,
+            amount: 48.6,
+            change: 1.62,
+        });
+        insta::assert_snapshot!(receipt(Some(77), &sale, &[], "", None));
+    }
+
+    #[test]
+    fn receipt_in_the_language_of_the_customer() {
+        let mut sale = sale(vec![
+            SaleItem::new("Apfelstrudel", 6.5, 2),
+            SaleItem::new("Kaffee", 3.2, 2),
+        ]);
+        sale.guests = Some(2);
+        sale.service_charge_percent = Some(10.0);
+        sale.tenders = vec![Tender {
+            code: "GC-1234".to_string(),
+            amount: 5.0,
+            ..Tender::default()
+        }];
+        let store = i18n::locale();
+        let receipt = i18n::with(i18n::Locale::German, || {
+            receipt(Some(31), &sale, &[], "", None)
+        });
+        insta::assert_snapshot!(receipt);
+        // Only the receipt is written in German
+        assert_eq!(i18n::locale(), store);
+    }
+
+    #[test]
+    fn header_and_footer_are_filled_in() {
+        let config = Config {
+            receipt_header: "Corner Café\nVAT no. DE123456789\n".to_string(),
+            receipt_footer: "Thank you for order {receipt_no}\n\
+                             Table for {guests}\n\
+                             Kept until {date} + 30 days"
+                .to_string(),
+            ..Config::default()
+        };
+        let sale = sale(vec![SaleItem::new("Espresso", 3.0, 1)]);
+        let lines = configured_lines(Some(12), &sale, &config);
+
+        assert_eq!(lines[0], Line::Center("Corner Café".to_string()));
+        assert_eq!(lines[1], Line::Center("VAT no. DE123456789".to_string()));
+        assert_eq!(lines[2], Line::Center("RECEIPT".to_string()));
+        // Lines whose placeholders are all empty are left out
+        let end = lines.len() - 3;
+        assert_eq!(
+            lines[end..],
+            [
+                Line::Rule('='),
+                Line::Center("Thank you for order 12".to_string()),
+                Line::Center("Kept until 2024-03-15 + 30 days".to_string()),
+            ]
+        );
+        assert!(layout::check(&config.receipt_footer).is_ok());
+        assert!(layout::check("{nope}").is_err());
+    }
+
+    #[test]
+    fn receipts_carry_the_tax_ids() {
+        let config = Config {
+            tax_registration: vat::Registration {
+                country: vat::Country::Germany,
+                number: "123 456 789".to_string(),
+            },
+            ..Config::default()
+        };
+        let mut sale = sale(vec![SaleItem::new("Espresso", 3.0, 1)]);
+        sale.customer_tax_id = "FR AB123456789".to_string();
+        let lines = configured_lines(Some(12), &sale, &config);
+        assert_eq!(
+            lines[3..5],
+            [
+                Line::Split("Tax ID".to_string(), "DE123456789".to_string()),
+                Line::Split(
+                    "Customer tax ID".to_string(),
+                    "FR AB123456789".to_string()
+                ),
+            ]
+        );
+
+        // Neither is printed when not known
+        let plain = configured_lines(Some(12), &sale, &Config::default());
+        sale.customer_tax_id.clear();
+        let none = configured_lines(Some(12), &sale, &Config::default());
+        assert_eq!(plain.len(), lines.len() - 1);
+        assert_eq!(none.len(), lines.len() - 2);
+    }
+
+    #[test]
+    fn reprinted_receipts_say_copy() {
+        let config = Config::default();
+        let mut sale = sale(vec![SaleItem::new("Espresso", 3.0, 1)]);
+        let first = printed_receipt(Some(12), &sale, &config);
+        assert!(!first.contains("COPY"));
+
+        sale.issued.push(Issue {
+            at: sale.created_at,
+            by: Some("Alice".to_string()),
+            via: Via::Print,
+        });
+        let again = printed_receipt(Some(12), &sale, &config);
+        assert!(again.starts_with(&center("*** COPY ***")));
+        assert!(again.ends_with("*** COPY ***"));
+        assert!(again.contains(&first));
+    }
+
+    #[test]
+    fn signed_receipts_carry_the_fiscal_signature() {
+        let config = Config::default();
+        let mut sale = sale(vec![SaleItem::new("Espresso", 3.0, 1)]);
+        sale.fiscal = Some(Signature {
+            number: "MOCK-000007".to_string(),
+            signature: "a".repeat(40),
+            qr: Some("MOCK-000007|12|3.00".to_string()),
+            signed_at: sale.created_at,
+        });
+        let receipt = printed_receipt(Some(12), &sale, &config);
+        let lines: Vec<&str> = receipt.lines().rev().take(3).collect();
+        assert_eq!(
+            lines,
+            [
+                "a".repeat(8),
+                "a".repeat(32),
+                line("Fiscal no.", "MOCK-000007")
+            ]
+        );
+        assert_eq!(
+            qr::payload(&config.receipt_qr, 12, &sale).as_deref(),
+            Some("MOCK-000007|12|3.00")
+        );
+    }
+
+    #[test]
+    fn kitchen_ticket_by_course() {
+        let starters = Section {
+            name: "Starters".to_string(),
+            ..Section::default()
+        };
+        let mains = Section {
+            name: "Mains".to_string(),
+            ..Section::default()
+        };
+        let mut bread = SaleItem::new("Garlic bread", 6.0, 1);
+        bread.section = Some(starters.id);
+        bread.allergens = vec![Allergen::Gluten, Allergen::Dairy];
+        let mut linguine = SaleItem::new("Seafood linguine", 24.0, 2);
+        linguine.section = Some(mains.id);
+        linguine.allergens = vec![Allergen::Shellfish];
+
+        let mut sale = sale(vec![
+            SaleItem::new("Sparkling water", 3.0, 2),
+            bread,
+            linguine,
+        ]);
+        sale.sections = vec![starters, mains];
+        sale.name = "Table 4".to_string();
+        sale.allergy_note = "Severe peanut allergy, no nuts anywhere near \
+                             this order please"
+            .to_string();
+        insta::assert_snapshot!(kitchen(Some(1042), &sale));
+    }
+
+    /// A delivery order with its address and fee.
+    fn delivery() -> Sale {
+        let mut sale = sale(vec![
+            SaleItem::new("Margherita pizza", 11.5, 2),
+            SaleItem::new("Lemonade", 3.25, 1),
+        ]);
+        sale.name = "Jordan".to_string();
+        sale.order_type = OrderType::Delivery;
+        sale.delivery = Some(Delivery {
+            address: "Flat 3, 221 Long Harbour Road, Springfield".to_string(),
+            phone: "555-0134".to_string(),
+            distance: Some(4.2),
+            fee: 4.5,
+        });
+        sale
+    }
+
+    #[test]
+    fn receipt_of_a_delivery() {
+        insta::assert_snapshot!(receipt(
+            Some(1043),
+            &delivery(),
+            &[],
+            "",
+            None
+        ));
+    }
+
+    #[test]
+    fn kitchen_ticket_of_a_delivery() {
+        insta::assert_snapshot!(kitchen(Some(1043), &delivery()));
+    }
+}
@@ -0,0 +1,149 @@
+//! Transient notifications shown over the current screen
+//!
+//! Toasts are queued in the app and dismissed automatically once their
+//! timeout has passed, or by clicking them. Progress toasts stay up until the
+//! operation they report on finishes and dismisses them. Screens raise toasts
+//! through their [`Action`](crate::Action)s, e.g. with
+//! `Action::toast(Kind::Error, "Not a valid amount")`.
+use iced::futures::channel::oneshot;
+use iced::widget::{button, column, container, stack, text};
+use iced::{Background, Color, Element, Fill, Task, Theme};
+use std::time::Duration;
+
+/// How long a toast stays up before being dismissed.
+const TIMEOUT: Duration = Duration::from_secs(4);
+/// How long an error stays up, since it usually needs reading.
+const ERROR_TIMEOUT: Duration = Duration::from_secs(8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Progress,
+    Success,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub id: usize,
+    pub kind: Kind,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Toasts {
+    next_id: usize,
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    /// Show a toast, returning a task which yields its id once it should be
+    /// dismissed.
+    pub fn push(
+        &mut self,
+        kind: Kind,
+        message: impl Into<String>,
+    ) -> Task<usize> {
+        let id = self.add(kind, message.into());
+        let timeout = match kind {
+            Kind::Warning | Kind::Error => ERROR_TIMEOUT,
+            Kind::Progress | Kind::Success => TIMEOUT,
+        };
+        Task::perform(sleep(timeout), move |_| id)
+    }
+
+    /// Show a toast for an operation in progress, which stays up until it is
+    /// dismissed with the returned id.
+    pub fn progress(&mut self, message: impl Into<String>) -> usize {
+        self.add(Kind::Progress, message.into())
+    }
+
+    pub fn len(&self) -> usize {
+        self.toasts.len()
+    }
+
+    pub fn dismiss(&mut self, id: usize) {
+        self.toasts.retain(|toast| toast.id != id);
+    }
+
+    fn add(&mut self, kind: Kind, message: String) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.toasts.push(Toast { id, kind, message });
+        id
+    }
+
+    /// Show the toasts stacked in the bottom right corner over `content`.
+    pub fn view<'a, Message: 'a>(
+        &'a self,
+        content: impl Into<Element<'a, Message>>,
+        on_dismiss: impl Fn(usize) -> Message + 'a,
+    ) -> Element<'a, Message> {
+        if self.toasts.is_empty() {
+            return content.into();
+        }
+
+        let toasts = self.toasts.iter().fold(
+            column![].spacing(10).width(300.0),
+            |col, toast| {
+                col.push(
+                    button(
+                        text(&toast.message)
+                            .size(13)
+                            .shaping(text::Shaping::Advanced),
+                    )
+                    .width(Fill)
+                    .padding(10)
+                    .style(style(toast.kind))
+                    .on_press(toast.id),
+                )
+            },
+        );
+
+        stack![
+            content.into(),
+            Element::from(
+                container(toasts)
+                    .align_right(Fill)
+                    .align_bottom(Fill)
+                    .padding(20)
+            )
+            .map(on_dismiss),
+        ]
+        .into()
+    }
+}
+
+fn style(kind: Kind) -> impl Fn(&Theme, button::Status) -> button::Style {
+    move |theme, status| match kind {
+        Kind::Progress => button::secondary(theme, status),
+        Kind::Success => button::success(theme, status),
+        Kind::Warning => warning(theme, status),
+        Kind::Error => button::danger(theme, status),
+    }
+}
+
+/// Like [`button::danger`], in amber.
+fn warning(theme: &Theme, status: button::Status) -> button::Style {
+    let amber = Color::from_rgb8(0xE6, 0x9B, 0x00);
+    let style = button::danger(theme, status);
+
+    button::Style {
+        background: Some(Background::Color(match status {
+            button::Status::Hovered => amber.scale_alpha(0.9),
+            _ => amber,
+        })),
+        text_color: Color::BLACK,
+        ..style
+    }
+}
+
+/// Wait for `duration` without blocking the executor.
+async fn sleep(duration: Duration) {
+    let (done, wait) = oneshot::channel();
+    std::thread::spawn(move || {
+        std::thread::sleep(duration);
+        let _ = done.send(());
+    });
+    let _ = wait.await;
+}
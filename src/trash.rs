@@ -0,0 +1,190 @@
+//! The trash screen, restoring deleted sales or purging them for good
+use chrono::Local;
+use iced::widget::{
+    button, column, container, horizontal_space, row, scrollable, text,
+};
+use iced::{Alignment, Element, Fill};
+
+use crate::dialog;
+use crate::i18n::{money, tr};
+use crate::{Action, Hotkey, Screen};
+
+pub use receipts_core::trash::*;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Back,
+    Restore(usize),
+    Purge(usize),
+    Empty,
+    /// Purge what was asked to, once sure.
+    ConfirmPurge,
+    CancelPurge,
+}
+
+#[derive(Debug, Clone)]
+pub enum Instruction {
+    Navigate(Screen),
+    Restore(usize),
+    /// Delete these sales for good.
+    Purge(Vec<usize>),
+}
+
+impl From<Screen> for Instruction {
+    fn from(screen: Screen) -> Self {
+        Instruction::Navigate(screen)
+    }
+}
+
+/// What is being done on the trash screen.
+#[derive(Debug, Clone, Default)]
+pub struct Form {
+    /// The sales to purge once it is confirmed.
+    pub purging: Option<Vec<usize>>,
+}
+
+pub fn update(
+    trash: &Trash,
+    form: &mut Form,
+    message: Message,
+) -> Action<Instruction, Message> {
+    match message {
+        Message::Back => Action::navigate(Screen::List),
+        Message::Restore(id) => Action::instruction(Instruction::Restore(id)),
+        Message::Purge(id) => {
+            form.purging = Some(vec![id]);
+            Action::none()
+        }
+        Message::Empty => {
+            form.purging = Some(trash.iter().map(|(id, _)| id).collect());
+            Action::none()
+        }
+        Message::ConfirmPurge => match form.purging.take() {
+            Some(ids) => Action::instruction(Instruction::Purge(ids)),
+            None => Action::none(),
+        },
+        Message::CancelPurge => {
+            form.purging = None;
+            Action::none()
+        }
+    }
+}
+
+pub fn view<'a>(
+    trash: &'a Trash,
+    form: &'a Form,
+    retention: Option<u32>,
+) -> Element<'a, Message> {
+    let header = row![
+        button(text("←").center()).width(40).on_press(Message::Back),
+        text(tr("Trash")).size(16),
+        horizontal_space(),
+        button(tr("Empty trash"))
+            .on_press_maybe((!trash.is_empty()).then_some(Message::Empty))
+            .style(button::danger),
+    ]
+    .spacing(10)
+    .align_y(Alignment::Center);
+
+    let retention = text(match retention {
+        Some(days) => format!(
+            "{} {days} {}",
+            tr("Deleted sales are purged for good after"),
+            tr("days")
+        ),
+        None => tr("Deleted sales are kept until purged").to_string(),
+    })
+    .size(12);
+
+    let sales: Element<_> = if trash.is_empty() {
+        text(tr("The trash is empty")).size(12).into()
+    } else {
+        trash
+            .iter()
+            .fold(column![].spacing(5), |col, (id, trashed)| {
+                let deleted = trashed
+                    .deleted_at
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M");
+                let name = match trashed.sale.name.as_str() {
+                    "" => format!("#{id}"),
+                    name => format!("#{id} {name}"),
+                };
+                col.push(
+                    row![
+                        text(name).width(Fill),
+                        text(money(trashed.sale.calculate_total()))
+                            .width(100.0)
+                            .align_x(Alignment::End),
+                        text(format!("{} {deleted}", tr("deleted")))
+                            .size(12)
+                            .width(170.0),
+                        button(text(tr("Restore")).size(14))
+                            .on_press(Message::Restore(id))
+                            .style(button::success),
+                        button(text(tr("Purge")).size(14))
+                            .on_press(Message::Purge(id))
+                            .style(button::danger),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                )
+            })
+            .into()
+    };
+
+    let screen = container(scrollable(
+        column![
+            header,
+            retention,
+            container(sales)
+                .padding(20)
+                .width(Fill)
+                .style(container::rounded_box),
+        ]
+        .spacing(20),
+    ))
+    .padding(20);
+
+    match &form.purging {
+        Some(ids) => dialog::view(
+            screen,
+            column![
+                text(format!(
+                    "{} {} {}",
+                    tr("Purge"),
+                    ids.len(),
+                    tr("sale(s) for good? This can't be undone.")
+                )),
+                row![
+                    horizontal_space(),
+                    button(tr("Cancel"))
+                        .on_press(Message::CancelPurge)
+                        .style(button::secondary),
+                    button(tr("Purge"))
+                        .on_press(Message::ConfirmPurge)
+                        .style(button::danger),
+                ]
+                .spacing(10),
+            ]
+            .spacing(20)
+            .width(400),
+            Message::CancelPurge,
+        ),
+        None => screen.into(),
+    }
+}
+
+pub fn handle_hotkey(
+    form: &mut Form,
+    hotkey: Hotkey,
+) -> Action<Instruction, Message> {
+    match hotkey {
+        Hotkey::Escape if form.purging.is_some() => {
+            form.purging = None;
+            Action::none()
+        }
+        Hotkey::Escape => Action::navigate(Screen::List),
+        _ => Action::none(),
+    }
+}